@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const PREFERENCES_FILE: &str = "preferences.json";
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+const LOG_FILE: &str = "bookokcat.log";
+/// Comments and highlights, one YAML file per book; the name they had in the current
+/// directory, where earlier versions kept them
+const COMMENTS_DIR: &str = ".bookokcat_comments";
+/// Folder beside the executable that `--portable` keeps everything in
+const PORTABLE_DIR: &str = "bookokcat-data";
+
+/// Set once by `main`. Until then (in tests, or for other users of the library) files are
+/// read and written in the current directory, as they always were.
+static DIRS: OnceLock<AppDirs> = OnceLock::new();
+
+/// Where the files bookokcat keeps between runs live
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppDirs {
+    /// `preferences.json`
+    pub config: PathBuf,
+    /// `bookmarks.json`, the lock files next to it, comments and the log
+    pub state: PathBuf,
+    /// Downloaded books and images extracted from books; safe to delete
+    pub cache: PathBuf,
+    /// Images saved from the image popup, unless `image_save_dir` is set
+    pub saved_images: PathBuf,
+}
+
+impl AppDirs {
+    /// The XDG base directories, unless `BOOKOKCAT_CONFIG_DIR` or `BOOKOKCAT_STATE_DIR`
+    /// say otherwise. Setting `BOOKOKCAT_STATE_DIR` moves the cache into it as well.
+    /// Saved images go to the XDG download directory, else the data directory.
+    /// `var` looks up an environment variable, `home` is the home directory.
+    pub fn from_env(var: impl Fn(&str) -> Option<OsString>, home: Option<PathBuf>) -> Self {
+        let var = |name: &str| {
+            var(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        // The spec says relative XDG paths are invalid and should be ignored
+        let xdg = |name: &str| var(name).filter(|dir| dir.is_absolute());
+        let home = home.filter(|dir| !dir.as_os_str().is_empty());
+        let base = |xdg_var: &str, under_home: &[&str]| {
+            xdg(xdg_var)
+                .or_else(|| {
+                    home.as_ref().map(|home| {
+                        under_home
+                            .iter()
+                            .fold(home.clone(), |dir, part| dir.join(part))
+                    })
+                })
+                .map(|dir| dir.join("bookokcat"))
+                // Without a home directory everything stays in the current one
+                .unwrap_or_else(|| PathBuf::from("."))
+        };
+
+        let state_override = var("BOOKOKCAT_STATE_DIR");
+        AppDirs {
+            config: var("BOOKOKCAT_CONFIG_DIR")
+                .unwrap_or_else(|| base("XDG_CONFIG_HOME", &[".config"])),
+            cache: state_override
+                .as_ref()
+                .map(|state| state.join("cache"))
+                .unwrap_or_else(|| base("XDG_CACHE_HOME", &[".cache"])),
+            state: state_override.unwrap_or_else(|| base("XDG_STATE_HOME", &[".local", "state"])),
+            saved_images: xdg("XDG_DOWNLOAD_DIR")
+                .unwrap_or_else(|| base("XDG_DATA_HOME", &[".local", "share"]).join("images")),
+        }
+    }
+
+    /// Everything in one folder beside the executable, for running from a USB stick
+    pub fn portable(exe_dir: &Path) -> Self {
+        let dir = exe_dir.join(PORTABLE_DIR);
+        AppDirs {
+            config: dir.clone(),
+            state: dir.clone(),
+            cache: dir.join("cache"),
+            saved_images: dir.join("images"),
+        }
+    }
+
+    /// Relative directories are taken from the current one, so changing directory later
+    /// doesn't move them
+    fn absolute(self) -> Result<Self> {
+        let cwd = env::current_dir().context("Could not determine current directory")?;
+        Ok(AppDirs {
+            config: cwd.join(self.config),
+            state: cwd.join(self.state),
+            cache: cwd.join(self.cache),
+            saved_images: cwd.join(self.saved_images),
+        })
+    }
+}
+
+/// Work out where files go (beside the executable with `--portable`) and create the
+/// directories. Called by `main` before anything is read.
+pub fn init(portable: bool) -> Result<&'static AppDirs> {
+    let dirs = if portable {
+        let exe = env::current_exe().context("Could not locate the bookokcat executable")?;
+        AppDirs::portable(exe.parent().unwrap_or(Path::new(".")))
+    } else {
+        AppDirs::from_env(|name| env::var_os(name), home::home_dir())
+    }
+    .absolute()?;
+    for dir in [&dirs.config, &dirs.state, &dirs.cache] {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    Ok(DIRS.get_or_init(|| dirs))
+}
+
+/// Copy `preferences.json`, `bookmarks.json` and the comments left in the current directory
+/// by earlier versions to where they now belong, unless there's already one there. The old
+/// files are left alone.
+pub fn adopt_legacy_files() {
+    let Some(dirs) = DIRS.get() else {
+        return;
+    };
+    for (name, dir) in [
+        (PREFERENCES_FILE, &dirs.config),
+        (BOOKMARKS_FILE, &dirs.state),
+    ] {
+        let (legacy, dest) = (PathBuf::from(name), dir.join(name));
+        if dest.exists() || !legacy.is_file() || same_file(&legacy, &dest) {
+            continue;
+        }
+        match fs::copy(&legacy, &dest) {
+            Ok(_) => info!(
+                "Copied {name} from the current directory to {}",
+                dest.display()
+            ),
+            Err(e) => warn!("Failed to copy {name} to {}: {e}", dest.display()),
+        }
+    }
+    adopt_legacy_comments(Path::new(COMMENTS_DIR), &dirs.state.join(COMMENTS_DIR));
+}
+
+/// Copy each book's comments file from `legacy` into `dest` that isn't there yet
+fn adopt_legacy_comments(legacy: &Path, dest: &Path) {
+    let Ok(entries) = fs::read_dir(legacy) else {
+        return;
+    };
+    if same_file(legacy, dest) {
+        return;
+    }
+    if let Err(e) = fs::create_dir_all(dest) {
+        warn!("Failed to create {}: {e}", dest.display());
+        return;
+    }
+    let mut copied = 0;
+    for entry in entries.flatten() {
+        let target = dest.join(entry.file_name());
+        if !entry.path().is_file() || target.exists() {
+            continue;
+        }
+        match fs::copy(entry.path(), &target) {
+            Ok(_) => copied += 1,
+            Err(e) => warn!(
+                "Failed to copy {} to {}: {e}",
+                entry.path().display(),
+                target.display()
+            ),
+        }
+    }
+    if copied > 0 {
+        info!(
+            "Copied {copied} comments file(s) from the current directory to {}",
+            dest.display()
+        );
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+pub fn preferences_file() -> PathBuf {
+    in_dir(|dirs| &dirs.config, PREFERENCES_FILE)
+}
+
+pub fn bookmarks_file() -> PathBuf {
+    in_dir(|dirs| &dirs.state, BOOKMARKS_FILE)
+}
+
+pub fn log_file() -> PathBuf {
+    in_dir(|dirs| &dirs.state, LOG_FILE)
+}
+
+/// Where each book's comments are kept
+pub fn comments_dir() -> Result<PathBuf> {
+    match DIRS.get() {
+        Some(dirs) => Ok(dirs.state.join(COMMENTS_DIR)),
+        None => Ok(env::current_dir()
+            .context("Could not determine current directory")?
+            .join(COMMENTS_DIR)),
+    }
+}
+
+/// Where books opened from a URL are downloaded to
+pub fn downloads_dir() -> Result<PathBuf> {
+    match DIRS.get() {
+        Some(dirs) => Ok(dirs.cache.join("downloads")),
+        None => Ok(env::current_dir()
+            .context("Could not determine current directory")?
+            .join(".bookokcat_cache")),
+    }
+}
+
+/// Where images are extracted from the books being read
+pub fn images_dir() -> PathBuf {
+    match DIRS.get() {
+        Some(dirs) => dirs.cache.join("images"),
+        None => PathBuf::from("temp_images"),
+    }
+}
+
+/// Where images saved from the image popup go by default
+pub fn saved_images_dir() -> PathBuf {
+    match DIRS.get() {
+        Some(dirs) => dirs.saved_images.clone(),
+        None => PathBuf::from("."),
+    }
+}
+
+fn in_dir(dir: impl Fn(&AppDirs) -> &PathBuf, name: &str) -> PathBuf {
+    match DIRS.get() {
+        Some(dirs) => dir(dirs).join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs_with(vars: &[(&str, &str)], home: Option<&str>) -> AppDirs {
+        AppDirs::from_env(
+            |name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            },
+            home.map(PathBuf::from),
+        )
+    }
+
+    #[test]
+    fn test_dirs_follow_xdg_and_overrides() {
+        assert_eq!(
+            dirs_with(&[], Some("/home/me")),
+            AppDirs {
+                config: PathBuf::from("/home/me/.config/bookokcat"),
+                state: PathBuf::from("/home/me/.local/state/bookokcat"),
+                cache: PathBuf::from("/home/me/.cache/bookokcat"),
+                saved_images: PathBuf::from("/home/me/.local/share/bookokcat/images"),
+            }
+        );
+        assert_eq!(
+            dirs_with(
+                &[("XDG_DOWNLOAD_DIR", "/home/me/Downloads")],
+                Some("/home/me")
+            )
+            .saved_images,
+            PathBuf::from("/home/me/Downloads")
+        );
+
+        let dirs = dirs_with(
+            &[
+                ("XDG_CONFIG_HOME", "/xdg/config"),
+                ("XDG_STATE_HOME", "relative/state"),
+                ("XDG_CACHE_HOME", ""),
+            ],
+            Some("/home/me"),
+        );
+        assert_eq!(dirs.config, PathBuf::from("/xdg/config/bookokcat"));
+        assert_eq!(dirs.state, PathBuf::from("/home/me/.local/state/bookokcat"));
+        assert_eq!(dirs.cache, PathBuf::from("/home/me/.cache/bookokcat"));
+
+        let dirs = dirs_with(
+            &[
+                ("BOOKOKCAT_CONFIG_DIR", "/stick/config"),
+                ("BOOKOKCAT_STATE_DIR", "/stick/state"),
+                ("XDG_CONFIG_HOME", "/xdg/config"),
+            ],
+            None,
+        );
+        assert_eq!(dirs.config, PathBuf::from("/stick/config"));
+        assert_eq!(dirs.state, PathBuf::from("/stick/state"));
+        assert_eq!(dirs.cache, PathBuf::from("/stick/state/cache"));
+
+        assert_eq!(dirs_with(&[], None).config, PathBuf::from("."));
+        assert_eq!(
+            AppDirs::portable(Path::new("/media/usb")).cache,
+            PathBuf::from("/media/usb/bookokcat-data/cache")
+        );
+    }
+
+    #[test]
+    fn test_legacy_comments_are_copied_without_replacing_any() {
+        let dir = tempfile::tempdir().unwrap();
+        let (legacy, dest) = (dir.path().join("old"), dir.path().join("state/comments"));
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("book_a.yaml"), "old a").unwrap();
+        fs::write(legacy.join("book_b.yaml"), "old b").unwrap();
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("book_b.yaml"), "new b").unwrap();
+
+        adopt_legacy_comments(&legacy, &dest);
+        assert_eq!(
+            fs::read_to_string(dest.join("book_a.yaml")).unwrap(),
+            "old a"
+        );
+        assert_eq!(
+            fs::read_to_string(dest.join("book_b.yaml")).unwrap(),
+            "new b"
+        );
+        assert!(legacy.join("book_a.yaml").exists());
+    }
+}
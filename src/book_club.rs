@@ -0,0 +1,93 @@
+use crate::comments::Comment;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// A reader's place in a chapter and their notes on it, shared with a reading group as a
+/// small YAML file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedReading {
+    pub reader: String,
+    /// The book's file name, to catch a file meant for another book
+    pub book: String,
+    pub chapter_href: String,
+    pub chapter_index: usize,
+    /// The paragraph the reader had at the top of the screen
+    pub node_index: usize,
+    pub shared_at: DateTime<Utc>,
+    pub annotations: Vec<Comment>,
+}
+
+impl SharedReading {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("{} isn't a shared reading", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_yaml::to_string(self)?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// `<book>-ch<N>-<reader>.club.yaml`, e.g. `moby-dick-ch3-alice.club.yaml`
+    pub fn default_file_name(&self) -> String {
+        let book = Path::new(&self.book)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("book");
+        format!(
+            "{}-ch{}-{}.club.yaml",
+            book.replace(char::is_whitespace, "-"),
+            self.chapter_index + 1,
+            self.reader.replace(char::is_whitespace, "-")
+        )
+    }
+}
+
+/// Who is sharing: `$USER`, `$USERNAME` on Windows, or `reader`
+pub fn reader_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "reader".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_reading_round_trips_through_its_file() {
+        let reading = SharedReading {
+            reader: "alice".to_string(),
+            book: "Moby Dick.epub".to_string(),
+            chapter_href: "OEBPS/ch03.xhtml".to_string(),
+            chapter_index: 2,
+            node_index: 14,
+            shared_at: Utc::now(),
+            annotations: vec![Comment {
+                chapter_href: "OEBPS/ch03.xhtml".to_string(),
+                paragraph_index: 14,
+                word_range: Some((2, 6)),
+                content: "Call me Ishmael, again".to_string(),
+                updated_at: Utc::now(),
+                anchor: None,
+                text_offset: None,
+            }],
+        };
+        assert_eq!(reading.default_file_name(), "Moby-Dick-ch3-alice.club.yaml");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(reading.default_file_name());
+        reading.save(&path).unwrap();
+        assert_eq!(SharedReading::load(&path).unwrap(), reading);
+
+        fs::write(&path, "not: [a, reading").unwrap();
+        assert!(SharedReading::load(&path).is_err());
+    }
+}
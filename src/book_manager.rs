@@ -1,9 +1,14 @@
+use crate::comic_handler;
 use crate::pdf_handler::PdfDocument;
+use crate::text_handler;
 use epub::doc::EpubDoc;
 use log::{error, info, warn};
 use std::io::BufReader;
 use std::path::Path;
 
+/// Files with these extensions, in any case, are listed as books
+pub const BOOK_EXTENSIONS: &[&str] = &["epub", "html", "htm", "pdf", "cbz", "cbr", "txt"];
+
 pub struct BookManager {
     pub books: Vec<BookInfo>,
     scan_directory: String,
@@ -46,10 +51,9 @@ impl BookManager {
                 let entry = entry.ok()?;
                 let path = entry.path();
                 let extension = path.extension()?.to_str()?;
-                if extension == "epub"
-                    || extension == "html"
-                    || extension == "htm"
-                    || extension == "pdf"
+                if BOOK_EXTENSIONS
+                    .iter()
+                    .any(|known| extension.eq_ignore_ascii_case(known))
                 {
                     let path_str = path.to_str()?.to_string();
                     let display_name = Self::extract_display_name(&path_str);
@@ -69,7 +73,7 @@ impl BookManager {
 
         // For HTML files, preserve the full filename with extension
         if let Some(extension) = path.extension() {
-            if extension == "html" || extension == "htm" {
+            if extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm") {
                 return path
                     .file_name()
                     .unwrap_or_default()
@@ -102,6 +106,18 @@ impl BookManager {
         } else if self.is_pdf_file(path) {
             // For PDF files, create a fake EPUB
             self.create_fake_epub_from_pdf(path)
+        } else if comic_handler::is_comic_file(path) {
+            // Comics become a fake EPUB with a chapter per page
+            comic_handler::create_comic_epub(path).map_err(|e| {
+                error!("Failed to load comic {path}: {e}");
+                format!("Failed to load comic: {e}")
+            })
+        } else if text_handler::is_text_file(path) {
+            // Plain text is split into chapters at its headings
+            text_handler::create_text_epub(path).map_err(|e| {
+                error!("Failed to load text {path}: {e}");
+                format!("Failed to load text: {e}")
+            })
         } else {
             info!("Attempting to load EPUB file: {path}");
             match EpubDoc::new(path) {
@@ -427,6 +443,26 @@ impl BookManager {
         self.books.iter().position(|book| book.path == path)
     }
 
+    /// Add a book from outside the scanned directory, returning its index. A file already
+    /// in the library is found by its canonical path instead of being added twice.
+    pub fn add_book(&mut self, path: &Path) -> usize {
+        let canonical = path.canonicalize().ok();
+        if let Some(index) = self.books.iter().position(|book| {
+            book.path == path.to_string_lossy()
+                || canonical.is_some() && Path::new(&book.path).canonicalize().ok() == canonical
+        }) {
+            return index;
+        }
+
+        let path_str = path.to_string_lossy().to_string();
+        let display_name = Self::extract_display_name(&path_str);
+        self.books.push(BookInfo {
+            path: path_str,
+            display_name,
+        });
+        self.books.len() - 1
+    }
+
     pub fn contains_book(&self, path: &str) -> bool {
         self.books.iter().any(|book| book.path == path)
     }
@@ -434,7 +470,7 @@ impl BookManager {
     pub fn is_html_file(&self, path: &str) -> bool {
         let path = Path::new(path);
         match path.extension().and_then(|ext| ext.to_str()) {
-            Some(ext) => ext == "html" || ext == "htm",
+            Some(ext) => ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"),
             None => false,
         }
     }
@@ -442,8 +478,30 @@ impl BookManager {
     pub fn is_pdf_file(&self, path: &str) -> bool {
         let path = Path::new(path);
         match path.extension().and_then(|ext| ext.to_str()) {
-            Some(ext) => ext == "pdf",
+            Some(ext) => ext.eq_ignore_ascii_case("pdf"),
             None => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_books_are_found_whatever_the_case_of_their_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.EPUB", "b.Txt", "c.CBZ", "d.PDF", "e.Htm", "notes.md"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let manager = BookManager::new_with_directory(dir.path().to_str().unwrap());
+        let names: Vec<&str> = manager
+            .books
+            .iter()
+            .map(|book| book.display_name.as_str())
+            .collect();
+        assert_eq!(names, ["a", "b", "c", "d", "e.Htm"]);
+        assert!(manager.is_pdf_file(&manager.books[3].path));
+        assert!(manager.is_html_file(&manager.books[4].path));
+    }
+}
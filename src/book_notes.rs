@@ -0,0 +1,207 @@
+use crate::markdown::{
+    Block, Document, HeadingLevel, Inline, LinkType, Node, Text, TextNode, TextOrInline,
+};
+
+/// Pseudo href used by the TOC entry that opens the compiled notes chapter
+pub const NOTES_HREF: &str = "bookokcat:notes";
+pub const NOTES_TITLE: &str = "Notes";
+
+const NOTE_TYPES: [&str; 4] = ["footnote", "endnote", "rearnote", "note"];
+
+/// A footnote/endnote block found somewhere in the spine
+#[derive(Debug, Clone)]
+pub struct CollectedNote {
+    pub chapter_index: usize,
+    pub chapter_href: String,
+    pub chapter_title: String,
+    pub id: Option<String>,
+    pub content: Vec<Node>,
+}
+
+/// All notes of a book, compiled into a virtual "Notes" chapter
+#[derive(Debug, Default)]
+pub struct BookNotes {
+    notes: Vec<CollectedNote>,
+}
+
+impl BookNotes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// The note with `id` in the chapter at `chapter_href`; the href may be relative to the
+    /// book root or a suffix of it, as in links between chapters
+    pub fn find(&self, chapter_href: &str, id: &str) -> Option<&CollectedNote> {
+        let chapter_href = chapter_href.trim_start_matches("../");
+        self.notes.iter().find(|note| {
+            note.id.as_deref() == Some(id)
+                && (note.chapter_href == chapter_href
+                    || note.chapter_href.ends_with(&format!("/{chapter_href}")))
+        })
+    }
+
+    /// Collect note blocks from a converted chapter, including ones nested in containers
+    pub fn collect_from_chapter(
+        &mut self,
+        chapter_index: usize,
+        chapter_href: &str,
+        chapter_title: &str,
+        doc: &Document,
+    ) {
+        self.collect_from_nodes(chapter_index, chapter_href, chapter_title, &doc.blocks);
+    }
+
+    fn collect_from_nodes(
+        &mut self,
+        chapter_index: usize,
+        chapter_href: &str,
+        chapter_title: &str,
+        nodes: &[Node],
+    ) {
+        for node in nodes {
+            match &node.block {
+                Block::EpubBlock {
+                    epub_type, content, ..
+                } if is_note_type(epub_type) && !content.is_empty() => {
+                    self.notes.push(CollectedNote {
+                        chapter_index,
+                        chapter_href: chapter_href.to_string(),
+                        chapter_title: chapter_title.to_string(),
+                        id: node.id.clone(),
+                        content: content.clone(),
+                    });
+                }
+                Block::EpubBlock { epub_type, .. } if is_note_type(epub_type) => {}
+                Block::EpubBlock { content, .. }
+                | Block::Quote { content }
+                | Block::Details { content, .. } => {
+                    self.collect_from_nodes(chapter_index, chapter_href, chapter_title, content);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Build the virtual chapter: notes grouped by source chapter, each followed by
+    /// a back-reference link to where it lives in the book
+    pub fn to_document(&self) -> Document {
+        let mut doc = Document::new();
+        doc.blocks.push(Node::new(
+            Block::Heading {
+                level: HeadingLevel::H1,
+                content: plain_text(NOTES_TITLE),
+            },
+            0..0,
+        ));
+
+        let mut current_chapter = None;
+        for note in &self.notes {
+            if current_chapter != Some(note.chapter_index) {
+                current_chapter = Some(note.chapter_index);
+                doc.blocks.push(Node::new(
+                    Block::Heading {
+                        level: HeadingLevel::H2,
+                        content: plain_text(&note.chapter_title),
+                    },
+                    0..0,
+                ));
+            }
+
+            doc.blocks.extend(note.content.iter().cloned());
+
+            let url = match &note.id {
+                Some(id) => format!("{}#{id}", note.chapter_href),
+                None => note.chapter_href.clone(),
+            };
+            let mut back_ref = Text::default();
+            back_ref.push_inline(Inline::Link {
+                text: plain_text(&format!("↩ {}", note.chapter_title)),
+                url,
+                title: None,
+                link_type: LinkType::InternalChapter,
+                target_chapter: Some(note.chapter_href.clone()),
+                target_anchor: note.id.clone(),
+            });
+            doc.blocks
+                .push(Node::new(Block::Paragraph { content: back_ref }, 0..0));
+        }
+
+        doc
+    }
+}
+
+fn is_note_type(epub_type: &str) -> bool {
+    epub_type
+        .split_whitespace()
+        .any(|t| NOTE_TYPES.contains(&t))
+}
+
+fn plain_text(content: &str) -> Text {
+    let mut text = Text::default();
+    text.push(TextOrInline::Text(TextNode::new(content.to_string(), None)));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_node(id: &str, text: &str) -> Node {
+        Node::new_with_id(
+            Block::EpubBlock {
+                epub_type: "footnote".to_string(),
+                element_name: "aside".to_string(),
+                content: vec![Node::new(
+                    Block::Paragraph {
+                        content: plain_text(text),
+                    },
+                    0..0,
+                )],
+            },
+            0..0,
+            Some(id.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_collects_notes_and_builds_back_references() {
+        let mut chapter = Document::new();
+        chapter.blocks.push(Node::new(
+            Block::Paragraph {
+                content: plain_text("Body text"),
+            },
+            0..0,
+        ));
+        chapter.blocks.push(note_node("fn1", "First note"));
+
+        let mut notes = BookNotes::new();
+        notes.collect_from_chapter(2, "OEBPS/ch2.xhtml", "Chapter Two", &chapter);
+        assert_eq!(notes.len(), 1);
+
+        let doc = notes.to_document();
+        // Title, chapter heading, note paragraph, back-reference
+        assert_eq!(doc.blocks.len(), 4);
+
+        let Block::Paragraph { content } = &doc.blocks[3].block else {
+            panic!("expected back-reference paragraph");
+        };
+        let Some(TextOrInline::Inline(Inline::Link {
+            target_chapter,
+            target_anchor,
+            ..
+        })) = content.iter().next()
+        else {
+            panic!("expected back-reference link");
+        };
+        assert_eq!(target_chapter.as_deref(), Some("OEBPS/ch2.xhtml"));
+        assert_eq!(target_anchor.as_deref(), Some("fn1"));
+    }
+}
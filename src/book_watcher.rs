@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the watched file is looked at
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the file looked like when it was checked: its modification time and size
+type Stamp = (SystemTime, u64);
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Notices when the open book's file is saved again, so a book can be previewed while
+/// it's being written
+pub struct BookWatcher {
+    path: PathBuf,
+    loaded: Option<Stamp>,
+    /// A change seen on the last check, which may still be being written
+    pending: Option<Stamp>,
+    last_check: Instant,
+}
+
+impl BookWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        BookWatcher {
+            loaded: stamp(&path),
+            path,
+            pending: None,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since it was last loaded. Cheap enough to call every tick;
+    /// the file is only looked at every `CHECK_INTERVAL`.
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        self.check()
+    }
+
+    /// A change counts once the file looks the same on two checks in a row, so a book is
+    /// never read halfway through being saved. A missing file is being replaced.
+    fn check(&mut self) -> bool {
+        let current = stamp(&self.path);
+        if current.is_none() || current == self.loaded {
+            self.pending = None;
+            return false;
+        }
+        if self.pending == current {
+            self.loaded = current;
+            self.pending = None;
+            true
+        } else {
+            self.pending = current;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_count_once_the_file_settles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("draft.html");
+        fs::write(&path, "<p>First draft</p>").unwrap();
+
+        let mut watcher = BookWatcher::new(&path);
+        assert!(!watcher.check());
+
+        fs::write(&path, "<p>Second draft, longer</p>").unwrap();
+        assert!(!watcher.check());
+        assert!(watcher.check());
+        assert!(!watcher.check());
+
+        // Replaced by a save that removes the file first
+        fs::remove_file(&path).unwrap();
+        assert!(!watcher.check());
+        fs::write(&path, "<p>Third</p>").unwrap();
+        assert!(!watcher.check());
+        assert!(watcher.check());
+    }
+}
@@ -1,7 +1,12 @@
+use crate::file_lock::FileLock;
+use crate::migrate::{self, BOOKMARKS_VERSION};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Lock files shared by all instances using the same bookmarks file live here
+const LOCKS_DIR: &str = ".bookokcat_locks";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Bookmark {
@@ -21,8 +26,84 @@ pub struct Bookmark {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Bookmarks {
+    /// Format the file was written in; older ones are upgraded on load
+    #[serde(default)]
+    version: u64,
+
     books: HashMap<String, Bookmark>,
 
+    /// Chapter (spine) indices the reader has finished, per book
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    read_chapters: HashMap<String, BTreeSet<usize>>,
+
+    /// Chapters this instance marked as unread again, so merging doesn't bring them back
+    #[serde(skip)]
+    unread_chapters: HashMap<String, BTreeSet<usize>>,
+
+    /// Books whose TOC is rebuilt from in-content headings instead of the publisher's
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    heading_toc_books: BTreeSet<String>,
+
+    /// Books whose `heading_toc_books` entry was changed by this instance
+    #[serde(skip)]
+    heading_toc_changed: BTreeSet<String>,
+
+    /// Books where dialog grouping was switched away from the `dialog_grouping` preference
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    dialog_grouping_books: HashMap<String, bool>,
+
+    /// Books whose `dialog_grouping_books` entry was changed by this instance
+    #[serde(skip)]
+    dialog_grouping_changed: BTreeSet<String>,
+
+    /// Books whose footnotes are shown under the paragraphs that refer to them
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    inline_notes_books: BTreeSet<String>,
+
+    /// Books whose `inline_notes_books` entry was changed by this instance
+    #[serde(skip)]
+    inline_notes_changed: BTreeSet<String>,
+
+    /// Books laid out line by line as their `<br/>`s say (scripts, lyrics)
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    line_break_books: BTreeSet<String>,
+
+    /// Books whose `line_break_books` entry was changed by this instance
+    #[serde(skip)]
+    line_breaks_changed: BTreeSet<String>,
+
+    /// TOC sections left expanded, per book, as paths of section titles
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    toc_expansion: HashMap<String, Vec<String>>,
+
+    /// Books whose `toc_expansion` entry was changed by this instance
+    #[serde(skip)]
+    toc_expansion_changed: BTreeSet<String>,
+
+    /// Custom reading orders, per book, as the chapter hrefs to read in turn
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    reading_orders: HashMap<String, Vec<String>>,
+
+    /// Books whose `reading_orders` entry was changed by this instance
+    #[serde(skip)]
+    reading_orders_changed: BTreeSet<String>,
+
+    /// Terms highlighted wherever they appear, per book
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    watchlists: HashMap<String, Vec<String>>,
+
+    /// Books whose `watchlists` entry was changed by this instance
+    #[serde(skip)]
+    watchlists_changed: BTreeSet<String>,
+
+    /// Books hidden from the library without deleting them or their reading state
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    archived_books: BTreeSet<String>,
+
+    /// Books whose `archived_books` entry was changed by this instance
+    #[serde(skip)]
+    archived_changed: BTreeSet<String>,
+
     #[serde(skip)]
     file_path: Option<String>,
 }
@@ -30,14 +111,52 @@ pub struct Bookmarks {
 impl Bookmarks {
     pub fn ephemeral() -> Self {
         Self {
+            version: BOOKMARKS_VERSION,
             books: HashMap::new(),
+            read_chapters: HashMap::new(),
+            unread_chapters: HashMap::new(),
+            heading_toc_books: BTreeSet::new(),
+            heading_toc_changed: BTreeSet::new(),
+            dialog_grouping_books: HashMap::new(),
+            dialog_grouping_changed: BTreeSet::new(),
+            inline_notes_books: BTreeSet::new(),
+            inline_notes_changed: BTreeSet::new(),
+            line_break_books: BTreeSet::new(),
+            line_breaks_changed: BTreeSet::new(),
+            toc_expansion: HashMap::new(),
+            toc_expansion_changed: BTreeSet::new(),
+            reading_orders: HashMap::new(),
+            reading_orders_changed: BTreeSet::new(),
+            watchlists: HashMap::new(),
+            watchlists_changed: BTreeSet::new(),
+            archived_books: BTreeSet::new(),
+            archived_changed: BTreeSet::new(),
             file_path: None,
         }
     }
 
     pub fn with_file(file_path: &str) -> Self {
         Self {
+            version: BOOKMARKS_VERSION,
             books: HashMap::new(),
+            read_chapters: HashMap::new(),
+            unread_chapters: HashMap::new(),
+            heading_toc_books: BTreeSet::new(),
+            heading_toc_changed: BTreeSet::new(),
+            dialog_grouping_books: HashMap::new(),
+            dialog_grouping_changed: BTreeSet::new(),
+            inline_notes_books: BTreeSet::new(),
+            inline_notes_changed: BTreeSet::new(),
+            line_break_books: BTreeSet::new(),
+            line_breaks_changed: BTreeSet::new(),
+            toc_expansion: HashMap::new(),
+            toc_expansion_changed: BTreeSet::new(),
+            reading_orders: HashMap::new(),
+            reading_orders_changed: BTreeSet::new(),
+            watchlists: HashMap::new(),
+            watchlists_changed: BTreeSet::new(),
+            archived_books: BTreeSet::new(),
+            archived_changed: BTreeSet::new(),
             file_path: Some(file_path.to_string()),
         }
     }
@@ -45,26 +164,58 @@ impl Bookmarks {
     pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
         match file_path {
             Some(path) => Self::load_from_file(path).unwrap_or_else(|e| {
-                log::error!("Failed to load bookmarks from {path}: {e}");
+                if migrate::is_newer_format(&e) {
+                    log::warn!("{path}: {e:#}; bookmarks won't be saved");
+                    return Self::read_only(path);
+                }
+                log::error!("Failed to load bookmarks from {path}: {e:#}");
+                // Saving starts a new file, so keep what couldn't be read
+                if Path::new(path).is_file() {
+                    match migrate::back_up(Path::new(path), "unreadable") {
+                        Ok(backup) => log::warn!("Kept the old bookmarks as {}", backup.display()),
+                        Err(e) => log::error!("{e:#}"),
+                    }
+                }
                 Self::with_file(path)
             }),
             None => Self::ephemeral(),
         }
     }
 
+    /// What can be read of a file from a newer bookokcat, not tied to the file so it's
+    /// never written over
+    fn read_only(file_path: &str) -> Self {
+        fs::read_to_string(file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .map(|bookmarks| Self {
+                file_path: None,
+                ..bookmarks
+            })
+            .unwrap_or_else(Self::ephemeral)
+    }
+
     pub fn load_from_file(file_path: &str) -> anyhow::Result<Self> {
         let path = Path::new(file_path);
         if path.exists() {
             let content = fs::read_to_string(path)?;
 
-            match serde_json::from_str::<Self>(&content) {
+            let parsed = serde_json::from_str(&content)
+                .map_err(anyhow::Error::from)
+                .and_then(|mut value| {
+                    if let Some(upgrade) = migrate::upgrade_bookmarks(&mut value)? {
+                        log::info!("Read bookmarks in format {}", upgrade.from);
+                    }
+                    Ok(serde_json::from_value::<Self>(value)?)
+                });
+            match parsed {
                 Ok(mut bookmarks) => {
                     bookmarks.file_path = Some(file_path.to_string());
                     Ok(bookmarks)
                 }
                 Err(e) => {
                     log::error!("Failed to parse bookmarks file: {e}");
-                    Err(anyhow::anyhow!("Failed to parse bookmarks: {}", e))
+                    Err(e.context("Failed to parse bookmarks"))
                 }
             }
         } else {
@@ -72,15 +223,142 @@ impl Bookmarks {
         }
     }
 
-    pub fn save(&self) -> anyhow::Result<()> {
-        match &self.file_path {
-            Some(path) => {
-                let content = serde_json::to_string_pretty(self)?;
-                fs::write(path, content)?;
-                Ok(())
+    /// Write the bookmarks file. Other bookokcat instances may be writing it too, so under
+    /// a lock the file on disk is merged in first: per book the most recently read position
+    /// wins, read chapters are combined. The file is then replaced atomically.
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        let Some(path) = self.file_path.clone() else {
+            return Ok(());
+        };
+
+        if self.is_special_file() {
+            fs::write(&path, serde_json::to_string_pretty(self)?)?;
+            return Ok(());
+        }
+
+        let _lock = FileLock::exclusive(&self.locks_dir().join("bookmarks.lock"))?;
+        match Self::load_from_file(&path) {
+            Ok(on_disk) => self.merge(on_disk),
+            Err(e) if migrate::is_newer_format(&e) => {
+                // A newer bookokcat wrote the file since this one started
+                log::warn!("{path}: {e:#}; bookmarks won't be saved");
+                self.file_path = None;
+                return Ok(());
             }
-            None => Ok(()),
+            Err(e) => {
+                log::error!("Failed to read bookmarks from {path} before saving: {e:#}");
+                let backup = migrate::back_up(Path::new(&path), "unreadable")?;
+                log::warn!("Kept the old bookmarks as {}", backup.display());
+            }
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        let temp_path = format!("{path}.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &path)?;
+        self.clear_changes();
+        Ok(())
+    }
+
+    /// Merge records written by another instance, last writer wins per book
+    fn merge(&mut self, other: Bookmarks) {
+        for (path, theirs) in other.books {
+            let ours_is_newer = self
+                .books
+                .get(&path)
+                .is_some_and(|ours| ours.last_read >= theirs.last_read);
+            if !ours_is_newer {
+                self.books.insert(path, theirs);
+            }
+        }
+
+        for (path, chapters) in other.read_chapters {
+            let unread = self.unread_chapters.get(&path);
+            let chapters = chapters
+                .into_iter()
+                .filter(|chapter| !unread.is_some_and(|unread| unread.contains(chapter)));
+            self.read_chapters.entry(path).or_default().extend(chapters);
         }
+
+        merge_book_set(
+            &mut self.heading_toc_books,
+            other.heading_toc_books,
+            &self.heading_toc_changed,
+        );
+        merge_book_map(
+            &mut self.dialog_grouping_books,
+            other.dialog_grouping_books,
+            &self.dialog_grouping_changed,
+        );
+        merge_book_set(
+            &mut self.inline_notes_books,
+            other.inline_notes_books,
+            &self.inline_notes_changed,
+        );
+        merge_book_set(
+            &mut self.line_break_books,
+            other.line_break_books,
+            &self.line_breaks_changed,
+        );
+        merge_book_map(
+            &mut self.toc_expansion,
+            other.toc_expansion,
+            &self.toc_expansion_changed,
+        );
+        merge_book_map(
+            &mut self.reading_orders,
+            other.reading_orders,
+            &self.reading_orders_changed,
+        );
+        merge_book_map(
+            &mut self.watchlists,
+            other.watchlists,
+            &self.watchlists_changed,
+        );
+        merge_book_set(
+            &mut self.archived_books,
+            other.archived_books,
+            &self.archived_changed,
+        );
+    }
+
+    /// Once saved, the file holds this instance's changes, so later saves take the other
+    /// instances' word for those books again
+    fn clear_changes(&mut self) {
+        self.heading_toc_changed.clear();
+        self.dialog_grouping_changed.clear();
+        self.inline_notes_changed.clear();
+        self.line_breaks_changed.clear();
+        self.toc_expansion_changed.clear();
+        self.reading_orders_changed.clear();
+        self.watchlists_changed.clear();
+        self.archived_changed.clear();
+    }
+
+    /// Special files such as /dev/null can't be merged, locked or renamed over
+    fn is_special_file(&self) -> bool {
+        self.file_path
+            .as_ref()
+            .is_some_and(|path| fs::metadata(path).is_ok_and(|metadata| !metadata.is_file()))
+    }
+
+    fn locks_dir(&self) -> PathBuf {
+        let parent = self
+            .file_path
+            .as_deref()
+            .and_then(|path| Path::new(path).parent())
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        parent.join(LOCKS_DIR)
+    }
+
+    /// Lock file marking a book as open in some instance; `None` without a bookmarks file
+    pub fn book_lock_path(&self, book_path: &str) -> Option<PathBuf> {
+        if self.file_path.is_none() || self.is_special_file() {
+            return None;
+        }
+        let digest = md5::compute(book_path.as_bytes());
+        Some(self.locks_dir().join(format!("{digest:x}.lock")))
     }
 
     pub fn get_bookmark(&self, path: &str) -> Option<&Bookmark> {
@@ -120,7 +398,360 @@ impl Bookmarks {
         }
     }
 
+    /// Record that a chapter has been read; returns true if it wasn't marked before
+    pub fn mark_chapter_read(&mut self, path: &str, chapter_index: usize) -> bool {
+        if let Some(unread) = self.unread_chapters.get_mut(path) {
+            unread.remove(&chapter_index);
+        }
+        self.read_chapters
+            .entry(path.to_string())
+            .or_default()
+            .insert(chapter_index)
+    }
+
+    /// Mark a range of chapters as read or unread at once, e.g. to catch up with a paper copy
+    pub fn set_chapters_read(
+        &mut self,
+        path: &str,
+        chapters: impl IntoIterator<Item = usize>,
+        read: bool,
+    ) {
+        for chapter_index in chapters {
+            if read {
+                self.mark_chapter_read(path, chapter_index);
+            } else {
+                if let Some(chapters) = self.read_chapters.get_mut(path) {
+                    chapters.remove(&chapter_index);
+                }
+                self.unread_chapters
+                    .entry(path.to_string())
+                    .or_default()
+                    .insert(chapter_index);
+            }
+        }
+        if self.read_chapters.get(path).is_some_and(BTreeSet::is_empty) {
+            self.read_chapters.remove(path);
+        }
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    /// How many chapters of a book have been read
+    pub fn read_chapter_count(&self, path: &str) -> usize {
+        self.read_chapters.get(path).map_or(0, BTreeSet::len)
+    }
+
+    pub fn is_chapter_read(&self, path: &str, chapter_index: usize) -> bool {
+        self.read_chapters
+            .get(path)
+            .is_some_and(|chapters| chapters.contains(&chapter_index))
+    }
+
+    pub fn uses_heading_toc(&self, path: &str) -> bool {
+        self.heading_toc_books.contains(path)
+    }
+
+    pub fn set_heading_toc(&mut self, path: &str, enabled: bool) {
+        if enabled {
+            self.heading_toc_books.insert(path.to_string());
+        } else {
+            self.heading_toc_books.remove(path);
+        }
+        self.heading_toc_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    pub fn archived_books(&self) -> &BTreeSet<String> {
+        &self.archived_books
+    }
+
+    pub fn set_archived(&mut self, path: &str, archived: bool) {
+        if archived {
+            self.archived_books.insert(path.to_string());
+        } else {
+            self.archived_books.remove(path);
+        }
+        self.archived_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    pub fn inlines_notes(&self, path: &str) -> bool {
+        self.inline_notes_books.contains(path)
+    }
+
+    pub fn set_inline_notes(&mut self, path: &str, enabled: bool) {
+        if enabled {
+            self.inline_notes_books.insert(path.to_string());
+        } else {
+            self.inline_notes_books.remove(path);
+        }
+        self.inline_notes_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    pub fn preserves_line_breaks(&self, path: &str) -> bool {
+        self.line_break_books.contains(path)
+    }
+
+    pub fn set_preserve_line_breaks(&mut self, path: &str, enabled: bool) {
+        if enabled {
+            self.line_break_books.insert(path.to_string());
+        } else {
+            self.line_break_books.remove(path);
+        }
+        self.line_breaks_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    /// Dialog grouping chosen for this book, if it differs from the preference
+    pub fn dialog_grouping_override(&self, path: &str) -> Option<bool> {
+        self.dialog_grouping_books.get(path).copied()
+    }
+
+    /// `None` goes back to following the preference
+    pub fn set_dialog_grouping_override(&mut self, path: &str, enabled: Option<bool>) {
+        match enabled {
+            Some(enabled) => self.dialog_grouping_books.insert(path.to_string(), enabled),
+            None => self.dialog_grouping_books.remove(path),
+        };
+        self.dialog_grouping_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    /// The TOC sections that were expanded when this book was last read
+    pub fn toc_expansion(&self, path: &str) -> &[String] {
+        self.toc_expansion.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Remember the expanded TOC sections of a book; saves only when they changed
+    pub fn set_toc_expansion(&mut self, path: &str, expanded: Vec<String>) {
+        if self.toc_expansion(path) == expanded.as_slice() {
+            return;
+        }
+        if expanded.is_empty() {
+            self.toc_expansion.remove(path);
+        } else {
+            self.toc_expansion.insert(path.to_string(), expanded);
+        }
+        self.toc_expansion_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    /// The book's custom reading order; empty when it's read in spine order
+    pub fn reading_order(&self, path: &str) -> &[String] {
+        self.reading_orders.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Keep a custom reading order for a book, or go back to spine order with an empty one
+    pub fn set_reading_order(&mut self, path: &str, order: Vec<String>) {
+        if order.is_empty() {
+            self.reading_orders.remove(path);
+        } else {
+            self.reading_orders.insert(path.to_string(), order);
+        }
+        self.reading_orders_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    /// Terms to highlight in a book
+    pub fn watchlist(&self, path: &str) -> &[String] {
+        self.watchlists.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set_watchlist(&mut self, path: &str, terms: Vec<String>) {
+        if terms.is_empty() {
+            self.watchlists.remove(path);
+        } else {
+            self.watchlists.insert(path.to_string(), terms);
+        }
+        self.watchlists_changed.insert(path.to_string());
+
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Bookmark)> {
         self.books.iter()
     }
 }
+
+/// Per-book setting stored as a set: books this instance changed keep its choice, the rest
+/// follow the file on disk
+fn merge_book_set(
+    ours: &mut BTreeSet<String>,
+    theirs: BTreeSet<String>,
+    changed: &BTreeSet<String>,
+) {
+    ours.retain(|path| changed.contains(path) || theirs.contains(path));
+    ours.extend(theirs.into_iter().filter(|path| !changed.contains(path)));
+}
+
+/// Per-book setting stored as a map, merged like [`merge_book_set`]
+fn merge_book_map<V>(
+    ours: &mut HashMap<String, V>,
+    theirs: HashMap<String, V>,
+    changed: &BTreeSet<String>,
+) {
+    ours.retain(|path, _| changed.contains(path) || theirs.contains_key(path));
+    for (path, value) in theirs {
+        if !changed.contains(&path) {
+            ours.insert(path, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_merges_records_from_other_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let file = file.to_str().unwrap();
+
+        let mut first = Bookmarks::with_file(file);
+        let mut second = Bookmarks::with_file(file);
+
+        first.update_bookmark("a.epub", "ch1.xhtml".to_string(), None, Some(1), Some(9));
+        first.mark_chapter_read("a.epub", 0);
+        second.update_bookmark("b.epub", "ch4.xhtml".to_string(), None, Some(4), Some(9));
+        // The second instance read "a.epub" most recently, so its position wins
+        second.update_bookmark("a.epub", "ch2.xhtml".to_string(), None, Some(2), Some(9));
+        first.save().unwrap();
+
+        let merged = Bookmarks::load_from_file(file).unwrap();
+        assert_eq!(
+            merged.get_bookmark("a.epub").unwrap().chapter_href,
+            "ch2.xhtml"
+        );
+        assert_eq!(
+            merged.get_bookmark("b.epub").unwrap().chapter_href,
+            "ch4.xhtml"
+        );
+        assert!(merged.is_chapter_read("a.epub", 0));
+    }
+
+    #[test]
+    fn test_chapters_marked_unread_stay_unread_after_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let file = file.to_str().unwrap();
+
+        let mut bookmarks = Bookmarks::with_file(file);
+        bookmarks.set_chapters_read("a.epub", 0..4, true);
+        // The copy on disk still has them as read when this saves
+        bookmarks.set_chapters_read("a.epub", 2..4, false);
+        bookmarks.mark_chapter_read("a.epub", 3);
+        bookmarks.save().unwrap();
+
+        let merged = Bookmarks::load_from_file(file).unwrap();
+        assert_eq!(merged.read_chapter_count("a.epub"), 3);
+        assert!(merged.is_chapter_read("a.epub", 1));
+        assert!(!merged.is_chapter_read("a.epub", 2));
+        assert!(merged.is_chapter_read("a.epub", 3));
+    }
+
+    #[test]
+    fn test_toc_expansion_is_kept_per_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let file = file.to_str().unwrap();
+
+        let mut first = Bookmarks::with_file(file);
+        let mut second = Bookmarks::with_file(file);
+        first.set_toc_expansion("a.epub", vec!["Part I".to_string()]);
+        second.set_toc_expansion("b.epub", vec!["Part II › Tools".to_string()]);
+        // Folding everything in "a.epub" again drops its entry
+        first.set_toc_expansion("a.epub", Vec::new());
+
+        let merged = Bookmarks::load_from_file(file).unwrap();
+        assert!(merged.toc_expansion("a.epub").is_empty());
+        assert_eq!(merged.toc_expansion("b.epub"), ["Part II › Tools"]);
+    }
+
+    #[test]
+    fn test_per_book_settings_from_other_instances_survive_later_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let file = file.to_str().unwrap();
+
+        let mut first = Bookmarks::with_file(file);
+        let mut second = Bookmarks::with_file(file);
+        first.set_archived("p.epub", true);
+        second.set_archived("q.epub", true);
+        second.set_heading_toc("q.epub", true);
+        // An ordinary page turn in the first instance must not undo the second's changes
+        first.update_bookmark("p.epub", "ch1.xhtml".to_string(), None, Some(1), Some(9));
+
+        let merged = Bookmarks::load_from_file(file).unwrap();
+        assert!(merged.archived_books().contains("p.epub"));
+        assert!(merged.archived_books().contains("q.epub"));
+        assert!(merged.uses_heading_toc("q.epub"));
+
+        // Unarchiving elsewhere is picked up rather than written back
+        second.set_archived("p.epub", false);
+        first.update_bookmark("p.epub", "ch2.xhtml".to_string(), None, Some(2), Some(9));
+        let merged = Bookmarks::load_from_file(file).unwrap();
+        assert!(!merged.archived_books().contains("p.epub"));
+    }
+
+    #[test]
+    fn test_save_leaves_files_it_cannot_read_intact() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let file = file.to_str().unwrap();
+
+        // A newer bookokcat writes the file while this one is running
+        let mut bookmarks = Bookmarks::with_file(file);
+        let newer = r#"{"version": 999, "books": {}}"#;
+        fs::write(file, newer).unwrap();
+        bookmarks.update_bookmark("a.epub", "ch1.xhtml".to_string(), None, Some(1), Some(9));
+        assert_eq!(fs::read_to_string(file).unwrap(), newer);
+        // It stays read-only from then on
+        bookmarks.set_archived("a.epub", true);
+        assert_eq!(fs::read_to_string(file).unwrap(), newer);
+
+        // A damaged file is backed up before it's replaced
+        let mut bookmarks = Bookmarks::with_file(file);
+        fs::write(file, "{ not json").unwrap();
+        bookmarks.update_bookmark("a.epub", "ch1.xhtml".to_string(), None, Some(1), Some(9));
+        let backups: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".unreadable-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), "{ not json");
+        assert!(
+            Bookmarks::load_from_file(file)
+                .unwrap()
+                .get_bookmark("a.epub")
+                .is_some()
+        );
+    }
+}
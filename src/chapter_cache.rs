@@ -0,0 +1,306 @@
+use crate::markdown::{Document, Node};
+use crate::parsing::content_filter::ContentFilters;
+use crate::parsing::css::StyleSheet;
+use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+use crate::parsing::substitutions::Substitutions;
+use crate::parsing::text_cleanup::TextCleanup;
+use crate::parsing::text_generator::TextGenerator;
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// A chapter converted from HTML, ready to be rendered
+#[derive(Clone)]
+pub struct CachedChapter {
+    pub document: Arc<Document>,
+    pub title: Option<String>,
+}
+
+/// Closed while the app is idle; the worker sleeps at it until it's opened again
+#[derive(Default)]
+struct PauseGate {
+    paused: Mutex<bool>,
+    resumed: Condvar,
+}
+
+impl PauseGate {
+    fn set_paused(&self, paused: bool) {
+        if let Ok(mut current) = self.paused.lock() {
+            *current = paused;
+        }
+        if !paused {
+            self.resumed.notify_all();
+        }
+    }
+
+    /// Block while paused
+    fn wait(&self) {
+        if let Ok(paused) = self.paused.lock() {
+            drop(self.resumed.wait_while(paused, |paused| *paused));
+        }
+    }
+}
+
+struct PrefetchRequest {
+    generation: u64,
+    chapter_index: usize,
+    raw_html: String,
+}
+
+struct PrefetchResult {
+    generation: u64,
+    chapter_index: usize,
+    chapter: CachedChapter,
+}
+
+/// Converted chapters of the current book. Chapters can be parsed ahead of time on a
+/// background thread so that navigating to them doesn't have to wait for the conversion.
+pub struct ChapterCache {
+    chapters: HashMap<usize, CachedChapter>,
+    /// Rough size in bytes of each cached document, taken when it's inserted
+    sizes: HashMap<usize, usize>,
+    pending: HashSet<usize>,
+    /// Bumped on every book change so late results from the previous book are dropped
+    generation: u64,
+    requests: Option<Sender<PrefetchRequest>>,
+    results_sender: Sender<PrefetchResult>,
+    results: Receiver<PrefetchResult>,
+    content_filters: ContentFilters,
+    text_cleanup: TextCleanup,
+    substitutions: Substitutions,
+    styles: Option<Arc<StyleSheet>>,
+    group_dialog: bool,
+    preserve_line_breaks: bool,
+    chapter_contents: bool,
+    /// Set while the app is idle; the worker holds queued conversions until it's cleared
+    pause_gate: Arc<PauseGate>,
+}
+
+impl Default for ChapterCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChapterCache {
+    pub fn new() -> Self {
+        let (results_sender, results) = channel();
+        Self {
+            chapters: HashMap::new(),
+            sizes: HashMap::new(),
+            pending: HashSet::new(),
+            generation: 0,
+            requests: None,
+            results_sender,
+            results,
+            content_filters: ContentFilters::default(),
+            text_cleanup: TextCleanup::default(),
+            substitutions: Substitutions::default(),
+            styles: None,
+            group_dialog: true,
+            preserve_line_breaks: false,
+            chapter_contents: false,
+            pause_gate: Arc::new(PauseGate::default()),
+        }
+    }
+
+    /// Filters the background conversions apply; set before the first prefetch
+    pub fn set_content_filters(&mut self, filters: ContentFilters) {
+        self.content_filters = filters;
+    }
+
+    /// Text cleanup the background conversions apply; set before the first prefetch
+    pub fn set_text_cleanup(&mut self, cleanup: TextCleanup) {
+        self.text_cleanup = cleanup;
+    }
+
+    /// The stylesheet of the current book, if its CSS is honored. The worker is replaced
+    /// so that conversions from here on use it.
+    pub fn set_styles(&mut self, styles: Option<Arc<StyleSheet>>) {
+        self.styles = styles;
+        self.requests = None;
+    }
+
+    /// The current book's find/replace rules; replaces the worker like `set_styles`
+    pub fn set_substitutions(&mut self, substitutions: Substitutions) {
+        self.substitutions = substitutions;
+        self.requests = None;
+    }
+
+    /// Whether conversions join dialog paragraphs; replaces the worker like `set_styles`
+    pub fn set_dialog_grouping(&mut self, enabled: bool) {
+        self.group_dialog = enabled;
+        self.requests = None;
+    }
+
+    /// Whether conversions keep `<br/>` line layout; replaces the worker like `set_styles`
+    pub fn set_line_breaks_preserved(&mut self, enabled: bool) {
+        self.preserve_line_breaks = enabled;
+        self.requests = None;
+    }
+
+    /// Whether conversions list a chapter's sections under its title; set before the
+    /// first prefetch
+    pub fn set_chapter_contents(&mut self, enabled: bool) {
+        self.chapter_contents = enabled;
+    }
+
+    /// Hold background conversions (e.g. while nobody is reading) or let them carry on.
+    /// Requests keep queueing up while paused.
+    pub fn set_paused(&self, paused: bool) {
+        self.pause_gate.set_paused(paused);
+    }
+
+    /// Forget everything cached for the previous book
+    pub fn clear(&mut self) {
+        self.chapters.clear();
+        self.sizes.clear();
+        self.pending.clear();
+        self.generation += 1;
+    }
+
+    pub fn get(&self, chapter_index: usize) -> Option<&CachedChapter> {
+        self.chapters.get(&chapter_index)
+    }
+
+    pub fn insert(&mut self, chapter_index: usize, chapter: CachedChapter) {
+        self.pending.remove(&chapter_index);
+        self.sizes
+            .insert(chapter_index, approximate_size(&chapter.document));
+        self.chapters.insert(chapter_index, chapter);
+    }
+
+    pub fn is_cached(&self, chapter_index: usize) -> bool {
+        self.chapters.contains_key(&chapter_index)
+    }
+
+    pub fn is_pending(&self, chapter_index: usize) -> bool {
+        self.pending.contains(&chapter_index)
+    }
+
+    pub fn cached_count(&self) -> usize {
+        self.chapters.len()
+    }
+
+    /// Rough memory held by the cached documents, in bytes
+    pub fn approximate_bytes(&self) -> usize {
+        self.sizes.values().sum()
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Queue a chapter for conversion on the background thread
+    pub fn prefetch(&mut self, chapter_index: usize, raw_html: String) {
+        if self.is_cached(chapter_index) || self.is_pending(chapter_index) {
+            return;
+        }
+
+        let request = PrefetchRequest {
+            generation: self.generation,
+            chapter_index,
+            raw_html,
+        };
+        let request = match self.worker().send(request) {
+            Ok(()) => {
+                self.pending.insert(chapter_index);
+                return;
+            }
+            Err(e) => e.0,
+        };
+
+        // The worker went away (e.g. it panicked); start a new one and retry once
+        warn!("Chapter prefetch worker stopped, restarting it");
+        self.requests = None;
+        if self.worker().send(request).is_ok() {
+            self.pending.insert(chapter_index);
+        }
+    }
+
+    /// Move finished conversions into the cache; returns true if anything arrived
+    pub fn receive(&mut self) -> bool {
+        let mut received = false;
+        while let Ok(result) = self.results.try_recv() {
+            if result.generation != self.generation {
+                continue;
+            }
+            debug!("Prefetched chapter {}", result.chapter_index);
+            self.insert(result.chapter_index, result.chapter);
+            received = true;
+        }
+        received
+    }
+
+    fn worker(&mut self) -> &Sender<PrefetchRequest> {
+        self.requests.get_or_insert_with(|| {
+            let (sender, requests) = channel::<PrefetchRequest>();
+            let results = self.results_sender.clone();
+            let filters = self.content_filters.clone();
+            let cleanup = self.text_cleanup;
+            let substitutions = self.substitutions.clone();
+            let styles = self.styles.clone();
+            let group_dialog = self.group_dialog;
+            let preserve_line_breaks = self.preserve_line_breaks;
+            let chapter_contents = self.chapter_contents;
+            let pause_gate = self.pause_gate.clone();
+            thread::spawn(move || {
+                let mut converter = HtmlToMarkdownConverter::new()
+                    .with_filters(filters)
+                    .with_cleanup(cleanup)
+                    .with_substitutions(substitutions)
+                    .with_styles(styles)
+                    .with_dialog_grouping(group_dialog)
+                    .with_line_breaks_preserved(preserve_line_breaks)
+                    .with_chapter_contents(chapter_contents);
+                // Ends once the cache (and with it the request sender) is dropped
+                while let Ok(request) = requests.recv() {
+                    pause_gate.wait();
+                    let chapter = CachedChapter {
+                        title: TextGenerator::extract_chapter_title(&request.raw_html),
+                        document: Arc::new(converter.convert(&request.raw_html)),
+                    };
+                    let result = PrefetchResult {
+                        generation: request.generation,
+                        chapter_index: request.chapter_index,
+                        chapter,
+                    };
+                    if results.send(result).is_err() {
+                        break;
+                    }
+                }
+            });
+            sender
+        })
+    }
+}
+
+/// The document's text plus its top-level blocks; nested blocks and styling aren't counted
+fn approximate_size(document: &Document) -> usize {
+    document.plain_text().len() + document.blocks.len() * std::mem::size_of::<Node>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_paused_cache_holds_conversions_until_resumed() {
+        let mut cache = ChapterCache::new();
+        cache.set_paused(true);
+        cache.prefetch(3, "<h1>Three</h1><p>Text.</p>".to_string());
+        thread::sleep(Duration::from_millis(500));
+        assert!(!cache.receive());
+        assert!(cache.is_pending(3));
+
+        cache.set_paused(false);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !cache.receive() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(cache.is_cached(3));
+    }
+}
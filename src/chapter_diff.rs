@@ -0,0 +1,234 @@
+use crate::markdown::{Block, Document, HeadingLevel, Node, Text, TextNode};
+use epub::doc::EpubDoc;
+use std::io::{Read, Seek};
+
+/// Past this many cells the table lining up the blocks gets too big; the blocks between
+/// the common start and end are then shown as replaced wholesale
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// What happened to a block between the two versions of a chapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockChange {
+    Unchanged,
+    Inserted,
+    Deleted,
+}
+
+impl BlockChange {
+    /// The `epub:type` of the block a run of changes is wrapped in for the reader to color
+    pub fn epub_type(self) -> Option<&'static str> {
+        match self {
+            BlockChange::Unchanged => None,
+            BlockChange::Inserted => Some("bookokcat-inserted"),
+            BlockChange::Deleted => Some("bookokcat-deleted"),
+        }
+    }
+
+    pub fn from_epub_type(epub_type: &str) -> Option<Self> {
+        [BlockChange::Inserted, BlockChange::Deleted]
+            .into_iter()
+            .find(|change| change.epub_type() == Some(epub_type))
+    }
+}
+
+/// The HTML of a chapter by its spine index, leaving the book at the chapter it was on
+pub fn chapter_html<R: Read + Seek>(doc: &mut EpubDoc<R>, chapter_index: usize) -> Option<String> {
+    let original_chapter = doc.get_current_chapter();
+    let html = doc
+        .set_current_chapter(chapter_index)
+        .then(|| doc.get_current_str().map(|(html, _)| html))
+        .flatten();
+    doc.set_current_chapter(original_chapter);
+    html
+}
+
+/// A block compares by its words, so reflowed or re-indented text counts as the same
+fn block_key(node: &Node) -> String {
+    node.plain_text()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Line up the top-level blocks of two versions of a chapter, keeping as many as possible
+/// unchanged. Deletions come before the insertions that replace them.
+pub fn diff_blocks(old: &Document, new: &Document) -> Vec<(BlockChange, Node)> {
+    let old_keys: Vec<String> = old.blocks.iter().map(block_key).collect();
+    let new_keys: Vec<String> = new.blocks.iter().map(block_key).collect();
+
+    let prefix = old_keys
+        .iter()
+        .zip(&new_keys)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_keys[prefix..]
+        .iter()
+        .rev()
+        .zip(new_keys[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_keys[prefix..old_keys.len() - suffix];
+    let new_mid = &new_keys[prefix..new_keys.len() - suffix];
+
+    let mut changes: Vec<(BlockChange, usize)> = Vec::new();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        changes.extend((0..old_mid.len()).map(|i| (BlockChange::Deleted, prefix + i)));
+        changes.extend((0..new_mid.len()).map(|j| (BlockChange::Inserted, prefix + j)));
+    } else {
+        // common[i][j]: blocks the rest of the chapters from old_mid[i] and new_mid[j] share
+        let mut common = vec![vec![0u32; new_mid.len() + 1]; old_mid.len() + 1];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                common[i][j] = if old_mid[i] == new_mid[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() || j < new_mid.len() {
+            if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+                changes.push((BlockChange::Unchanged, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == new_mid.len()
+                || (i < old_mid.len() && common[i + 1][j] >= common[i][j + 1])
+            {
+                changes.push((BlockChange::Deleted, prefix + i));
+                i += 1;
+            } else {
+                changes.push((BlockChange::Inserted, prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    let block = |change: BlockChange, idx: usize| match change {
+        BlockChange::Deleted => old.blocks[idx].clone(),
+        _ => new.blocks[idx].clone(),
+    };
+    (0..prefix)
+        .map(|idx| (BlockChange::Unchanged, idx))
+        .chain(changes)
+        .chain((new_keys.len() - suffix..new_keys.len()).map(|idx| (BlockChange::Unchanged, idx)))
+        .map(|(change, idx)| (change, block(change, idx)))
+        .collect()
+}
+
+/// The `:diff` report: a summary, then the newer version of the chapter with the blocks
+/// it dropped put back where they were. Runs of changed blocks are wrapped in an epub
+/// block of their kind, which the reader draws in green or red.
+pub fn diff_report(old_name: &str, new_name: &str, changes: Vec<(BlockChange, Node)>) -> Document {
+    fn paragraph(content: String) -> Node {
+        Node::new(
+            Block::Paragraph {
+                content: Text::from(TextNode::new(content, None)),
+            },
+            0..0,
+        )
+    }
+
+    let count = |kind: BlockChange| changes.iter().filter(|(change, _)| *change == kind).count();
+    let (inserted, deleted) = (count(BlockChange::Inserted), count(BlockChange::Deleted));
+
+    let mut doc = Document::new();
+    doc.blocks.push(Node::new(
+        Block::Heading {
+            level: HeadingLevel::H1,
+            content: Text::from("Chapter diff"),
+        },
+        0..0,
+    ));
+    doc.blocks
+        .push(paragraph(format!("{old_name} → {new_name}")));
+    doc.blocks.push(paragraph(if inserted + deleted == 0 {
+        "No differences.".to_string()
+    } else {
+        format!(
+            "{inserted} blocks added (+), {deleted} removed (-), {} unchanged.",
+            changes.len() - inserted - deleted
+        )
+    }));
+
+    let mut changes = changes.into_iter().peekable();
+    while let Some((change, node)) = changes.next() {
+        let Some(epub_type) = change.epub_type() else {
+            doc.blocks.push(node);
+            continue;
+        };
+        let mut content = vec![node];
+        while let Some((_, node)) = changes.next_if(|(next, _)| *next == change) {
+            content.push(node);
+        }
+        doc.blocks.push(Node::new(
+            Block::EpubBlock {
+                epub_type: epub_type.to_string(),
+                element_name: "div".to_string(),
+                content,
+            },
+            0..0,
+        ));
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    fn summary(changes: &[(BlockChange, Node)]) -> Vec<(BlockChange, String)> {
+        changes
+            .iter()
+            .map(|(change, node)| (*change, node.plain_text()))
+            .collect()
+    }
+
+    #[test]
+    fn test_blocks_line_up_across_revisions() {
+        let old = HtmlToMarkdownConverter::new().convert(
+            "<h1>One</h1><p>Kept.</p><p>Dropped.</p><p>Also   kept.</p><p>Old ending.</p>",
+        );
+        let new = HtmlToMarkdownConverter::new().convert(
+            "<h1>One</h1><p>Kept.</p><p>Also kept.</p><p>Brand new.</p><p>New ending.</p>",
+        );
+        use BlockChange::*;
+        assert_eq!(
+            summary(&diff_blocks(&old, &new)),
+            vec![
+                (Unchanged, "One".to_string()),
+                (Unchanged, "Kept.".to_string()),
+                (Deleted, "Dropped.".to_string()),
+                (Unchanged, "Also kept.".to_string()),
+                (Deleted, "Old ending.".to_string()),
+                (Inserted, "Brand new.".to_string()),
+                (Inserted, "New ending.".to_string()),
+            ]
+        );
+
+        let report = diff_report("old", "new", diff_blocks(&old, &new));
+        let kinds: Vec<Option<BlockChange>> = report.blocks[3..]
+            .iter()
+            .map(|node| match &node.block {
+                Block::EpubBlock { epub_type, .. } => BlockChange::from_epub_type(epub_type),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                None,
+                None,
+                Some(Deleted),
+                None,
+                Some(Deleted),
+                Some(Inserted)
+            ]
+        );
+        assert_eq!(
+            report.blocks[2].plain_text(),
+            "2 blocks added (+), 2 removed (-), 3 unchanged."
+        );
+    }
+}
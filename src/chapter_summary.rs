@@ -0,0 +1,251 @@
+use crate::markdown::{Block, Document, Node};
+use std::collections::HashMap;
+
+/// How many of the most frequent terms a summary lists
+const TOP_TERMS: usize = 15;
+
+/// Function words that say nothing about what a chapter is about
+pub(crate) const STOPWORDS: &[&str] = &[
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "also",
+    "although",
+    "among",
+    "and",
+    "another",
+    "any",
+    "are",
+    "around",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "can",
+    "cannot",
+    "could",
+    "did",
+    "does",
+    "doing",
+    "done",
+    "down",
+    "during",
+    "each",
+    "either",
+    "even",
+    "ever",
+    "every",
+    "few",
+    "for",
+    "from",
+    "further",
+    "had",
+    "has",
+    "have",
+    "having",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "however",
+    "into",
+    "its",
+    "itself",
+    "just",
+    "let",
+    "like",
+    "made",
+    "make",
+    "many",
+    "may",
+    "might",
+    "more",
+    "most",
+    "much",
+    "must",
+    "myself",
+    "neither",
+    "never",
+    "nor",
+    "not",
+    "now",
+    "off",
+    "often",
+    "once",
+    "one",
+    "only",
+    "other",
+    "others",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "said",
+    "same",
+    "says",
+    "see",
+    "seen",
+    "shall",
+    "she",
+    "should",
+    "since",
+    "some",
+    "still",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "though",
+    "through",
+    "thus",
+    "too",
+    "under",
+    "until",
+    "upon",
+    "very",
+    "was",
+    "way",
+    "well",
+    "were",
+    "what",
+    "when",
+    "where",
+    "whether",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "whose",
+    "why",
+    "will",
+    "with",
+    "within",
+    "without",
+    "would",
+    "yet",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+
+/// What a chapter is about at a glance: its length, headings and most frequent terms
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChapterSummary {
+    pub words: usize,
+    pub headings: Vec<String>,
+    /// The most frequent non-stopword terms with their counts, most frequent first
+    pub terms: Vec<(String, usize)>,
+    /// The chapter's language, detected or declared
+    pub language: Option<String>,
+}
+
+impl ChapterSummary {
+    pub fn of(doc: &Document) -> ChapterSummary {
+        let text = doc.plain_text();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut words = 0;
+        for word in text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’')) {
+            let word = word.trim_matches(|c| c == '\'' || c == '’');
+            if word.is_empty() {
+                continue;
+            }
+            words += 1;
+            let word = word.to_lowercase();
+            let word = word
+                .strip_suffix("'s")
+                .or_else(|| word.strip_suffix("’s"))
+                .unwrap_or(&word)
+                .to_string();
+            if word.chars().count() < 3
+                || !word.chars().any(char::is_alphabetic)
+                || STOPWORDS.contains(&word.as_str())
+            {
+                continue;
+            }
+            *counts.entry(word).or_default() += 1;
+        }
+
+        let mut terms: Vec<(String, usize)> = counts.into_iter().filter(|(_, n)| *n > 1).collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        terms.truncate(TOP_TERMS);
+
+        let mut headings = Vec::new();
+        collect_headings(&doc.blocks, &mut headings);
+        ChapterSummary {
+            words,
+            headings,
+            terms,
+            language: doc.language.clone(),
+        }
+    }
+}
+
+fn collect_headings(nodes: &[Node], headings: &mut Vec<String>) {
+    for node in nodes {
+        match &node.block {
+            Block::Heading { content, .. } => {
+                let title = content
+                    .plain_text()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if !title.is_empty() {
+                    headings.push(title);
+                }
+            }
+            Block::Quote { content }
+            | Block::EpubBlock { content, .. }
+            | Block::Details { content, .. } => collect_headings(content, headings),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_summary_counts_terms_without_stopwords() {
+        let html = "<h1>The Whale</h1><p>The whale and the sea. The whale's sea was grey,
+            and the whale was there.</p><section><h2>Ahab</h2><p>Ahab saw the whale.</p></section>";
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let summary = ChapterSummary::of(&doc);
+
+        assert_eq!(summary.headings, ["The Whale", "Ahab"]);
+        assert_eq!(
+            summary.terms,
+            [
+                ("whale".to_string(), 5),
+                ("ahab".to_string(), 2),
+                ("sea".to_string(), 2)
+            ]
+        );
+    }
+}
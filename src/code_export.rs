@@ -0,0 +1,88 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// File extension for a code block's language hint; plain `txt` when unknown
+pub fn extension_for_language(language: Option<&str>) -> &'static str {
+    let Some(language) = language else {
+        return "txt";
+    };
+    match language.trim().to_ascii_lowercase().as_str() {
+        "rust" | "rs" => "rs",
+        "python" | "py" | "python3" => "py",
+        "javascript" | "js" | "node" => "js",
+        "typescript" | "ts" => "ts",
+        "java" => "java",
+        "kotlin" | "kt" => "kt",
+        "go" | "golang" => "go",
+        "c" => "c",
+        "cpp" | "c++" | "cxx" => "cpp",
+        "csharp" | "c#" | "cs" => "cs",
+        "ruby" | "rb" => "rb",
+        "php" => "php",
+        "swift" => "swift",
+        "scala" => "scala",
+        "haskell" | "hs" => "hs",
+        "elixir" | "ex" => "ex",
+        "erlang" | "erl" => "erl",
+        "clojure" | "clj" => "clj",
+        "lua" => "lua",
+        "perl" | "pl" => "pl",
+        "r" => "r",
+        "sql" => "sql",
+        "shell" | "sh" | "bash" | "zsh" | "console" => "sh",
+        "powershell" | "ps1" => "ps1",
+        "html" | "xhtml" => "html",
+        "xml" => "xml",
+        "css" => "css",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "markdown" | "md" => "md",
+        "dockerfile" | "docker" => "dockerfile",
+        "makefile" | "make" => "mk",
+        _ => "txt",
+    }
+}
+
+/// Append a code block to a scratch file, preceded by a line saying where it came from
+pub fn append_snippet(path: &Path, source: &str, code: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let needs_separator = fs::metadata(path).is_ok_and(|metadata| metadata.len() > 0);
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if needs_separator {
+        writeln!(file)?;
+    }
+    writeln!(file, "---- {source} ----")?;
+    write!(file, "{code}")?;
+    if !code.ends_with('\n') {
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_snippet_separates_blocks() {
+        assert_eq!(extension_for_language(Some("Rust")), "rs");
+        assert_eq!(extension_for_language(Some("brainfuck")), "txt");
+        assert_eq!(extension_for_language(None), "txt");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scratch.txt");
+        append_snippet(&path, "Chapter 1", "fn main() {}").unwrap();
+        append_snippet(&path, "Chapter 2", "let x = 1;\n").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "---- Chapter 1 ----\nfn main() {}\n\n---- Chapter 2 ----\nlet x = 1;\n"
+        );
+    }
+}
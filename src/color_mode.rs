@@ -1,6 +1,12 @@
-use ratatui::style::Color;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
 use std::env;
 
+/// Check the `NO_COLOR` convention (https://no-color.org): any non-empty value disables color
+pub fn no_color_requested() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
 /// Detect if the terminal supports true color (24-bit RGB)
 pub fn supports_true_color() -> bool {
     if let Ok(colorterm) = env::var("COLORTERM") {
@@ -20,6 +26,47 @@ pub fn supports_true_color() -> bool {
     false
 }
 
+/// Guess whether the terminal understands OSC 8 hyperlinks. `FORCE_HYPERLINK` settles
+/// it either way; multiplexers like tmux only pass them on when configured to, so they
+/// count as no.
+pub fn supports_hyperlinks() -> bool {
+    if let Ok(force) = env::var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if env::var_os("TMUX").is_some()
+        || env::var("TERM").is_ok_and(|term| term.starts_with("screen"))
+    {
+        return false;
+    }
+
+    if env::var("TERM_PROGRAM").is_ok_and(|program| {
+        matches!(
+            program.as_str(),
+            "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "Tabby"
+        )
+    }) {
+        return true;
+    }
+    if env::var("VTE_VERSION")
+        .ok()
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5000)
+    {
+        return true;
+    }
+    if ["KITTY_WINDOW_ID", "WT_SESSION", "KONSOLE_VERSION"]
+        .iter()
+        .any(|name| env::var_os(name).is_some())
+    {
+        return true;
+    }
+    env::var("TERM").is_ok_and(|term| {
+        ["kitty", "foot", "alacritty", "wezterm", "ghostty"]
+            .iter()
+            .any(|name| term.contains(name))
+    })
+}
+
 /// Convert RGB color to nearest 256-color palette index
 fn rgb_to_256color(r: u8, g: u8, b: u8) -> u8 {
     let avg = (r as u16 + g as u16 + b as u16) / 3;
@@ -87,6 +134,24 @@ pub fn smart_color(rgb: u32) -> Color {
     }
 }
 
+/// Remove all colors from a drawn buffer, keeping highlighted cells visible through
+/// reverse video and dimmed text through the DIM attribute
+pub fn strip_colors(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.skip {
+            continue;
+        }
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        if cell.fg == Color::DarkGray {
+            cell.modifier.insert(Modifier::DIM);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,4 +191,25 @@ mod tests {
         let idx = rgb_to_256color(128, 128, 128);
         assert!(idx >= 16); // Should be in valid range
     }
+
+    #[test]
+    fn test_strip_colors_keeps_highlights_visible() {
+        use ratatui::layout::Rect;
+        use ratatui::style::Style;
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "a", Style::default().fg(Color::Red));
+        buffer.set_string(1, 0, "b", Style::default().fg(Color::White).bg(Color::Blue));
+        buffer.set_string(2, 0, "c", Style::default().fg(Color::DarkGray));
+
+        strip_colors(&mut buffer);
+
+        for cell in buffer.content.iter() {
+            assert_eq!(cell.fg, Color::Reset);
+            assert_eq!(cell.bg, Color::Reset);
+        }
+        assert!(buffer.content[0].modifier.is_empty());
+        assert!(buffer.content[1].modifier.contains(Modifier::REVERSED));
+        assert!(buffer.content[2].modifier.contains(Modifier::DIM));
+    }
 }
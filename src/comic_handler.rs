@@ -0,0 +1,298 @@
+use crate::epub_builder::{Chapter, build_epub};
+use anyhow::{Context, Result, bail};
+use epub::doc::EpubDoc;
+use log::{info, warn};
+use std::cmp::Ordering;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::Command;
+
+/// Archive entries with these extensions are pages; anything else (ComicInfo.xml, scan
+/// credits as text) is left out
+const PAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "bmp"];
+
+/// Whether `path` names a comic book archive: `.cbz` (ZIP) or `.cbr` (RAR)
+pub fn is_comic_file(path: &str) -> bool {
+    matches!(extension(path).as_deref(), Some("cbz" | "cbr"))
+}
+
+fn extension(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+fn is_page(name: &str) -> bool {
+    // Finder metadata and other hidden files carry image extensions too
+    let hidden = name
+        .split(['/', '\\'])
+        .any(|part| part.starts_with('.') || part == "__MACOSX");
+    !hidden && extension(name).is_some_and(|ext| PAGE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Compare page names the way people number them: `page2` before `page10`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits.trim_start_matches('0').to_string()
+                };
+                let (x, y) = (number(&mut a), number(&mut b));
+                let order = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let order = x.to_lowercase().cmp(y.to_lowercase());
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+enum ArchiveKind {
+    Zip,
+    Rar,
+}
+
+/// Told apart by their contents, as plenty of `.cbr` files are ZIP archives renamed
+fn archive_kind(path: &Path) -> Result<ArchiveKind> {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    match &magic {
+        b"PK\x03\x04" => Ok(ArchiveKind::Zip),
+        b"Rar!" => Ok(ArchiveKind::Rar),
+        _ => bail!("{} is neither a ZIP nor a RAR archive", path.display()),
+    }
+}
+
+/// Run the first of `commands` that is installed, returning what it printed
+fn run_first_installed(commands: &[(&str, Vec<OsString>)]) -> Result<Vec<u8>> {
+    for (program, args) in commands {
+        match Command::new(program).args(args).output() {
+            Ok(output) if output.status.success() => return Ok(output.stdout),
+            Ok(output) => bail!(
+                "{program} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to run {program}")),
+        }
+    }
+    bail!("Reading CBR comics needs unrar or bsdtar installed")
+}
+
+fn rar_list(path: &Path) -> Result<Vec<String>> {
+    let output = run_first_installed(&[
+        ("unrar", vec!["lb".into(), "-p-".into(), path.into()]),
+        ("bsdtar", vec!["-tf".into(), path.into()]),
+    ])?;
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+fn rar_extract(path: &Path, dir: &Path) -> Result<()> {
+    // unrar takes the directory to extract to with a trailing separator
+    let mut unrar_dir = dir.as_os_str().to_owned();
+    unrar_dir.push("/");
+    run_first_installed(&[
+        (
+            "unrar",
+            vec![
+                "x".into(),
+                "-o+".into(),
+                "-p-".into(),
+                "-inul".into(),
+                path.into(),
+                unrar_dir,
+            ],
+        ),
+        (
+            "bsdtar",
+            vec!["-xf".into(), path.into(), "-C".into(), dir.into()],
+        ),
+    ])
+    .map(|_| ())
+}
+
+/// The page images of a comic book archive, in reading order
+pub fn list_pages(path: &Path) -> Result<Vec<String>> {
+    let mut names = match archive_kind(path)? {
+        ArchiveKind::Zip => {
+            let archive = zip::ZipArchive::new(File::open(path)?)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            archive.file_names().map(str::to_string).collect()
+        }
+        ArchiveKind::Rar => rar_list(path)?,
+    };
+    names.retain(|name| is_page(name));
+    names.sort_by(|a, b| natural_cmp(a, b));
+    Ok(names)
+}
+
+/// Where page `index` (from 0), stored in the archive as `name`, is found in the book
+/// made of a comic
+pub fn page_href(index: usize, name: &str) -> String {
+    format!(
+        "pages/{:04}.{}",
+        index + 1,
+        extension(name).unwrap_or_default()
+    )
+}
+
+/// Write the pages of the comic at `path` into `dir` under their `page_href`s, for the
+/// reader's image storage. Returns how many there were.
+pub fn extract_pages(path: &Path, dir: &Path) -> Result<usize> {
+    let pages = list_pages(path)?;
+    fs::create_dir_all(dir.join("pages"))
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    match archive_kind(path)? {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            for (index, name) in pages.iter().enumerate() {
+                let mut data = Vec::new();
+                archive
+                    .by_name(name)
+                    .with_context(|| format!("Failed to find page {name}"))?
+                    .read_to_end(&mut data)
+                    .with_context(|| format!("Failed to read page {name}"))?;
+                fs::write(dir.join(page_href(index, name)), data)?;
+            }
+        }
+        ArchiveKind::Rar => {
+            let unpacked = tempfile::tempdir()?;
+            rar_extract(path, unpacked.path())?;
+            for (index, name) in pages.iter().enumerate() {
+                if let Err(e) =
+                    fs::copy(unpacked.path().join(name), dir.join(page_href(index, name)))
+                {
+                    warn!("Failed to extract page {name}: {e}");
+                }
+            }
+        }
+    }
+    Ok(pages.len())
+}
+
+/// A book with one chapter per page of the comic at `path`, each just the page image, so
+/// `h`/`l` turn pages and the table of contents lists them. The images themselves are
+/// unpacked from the archive by the image storage (see `extract_pages`). The guide
+/// points at the first page, as a comic's cover isn't front matter to skip.
+pub fn create_comic_epub(path: &str) -> Result<EpubDoc<BufReader<File>>> {
+    let pages = list_pages(Path::new(path))?;
+    if pages.is_empty() {
+        bail!("No page images found in {path}");
+    }
+    info!("Comic {path} has {} pages", pages.len());
+
+    let chapters: Vec<Chapter> = pages
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let number = index + 1;
+            Chapter {
+                id: format!("page{number:04}"),
+                title: format!("Page {number}"),
+                body: format!(
+                    r#"<img src="{}" alt="Page {number}"/>"#,
+                    page_href(index, name)
+                ),
+            }
+        })
+        .collect();
+    let title = Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Comic");
+    build_epub(
+        title,
+        &format!("comic-{}", path.replace('/', "_")),
+        &chapters,
+    )
+    .with_context(|| format!("Failed to open the book made of {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::toc_parser::TocParser;
+    use std::io::Write;
+
+    fn write_cbz(path: &Path, names: &[&str]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for name in names {
+            zip.start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_pages_are_listed_in_order_and_become_chapters() {
+        let dir = tempfile::tempdir().unwrap();
+        // Misnamed `.cbr` files that are really ZIP archives open all the same
+        let path = dir.path().join("issue 1.cbr");
+        write_cbz(
+            &path,
+            &[
+                "Issue/p10.png",
+                "Issue/p2.PNG",
+                "Issue/p1.jpg",
+                "__MACOSX/Issue/._p1.jpg",
+                "ComicInfo.xml",
+            ],
+        );
+
+        assert!(is_comic_file(path.to_str().unwrap()));
+        assert_eq!(
+            list_pages(&path).unwrap(),
+            ["Issue/p1.jpg", "Issue/p2.PNG", "Issue/p10.png"]
+        );
+
+        let images = dir.path().join("images");
+        assert_eq!(extract_pages(&path, &images).unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(images.join("pages/0003.png")).unwrap(),
+            "Issue/p10.png"
+        );
+
+        let mut doc = create_comic_epub(path.to_str().unwrap()).unwrap();
+        assert_eq!(doc.get_num_chapters(), 3);
+        assert_eq!(doc.mdata("title").unwrap().value, "issue 1");
+        assert_eq!(TocParser::find_bodymatter(&mut doc), Some(0));
+        doc.set_current_chapter(1);
+        let (html, _) = doc.get_current_str().unwrap();
+        assert!(html.contains(r#"<img src="pages/0002.png" alt="Page 2"/>"#));
+    }
+
+    #[test]
+    fn test_natural_order() {
+        let mut names = vec!["page10", "Page9", "page009b", "page1", "cover"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["cover", "page1", "Page9", "page009b", "page10"]);
+    }
+}
@@ -0,0 +1,408 @@
+use crate::inputs::KeymapPreset;
+use crate::logging::{LogDirective, LogFormat, parse_directives};
+use crate::quote_capture::ReferenceFormat;
+use crate::widget::dock_panel::DockKind;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Commands that can be typed on the `:` command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Parse every chapter of the book in the background
+    PrefetchAll,
+    /// Copy a `bookokcat://` link to the current position
+    Link,
+    /// Switch to another keymap preset
+    Keymap(KeymapPreset),
+    /// Save the code block on screen to a file
+    WriteCode(String),
+    /// Append the code block on screen to a scratch file (the default one if `None`)
+    AppendCode(Option<String>),
+    /// Report markup each chapter lost in conversion
+    Doctor,
+    /// Check the book's zip, OPF, spine and internal links
+    Check,
+    /// Open another edition beside the reader, its chapters lined up with this book's
+    Compare(String),
+    /// Close the edition opened with `:compare`
+    CloseComparison,
+    /// Show or hide a panel docked under the reader
+    Panel(DockKind),
+    /// Show what changed between another chapter of this book (by number) and the
+    /// current one, block by block
+    DiffChapter(usize),
+    /// Show what changed in the current chapter since its counterpart in another edition
+    DiffEdition(String),
+    /// Show the current chapter's headings and most frequent terms
+    Summary,
+    /// Summarize the current chapter with the `summary_command` preference
+    Summarize,
+    /// Open the current chapter as Markdown in `$EDITOR`
+    Edit,
+    /// Open the current chapter as Markdown in `$PAGER`
+    Pager,
+    /// Change log levels (default and per module); show them when there are none
+    LogLevel(Vec<LogDirective>),
+    /// Write the log as text or JSON lines
+    LogFormat(LogFormat),
+    /// Show today's progress towards the daily reading goal
+    Goal,
+    /// Save our position and notes on the chapter for a reading group (to a default
+    /// file name if `None`)
+    ClubExport(Option<String>),
+    /// Show another reader's shared position and notes
+    ClubImport(String),
+    /// Hide all imported readers' notes
+    ClubClear,
+    /// Turn the book's notes into Anki cards (next to the book if no file is given)
+    Flashcards(Option<String>),
+    /// Copy a reference to the book, or to the selected passage, for a citation manager
+    Cite(ReferenceFormat),
+    /// Count a term's occurrences in each chapter
+    Count(String),
+    /// Show which names appear in which chapters
+    Entities,
+    /// List dated headings and epigraphs in date order
+    Timeline,
+    /// Highlight a term throughout the book; list the watched terms when there's none
+    Watch(Option<String>),
+    /// Stop highlighting a term, or all of them when there's none
+    Unwatch(Option<String>),
+    /// Turn on or off reloading the book whenever its file is saved
+    LiveReload,
+    /// Go to a chapter file by its href, and to an anchor in it; an empty href means the
+    /// current chapter
+    Goto {
+        href: String,
+        anchor: Option<String>,
+    },
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Command, String> {
+        let mut words = input.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("Empty command".to_string());
+        };
+        let args: Vec<&str> = words.collect();
+        // Everything after the command name, for arguments that may contain spaces
+        let rest = input.trim_start()[name.len()..].trim();
+
+        match (name, args.as_slice()) {
+            ("prefetch", ["all"]) => Ok(Command::PrefetchAll),
+            ("prefetch", _) => Err("Usage: :prefetch all".to_string()),
+            ("link", []) => Ok(Command::Link),
+            ("keymap", [preset]) => KeymapPreset::from_name(preset)
+                .map(Command::Keymap)
+                .ok_or_else(|| format!("Unknown keymap: {preset} (vim, emacs or arrows)")),
+            ("keymap", _) => Err("Usage: :keymap vim|emacs|arrows".to_string()),
+            ("write-code", []) => Err("Usage: :write-code <file>".to_string()),
+            ("write-code", _) => Ok(Command::WriteCode(rest.to_string())),
+            ("append-code", []) => Ok(Command::AppendCode(None)),
+            ("append-code", _) => Ok(Command::AppendCode(Some(rest.to_string()))),
+            ("doctor", []) => Ok(Command::Doctor),
+            ("check", []) => Ok(Command::Check),
+            ("summary", []) => Ok(Command::Summary),
+            ("summarize", []) => Ok(Command::Summarize),
+            ("edit", []) => Ok(Command::Edit),
+            ("pager", []) => Ok(Command::Pager),
+            ("compare", []) => Err("Usage: :compare <other edition.epub>|off".to_string()),
+            ("compare", ["off"]) => Ok(Command::CloseComparison),
+            ("compare", _) => Ok(Command::Compare(rest.to_string())),
+            ("panel", [name]) => DockKind::from_name(name)
+                .map(Command::Panel)
+                .ok_or_else(|| format!("Unknown panel: {name} (search, annotations or log)")),
+            ("panel", _) => Err("Usage: :panel search|annotations|log".to_string()),
+            ("diff", []) => Err("Usage: :diff <chapter number>|<other edition.epub>".to_string()),
+            ("diff", _) => match rest.parse::<usize>() {
+                Ok(0) => Err("Chapters are numbered from 1".to_string()),
+                Ok(chapter) => Ok(Command::DiffChapter(chapter)),
+                Err(_) => Ok(Command::DiffEdition(rest.to_string())),
+            },
+            ("loglevel", _) => parse_directives(rest).map(Command::LogLevel),
+            ("logformat", ["text"]) => Ok(Command::LogFormat(LogFormat::Text)),
+            ("logformat", ["json"]) => Ok(Command::LogFormat(LogFormat::Json)),
+            ("logformat", _) => Err("Usage: :logformat text|json".to_string()),
+            ("goal", []) => Ok(Command::Goal),
+            ("club", ["export"]) => Ok(Command::ClubExport(None)),
+            ("club", ["export", ..]) => Ok(Command::ClubExport(Some(
+                rest["export".len()..].trim().to_string(),
+            ))),
+            ("club", ["import", _, ..]) => Ok(Command::ClubImport(
+                rest["import".len()..].trim().to_string(),
+            )),
+            ("club", ["clear"]) => Ok(Command::ClubClear),
+            ("cite", [] | ["bibtex"]) => Ok(Command::Cite(ReferenceFormat::Bibtex)),
+            ("cite", ["csl"] | ["csl-json"]) => Ok(Command::Cite(ReferenceFormat::CslJson)),
+            ("cite", _) => Err("Usage: :cite [bibtex|csl]".to_string()),
+            ("count", []) => Err("Usage: :count <term>".to_string()),
+            ("count", _) => Ok(Command::Count(rest.to_string())),
+            ("entities", []) => Ok(Command::Entities),
+            ("timeline", []) => Ok(Command::Timeline),
+            ("watch", []) => Ok(Command::Watch(None)),
+            ("watch", _) => Ok(Command::Watch(Some(rest.to_string()))),
+            ("unwatch", []) => Ok(Command::Unwatch(None)),
+            ("unwatch", _) => Ok(Command::Unwatch(Some(rest.to_string()))),
+            ("live", []) => Ok(Command::LiveReload),
+            ("flashcards", []) => Ok(Command::Flashcards(None)),
+            ("flashcards", _) => Ok(Command::Flashcards(Some(rest.to_string()))),
+            ("goto", [target]) => {
+                let (href, anchor) = match target.split_once('#') {
+                    Some((href, anchor)) => (href, Some(anchor.to_string())),
+                    None => (*target, None),
+                };
+                if href.is_empty() && anchor.as_deref().is_none_or(str::is_empty) {
+                    return Err("Usage: :goto <href>[#anchor]".to_string());
+                }
+                Ok(Command::Goto {
+                    href: href.to_string(),
+                    anchor: anchor.filter(|anchor| !anchor.is_empty()),
+                })
+            }
+            ("goto", _) => Err("Usage: :goto <href>[#anchor]".to_string()),
+            ("club", _) => Err("Usage: :club export [file] | import <file> | clear".to_string()),
+            _ => Err(format!("Unknown command: {name}")),
+        }
+    }
+}
+
+pub enum CommandLineAction {
+    Execute(String),
+    Cancel,
+    /// Tab was pressed; the caller should offer completions for the last word
+    Complete,
+}
+
+/// Completions being cycled through with Tab
+struct Completion {
+    /// The input before the completed word
+    stem: String,
+    candidates: Vec<String>,
+    current: usize,
+}
+
+/// The `:` prompt shown in the help bar
+#[derive(Default)]
+pub struct CommandLine {
+    input: String,
+    completion: Option<Completion>,
+}
+
+impl CommandLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A prompt with the input already filled in, ready to be edited
+    pub fn with_input(input: &str) -> Self {
+        let mut command_line = Self::new();
+        command_line.insert_str(input);
+        command_line
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        self.input
+            .extend(text.chars().map(|c| if c.is_control() { ' ' } else { c }));
+    }
+
+    /// Complete the last word of the input with `candidates`: fill in what they all
+    /// start with, then cycle through them on each further Tab
+    pub fn complete(&mut self, candidates: Vec<String>) {
+        let stem_len = self.input.rfind(' ').map_or(0, |space| space + 1);
+        let (stem, word) = self.input.split_at(stem_len);
+        let Some(first) = candidates.first() else {
+            return;
+        };
+        let common = candidates.iter().fold(first.as_str(), |common, candidate| {
+            let len = common
+                .char_indices()
+                .zip(candidate.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(candidate.len()), |((i, _), _)| i);
+            &common[..len]
+        });
+        if candidates.len() == 1 || common.len() > word.len() {
+            self.input = format!("{stem}{common}");
+            return;
+        }
+        let stem = stem.to_string();
+        self.input = format!("{stem}{first}");
+        self.completion = Some(Completion {
+            stem,
+            candidates,
+            current: 0,
+        });
+    }
+
+    /// Which of the completions is showing, like `[2/7]`
+    pub fn completion_info(&self) -> Option<String> {
+        self.completion.as_ref().map(|completion| {
+            format!(
+                "[{}/{}]",
+                completion.current + 1,
+                completion.candidates.len()
+            )
+        })
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<CommandLineAction> {
+        if key.code == KeyCode::Tab {
+            let Some(completion) = &mut self.completion else {
+                return Some(CommandLineAction::Complete);
+            };
+            completion.current = (completion.current + 1) % completion.candidates.len();
+            self.input = format!(
+                "{}{}",
+                completion.stem, completion.candidates[completion.current]
+            );
+            return None;
+        }
+        self.completion = None;
+        match key.code {
+            KeyCode::Esc => return Some(CommandLineAction::Cancel),
+            KeyCode::Enter => {
+                return Some(CommandLineAction::Execute(self.input.trim().to_string()));
+            }
+            // Deleting past the start closes the prompt, like in vim
+            KeyCode::Backspace if self.input.is_empty() => {
+                return Some(CommandLineAction::Cancel);
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.push(c);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prefetch() {
+        assert_eq!(Command::parse("  prefetch  all "), Ok(Command::PrefetchAll));
+        assert!(Command::parse("prefetch").is_err());
+        assert!(Command::parse("prefetch some").is_err());
+        assert!(Command::parse("frobnicate").is_err());
+        assert_eq!(
+            Command::parse("keymap emacs"),
+            Ok(Command::Keymap(KeymapPreset::Emacs))
+        );
+        assert!(Command::parse("keymap nano").is_err());
+        assert_eq!(
+            Command::parse("write-code  my snippets/main.rs "),
+            Ok(Command::WriteCode("my snippets/main.rs".to_string()))
+        );
+        assert_eq!(Command::parse("append-code"), Ok(Command::AppendCode(None)));
+        assert_eq!(Command::parse("doctor"), Ok(Command::Doctor));
+        assert_eq!(Command::parse("check"), Ok(Command::Check));
+        assert_eq!(
+            Command::parse("compare books/second edition.epub"),
+            Ok(Command::Compare("books/second edition.epub".to_string()))
+        );
+        assert!(Command::parse("compare").is_err());
+        assert_eq!(Command::parse("compare off"), Ok(Command::CloseComparison));
+        assert_eq!(
+            Command::parse("panel log"),
+            Ok(Command::Panel(DockKind::Log))
+        );
+        assert!(Command::parse("panel").is_err());
+        assert!(Command::parse("panel bookmarks").is_err());
+        assert_eq!(Command::parse("diff 12"), Ok(Command::DiffChapter(12)));
+        assert_eq!(
+            Command::parse("diff drafts/v2.epub"),
+            Ok(Command::DiffEdition("drafts/v2.epub".to_string()))
+        );
+        assert!(Command::parse("diff 0").is_err());
+        assert!(Command::parse("diff").is_err());
+        assert_eq!(Command::parse("live"), Ok(Command::LiveReload));
+        assert_eq!(Command::parse("summary"), Ok(Command::Summary));
+        assert_eq!(Command::parse("summarize"), Ok(Command::Summarize));
+        assert_eq!(Command::parse("goal"), Ok(Command::Goal));
+        assert_eq!(Command::parse("club export"), Ok(Command::ClubExport(None)));
+        assert_eq!(
+            Command::parse("club import ch3 alice.club.yaml"),
+            Ok(Command::ClubImport("ch3 alice.club.yaml".to_string()))
+        );
+        assert!(Command::parse("club import").is_err());
+        assert_eq!(
+            Command::parse("cite csl"),
+            Ok(Command::Cite(ReferenceFormat::CslJson))
+        );
+        assert_eq!(
+            Command::parse("watch Captain Ahab"),
+            Ok(Command::Watch(Some("Captain Ahab".to_string())))
+        );
+        assert_eq!(Command::parse("unwatch"), Ok(Command::Unwatch(None)));
+        assert_eq!(Command::parse("entities"), Ok(Command::Entities));
+        assert_eq!(
+            Command::parse("count white whale"),
+            Ok(Command::Count("white whale".to_string()))
+        );
+        assert!(Command::parse("count").is_err());
+        assert_eq!(Command::parse("timeline"), Ok(Command::Timeline));
+        assert_eq!(
+            Command::parse("flashcards cards.tsv"),
+            Ok(Command::Flashcards(Some("cards.tsv".to_string())))
+        );
+        assert_eq!(Command::parse("pager"), Ok(Command::Pager));
+        assert_eq!(
+            Command::parse("loglevel parsing=trace"),
+            Ok(Command::LogLevel(vec![LogDirective {
+                target: "parsing".to_string(),
+                level: log::LevelFilter::Trace,
+            }]))
+        );
+        assert_eq!(Command::parse("loglevel"), Ok(Command::LogLevel(vec![])));
+        assert_eq!(
+            Command::parse("logformat json"),
+            Ok(Command::LogFormat(LogFormat::Json))
+        );
+        assert_eq!(
+            Command::parse("goto Text/ch03.xhtml#sec2"),
+            Ok(Command::Goto {
+                href: "Text/ch03.xhtml".to_string(),
+                anchor: Some("sec2".to_string()),
+            })
+        );
+        assert_eq!(
+            Command::parse("goto #notes"),
+            Ok(Command::Goto {
+                href: String::new(),
+                anchor: Some("notes".to_string()),
+            })
+        );
+        assert!(Command::parse("goto").is_err());
+        assert!(Command::parse("goto #").is_err());
+    }
+
+    #[test]
+    fn test_tab_completes_then_cycles() {
+        let tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+        let mut command_line = CommandLine::with_input("goto Text/c");
+        assert!(matches!(
+            command_line.handle_key(tab),
+            Some(CommandLineAction::Complete)
+        ));
+        let candidates = vec!["Text/ch01.xhtml".to_string(), "Text/ch02.xhtml".to_string()];
+        command_line.complete(candidates.clone());
+        assert_eq!(command_line.input(), "goto Text/ch0");
+
+        command_line.complete(candidates);
+        assert_eq!(command_line.input(), "goto Text/ch01.xhtml");
+        assert_eq!(command_line.completion_info().as_deref(), Some("[1/2]"));
+        assert!(command_line.handle_key(tab).is_none());
+        assert_eq!(command_line.input(), "goto Text/ch02.xhtml");
+
+        command_line.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        assert_eq!(command_line.completion_info(), None);
+    }
+}
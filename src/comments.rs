@@ -1,3 +1,4 @@
+use crate::markdown::Document;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,12 @@ pub struct Comment {
     pub word_range: Option<(usize, usize)>,
     pub content: String,
     pub updated_at: DateTime<Utc>,
+    /// Id of the element the paragraph was made from, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// Characters other than whitespace in the chapter before the paragraph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_offset: Option<usize>,
 }
 
 impl Comment {
@@ -27,6 +34,96 @@ impl Comment {
     }
 }
 
+/// Where the blocks of a chapter are, by element id and by text offset. Comments keep
+/// both, so they find their paragraph again when the chapter's blocks are numbered
+/// differently (by another version of the converter, or of the book).
+pub struct ChapterAnchors {
+    ids: Vec<Option<String>>,
+    /// Characters other than whitespace before each block
+    offsets: Vec<usize>,
+    /// Text offset of each word, to carry word ranges over when blocks are joined
+    word_starts: Vec<usize>,
+}
+
+impl ChapterAnchors {
+    pub fn new(doc: &Document) -> Self {
+        let mut offset = 0;
+        let mut offsets = Vec::with_capacity(doc.blocks.len());
+        let mut word_starts = Vec::new();
+        for node in &doc.blocks {
+            offsets.push(offset);
+            for word in node.plain_text().split_whitespace() {
+                word_starts.push(offset);
+                offset += word.chars().count();
+            }
+        }
+        Self {
+            ids: doc.blocks.iter().map(|node| node.id.clone()).collect(),
+            offsets,
+            word_starts,
+        }
+    }
+
+    /// The element id and text offset of block `index`, for a comment on it
+    pub fn locate(&self, index: usize) -> (Option<String>, Option<usize>) {
+        (
+            self.ids.get(index).cloned().flatten(),
+            self.offsets.get(index).copied(),
+        )
+    }
+
+    /// The block `comment` is on: the one with its element id, else the one its text
+    /// offset falls in, else the one at its index. Ids survive edits to the text; offsets
+    /// survive blocks being split, joined or numbered differently.
+    pub fn resolve(&self, comment: &Comment) -> usize {
+        if let Some(index) = comment
+            .anchor
+            .as_ref()
+            .and_then(|anchor| self.ids.iter().position(|id| id.as_ref() == Some(anchor)))
+        {
+            return index;
+        }
+        let Some(offset) = comment.text_offset else {
+            return comment.paragraph_index;
+        };
+        let covers = |index: usize| {
+            self.offsets.get(index).is_some_and(|&start| {
+                start == offset
+                    || (start < offset
+                        && self.offsets.get(index + 1).is_none_or(|&end| offset < end))
+            })
+        };
+        if covers(comment.paragraph_index) {
+            return comment.paragraph_index;
+        }
+        // Blocks without text (images) start where the next one does; the last of them is
+        // the one with text
+        match self.offsets.partition_point(|&start| start <= offset) {
+            0 => comment.paragraph_index,
+            after => after - 1,
+        }
+    }
+
+    /// `comment`'s word range counted from the start of block `index`, when its paragraph
+    /// was joined onto that block. `None` when the block doesn't hold the paragraph's start.
+    fn shift_word_range(&self, comment: &Comment, index: usize) -> Option<(usize, usize)> {
+        let (start, end) = comment.word_range?;
+        let old_start = comment.text_offset?;
+        let new_start = *self.offsets.get(index)?;
+        if old_start < new_start
+            || self
+                .offsets
+                .get(index + 1)
+                .is_some_and(|&next| old_start >= next)
+        {
+            return None;
+        }
+        let words_before = |offset: usize| self.word_starts.partition_point(|&word| word < offset);
+        let shift = words_before(old_start) - words_before(new_start);
+        Some((start + shift, end + shift))
+    }
+}
+
 pub struct BookComments {
     pub file_path: PathBuf,
     comments: Vec<Comment>,
@@ -111,16 +208,17 @@ impl BookComments {
         chapter_href: &str,
         paragraph_index: usize,
         word_range: Option<(usize, usize)>,
-    ) -> Result<()> {
+    ) -> Result<Comment> {
         let idx = self
             .find_comment_index(chapter_href, paragraph_index, word_range)
             .context("Comment not found")?;
 
-        let _comment = self.comments.remove(idx);
+        let comment = self.comments.remove(idx);
 
         self.rebuild_indices();
 
-        self.save_to_disk()
+        self.save_to_disk()?;
+        Ok(comment)
     }
 
     /// Efficiently get comments for a specific paragraph in a chapter
@@ -152,6 +250,42 @@ impl BookComments {
         &self.comments
     }
 
+    /// Move the chapter's comments to the paragraphs `anchors` finds them on, unless another
+    /// comment is already there. Word ranges follow their words into a joined paragraph;
+    /// otherwise a moved comment is on the whole paragraph. Returns whether any moved.
+    pub fn reanchor_chapter(
+        &mut self,
+        chapter_href: &str,
+        anchors: &ChapterAnchors,
+    ) -> Result<bool> {
+        let mut moved = false;
+        for idx in 0..self.comments.len() {
+            let comment = &self.comments[idx];
+            if comment.chapter_href != chapter_href {
+                continue;
+            }
+            let paragraph_index = anchors.resolve(comment);
+            if paragraph_index == comment.paragraph_index {
+                continue;
+            }
+            let word_range = anchors.shift_word_range(comment, paragraph_index);
+            if self
+                .find_comment_index(chapter_href, paragraph_index, word_range)
+                .is_some()
+            {
+                continue;
+            }
+            self.comments[idx].paragraph_index = paragraph_index;
+            self.comments[idx].word_range = word_range;
+            moved = true;
+        }
+        if moved {
+            self.sort_comments();
+            self.save_to_disk()?;
+        }
+        Ok(moved)
+    }
+
     fn compute_book_hash(book_path: &Path) -> String {
         let filename = book_path
             .file_name()
@@ -166,9 +300,7 @@ impl BookComments {
     }
 
     fn get_comments_dir() -> Result<PathBuf> {
-        let comments_dir = std::env::current_dir()
-            .context("Could not determine current directory")?
-            .join(".bookokcat_comments");
+        let comments_dir = crate::app_dirs::comments_dir()?;
 
         if !comments_dir.exists() {
             fs::create_dir_all(&comments_dir).context("Failed to create comments directory")?;
@@ -263,6 +395,8 @@ mod tests {
             word_range: None,
             content: content.to_string(),
             updated_at: Utc::now(),
+            anchor: None,
+            text_offset: None,
         }
     }
 
@@ -443,4 +577,91 @@ mod tests {
         assert_eq!(comments.get_all_comments().len(), 1);
         assert_eq!(comments.get_all_comments()[0].content, "Second version");
     }
+
+    #[test]
+    fn test_comments_find_their_paragraph_when_blocks_are_renumbered() {
+        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut comments = BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        let before = HtmlToMarkdownConverter::new()
+            .convert(r#"<p>Intro.</p><p id="x">Anchored.</p><p>Second line.</p><p>Last.</p>"#);
+        let anchors = ChapterAnchors::new(&before);
+        for (para, content) in [(1, "On the anchor"), (3, "On the last")] {
+            let (anchor, text_offset) = anchors.locate(para);
+            comments
+                .add_comment(Comment {
+                    anchor,
+                    text_offset,
+                    ..create_test_comment("chapter1.xhtml", para, content)
+                })
+                .unwrap();
+        }
+        let (anchor, text_offset) = anchors.locate(3);
+        comments
+            .add_comment(Comment {
+                anchor,
+                text_offset,
+                word_range: Some((0, 1)),
+                ..create_test_comment("chapter1.xhtml", 3, "On a word of the last")
+            })
+            .unwrap();
+        comments
+            .add_comment(create_test_comment(
+                "chapter1.xhtml",
+                0,
+                "Without an anchor",
+            ))
+            .unwrap();
+        assert!(
+            !comments
+                .reanchor_chapter("chapter1.xhtml", &anchors)
+                .unwrap()
+        );
+
+        // Two paragraphs joined and one added: the comment on the joined one follows its
+        // text, not its old index
+        let after = HtmlToMarkdownConverter::new().convert(
+            r#"<p>Intro.</p><p id="x">Anchored.</p><p>Second line.<br/>Last.</p><p>Coda.</p>"#,
+        );
+        assert!(
+            comments
+                .reanchor_chapter("chapter1.xhtml", &ChapterAnchors::new(&after))
+                .unwrap()
+        );
+        let paragraph_of = |content: &str| {
+            comments
+                .get_all_comments()
+                .iter()
+                .find(|comment| comment.content == content)
+                .unwrap()
+                .paragraph_index
+        };
+        assert_eq!(paragraph_of("On the anchor"), 1);
+        assert_eq!(paragraph_of("On the last"), 2);
+        assert_eq!(paragraph_of("Without an anchor"), 0);
+        // The word comment keeps pointing at "Last.", now the third word of its paragraph
+        let on_word = comments
+            .get_all_comments()
+            .iter()
+            .find(|comment| comment.content == "On a word of the last")
+            .unwrap();
+        assert_eq!(
+            (on_word.paragraph_index, on_word.word_range),
+            (2, Some((2, 3)))
+        );
+
+        let reloaded = BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        assert_eq!(
+            reloaded.get_paragraph_comments("chapter1.xhtml", 2).len(),
+            2
+        );
+
+        // Ids win over offsets, which text added before the paragraph throws off
+        let revised = HtmlToMarkdownConverter::new()
+            .convert(r#"<p>A new preface.</p><p>Intro.</p><p id="x">Anchored.</p>"#);
+        let on_anchor = reloaded.get_paragraph_comments("chapter1.xhtml", 1)[0];
+        assert_eq!(ChapterAnchors::new(&revised).resolve(on_anchor), 2);
+    }
 }
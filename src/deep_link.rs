@@ -0,0 +1,116 @@
+use std::fmt;
+
+const SCHEME: &str = "bookokcat://book";
+
+/// A position in a book, shareable as `bookokcat://book?path=...&href=...&anchor=...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepLink {
+    pub path: String,
+    pub href: String,
+    pub anchor: Option<String>,
+}
+
+impl DeepLink {
+    pub fn is_deep_link(text: &str) -> bool {
+        text.starts_with("bookokcat://")
+    }
+
+    pub fn parse(text: &str) -> Result<DeepLink, String> {
+        let query = text
+            .trim()
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.strip_prefix('?'))
+            .ok_or_else(|| format!("Not a bookokcat link: {text}"))?;
+
+        let mut path = None;
+        let mut href = None;
+        let mut anchor = None;
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = decode(value)?;
+            match key {
+                "path" => path = Some(value),
+                "href" => href = Some(value),
+                "anchor" => anchor = Some(value).filter(|anchor| !anchor.is_empty()),
+                _ => {}
+            }
+        }
+
+        Ok(DeepLink {
+            path: path.ok_or("Link is missing the book path")?,
+            href: href.ok_or("Link is missing the chapter href")?,
+            anchor,
+        })
+    }
+}
+
+impl fmt::Display for DeepLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{SCHEME}?path={}&href={}",
+            encode(&self.path),
+            encode(&self.href)
+        )?;
+        if let Some(anchor) = &self.anchor {
+            write!(f, "&anchor={}", encode(anchor))?;
+        }
+        Ok(())
+    }
+}
+
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~' | b'/') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let hex: Vec<u8> = input.by_ref().take(2).collect();
+                let digit = std::str::from_utf8(&hex)
+                    .ok()
+                    .filter(|digit| digit.len() == 2)
+                    .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+                    .ok_or_else(|| format!("Invalid escape in link: {value}"))?;
+                bytes.push(digit);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| format!("Link is not valid UTF-8: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deep_link_round_trip() {
+        let link = DeepLink {
+            path: "./Rust in Action & More.epub".to_string(),
+            href: "OEBPS/ch02.xhtml".to_string(),
+            anchor: Some("sec-2.1".to_string()),
+        };
+        let text = link.to_string();
+        assert_eq!(
+            text,
+            "bookokcat://book?path=./Rust%20in%20Action%20%26%20More.epub&href=OEBPS/ch02.xhtml&anchor=sec-2.1"
+        );
+        assert_eq!(DeepLink::parse(&text), Ok(link));
+
+        assert!(DeepLink::parse("bookokcat://book?href=ch1.xhtml").is_err());
+        assert!(DeepLink::parse("https://example.com").is_err());
+    }
+}
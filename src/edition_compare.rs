@@ -0,0 +1,227 @@
+use crate::parsing::text_generator::TextGenerator;
+use epub::doc::EpubDoc;
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+
+/// Chapters scoring less than this (the mean of how alike their headings and their text
+/// are) aren't the same chapter. Headings alone can't tell "Chapter 1" from "Chapter 2".
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// A chapter of one edition and its counterpart in the other, if there is one. Chapters
+/// are `(spine index, heading)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChapterPair {
+    pub left: Option<(usize, String)>,
+    pub right: Option<(usize, String)>,
+}
+
+/// A chapter of an edition, with what it's matched by
+#[derive(Debug, Clone)]
+pub struct EditionChapter {
+    /// Spine index
+    pub index: usize,
+    pub heading: String,
+    heading_words: HashSet<String>,
+    /// Consecutive words of its text, two by two: versions of a chapter share most of
+    /// them, different chapters few
+    word_pairs: HashSet<String>,
+}
+
+impl EditionChapter {
+    pub fn new(index: usize, heading: String, text: &str) -> Self {
+        let text_words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        EditionChapter {
+            index,
+            heading_words: words(&heading),
+            heading,
+            word_pairs: text_words
+                .windows(2)
+                .map(|pair| format!("{} {}", pair[0], pair[1]))
+                .collect(),
+        }
+    }
+
+    fn label(&self) -> (usize, String) {
+        (self.index, self.heading.clone())
+    }
+
+    /// How likely `other` is the same chapter, from 0 to 1
+    fn similarity(&self, other: &EditionChapter) -> f64 {
+        (similarity(&self.heading_words, &other.heading_words)
+            + similarity(&self.word_pairs, &other.word_pairs))
+            / 2.0
+    }
+}
+
+/// The chapters of a book in spine order. Chapters without a heading, such as covers and
+/// title pages, are left out.
+pub fn edition_chapters<R: Read + Seek>(doc: &mut EpubDoc<R>) -> Vec<EditionChapter> {
+    let original_chapter = doc.get_current_chapter();
+    let mut chapters = Vec::new();
+    for chapter_index in 0..doc.get_num_chapters() {
+        if !doc.set_current_chapter(chapter_index) {
+            continue;
+        }
+        let Some((raw_html, _)) = doc.get_current_str() else {
+            continue;
+        };
+        if let Some(title) = TextGenerator::extract_chapter_title(&raw_html) {
+            let body = raw_html
+                .find("<body")
+                .map_or(&raw_html[..], |start| &raw_html[start..]);
+            let text = TextGenerator::extract_text_from_html(body);
+            chapters.push(EditionChapter::new(chapter_index, title, &text));
+        }
+    }
+    doc.set_current_chapter(original_chapter);
+    chapters
+}
+
+fn words(heading: &str) -> HashSet<String> {
+    heading
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How alike two sets of words are, from 0 (nothing in common) to 1 (the same)
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+/// Pair up the chapters of two editions, keeping both in reading order and matching the
+/// most similar chapters overall. Unmatched chapters exist in one edition only.
+pub fn match_chapters(left: &[EditionChapter], right: &[EditionChapter]) -> Vec<ChapterPair> {
+    let scores: Vec<Vec<Option<f64>>> = left
+        .iter()
+        .map(|a| {
+            right
+                .iter()
+                .map(|b| {
+                    let score = a.similarity(b);
+                    (score >= MIN_SIMILARITY).then_some(score)
+                })
+                .collect()
+        })
+        .collect();
+    let score = |i: usize, j: usize| scores[i][j];
+
+    // best[i][j]: the highest total similarity aligning the first i and j chapters
+    let mut best = vec![vec![0.0_f64; right.len() + 1]; left.len() + 1];
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let mut value = best[i - 1][j].max(best[i][j - 1]);
+            if let Some(score) = score(i - 1, j - 1) {
+                value = value.max(best[i - 1][j - 1] + score);
+            }
+            best[i][j] = value;
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (left.len(), right.len());
+    while i > 0 || j > 0 {
+        let matched = i > 0
+            && j > 0
+            && score(i - 1, j - 1).is_some_and(|score| best[i][j] == best[i - 1][j - 1] + score);
+        if matched {
+            pairs.push(ChapterPair {
+                left: Some(left[i - 1].label()),
+                right: Some(right[j - 1].label()),
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && (j == 0 || best[i][j] == best[i - 1][j]) {
+            pairs.push(ChapterPair {
+                left: Some(left[i - 1].label()),
+                right: None,
+            });
+            i -= 1;
+        } else {
+            pairs.push(ChapterPair {
+                left: None,
+                right: Some(right[j - 1].label()),
+            });
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORM: &str =
+        "The wind rose before dawn and the mast gave way under the weight of the sails.";
+    const LANDFALL: &str = "At noon the lookout saw the reef, and beyond it a strip of white sand.";
+
+    fn chapters(chapters: &[(&str, &str)]) -> Vec<EditionChapter> {
+        chapters
+            .iter()
+            .enumerate()
+            .map(|(idx, (heading, text))| EditionChapter::new(idx, heading.to_string(), text))
+            .collect()
+    }
+
+    fn indices(pairs: Vec<ChapterPair>) -> Vec<(Option<usize>, Option<usize>)> {
+        pairs
+            .into_iter()
+            .map(|pair| (pair.left.map(|c| c.0), pair.right.map(|c| c.0)))
+            .collect()
+    }
+
+    #[test]
+    fn test_chapters_match_by_heading_in_order() {
+        let first = chapters(&[
+            ("Preface", "Why this book was written."),
+            ("Chapter 1: The Storm", STORM),
+            ("Chapter 2: Landfall", LANDFALL),
+        ]);
+        let second = chapters(&[
+            ("1. The Storm", STORM),
+            ("Interlude", "A letter home, never sent."),
+            ("2. Landfall", LANDFALL),
+            (
+                "Afterword to the new edition",
+                "Thanks to the readers of the first.",
+            ),
+        ]);
+        assert_eq!(
+            indices(match_chapters(&first, &second)),
+            [
+                (Some(0), None),
+                (Some(1), Some(0)),
+                (None, Some(1)),
+                (Some(2), Some(2)),
+                (None, Some(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbered_headings_match_by_their_text() {
+        // "Chapter 1" and "Chapter 2" share half their words, but not their text
+        let first = chapters(&[("Chapter 1", STORM), ("Chapter 2", LANDFALL)]);
+        let second = chapters(&[("Chapter 2", STORM), ("Chapter 3", LANDFALL)]);
+        assert_eq!(
+            indices(match_chapters(&first, &second)),
+            [(Some(0), Some(0)), (Some(1), Some(1))]
+        );
+
+        let revised = chapters(&[("Chapter 2", "A chapter written for this edition alone.")]);
+        assert_eq!(
+            indices(match_chapters(&first[..1], &revised)),
+            [(None, Some(0)), (Some(0), None)]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::chapter_summary::STOPWORDS;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Names mentioned fewer times than this are left out of the index
+const MIN_MENTIONS: usize = 3;
+
+/// Two-letter words that start sentences but are never names
+const SHORT_WORDS: &[&str] = &[
+    "ah", "an", "as", "at", "be", "by", "do", "go", "he", "if", "in", "is", "it", "me", "my", "no",
+    "of", "oh", "ok", "on", "or", "so", "to", "up", "us", "we",
+];
+
+/// Abbreviated titles whose period doesn't end a sentence
+const TITLES: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "st", "mt", "prof", "capt", "col", "gen",
+];
+
+/// Where a name is mentioned: a chapter and a line of its plain text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mention {
+    pub chapter_index: usize,
+    pub line_number: usize,
+}
+
+/// A name and where it appears in the book
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub name: String,
+    pub mentions: usize,
+    /// Mentions per chapter (spine) index
+    pub chapters: BTreeMap<usize, usize>,
+    pub first: Mention,
+    pub last: Mention,
+}
+
+/// Index capitalized names (runs like "Captain Ahab" or "Queequeg") in the chapters'
+/// plain text lines, most mentioned first. A capitalized word only counts as a name if it
+/// shows up somewhere other than the start of a sentence, and isn't usually lowercase.
+pub fn index_entities<'a>(chapters: impl Iterator<Item = (usize, &'a [String])>) -> Vec<Entity> {
+    let mut found: HashMap<String, Entity> = HashMap::new();
+    let mut mid_sentence: HashSet<String> = HashSet::new();
+    let mut lowercase: HashMap<String, usize> = HashMap::new();
+
+    for (chapter_index, lines) in chapters {
+        for (line_number, line) in lines.iter().enumerate() {
+            let mention = Mention {
+                chapter_index,
+                line_number,
+            };
+            for (name, sentence_start) in names_in_line(line, &mut lowercase) {
+                if !sentence_start {
+                    mid_sentence.insert(name.clone());
+                }
+                let entity = found.entry(name.clone()).or_insert_with(|| Entity {
+                    name,
+                    mentions: 0,
+                    chapters: BTreeMap::new(),
+                    first: mention,
+                    last: mention,
+                });
+                entity.mentions += 1;
+                *entity.chapters.entry(chapter_index).or_default() += 1;
+                entity.last = mention;
+            }
+        }
+    }
+
+    let mut entities: Vec<Entity> = found
+        .into_values()
+        .filter(|entity| entity.mentions >= MIN_MENTIONS && mid_sentence.contains(&entity.name))
+        .filter(|entity| {
+            entity.name.contains(' ')
+                || lowercase
+                    .get(&entity.name.to_lowercase())
+                    .is_none_or(|count| *count < entity.mentions)
+        })
+        .collect();
+    entities.sort_by(|a, b| {
+        b.mentions
+            .cmp(&a.mentions)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entities
+}
+
+/// The capitalized runs in a line, each with whether it opens a sentence. Counts the
+/// line's lowercase words into `lowercase` on the way.
+fn names_in_line(line: &str, lowercase: &mut HashMap<String, usize>) -> Vec<(String, bool)> {
+    let mut names = Vec::new();
+    let mut run: Vec<&str> = Vec::new();
+    let mut run_starts_sentence = false;
+    let mut sentence_start = true;
+
+    let mut flush = |run: &mut Vec<&str>, starts_sentence: bool| {
+        // "The Pequod" at the start of a sentence is about the Pequod
+        let words = run.len();
+        while run.first().is_some_and(|word| is_function_word(word)) {
+            run.remove(0);
+        }
+        if !run.is_empty() {
+            names.push((run.join(" "), starts_sentence && words == 1));
+        }
+        run.clear();
+    };
+
+    for token in line.split_whitespace() {
+        let opens_quote = token.starts_with(['"', '“', '‘', '\'', '(', '—', '-']);
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+        let word = word
+            .strip_suffix("'s")
+            .or_else(|| word.strip_suffix("’s"))
+            .unwrap_or(word);
+        let word_end = token
+            .char_indices()
+            .rfind(|(_, c)| c.is_alphanumeric())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let trailing = &token[word_end..];
+        let is_title = TITLES.contains(&word.to_lowercase().as_str());
+        let ends_sentence = trailing.contains(['.', '!', '?', '…', ':']) && !is_title;
+        let breaks_run = !trailing.is_empty() && !trailing.starts_with(['\'', '’']) && !is_title;
+
+        if opens_quote {
+            flush(&mut run, run_starts_sentence);
+            sentence_start = true;
+        }
+        if is_capitalized(word) {
+            if run.is_empty() {
+                run_starts_sentence = sentence_start;
+            }
+            run.push(word);
+        } else {
+            flush(&mut run, run_starts_sentence);
+            if word.chars().next().is_some_and(char::is_lowercase) {
+                *lowercase.entry(word.to_string()).or_default() += 1;
+            }
+        }
+        if breaks_run {
+            flush(&mut run, run_starts_sentence);
+        }
+        sentence_start = ends_sentence || (sentence_start && word.is_empty());
+    }
+    flush(&mut run, run_starts_sentence);
+    names
+}
+
+/// `Ahab` or `Élodie`, but not `I`, `NASA` or `2nd`
+fn is_capitalized(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(char::is_uppercase)
+        && chars.clone().any(char::is_lowercase)
+        && chars.all(|c| c.is_alphabetic() || c == '-' || c == '\'' || c == '’')
+}
+
+fn is_function_word(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    STOPWORDS.contains(&lower.as_str()) || SHORT_WORDS.contains(&lower.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_are_indexed_per_chapter_with_first_and_last_mention() {
+        let chapter = |text: &str| text.lines().map(str::to_string).collect::<Vec<_>>();
+        let one = chapter(
+            "Call me Ishmael. Some years ago I went to sea.\n\
+             The Pequod sailed. Ishmael met Queequeg at the inn.\n\
+             Queequeg smoked. Then Ishmael slept.",
+        );
+        let two = chapter(
+            "Captain Ahab paced the deck of the Pequod.\n\
+             \"Where is Queequeg?\" asked Captain Ahab, and Mr. Starbuck frowned.\n\
+             Ahab had a whale to find; Captain Ahab would not rest, and the Pequod sailed.",
+        );
+
+        let entities = index_entities([(1, one.as_slice()), (2, two.as_slice())].into_iter());
+        let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Captain Ahab", "Ishmael", "Pequod", "Queequeg"]);
+
+        let queequeg = &entities[3];
+        assert_eq!(queequeg.chapters, BTreeMap::from([(1, 2), (2, 1)]));
+        assert_eq!(
+            queequeg.first,
+            Mention {
+                chapter_index: 1,
+                line_number: 1
+            }
+        );
+        assert_eq!(
+            queequeg.last,
+            Mention {
+                chapter_index: 2,
+                line_number: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use epub::doc::EpubDoc;
+use std::fs::File;
+use std::io::{BufReader, Write};
+
+/// A chapter of a book put together from something that isn't an EPUB
+pub struct Chapter {
+    /// Manifest id, also the chapter's file name: `<id>.xhtml`
+    pub id: String,
+    /// Plain text, for the table of contents and `<title>`
+    pub title: String,
+    /// XHTML to put inside `<body>`
+    pub body: String,
+}
+
+/// Write `chapters` into a temporary EPUB 2 and open it, so formats such as comics and
+/// plain text read like any other book. Each chapter is an entry in the table of
+/// contents, and the guide points at the first one so none of it is skipped as front
+/// matter.
+pub fn build_epub(
+    title: &str,
+    uid: &str,
+    chapters: &[Chapter],
+) -> Result<EpubDoc<BufReader<File>>> {
+    use zip::{ZipWriter, write::FileOptions};
+
+    let title = html_escape::encode_text(title).to_string();
+    let uid = html_escape::encode_double_quoted_attribute(uid).to_string();
+
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    let mut nav_points = String::new();
+    let temp_file = tempfile::NamedTempFile::new()?;
+    let mut zip = ZipWriter::new(File::create(temp_file.path())?);
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    zip.start_file("mimetype", options)?;
+    zip.write_all(b"application/epub+zip")?;
+    zip.start_file("META-INF/container.xml", options)?;
+    zip.write_all(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>"#,
+    )?;
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        let id = &chapter.id;
+        let number = index + 1;
+        let chapter_title = html_escape::encode_text(&chapter.title);
+        manifest.push_str(&format!(
+            "        <item id=\"{id}\" href=\"{id}.xhtml\" media-type=\"application/xhtml+xml\"/>\n"
+        ));
+        spine.push_str(&format!("        <itemref idref=\"{id}\"/>\n"));
+        nav_points.push_str(&format!(
+            "        <navPoint id=\"{id}\" playOrder=\"{number}\">\n            \
+             <navLabel><text>{chapter_title}</text></navLabel>\n            \
+             <content src=\"{id}.xhtml\"/>\n        </navPoint>\n"
+        ));
+
+        zip.start_file(format!("OEBPS/{id}.xhtml"), options)?;
+        zip.write_all(
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>{chapter_title}</title></head>
+<body>{}</body>
+</html>"#,
+                chapter.body
+            )
+            .as_bytes(),
+        )?;
+    }
+
+    let guide = chapters
+        .first()
+        .map(|first| {
+            format!(
+                "    <guide>\n        <reference type=\"text\" title=\"{}\" href=\"{}.xhtml\"/>\n    </guide>\n",
+                html_escape::encode_double_quoted_attribute(&first.title),
+                first.id
+            )
+        })
+        .unwrap_or_default();
+
+    zip.start_file("OEBPS/content.opf", options)?;
+    zip.write_all(
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" unique-identifier="bookid" version="2.0">
+    <metadata>
+        <dc:title xmlns:dc="http://purl.org/dc/elements/1.1/">{title}</dc:title>
+        <dc:identifier xmlns:dc="http://purl.org/dc/elements/1.1/" id="bookid">{uid}</dc:identifier>
+    </metadata>
+    <manifest>
+{manifest}        <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    </manifest>
+    <spine toc="ncx">
+{spine}    </spine>
+{guide}</package>"#
+        )
+        .as_bytes(),
+    )?;
+
+    zip.start_file("OEBPS/toc.ncx", options)?;
+    zip.write_all(
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+    <head>
+        <meta name="dtb:uid" content="{uid}"/>
+    </head>
+    <docTitle><text>{title}</text></docTitle>
+    <navMap>
+{nav_points}    </navMap>
+</ncx>"#
+        )
+        .as_bytes(),
+    )?;
+    zip.finish()?;
+
+    EpubDoc::new(temp_file.path()).context("Failed to open the book put together")
+}
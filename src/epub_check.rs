@@ -0,0 +1,465 @@
+use crate::markdown::{Block, Document, HeadingLevel, ListItem, ListKind, Node, Text, TextNode};
+use crate::parsing::toc_parser::resolve_relative;
+use epub::doc::EpubDoc;
+use html5ever::parse_document;
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// What part of the book a problem was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckCategory {
+    /// The zip container itself: unreadable entries, bad checksums, a missing `mimetype`
+    Archive,
+    /// Manifest items in the OPF pointing to files that aren't there
+    Manifest,
+    /// Spine items that are unknown or missing
+    Spine,
+    /// Internal links to files or anchors that don't exist
+    Links,
+}
+
+impl CheckCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckCategory::Archive => "Archive",
+            CheckCategory::Manifest => "Manifest",
+            CheckCategory::Spine => "Spine",
+            CheckCategory::Links => "Links",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckIssue {
+    pub category: CheckCategory,
+    /// The file (or entry) the problem is in
+    pub location: String,
+    pub message: String,
+}
+
+/// What `:check` and `bookokcat check` found in an EPUB
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub issues: Vec<CheckIssue>,
+    pub files: usize,
+    pub chapters: usize,
+    pub links: usize,
+}
+
+impl CheckReport {
+    fn add(&mut self, category: CheckCategory, location: &str, message: String) {
+        self.issues.push(CheckIssue {
+            category,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    pub fn summary(&self) -> String {
+        let problems = match self.issues.len() {
+            0 => "No problems".to_string(),
+            1 => "1 problem".to_string(),
+            n => format!("{n} problems"),
+        };
+        format!(
+            "{problems} in {} files, {} spine chapters and {} internal links.",
+            self.files, self.chapters, self.links
+        )
+    }
+
+    /// One line per problem, grouped by category, e.g. `Links  ch1.xhtml: ...`
+    pub fn lines(&self) -> Vec<String> {
+        let mut issues: Vec<_> = self.issues.iter().collect();
+        issues.sort_by_key(|issue| issue.category);
+        issues
+            .into_iter()
+            .map(|issue| {
+                format!(
+                    "{:<9}{}: {}",
+                    issue.category.label(),
+                    issue.location,
+                    issue.message
+                )
+            })
+            .collect()
+    }
+
+    /// The report as a virtual chapter: a summary, then a list of problems per category
+    pub fn to_document(&self) -> Document {
+        fn text(content: &str) -> Text {
+            let mut text = Text::default();
+            text.push_text(TextNode::new(content.to_string(), None));
+            text
+        }
+        fn block(block: Block) -> Node {
+            Node::new(block, 0..0)
+        }
+
+        let mut doc = Document::new();
+        doc.blocks.push(block(Block::Heading {
+            level: HeadingLevel::H1,
+            content: text("EPUB check"),
+        }));
+        doc.blocks.push(block(Block::Paragraph {
+            content: text(&self.summary()),
+        }));
+
+        let mut by_category: BTreeMap<CheckCategory, Vec<&CheckIssue>> = BTreeMap::new();
+        for issue in &self.issues {
+            by_category.entry(issue.category).or_default().push(issue);
+        }
+        for (category, issues) in by_category {
+            doc.blocks.push(block(Block::Heading {
+                level: HeadingLevel::H2,
+                content: text(category.label()),
+            }));
+            let items = issues
+                .into_iter()
+                .map(|issue| {
+                    let line = format!("{}: {}", issue.location, issue.message);
+                    ListItem::new(vec![block(Block::Paragraph {
+                        content: text(&line),
+                    })])
+                })
+                .collect();
+            doc.blocks.push(block(Block::List {
+                kind: ListKind::Unordered,
+                items,
+            }));
+        }
+        doc
+    }
+}
+
+fn is_content_document(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.ends_with(".xhtml") || name.ends_with(".html") || name.ends_with(".htm")
+}
+
+/// Undo `%XX` escapes in an href; malformed escapes are left as they are
+fn percent_decode(href: &str) -> String {
+    let bytes = href.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| href.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// `mailto:`, `https://` and other links with a scheme leave the book
+fn is_external(href: &str) -> bool {
+    href.split(['/', '#', '?'])
+        .next()
+        .is_some_and(|head| head.contains(':'))
+}
+
+/// Element ids (and old-style `<a name>`) and link targets of a content document
+#[derive(Default)]
+struct Targets {
+    ids: HashSet<String>,
+    hrefs: Vec<String>,
+    headings: Vec<String>,
+}
+
+/// What in a chapter's markup an anchor can name
+#[derive(Debug, Default)]
+pub(crate) struct AnchorTargets {
+    /// Element ids and `<a name>`s
+    pub ids: HashSet<String>,
+    /// The text of its `<h1>`..`<h6>`s, in order
+    pub headings: Vec<String>,
+}
+
+impl AnchorTargets {
+    /// Whether following `#anchor` gets somewhere: to its element, or to the heading the
+    /// reader falls back to for ids the book lost
+    pub fn resolves(&self, anchor: &str) -> bool {
+        self.ids.contains(anchor)
+            || crate::markdown_text_reader::best_heading_match(
+                anchor,
+                self.headings.iter().cloned().enumerate(),
+            )
+            .is_some()
+    }
+}
+
+fn text_content(node: &Handle, text: &mut String) {
+    match node.data {
+        NodeData::Text { ref contents } => text.push_str(&contents.borrow()),
+        _ => {
+            for child in node.children.borrow().iter() {
+                text_content(child, text);
+            }
+        }
+    }
+}
+
+fn collect_targets(node: &Handle, targets: &mut Targets) {
+    if let NodeData::Element {
+        ref name,
+        ref attrs,
+        ..
+    } = node.data
+    {
+        let tag = name.local.as_ref();
+        if matches!(tag, "h1" | "h2" | "h3" | "h4" | "h5" | "h6") {
+            let mut text = String::new();
+            text_content(node, &mut text);
+            targets
+                .headings
+                .push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+        for attr in attrs.borrow().iter() {
+            match attr.name.local.as_ref() {
+                "id" => {
+                    targets.ids.insert(attr.value.to_string());
+                }
+                "name" if tag == "a" => {
+                    targets.ids.insert(attr.value.to_string());
+                }
+                "href" if tag == "a" => targets.hrefs.push(attr.value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    for child in node.children.borrow().iter() {
+        collect_targets(child, targets);
+    }
+}
+
+/// The targets a chapter's anchors can name
+pub(crate) fn anchor_targets(html: &str) -> AnchorTargets {
+    let Targets { ids, headings, .. } = parse_targets(html.as_bytes());
+    AnchorTargets { ids, headings }
+}
+
+fn parse_targets(html: &[u8]) -> Targets {
+    let mut targets = Targets::default();
+    if let Ok(dom) = parse_document(RcDom::default(), Default::default())
+        .from_utf8()
+        .read_from(&mut &html[..])
+    {
+        collect_targets(&dom.document, &mut targets);
+    }
+    targets
+}
+
+/// A lightweight epubcheck: read every zip entry (which verifies its checksum), then
+/// check the OPF manifest and spine against the archive and every internal link against
+/// the files and anchors it points to.
+pub fn check_epub(path: &Path) -> CheckReport {
+    use CheckCategory::*;
+
+    let mut report = CheckReport::default();
+    let book = path.display().to_string();
+    let mut archive = match File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| zip::ZipArchive::new(file).map_err(|e| e.to_string()))
+    {
+        Ok(archive) => archive,
+        Err(e) => {
+            report.add(Archive, &book, format!("not a readable zip archive: {e}"));
+            return report;
+        }
+    };
+
+    let mut names = HashSet::new();
+    let mut documents = HashMap::new();
+    for index in 0..archive.len() {
+        let mut entry = match archive.by_index(index) {
+            Ok(entry) => entry,
+            Err(e) => {
+                report.add(Archive, &format!("entry {}", index + 1), e.to_string());
+                continue;
+            }
+        };
+        let name = entry.name().to_string();
+        let mut data = Vec::new();
+        if let Err(e) = entry.read_to_end(&mut data) {
+            report.add(Archive, &name, format!("corrupt data: {e}"));
+        }
+        if name == "mimetype" && data.trim_ascii() != b"application/epub+zip" {
+            report.add(
+                Archive,
+                &name,
+                "should contain `application/epub+zip`".to_string(),
+            );
+        }
+        if is_content_document(&name) {
+            documents.insert(name.clone(), parse_targets(&data));
+        }
+        names.insert(name);
+    }
+    report.files = names.len();
+    if !names.contains("mimetype") {
+        report.add(Archive, "mimetype", "missing".to_string());
+    }
+
+    let doc = match EpubDoc::new(path) {
+        Ok(doc) => doc,
+        Err(e) => {
+            report.add(Manifest, &book, format!("package document unreadable: {e}"));
+            return report;
+        }
+    };
+    let zip_path = |path: &Path| path.to_string_lossy().replace('\\', "/");
+    let spine_ids: HashSet<&str> = doc.spine.iter().map(|item| item.idref.as_str()).collect();
+    let resources: BTreeMap<_, _> = doc.resources.iter().collect();
+    for (id, resource) in resources {
+        let file = zip_path(&resource.path);
+        if !names.contains(&file) && !spine_ids.contains(id.as_str()) {
+            report.add(
+                Manifest,
+                &file,
+                format!("manifest item `{id}` points to a missing file"),
+            );
+        }
+    }
+
+    let mut chapters = Vec::new();
+    for (index, item) in doc.spine.iter().enumerate() {
+        match doc.resources.get(&item.idref) {
+            None => report.add(
+                Spine,
+                &format!("spine item {}", index + 1),
+                format!("refers to `{}`, which isn't in the manifest", item.idref),
+            ),
+            Some(resource) if !names.contains(&zip_path(&resource.path)) => report.add(
+                Spine,
+                &zip_path(&resource.path),
+                format!("spine item {} is missing from the archive", index + 1),
+            ),
+            Some(resource) => chapters.push(zip_path(&resource.path)),
+        }
+    }
+    report.chapters = chapters.len();
+
+    for chapter in &chapters {
+        let Some(targets) = documents.get(chapter) else {
+            continue;
+        };
+        let base_dir = Path::new(chapter).parent().unwrap_or(Path::new(""));
+        for href in &targets.hrefs {
+            if href.is_empty() || is_external(href) {
+                continue;
+            }
+            report.links += 1;
+            let (file, fragment) = href.split_once('#').unwrap_or((href, ""));
+            let target = if file.is_empty() {
+                chapter.clone()
+            } else {
+                resolve_relative(base_dir, &percent_decode(file)).replace('\\', "/")
+            };
+            if !names.contains(&target) {
+                report.add(Links, chapter, format!("`{href}` points to a missing file"));
+                continue;
+            }
+            let fragment = percent_decode(fragment);
+            let anchor_missing = documents
+                .get(&target)
+                .is_some_and(|target| !fragment.is_empty() && !target.ids.contains(&fragment));
+            if anchor_missing {
+                report.add(
+                    Links,
+                    chapter,
+                    format!("`{href}` points to a missing anchor"),
+                );
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    fn write_epub(files: &[(&str, &str)]) -> tempfile::NamedTempFile {
+        let file = tempfile::Builder::new().suffix(".epub").tempfile().unwrap();
+        let mut zip = zip::ZipWriter::new(file.reopen().unwrap());
+        for (name, content) in files {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_check_reports_missing_files_and_broken_links() {
+        let container = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#;
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Test</dc:title><dc:identifier id="id">x</dc:identifier></metadata>
+  <manifest>
+    <item id="ch1" href="ch1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch2" href="ch2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="cover" href="cover.jpg" media-type="image/jpeg"/>
+  </manifest>
+  <spine><itemref idref="ch1"/><itemref idref="ch2"/></spine>
+</package>"#;
+        let chapter = r##"<html><body><h1 id="top">One</h1>
+            <a href="#top">ok</a> <a href="#nowhere">anchor</a>
+            <a href="gone.xhtml">file</a> <a href="https://example.com">web</a></body></html>"##;
+        let epub = write_epub(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", container),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/ch1.xhtml", chapter),
+        ]);
+
+        let report = check_epub(epub.path());
+        assert_eq!(report.chapters, 1);
+        assert_eq!(report.links, 3);
+        let issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.category, issue.location.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (CheckCategory::Manifest, "OEBPS/cover.jpg"),
+                (CheckCategory::Spine, "OEBPS/ch2.xhtml"),
+                (CheckCategory::Links, "OEBPS/ch1.xhtml"),
+                (CheckCategory::Links, "OEBPS/ch1.xhtml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anchors_resolve_to_ids_or_the_heading_standing_in_for_them() {
+        let targets = anchor_targets(
+            r#"<html><body><h2 id="intro">Introduction</h2>
+            <h2>Evaluation <em>Methodology</em></h2><p><a name="note1">1</a></p></body></html>"#,
+        );
+        assert_eq!(targets.headings, ["Introduction", "Evaluation Methodology"]);
+        assert!(targets.resolves("intro"));
+        assert!(targets.resolves("note1"));
+        assert!(targets.resolves("ch03a_evaluation_methodology_1730150757064067"));
+        assert!(!targets.resolves("appendix_b"));
+    }
+}
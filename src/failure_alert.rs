@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How long the screen stays inverted for a flash
+const FLASH_DURATION: Duration = Duration::from_millis(150);
+
+/// How an action that can't be done (a search going round past the last match, a link
+/// that leads nowhere, the end of the book) is signalled besides any message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailureAlert {
+    /// Invert the screen for a moment, like vim's `visualbell`
+    Flash,
+    /// Ring the terminal bell
+    Bell,
+    Both,
+    /// Nothing but the message, unless the reader opts in to one of the others
+    #[default]
+    Off,
+}
+
+impl FailureAlert {
+    fn flashes(self) -> bool {
+        matches!(self, FailureAlert::Flash | FailureAlert::Both)
+    }
+
+    fn rings(self) -> bool {
+        matches!(self, FailureAlert::Bell | FailureAlert::Both)
+    }
+}
+
+/// Alerts raised by failed actions, waiting to be drawn or sent to the terminal
+#[derive(Debug, Default)]
+pub struct Alerts {
+    flash_until: Option<Instant>,
+    bell: bool,
+}
+
+impl Alerts {
+    pub fn raise(&mut self, mode: FailureAlert) {
+        if mode.flashes() {
+            self.flash_until = Some(Instant::now() + FLASH_DURATION);
+        }
+        self.bell |= mode.rings();
+    }
+
+    pub fn is_flashing(&self) -> bool {
+        self.flash_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Forget a flash that is over. Returns whether one was, so the screen is drawn
+    /// again without it.
+    pub fn update(&mut self) -> bool {
+        if self.flash_until.is_some() && !self.is_flashing() {
+            self.flash_until = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the bell should ring, once per alert
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerts_follow_the_preference() {
+        let mut alerts = Alerts::default();
+        alerts.raise(FailureAlert::Off);
+        assert!(!alerts.is_flashing());
+        assert!(!alerts.take_bell());
+
+        alerts.raise(FailureAlert::Flash);
+        assert!(alerts.is_flashing());
+        assert!(!alerts.take_bell());
+        assert!(!alerts.update());
+
+        alerts.raise(FailureAlert::Bell);
+        assert!(alerts.take_bell());
+        assert!(!alerts.take_bell());
+
+        std::thread::sleep(FLASH_DURATION);
+        assert!(alerts.update());
+        assert!(!alerts.is_flashing());
+        assert!(!alerts.update());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+/// Advisory lock on a lock file, released when dropped (or when the process exits).
+/// Other bookokcat instances honour it; it doesn't stop anything else from touching files.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Wait until the lock is ours
+    pub fn exclusive(path: &Path) -> io::Result<FileLock> {
+        let file = open_lock_file(path)?;
+        lock(&file, true)?;
+        Ok(FileLock { _file: file })
+    }
+
+    /// Take the lock if it's free; `None` means another instance is holding it
+    pub fn try_exclusive(path: &Path) -> io::Result<Option<FileLock>> {
+        let file = open_lock_file(path)?;
+        match lock(&file, false) {
+            Ok(()) => Ok(Some(FileLock { _file: file })),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn open_lock_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+}
+
+#[cfg(not(windows))]
+fn lock(file: &File, blocking: bool) -> io::Result<()> {
+    use rustix::fs::{FlockOperation, flock};
+
+    let operation = if blocking {
+        FlockOperation::LockExclusive
+    } else {
+        FlockOperation::NonBlockingLockExclusive
+    };
+    flock(file, operation).map_err(io::Error::from)
+}
+
+#[cfg(windows)]
+fn lock(_file: &File, _blocking: bool) -> io::Result<()> {
+    // No advisory locking on Windows; the merge on save still avoids lost updates
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_try_exclusive_reports_held_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locks").join("book.lock");
+
+        let held = FileLock::try_exclusive(&path).unwrap();
+        assert!(held.is_some());
+        assert!(FileLock::try_exclusive(&path).unwrap().is_none());
+
+        drop(held);
+        assert!(FileLock::try_exclusive(&path).unwrap().is_some());
+    }
+}
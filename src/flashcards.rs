@@ -0,0 +1,127 @@
+use crate::quote_capture::Citation;
+use serde::{Deserialize, Serialize};
+
+/// How a note and its passage become the two sides of a card. `{note}`, `{passage}`,
+/// `{citation}`, `{author}`, `{title}` and `{chapter}` are filled in, escaped; Anki reads
+/// the sides as HTML, so `<br>` in the template breaks a line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlashcardTemplate {
+    pub front: String,
+    pub back: String,
+}
+
+impl Default for FlashcardTemplate {
+    fn default() -> Self {
+        FlashcardTemplate {
+            front: "{note}".to_string(),
+            back: "\"{passage}\"<br>— {citation}".to_string(),
+        }
+    }
+}
+
+/// A note and the passage it was written on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flashcard {
+    pub note: String,
+    pub passage: String,
+    pub chapter: Option<String>,
+}
+
+impl FlashcardTemplate {
+    /// The front and back of `card`
+    pub fn fill(&self, card: &Flashcard, citation: &Citation) -> (String, String) {
+        let citation = Citation {
+            chapter: card.chapter.clone(),
+            ..citation.clone()
+        };
+        let escape = |text: &str| html_escape::encode_text(text).into_owned();
+        let field = |text: Option<&String>| escape(text.map_or("", String::as_str));
+        let fill = |side: &str| {
+            side.replace("{note}", &escape(card.note.trim()))
+                .replace("{passage}", &escape(card.passage.trim()))
+                .replace("{citation}", &escape(&citation.source_line()))
+                .replace("{author}", &field(citation.author.as_ref()))
+                .replace("{title}", &field(citation.title.as_ref()))
+                .replace("{chapter}", &field(citation.chapter.as_ref()))
+        };
+        (fill(&self.front), fill(&self.back))
+    }
+}
+
+/// Words `start..end` of a paragraph, for a note on part of it
+pub fn passage_words(paragraph: &str, word_range: Option<(usize, usize)>) -> String {
+    match word_range {
+        Some((start, end)) => paragraph
+            .split_whitespace()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => paragraph.split_whitespace().collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// A file for Anki's File > Import: one card per line, front and back separated by a tab,
+/// with line breaks as `<br>`
+pub fn to_anki_tsv(cards: &[(String, String)]) -> String {
+    let field = |text: &str| {
+        text.trim()
+            .replace("\r\n", "\n")
+            .replace('\n', "<br>")
+            .replace('\t', " ")
+    };
+    let mut tsv = String::from("#separator:tab\n#html:true\n");
+    for (front, back) in cards {
+        tsv.push_str(&format!("{}\t{}\n", field(front), field(back)));
+    }
+    tsv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cards_fill_the_template_and_export_as_anki_tsv() {
+        let citation = Citation {
+            author: Some("Herman Melville".to_string()),
+            title: Some("Moby-Dick".to_string()),
+            chapter: None,
+            year: None,
+        };
+        let card = Flashcard {
+            note: "Who narrates?\n".to_string(),
+            passage: passage_words("Call me   Ishmael. Some years ago", Some((0, 3))),
+            chapter: Some("Loomings".to_string()),
+        };
+
+        let sides = FlashcardTemplate::default().fill(&card, &citation);
+        assert_eq!(
+            sides,
+            (
+                "Who narrates?".to_string(),
+                "\"Call me Ishmael.\"<br>— Herman Melville, Moby-Dick, Loomings".to_string()
+            )
+        );
+
+        let template = FlashcardTemplate {
+            front: "{chapter}: {note}".to_string(),
+            back: "{passage}\n({title})".to_string(),
+        };
+        let custom = template.fill(&card, &citation);
+        assert_eq!(
+            to_anki_tsv(&[custom]),
+            "#separator:tab\n#html:true\nLoomings: Who narrates?\tCall me Ishmael.<br>(Moby-Dick)\n"
+        );
+
+        // What readers wrote is text, not markup
+        let card = Flashcard {
+            note: "Is <i> & </i> markup?".to_string(),
+            ..card
+        };
+        assert_eq!(
+            FlashcardTemplate::default().fill(&card, &citation).0,
+            "Is &lt;i&gt; &amp; &lt;/i&gt; markup?"
+        );
+    }
+}
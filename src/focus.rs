@@ -0,0 +1,123 @@
+use ratatui::layout::Rect;
+
+/// Where `Ctrl+W h/j/k/l` moves the focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusDirection {
+    Left,
+    Down,
+    Up,
+    Right,
+}
+
+impl FocusDirection {
+    /// The vim window motion for a key, if it is one
+    pub fn from_key(c: char) -> Option<Self> {
+        match c {
+            'h' => Some(FocusDirection::Left),
+            'j' => Some(FocusDirection::Down),
+            'k' => Some(FocusDirection::Up),
+            'l' => Some(FocusDirection::Right),
+            _ => None,
+        }
+    }
+}
+
+/// The panels on screen that can take focus, in Tab order, with where they were last
+/// drawn. Panels register themselves while the frame is laid out, so ones that aren't
+/// shown can't be focused.
+#[derive(Debug, Clone)]
+pub struct FocusRing<P> {
+    panels: Vec<(P, Rect)>,
+}
+
+impl<P> Default for FocusRing<P> {
+    fn default() -> Self {
+        FocusRing { panels: Vec::new() }
+    }
+}
+
+impl<P: Copy + PartialEq> FocusRing<P> {
+    pub fn clear(&mut self) {
+        self.panels.clear();
+    }
+
+    pub fn push(&mut self, panel: P, area: Rect) {
+        if area.width > 0 && area.height > 0 {
+            self.panels.push((panel, area));
+        }
+    }
+
+    /// The panel after `current` in Tab order (before it when `forward` is false),
+    /// wrapping around. A panel that isn't in the ring goes to the first one.
+    pub fn cycle(&self, current: P, forward: bool) -> Option<P> {
+        let count = self.panels.len();
+        let next = match self.panels.iter().position(|(panel, _)| *panel == current) {
+            Some(idx) if forward => (idx + 1) % count,
+            Some(idx) => (idx + count - 1) % count,
+            None => 0,
+        };
+        self.panels.get(next).map(|(panel, _)| *panel)
+    }
+
+    /// The nearest panel entirely on one side of `current`, preferring the one sharing
+    /// the most of its edge, like `Ctrl+W` between vim windows
+    pub fn neighbor(&self, current: P, direction: FocusDirection) -> Option<P> {
+        let (_, from) = self.panels.iter().find(|(panel, _)| *panel == current)?;
+        let overlap = |a0: u16, a1: u16, b0: u16, b1: u16| a1.min(b1).saturating_sub(a0.max(b0));
+        self.panels
+            .iter()
+            .filter(|(panel, _)| *panel != current)
+            .filter_map(|(panel, to)| {
+                let (gap, shared) = match direction {
+                    FocusDirection::Left if to.right() <= from.x => (
+                        from.x - to.right(),
+                        overlap(from.y, from.bottom(), to.y, to.bottom()),
+                    ),
+                    FocusDirection::Right if to.x >= from.right() => (
+                        to.x - from.right(),
+                        overlap(from.y, from.bottom(), to.y, to.bottom()),
+                    ),
+                    FocusDirection::Up if to.bottom() <= from.y => (
+                        from.y - to.bottom(),
+                        overlap(from.x, from.right(), to.x, to.right()),
+                    ),
+                    FocusDirection::Down if to.y >= from.bottom() => (
+                        to.y - from.bottom(),
+                        overlap(from.x, from.right(), to.x, to.right()),
+                    ),
+                    _ => return None,
+                };
+                Some((gap, std::cmp::Reverse(shared), *panel))
+            })
+            .min_by_key(|(gap, shared, _)| (*gap, *shared))
+            .map(|(_, _, panel)| panel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycling_and_moving_between_panels() {
+        // Navigation on the left, the reader top right, a results list under the reader
+        let mut ring = FocusRing::default();
+        ring.push('n', Rect::new(0, 0, 30, 40));
+        ring.push('r', Rect::new(30, 0, 70, 30));
+        ring.push('s', Rect::new(30, 30, 70, 10));
+        ring.push('x', Rect::new(0, 0, 0, 0));
+
+        assert_eq!(ring.cycle('n', true), Some('r'));
+        assert_eq!(ring.cycle('s', true), Some('n'));
+        assert_eq!(ring.cycle('n', false), Some('s'));
+        assert_eq!(ring.cycle('x', true), Some('n'));
+
+        use FocusDirection::*;
+        assert_eq!(ring.neighbor('n', Right), Some('r'));
+        assert_eq!(ring.neighbor('r', Down), Some('s'));
+        assert_eq!(ring.neighbor('s', Up), Some('r'));
+        assert_eq!(ring.neighbor('s', Left), Some('n'));
+        assert_eq!(ring.neighbor('n', Left), None);
+        assert_eq!(ring.neighbor('r', Up), None);
+    }
+}
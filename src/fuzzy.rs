@@ -0,0 +1,69 @@
+/// Score `candidate` against `query`, whose characters must all appear in it in order
+/// (spaces in the query are ignored, case is not). Matches at the start of words and
+/// runs of consecutive characters score higher, gaps lower. Returns the score with the
+/// char positions of the matched characters, or `None` when it doesn't match.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    // Greedy from each place the first character occurs, keeping the best
+    (0..lower.len())
+        .filter(|&start| lower[start] == query[0])
+        .filter_map(|start| {
+            let mut positions = vec![start];
+            let mut next = start + 1;
+            for wanted in &query[1..] {
+                let found = next + lower[next..].iter().position(|c| c == wanted)?;
+                positions.push(found);
+                next = found + 1;
+            }
+            Some((score(&chars, &positions), positions))
+        })
+        .max_by_key(|(score, _)| *score)
+}
+
+fn score(chars: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    for (i, &pos) in positions.iter().enumerate() {
+        score += 1;
+        if pos == 0 || !chars[pos - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match i.checked_sub(1).map(|prev| positions[prev]) {
+            Some(prev) if prev + 1 == pos => score += 5,
+            Some(prev) => score -= (pos - prev - 1).min(5) as i64,
+            None => score -= pos.min(10) as i64,
+        }
+    }
+    // Between otherwise equal matches, the shorter title is the closer one
+    score * 100 - chars.len() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match_ranks_word_starts_and_runs() {
+        assert_eq!(fuzzy_match("mdk", "Moby-Dick").unwrap().1, vec![0, 5, 8]);
+        assert_eq!(fuzzy_match("", "Emma").unwrap().0, 0);
+        assert!(fuzzy_match("xyz", "Moby-Dick").is_none());
+        assert!(fuzzy_match("kcid", "Moby-Dick").is_none());
+
+        let score = |query, candidate| fuzzy_match(query, candidate).unwrap().0;
+        assert!(score("pride", "Pride and Prejudice") > score("pride", "A Spider's Ride"));
+        assert!(score("ch 3", "Chapter 3") > score("ch 3", "The Cherry Orchard 3"));
+        assert!(score("war", "War and Peace") > score("war", "The Awkward Age"));
+    }
+}
@@ -122,6 +122,13 @@ impl BookImages {
         }
     }
 
+    /// Path of the extracted image file on disk
+    pub fn image_file_path(&self, image_src: &str, chapter_path: Option<&str>) -> Option<PathBuf> {
+        let epub_path = self.current_epub_path.as_ref()?;
+        self.storage
+            .resolve_image_path_with_context(epub_path, image_src, chapter_path)
+    }
+
     /// Load and resize an image for display
     /// Returns (resized_image, width_cells, height_cells)
     pub fn load_and_resize_image(
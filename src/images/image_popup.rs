@@ -1,6 +1,7 @@
 use crate::ratatui_image::{Image, Resize, ViewportOptions, picker::Picker, protocol::Protocol};
+use crossterm::event::{KeyCode, KeyEvent};
 use image::{DynamicImage, GenericImageView};
-use log::debug;
+use log::{debug, warn};
 use ratatui::{
     Frame,
     layout::{Alignment, Rect},
@@ -8,9 +9,19 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
 };
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
+const MAX_ZOOM: u32 = 8;
+/// Share of the visible region moved by one pan step
+const PAN_STEP: f32 = 0.25;
+
+pub enum ImagePopupAction {
+    Close,
+    Save,
+}
+
 pub struct ImagePopup {
     pub image: Arc<DynamicImage>,
     pub protocol: Option<Protocol>,
@@ -19,6 +30,11 @@ pub struct ImagePopup {
     pub is_loading: bool,
     pub load_start: Option<Instant>,
     pub popup_area: Option<Rect>, // Check if this completes a key sequence (Space+d for stats)
+    /// Extracted image file, used for saving and external viewers
+    pub file_path: Option<PathBuf>,
+    zoom: u32,
+    /// Center of the visible region, relative to the image size
+    center: (f32, f32),
 }
 
 impl ImagePopup {
@@ -31,7 +47,70 @@ impl ImagePopup {
             is_loading: true,
             load_start: Some(Instant::now()),
             popup_area: None,
+            file_path: None,
+            zoom: 1,
+            center: (0.5, 0.5),
+        }
+    }
+
+    pub fn with_file_path(mut self, file_path: Option<PathBuf>) -> Self {
+        self.file_path = file_path;
+        self
+    }
+
+    /// Zoom and pan keys are consumed here; any other key closes the popup
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<ImagePopupAction> {
+        match key.code {
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom_in(),
+            KeyCode::Char('-') => self.zoom_out(),
+            KeyCode::Char('0') => {
+                self.zoom = 1;
+                self.center = (0.5, 0.5);
+            }
+            KeyCode::Char('h') | KeyCode::Left => self.pan(-1.0, 0.0),
+            KeyCode::Char('l') | KeyCode::Right => self.pan(1.0, 0.0),
+            KeyCode::Char('k') | KeyCode::Up => self.pan(0.0, -1.0),
+            KeyCode::Char('j') | KeyCode::Down => self.pan(0.0, 1.0),
+            KeyCode::Char('s') => return Some(ImagePopupAction::Save),
+            _ => return Some(ImagePopupAction::Close),
+        }
+        None
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom * 2).min(MAX_ZOOM);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom / 2).max(1);
+        if self.zoom == 1 {
+            self.center = (0.5, 0.5);
+        }
+    }
+
+    fn pan(&mut self, dx: f32, dy: f32) {
+        if self.zoom == 1 {
+            return;
         }
+        let half_view = 0.5 / self.zoom as f32;
+        let step = PAN_STEP / self.zoom as f32;
+        self.center.0 = (self.center.0 + dx * step).clamp(half_view, 1.0 - half_view);
+        self.center.1 = (self.center.1 + dy * step).clamp(half_view, 1.0 - half_view);
+    }
+
+    /// Pixel region of the image currently in view as (x, y, width, height)
+    fn visible_region(&self) -> (u32, u32, u32, u32) {
+        let (width, height) = self.image.dimensions();
+        let view_width = (width / self.zoom).max(1);
+        let view_height = (height / self.zoom).max(1);
+        let x = (self.center.0 * width as f32 - view_width as f32 / 2.0).max(0.0) as u32;
+        let y = (self.center.1 * height as f32 - view_height as f32 / 2.0).max(0.0) as u32;
+        (
+            x.min(width.saturating_sub(view_width)),
+            y.min(height.saturating_sub(view_height)),
+            view_width,
+            view_height,
+        )
     }
 
     pub fn render(&mut self, f: &mut Frame, terminal_size: Rect) {
@@ -45,10 +124,18 @@ impl ImagePopup {
         let clear_duration = clear_start.elapsed();
 
         let (width, height) = self.image.dimensions();
-        let title = format!(" {} [{}x{} px] ", self.src_path, width, height);
+        let title = if self.zoom > 1 {
+            format!(
+                " {} [{}x{} px] {}x ",
+                self.src_path, width, height, self.zoom
+            )
+        } else {
+            format!(" {} [{}x{} px] ", self.src_path, width, height)
+        };
         let block = Block::default()
             .borders(Borders::ALL)
             .title(title)
+            .title_bottom(Line::from(" +/-: zoom | hjkl: pan | s: save ").right_aligned())
             .style(Style::default().bg(Color::Black));
 
         let inner_area = block.inner(popup_area);
@@ -98,9 +185,15 @@ impl ImagePopup {
 
         // Time the protocol creation (which includes resize)
         let start = Instant::now();
-        let protocol = self
-            .picker
-            .new_protocol(
+        let protocol = if self.zoom > 1 {
+            let (x, y, view_width, view_height) = self.visible_region();
+            self.picker.new_protocol(
+                self.image.crop_imm(x, y, view_width, view_height),
+                inner_area,
+                Resize::Scale(None),
+            )
+        } else {
+            self.picker.new_protocol(
                 self.image.as_ref().clone(),
                 self.calculate_optimal_popup_area(terminal_size),
                 Resize::Viewport(ViewportOptions {
@@ -108,7 +201,29 @@ impl ImagePopup {
                     x_offset: 0,
                 }),
             )
-            .unwrap();
+        };
+        let protocol = match protocol {
+            Ok(protocol) => protocol,
+            Err(e) => {
+                warn!("Failed to display image '{}': {e}", self.src_path);
+                let failed = Paragraph::new(vec![
+                    Line::from(""),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        format!("Failed to display image: {e}"),
+                        Style::default().fg(Color::Red),
+                    )),
+                ])
+                .alignment(Alignment::Center)
+                .style(Style::default().bg(Color::Black));
+                f.render_widget(Clear, inner_area);
+                f.render_widget(failed, inner_area);
+                self.protocol = None;
+                self.is_loading = false;
+                self.popup_area = Some(popup_area);
+                return;
+            }
+        };
         let duration = start.elapsed();
 
         self.protocol = Some(protocol);
@@ -156,6 +271,42 @@ impl ImagePopup {
         self.popup_area = Some(popup_area)
     }
 
+    /// Copy the image file into `dir`, numbering the copy (`cover-1.jpg`, ...) rather than
+    /// replacing a file already there. Returns where it was saved.
+    pub fn save_to(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        let Some(source) = &self.file_path else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Image file not available",
+            ));
+        };
+        let stem = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "image".to_string());
+        let name = |counter: usize| {
+            let stem = if counter == 0 {
+                stem.clone()
+            } else {
+                format!("{stem}-{counter}")
+            };
+            match source.extension() {
+                Some(ext) => format!("{stem}.{}", ext.to_string_lossy()),
+                None => stem,
+            }
+        };
+
+        std::fs::create_dir_all(dir)?;
+        let mut counter = 0;
+        let mut target = dir.join(name(counter));
+        while target.exists() {
+            counter += 1;
+            target = dir.join(name(counter));
+        }
+        std::fs::copy(source, &target)?;
+        Ok(target)
+    }
+
     /// Calculate the optimal popup area based on image dimensions and terminal size
     fn calculate_optimal_popup_area(&self, terminal_size: Rect) -> Rect {
         let (img_width, img_height) = self.image.dimensions();
@@ -204,3 +355,89 @@ impl ImagePopup {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    fn popup(width: u32, height: u32) -> ImagePopup {
+        let image = DynamicImage::ImageRgb8(RgbImage::new(width, height));
+        ImagePopup::new(
+            Arc::new(image),
+            &Picker::from_fontsize((8, 16)),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_visible_region_shrinks_around_the_center_as_zoom_grows() {
+        let mut popup = popup(800, 400);
+        assert_eq!(popup.visible_region(), (0, 0, 800, 400));
+        popup.zoom_in();
+        assert_eq!(popup.visible_region(), (200, 100, 400, 200));
+        popup.zoom_in();
+        assert_eq!(popup.visible_region(), (300, 150, 200, 100));
+        for _ in 0..5 {
+            popup.zoom_in();
+        }
+        assert_eq!(popup.zoom, MAX_ZOOM);
+        assert_eq!(popup.visible_region(), (350, 175, 100, 50));
+
+        // Back at 1x the whole image is in view again, wherever it was panned to
+        popup.pan(1.0, 1.0);
+        for _ in 0..3 {
+            popup.zoom_out();
+        }
+        assert_eq!(popup.zoom, 1);
+        assert_eq!(popup.visible_region(), (0, 0, 800, 400));
+    }
+
+    #[test]
+    fn test_panning_stops_at_the_edges() {
+        let mut popup = popup(800, 400);
+        popup.pan(1.0, 0.0);
+        assert_eq!(popup.visible_region(), (0, 0, 800, 400));
+
+        popup.zoom_in();
+        popup.zoom_in();
+        for _ in 0..20 {
+            popup.pan(-1.0, -1.0);
+        }
+        assert_eq!(popup.visible_region(), (0, 0, 200, 100));
+        for _ in 0..20 {
+            popup.pan(1.0, 1.0);
+        }
+        assert_eq!(popup.visible_region(), (600, 300, 200, 100));
+    }
+
+    #[test]
+    fn test_saving_numbers_copies_instead_of_replacing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("cover.jpg");
+        std::fs::write(&source, "jpeg").unwrap();
+        let popup = popup(1, 1).with_file_path(Some(source));
+        let saved = temp_dir.path().join("saved");
+
+        let names: Vec<_> = (0..3)
+            .map(|_| popup.save_to(&saved).unwrap())
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["cover.jpg", "cover-1.jpg", "cover-2.jpg"]);
+        assert_eq!(
+            std::fs::read_to_string(saved.join("cover-2.jpg")).unwrap(),
+            "jpeg"
+        );
+
+        assert!(popup.with_file_path(None).save_to(&saved).is_err());
+    }
+
+    #[test]
+    fn test_visible_region_of_an_empty_image() {
+        let mut popup = popup(0, 0);
+        assert_eq!(popup.visible_region(), (0, 0, 1, 1));
+        popup.zoom_in();
+        popup.pan(1.0, 1.0);
+        assert_eq!(popup.visible_region(), (0, 0, 1, 1));
+    }
+}
@@ -25,7 +25,7 @@ impl ImageStorage {
     }
 
     pub fn new_in_project_temp() -> Result<Self> {
-        let base_dir = PathBuf::from("temp_images");
+        let base_dir = crate::app_dirs::images_dir();
         Self::new(base_dir)
     }
 
@@ -86,6 +86,16 @@ impl ImageStorage {
         fs::create_dir_all(&book_dir)
             .with_context(|| format!("Failed to create book directory: {book_dir:?}"))?;
 
+        if crate::comic_handler::is_comic_file(&epub_path_str) {
+            let page_count = crate::comic_handler::extract_pages(epub_path, &book_dir)?;
+            info!("Extracted {page_count} comic pages to {book_dir:?}");
+            self.book_dirs
+                .lock()
+                .unwrap()
+                .insert(epub_path_str, book_dir);
+            return Ok(());
+        }
+
         let file = fs::File::open(epub_path)
             .with_context(|| format!("Failed to open EPUB file: {epub_path:?}"))?;
         let mut doc = EpubDoc::from_reader(BufReader::new(file))
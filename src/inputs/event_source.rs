@@ -1,6 +1,8 @@
+use crate::inputs::session_script::{SessionRecorder, TimedEvent};
 use anyhow::Result;
 pub use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Trait for abstracting event sources to enable testing
 pub trait EventSource {
@@ -11,6 +13,16 @@ pub trait EventSource {
     fn read(&mut self) -> Result<Event>;
 }
 
+impl<S: EventSource + ?Sized> EventSource for Box<S> {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        (**self).poll(timeout)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        (**self).read()
+    }
+}
+
 /// Real keyboard event source using crossterm
 pub struct KeyboardEventSource;
 
@@ -107,6 +119,11 @@ impl SimulatedEventSource {
             modifiers: KeyModifiers::empty(),
         })
     }
+
+    /// Helper method to create a bracketed paste event
+    pub fn paste(text: &str) -> Event {
+        Event::Paste(text.to_string())
+    }
 }
 
 impl EventSource for SimulatedEventSource {
@@ -126,6 +143,84 @@ impl EventSource for SimulatedEventSource {
     }
 }
 
+/// Replays a recorded session script with its original timing, then hands over to
+/// another source (normally the keyboard) once the script has run out. Input from
+/// that source during the replay stops it early.
+pub struct ReplayEventSource<S: EventSource> {
+    events: std::collections::VecDeque<TimedEvent>,
+    last_event_at: Option<Instant>,
+    fallback: S,
+}
+
+impl<S: EventSource> ReplayEventSource<S> {
+    pub fn new(events: Vec<TimedEvent>, fallback: S) -> Self {
+        Self {
+            events: events.into(),
+            last_event_at: None,
+            fallback,
+        }
+    }
+
+    /// Time left until the next scripted event is due
+    fn time_until_next(&mut self) -> Option<Duration> {
+        let next = self.events.front()?;
+        let last_event_at = *self.last_event_at.get_or_insert_with(Instant::now);
+        Some(next.delay.saturating_sub(last_event_at.elapsed()))
+    }
+}
+
+impl<S: EventSource> EventSource for ReplayEventSource<S> {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        let Some(wait) = self.time_until_next() else {
+            return self.fallback.poll(timeout);
+        };
+        if self.fallback.poll(wait.min(timeout))? {
+            self.events.clear();
+            return Ok(true);
+        }
+        Ok(wait <= timeout)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        if let Some(wait) = self.time_until_next() {
+            std::thread::sleep(wait);
+        }
+        match self.events.pop_front() {
+            Some(timed) => {
+                self.last_event_at = Some(Instant::now());
+                Ok(timed.event)
+            }
+            None => self.fallback.read(),
+        }
+    }
+}
+
+/// Passes events through from another source, writing each one to a session recording
+pub struct RecordingEventSource<S: EventSource> {
+    inner: S,
+    recorder: Arc<Mutex<SessionRecorder>>,
+}
+
+impl<S: EventSource> RecordingEventSource<S> {
+    pub fn new(inner: S, recorder: Arc<Mutex<SessionRecorder>>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<S: EventSource> EventSource for RecordingEventSource<S> {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        self.inner.poll(timeout)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        let event = self.inner.read()?;
+        if let Ok(mut recorder) = self.recorder.lock() {
+            recorder.record_event(&event);
+        }
+        Ok(event)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,4 +286,27 @@ mod tests {
 
         assert!(!source.poll(Duration::from_millis(0)).unwrap());
     }
+
+    #[test]
+    fn test_replay_waits_for_scripted_delay() {
+        let script = vec![
+            TimedEvent {
+                delay: Duration::ZERO,
+                event: SimulatedEventSource::char_key('j'),
+            },
+            TimedEvent {
+                delay: Duration::from_millis(30),
+                event: SimulatedEventSource::char_key('k'),
+            },
+        ];
+        let fallback = SimulatedEventSource::new(Vec::new());
+        let mut source = ReplayEventSource::new(script, fallback);
+
+        assert!(source.poll(Duration::ZERO).unwrap());
+        assert_eq!(source.read().unwrap(), SimulatedEventSource::char_key('j'));
+        assert!(!source.poll(Duration::ZERO).unwrap());
+        assert!(source.poll(Duration::from_millis(200)).unwrap());
+        assert_eq!(source.read().unwrap(), SimulatedEventSource::char_key('k'));
+        assert_eq!(source.read().unwrap(), SimulatedEventSource::char_key('q'));
+    }
 }
@@ -3,6 +3,8 @@ use std::time::Instant;
 pub struct KeySeq {
     key_sequence: Vec<char>,
     last_key_time: Option<Instant>,
+    // Pending numeric prefix for the next motion, e.g. the 5 in `5j`
+    count: Option<usize>,
 }
 
 impl Default for KeySeq {
@@ -16,10 +18,12 @@ impl KeySeq {
         Self {
             key_sequence: Vec::new(),
             last_key_time: None,
+            count: None,
         }
     }
 
-    fn should_reset_key_sequence(&self) -> bool {
+    /// Whether the keys typed so far are too old for the next one to continue them
+    pub fn should_reset_key_sequence(&self) -> bool {
         const KEY_SEQUENCE_TIMEOUT_MS: u64 = 1000; // 1 second timeout
 
         if let Some(last_time) = self.last_key_time {
@@ -52,4 +56,54 @@ impl KeySeq {
     pub fn current_sequence(&self) -> String {
         self.key_sequence.iter().collect()
     }
+
+    /// Accumulate a count digit. A leading `0` is not a count and is rejected.
+    pub fn push_count_digit(&mut self, digit: char) -> bool {
+        let Some(value) = digit.to_digit(10) else {
+            return false;
+        };
+        if value == 0 && self.count.is_none() {
+            return false;
+        }
+        let count = self.count.unwrap_or(0);
+        self.count = Some(count.saturating_mul(10).saturating_add(value as usize));
+        true
+    }
+
+    pub fn pending_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Consume the pending count, defaulting to 1 when none was typed
+    pub fn take_count(&mut self) -> usize {
+        self.count.take().unwrap_or(1)
+    }
+
+    /// Put back a count taken for a key that only starts a sequence, like the `]` of
+    /// `3]f`, so the key completing it gets the count
+    pub fn keep_count(&mut self, count: usize) {
+        if count > 1 {
+            self.count = Some(count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_prefix() {
+        let mut seq = KeySeq::new();
+        assert!(!seq.push_count_digit('0'));
+        assert!(seq.push_count_digit('1'));
+        assert!(seq.push_count_digit('0'));
+        assert_eq!(seq.pending_count(), Some(10));
+        assert_eq!(seq.take_count(), 10);
+        assert_eq!(seq.take_count(), 1);
+        seq.keep_count(3);
+        assert_eq!(seq.take_count(), 3);
+        seq.keep_count(1);
+        assert_eq!(seq.pending_count(), None);
+    }
 }
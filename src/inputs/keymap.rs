@@ -0,0 +1,112 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// Alternative bindings layered on top of the vim-style keys, which keep working in every preset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeymapPreset {
+    #[default]
+    Vim,
+    /// C-n/C-p lines, C-f/C-b forward/back, C-v/M-v pages, M-</M-> ends, C-s search, C-g cancel,
+    /// M-p the quick switcher (as C-p is taken)
+    Emacs,
+    /// Arrow keys, PgUp/PgDn and Home/End
+    Arrows,
+}
+
+impl KeymapPreset {
+    pub const ALL: [KeymapPreset; 3] =
+        [KeymapPreset::Vim, KeymapPreset::Emacs, KeymapPreset::Arrows];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeymapPreset::Vim => "vim",
+            KeymapPreset::Emacs => "emacs",
+            KeymapPreset::Arrows => "arrows",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<KeymapPreset> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Translate a key press into the vim keys it stands for. Keys the preset doesn't
+    /// bind are passed through unchanged.
+    pub fn translate(self, key: KeyEvent) -> Vec<KeyEvent> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        let translated = match self {
+            KeymapPreset::Vim => None,
+            KeymapPreset::Emacs => match key.code {
+                KeyCode::Char('n') if ctrl => Some(vec![plain('j')]),
+                KeyCode::Char('p') if ctrl => Some(vec![plain('k')]),
+                KeyCode::Char('f') if ctrl => Some(vec![plain('l')]),
+                KeyCode::Char('b') if ctrl => Some(vec![plain('h')]),
+                KeyCode::Char('v') if ctrl => Some(vec![control('d')]),
+                KeyCode::Char('v') if alt => Some(vec![control('u')]),
+                KeyCode::Char('<') if alt => Some(vec![plain('g'), plain('g')]),
+                KeyCode::Char('>') if alt => Some(vec![plain('G')]),
+                KeyCode::Char('s') if ctrl => Some(vec![plain('/')]),
+                KeyCode::Char('g') if ctrl => Some(vec![KeyEvent::from(KeyCode::Esc)]),
+                KeyCode::Char('p') if alt => Some(vec![control('p')]),
+                _ => None,
+            },
+            KeymapPreset::Arrows => match key.code {
+                KeyCode::Down => Some(vec![plain('j')]),
+                KeyCode::Up => Some(vec![plain('k')]),
+                KeyCode::Left => Some(vec![plain('h')]),
+                KeyCode::Right => Some(vec![plain('l')]),
+                KeyCode::PageDown => Some(vec![control('d')]),
+                KeyCode::PageUp => Some(vec![control('u')]),
+                KeyCode::Home => Some(vec![plain('g'), plain('g')]),
+                KeyCode::End => Some(vec![plain('G')]),
+                _ => None,
+            },
+        };
+
+        translated.unwrap_or_else(|| vec![key])
+    }
+}
+
+fn plain(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+}
+
+fn control(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_presets() {
+        let ctrl_v = control('v');
+        assert_eq!(KeymapPreset::Vim.translate(ctrl_v), vec![ctrl_v]);
+        assert_eq!(KeymapPreset::Emacs.translate(ctrl_v), vec![control('d')]);
+        assert_eq!(
+            KeymapPreset::Emacs.translate(KeyEvent::new(KeyCode::Char('<'), KeyModifiers::ALT)),
+            vec![plain('g'), plain('g')]
+        );
+        // C-p moves up a line, so the quick switcher moves to M-p
+        assert_eq!(
+            KeymapPreset::Emacs.translate(control('p')),
+            vec![plain('k')]
+        );
+        assert_eq!(
+            KeymapPreset::Emacs.translate(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT)),
+            vec![control('p')]
+        );
+        assert_eq!(
+            KeymapPreset::Arrows.translate(KeyEvent::from(KeyCode::PageDown)),
+            vec![control('d')]
+        );
+        // Vim keys keep working in the other presets
+        assert_eq!(KeymapPreset::Arrows.translate(plain('j')), vec![plain('j')]);
+        assert_eq!(KeymapPreset::from_name("Emacs"), Some(KeymapPreset::Emacs));
+    }
+}
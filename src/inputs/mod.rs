@@ -1,8 +1,11 @@
 pub mod event_source;
 pub mod key_seq;
+pub mod keymap;
 pub mod mouse_tracker;
+pub mod session_script;
 pub mod text_area_utils;
 
 pub use key_seq::KeySeq;
-pub use mouse_tracker::{ClickType, MouseTracker};
+pub use keymap::KeymapPreset;
+pub use mouse_tracker::{ClickType, MAX_NAV_PERCENT, MIN_NAV_PERCENT, MouseTracker};
 pub use text_area_utils::map_keys_to_input;
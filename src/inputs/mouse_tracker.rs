@@ -1,4 +1,23 @@
-use std::time::Instant;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Wheel events older than this don't count towards the scroll velocity
+const VELOCITY_WINDOW: Duration = Duration::from_millis(150);
+/// Lines scrolled within the window that count as a flick
+const FLICK_THRESHOLD: i32 = 6;
+/// Fastest momentum, in lines per second
+const MAX_MOMENTUM: f32 = 120.0;
+/// Fraction of the momentum lost per second
+const MOMENTUM_DECAY: f32 = 3.0;
+/// Momentum below this (lines per second) stops
+const MIN_MOMENTUM: f32 = 4.0;
+
+/// Narrowest and widest the navigation panel can be dragged, in percent of the screen
+pub const MIN_NAV_PERCENT: u16 = 15;
+pub const MAX_NAV_PERCENT: u16 = 60;
+/// Widths the splitter snaps to when let go within `SNAP_DISTANCE` percent of them
+const SNAP_PERCENTS: [u16; 5] = [20, 25, 30, 40, 50];
+const SNAP_DISTANCE: u16 = 2;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ClickType {
@@ -11,6 +30,17 @@ pub struct MouseTracker {
     last_click_time: Option<Instant>,
     last_click_position: Option<(u16, u16)>,
     click_count: u32,
+    /// Row the content was grabbed at during a drag-scroll
+    drag_scroll_row: Option<u16>,
+    /// The border between the navigation panel and the reader is being dragged
+    splitter_drag: bool,
+    /// Recent wheel scrolls (time, lines) used to estimate velocity
+    scroll_samples: VecDeque<(Instant, i32)>,
+    /// Remaining kinetic scroll velocity in lines per second, and the column it applies to
+    momentum: Option<(f32, u16)>,
+    /// Fractional lines carried between momentum steps
+    momentum_carry: f32,
+    last_momentum_step: Option<Instant>,
 }
 
 impl MouseTracker {
@@ -19,8 +49,15 @@ impl MouseTracker {
             last_click_time: None,
             last_click_position: None,
             click_count: 0,
+            drag_scroll_row: None,
+            splitter_drag: false,
+            scroll_samples: VecDeque::new(),
+            momentum: None,
+            momentum_carry: 0.0,
+            last_momentum_step: None,
         }
     }
+
     pub fn detect_click_type(&mut self, column: u16, row: u16) -> ClickType {
         const DOUBLE_CLICK_TIME_MS: u64 = 500; // Maximum time between clicks for double-click
         const CLICK_DISTANCE_THRESHOLD: u16 = 3; // Maximum distance between clicks
@@ -57,6 +94,141 @@ impl MouseTracker {
             _ => ClickType::Single,
         }
     }
+
+    /// Grab the content at `row`; following drags scroll it along with the pointer
+    pub fn start_drag_scroll(&mut self, row: u16) {
+        self.stop_momentum();
+        self.drag_scroll_row = Some(row);
+    }
+
+    /// Lines to scroll (positive is down) for a drag to `row`. Pulling the pointer up
+    /// moves the text up, i.e. scrolls down.
+    pub fn drag_scroll(&mut self, row: u16) -> i32 {
+        let Some(last_row) = self.drag_scroll_row.replace(row) else {
+            return 0;
+        };
+        last_row as i32 - row as i32
+    }
+
+    pub fn end_drag_scroll(&mut self) {
+        self.drag_scroll_row = None;
+    }
+
+    pub fn is_drag_scrolling(&self) -> bool {
+        self.drag_scroll_row.is_some()
+    }
+
+    pub fn start_splitter_drag(&mut self) {
+        self.stop_momentum();
+        self.splitter_drag = true;
+    }
+
+    pub fn is_dragging_splitter(&self) -> bool {
+        self.splitter_drag
+    }
+
+    pub fn end_splitter_drag(&mut self) {
+        self.splitter_drag = false;
+    }
+
+    /// The navigation panel's share of a `width` columns wide screen, in percent, with
+    /// its border dragged to `column`. Snaps to the usual widths when close to one.
+    pub fn splitter_percent(column: u16, width: u16) -> u16 {
+        if width == 0 {
+            return SNAP_PERCENTS[2];
+        }
+        let percent = ((u32::from(column) + 1) * 100 + u32::from(width) / 2) / u32::from(width);
+        let percent = (percent as u16).clamp(MIN_NAV_PERCENT, MAX_NAV_PERCENT);
+        SNAP_PERCENTS
+            .into_iter()
+            .find(|snap| snap.abs_diff(percent) <= SNAP_DISTANCE)
+            .unwrap_or(percent)
+    }
+
+    /// Track a wheel scroll; a fast enough flick leaves momentum behind that
+    /// `momentum_step` keeps applying after the wheel stops
+    pub fn record_scroll(&mut self, lines: i32, column: u16) {
+        self.record_scroll_at(lines, column, Instant::now());
+    }
+
+    fn record_scroll_at(&mut self, lines: i32, column: u16, now: Instant) {
+        if lines == 0 {
+            return;
+        }
+
+        // Reversing direction brakes immediately
+        let reversed = self
+            .scroll_samples
+            .back()
+            .is_some_and(|(_, last)| last.signum() != lines.signum());
+        if reversed {
+            self.scroll_samples.clear();
+            self.stop_momentum();
+        }
+
+        self.scroll_samples.push_back((now, lines));
+        while self
+            .scroll_samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > VELOCITY_WINDOW)
+        {
+            self.scroll_samples.pop_front();
+        }
+
+        let total: i32 = self.scroll_samples.iter().map(|(_, lines)| lines).sum();
+        if total.abs() < FLICK_THRESHOLD {
+            return;
+        }
+
+        let velocity =
+            (total as f32 / VELOCITY_WINDOW.as_secs_f32()).clamp(-MAX_MOMENTUM, MAX_MOMENTUM);
+        self.momentum = Some((velocity, column));
+        self.last_momentum_step = Some(now);
+    }
+
+    /// Lines to scroll since the previous step, and the column they apply to
+    pub fn momentum_step(&mut self) -> Option<(i32, u16)> {
+        self.momentum_step_at(Instant::now())
+    }
+
+    fn momentum_step_at(&mut self, now: Instant) -> Option<(i32, u16)> {
+        let (velocity, column) = self.momentum?;
+        let last_step = self.last_momentum_step.unwrap_or(now);
+        let elapsed = now.duration_since(last_step).as_secs_f32();
+        self.last_momentum_step = Some(now);
+
+        // Keep scrolling with the wheel rather than adding to it
+        if self
+            .scroll_samples
+            .back()
+            .is_some_and(|(time, _)| now.duration_since(*time) < VELOCITY_WINDOW)
+        {
+            return None;
+        }
+
+        self.momentum_carry += velocity * elapsed;
+        let lines = self.momentum_carry.trunc();
+        self.momentum_carry -= lines;
+
+        let velocity = velocity * (-MOMENTUM_DECAY * elapsed).exp();
+        if velocity.abs() < MIN_MOMENTUM {
+            self.stop_momentum();
+        } else {
+            self.momentum = Some((velocity, column));
+        }
+
+        (lines != 0.0).then_some((lines as i32, column))
+    }
+
+    pub fn has_momentum(&self) -> bool {
+        self.momentum.is_some()
+    }
+
+    pub fn stop_momentum(&mut self) {
+        self.momentum = None;
+        self.momentum_carry = 0.0;
+        self.last_momentum_step = None;
+    }
 }
 
 impl Default for MouseTracker {
@@ -64,3 +236,65 @@ impl Default for MouseTracker {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flick_leaves_decaying_momentum() {
+        let mut tracker = MouseTracker::new();
+        let start = Instant::now();
+
+        // Slow scrolling doesn't start momentum
+        tracker.record_scroll_at(2, 50, start);
+        assert!(!tracker.has_momentum());
+
+        tracker.record_scroll_at(5, 50, start + Duration::from_millis(40));
+        assert!(tracker.has_momentum());
+
+        // Nothing while the wheel is still turning, then lines that shrink until it stops
+        assert_eq!(
+            tracker.momentum_step_at(start + Duration::from_millis(60)),
+            None
+        );
+        let mut now = start + Duration::from_millis(200);
+        let mut steps = Vec::new();
+        while let Some((lines, column)) = tracker.momentum_step_at(now) {
+            assert_eq!(column, 50);
+            steps.push(lines);
+            now += Duration::from_millis(50);
+        }
+        assert!(steps.len() > 2);
+        assert!(steps.iter().all(|lines| *lines > 0));
+        assert!(steps.first() >= steps.last());
+
+        // Scrolling the other way brakes
+        tracker.record_scroll_at(3, 50, now);
+        tracker.record_scroll_at(-1, 50, now + Duration::from_millis(10));
+        assert!(!tracker.has_momentum());
+    }
+
+    #[test]
+    fn test_drag_scroll_follows_pointer() {
+        let mut tracker = MouseTracker::new();
+        assert_eq!(tracker.drag_scroll(10), 0);
+
+        tracker.start_drag_scroll(10);
+        assert_eq!(tracker.drag_scroll(7), 3);
+        assert_eq!(tracker.drag_scroll(9), -2);
+        tracker.end_drag_scroll();
+        assert!(!tracker.is_drag_scrolling());
+    }
+
+    #[test]
+    fn test_splitter_snaps_and_stays_in_bounds() {
+        // The border at column 29 of 100 leaves the panel 30 columns
+        assert_eq!(MouseTracker::splitter_percent(29, 100), 30);
+        assert_eq!(MouseTracker::splitter_percent(31, 100), 30);
+        assert_eq!(MouseTracker::splitter_percent(34, 100), 35);
+        assert_eq!(MouseTracker::splitter_percent(48, 120), 40);
+        assert_eq!(MouseTracker::splitter_percent(2, 100), MIN_NAV_PERCENT);
+        assert_eq!(MouseTracker::splitter_percent(90, 100), MAX_NAV_PERCENT);
+    }
+}
@@ -0,0 +1,474 @@
+use anyhow::{Context, Result, anyhow, bail};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// An event and how long to wait after the previous one before delivering it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedEvent {
+    pub delay: Duration,
+    pub event: Event,
+}
+
+/// Parse a session script: one input event per line, preceded by the milliseconds to
+/// wait after the previous event. Blank lines and lines starting with `#` are skipped.
+///
+/// ```text
+/// # Open the first book and page down twice
+/// 500 key enter
+/// 200 key ctrl+d
+/// 200 key ctrl+d
+/// 100 mouse scroll-down 40 12
+/// 0 paste some text\nwith a line break
+/// 300 key q
+/// ```
+///
+/// Recordings write `key <char>` for a character typed into an input field, which replays
+/// as `x`, and `paste <N bytes>` for a paste, so neither carries what was typed.
+pub fn parse_script(script: &str) -> Result<Vec<TimedEvent>> {
+    script
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(index, line)| parse_line(line).with_context(|| format!("line {}", index + 1)))
+        .collect()
+}
+
+/// Parse `<delay ms> <event>`
+pub fn parse_line(line: &str) -> Result<TimedEvent> {
+    let line = line.trim_start();
+    let (delay, event) = line
+        .split_once(' ')
+        .ok_or_else(|| anyhow!("expected '<delay ms> <event>'"))?;
+    let delay = delay
+        .parse::<u64>()
+        .with_context(|| format!("invalid delay '{delay}'"))?;
+    Ok(TimedEvent {
+        delay: Duration::from_millis(delay),
+        event: parse_event(event)?,
+    })
+}
+
+/// Format a line that `parse_line` reads back. Events a script cannot express (focus
+/// changes, key releases) give `None`.
+pub fn format_line(delay: Duration, event: &Event) -> Option<String> {
+    Some(format!("{} {}", delay.as_millis(), format_event(event)?))
+}
+
+fn parse_event(text: &str) -> Result<Event> {
+    let (kind, rest) = text.split_once(' ').unwrap_or((text, ""));
+    match kind {
+        "key" => Ok(Event::Key(parse_key(rest)?)),
+        "mouse" => Ok(Event::Mouse(parse_mouse(rest)?)),
+        "paste" => Ok(Event::Paste(unescape(rest))),
+        "resize" => {
+            let (columns, rows) = parse_position(rest)?;
+            Ok(Event::Resize(columns, rows))
+        }
+        _ => bail!("unknown event '{kind}'"),
+    }
+}
+
+fn format_event(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => {
+            Some(format!("key {}", format_key(key)?))
+        }
+        Event::Mouse(mouse) => {
+            let kind = match mouse.kind {
+                MouseEventKind::Down(button) => format!("down {}", button_name(button)),
+                MouseEventKind::Up(button) => format!("up {}", button_name(button)),
+                MouseEventKind::Drag(button) => format!("drag {}", button_name(button)),
+                MouseEventKind::Moved => "moved".to_string(),
+                MouseEventKind::ScrollDown => "scroll-down".to_string(),
+                MouseEventKind::ScrollUp => "scroll-up".to_string(),
+                MouseEventKind::ScrollLeft => "scroll-left".to_string(),
+                MouseEventKind::ScrollRight => "scroll-right".to_string(),
+            };
+            Some(format!(
+                "mouse {}{kind} {} {}",
+                modifier_prefix(mouse.modifiers),
+                mouse.column,
+                mouse.row
+            ))
+        }
+        Event::Paste(text) => Some(format!("paste {}", escape(text))),
+        Event::Resize(columns, rows) => Some(format!("resize {columns} {rows}")),
+        _ => None,
+    }
+}
+
+/// Strip `ctrl+`, `alt+` and `shift+` prefixes
+fn parse_modifiers(mut text: &str) -> (KeyModifiers, &str) {
+    let mut modifiers = KeyModifiers::empty();
+    loop {
+        let lower = text.to_ascii_lowercase();
+        let (modifier, prefix_len) = if lower.starts_with("ctrl+") && text.len() > 5 {
+            (KeyModifiers::CONTROL, 5)
+        } else if lower.starts_with("alt+") && text.len() > 4 {
+            (KeyModifiers::ALT, 4)
+        } else if lower.starts_with("shift+") && text.len() > 6 {
+            (KeyModifiers::SHIFT, 6)
+        } else {
+            return (modifiers, text);
+        };
+        modifiers |= modifier;
+        text = &text[prefix_len..];
+    }
+}
+
+fn modifier_prefix(modifiers: KeyModifiers) -> String {
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("shift+");
+    }
+    prefix
+}
+
+fn parse_key(text: &str) -> Result<KeyEvent> {
+    let (modifiers, name) = parse_modifiers(text.trim());
+    let code = match name.to_ascii_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "space" => KeyCode::Char(' '),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        TYPED_CHAR => KeyCode::Char('x'),
+        lower => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) => KeyCode::F(n),
+                    None => bail!("unknown key '{name}'"),
+                },
+            }
+        }
+    };
+    Ok(KeyEvent {
+        code,
+        modifiers,
+        kind: KeyEventKind::Press,
+        state: KeyEventState::empty(),
+    })
+}
+
+fn format_key(key: &KeyEvent) -> Option<String> {
+    let name = match key.code {
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "backtab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::F(n) => format!("f{n}"),
+        KeyCode::Char(c) => c.to_string(),
+        _ => return None,
+    };
+    Some(format!("{}{name}", modifier_prefix(key.modifiers)))
+}
+
+fn parse_mouse(text: &str) -> Result<MouseEvent> {
+    let (modifiers, text) = parse_modifiers(text.trim());
+    let (kind, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let button_and_position = || -> Result<(MouseButton, &str)> {
+        let (button, position) = rest.split_once(' ').unwrap_or((rest, ""));
+        let button = match button {
+            "left" => MouseButton::Left,
+            "right" => MouseButton::Right,
+            "middle" => MouseButton::Middle,
+            _ => bail!("unknown mouse button '{button}'"),
+        };
+        Ok((button, position))
+    };
+    let (kind, position) = match kind {
+        "down" => {
+            let (button, position) = button_and_position()?;
+            (MouseEventKind::Down(button), position)
+        }
+        "up" => {
+            let (button, position) = button_and_position()?;
+            (MouseEventKind::Up(button), position)
+        }
+        "drag" => {
+            let (button, position) = button_and_position()?;
+            (MouseEventKind::Drag(button), position)
+        }
+        "moved" => (MouseEventKind::Moved, rest),
+        "scroll-down" => (MouseEventKind::ScrollDown, rest),
+        "scroll-up" => (MouseEventKind::ScrollUp, rest),
+        "scroll-left" => (MouseEventKind::ScrollLeft, rest),
+        "scroll-right" => (MouseEventKind::ScrollRight, rest),
+        _ => bail!("unknown mouse event '{kind}'"),
+    };
+    let (column, row) = parse_position(position)?;
+    Ok(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers,
+    })
+}
+
+fn button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    }
+}
+
+fn parse_position(text: &str) -> Result<(u16, u16)> {
+    let mut numbers = text.split_whitespace().map(str::parse::<u16>);
+    match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Ok((x, y)),
+        _ => bail!("expected two numbers, got '{text}'"),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Stands in for a character typed into an input field
+const TYPED_CHAR: &str = "<char>";
+
+/// Writes input events as a script `parse_script` can replay. Between events, `#` lines
+/// note which book, chapter and panel the app was in, but never any book text, and what
+/// was typed into input fields or pasted is left out.
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    last_event_at: Option<Instant>,
+    last_state: String,
+    typing: bool,
+    failed: bool,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(
+            writer,
+            "# bookokcat {} session recorded {}",
+            env!("CARGO_PKG_VERSION"),
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        )?;
+        if let Ok((columns, rows)) = crossterm::terminal::size() {
+            writeln!(writer, "# terminal {columns}x{rows}")?;
+        }
+        writer.flush()?;
+        Ok(Self {
+            writer,
+            last_event_at: None,
+            last_state: String::new(),
+            typing: false,
+            failed: false,
+        })
+    }
+
+    pub fn record_event(&mut self, event: &Event) {
+        let now = Instant::now();
+        let delay = self
+            .last_event_at
+            .map_or(Duration::ZERO, |last| now.duration_since(last));
+        let line = match event {
+            Event::Key(key)
+                if self.typing
+                    && key.kind != KeyEventKind::Release
+                    && matches!(key.code, KeyCode::Char(_))
+                    && !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Some(format!("{} key {TYPED_CHAR}", delay.as_millis()))
+            }
+            Event::Paste(text) => Some(format!(
+                "{} paste <{} bytes>",
+                delay.as_millis(),
+                text.len()
+            )),
+            _ => format_line(delay, event),
+        };
+        if let Some(line) = line {
+            self.last_event_at = Some(now);
+            self.write_line(&line);
+        }
+    }
+
+    /// Whether the next keys go into an input field, so their characters are left out
+    pub fn set_typing(&mut self, typing: bool) {
+        self.typing = typing;
+    }
+
+    /// Note the app state when it differs from the last one noted
+    pub fn record_state(&mut self, state: &str) {
+        if state != self.last_state {
+            self.last_state = state.to_string();
+            self.write_line(&format!("# state: {state}"));
+        }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.failed {
+            return;
+        }
+        // Flush every line so a crash still leaves a complete recording
+        let result = writeln!(self.writer, "{line}").and_then(|_| self.writer.flush());
+        if let Err(e) = result {
+            log::warn!("Stopped recording session: {e}");
+            self.failed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_round_trip() {
+        let script = "\
+# comment
+500 key enter
+20 key ctrl++
+0 key ctrl+shift+tab
+15 mouse down middle 10 4
+15 mouse ctrl+scroll-down 40 12
+0 paste two\\nlines \\\\ and a tab\\t
+0 resize 120 40
+";
+        let events = parse_script(script).unwrap();
+        assert_eq!(events.len(), 7);
+        assert_eq!(events[0].delay, Duration::from_millis(500));
+        assert_eq!(
+            events[1].event,
+            Event::Key(KeyEvent::new(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            events[5].event,
+            Event::Paste("two\nlines \\ and a tab\t".to_string())
+        );
+
+        let formatted: Vec<String> = events
+            .iter()
+            .map(|timed| format_line(timed.delay, &timed.event).unwrap())
+            .collect();
+        let reparsed = parse_script(&formatted.join("\n")).unwrap();
+        assert_eq!(reparsed, events);
+
+        let error = parse_script("10 key enter\n10 jump").unwrap_err();
+        assert_eq!(error.to_string(), "line 2");
+    }
+
+    #[test]
+    fn test_recording_replays_with_state_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.txt");
+        let mut recorder = SessionRecorder::create(&path).unwrap();
+        recorder.record_state("panel=library");
+        recorder.record_event(&Event::Key(KeyEvent::new(
+            KeyCode::Enter,
+            KeyModifiers::empty(),
+        )));
+        recorder.record_state("panel=library");
+        recorder.record_state("book=a.epub chapter=1/3 panel=content");
+        recorder.record_event(&Event::FocusLost);
+        recorder.record_event(&Event::Paste("secret".to_string()));
+        recorder.set_typing(true);
+        recorder.record_event(&Event::Key(KeyEvent::new(
+            KeyCode::Char('s'),
+            KeyModifiers::empty(),
+        )));
+        recorder.record_event(&Event::Key(KeyEvent::new(
+            KeyCode::Char('u'),
+            KeyModifiers::CONTROL,
+        )));
+        drop(recorder);
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        let states: Vec<&str> = recording
+            .lines()
+            .filter(|line| line.starts_with("# state"))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                "# state: panel=library",
+                "# state: book=a.epub chapter=1/3 panel=content"
+            ]
+        );
+
+        let events: Vec<Event> = parse_script(&recording)
+            .unwrap()
+            .into_iter()
+            .map(|timed| timed.event)
+            .collect();
+        assert_eq!(
+            events,
+            [
+                Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::empty())),
+                Event::Paste("<6 bytes>".to_string()),
+                Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::empty())),
+                Event::Key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL))
+            ]
+        );
+        assert!(!recording.contains("secret"));
+    }
+}
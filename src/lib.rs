@@ -1,11 +1,37 @@
 // Export modules for use in tests
+pub mod app_dirs;
+pub mod book_club;
 pub mod book_manager;
+pub mod book_notes;
+pub mod book_watcher;
 pub mod bookmarks;
+pub mod chapter_cache;
+pub mod chapter_diff;
+pub mod chapter_summary;
 pub mod chatgpt_client;
+pub mod code_export;
 pub mod color_mode;
+pub mod comic_handler;
+pub mod command_line;
 pub mod comments;
+pub mod deep_link;
+pub mod edition_compare;
+pub mod entity_index;
+pub mod epub_builder;
+pub mod epub_check;
+pub mod failure_alert;
+pub mod file_lock;
+pub mod flashcards;
+pub mod focus;
+pub mod fuzzy;
+pub mod migrate;
 pub mod pdf_handler;
 pub mod preferences;
+pub mod quote_capture;
+pub mod reading_goal;
+pub mod remote_book;
+pub mod text_handler;
+pub mod timeline;
 pub use inputs::event_source;
 pub mod components;
 pub mod images;
@@ -14,8 +40,10 @@ pub mod vendored;
 pub use vendored::ratatui_image;
 pub mod inputs;
 pub mod jump_list;
+pub mod logging;
 pub mod main_app;
 pub mod markdown;
+pub mod note_files;
 pub mod notification;
 pub mod widget;
 pub use components::mathml_renderer;
@@ -30,9 +58,14 @@ pub mod parsing;
 pub mod search;
 pub mod search_engine;
 pub mod system_command;
+pub mod table_export;
+pub mod terminal_title;
+pub mod text_stats;
 pub use components::table;
 pub mod theme;
 pub mod types;
+pub mod undo;
+pub mod vocabulary;
 // Test utilities - only available when test-utils feature is enabled or during tests
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
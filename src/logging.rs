@@ -0,0 +1,355 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+/// Our own modules can be named without the crate prefix: `parsing` is `bookokcat::parsing`
+const CRATE_PREFIX: &str = "bookokcat::";
+
+/// The log is rotated once it grows past this many bytes
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated logs kept next to the current one: `bookokcat.log.1` (newest) to `.3`
+const ROTATED_LOGS: usize = 3;
+
+/// Log lines kept in memory for the log panel
+const RECENT_LINES: usize = 200;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// The log file, appended to across runs and rotated by size
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+}
+
+impl LogFile {
+    fn open(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(LogFile {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+        })
+    }
+
+    fn rotated_path(&self, generation: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{generation}"));
+        PathBuf::from(name)
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+        if self.size >= self.max_bytes {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    /// Shift `log.1`.. `log.N-1` up by one (dropping the oldest), move the log to
+    /// `log.1` and start an empty one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for generation in (1..ROTATED_LOGS).rev() {
+            let from = self.rotated_path(generation);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(generation + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// How each log record is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// `12:34:56 [DEBUG] bookokcat::main_app: message`
+    #[default]
+    Text,
+    /// One JSON object per line, with `time`, `level`, `target` and `message`
+    Json,
+}
+
+/// The level for log targets under `target`, or for everything else when it's empty
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogDirective {
+    pub target: String,
+    pub level: LevelFilter,
+}
+
+/// Parse a comma-separated list like `info,parsing=trace,html5ever=off`: a bare level is
+/// the default, `module=level` sets a module and everything below it
+pub fn parse_directives(spec: &str) -> Result<Vec<LogDirective>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (target, level) = part.split_once('=').unwrap_or(("", part));
+            let level = LevelFilter::from_str(level.trim()).map_err(|_| {
+                format!("Unknown log level: {level} (off, error, warn, info, debug or trace)")
+            })?;
+            Ok(LogDirective {
+                target: target.trim().to_string(),
+                level,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct LogConfig {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+    format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            default: LevelFilter::Debug,
+            // html5ever's debug output would bury everything else
+            modules: vec![("html5ever".to_string(), LevelFilter::Off)],
+            format: LogFormat::Text,
+        }
+    }
+}
+
+impl LogConfig {
+    fn apply(&mut self, directives: &[LogDirective]) {
+        for directive in directives {
+            if directive.target.is_empty() {
+                self.default = directive.level;
+                continue;
+            }
+            match self
+                .modules
+                .iter_mut()
+                .find(|(target, _)| *target == directive.target)
+            {
+                Some((_, level)) => *level = directive.level,
+                None => self
+                    .modules
+                    .push((directive.target.clone(), directive.level)),
+            }
+        }
+    }
+
+    /// The level of the most specific module naming `target`, or the default
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let covers = |module: &str, target: &str| {
+            target
+                .strip_prefix(module)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        };
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                covers(module, target)
+                    || target
+                        .strip_prefix(CRATE_PREFIX)
+                        .is_some_and(|target| covers(module, target))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = vec![self.default.to_string().to_lowercase()];
+        parts.extend(
+            self.modules
+                .iter()
+                .map(|(module, level)| format!("{module}={}", level.to_string().to_lowercase())),
+        );
+        format!("{} ({:?})", parts.join(","), self.format).to_lowercase()
+    }
+}
+
+struct FileLogger {
+    config: RwLock<LogConfig>,
+    file: Mutex<LogFile>,
+}
+
+static LOGGER: OnceLock<FileLogger> = OnceLock::new();
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.config
+            .read()
+            .is_ok_and(|config| metadata.level() <= config.level_for(metadata.target()))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let format = self.config.read().map(|config| config.format);
+        let now = chrono::Local::now();
+        let line = match format.unwrap_or_default() {
+            LogFormat::Text => format!(
+                "{} [{}] {}: {}",
+                now.format("%H:%M:%S"),
+                record.level(),
+                record.target(),
+                record.args()
+            ),
+            LogFormat::Json => serde_json::json!({
+                "time": now.to_rfc3339(),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            })
+            .to_string(),
+        };
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_line(&line);
+        }
+        remember(line);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+fn remember(line: String) {
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+/// The latest lines written to the log, oldest first
+pub fn recent_lines() -> Vec<String> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Append log records to `path`, at debug level with html5ever silenced until configured
+pub fn init(path: &Path) -> anyhow::Result<()> {
+    let file = LogFile::open(path, MAX_LOG_BYTES)
+        .map_err(|e| anyhow::anyhow!("Failed to open log file {}: {e}", path.display()))?;
+    let logger = FileLogger {
+        config: RwLock::new(LogConfig::default()),
+        file: Mutex::new(file),
+    };
+    if LOGGER.set(logger).is_err() {
+        anyhow::bail!("Logging is already initialized");
+    }
+    let logger = LOGGER.get().expect("logger was just set");
+    log::set_logger(logger).map_err(|e| anyhow::anyhow!("Failed to install logger: {e}"))?;
+    update_max_level();
+    Ok(())
+}
+
+fn update_max_level() {
+    if let Some(config) = LOGGER.get().and_then(|logger| logger.config.read().ok()) {
+        log::set_max_level(config.max_level());
+    }
+}
+
+/// Change the default and per-module levels; modules not mentioned keep theirs
+pub fn apply_directives(directives: &[LogDirective]) {
+    if let Some(mut config) = LOGGER.get().and_then(|logger| logger.config.write().ok()) {
+        config.apply(directives);
+    }
+    update_max_level();
+}
+
+pub fn set_format(format: LogFormat) {
+    if let Some(mut config) = LOGGER.get().and_then(|logger| logger.config.write().ok()) {
+        config.format = format;
+    }
+}
+
+/// The levels and format in effect, e.g. `debug,html5ever=off (text)`
+pub fn describe() -> String {
+    LOGGER
+        .get()
+        .and_then(|logger| logger.config.read().ok().map(|config| config.describe()))
+        .unwrap_or_else(|| "logging is off".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_module_level_wins() {
+        let mut config = LogConfig::default();
+        config.apply(&parse_directives("info, parsing=trace,parsing::css=warn").unwrap());
+
+        assert_eq!(config.level_for("bookokcat::main_app"), LevelFilter::Info);
+        assert_eq!(
+            config.level_for("bookokcat::parsing::html_to_markdown"),
+            LevelFilter::Trace
+        );
+        assert_eq!(
+            config.level_for("bookokcat::parsing::css"),
+            LevelFilter::Warn
+        );
+        // A module name only covers whole path segments
+        assert_eq!(
+            config.level_for("bookokcat::parsing_extra"),
+            LevelFilter::Info
+        );
+        assert_eq!(
+            config.level_for("html5ever::tree_builder"),
+            LevelFilter::Off
+        );
+        assert_eq!(config.max_level(), LevelFilter::Trace);
+        assert_eq!(
+            config.describe(),
+            "info,html5ever=off,parsing=trace,parsing::css=warn (text)"
+        );
+
+        assert!(parse_directives("parsing=loud").is_err());
+    }
+
+    #[test]
+    fn test_log_rotates_by_size_keeping_a_few_old_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("bookokcat.log");
+        let mut log = LogFile::open(&path, 100).unwrap();
+        for round in 0..5 {
+            for _ in 0..4 {
+                log.write_line(&format!("round {round}: {}", "x".repeat(20)))
+                    .unwrap();
+            }
+        }
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap_or_default();
+        assert!(read(path.clone()).len() < 100);
+        assert!(read(log.rotated_path(1)).starts_with("round 4"));
+        assert!(read(log.rotated_path(3)).starts_with("round 2"));
+        assert!(!log.rotated_path(4).exists());
+    }
+}
@@ -1,32 +1,42 @@
-use std::{env, fs::File, io::stdout};
+use std::{
+    env, fs,
+    io::stdout,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
 use anyhow::{Context, Result};
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use log::{error, info};
+use log::{error, info, warn};
 use ratatui::{Terminal, backend::CrosstermBackend};
-use simplelog::{LevelFilter, WriteLogger};
 
 // Use modules from the library crate
-use bookokcat::event_source::KeyboardEventSource;
+use bookokcat::app_dirs;
+use bookokcat::color_mode::supports_hyperlinks;
+use bookokcat::deep_link::DeepLink;
+use bookokcat::event_source::{
+    EventSource, KeyboardEventSource, RecordingEventSource, ReplayEventSource,
+};
+use bookokcat::inputs::session_script::{SessionRecorder, parse_script};
+use bookokcat::logging;
 use bookokcat::main_app::{App, run_app_with_event_source};
 use bookokcat::panic_handler;
+use bookokcat::preferences::Preferences;
+use bookokcat::remote_book::parse_book_source;
+use bookokcat::terminal_title;
 
 fn main() -> Result<()> {
-    // Initialize logging with html5ever DEBUG logs filtered out
-    WriteLogger::init(
-        LevelFilter::Debug,
-        simplelog::ConfigBuilder::new()
-            .set_max_level(LevelFilter::Debug)
-            .add_filter_ignore_str("html5ever")
-            .build(),
-        File::create("bookokcat.log")?,
-    )?;
-
     let args: Vec<String> = env::args().skip(1).collect();
+
+    app_dirs::init(args.iter().any(|arg| arg == "--portable"))?;
+    // Debug logging with html5ever filtered out, until preferences or `:loglevel` say otherwise
+    logging::init(&log_file_path(&args))?;
+    app_dirs::adopt_legacy_files();
+
     if matches!(args.first().map(|s| s.as_str()), Some("--debug-pdf")) {
         let pdf_path = args
             .get(1)
@@ -41,6 +51,66 @@ fn main() -> Result<()> {
         return result;
     }
 
+    if matches!(args.first().map(|s| s.as_str()), Some("render-html")) {
+        return run_render_html(&args[1..]);
+    }
+
+    if matches!(args.first().map(|s| s.as_str()), Some("check")) {
+        return run_check(&args[1..]);
+    }
+
+    if matches!(args.first().map(|s| s.as_str()), Some("remind")) {
+        return run_remind();
+    }
+
+    if matches!(args.first().map(|s| s.as_str()), Some("migrate")) {
+        return run_migrate(&args[1..]);
+    }
+
+    let mut deep_link = None;
+    let mut book_source = None;
+    let mut replay_script = None;
+    let mut session_recorder = None;
+    let mut live_reload = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => {
+                let path = args
+                    .next()
+                    .context("Usage: bookokcat --replay <script-file>")?;
+                let script = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read replay script {path}"))?;
+                replay_script = Some(
+                    parse_script(&script)
+                        .with_context(|| format!("Invalid replay script {path}"))?,
+                );
+            }
+            "--log-file" => {
+                // Already taken into account when logging was set up
+                args.next();
+            }
+            "--record" => {
+                let path = args
+                    .next()
+                    .context("Usage: bookokcat --record <script-file>")?;
+                let recorder = SessionRecorder::create(Path::new(&path))
+                    .with_context(|| format!("Failed to create session recording {path}"))?;
+                session_recorder = Some(Arc::new(Mutex::new(recorder)));
+            }
+            "--live" => live_reload = true,
+            // Already taken into account when the storage directories were set up
+            "--portable" => {}
+            arg if DeepLink::is_deep_link(arg) => {
+                deep_link = Some(DeepLink::parse(arg).map_err(|e| anyhow::anyhow!(e))?);
+            }
+            arg if !arg.starts_with("--") && parse_book_source(arg).is_some() => {
+                book_source = parse_book_source(arg);
+            }
+            _ => {}
+        }
+    }
+
     // Initialize panic handler only for interactive TUI mode
     panic_handler::initialize_panic_handler();
 
@@ -55,8 +125,16 @@ fn main() -> Result<()> {
         )
     })?;
     let mut stdout = stdout();
+    // Put back on exit whatever title the terminal had before
+    let _ = terminal_title::push_title(&mut stdout);
 
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture).map_err(|e| {
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )
+    .map_err(|e| {
         error!("Failed to setup terminal: {e}");
         let _ = disable_raw_mode();
         anyhow::anyhow!(
@@ -67,19 +145,52 @@ fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    // Upgrade files from earlier versions before they are read
+    let migration_error = migrate_on_start();
+
     // Create app and run it
     let mut app = App::new();
-    let mut event_source = KeyboardEventSource;
-    let res = run_app_with_event_source(&mut terminal, &mut app, &mut event_source);
+    if let Some(message) = migration_error {
+        app.show_error(message);
+    }
+    app.set_terminal_hyperlinks(supports_hyperlinks());
+    app.set_terminal_window_title(true);
+    if let Some(link) = &deep_link {
+        if let Err(e) = app.open_deep_link(link) {
+            error!("Failed to open {link}: {e}");
+            app.show_error(format!("Failed to open link: {e}"));
+        }
+    }
+    if let Some(source) = book_source {
+        info!("Opening {source:?} from the command line");
+        app.open_book_source_on_start(source);
+    }
+    app.set_welcome_screen(true);
+    app.set_live_reload(live_reload);
+    let mut event_source: Box<dyn EventSource> = match replay_script {
+        Some(events) => {
+            info!("Replaying {} scripted events", events.len());
+            Box::new(ReplayEventSource::new(events, KeyboardEventSource))
+        }
+        None => Box::new(KeyboardEventSource),
+    };
+    if let Some(recorder) = session_recorder {
+        info!("Recording session input");
+        app.set_session_recorder(recorder.clone());
+        event_source = Box::new(RecordingEventSource::new(event_source, recorder));
+    }
+    let res = run_app_with_event_source(&mut terminal, &mut app, event_source.as_mut());
 
     // Restore terminal state
     let _ = disable_raw_mode();
     let _ = execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     );
     let _ = terminal.show_cursor();
+    let _ = terminal_title::pop_title(terminal.backend_mut());
 
     if let Err(err) = res {
         error!("Application error: {err:?}");
@@ -90,6 +201,162 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// `bookokcat render-html <file.html|-> [--width N]`: print an HTML snippet as the reader
+/// would lay it out, without starting the TUI
+fn run_render_html(args: &[String]) -> Result<()> {
+    use bookokcat::markdown_text_reader::MarkdownTextReader;
+    use std::io::Read;
+
+    const USAGE: &str = "Usage: bookokcat render-html <file.html|-> [--width N]";
+
+    let mut path = None;
+    let mut width = 80;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => {
+                let value = args.next().context(USAGE)?;
+                width = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .with_context(|| format!("Invalid width '{value}'"))?;
+            }
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => anyhow::bail!(USAGE),
+        }
+    }
+
+    let html = match path.context(USAGE)? {
+        "-" => {
+            let mut html = String::new();
+            std::io::stdin().read_to_string(&mut html)?;
+            html
+        }
+        path => fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?,
+    };
+    print!(
+        "{}",
+        MarkdownTextReader::render_html_to_plain_text(&html, width)
+    );
+    Ok(())
+}
+
+/// `bookokcat check <file.epub>`: list structural problems in an EPUB without starting
+/// the TUI. Exits with status 1 when problems are found, so it can be used in scripts.
+fn run_check(args: &[String]) -> Result<()> {
+    use bookokcat::epub_check::check_epub;
+
+    let [path] = args else {
+        anyhow::bail!("Usage: bookokcat check <file.epub>");
+    };
+    let report = check_epub(Path::new(path));
+    for line in report.lines() {
+        println!("{line}");
+    }
+    println!("{}", report.summary());
+    if !report.issues.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `bookokcat migrate [bookmarks.json]`: upgrade the bookmarks file to the current format,
+/// keeping the old one as a backup beside it. Also done on every start.
+fn run_migrate(args: &[String]) -> Result<()> {
+    use bookokcat::migrate::{Migration, migrate_bookmarks_file};
+
+    let path = match args {
+        [] => app_dirs::bookmarks_file(),
+        [path] => PathBuf::from(path),
+        _ => anyhow::bail!("Usage: bookokcat migrate [bookmarks.json]"),
+    };
+    match migrate_bookmarks_file(&path)? {
+        Migration::Missing => println!("No bookmarks at {}", path.display()),
+        Migration::UpToDate => println!("{} is up to date", path.display()),
+        Migration::Upgraded { upgrade, backup } => {
+            println!("Upgraded {} from format {}:", path.display(), upgrade.from);
+            for change in &upgrade.changes {
+                println!("  {change}");
+            }
+            println!("The old file is kept as {}", backup.display());
+        }
+    }
+    Ok(())
+}
+
+/// Upgrade the bookmarks file before the app reads it. Returns a message to show when it
+/// can't be, in which case it is left untouched.
+fn migrate_on_start() -> Option<String> {
+    use bookokcat::migrate::{Migration, migrate_bookmarks_file};
+
+    match migrate_bookmarks_file(&app_dirs::bookmarks_file()) {
+        Ok(Migration::Upgraded { upgrade, backup }) => {
+            info!(
+                "Upgraded bookmarks from format {}: {}; old file kept as {}",
+                upgrade.from,
+                upgrade.changes.join(", "),
+                backup.display()
+            );
+            None
+        }
+        Ok(Migration::Missing | Migration::UpToDate) => None,
+        Err(e) if bookokcat::migrate::is_newer_format(&e) => {
+            warn!("Bookmarks are from a newer bookokcat: {e:#}");
+            Some(
+                "Bookmarks are from a newer bookokcat; reading positions won't be saved"
+                    .to_string(),
+            )
+        }
+        Err(e) => {
+            error!("Bookmarks migration failed: {e:#}");
+            Some(format!("Bookmarks not upgraded: {e:#}"))
+        }
+    }
+}
+
+/// `bookokcat remind`: meant to run from cron; sends a desktop notification when it's past
+/// `reading_reminder_hour` and no book has been read today
+fn run_remind() -> Result<()> {
+    use bookokcat::bookmarks::Bookmarks;
+    use bookokcat::reading_goal::reminder_due;
+    use bookokcat::system_command::send_desktop_notification;
+
+    let preferences_file = app_dirs::preferences_file();
+    let preferences = Preferences::load_or_ephemeral(preferences_file.to_str());
+    let Some(hour) = preferences.reading_reminder_hour else {
+        println!("No reading_reminder_hour set in preferences.json");
+        return Ok(());
+    };
+    let bookmarks_file = app_dirs::bookmarks_file();
+    let bookmarks = Bookmarks::load_or_ephemeral(bookmarks_file.to_str());
+    let last_read = bookmarks
+        .get_most_recent()
+        .map(|(_, bookmark)| bookmark.last_read.with_timezone(&chrono::Local));
+    if reminder_due(chrono::Local::now(), hour, last_read) {
+        info!("Sending reading reminder");
+        send_desktop_notification("bookokcat", "You haven't read today yet")
+            .map_err(|e| anyhow::anyhow!(e))?;
+    }
+    Ok(())
+}
+
+/// Where to log: `--log-file <path>`, else `log_file` in preferences, else the state
+/// directory
+fn log_file_path(args: &[String]) -> PathBuf {
+    args.iter()
+        .position(|arg| arg == "--log-file")
+        .and_then(|idx| args.get(idx + 1))
+        .map(PathBuf::from)
+        .or_else(|| {
+            Preferences::load_from_file(&app_dirs::preferences_file().to_string_lossy())
+                .ok()
+                .and_then(|preferences| preferences.log_file)
+                .map(PathBuf::from)
+        })
+        .unwrap_or_else(app_dirs::log_file)
+}
+
 fn run_pdf_debug(pdf_path: &str) -> Result<()> {
     use bookokcat::book_manager::BookManager;
     use bookokcat::pdf_handler::{
@@ -1,28 +1,74 @@
+use crate::book_club::{SharedReading, reader_name};
 use crate::book_manager::BookManager;
+use crate::book_notes::{BookNotes, NOTES_HREF, NOTES_TITLE};
 use crate::book_search::{BookSearch, BookSearchAction};
 use crate::book_stat::{BookStat, BookStatAction};
+use crate::book_watcher::BookWatcher;
 use crate::bookmarks::Bookmarks;
-use crate::comments::BookComments;
+use crate::chapter_cache::{CachedChapter, ChapterCache};
+use crate::code_export::{append_snippet, extension_for_language};
+use crate::command_line::{Command, CommandLine, CommandLineAction};
+use crate::comments::{BookComments, Comment};
+use crate::deep_link::DeepLink;
 use crate::event_source::EventSource;
+use crate::failure_alert::Alerts;
+use crate::file_lock::FileLock;
+use crate::flashcards::{Flashcard, passage_words, to_anki_tsv};
+use crate::focus::{FocusDirection, FocusRing};
 use crate::images::book_images::BookImages;
-use crate::images::image_popup::ImagePopup;
+use crate::images::image_popup::{ImagePopup, ImagePopupAction};
 use crate::images::image_storage::ImageStorage;
-use crate::inputs::{ClickType, KeySeq, MouseTracker, map_keys_to_input};
+use crate::inputs::session_script::SessionRecorder;
+use crate::inputs::{
+    ClickType, KeySeq, KeymapPreset, MAX_NAV_PERCENT, MIN_NAV_PERCENT, MouseTracker,
+    map_keys_to_input,
+};
 use crate::jump_list::{JumpList, JumpLocation};
-use crate::markdown_text_reader::MarkdownTextReader;
-use crate::navigation_panel::{CurrentBookInfo, NavigationPanel, TableOfContents};
-use crate::notification::NotificationManager;
+use crate::logging;
+use crate::markdown::{Document, Inline, LinkType};
+use crate::markdown_text_reader::{GutterMarker, MarkdownTextReader, PointOfInterest};
+use crate::navigation_panel::{
+    BookList, CurrentBookInfo, NavigationMode, NavigationPanel, ReadingOrderEditor, TableOfContents,
+};
+use crate::notification::{Notification, NotificationManager};
+use crate::parsing::conversion_diagnostics::{BookHealth, doctor_report};
+use crate::parsing::css::StyleSheet;
+use crate::parsing::substitutions::Substitutions;
 use crate::parsing::text_generator::TextGenerator;
-use crate::parsing::toc_parser::TocParser;
+use crate::parsing::toc_parser::{PageTarget, TocParser};
 use crate::preferences::Preferences;
+use crate::quote_capture::{
+    BookReference, Citation, Locator, ReferenceFormat, find_isbn, format_quote,
+};
+use crate::reading_goal::GoalTracker;
 use crate::reading_history::ReadingHistory;
-use crate::search::{SearchMode, SearchablePanel};
+use crate::remote_book::{BookSource, DownloadEvent};
+use crate::search::{SearchMode, SearchablePanel, search_wrapped};
 use crate::search_engine::SearchEngine;
 use crate::system_command::{RealSystemCommandExecutor, SystemCommandExecutor};
-use crate::table_of_contents::TocItem;
-use crate::theme::OCEANIC_NEXT;
+use crate::table_export::{table_grid, to_csv, to_tsv};
+use crate::table_of_contents::{TocItem, TocLengthIndicator};
+use crate::terminal_title;
+use crate::text_stats::TextStats;
+use crate::theme::current_theme;
+use crate::timeline::{TimelineCollector, TimelineEntry};
 use crate::types::LinkInfo;
+use crate::undo::{UndoEntry, UndoHistory};
+use crate::vocabulary::{VocabularyEntry, append_entry, look_up_definition, sentence_containing};
+use crate::widget::chapter_summary_popup::ChapterSummaryPopup;
+use crate::widget::count_popup::{CountAction, CountPopup};
+use crate::widget::dock_panel::{DockAction, DockEntry, DockKind, DockPanel, DockTarget};
+use crate::widget::edition_pane::EditionPane;
+use crate::widget::entity_popup::{EntityAction, EntityPopup};
+use crate::widget::frame_stats_overlay::{self, CacheFigures, FrameStats};
 use crate::widget::help_popup::{HelpPopup, HelpPopupAction};
+use crate::widget::page_list_popup::{PageListAction, PageListPopup};
+use crate::widget::quick_switcher_popup::{
+    QuickSwitcherAction, QuickSwitcherPopup, SwitchTarget, chapter_entries,
+};
+use crate::widget::resources_popup::{ResourcesAction, ResourcesPopup, extract_resources};
+use crate::widget::timeline_popup::{TimelineAction, TimelinePopup};
+use crate::widget::welcome_screen::WelcomeScreen;
 use image::GenericImageView;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -31,6 +77,7 @@ pub enum ChapterDirection {
     Previous,
 }
 
+use std::collections::HashMap;
 use std::io::BufReader;
 use std::path::Path;
 use std::sync::{Arc, Mutex, mpsc};
@@ -79,39 +126,119 @@ pub enum AppAction {
     Quit,
 }
 
+/// A program to run in place of the TUI, on a file; see `App::take_external_handoff`
+pub struct ExternalHandoff {
+    pub program: String,
+    pub path: std::path::PathBuf,
+    /// Set when `path` was written just for the program; it's deleted with the handoff
+    pub temp_file: Option<tempfile::TempPath>,
+}
+
 pub struct App {
     pub book_manager: BookManager,
     pub navigation_panel: NavigationPanel,
     text_reader: MarkdownTextReader,
     bookmarks: Bookmarks,
     preferences: Preferences,
+    book_notes: Arc<BookNotes>,
+    /// Alternate TOC rebuilt from the H1–H3 headings of the current book
+    heading_toc: Vec<TocItem>,
+    /// Dated headings and epigraphs of the open book, in date order
+    timeline: Vec<TimelineEntry>,
+    /// Chapters already converted from HTML, filled ahead of time by the prefetcher
+    chapter_cache: ChapterCache,
+    /// Element ids of the current book's chapters, by spine index, for checking links
+    chapter_anchor_targets: HashMap<usize, crate::epub_check::AnchorTargets>,
+    /// Set by `:compare`: another edition shown beside the reader
+    edition_pane: Option<EditionPane>,
+    /// Set after `:prefetch all` until every chapter has been parsed
+    prefetch_all_running: bool,
+    /// The `:` prompt, while it is open
+    command_line: Option<CommandLine>,
+    /// Set by `--record`; notes state changes next to the recorded input
+    session_recorder: Option<Arc<Mutex<SessionRecorder>>>,
+    /// Advisory lock telling other instances that the current book is open here
+    book_lock: Option<FileLock>,
+    /// Set by `:live` or `--live`: the open book is reloaded whenever its file is saved
+    live_reload: bool,
+    book_watcher: Option<BookWatcher>,
+    /// Whether the terminal's window title follows the book and chapter being read
+    window_title_updates: bool,
+    /// The window title last sent to the terminal
+    last_window_title: Option<String>,
+    /// Drawn in the reader pane while no book is open, once `main` asks for it
+    welcome_screen: Option<WelcomeScreen>,
+    virtual_chapter_active: bool,
+    pending_external_link: Option<String>,
+    /// Problems found while opening the book, shown by the event loop once it's on screen
+    pending_book_health: Option<BookHealth>,
+    /// Manual day/night choice as (is_day, scheduled is_day when it was made); it lasts
+    /// until the schedule itself flips
+    theme_override: Option<(bool, bool)>,
     book_images: BookImages,
     current_book: Option<EpubBook>,
     pub focused_panel: FocusedPanel,
     previous_main_panel: MainPanel,
+    /// The main panels as last drawn, for Tab and `Ctrl+W` to move between
+    focus_ring: FocusRing<MainPanel>,
+    /// `Ctrl+W` was pressed; the next key says which panel to focus
+    window_command_pending: bool,
+    /// Lists docked under the reader with `:panel`, in the order they were opened
+    docks: Vec<DockPanel>,
     pub system_command_executor: Box<dyn SystemCommandExecutor>,
     last_bookmark_save: std::time::Instant,
     mouse_tracker: MouseTracker,
     key_sequence: KeySeq,
+    /// Where the reader was before a Space in page mode turned the page on screen. The
+    /// turn is only saved (or carried on to the next chapter) once the next key shows it
+    /// didn't start a Space+key command, which puts the page back.
+    pending_page_turn: Option<usize>,
     reading_history: Option<ReadingHistory>,
     image_popup: Option<ImagePopup>,
     terminal_size: Rect,
     profiler: Arc<Mutex<Option<pprof::ProfilerGuard<'static>>>>,
+    /// Recent draw times and whether the `Space+m` frame stats overlay is shown
+    frame_stats: FrameStats,
+    show_frame_stats: bool,
+    reading_goal: GoalTracker,
+    /// Other readers' positions and notes imported with `:club import`, for this book
+    shared_readings: Vec<SharedReading>,
+    /// Chapter and furthest scroll offset reached in it, for counting pages towards the goal
+    goal_position: Option<(usize, usize)>,
     book_stat: BookStat,
     jump_list: JumpList,
+    undo_history: UndoHistory,
     book_search: Option<BookSearch>,
+    /// The search index or the TOC changed since TOC word counts were last filled in
+    toc_word_counts_pending: bool,
     help_popup: Option<HelpPopup>,
     chatgpt_popup: Option<crate::widget::chatgpt_popup::ChatGPTPopup>,
     language_select_popup: Option<crate::widget::language_select_popup::LanguageSelectPopup>,
+    resources_popup: Option<ResourcesPopup>,
+    page_list_popup: Option<PageListPopup>,
+    chapter_summary_popup: Option<ChapterSummaryPopup>,
+    entity_popup: Option<EntityPopup>,
+    count_popup: Option<CountPopup>,
+    timeline_popup: Option<TimelinePopup>,
+    quick_switcher_popup: Option<QuickSwitcherPopup>,
+    /// Print pages of the current book, from its page-list navigation
+    page_list: Vec<PageTarget>,
     summary_language: crate::widget::language_select_popup::Language,
     summary_sender: mpsc::Sender<Result<String, String>>,
     summary_receiver: mpsc::Receiver<Result<String, String>>,
     pdf_load_sender: mpsc::Sender<Result<PdfLoadSuccess, String>>,
     pdf_load_receiver: mpsc::Receiver<Result<PdfLoadSuccess, String>>,
+    download_sender: mpsc::Sender<DownloadEvent>,
+    download_receiver: mpsc::Receiver<DownloadEvent>,
+    /// Title of the book being downloaded, once read from the server
+    download_title: Option<String>,
     notifications: NotificationManager,
+    /// Flash or bell raised by an action that couldn't be done
+    alerts: Alerts,
     help_bar_area: Rect,
     deferred_book_path: Option<String>,
     deferred_book_index: Option<usize>,
+    external_handoff: Option<ExternalHandoff>,
     progress_dialog: Arc<Mutex<crate::widget::progress_dialog::ProgressDialog>>,
 }
 
@@ -136,6 +263,8 @@ pub enum FocusedPanel {
 pub enum MainPanel {
     NavigationList,
     Content,
+    /// A list docked under the reader
+    Dock(DockKind),
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
@@ -147,6 +276,13 @@ pub enum PopupWindow {
     Help,
     ChatGPT,
     LanguageSelect,
+    Resources,
+    PageList,
+    ChapterSummary,
+    Entities,
+    Timeline,
+    Count,
+    QuickSwitcher,
 }
 
 impl Default for App {
@@ -157,7 +293,8 @@ impl Default for App {
 
 impl App {
     pub fn new() -> Self {
-        Self::new_with_config(None, Some("bookmarks.json"), true)
+        let bookmarks_file = crate::app_dirs::bookmarks_file();
+        Self::new_with_config(None, bookmarks_file.to_str(), true)
     }
 
     fn setup_pdf_progress_callback(&mut self) {
@@ -184,6 +321,190 @@ impl App {
         self.focused_panel = FocusedPanel::Main(panel);
     }
 
+    /// Focus another main panel, as Tab and `Ctrl+W` do
+    fn move_focus_to(&mut self, panel: MainPanel) {
+        if panel == MainPanel::Content {
+            self.navigation_panel
+                .table_of_contents
+                .clear_manual_navigation();
+        }
+        self.set_main_panel_focus(panel);
+    }
+
+    /// Tab / Shift+Tab: the next or previous panel on screen
+    fn cycle_focus(&mut self, forward: bool) {
+        let FocusedPanel::Main(current) = self.focused_panel else {
+            return;
+        };
+        let next = self
+            .focus_ring
+            .cycle(current, forward)
+            .unwrap_or(match current {
+                MainPanel::NavigationList => MainPanel::Content,
+                MainPanel::Content | MainPanel::Dock(_) => MainPanel::NavigationList,
+            });
+        self.move_focus_to(next);
+    }
+
+    /// `Ctrl+W` and the key after it: `h`/`j`/`k`/`l` (or the arrows) focus the panel in
+    /// that direction, `w`/`W` the next/previous one, like between vim windows
+    fn handle_window_command(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::KeyCode;
+
+        let FocusedPanel::Main(current) = self.focused_panel else {
+            return;
+        };
+        let direction = match key.code {
+            KeyCode::Left => Some(FocusDirection::Left),
+            KeyCode::Down => Some(FocusDirection::Down),
+            KeyCode::Up => Some(FocusDirection::Up),
+            KeyCode::Right => Some(FocusDirection::Right),
+            KeyCode::Char('w') => return self.cycle_focus(true),
+            KeyCode::Char('W') => return self.cycle_focus(false),
+            KeyCode::Char(c) => FocusDirection::from_key(c.to_ascii_lowercase()),
+            _ => None,
+        };
+        if let Some(panel) =
+            direction.and_then(|direction| self.focus_ring.neighbor(current, direction))
+        {
+            self.move_focus_to(panel);
+        }
+    }
+
+    /// `:panel <name>`: dock a list under the reader and focus it, or close it if it's
+    /// already there
+    fn toggle_dock(&mut self, kind: DockKind) {
+        if let Some(index) = self.docks.iter().position(|dock| dock.kind == kind) {
+            self.close_dock(index);
+            return;
+        }
+        self.docks.push(DockPanel::new(kind));
+        self.refresh_docks();
+        self.move_focus_to(MainPanel::Dock(kind));
+    }
+
+    fn close_dock(&mut self, index: usize) {
+        let kind = self.docks.remove(index).kind;
+        if self.is_main_panel(MainPanel::Dock(kind)) {
+            self.move_focus_to(MainPanel::Content);
+        }
+        if self.previous_main_panel == MainPanel::Dock(kind) {
+            self.previous_main_panel = MainPanel::Content;
+        }
+    }
+
+    /// Bring the docked lists up to date with the search, comments and log
+    fn refresh_docks(&mut self) {
+        for index in 0..self.docks.len() {
+            let entries = self.dock_entries(self.docks[index].kind);
+            self.docks[index].set_entries(entries);
+        }
+    }
+
+    fn dock_entries(&self, kind: DockKind) -> Vec<DockEntry> {
+        match kind {
+            DockKind::SearchResults => {
+                let Some(book_search) = &self.book_search else {
+                    return Vec::new();
+                };
+                let (query, results) = book_search.last_results();
+                results
+                    .iter()
+                    .map(|result| DockEntry {
+                        text: format!("{}: {}", result.chapter_title, result.snippet.trim()),
+                        target: Some(DockTarget::SearchHit {
+                            chapter_index: result.chapter_index,
+                            line_number: result.line_number,
+                            query: query.to_string(),
+                        }),
+                    })
+                    .collect()
+            }
+            DockKind::Annotations => {
+                let mut comments: Vec<(Option<usize>, Comment)> = self
+                    .text_reader
+                    .all_comments()
+                    .into_iter()
+                    .map(|comment| {
+                        (
+                            self.find_spine_index_by_href(&comment.chapter_href),
+                            comment,
+                        )
+                    })
+                    .collect();
+                comments.sort_by_key(|(chapter, comment)| (*chapter, comment.paragraph_index));
+                comments
+                    .into_iter()
+                    .map(|(chapter, comment)| DockEntry {
+                        text: format!(
+                            "Ch {}: {}",
+                            chapter
+                                .map_or_else(|| "?".to_string(), |index| (index + 1).to_string()),
+                            comment.content.lines().next().unwrap_or_default()
+                        ),
+                        target: Some(DockTarget::Paragraph {
+                            chapter_href: comment.chapter_href,
+                            node_index: comment.paragraph_index,
+                        }),
+                    })
+                    .collect()
+            }
+            DockKind::Log => logging::recent_lines()
+                .into_iter()
+                .map(|text| DockEntry { text, target: None })
+                .collect(),
+        }
+    }
+
+    /// Keys for a docked list that has focus; false if the list leaves the key alone
+    fn handle_dock_key(&mut self, kind: DockKind, key: crossterm::event::KeyEvent) -> bool {
+        let Some(index) = self.docks.iter().position(|dock| dock.kind == kind) else {
+            return false;
+        };
+        match self.docks[index].handle_key(key) {
+            None => return false,
+            Some(DockAction::Moved) => {}
+            Some(DockAction::Close) => self.close_dock(index),
+            Some(DockAction::Jump(target)) => self.jump_to_dock_target(target),
+        }
+        true
+    }
+
+    fn jump_to_dock_target(&mut self, target: DockTarget) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        self.jump_list.push(JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+        });
+        self.move_focus_to(MainPanel::Content);
+        let result = match target {
+            DockTarget::SearchHit {
+                chapter_index,
+                line_number,
+                query,
+            } => self.navigate_to_chapter(chapter_index).map(|_| {
+                self.text_reader
+                    .store_pending_text_line_scroll(line_number, query);
+            }),
+            DockTarget::Paragraph {
+                chapter_href,
+                node_index,
+            } => match self.find_spine_index_by_href(&chapter_href) {
+                Some(chapter_index) => self.navigate_to_chapter(chapter_index).map(|_| {
+                    self.text_reader.restore_to_node_index(node_index);
+                }),
+                None => Err(anyhow::anyhow!("{chapter_href} is not in this book")),
+            },
+        };
+        if let Err(e) = result {
+            error!("Failed to jump from the panel: {e}");
+            self.show_error(format!("Failed to jump: {e}"));
+        }
+    }
+
     /// Close current popup and return focus to previous main panel
     fn close_popup_to_previous(&mut self) {
         self.focused_panel = FocusedPanel::Main(self.previous_main_panel);
@@ -208,15 +529,7 @@ impl App {
     /// Handle search input
     fn handle_search_input(&mut self, c: char) {
         if self.navigation_panel.is_searching() {
-            if self.navigation_panel.is_in_book_mode() {
-                // Book list search uses character input field
-                self.navigation_panel.handle_search_char(c);
-            } else {
-                // TOC search uses traditional query string
-                let mut query = self.navigation_panel.get_search_state().query.clone();
-                query.push(c);
-                self.navigation_panel.update_search_query(&query);
-            }
+            self.navigation_panel.handle_search_char(c);
         } else if self.text_reader.is_searching() {
             let mut query = self.text_reader.get_search_state().query.clone();
             query.push(c);
@@ -224,18 +537,74 @@ impl App {
         }
     }
 
+    /// Handle text delivered through bracketed paste. It goes into the active input field
+    /// as a whole and is never interpreted as key commands.
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Some(command_line) = &mut self.command_line {
+            command_line.insert_str(text);
+            return;
+        }
+
+        if self.text_reader.handle_comment_paste(text) {
+            return;
+        }
+
+        if self.is_search_input_mode() {
+            // Search queries are single-line
+            for c in text.chars().filter(|c| *c != '\r') {
+                self.handle_search_input(if c == '\n' { ' ' } else { c });
+            }
+            return;
+        }
+
+        // Popups with a query field take the paste as if it had been typed
+        let keys = text.chars().filter(|c| *c != '\r').map(|c| {
+            let c = if c == '\n' { ' ' } else { c };
+            crossterm::event::KeyEvent::new(
+                crossterm::event::KeyCode::Char(c),
+                crossterm::event::KeyModifiers::NONE,
+            )
+        });
+        match self.focused_panel {
+            FocusedPanel::Popup(PopupWindow::BookSearch) => {
+                if let Some(book_search) = &mut self.book_search
+                    && book_search.is_input_focused()
+                {
+                    for key in keys {
+                        book_search.handle_key_event(key);
+                    }
+                    return;
+                }
+            }
+            FocusedPanel::Popup(PopupWindow::QuickSwitcher) => {
+                if let Some(popup) = &mut self.quick_switcher_popup {
+                    for key in keys {
+                        popup.handle_key(key);
+                    }
+                    return;
+                }
+            }
+            FocusedPanel::Popup(PopupWindow::PageList) => {
+                if let Some(popup) = &mut self.page_list_popup {
+                    for key in keys {
+                        popup.handle_key(key);
+                    }
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        debug!(
+            "Ignoring paste of {} bytes outside an input field",
+            text.len()
+        );
+    }
+
     /// Handle search backspace
     fn handle_search_backspace(&mut self) {
         if self.navigation_panel.is_searching() {
-            if self.navigation_panel.is_in_book_mode() {
-                // Book list search uses character input field
-                self.navigation_panel.handle_search_backspace();
-            } else {
-                // TOC search uses traditional query string
-                let mut query = self.navigation_panel.get_search_state().query.clone();
-                query.pop();
-                self.navigation_panel.update_search_query(&query);
-            }
+            self.navigation_panel.handle_search_backspace();
         } else if self.text_reader.is_searching() {
             let mut query = self.text_reader.get_search_state().query.clone();
             query.pop();
@@ -282,6 +651,11 @@ impl App {
         self.notifications.show_error(message);
     }
 
+    /// Signal an action that couldn't be done, as the `failure_alert` preference says
+    fn alert_failure(&mut self) {
+        self.alerts.raise(self.preferences.failure_alert);
+    }
+
     #[cfg(any(test, feature = "test-utils"))]
     pub fn new_with_mock_system_executor(
         book_directory: Option<&str>,
@@ -321,10 +695,65 @@ impl App {
             None => BookManager::new(),
         };
 
-        let navigation_panel = NavigationPanel::new(&book_manager);
-        let text_reader = MarkdownTextReader::new();
+        let mut navigation_panel = NavigationPanel::new(&book_manager);
+        let mut text_reader = MarkdownTextReader::new();
         let bookmarks = Bookmarks::load_or_ephemeral(bookmark_file);
-        let preferences = Preferences::load_or_ephemeral(Some("preferences.json"));
+        let preferences_file = crate::app_dirs::preferences_file();
+        let preferences = Preferences::load_or_ephemeral(preferences_file.to_str());
+        crate::theme::set_active_theme(preferences.theme);
+        logging::set_format(preferences.log_format);
+        if let Some(spec) = &preferences.log_levels {
+            match logging::parse_directives(spec) {
+                Ok(directives) => logging::apply_directives(&directives),
+                Err(e) => error!("Ignoring log_levels in preferences: {e}"),
+            }
+        }
+        text_reader.set_bionic_reading(
+            preferences
+                .bionic_reading
+                .then_some(preferences.bionic_ratio),
+        );
+        text_reader.set_paragraph_chunks(
+            preferences
+                .paragraph_chunks
+                .then_some(preferences.paragraph_chunk_sentences),
+        );
+        text_reader.set_zoom(preferences.content_zoom, preferences.zoom_double_width);
+        text_reader.set_scroll_behavior(preferences.scrolloff, preferences.smooth_scroll);
+        text_reader.set_annotation_gutter(preferences.annotation_gutter);
+        navigation_panel
+            .table_of_contents
+            .set_scroll_padding(preferences.scrolloff);
+        navigation_panel
+            .book_list
+            .set_scroll_padding(preferences.scrolloff);
+        navigation_panel
+            .book_list
+            .set_archived(bookmarks.archived_books().clone());
+        navigation_panel
+            .table_of_contents
+            .set_length_indicator(preferences.toc_length_indicator);
+        navigation_panel
+            .table_of_contents
+            .set_cache_indicator(preferences.chapter_cache_indicator);
+        navigation_panel
+            .table_of_contents
+            .set_numbering(preferences.toc_numbering);
+        navigation_panel
+            .table_of_contents
+            .set_progress_indicator(preferences.toc_progress);
+        text_reader.set_sticky_heading(preferences.sticky_heading);
+        text_reader.set_page_mode(preferences.page_mode);
+        text_reader.set_content_filters(preferences.content_filters.clone());
+        text_reader.set_text_cleanup(preferences.text_cleanup);
+        text_reader.set_chapter_contents(preferences.chapter_contents);
+        // Until told what the terminal supports (see `set_terminal_hyperlinks`)
+        text_reader.set_hyperlinks(preferences.hyperlinks.enabled(false));
+        let reading_goal = GoalTracker::new(preferences.reading_goal);
+        let mut chapter_cache = ChapterCache::new();
+        chapter_cache.set_content_filters(preferences.content_filters.clone());
+        chapter_cache.set_text_cleanup(preferences.text_cleanup);
+        chapter_cache.set_chapter_contents(preferences.chapter_contents);
 
         let image_storage = Arc::new(ImageStorage::new_in_project_temp().unwrap_or_else(|e| {
             error!("Failed to initialize image storage: {e}. Using fallback.");
@@ -343,6 +772,7 @@ impl App {
 
         let (summary_sender, summary_receiver) = mpsc::channel();
         let (pdf_load_sender, pdf_load_receiver) = mpsc::channel();
+        let (download_sender, download_receiver) = mpsc::channel();
 
         // Load language preference
         let summary_language = match preferences.summary_language.as_str() {
@@ -364,33 +794,76 @@ impl App {
             text_reader,
             bookmarks,
             preferences,
+            book_notes: Arc::new(BookNotes::new()),
+            heading_toc: Vec::new(),
+            timeline: Vec::new(),
+            chapter_cache,
+            chapter_anchor_targets: HashMap::new(),
+            edition_pane: None,
+            prefetch_all_running: false,
+            session_recorder: None,
+            command_line: None,
+            book_lock: None,
+            live_reload: false,
+            book_watcher: None,
+            window_title_updates: false,
+            last_window_title: None,
+            welcome_screen: None,
+            virtual_chapter_active: false,
+            pending_external_link: None,
+            pending_book_health: None,
+            theme_override: None,
             book_images,
             current_book: None,
             focused_panel: FocusedPanel::Main(MainPanel::NavigationList),
             previous_main_panel: MainPanel::NavigationList,
+            focus_ring: FocusRing::default(),
+            window_command_pending: false,
+            docks: Vec::new(),
             system_command_executor: system_executor,
             last_bookmark_save: std::time::Instant::now(),
             mouse_tracker: MouseTracker::new(),
             key_sequence: KeySeq::new(),
+            pending_page_turn: None,
             reading_history: None,
             image_popup: None,
             terminal_size,
             profiler: Arc::new(Mutex::new(None)),
+            frame_stats: FrameStats::new(),
+            show_frame_stats: false,
+            reading_goal,
+            shared_readings: Vec::new(),
+            goal_position: None,
             book_stat: BookStat::new(),
             jump_list: JumpList::new(20),
+            undo_history: UndoHistory::new(),
             book_search: None,
+            toc_word_counts_pending: false,
             help_popup: None,
             chatgpt_popup: None,
             language_select_popup: None,
+            resources_popup: None,
+            page_list_popup: None,
+            chapter_summary_popup: None,
+            entity_popup: None,
+            count_popup: None,
+            timeline_popup: None,
+            quick_switcher_popup: None,
+            page_list: Vec::new(),
             summary_language,
             summary_sender,
             summary_receiver,
             pdf_load_sender,
             pdf_load_receiver,
+            download_sender,
+            download_receiver,
+            download_title: None,
             notifications: NotificationManager::new(),
+            alerts: Alerts::default(),
             help_bar_area: Rect::default(),
             deferred_book_path,
             deferred_book_index: None,
+            external_handoff: None,
             progress_dialog: Arc::new(Mutex::new(
                 crate::widget::progress_dialog::ProgressDialog::new("Loading..."),
             )),
@@ -486,6 +959,247 @@ impl App {
         self.open_book_for_reading(book_index)
     }
 
+    /// Open a book that may live outside the library directory, adding it to the list
+    pub fn open_book_from_path(&mut self, path: &Path) -> Result<()> {
+        if !path.is_file() {
+            anyhow::bail!("No such file: {}", path.display());
+        }
+        let known_books = self.book_manager.books.len();
+        let book_index = self.book_manager.add_book(path);
+        if self.book_manager.books.len() != known_books {
+            self.navigation_panel.book_list = BookList::new(&self.book_manager);
+            self.navigation_panel
+                .book_list
+                .set_archived(self.bookmarks.archived_books().clone());
+            self.navigation_panel.book_list.select_book(book_index);
+        }
+        self.open_book_for_reading(book_index)
+    }
+
+    /// Archive a book out of the library list, or restore it. Archived books stay on disk
+    /// and keep their bookmarks, comments and other state.
+    pub fn set_book_archived(&mut self, book_index: usize, archived: bool) {
+        let Some(book) = self.book_manager.get_book_info(book_index).cloned() else {
+            return;
+        };
+        self.bookmarks.set_archived(&book.path, archived);
+        self.navigation_panel
+            .book_list
+            .set_archived(self.bookmarks.archived_books().clone());
+        if archived {
+            self.show_info(format!(
+                "Archived {} (A lists archived books)",
+                book.display_name
+            ));
+        } else {
+            self.show_info(format!("Restored {} to the library", book.display_name));
+        }
+    }
+
+    /// Open a book by path, or by http(s) URL after downloading it into the cache in the
+    /// background with the progress shown in a toast
+    pub fn open_book_source(&mut self, source: BookSource) {
+        match source {
+            BookSource::Local(path) => {
+                if let Err(e) = self.open_book_from_path(&path) {
+                    self.show_error(format!("Failed to open book: {e}"));
+                }
+            }
+            BookSource::Remote(url) => self.download_book(url),
+        }
+    }
+
+    /// Whether the terminal we're drawing to understands OSC 8 hyperlinks; decides the
+    /// `auto` setting of `hyperlinks`
+    pub fn set_terminal_hyperlinks(&mut self, supported: bool) {
+        self.text_reader
+            .set_hyperlinks(self.preferences.hyperlinks.enabled(supported));
+    }
+
+    /// Let the terminal's window title show what's being read, unless the `window_title`
+    /// preference turns it off. Only the real terminal is told, so tests draw as before.
+    pub fn set_terminal_window_title(&mut self, enabled: bool) {
+        self.window_title_updates = enabled && self.preferences.window_title;
+    }
+
+    /// Greet with recent books instead of an empty reader pane when no book is being
+    /// reopened. Only the real terminal asks, so tests draw the plain pane as before.
+    pub fn set_welcome_screen(&mut self, enabled: bool) {
+        self.welcome_screen =
+            (enabled && self.current_book.is_none() && self.deferred_book_path.is_none())
+                .then(|| WelcomeScreen::new(&self.bookmarks));
+    }
+
+    /// Open a book listed on the welcome screen, which may live outside the library
+    fn open_welcome_book(&mut self, path: String) {
+        if let Err(e) = self.open_book_from_path(Path::new(&path)) {
+            error!("Failed to open {path}: {e}");
+            self.show_error(format!("Failed to open book: {e}"));
+        }
+    }
+
+    /// The window title for what's on screen, if it changed since it was last taken
+    pub fn take_window_title(&mut self) -> Option<String> {
+        if !self.window_title_updates {
+            return None;
+        }
+        let book_title = self.current_book.as_ref().map(|book| {
+            book.epub
+                .mdata("title")
+                .map(|item| item.value.clone())
+                .unwrap_or_else(|| {
+                    Path::new(&book.file)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                })
+        });
+        let chapter = self.current_book.as_ref().map(|book| {
+            self.text_reader
+                .get_chapter_title()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Chapter {}", book.current_chapter() + 1))
+        });
+        let title = terminal_title::window_title(book_title.as_deref(), chapter.as_deref());
+        if self.last_window_title.as_ref() == Some(&title) {
+            return None;
+        }
+        self.last_window_title = Some(title.clone());
+        Some(title)
+    }
+
+    /// `bookokcat <path|url>`: the book asked for replaces the most recent one that would
+    /// otherwise be reopened on start
+    pub fn open_book_source_on_start(&mut self, source: BookSource) {
+        self.deferred_book_path = None;
+        self.open_book_source(source);
+    }
+
+    /// Open the book whose path or http(s) URL is on the clipboard
+    pub fn open_book_from_clipboard(&mut self) {
+        let text = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .unwrap_or_default();
+        match crate::remote_book::parse_book_source(&text) {
+            Some(source) => self.open_book_source(source),
+            None => self.show_error(
+                "No book path or URL on the clipboard (EPUB, PDF, HTML, CBZ/CBR or TXT)",
+            ),
+        }
+    }
+
+    fn download_book(&mut self, url: String) {
+        let dest = match crate::remote_book::cache_path(&url) {
+            Ok(dest) => dest,
+            Err(e) => {
+                self.show_error(format!("Failed to download book: {e}"));
+                return;
+            }
+        };
+        if dest.is_file() {
+            if let Err(e) = self.open_book_from_path(&dest) {
+                self.show_error(format!("Failed to open book: {e}"));
+            }
+            return;
+        }
+
+        self.notifications
+            .show(Notification::persistent_info(format!(
+                "Downloading {url}..."
+            )));
+        self.download_title = None;
+        let sender = self.download_sender.clone();
+        std::thread::spawn(move || {
+            let title_sender = sender.clone();
+            let progress_sender = sender.clone();
+            match crate::remote_book::download_ranged(
+                &url,
+                &dest,
+                |title| {
+                    let _ = title_sender.send(DownloadEvent::Title(title));
+                },
+                |received, total| {
+                    let _ = progress_sender.send(DownloadEvent::Progress { received, total });
+                },
+            ) {
+                Ok(true) => {
+                    let _ = sender.send(DownloadEvent::Finished(Ok(dest)));
+                    return;
+                }
+                Ok(false) => info!("{url} doesn't answer range requests, downloading it whole"),
+                Err(e) => {
+                    let _ = sender.send(DownloadEvent::Finished(Err(e.to_string())));
+                    return;
+                }
+            }
+            let rt = match tokio::runtime::Runtime::new() {
+                Ok(rt) => rt,
+                Err(e) => {
+                    error!("Failed to create tokio runtime: {e}");
+                    let _ = sender.send(DownloadEvent::Finished(Err(format!(
+                        "Failed to create async runtime: {e}"
+                    ))));
+                    return;
+                }
+            };
+            let result = rt.block_on(crate::remote_book::download(
+                &url,
+                &dest,
+                |received, total| {
+                    let _ = sender.send(DownloadEvent::Progress { received, total });
+                },
+            ));
+            let _ = sender.send(DownloadEvent::Finished(
+                result.map(|_| dest).map_err(|e| e.to_string()),
+            ));
+        });
+    }
+
+    /// Apply download progress reported by the background thread. Returns true if
+    /// anything changed on screen.
+    fn receive_download_events(&mut self) -> bool {
+        let mut changed = false;
+        let mut progress = None;
+        while let Ok(event) = self.download_receiver.try_recv() {
+            changed = true;
+            match event {
+                DownloadEvent::Title(title) => self.download_title = Some(title),
+                DownloadEvent::Progress { received, total } => progress = Some((received, total)),
+                DownloadEvent::Finished(Ok(path)) => {
+                    progress = None;
+                    self.notifications.dismiss();
+                    if let Err(e) = self.open_book_from_path(&path) {
+                        self.show_error(format!("Failed to open book: {e}"));
+                    }
+                }
+                DownloadEvent::Finished(Err(e)) => {
+                    progress = None;
+                    error!("Book download failed: {e}");
+                    self.show_error(format!("Download failed: {e}"));
+                }
+            }
+        }
+        if let Some((received, total)) = progress {
+            const MB: f64 = 1024.0 * 1024.0;
+            let what = self
+                .download_title
+                .as_ref()
+                .map_or_else(String::new, |title| format!(" {title}"));
+            let message = match total {
+                Some(total) if total > 0 => format!(
+                    "Downloading{what}... {:.1} of {:.1} MB ({}%)",
+                    received as f64 / MB,
+                    total as f64 / MB,
+                    received * 100 / total
+                ),
+                _ => format!("Downloading{what}... {:.1} MB", received as f64 / MB),
+            };
+            self.notifications
+                .show(Notification::persistent_info(message));
+        }
+        changed
+    }
+
     /// Navigate to a specific chapter - ensures all state is properly updated
     pub fn navigate_to_chapter(&mut self, chapter_index: usize) -> Result<()> {
         if let Some(doc) = &mut self.current_book {
@@ -509,15 +1223,27 @@ impl App {
 
     /// Navigate to next or previous chapter - maintains all state consistency
     pub fn navigate_chapter_relative(&mut self, direction: ChapterDirection) -> Result<()> {
+        let custom_target = self.reading_order_step(direction);
         if let Some(book) = &mut self.current_book {
-            if (direction == ChapterDirection::Next && book.epub.go_next())
-                || (direction == ChapterDirection::Previous && book.epub.go_prev())
-            {
+            if direction == ChapterDirection::Next {
+                // Moving on to the next chapter means this one was read
+                self.bookmarks
+                    .mark_chapter_read(&book.file, book.current_chapter());
+            }
+            let moved = match custom_target {
+                Some(target) => target.is_some_and(|idx| book.epub.set_current_chapter(idx)),
+                None => {
+                    (direction == ChapterDirection::Next && book.epub.go_next())
+                        || (direction == ChapterDirection::Previous && book.epub.go_prev())
+                }
+            };
+            if moved {
                 self.update_content();
                 self.update_toc_state();
                 self.save_bookmark_with_throttle(true);
                 Ok(())
             } else {
+                self.alert_failure();
                 anyhow::bail!("Already at the end/beginning of the book")
             }
         } else {
@@ -525,6 +1251,67 @@ impl App {
         }
     }
 
+    /// The chapter before or after the current one in the book's custom reading order.
+    /// `None` when the book has no custom order or the current chapter isn't in it;
+    /// `Some(None)` at either end of the order.
+    fn reading_order_step(&self, direction: ChapterDirection) -> Option<Option<usize>> {
+        let book = self.current_book.as_ref()?;
+        let order = self.bookmarks.reading_order(&book.file);
+        let current_href = Self::get_chapter_href(&book.epub, book.current_chapter())?;
+        let position = order.iter().position(|href| *href == current_href)?;
+        let target = match direction {
+            ChapterDirection::Next => order.get(position + 1),
+            ChapterDirection::Previous => position.checked_sub(1).and_then(|idx| order.get(idx)),
+        };
+        Some(target.and_then(|href| Self::find_chapter_index_by_href(&book.epub, href)))
+    }
+
+    /// Put the TOC in reorder mode, listing the chapters in the book's reading order
+    fn edit_reading_order(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let toc_titles: Vec<(usize, String)> = self
+            .navigation_panel
+            .table_of_contents
+            .titled_hrefs()
+            .into_iter()
+            .filter_map(|(href, title)| Some((self.find_spine_index_by_href(&href)?, title)))
+            .collect();
+        let chapters = (0..book.total_chapters())
+            .filter_map(|chapter_index| {
+                let href = Self::get_chapter_href(&book.epub, chapter_index)?;
+                let title = toc_titles
+                    .iter()
+                    .find(|(idx, _)| *idx == chapter_index)
+                    .map(|(_, title)| title.clone())
+                    .unwrap_or_else(|| href.rsplit('/').next().unwrap_or(&href).to_string());
+                Some((href, title))
+            })
+            .collect();
+        self.navigation_panel.reading_order = Some(ReadingOrderEditor::new(
+            chapters,
+            self.bookmarks.reading_order(&book.file),
+        ));
+    }
+
+    fn save_reading_order(&mut self, order: Vec<String>) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let spine_order: Vec<String> = (0..book.total_chapters())
+            .filter_map(|chapter_index| Self::get_chapter_href(&book.epub, chapter_index))
+            .collect();
+        if order == spine_order {
+            self.bookmarks.set_reading_order(&book.file, Vec::new());
+            self.show_info("Reading in book order");
+        } else {
+            let count = order.len();
+            self.bookmarks.set_reading_order(&book.file, order);
+            self.show_info(format!("Custom reading order of {count} chapters saved"));
+        }
+    }
+
     pub fn switch_to_book_list_mode(&mut self) {
         self.navigation_panel.switch_to_book_mode();
         self.focused_panel = FocusedPanel::Main(MainPanel::NavigationList);
@@ -578,11 +1365,63 @@ impl App {
             error!("Failed to load book in BookImages: {e}");
         }
 
+        self.text_reader
+            .set_watchlist(self.bookmarks.watchlist(path).to_vec());
+        let styles = self
+            .preferences
+            .book_styles
+            .then(|| Arc::new(Self::load_book_styles(&mut doc)));
+        self.text_reader.set_styles(styles.clone());
+        let group_dialog = self.resolve_dialog_grouping(path, &doc);
+        self.text_reader.set_dialog_grouping(group_dialog);
+        let preserve_line_breaks = self.bookmarks.preserves_line_breaks(path);
+        self.text_reader
+            .set_line_breaks_preserved(preserve_line_breaks);
+        let (substitutions, errors) =
+            Substitutions::for_book(&self.preferences.substitutions, path);
+        for error in &errors {
+            warn!("{error}");
+        }
+        if let Some(error) = errors.first() {
+            self.show_warning(error.clone());
+        }
+        self.text_reader.set_substitutions(substitutions.clone());
+        self.chapter_cache.clear();
+        self.chapter_anchor_targets.clear();
+        self.edition_pane = None;
+        self.chapter_cache.set_styles(styles);
+        self.chapter_cache.set_dialog_grouping(group_dialog);
+        self.chapter_cache
+            .set_line_breaks_preserved(preserve_line_breaks);
+        self.chapter_cache.set_substitutions(substitutions);
+
+        // The index, timeline and notes are built from chapters converted as the reader
+        // will show them, so this book's settings above have to be in place first
         self.notifications
             .show(crate::notification::Notification::persistent_info(
                 "Initializing search engine...",
             ));
-        self.initialize_search_engine(&mut doc);
+        let (mut health, chapter_links) = self.initialize_search_engine(&mut doc);
+        health.unsupported_images = doc
+            .resources
+            .values()
+            .filter(|resource| resource.mime == "image/svg+xml")
+            .count();
+        self.text_reader.set_inline_notes(
+            self.bookmarks
+                .inlines_notes(path)
+                .then(|| self.book_notes.clone()),
+        );
+        self.prefetch_all_running = false;
+        self.undo_history.clear();
+        if !self.shared_readings.is_empty() {
+            self.shared_readings.clear();
+            self.text_reader.set_shared_comments(Vec::new());
+        }
+        self.page_list = TocParser::parse_page_list(&mut doc);
+        self.navigation_panel
+            .table_of_contents
+            .clear_chapter_word_counts();
 
         match BookComments::new(&path_buf) {
             Ok(comments) => {
@@ -596,9 +1435,17 @@ impl App {
 
         // Variables to store position to restore after content is loaded
         let mut node_to_restore = None;
+        self.text_reader.set_bookmark_marker(None);
 
         if !ignore_bookmarks && let Some(bookmark) = self.bookmarks.get_bookmark(path) {
-            let chapter_to_restore = Self::find_chapter_index_by_href(&doc, &bookmark.chapter_href);
+            // Bookmarks upgraded from the first format only have the chapter's number
+            let chapter_to_restore = if bookmark.chapter_href.is_empty() {
+                bookmark
+                    .chapter_index
+                    .filter(|&index| index < doc.get_num_chapters())
+            } else {
+                Self::find_chapter_index_by_href(&doc, &bookmark.chapter_href)
+            };
 
             if let Some(chapter_index) = chapter_to_restore {
                 if !doc.set_current_chapter(chapter_index) {
@@ -611,11 +1458,19 @@ impl App {
 
                 if let Some(node_idx) = bookmark.node_index {
                     node_to_restore = Some(node_idx);
+                    self.text_reader
+                        .set_bookmark_marker(Some((bookmark.chapter_href.clone(), node_idx)));
                 }
             } else {
                 warn!("Could not find chapter for href: {}", bookmark.chapter_href);
             }
+        } else if self.preferences.skip_front_matter
+            && let Some(bodymatter) = TocParser::find_bodymatter(&mut doc)
+            && doc.set_current_chapter(bodymatter)
+        {
+            info!("Skipped front matter to chapter {bodymatter}");
         } else if doc.get_num_chapters() > 1 {
+            // No hint where the body starts; the first page is usually the cover
             if doc.go_next() {
                 if doc.get_current_str().is_none() {
                     error!(
@@ -643,6 +1498,15 @@ impl App {
             }
         }
 
+        self.lock_book(path);
+        if self.live_reload
+            && self
+                .book_watcher
+                .as_ref()
+                .is_none_or(|watcher| watcher.path() != Path::new(path))
+        {
+            self.book_watcher = Some(BookWatcher::new(path));
+        }
         let current_book = EpubBook::new(path.to_string(), doc);
         self.switch_to_toc_mode(&current_book);
 
@@ -653,9 +1517,107 @@ impl App {
             self.text_reader.restore_to_node_index(node_idx);
         }
         self.notifications.dismiss();
+
+        // A standalone HTML file's links to other files aren't checked, as when reading
+        if !self.book_manager.is_html_file(path) {
+            health.broken_links = chapter_links
+                .iter()
+                .map(|links| self.broken_chapter_links(links).len())
+                .sum();
+        }
+        info!("Book health: {}", health.summary());
+        self.pending_book_health = health.has_problems().then_some(health);
         Ok(())
     }
 
+    /// Reload whichever book is open whenever its file is saved, for writers previewing
+    /// a book as they edit it
+    pub fn set_live_reload(&mut self, enabled: bool) {
+        self.live_reload = enabled;
+        self.book_watcher = self
+            .current_book
+            .as_ref()
+            .filter(|_| enabled)
+            .map(|book| BookWatcher::new(&book.file));
+    }
+
+    /// Reload the book if its file was saved since it was loaded, staying on the same
+    /// chapter and paragraph. Returns whether it was reloaded.
+    pub fn reload_changed_book(&mut self) -> bool {
+        // Notes, reports and the like don't show the book; it's reloaded once they're closed
+        if self.virtual_chapter_active {
+            return false;
+        }
+        if !self.book_watcher.as_mut().is_some_and(BookWatcher::poll) {
+            return false;
+        }
+        let Some(book) = &self.current_book else {
+            return false;
+        };
+        let path = book.file.clone();
+        let chapter = book.current_chapter();
+        let node_index = self.text_reader.get_current_node_index();
+        info!("{path} changed on disk, reloading");
+
+        self.save_bookmark_with_throttle(true);
+        let doc = match self.book_manager.load_epub(&path) {
+            Ok(doc) => doc,
+            Err(e) => {
+                // Most likely saved halfway; the next save is picked up again
+                warn!("Failed to reload {path}: {e}");
+                self.show_error(format!("Failed to reload the book: {e}"));
+                return true;
+            }
+        };
+        if let Err(e) = self.finalize_loaded_document(&path, doc, true) {
+            error!("Failed to reload {path}: {e}");
+            return true;
+        }
+        // Every save would otherwise bring the same notice back
+        self.pending_book_health = None;
+
+        let last_chapter = self
+            .current_book
+            .as_ref()
+            .map_or(0, |book| book.total_chapters().saturating_sub(1));
+        if let Err(e) = self.navigate_to_chapter(chapter.min(last_chapter)) {
+            warn!("Failed to return to chapter {chapter} after reloading: {e}");
+        }
+        self.text_reader.restore_to_node_index(node_index);
+        self.show_info("Reloaded the book");
+        true
+    }
+
+    /// Flag the just-opened book's quality problems; `:doctor` has the details. A clean
+    /// book opens without a notice. Returns whether a notice was shown.
+    pub fn show_pending_book_health(&mut self) -> bool {
+        let Some(health) = self.pending_book_health.take() else {
+            return false;
+        };
+        self.show_warning(format!("{} (:doctor for details)", health.summary()));
+        true
+    }
+
+    /// All of the book's CSS files as one stylesheet, in manifest path order
+    fn load_book_styles(doc: &mut EpubDoc<BufReader<std::fs::File>>) -> StyleSheet {
+        let mut paths: Vec<_> = doc
+            .resources
+            .values()
+            .filter(|resource| resource.mime == "text/css")
+            .map(|resource| resource.path.clone())
+            .collect();
+        paths.sort();
+
+        let mut styles = StyleSheet::default();
+        for path in paths {
+            match doc.get_resource_str_by_path(&path) {
+                Some(css) => styles.extend(&css),
+                None => warn!("Failed to read stylesheet {}", path.display()),
+            }
+        }
+        styles
+    }
+
     /// Get the href/path for a chapter at a specific index using the EPUB spine
     fn get_chapter_href(
         doc: &EpubDoc<BufReader<std::fs::File>>,
@@ -690,7 +1652,24 @@ impl App {
     }
 
     fn switch_to_toc_mode(&mut self, book: &EpubBook) {
-        let toc_items = TocParser::parse_toc_structure(&book.epub);
+        self.toc_word_counts_pending = true;
+        let mut toc_items =
+            if self.bookmarks.uses_heading_toc(&book.file) && !self.heading_toc.is_empty() {
+                self.heading_toc.clone()
+            } else {
+                TocParser::parse_toc_structure(&book.epub)
+            };
+        TableOfContents::apply_expanded_sections(
+            &mut toc_items,
+            self.bookmarks.toc_expansion(&book.file),
+        );
+        if !self.book_notes.is_empty() {
+            toc_items.push(TocItem::Chapter {
+                title: format!("{NOTES_TITLE} ({})", self.book_notes.len()),
+                href: NOTES_HREF.to_string(),
+                anchor: None,
+            });
+        }
         let current_chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter());
         let available_anchors =
             TableOfContents::anchors_for_items(&toc_items, current_chapter_href.as_deref());
@@ -709,15 +1688,34 @@ impl App {
         };
 
         self.navigation_panel.switch_to_toc_mode(book_info);
+        self.update_toc_read_markers();
     }
 
-    fn update_toc_state(&mut self) {
-        let nav_area = self.get_navigation_panel_area();
-        let toc_height = nav_area.height as usize;
-
-        if let Some(book) = &self.current_book {
-            let current_chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter());
-            let current_chapter = book.current_chapter();
+    /// Save which TOC sections are expanded, so the book's TOC opens the same way next time
+    fn remember_toc_expansion(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let toc = &self.navigation_panel.table_of_contents;
+        // The TOC may still show the previous book until it's rebuilt
+        if toc
+            .get_current_book_info()
+            .is_none_or(|info| info.path != book.file)
+        {
+            return;
+        }
+        if let Some(expanded) = toc.expanded_sections() {
+            self.bookmarks.set_toc_expansion(&book.file, expanded);
+        }
+    }
+
+    fn update_toc_state(&mut self) {
+        let nav_area = self.get_navigation_panel_area();
+        let toc_height = nav_area.height as usize;
+
+        if let Some(book) = &self.current_book {
+            let current_chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter());
+            let current_chapter = book.current_chapter();
             let available_anchors = self
                 .navigation_panel
                 .table_of_contents
@@ -739,6 +1737,13 @@ impl App {
             self.navigation_panel
                 .table_of_contents
                 .update_active_section(&active_selection, toc_height); // todo: double update is dumb
+
+            if let Some(href) = &current_chapter_href {
+                let percent = self.text_reader.scroll_percent();
+                self.navigation_panel
+                    .table_of_contents
+                    .set_chapter_progress(href, percent);
+            }
         }
     }
 
@@ -747,6 +1752,11 @@ impl App {
     }
 
     pub fn save_bookmark_with_throttle(&mut self, force: bool) {
+        if self.virtual_chapter_active {
+            // The notes chapter isn't part of the spine, keep the last real position
+            return;
+        }
+        let mut read_just_now = false;
         if let Some(book) = &self.current_book {
             let chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter())
                 .unwrap_or_else(|| format!("chapter_{}", book.current_chapter()));
@@ -759,6 +1769,13 @@ impl App {
                 Some(book.total_chapters()),
             );
 
+            // Reaching the bottom of a chapter counts as having read it
+            read_just_now = self.text_reader.get_max_scroll_offset() > 0
+                && self.text_reader.get_scroll_offset() >= self.text_reader.get_max_scroll_offset()
+                && self
+                    .bookmarks
+                    .mark_chapter_read(&book.file, book.current_chapter());
+
             // Only save to disk if enough time has passed or if forced
             let now = std::time::Instant::now();
             if force
@@ -771,36 +1788,635 @@ impl App {
                 self.last_bookmark_save = now;
             }
         }
+        if read_just_now {
+            self.update_toc_read_markers();
+        }
     }
 
     fn update_content(&mut self) {
+        self.virtual_chapter_active = false;
         if let Some(book) = &mut self.current_book {
-            let (content, title) = match book.epub.get_current_str() {
-                Some((raw_html, _mime)) => {
-                    let title = TextGenerator::extract_chapter_title(&raw_html);
-                    (raw_html, title)
-                }
-                None => {
-                    error!("Failed to get raw HTML");
-                    ("Error reading chapter content.".to_string(), None)
-                }
-            };
-
-            if let Some(chapter_file) = Self::get_chapter_href(&book.epub, book.current_chapter()) {
+            let chapter_index = book.current_chapter();
+            if let Some(chapter_file) = Self::get_chapter_href(&book.epub, chapter_index) {
                 self.text_reader
                     .set_current_chapter_file(Some(chapter_file));
             } else {
                 self.text_reader.set_current_chapter_file(None);
             }
 
-            self.text_reader.set_content_from_string(&content, title);
+            if let Some(cached) = self.chapter_cache.get(chapter_index).cloned() {
+                self.text_reader
+                    .set_content_from_shared_document(cached.document, cached.title);
+            } else {
+                match book.epub.get_current_str() {
+                    Some((raw_html, _mime)) => {
+                        let title = TextGenerator::extract_chapter_title(&raw_html);
+                        self.text_reader
+                            .set_content_from_string(&raw_html, title.clone());
+                        if let Some(document) = self.text_reader.shared_document() {
+                            self.chapter_cache
+                                .insert(chapter_index, CachedChapter { document, title });
+                        }
+                    }
+                    None => {
+                        error!("Failed to get raw HTML");
+                        self.text_reader
+                            .set_content_from_string("Error reading chapter content.", None);
+                    }
+                }
+            }
             self.text_reader.preload_image_dimensions(&self.book_images);
+            self.mark_broken_links();
+
+            // Parse the neighbours in the background so flipping chapters is instant
+            let adjacent = [chapter_index.checked_sub(1), Some(chapter_index + 1)];
+            self.prefetch_chapters(adjacent.into_iter().flatten());
+            self.update_toc_cache_markers();
+            self.update_toc_read_markers();
         } else {
             error!("No EPUB document loaded");
             self.text_reader.clear_content();
         }
     }
 
+    /// Take the per-book lock, warning when another instance already has the book open.
+    /// Both can keep reading: progress is merged on save and the latest position wins.
+    fn lock_book(&mut self, book_path: &str) {
+        self.book_lock = None;
+        let Some(lock_path) = self.bookmarks.book_lock_path(book_path) else {
+            return;
+        };
+
+        match FileLock::try_exclusive(&lock_path) {
+            Ok(Some(lock)) => self.book_lock = Some(lock),
+            Ok(None) => {
+                warn!("{book_path} is already open in another instance");
+                self.show_warning(
+                    "This book is open in another bookokcat window; the latest reading position wins",
+                );
+            }
+            Err(e) => warn!("Failed to lock {lock_path:?}: {e}"),
+        }
+    }
+
+    /// Queue chapters (by spine index) for background parsing; returns how many were queued
+    fn prefetch_chapters(&mut self, chapter_indices: impl IntoIterator<Item = usize>) -> usize {
+        let Some(book) = &mut self.current_book else {
+            return 0;
+        };
+
+        let mut queued = 0;
+        for index in chapter_indices {
+            if self.chapter_cache.is_cached(index) || self.chapter_cache.is_pending(index) {
+                continue;
+            }
+            let Some(idref) = book.epub.spine.get(index).map(|item| item.idref.clone()) else {
+                continue;
+            };
+            if let Some((raw_html, _mime)) = book.epub.get_resource_str(&idref) {
+                self.chapter_cache.prefetch(index, raw_html);
+                queued += 1;
+            }
+        }
+        queued
+    }
+
+    fn prefetch_all_chapters(&mut self) {
+        let Some(book) = &self.current_book else {
+            self.show_error("No book is open");
+            return;
+        };
+
+        let total = book.total_chapters();
+        let queued = self.prefetch_chapters(0..total);
+        if queued == 0 && !self.chapter_cache.has_pending() {
+            self.show_info(format!("All {total} chapters are already parsed"));
+        } else {
+            self.prefetch_all_running = true;
+            self.show_info(format!("Parsing {queued} chapters in the background"));
+        }
+    }
+
+    /// How long without input before the app goes idle, if it ever does
+    fn idle_timeout(&self) -> Option<Duration> {
+        let secs = self.preferences.idle_after_secs;
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    /// Count reading towards the daily goal: time spent with a chapter open while not
+    /// idle, and lines scrolled past the furthest point reached in the chapter
+    fn track_reading_goal(&mut self, elapsed: Duration, idle: bool) {
+        let position = self
+            .current_book
+            .as_ref()
+            .filter(|_| !self.virtual_chapter_active)
+            .map(|book| (book.current_chapter(), self.text_reader.get_scroll_offset()));
+        let mut lines = 0;
+        self.goal_position = match (self.goal_position, position) {
+            (Some((chapter, furthest)), Some((current, offset))) if chapter == current => {
+                lines = offset.saturating_sub(furthest);
+                Some((chapter, furthest.max(offset)))
+            }
+            _ => position,
+        };
+        let reading_time = if position.is_some() && !idle {
+            elapsed
+        } else {
+            Duration::ZERO
+        };
+        let today = chrono::Local::now().date_naive();
+        let page_height = self.text_reader.visible_height();
+        if self
+            .reading_goal
+            .record(today, reading_time, lines, page_height)
+        {
+            info!("Daily reading goal met: {}", self.reading_goal.describe());
+            self.show_info(format!(
+                "Daily goal reached, well done! {}",
+                self.reading_goal.describe()
+            ));
+        }
+    }
+
+    /// While idle, background parsing waits for the reader to come back
+    fn set_idle(&mut self, idle: bool) {
+        debug!("{} idle mode", if idle { "Entering" } else { "Leaving" });
+        self.chapter_cache.set_paused(idle);
+    }
+
+    /// Pick up chapters parsed in the background; returns true if the cache changed
+    fn receive_prefetched_chapters(&mut self) -> bool {
+        if !self.chapter_cache.receive() {
+            return false;
+        }
+        self.update_toc_cache_markers();
+
+        if self.prefetch_all_running && !self.chapter_cache.has_pending() {
+            self.prefetch_all_running = false;
+            self.show_info(format!(
+                "Prefetch complete: {} chapters parsed",
+                self.chapter_cache.cached_count()
+            ));
+        }
+        true
+    }
+
+    /// Tell the TOC which chapter files have been read, for its check marks
+    fn update_toc_read_markers(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let read = self
+            .navigation_panel
+            .table_of_contents
+            .chapter_hrefs()
+            .into_iter()
+            .filter(|href| {
+                self.find_spine_index_by_href(href)
+                    .is_some_and(|index| self.bookmarks.is_chapter_read(&book.file, index))
+            })
+            .collect();
+        self.navigation_panel
+            .table_of_contents
+            .set_read_chapters(read);
+    }
+
+    /// Mark every chapter up to and including the one at `href` as read or unread
+    fn mark_read_up_to(&mut self, href: &str, read: bool) {
+        let Some(chapter_index) = self.find_spine_index_by_href(href) else {
+            return;
+        };
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let total_chapters = book.total_chapters();
+        self.bookmarks
+            .set_chapters_read(&book.file, 0..=chapter_index, read);
+        let read_count = self.bookmarks.read_chapter_count(&book.file);
+        self.update_toc_read_markers();
+        self.show_info(format!(
+            "Chapters 1–{} marked as {} · {read_count} of {total_chapters} read ({}%)",
+            chapter_index + 1,
+            if read { "read" } else { "unread" },
+            read_count * 100 / total_chapters.max(1)
+        ));
+    }
+
+    /// Tell the TOC which chapter files are cached, for its indicator dots
+    fn update_toc_cache_markers(&mut self) {
+        if !self.navigation_panel.table_of_contents.cache_indicator() {
+            return;
+        }
+
+        let cached = self
+            .navigation_panel
+            .table_of_contents
+            .chapter_hrefs()
+            .into_iter()
+            .filter(|href| {
+                self.find_spine_index_by_href(href)
+                    .is_some_and(|index| self.chapter_cache.is_cached(index))
+            })
+            .collect();
+        self.navigation_panel
+            .table_of_contents
+            .set_cached_chapters(cached);
+    }
+
+    fn open_command_line(&mut self) {
+        self.key_sequence.clear();
+        self.command_line = Some(CommandLine::new());
+    }
+
+    fn execute_command(&mut self, input: &str) {
+        if input.is_empty() {
+            return;
+        }
+        match Command::parse(input) {
+            Ok(Command::PrefetchAll) => self.prefetch_all_chapters(),
+            Ok(Command::Link) => self.copy_deep_link(),
+            Ok(Command::Keymap(preset)) => self.set_keymap(preset),
+            Ok(Command::WriteCode(path)) => self.write_focused_code_block(&path),
+            Ok(Command::AppendCode(path)) => self.append_focused_code_block(path),
+            Ok(Command::Doctor) => self.open_doctor_report(),
+            Ok(Command::Check) => self.open_check_report(),
+            Ok(Command::Compare(path)) => self.open_edition_comparison(&path),
+            Ok(Command::CloseComparison) => {
+                if self.edition_pane.take().is_some() {
+                    self.show_info("Closed the other edition");
+                } else {
+                    self.show_info("No other edition open");
+                }
+            }
+            Ok(Command::Panel(kind)) => self.toggle_dock(kind),
+            Ok(Command::DiffChapter(chapter)) => self.open_chapter_diff(chapter - 1),
+            Ok(Command::DiffEdition(path)) => self.open_edition_diff(&path),
+            Ok(Command::Summary) => self.open_chapter_summary(),
+            Ok(Command::Edit) => self.hand_off_chapter("EDITOR", "vi"),
+            Ok(Command::Pager) => self.hand_off_chapter("PAGER", "less"),
+            Ok(Command::LogLevel(directives)) => {
+                if !directives.is_empty() {
+                    logging::apply_directives(&directives);
+                }
+                self.show_info(format!("Log levels: {}", logging::describe()));
+            }
+            Ok(Command::LogFormat(format)) => {
+                logging::set_format(format);
+                self.show_info(format!("Log levels: {}", logging::describe()));
+            }
+            Ok(Command::Goal) => self.show_info(self.reading_goal.describe()),
+            Ok(Command::ClubExport(path)) => self.export_shared_reading(path),
+            Ok(Command::ClubImport(path)) => self.import_shared_reading(&path),
+            Ok(Command::Flashcards(path)) => self.export_flashcards(path),
+            Ok(Command::Cite(format)) => self.copy_reference(format),
+            Ok(Command::Count(term)) => self.open_count_popup(term),
+            Ok(Command::Summarize) => self.run_summary_command(),
+            Ok(Command::Entities) => self.open_entity_popup(),
+            Ok(Command::Timeline) => self.open_timeline_popup(),
+            Ok(Command::Watch(term)) => self.watch_term(term),
+            Ok(Command::LiveReload) => {
+                let enabled = !self.live_reload;
+                self.set_live_reload(enabled);
+                self.show_info(if enabled {
+                    "Reloading the book whenever its file is saved"
+                } else {
+                    "Stopped reloading the book on changes"
+                });
+            }
+            Ok(Command::Unwatch(term)) => self.unwatch_term(term),
+            Ok(Command::Goto { href, anchor }) => self.goto_href(&href, anchor),
+            Ok(Command::ClubClear) => {
+                self.shared_readings.clear();
+                self.text_reader.set_shared_comments(Vec::new());
+                self.show_info("Shared notes hidden");
+            }
+            Err(message) => self.show_error(message),
+        }
+    }
+
+    /// Completions for the last word on the `:` prompt: the book's chapter files after
+    /// `goto`, or the anchors in one of them once a `#` is typed
+    fn command_completions(&mut self, input: &str) -> Vec<String> {
+        let Some(target) = input
+            .trim_start()
+            .strip_prefix("goto ")
+            .map(str::trim_start)
+        else {
+            return Vec::new();
+        };
+        if target.contains(' ') {
+            return Vec::new();
+        }
+        let Some(book) = &self.current_book else {
+            return Vec::new();
+        };
+        let hrefs: Vec<String> = (0..book.epub.spine.len())
+            .filter_map(|index| Self::get_chapter_href(&book.epub, index))
+            .collect();
+
+        if let Some((href, anchor)) = target.split_once('#') {
+            let chapter = if href.is_empty() {
+                Some(book.current_chapter())
+            } else {
+                self.find_spine_index_by_href(href)
+            };
+            let Some(chapter) = chapter else {
+                return Vec::new();
+            };
+            let mut anchors: Vec<String> = self
+                .chapter_anchor_targets(chapter)
+                .ids
+                .iter()
+                .filter(|id| id.starts_with(anchor))
+                .map(|id| format!("{href}#{id}"))
+                .collect();
+            anchors.sort();
+            return anchors;
+        }
+
+        // Match the whole path or just the file name, since paths inside books vary
+        hrefs
+            .into_iter()
+            .filter(|path| {
+                path.starts_with(target)
+                    || std::path::Path::new(path)
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(target))
+            })
+            .collect()
+    }
+
+    /// `]f`, `[a` and the like: jump to the next or previous footnote, annotation, image
+    /// or table in the chapter, `count` times over
+    fn jump_to_point_of_interest(&mut self, kind: PointOfInterest, forward: bool, count: usize) {
+        let mut wrapped = false;
+        let mut last = None;
+        for _ in 0..count {
+            let Some(jump) = self.text_reader.jump_to_point_of_interest(kind, forward) else {
+                break;
+            };
+            wrapped |= jump.wrapped;
+            last = Some(jump);
+        }
+        match last {
+            Some(jump) => {
+                let wrapped = if !wrapped {
+                    ""
+                } else if forward {
+                    " (from the top)"
+                } else {
+                    " (from the bottom)"
+                };
+                self.show_info(format!(
+                    "Jumped to {} {}/{}{wrapped}",
+                    kind.name(),
+                    jump.index,
+                    jump.total
+                ));
+                self.save_bookmark();
+            }
+            None => self.show_info(format!("No {}s in this chapter", kind.name())),
+        }
+    }
+
+    /// Go to a chapter file of the book, and to an anchor in it, by name
+    fn goto_href(&mut self, href: &str, anchor: Option<String>) {
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let current_chapter = book.current_chapter();
+        let chapter = if href.is_empty() {
+            Some(current_chapter)
+        } else {
+            self.find_spine_index_by_href(href)
+        };
+        let Some(chapter) = chapter else {
+            self.show_error(format!("No chapter file {href} in this book"));
+            return;
+        };
+        if let Some(anchor) = &anchor {
+            if !self.chapter_anchor_targets(chapter).resolves(anchor) {
+                self.show_error(format!("No anchor #{anchor} in that chapter"));
+                return;
+            }
+        }
+
+        if let Some(book) = &self.current_book {
+            self.jump_list.push(JumpLocation {
+                epub_path: book.file.clone(),
+                chapter_index: current_chapter,
+                node_index: self.text_reader.get_current_node_index(),
+            });
+        }
+        if chapter == current_chapter {
+            // The chapter is already laid out, so there's no re-render to wait for
+            if let Some(anchor) = &anchor {
+                let _ = self.scroll_to_anchor(anchor);
+            }
+        } else {
+            if let Err(e) = self.navigate_to_chapter(chapter) {
+                error!("Failed to go to {href}: {e}");
+                self.show_error(format!("Failed to go to {href}"));
+                return;
+            }
+            if let Some(anchor) = &anchor {
+                self.text_reader.store_pending_anchor_scroll(anchor.clone());
+            }
+        }
+        self.text_reader.set_active_anchor(anchor);
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+        self.update_toc_state();
+    }
+
+    /// Write an Anki card for every note in the book: by default the note on the front
+    /// and the passage it's on, with a citation, on the back
+    fn export_flashcards(&mut self, path: Option<String>) {
+        let target = match path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => match self.path_next_to_book("flashcards.tsv") {
+                Some(target) => target,
+                None => {
+                    self.show_info("No book open");
+                    return;
+                }
+            },
+        };
+        if target
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("apkg"))
+        {
+            self.show_error("Can't write .apkg packages; write a .tsv for Anki's File > Import");
+            return;
+        }
+        let comments = self.text_reader.all_comments();
+        if comments.is_empty() {
+            self.show_info("No notes to make cards from");
+            return;
+        }
+        let spine_indices: HashMap<String, usize> = comments
+            .iter()
+            .filter_map(|comment| {
+                self.find_spine_index_by_href(&comment.chapter_href)
+                    .map(|index| (comment.chapter_href.clone(), index))
+            })
+            .collect();
+
+        let Some(book) = &mut self.current_book else {
+            return;
+        };
+        let metadata = |property: &str| book.epub.mdata(property).map(|item| item.value.clone());
+        let citation = Citation {
+            author: metadata("creator"),
+            title: metadata("title"),
+            chapter: None,
+            year: metadata("date").and_then(|date| date.get(..4).map(str::to_string)),
+        };
+        let original_chapter = book.current_chapter();
+        let mut converter = self.text_reader.converter();
+        let mut chapters: HashMap<usize, (Document, Option<String>)> = HashMap::new();
+        let mut cards = Vec::new();
+        for comment in &comments {
+            let Some(&index) = spine_indices.get(&comment.chapter_href) else {
+                continue;
+            };
+            let (doc, title) = match chapters.entry(index) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    if !book.epub.set_current_chapter(index) {
+                        continue;
+                    }
+                    let Some((raw_html, _)) = book.epub.get_current_str() else {
+                        continue;
+                    };
+                    let title = TextGenerator::extract_chapter_title(&raw_html);
+                    entry.insert((converter.convert(&raw_html), title))
+                }
+            };
+            let Some(node) = doc.blocks.get(comment.paragraph_index) else {
+                continue;
+            };
+            let card = Flashcard {
+                note: comment.content.clone(),
+                passage: passage_words(&node.plain_text(), comment.word_range),
+                chapter: title.clone(),
+            };
+            cards.push(self.preferences.flashcard_template.fill(&card, &citation));
+        }
+        book.epub.set_current_chapter(original_chapter);
+
+        match std::fs::write(&target, to_anki_tsv(&cards)) {
+            Ok(()) => self.show_info(format!(
+                "Wrote {} card(s) to {}",
+                cards.len(),
+                target.display()
+            )),
+            Err(e) => {
+                error!("Failed to write flashcards to {target:?}: {e}");
+                self.show_error(format!("Failed to write flashcards: {e}"));
+            }
+        }
+    }
+
+    /// Write our position and notes on the current chapter to a file others can import
+    fn export_shared_reading(&mut self, path: Option<String>) {
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        if self.virtual_chapter_active {
+            self.show_info("Open a chapter to share your place in it");
+            return;
+        }
+        let chapter_index = book.current_chapter();
+        let reading = SharedReading {
+            reader: reader_name(),
+            book: Path::new(&book.file)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            chapter_href: Self::get_chapter_href(&book.epub, chapter_index).unwrap_or_default(),
+            chapter_index,
+            node_index: self.text_reader.get_current_node_index(),
+            shared_at: chrono::Utc::now(),
+            annotations: self.text_reader.chapter_comments(),
+        };
+        let path = path.unwrap_or_else(|| reading.default_file_name());
+        match reading.save(Path::new(&path)) {
+            Ok(()) => self.show_info(format!(
+                "Shared your place and {} notes in {path}",
+                reading.annotations.len()
+            )),
+            Err(e) => {
+                error!("Failed to export shared reading: {e:#}");
+                self.show_error(format!("{e:#}"));
+            }
+        }
+    }
+
+    /// Overlay another reader's notes and say where they are
+    fn import_shared_reading(&mut self, path: &str) {
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let reading = match SharedReading::load(Path::new(path)) {
+            Ok(reading) => reading,
+            Err(e) => {
+                self.show_error(format!("{e:#}"));
+                return;
+            }
+        };
+        let this_book = Path::new(&book.file)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if reading.book != this_book {
+            self.show_error(format!(
+                "{}'s file is for {}, not {this_book}",
+                reading.reader, reading.book
+            ));
+            return;
+        }
+
+        info!(
+            "Imported {} notes from {} at chapter {}",
+            reading.annotations.len(),
+            reading.reader,
+            reading.chapter_index + 1
+        );
+        let message = format!(
+            "{} is at chapter {}; showing their {} notes",
+            reading.reader,
+            reading.chapter_index + 1,
+            reading.annotations.len()
+        );
+        // A newer file from the same reader replaces their old one
+        self.shared_readings
+            .retain(|shared| shared.reader != reading.reader);
+        self.shared_readings.push(reading);
+        self.text_reader.set_shared_comments(
+            self.shared_readings
+                .iter()
+                .flat_map(|shared| {
+                    shared
+                        .annotations
+                        .iter()
+                        .map(|comment| (shared.reader.clone(), comment.clone()))
+                })
+                .collect(),
+        );
+        self.show_info(message);
+    }
+
+    fn set_keymap(&mut self, preset: KeymapPreset) {
+        self.preferences.keymap = preset;
+        if let Err(e) = self.preferences.save() {
+            error!("Failed to save preferences: {e}");
+        }
+        self.show_info(format!("Keymap: {}", preset.name()));
+    }
+
     pub fn scroll_down(&mut self) {
         self.text_reader.scroll_down();
         self.save_bookmark();
@@ -925,11 +2541,56 @@ impl App {
         let net_scroll = scroll_down_count - scroll_up_count;
 
         self.apply_scroll(net_scroll, initial_column);
+
+        if self.preferences.kinetic_scroll
+            && !self.has_active_popup()
+            && initial_column >= self.nav_panel_width()
+        {
+            self.mouse_tracker.record_scroll(net_scroll, initial_column);
+        }
     }
 
     /// Handle non-scroll mouse events (clicks, drags, etc.)
     fn handle_non_scroll_mouse_event(&mut self, mouse_event: MouseEvent) {
+        // Any click catches a kinetic scroll
+        if matches!(mouse_event.kind, MouseEventKind::Down(_)) {
+            self.mouse_tracker.stop_momentum();
+        }
+
         match mouse_event.kind {
+            // Middle-button drag grabs the content and scrolls it with the pointer
+            MouseEventKind::Down(MouseButton::Middle)
+                if !self.has_active_popup() && mouse_event.column >= self.nav_panel_width() =>
+            {
+                self.mouse_tracker.start_drag_scroll(mouse_event.row);
+            }
+            MouseEventKind::Drag(MouseButton::Middle)
+                if self.mouse_tracker.is_drag_scrolling() && !self.has_active_popup() =>
+            {
+                let lines = self.mouse_tracker.drag_scroll(mouse_event.row);
+                self.apply_scroll(lines, mouse_event.column);
+            }
+            MouseEventKind::Up(MouseButton::Middle) => {
+                self.mouse_tracker.end_drag_scroll();
+            }
+            MouseEventKind::Down(MouseButton::Left)
+                if !self.has_active_popup()
+                    && self.is_on_splitter(mouse_event.column, mouse_event.row) =>
+            {
+                self.mouse_tracker.start_splitter_drag();
+            }
+            MouseEventKind::Drag(MouseButton::Left)
+                if self.mouse_tracker.is_dragging_splitter() =>
+            {
+                self.preferences.nav_panel_percent =
+                    MouseTracker::splitter_percent(mouse_event.column, self.terminal_size.width);
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.mouse_tracker.is_dragging_splitter() => {
+                self.mouse_tracker.end_splitter_drag();
+                if let Err(e) = self.preferences.save() {
+                    error!("Failed to save preferences: {e}");
+                }
+            }
             MouseEventKind::Down(MouseButton::Left) => {
                 if self.handle_help_bar_click(mouse_event.column, mouse_event.row) {
                     return;
@@ -968,24 +2629,112 @@ impl App {
 
                 if matches!(
                     self.focused_panel,
-                    FocusedPanel::Popup(PopupWindow::ReadingHistory)
+                    FocusedPanel::Popup(PopupWindow::Resources)
                 ) {
-                    let click_x = mouse_event.column;
-                    let click_y = mouse_event.row;
-
-                    if let Some(ref mut history) = self.reading_history {
-                        // Check if click is outside popup area - close it
-                        if history.is_outside_popup_area(click_x, click_y) {
-                            self.reading_history = None;
+                    if let Some(ref popup) = self.resources_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.resources_popup = None;
                             self.close_popup_to_previous();
-                            return;
                         }
+                    }
+                    return;
+                }
 
-                        let click_type = self
-                            .mouse_tracker
-                            .detect_click_type(mouse_event.column, mouse_event.row);
-
-                        match click_type {
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::PageList)
+                ) {
+                    if let Some(ref popup) = self.page_list_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.page_list_popup = None;
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::Entities)
+                ) {
+                    if let Some(ref popup) = self.entity_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.entity_popup = None;
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::Timeline)
+                ) {
+                    if let Some(ref popup) = self.timeline_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.timeline_popup = None;
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::QuickSwitcher)
+                ) {
+                    if let Some(ref popup) = self.quick_switcher_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.quick_switcher_popup = None;
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return;
+                }
+
+                if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Count)) {
+                    if let Some(ref popup) = self.count_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.count_popup = None;
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::ChapterSummary)
+                ) {
+                    if let Some(ref popup) = self.chapter_summary_popup {
+                        if popup.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.chapter_summary_popup = None;
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::ReadingHistory)
+                ) {
+                    let click_x = mouse_event.column;
+                    let click_y = mouse_event.row;
+
+                    if let Some(ref mut history) = self.reading_history {
+                        // Check if click is outside popup area - close it
+                        if history.is_outside_popup_area(click_x, click_y) {
+                            self.reading_history = None;
+                            self.close_popup_to_previous();
+                            return;
+                        }
+
+                        let click_type = self
+                            .mouse_tracker
+                            .detect_click_type(mouse_event.column, mouse_event.row);
+
+                        match click_type {
                             ClickType::Single | ClickType::Triple => {
                                 history.handle_mouse_click(mouse_event.column, mouse_event.row);
                             }
@@ -1087,6 +2836,30 @@ impl App {
                             .clear_manual_navigation();
                     }
 
+                    if let Some(path) = self
+                        .welcome_screen
+                        .as_ref()
+                        .filter(|_| self.current_book.is_none())
+                        .and_then(|screen| screen.path_at(mouse_event.column, mouse_event.row))
+                    {
+                        self.open_welcome_book(path.to_string());
+                        return;
+                    }
+
+                    if let Some(marker) = self
+                        .text_reader
+                        .handle_gutter_click(mouse_event.column, mouse_event.row)
+                    {
+                        if marker == GutterMarker::Bookmark {
+                            self.show_info("You were here when the book was opened");
+                        } else if let Some(path) =
+                            self.text_reader.gutter_note_file(mouse_event.row)
+                        {
+                            self.hand_off_file("EDITOR", "vi", path);
+                        }
+                        return;
+                    }
+
                     let click_type = self
                         .mouse_tracker
                         .detect_click_type(mouse_event.column, mouse_event.row);
@@ -1127,9 +2900,7 @@ impl App {
                         .text_reader
                         .handle_mouse_up(mouse_event.column, mouse_event.row)
                     {
-                        if let Err(e) = self.handle_link_click(&LinkInfo::from_url(url)) {
-                            error!("Failed to handle link click: {e}");
-                        }
+                        self.follow_link(&LinkInfo::from_url(url));
                     }
                 }
             }
@@ -1169,11 +2940,16 @@ impl App {
 
         match &link_info.link_type {
             crate::markdown::LinkType::External => {
-                if let Err(e) = open::that(&link_info.url) {
-                    error!("Failed to open external link: {e}");
-                    Ok(false)
-                } else {
+                if self.preferences.confirm_external_links {
+                    self.notifications
+                        .show(Notification::persistent_info(format!(
+                            "Open {} in browser? (y/n)",
+                            link_info.url
+                        )));
+                    self.pending_external_link = Some(link_info.url.clone());
                     Ok(true)
+                } else {
+                    Ok(self.open_external_link(&link_info.url))
                 }
             }
             crate::markdown::LinkType::InternalAnchor => {
@@ -1221,8 +2997,238 @@ impl App {
         }
     }
 
+    fn open_external_link(&mut self, url: &str) -> bool {
+        if let Err(e) = self.system_command_executor.open_url(url) {
+            error!("Failed to open external link: {e}");
+            self.show_error(format!("Failed to open link: {e}"));
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Keyboard counterpart of clicking a link: follows the focused link
+    fn open_focused_link(&mut self) {
+        let Some(link) = self.text_reader.focused_link().cloned() else {
+            self.show_info("No link on screen");
+            return;
+        };
+        self.follow_link(&link);
+    }
+
+    /// Follow a clicked or focused link, telling when it leads nowhere
+    fn follow_link(&mut self, link: &LinkInfo) {
+        match self.handle_link_click(link) {
+            Ok(true) => {}
+            Ok(false) => {
+                // A browser that failed to open has already said why
+                if link.link_type != crate::markdown::LinkType::External {
+                    self.show_info(format!("Link target not found: {}", link.url));
+                }
+                self.alert_failure();
+            }
+            Err(e) => {
+                error!("Failed to handle link: {e}");
+                self.alert_failure();
+            }
+        }
+    }
+
+    /// Copy the selection together with a citation built from the book metadata
+    fn capture_quote(&mut self) {
+        let (Some(book), Some(text)) = (&self.current_book, self.text_reader.get_selected_text())
+        else {
+            return;
+        };
+
+        let metadata = |property: &str| book.epub.mdata(property).map(|item| item.value.clone());
+        let citation = Citation {
+            author: metadata("creator"),
+            title: metadata("title"),
+            chapter: self.text_reader.get_chapter_title().map(str::to_string),
+            year: metadata("date").and_then(|date| date.get(..4).map(str::to_string)),
+        };
+        let quote = format_quote(&text, &citation, self.preferences.quote_style);
+
+        match self.text_reader.copy_text_to_clipboard(quote) {
+            Ok(()) => self.show_info("Quote copied with citation"),
+            Err(e) => {
+                error!("Quote capture failed: {e}");
+                self.show_error(e);
+            }
+        }
+    }
+
+    /// Add a term to the book's watchlist, or list the watched terms
+    fn watch_term(&mut self, term: Option<String>) {
+        let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) else {
+            self.show_info("No book open");
+            return;
+        };
+        let mut terms = self.bookmarks.watchlist(&path).to_vec();
+        let Some(term) = term else {
+            if terms.is_empty() {
+                self.show_info("No watched terms; add one with :watch <term>");
+            } else {
+                self.show_info(format!("Watching: {}", terms.join(", ")));
+            }
+            return;
+        };
+        if terms
+            .iter()
+            .any(|watched| watched.to_lowercase() == term.to_lowercase())
+        {
+            self.show_info(format!("Already watching \"{term}\""));
+            return;
+        }
+        terms.push(term.clone());
+        self.bookmarks.set_watchlist(&path, terms.clone());
+        self.text_reader.set_watchlist(terms);
+        self.show_info(format!("Highlighting \"{term}\" throughout the book"));
+    }
+
+    /// Take a term off the book's watchlist, or clear it
+    fn unwatch_term(&mut self, term: Option<String>) {
+        let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) else {
+            self.show_info("No book open");
+            return;
+        };
+        let mut terms = self.bookmarks.watchlist(&path).to_vec();
+        let count = terms.len();
+        match &term {
+            Some(term) => terms.retain(|watched| watched.to_lowercase() != term.to_lowercase()),
+            None => terms.clear(),
+        }
+        if terms.len() == count {
+            self.show_info(match term {
+                Some(term) => format!("\"{term}\" isn't watched"),
+                None => "No watched terms".to_string(),
+            });
+            return;
+        }
+        self.bookmarks.set_watchlist(&path, terms.clone());
+        self.text_reader.set_watchlist(terms);
+        self.show_info(match term {
+            Some(term) => format!("Stopped highlighting \"{term}\""),
+            None => "Watchlist cleared".to_string(),
+        });
+    }
+
+    /// Copy a BibTeX or CSL-JSON reference to the book; with a selection, to the passage,
+    /// with its print page and chapter
+    fn copy_reference(&mut self, format: ReferenceFormat) {
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let metadata = |property: &str| book.epub.mdata(property).map(|item| item.value.clone());
+        let reference = BookReference {
+            authors: book
+                .epub
+                .metadata
+                .iter()
+                .filter(|item| item.property == "creator")
+                .map(|item| item.value.trim().to_string())
+                .collect(),
+            title: metadata("title"),
+            publisher: metadata("publisher"),
+            date: metadata("date"),
+            isbn: find_isbn(
+                book.epub
+                    .metadata
+                    .iter()
+                    .filter(|item| item.property == "identifier")
+                    .map(|item| item.value.as_str()),
+            ),
+            language: metadata("language"),
+        };
+        let locator = self.text_reader.selection_start_line().map(|line| Locator {
+            page: self.print_page_at(line),
+            chapter: self.text_reader.get_chapter_title().map(str::to_string),
+        });
+        let entry = match format {
+            ReferenceFormat::Bibtex => reference.to_bibtex(locator.as_ref()),
+            ReferenceFormat::CslJson => reference.to_csl_json(locator.as_ref()),
+        };
+
+        match self.text_reader.copy_text_to_clipboard(entry) {
+            Ok(()) => self.show_info(if locator.is_some() {
+                "Passage reference copied"
+            } else {
+                "Book reference copied"
+            }),
+            Err(e) => {
+                error!("Failed to copy reference: {e}");
+                self.show_error(e);
+            }
+        }
+    }
+
+    /// Append the selected word with its sentence to the vocabulary file
+    fn save_vocabulary_word(&mut self) {
+        let Some(vocabulary_file) = self.preferences.vocabulary_file.clone() else {
+            self.show_info("Set vocabulary_file in preferences.json to collect words");
+            return;
+        };
+        let Some(word) = self
+            .text_reader
+            .get_selected_text()
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|word| !word.is_empty())
+        else {
+            return;
+        };
+
+        let context = self
+            .text_reader
+            .get_selection_paragraph()
+            .map(|paragraph| sentence_containing(&paragraph, &word))
+            .unwrap_or_default();
+        let book_title = self
+            .current_book
+            .as_ref()
+            .and_then(|book| book.epub.mdata("title").map(|item| item.value.clone()));
+        let source = [book_title.as_deref(), self.text_reader.get_chapter_title()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let definition = match self.preferences.dictionary_command.as_deref() {
+            Some(command) => look_up_definition(command, &word).unwrap_or_else(|e| {
+                warn!("Dictionary lookup failed: {e}");
+                String::new()
+            }),
+            None => String::new(),
+        };
+
+        let entry = VocabularyEntry {
+            word: word.clone(),
+            definition,
+            context,
+            source,
+        };
+        match append_entry(std::path::Path::new(&vocabulary_file), &entry) {
+            Ok(()) => {
+                self.text_reader.clear_selection();
+                self.show_info(format!("Saved \"{word}\" to {vocabulary_file}"));
+            }
+            Err(e) => {
+                error!("Failed to save vocabulary word: {e}");
+                self.show_error(format!("Failed to save word: {e}"));
+            }
+        }
+    }
+
+    fn copy_focused_link(&mut self) {
+        match self.text_reader.copy_focused_link_to_clipboard() {
+            Ok(url) => self.show_info(format!("Copied {url}")),
+            Err(e) => self.show_info(e),
+        }
+    }
+
     fn scroll_to_anchor(&mut self, anchor_id: &str) -> std::io::Result<bool> {
-        if let Some(target_line) = self.text_reader.get_anchor_position(anchor_id) {
+        if let Some(target_line) = self.text_reader.resolve_anchor_position(anchor_id) {
             self.text_reader.scroll_to_line(target_line);
             self.text_reader
                 .highlight_line_temporarily(target_line, Duration::from_secs(2));
@@ -1238,7 +3244,10 @@ impl App {
         chapter_file: &str,
         anchor_id: Option<&String>,
     ) -> std::io::Result<bool> {
-        if let Some(chapter_index) = self.find_chapter_by_filename(chapter_file) {
+        if let Some(chapter_index) = self
+            .find_chapter_by_filename(chapter_file)
+            .or_else(|| self.find_spine_index_by_href(chapter_file))
+        {
             if self.navigate_to_chapter(chapter_index).is_err() {
                 return Ok(false);
             }
@@ -1304,6 +3313,79 @@ impl App {
         None
     }
 
+    /// Element ids and headings in a chapter's markup, parsed once per book
+    fn chapter_anchor_targets(
+        &mut self,
+        chapter_index: usize,
+    ) -> &crate::epub_check::AnchorTargets {
+        let book = &mut self.current_book;
+        self.chapter_anchor_targets
+            .entry(chapter_index)
+            .or_insert_with(|| {
+                let Some(book) = book else {
+                    return Default::default();
+                };
+                let idref = book
+                    .epub
+                    .spine
+                    .get(chapter_index)
+                    .map(|item| item.idref.clone());
+                idref
+                    .and_then(|idref| book.epub.get_resource_str(&idref))
+                    .map(|(html, _)| crate::epub_check::anchor_targets(&html))
+                    .unwrap_or_default()
+            })
+    }
+
+    /// Urls of the links to other chapters that point to a chapter or anchor the book
+    /// doesn't have, in reading order
+    fn broken_chapter_links<'a>(
+        &mut self,
+        links: impl IntoIterator<Item = &'a Inline>,
+    ) -> Vec<String> {
+        let mut broken: Vec<String> = Vec::new();
+        for link in links {
+            let Inline::Link {
+                url,
+                link_type: LinkType::InternalChapter,
+                target_chapter: Some(chapter),
+                target_anchor,
+                ..
+            } = link
+            else {
+                continue;
+            };
+            let resolves = self.find_spine_index_by_href(chapter).is_some_and(|index| {
+                target_anchor
+                    .as_ref()
+                    .filter(|anchor| !anchor.is_empty())
+                    .is_none_or(|anchor| self.chapter_anchor_targets(index).resolves(anchor))
+            });
+            if !resolves && !broken.contains(url) {
+                broken.push(url.clone());
+            }
+        }
+        broken
+    }
+
+    /// Style the current chapter's links that can't be followed. A standalone HTML file
+    /// is a one-chapter book, so its links to other files aren't checked.
+    fn mark_broken_links(&mut self) {
+        let is_html = self
+            .current_book
+            .as_ref()
+            .is_some_and(|book| self.book_manager.is_html_file(&book.file));
+        let Some(doc) = self.text_reader.shared_document().filter(|_| !is_html) else {
+            return;
+        };
+        let broken = self.broken_chapter_links(doc.links());
+        if !broken.is_empty() {
+            debug!("{} broken links in this chapter", broken.len());
+            self.text_reader
+                .set_broken_links(broken.into_iter().collect());
+        }
+    }
+
     /// Find the spine index for a given href
     fn find_spine_index_by_href(&self, href: &str) -> Option<usize> {
         fn normalize_href(href: &str) -> String {
@@ -1342,6 +3424,28 @@ impl App {
     }
 
     fn handle_image_click(&mut self, image_src: &str, terminal_size: Rect) {
+        let chapter_file = self.text_reader.get_current_chapter_file().clone();
+        let file_path = self
+            .book_images
+            .image_file_path(image_src, chapter_file.as_deref());
+
+        if let Some(viewer) = self.preferences.image_viewer.clone() {
+            let Some(path) = file_path else {
+                self.show_error(format!("Image file not found: {image_src}"));
+                return;
+            };
+            let result = if viewer == "system" {
+                open::that(&path)
+            } else {
+                open::with(&path, &viewer)
+            };
+            if let Err(e) = result {
+                error!("Failed to open image with {viewer}: {e}");
+                self.show_error(format!("Failed to open image: {e}"));
+            }
+            return;
+        }
+
         let picker = match self.text_reader.get_image_picker() {
             Some(picker) => picker,
             None => {
@@ -1408,11 +3512,39 @@ impl App {
             self.previous_main_panel = panel;
         }
 
-        let popup = ImagePopup::new(prescaled_image, picker, image_src.to_string());
+        let popup = ImagePopup::new(prescaled_image, picker, image_src.to_string())
+            .with_file_path(file_path);
         self.image_popup = Some(popup);
         self.focused_panel = FocusedPanel::Popup(PopupWindow::ImagePopup);
     }
 
+    /// Copy the image shown in the popup into the working directory
+    fn save_popup_image(&mut self) {
+        let Some(popup) = self.image_popup.as_ref() else {
+            return;
+        };
+        let dir = self
+            .preferences
+            .image_save_dir
+            .as_ref()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(crate::app_dirs::saved_images_dir);
+
+        match popup.save_to(&dir) {
+            Ok(target) => self.show_info(format!("Saved image to {}", target.display())),
+            Err(e) => {
+                error!("Failed to save image {:?}: {e}", popup.file_path);
+                self.show_error(format!("Failed to save image: {e}"));
+            }
+        }
+    }
+
+    fn open_focused_image(&mut self) {
+        if let Some(image_src) = self.text_reader.focused_image() {
+            self.handle_image_click(&image_src, self.terminal_size);
+        }
+    }
+
     /// Apply scroll events (positive for down, negative for up)
     fn apply_scroll(&mut self, scroll_amount: i32, column: u16) {
         if scroll_amount == 0 {
@@ -1471,58 +3603,137 @@ impl App {
 
         if matches!(
             self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::ReadingHistory)
+            FocusedPanel::Popup(PopupWindow::Resources)
         ) {
-            if let Some(ref mut history) = self.reading_history {
+            if let Some(ref mut popup) = self.resources_popup {
                 if scroll_amount > 0 {
                     for _ in 0..scroll_amount.min(10) {
-                        history.handle_j();
+                        popup.handle_j();
                     }
                 } else {
                     for _ in 0..(-scroll_amount).min(10) {
-                        history.handle_k();
+                        popup.handle_k();
                     }
                 }
             }
             return;
         }
 
-        // Block scrolling for other popups
-        if self.has_active_popup() {
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::Entities)
+        ) {
+            if let Some(ref mut popup) = self.entity_popup {
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        popup.handle_j();
+                    }
+                } else {
+                    for _ in 0..(-scroll_amount).min(10) {
+                        popup.handle_k();
+                    }
+                }
+            }
             return;
         }
 
-        let is_nav_panel = column < self.nav_panel_width();
-
-        if is_nav_panel {
-            let nav_panel_height = self.terminal_size.height.saturating_sub(2);
-            if scroll_amount > 0 {
-                for _ in 0..scroll_amount.min(10) {
-                    self.navigation_panel.scroll_down(nav_panel_height);
-                }
-            } else {
-                for _ in 0..(-scroll_amount).min(10) {
-                    self.navigation_panel.scroll_up(nav_panel_height);
+        if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Count)) {
+            if let Some(ref mut popup) = self.count_popup {
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        popup.handle_j();
+                    }
+                } else {
+                    for _ in 0..(-scroll_amount).min(10) {
+                        popup.handle_k();
+                    }
                 }
             }
-        } else if scroll_amount > 0 {
-            for _ in 0..scroll_amount.min(10) {
-                self.scroll_down();
-            }
-        } else {
-            for _ in 0..(-scroll_amount).min(10) {
-                self.scroll_up();
+            return;
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::QuickSwitcher)
+        ) {
+            if let Some(ref mut popup) = self.quick_switcher_popup {
+                popup.select_offset(scroll_amount.clamp(-10, 10) as isize);
             }
+            return;
         }
-    }
 
-    pub fn open_with_system_viewer(&mut self) {
-        if let Some(book) = &self.current_book {
-            match self
-                .system_command_executor
-                .open_file_at_chapter(&book.file, book.current_chapter())
-            {
-                Ok(_) => {
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::Timeline)
+        ) {
+            if let Some(ref mut popup) = self.timeline_popup {
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        popup.handle_j();
+                    }
+                } else {
+                    for _ in 0..(-scroll_amount).min(10) {
+                        popup.handle_k();
+                    }
+                }
+            }
+            return;
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ReadingHistory)
+        ) {
+            if let Some(ref mut history) = self.reading_history {
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        history.handle_j();
+                    }
+                } else {
+                    for _ in 0..(-scroll_amount).min(10) {
+                        history.handle_k();
+                    }
+                }
+            }
+            return;
+        }
+
+        // Block scrolling for other popups
+        if self.has_active_popup() {
+            return;
+        }
+
+        let is_nav_panel = column < self.nav_panel_width();
+
+        if is_nav_panel {
+            let nav_panel_height = self.terminal_size.height.saturating_sub(2);
+            if scroll_amount > 0 {
+                for _ in 0..scroll_amount.min(10) {
+                    self.navigation_panel.scroll_down(nav_panel_height);
+                }
+            } else {
+                for _ in 0..(-scroll_amount).min(10) {
+                    self.navigation_panel.scroll_up(nav_panel_height);
+                }
+            }
+        } else if scroll_amount > 0 {
+            for _ in 0..scroll_amount.min(10) {
+                self.scroll_down();
+            }
+        } else {
+            for _ in 0..(-scroll_amount).min(10) {
+                self.scroll_up();
+            }
+        }
+    }
+
+    pub fn open_with_system_viewer(&mut self) {
+        if let Some(book) = &self.current_book {
+            match self
+                .system_command_executor
+                .open_file_at_chapter(&book.file, book.current_chapter())
+            {
+                Ok(_) => {
                     info!(
                         "Successfully opened EPUB with system viewer at chapter {}",
                         book.current_chapter()
@@ -1560,6 +3771,1018 @@ impl App {
         Ok(())
     }
 
+    fn open_resources_popup(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let popup = ResourcesPopup::new(&book.epub);
+        if popup.is_empty() {
+            self.show_info("This book has no resources outside the reading order");
+            return;
+        }
+
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.resources_popup = Some(popup);
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::Resources);
+    }
+
+    /// Ask the main loop to open a file with the program in `$<variable>` (or `fallback`),
+    /// suspending the TUI
+    fn hand_off_file(&mut self, variable: &str, fallback: &str, path: std::path::PathBuf) {
+        let program = std::env::var(variable)
+            .ok()
+            .filter(|program| !program.trim().is_empty())
+            .unwrap_or_else(|| fallback.to_string());
+        self.external_handoff = Some(ExternalHandoff {
+            program,
+            path,
+            temp_file: None,
+        });
+    }
+
+    /// Write the current chapter as Markdown to a temporary file and open it with the
+    /// program in `$<variable>` (or `fallback`)
+    fn hand_off_chapter(&mut self, variable: &str, fallback: &str) {
+        use crate::parsing::markdown_renderer::MarkdownRenderer;
+
+        let Some(doc) = self.text_reader.shared_document() else {
+            self.show_info("No chapter open");
+            return;
+        };
+        let stem = self
+            .current_book
+            .as_ref()
+            .and_then(|book| Path::new(&book.file).file_stem())
+            .map_or_else(
+                || "book".to_string(),
+                |stem| stem.to_string_lossy().into_owned(),
+            );
+        let chapter = self
+            .current_book
+            .as_ref()
+            .map_or(0, |book| book.current_chapter() + 1);
+        // A new file that only we can read, removed once the program is done with it
+        let written = tempfile::Builder::new()
+            .prefix(&format!("bookokcat-{stem}-chapter{chapter}-"))
+            .suffix(".md")
+            .tempfile()
+            .and_then(|mut file| {
+                use std::io::Write;
+                file.write_all(MarkdownRenderer::new().render(&doc).as_bytes())?;
+                Ok(file.into_temp_path())
+            });
+        let temp_file = match written {
+            Ok(temp_file) => temp_file,
+            Err(e) => {
+                error!("Failed to write chapter to a temporary file: {e}");
+                self.show_error(format!("Failed to write chapter: {e}"));
+                return;
+            }
+        };
+        self.hand_off_file(variable, fallback, temp_file.to_path_buf());
+        if let Some(handoff) = &mut self.external_handoff {
+            handoff.temp_file = Some(temp_file);
+        }
+    }
+
+    /// Open the note file of the selected passage (or of the paragraph at the top) in
+    /// `$EDITOR`. A passage without one gets a new file in `<book>_notes/`, made from the
+    /// note template, and a comment linking to it.
+    fn open_passage_note(&mut self) {
+        use crate::note_files::{DEFAULT_NOTE_TEMPLATE, NoteContext, fill_note_template};
+
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        if self.virtual_chapter_active {
+            self.show_info("Note files go with the book's own chapters");
+            return;
+        }
+        let node_index = self
+            .text_reader
+            .selection_node_index()
+            .unwrap_or_else(|| self.text_reader.get_current_node_index());
+        if let Some(path) = self.text_reader.note_file_for_node(node_index) {
+            self.hand_off_file("EDITOR", "vi", path);
+            return;
+        }
+
+        let metadata = |property: &str| book.epub.mdata(property).map(|item| item.value.clone());
+        let chapter = book.current_chapter() + 1;
+        let context = NoteContext {
+            title: metadata("title"),
+            author: metadata("creator"),
+            chapter,
+            chapter_title: self.text_reader.get_chapter_title().map(str::to_string),
+            quote: self.text_reader.get_selected_text(),
+            book_path: book.file.clone(),
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        };
+        let template = match &self.preferences.note_template {
+            Some(template_path) => match std::fs::read_to_string(template_path) {
+                Ok(template) => template,
+                Err(e) => {
+                    self.show_error(format!("Failed to read note template {template_path}: {e}"));
+                    return;
+                }
+            },
+            None => DEFAULT_NOTE_TEMPLATE.to_string(),
+        };
+        let Some(notes_dir) = self.path_next_to_book("notes") else {
+            return;
+        };
+        let path = notes_dir.join(format!(
+            "chapter{chapter}-{}.md",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let written = std::fs::create_dir_all(&notes_dir).and_then(|()| {
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .and_then(|mut file| {
+                    std::io::Write::write_all(
+                        &mut file,
+                        fill_note_template(&template, &context).as_bytes(),
+                    )
+                })
+        });
+        if let Err(e) = written {
+            error!("Failed to create note file {path:?}: {e}");
+            self.show_error(format!("Failed to create note file: {e}"));
+            return;
+        }
+        if let Err(e) = self.text_reader.link_note_file(node_index, &path) {
+            error!("Failed to link note file {path:?}: {e}");
+            self.show_error(format!("Failed to link the note file: {e}"));
+        }
+        self.text_reader.clear_selection();
+        self.hand_off_file("EDITOR", "vi", path);
+    }
+
+    /// A program the main loop should run on a file with the TUI suspended
+    pub fn take_external_handoff(&mut self) -> Option<ExternalHandoff> {
+        self.external_handoff.take()
+    }
+
+    /// Show what the current chapter is about: its headings and most frequent terms
+    fn open_chapter_summary(&mut self) {
+        let Some(doc) = self.text_reader.shared_document() else {
+            self.show_info("No chapter open");
+            return;
+        };
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        let title = self
+            .text_reader
+            .get_chapter_title()
+            .unwrap_or("Current chapter")
+            .to_string();
+        self.chapter_summary_popup = Some(ChapterSummaryPopup::new(
+            title,
+            crate::chapter_summary::ChapterSummary::of(&doc),
+        ));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ChapterSummary);
+    }
+
+    /// Index the names mentioned in the book and show where each one appears
+    fn open_entity_popup(&mut self) {
+        let (Some(book), Some(book_search)) = (&self.current_book, &self.book_search) else {
+            self.show_info("No book open");
+            return;
+        };
+        let chapter_count = book.epub.get_num_chapters();
+        let entities =
+            crate::entity_index::index_entities(book_search.search_engine().chapter_lines());
+        if entities.is_empty() {
+            self.show_info("No recurring names found in this book");
+            return;
+        }
+
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.entity_popup = Some(EntityPopup::new(entities, chapter_count));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::Entities);
+    }
+
+    /// Count a term in every chapter, to judge how central it is to the book
+    fn open_count_popup(&mut self, term: String) {
+        let Some(book_search) = &self.book_search else {
+            self.show_info("No book open");
+            return;
+        };
+        let counts = book_search.search_engine().count_occurrences(&term);
+        if counts.is_empty() {
+            self.show_info(format!("\"{term}\" doesn't appear in this book"));
+            return;
+        }
+
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.count_popup = Some(CountPopup::new(term, counts));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::Count);
+    }
+
+    fn open_timeline_popup(&mut self) {
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        if self.timeline.is_empty() {
+            self.show_info("No dated headings or epigraphs in this book");
+            return;
+        }
+
+        let current_chapter = book.epub.get_current_chapter();
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.timeline_popup = Some(TimelinePopup::new(self.timeline.clone(), current_chapter));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::Timeline);
+    }
+
+    /// `Ctrl+P`: pick any chapter of the open book or any book in the library by typing
+    /// part of its title
+    fn open_quick_switcher(&mut self) {
+        let mut entries = self
+            .navigation_panel
+            .table_of_contents
+            .get_current_book_info()
+            .filter(|_| self.current_book.is_some())
+            .map(|info| chapter_entries(&info.toc_items))
+            .unwrap_or_default();
+        entries.extend(self.book_manager.books.iter().map(|book| {
+            crate::widget::quick_switcher_popup::SwitchEntry {
+                title: book.display_name.clone(),
+                target: SwitchTarget::Book(book.path.clone()),
+            }
+        }));
+
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.quick_switcher_popup = Some(QuickSwitcherPopup::new(entries));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::QuickSwitcher);
+    }
+
+    fn open_page_list_popup(&mut self) {
+        if self.current_book.is_none() {
+            return;
+        }
+        if self.page_list.is_empty() {
+            self.show_info("This book has no print page list");
+            return;
+        }
+
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        let current_page = self.current_print_page();
+        self.page_list_popup = Some(PageListPopup::new(self.page_list.clone(), current_page));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::PageList);
+    }
+
+    /// Label of the last print page that starts at or before the top of the screen
+    fn current_print_page(&self) -> Option<String> {
+        self.print_page_at(self.text_reader.get_scroll_offset())
+    }
+
+    /// Label of the last print page that starts at or before rendered line `line`
+    fn print_page_at(&self, line: usize) -> Option<String> {
+        let current_chapter = self.current_book.as_ref()?.current_chapter();
+
+        let mut current = None;
+        for page in &self.page_list {
+            let Some(spine_index) = self.find_spine_index_by_href(&page.href) else {
+                continue;
+            };
+            let started = match spine_index.cmp(&current_chapter) {
+                std::cmp::Ordering::Less => true,
+                std::cmp::Ordering::Equal => page
+                    .anchor
+                    .as_deref()
+                    .and_then(|anchor| self.text_reader.get_anchor_position(anchor))
+                    .is_none_or(|anchor_line| anchor_line <= line),
+                std::cmp::Ordering::Greater => false,
+            };
+            if started {
+                current = Some(page.label.clone());
+            }
+        }
+        current
+    }
+
+    fn current_deep_link(&self) -> Option<DeepLink> {
+        let book = self.current_book.as_ref()?;
+        let href = Self::get_chapter_href(&book.epub, book.current_chapter())?;
+        let anchor = self
+            .text_reader
+            .anchor_at_or_before(self.text_reader.get_scroll_offset())
+            .map(str::to_string);
+        Some(DeepLink {
+            path: book.file.clone(),
+            href,
+            anchor,
+        })
+    }
+
+    /// Show the deep link to the current position and copy it to the clipboard
+    fn copy_deep_link(&mut self) {
+        let Some(link) = self.current_deep_link().map(|link| link.to_string()) else {
+            self.show_info("No book open");
+            return;
+        };
+        match self.text_reader.copy_text_to_clipboard(link.clone()) {
+            Ok(()) => self.show_info(format!("Copied {link}")),
+            Err(e) => {
+                warn!("Failed to copy deep link: {e}");
+                self.show_info(link);
+            }
+        }
+    }
+
+    pub fn set_session_recorder(&mut self, recorder: Arc<Mutex<SessionRecorder>>) {
+        self.session_recorder = Some(recorder);
+        self.record_session_state();
+    }
+
+    /// Where the app is, for session recordings: book file name, chapter, focused panel
+    /// and any half-typed key sequence
+    fn record_session_state(&self) {
+        let Some(recorder) = &self.session_recorder else {
+            return;
+        };
+        let mut state = String::new();
+        if let Some(book) = &self.current_book {
+            let name = Path::new(&book.file)
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default();
+            state.push_str(&format!(
+                "book={name} chapter={}/{} ",
+                book.current_chapter() + 1,
+                book.total_chapters()
+            ));
+        }
+        state.push_str(&format!("focus={:?}", self.focused_panel));
+        let pending = self.key_sequence.current_sequence();
+        if !pending.is_empty() {
+            state.push_str(&format!(" pending={pending:?}"));
+        }
+        if let Ok(mut recorder) = recorder.lock() {
+            recorder.record_state(&state);
+            recorder.set_typing(self.is_typing());
+        }
+    }
+
+    /// Whether keys go into a text field rather than run commands
+    fn is_typing(&self) -> bool {
+        self.command_line.is_some()
+            || self.text_reader.is_comment_input_active()
+            || self.is_search_input_mode()
+            || match self.focused_panel {
+                FocusedPanel::Popup(PopupWindow::BookSearch) => self
+                    .book_search
+                    .as_ref()
+                    .is_some_and(BookSearch::is_input_focused),
+                FocusedPanel::Popup(PopupWindow::QuickSwitcher | PopupWindow::PageList) => true,
+                _ => false,
+            }
+    }
+
+    /// Reverse the last undoable change (TOC folding, comment deletion)
+    fn undo(&mut self) {
+        let Some(entry) = self.undo_history.take_undo() else {
+            self.show_info("Nothing to undo");
+            return;
+        };
+        let description = entry.describe();
+        match self.apply_undo_entry(entry) {
+            Ok(reverse) => {
+                self.undo_history.push_redo(reverse);
+                self.show_info(format!("Undid {description}"));
+            }
+            Err(e) => {
+                error!("Failed to undo {description}: {e}");
+                self.show_error(format!("Failed to undo {description}: {e}"));
+            }
+        }
+    }
+
+    /// Reapply the last change that was undone
+    fn redo(&mut self) {
+        let Some(entry) = self.undo_history.take_redo() else {
+            self.show_info("Nothing to redo");
+            return;
+        };
+        let description = entry.describe();
+        match self.apply_undo_entry(entry) {
+            Ok(reverse) => {
+                self.undo_history.push_undo(reverse);
+                self.show_info(format!("Redid {description}"));
+            }
+            Err(e) => {
+                error!("Failed to redo {description}: {e}");
+                self.show_error(format!("Failed to redo {description}: {e}"));
+            }
+        }
+    }
+
+    /// Put back the state an entry recorded; returns the entry that reverses this
+    fn apply_undo_entry(&mut self, entry: UndoEntry) -> Result<UndoEntry> {
+        match entry {
+            UndoEntry::TocExpansion(snapshot) => {
+                let toc = &mut self.navigation_panel.table_of_contents;
+                let current = toc
+                    .expansion_snapshot()
+                    .ok_or_else(|| anyhow::anyhow!("No table of contents"))?;
+                toc.restore_expansion(&snapshot);
+                self.remember_toc_expansion();
+                Ok(UndoEntry::TocExpansion(current))
+            }
+            UndoEntry::CommentDeleted(comment) => {
+                self.text_reader.restore_comment(comment.clone())?;
+                Ok(UndoEntry::CommentRestored(comment))
+            }
+            UndoEntry::CommentRestored(comment) => {
+                let deleted = self
+                    .text_reader
+                    .delete_comment(
+                        &comment.chapter_href,
+                        comment.paragraph_index,
+                        comment.word_range,
+                    )?
+                    .unwrap_or(comment);
+                Ok(UndoEntry::CommentDeleted(deleted))
+            }
+        }
+    }
+
+    /// Report the word and character counts of the selection, or of the whole chapter
+    fn report_word_count(&mut self) {
+        if self.current_book.is_none() {
+            self.show_info("No book open");
+            return;
+        }
+        let message = match self.text_reader.get_selected_text() {
+            Some(selection) => format!("Selection: {}", TextStats::of(&selection)),
+            None => {
+                let text = self
+                    .text_reader
+                    .shared_document()
+                    .map(|document| document.plain_text())
+                    .unwrap_or_default();
+                format!("Chapter: {}", TextStats::of(&text))
+            }
+        };
+        self.show_info(message);
+    }
+
+    /// Open the book a deep link points at and jump to its chapter and anchor. Books are
+    /// matched by path first, then by file name so links work across libraries.
+    pub fn open_deep_link(&mut self, link: &DeepLink) -> Result<()> {
+        let file_name = std::path::Path::new(&link.path).file_name();
+        let book_index = self
+            .book_manager
+            .books
+            .iter()
+            .position(|book| book.path == link.path)
+            .or_else(|| {
+                self.book_manager.books.iter().position(|book| {
+                    file_name.is_some() && std::path::Path::new(&book.path).file_name() == file_name
+                })
+            })
+            .ok_or_else(|| anyhow::anyhow!("Book not found in library: {}", link.path))?;
+
+        self.open_book_for_reading(book_index)?;
+        if self.current_book.is_none() {
+            // PDFs load in the background and have no chapters to jump to yet
+            return Ok(());
+        }
+
+        if !self
+            .navigate_to_chapter_by_file(&link.href, link.anchor.as_ref())
+            .unwrap_or(false)
+        {
+            anyhow::bail!("Chapter not found in book: {}", link.href);
+        }
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+        Ok(())
+    }
+
+    fn jump_to_page(&mut self, page: &PageTarget) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let Some(spine_index) = self.find_spine_index_by_href(&page.href) else {
+            error!(
+                "Could not find spine index for page {}: {}",
+                page.label, page.href
+            );
+            self.show_error(format!("Page {} not found in book", page.label));
+            return;
+        };
+
+        self.jump_list.push(JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+        });
+
+        if let Err(e) = self.navigate_to_chapter(spine_index) {
+            error!("Failed to jump to page {}: {e}", page.label);
+            self.show_error(format!("Failed to jump to page {}", page.label));
+            return;
+        }
+        if let Some(anchor) = &page.anchor {
+            self.text_reader.store_pending_anchor_scroll(anchor.clone());
+        }
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+        self.show_info(format!("Page {}", page.label));
+    }
+
+    /// Extract resources into a `<book>_resources` directory next to the book
+    fn extract_book_resources(&mut self, ids: &[String]) {
+        let Some(book) = &mut self.current_book else {
+            return;
+        };
+        let book_path = std::path::Path::new(&book.file);
+        let stem = book_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "book".to_string());
+        let target_dir = book_path.with_file_name(format!("{stem}_resources"));
+
+        match extract_resources(&mut book.epub, ids, &target_dir) {
+            Ok(count) => self.show_info(format!(
+                "Extracted {count} file(s) to {}",
+                target_dir.display()
+            )),
+            Err(e) => {
+                error!("Failed to extract resources to {target_dir:?}: {e}");
+                self.show_error(format!("Failed to extract resources: {e}"));
+            }
+        }
+    }
+
+    /// Logical cells of the table on screen along with its node index
+    fn focused_table_grid(&mut self) -> Option<(usize, Vec<Vec<String>>)> {
+        let grid = self.text_reader.focused_table_index().and_then(|index| {
+            let doc = self.text_reader.shared_document()?;
+            match &doc.blocks.get(index)?.block {
+                crate::markdown::Block::Table { header, rows, .. } => {
+                    Some((index, table_grid(header.as_ref(), rows)))
+                }
+                _ => None,
+            }
+        });
+        if grid.is_none() {
+            self.show_info("No table on screen");
+        }
+        grid
+    }
+
+    /// Write the table on screen to `<book>_chapter<N>_table<M>.csv` next to the book
+    fn export_focused_table(&mut self) {
+        let Some((index, grid)) = self.focused_table_grid() else {
+            return;
+        };
+        let Some(chapter) = self
+            .current_book
+            .as_ref()
+            .map(|book| book.current_chapter() + 1)
+        else {
+            return;
+        };
+        let Some(target) = self.path_next_to_book(&format!("chapter{chapter}_table{index}.csv"))
+        else {
+            return;
+        };
+
+        match std::fs::write(&target, to_csv(&grid)) {
+            Ok(()) => self.show_info(format!(
+                "Exported {} row(s) to {}",
+                grid.len(),
+                target.display()
+            )),
+            Err(e) => {
+                error!("Failed to export table to {target:?}: {e}");
+                self.show_error(format!("Failed to export table: {e}"));
+            }
+        }
+    }
+
+    fn copy_focused_table(&mut self) {
+        let Some((_, grid)) = self.focused_table_grid() else {
+            return;
+        };
+        match self.text_reader.copy_text_to_clipboard(to_tsv(&grid)) {
+            Ok(()) => self.show_info(format!("Copied {} table row(s) as TSV", grid.len())),
+            Err(e) => {
+                error!("Failed to copy table: {e}");
+                self.show_error(e);
+            }
+        }
+    }
+
+    /// Node index, language and source of the code block on screen
+    fn focused_code_block(&mut self) -> Option<(usize, Option<String>, String)> {
+        let code = self
+            .text_reader
+            .focused_code_block_index()
+            .and_then(|index| {
+                let doc = self.text_reader.shared_document()?;
+                match &doc.blocks.get(index)?.block {
+                    crate::markdown::Block::CodeBlock { language, content } => {
+                        Some((index, language.clone(), content.clone()))
+                    }
+                    _ => None,
+                }
+            });
+        if code.is_none() {
+            self.show_info("No code block on screen");
+        }
+        code
+    }
+
+    /// `<book>_<suffix>` next to the book file
+    fn path_next_to_book(&self, suffix: &str) -> Option<std::path::PathBuf> {
+        let book_path = std::path::Path::new(&self.current_book.as_ref()?.file);
+        let stem = book_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "book".to_string());
+        Some(book_path.with_file_name(format!("{stem}_{suffix}")))
+    }
+
+    /// Open the command line with `write-code` and a file name based on the language hint
+    fn prompt_code_block_file(&mut self) {
+        let Some((index, language, _)) = self.focused_code_block() else {
+            return;
+        };
+        let Some(chapter) = self
+            .current_book
+            .as_ref()
+            .map(|book| book.current_chapter() + 1)
+        else {
+            return;
+        };
+        let extension = extension_for_language(language.as_deref());
+        if let Some(path) =
+            self.path_next_to_book(&format!("chapter{chapter}_code{index}.{extension}"))
+        {
+            self.key_sequence.clear();
+            self.command_line = Some(CommandLine::with_input(&format!(
+                "write-code {}",
+                path.display()
+            )));
+        }
+    }
+
+    fn write_focused_code_block(&mut self, path: &str) {
+        let Some((_, _, code)) = self.focused_code_block() else {
+            return;
+        };
+        let path = std::path::Path::new(path);
+        if path.exists() {
+            self.show_error(format!("{} already exists", path.display()));
+            return;
+        }
+
+        let result = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, &code));
+        match result {
+            Ok(()) => self.show_info(format!("Saved code to {}", path.display())),
+            Err(e) => {
+                error!("Failed to save code block to {path:?}: {e}");
+                self.show_error(format!("Failed to save code: {e}"));
+            }
+        }
+    }
+
+    fn append_focused_code_block(&mut self, path: Option<String>) {
+        let Some((_, _, code)) = self.focused_code_block() else {
+            return;
+        };
+        let Some(path) = path
+            .or_else(|| self.preferences.code_scratch_file.clone())
+            .map(std::path::PathBuf::from)
+            .or_else(|| self.path_next_to_book("snippets.txt"))
+        else {
+            return;
+        };
+
+        let source = self
+            .text_reader
+            .get_chapter_title()
+            .map(str::to_string)
+            .or_else(|| {
+                self.current_book
+                    .as_ref()
+                    .map(|book| format!("Chapter {}", book.current_chapter() + 1))
+            })
+            .unwrap_or_default();
+        match append_snippet(&path, &source, &code) {
+            Ok(()) => self.show_info(format!("Appended code to {}", path.display())),
+            Err(e) => {
+                error!("Failed to append code block to {path:?}: {e}");
+                self.show_error(format!("Failed to append code: {e}"));
+            }
+        }
+    }
+
+    /// Apply the day or night preset of the theme schedule, honoring a manual override
+    fn update_scheduled_theme(&mut self) {
+        let Some(schedule) = &self.preferences.theme_schedule else {
+            return;
+        };
+        let scheduled_day = schedule.is_day_at(chrono::Local::now());
+        let is_day = match self.theme_override {
+            Some((is_day, set_during_day)) if set_during_day == scheduled_day => is_day,
+            _ => {
+                self.theme_override = None;
+                scheduled_day
+            }
+        };
+
+        let preset = schedule.preset(is_day);
+        if crate::theme::active_theme() != preset {
+            crate::theme::set_active_theme(preset);
+        }
+    }
+
+    fn toggle_day_night_theme(&mut self) {
+        let Some(schedule) = &self.preferences.theme_schedule else {
+            self.show_info("Set theme_schedule in preferences.json to switch day/night themes");
+            return;
+        };
+        let scheduled_day = schedule.is_day_at(chrono::Local::now());
+        let current_day = self
+            .theme_override
+            .map_or(scheduled_day, |(is_day, _)| is_day);
+        let is_day = !current_day;
+
+        self.theme_override = (is_day != scheduled_day).then_some((is_day, scheduled_day));
+        self.update_scheduled_theme();
+        self.show_info(if is_day { "Day theme" } else { "Night theme" });
+    }
+
+    /// Show the virtual chapter compiled from every footnote/endnote in the book
+    fn open_notes_view(&mut self) {
+        if self.current_book.is_none() || self.book_notes.is_empty() {
+            self.show_info("This book has no footnotes or endnotes");
+            return;
+        }
+
+        self.save_bookmark_with_throttle(true);
+        self.text_reader.set_content_from_document(
+            self.book_notes.to_document(),
+            Some(NOTES_TITLE.to_string()),
+        );
+        // Back-references must always navigate to their source chapter
+        self.text_reader.set_current_chapter_file(None);
+        self.virtual_chapter_active = true;
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+    }
+
+    /// Open another edition beside the reader, showing the counterpart of each chapter
+    /// read, matched by heading and text
+    fn open_edition_comparison(&mut self, other_path: &str) {
+        let converter = self.text_reader.converter();
+        let Some(book) = &mut self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        match EditionPane::open(&mut book.epub, &book.file, other_path, converter) {
+            Ok(pane) => {
+                self.show_info(pane.summary());
+                self.edition_pane = Some(pane);
+            }
+            Err(e) => self.show_error(format!("{e:#}")),
+        }
+    }
+
+    /// `:diff <n>`: what changed from chapter `other_index` to the current one
+    fn open_chapter_diff(&mut self, other_index: usize) {
+        use crate::chapter_diff::chapter_html;
+
+        let Some(book) = &mut self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let total_chapters = book.total_chapters();
+        if other_index >= total_chapters {
+            self.show_error(format!(
+                "No chapter {} (the book has {total_chapters})",
+                other_index + 1
+            ));
+            return;
+        }
+        let current_index = book.current_chapter();
+        let (Some(old_html), Some(new_html)) = (
+            chapter_html(&mut book.epub, other_index),
+            chapter_html(&mut book.epub, current_index),
+        ) else {
+            self.show_error("Failed to read the chapters to compare");
+            return;
+        };
+        self.show_chapter_diff(
+            &format!("Chapter {}", other_index + 1),
+            &old_html,
+            &format!("Chapter {}", current_index + 1),
+            &new_html,
+        );
+    }
+
+    /// `:diff <file.epub>`: what changed in the current chapter since the other edition.
+    /// Its counterpart there is found like `:compare` does, or else by position.
+    fn open_edition_diff(&mut self, other_path: &str) {
+        use crate::chapter_diff::chapter_html;
+        use crate::edition_compare::{edition_chapters, match_chapters};
+
+        let Some(book) = &mut self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let mut other = match EpubDoc::new(other_path) {
+            Ok(other) => other,
+            Err(e) => {
+                self.show_error(format!("Failed to open {other_path}: {e}"));
+                return;
+            }
+        };
+        let current_index = book.current_chapter();
+        let other_index = match_chapters(
+            &edition_chapters(&mut book.epub),
+            &edition_chapters(&mut other),
+        )
+        .into_iter()
+        .find(|pair| {
+            pair.left
+                .as_ref()
+                .is_some_and(|(idx, _)| *idx == current_index)
+        })
+        .map_or(Some(current_index), |pair| pair.right.map(|(idx, _)| idx));
+        let Some(old_html) = other_index.and_then(|idx| chapter_html(&mut other, idx)) else {
+            self.show_info(format!("This chapter isn't in {other_path}"));
+            return;
+        };
+        let Some(new_html) = chapter_html(&mut book.epub, current_index) else {
+            self.show_error("Failed to read the current chapter");
+            return;
+        };
+        let file_name = |path: &str| {
+            std::path::Path::new(path).file_name().map_or_else(
+                || path.to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            )
+        };
+        let old_name = file_name(other_path);
+        let new_name = file_name(&book.file);
+        self.show_chapter_diff(&old_name, &old_html, &new_name, &new_html);
+    }
+
+    /// Convert both versions like the reader would and show their differences in place of
+    /// the chapter
+    fn show_chapter_diff(
+        &mut self,
+        old_name: &str,
+        old_html: &str,
+        new_name: &str,
+        new_html: &str,
+    ) {
+        use crate::chapter_diff::{diff_blocks, diff_report};
+        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+        let mut converter = HtmlToMarkdownConverter::new()
+            .with_filters(self.preferences.content_filters.clone())
+            .with_cleanup(self.preferences.text_cleanup)
+            .with_substitutions(self.text_reader.substitutions().clone())
+            .with_dialog_grouping(self.text_reader.dialog_grouping());
+        let old = converter.convert(old_html);
+        let new = converter.convert(new_html);
+        let report = diff_report(old_name, new_name, diff_blocks(&old, &new));
+
+        self.save_bookmark_with_throttle(true);
+        self.text_reader
+            .set_content_from_document(report, Some("Chapter diff".to_string()));
+        self.text_reader.set_current_chapter_file(None);
+        self.virtual_chapter_active = true;
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+    }
+
+    /// Convert every chapter with diagnostics on and show what their markup lost, in
+    /// place of the chapter like the notes view
+    fn open_doctor_report(&mut self) {
+        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+        let Some(book) = &mut self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let original_chapter = book.current_chapter();
+        let chapter_count = book.total_chapters();
+        let mut converter = HtmlToMarkdownConverter::with_diagnostics()
+            .with_filters(self.preferences.content_filters.clone())
+            .with_cleanup(self.preferences.text_cleanup)
+            .with_substitutions(self.text_reader.substitutions().clone());
+        let mut converted = Vec::new();
+        for chapter_index in 0..chapter_count {
+            if !book.epub.set_current_chapter(chapter_index) {
+                continue;
+            }
+            let Some((raw_html, _)) = book.epub.get_current_str() else {
+                continue;
+            };
+            let doc = converter.convert(&raw_html);
+            let links: Vec<Inline> = doc.links().into_iter().cloned().collect();
+            let diagnostics = converter.diagnostics().cloned().unwrap_or_default();
+            let href = Self::get_chapter_href(&book.epub, chapter_index).unwrap_or_default();
+            converted.push((format!("{}. {href}", chapter_index + 1), diagnostics, links));
+        }
+        book.epub.set_current_chapter(original_chapter);
+
+        let mut chapters = Vec::new();
+        for (label, mut diagnostics, links) in converted {
+            diagnostics.broken_links = self.broken_chapter_links(&links);
+            if !diagnostics.is_empty() {
+                chapters.push((label, diagnostics));
+            }
+        }
+
+        self.save_bookmark_with_throttle(true);
+        self.text_reader.set_content_from_document(
+            doctor_report(&chapters, chapter_count),
+            Some("Markup diagnostics".to_string()),
+        );
+        self.text_reader.set_current_chapter_file(None);
+        self.virtual_chapter_active = true;
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+    }
+
+    /// Check the open book's file for structural problems and list them in place of the
+    /// chapter, like the doctor report
+    fn open_check_report(&mut self) {
+        let Some(book) = &self.current_book else {
+            self.show_info("No book open");
+            return;
+        };
+        let report = crate::epub_check::check_epub(Path::new(&book.file));
+        info!("EPUB check of {}: {}", book.file, report.summary());
+
+        self.save_bookmark_with_throttle(true);
+        self.text_reader
+            .set_content_from_document(report.to_document(), Some("EPUB check".to_string()));
+        self.text_reader.set_current_chapter_file(None);
+        self.virtual_chapter_active = true;
+        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+    }
+
+    /// Jump to a random chapter that hasn't been read yet (handy for anthologies)
+    fn jump_to_random_unread_chapter(&mut self) {
+        use rand::seq::SliceRandom;
+
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let current_chapter = book.current_chapter();
+        let unread: Vec<usize> = (0..book.total_chapters())
+            .filter(|&idx| {
+                idx != current_chapter && !self.bookmarks.is_chapter_read(&book.file, idx)
+            })
+            .collect();
+
+        let Some(&chapter_index) = unread.choose(&mut rand::thread_rng()) else {
+            self.show_info("Every chapter has already been read");
+            return;
+        };
+
+        self.jump_list.push(JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: current_chapter,
+            node_index: self.text_reader.get_current_node_index(),
+        });
+
+        if let Err(e) = self.navigate_to_chapter(chapter_index) {
+            error!("Failed to jump to random chapter: {e}");
+            self.show_error(format!("Failed to jump to random chapter: {e}"));
+        } else {
+            self.show_info(format!(
+                "Random unread chapter ({} unread left)",
+                unread.len() - 1
+            ));
+        }
+    }
+
     /// Handle Ctrl+O - jump back in history
     fn jump_back(&mut self) {
         if let Some(location) = self.jump_list.jump_back() {
@@ -1582,8 +4805,27 @@ impl App {
 
     /// Calculate the navigation panel width based on stored terminal width
     fn nav_panel_width(&self) -> u16 {
-        // 30% of terminal width, minimum 20 columns
-        ((self.terminal_size.width * 30) / 100).max(20)
+        // The panel's share of terminal width, minimum 20 columns
+        ((self.terminal_size.width * self.nav_panel_percent()) / 100).max(20)
+    }
+
+    fn nav_panel_percent(&self) -> u16 {
+        self.preferences
+            .nav_panel_percent
+            .clamp(MIN_NAV_PERCENT, MAX_NAV_PERCENT)
+    }
+
+    /// The screen above the help bar split into the navigation panel and the reader
+    fn split_main_area(&self, area: Rect) -> [Rect; 2] {
+        use ratatui::layout::{Constraint, Direction, Layout};
+        let percent = self.nav_panel_percent();
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(percent),
+                Constraint::Percentage(100 - percent),
+            ])
+            .areas(area)
     }
 
     /// Get the navigation panel area based on current terminal size
@@ -1594,11 +4836,44 @@ impl App {
             .direction(Direction::Vertical)
             .constraints([Constraint::Min(0), Constraint::Length(3)])
             .split(self.terminal_size);
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-            .split(chunks[0]);
-        main_chunks[0]
+        self.split_main_area(chunks[0])[0]
+    }
+
+    /// Whether a click lands on the navigation panel's right border, the splitter between
+    /// it and the reader. The reader's own border next to it is left for selecting text.
+    fn is_on_splitter(&self, column: u16, row: u16) -> bool {
+        let nav_area = self.get_navigation_panel_area();
+        row >= nav_area.y && row < nav_area.bottom() && column + 1 == nav_area.right()
+    }
+
+    /// Go to a chapter of the table of contents, picked in the navigation panel or the
+    /// quick switcher
+    fn open_toc_chapter(&mut self, href: &str, anchor: Option<String>) {
+        if href == NOTES_HREF {
+            self.open_notes_view();
+            return;
+        }
+        // Find the spine index for this href
+        if let Some(spine_index) = self.find_spine_index_by_href(href) {
+            let _ = self.navigate_to_chapter(spine_index);
+            // Handle anchor if present
+            if let Some(anchor_id) = anchor {
+                self.text_reader
+                    .store_pending_anchor_scroll(anchor_id.clone());
+                self.text_reader.set_active_anchor(Some(anchor_id));
+            } else {
+                self.text_reader.set_active_anchor(None);
+            }
+
+            self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+            self.navigation_panel
+                .table_of_contents
+                .clear_manual_navigation();
+            self.update_toc_state();
+        } else {
+            error!("Could not find spine index for href: {href}");
+            self.show_error("Chapter not found in book");
+        }
     }
 
     /// Handle Enter key press in navigation panel
@@ -1611,33 +4886,13 @@ impl App {
                     self.show_error(format!("Failed to open book: {e}"));
                 }
             }
-            SelectedActionOwned::BackToBooks => {
-                self.navigation_panel.switch_to_book_mode();
-            }
-            SelectedActionOwned::TocItem(toc_item) => {
-                match toc_item {
-                    TocItem::Chapter { href, anchor, .. } => {
-                        // Find the spine index for this href
-                        if let Some(spine_index) = self.find_spine_index_by_href(&href) {
-                            let _ = self.navigate_to_chapter(spine_index);
-                            // Handle anchor if present
-                            if let Some(anchor_id) = anchor {
-                                self.text_reader
-                                    .store_pending_anchor_scroll(anchor_id.clone());
-                                self.text_reader.set_active_anchor(Some(anchor_id));
-                            } else {
-                                self.text_reader.set_active_anchor(None);
-                            }
-
-                            self.focused_panel = FocusedPanel::Main(MainPanel::Content);
-                            self.navigation_panel
-                                .table_of_contents
-                                .clear_manual_navigation();
-                            self.update_toc_state();
-                        } else {
-                            error!("Could not find spine index for href: {href}");
-                            self.show_error("Chapter not found in book");
-                        }
+            SelectedActionOwned::BackToBooks => {
+                self.navigation_panel.switch_to_book_mode();
+            }
+            SelectedActionOwned::TocItem(toc_item) => {
+                match toc_item {
+                    TocItem::Chapter { href, anchor, .. } => {
+                        self.open_toc_chapter(&href, anchor);
                     }
                     TocItem::Section { href, anchor, .. } => {
                         if let Some(section_href) = href {
@@ -1668,6 +4923,7 @@ impl App {
                             self.navigation_panel
                                 .table_of_contents
                                 .toggle_selected_expansion();
+                            self.remember_toc_expansion();
                         }
                     }
                 }
@@ -1686,7 +4942,7 @@ impl App {
 
         self.terminal_size = f.area();
 
-        let background_block = Block::default().style(Style::default().bg(OCEANIC_NEXT.base_00));
+        let background_block = Block::default().style(Style::default().bg(current_theme().base_00));
         f.render_widget(background_block, f.area());
 
         let chunks = Layout::default()
@@ -1694,16 +4950,49 @@ impl App {
             .constraints([Constraint::Min(0), Constraint::Length(3)])
             .split(f.area());
 
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-            .split(chunks[0]);
+        let mut main_chunks = self.split_main_area(chunks[0]);
+        let mut dock_areas = Vec::new();
+        if !self.docks.is_empty() {
+            let [reader, strip] = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(main_chunks[1]);
+            main_chunks[1] = reader;
+            dock_areas = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Ratio(1, self.docks.len() as u32);
+                    self.docks.len()
+                ])
+                .split(strip)
+                .to_vec();
+        }
 
+        let mut edition_area = None;
+        if self.edition_pane.is_some() && self.current_book.is_some() {
+            let [reader, other] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(main_chunks[1]);
+            main_chunks[1] = reader;
+            edition_area = Some(other);
+        }
+
+        self.focus_ring.clear();
+        self.focus_ring
+            .push(MainPanel::NavigationList, main_chunks[0]);
+        self.focus_ring.push(MainPanel::Content, main_chunks[1]);
+        for (dock, area) in self.docks.iter().zip(&dock_areas) {
+            self.focus_ring.push(MainPanel::Dock(dock.kind), *area);
+        }
+
+        self.update_scheduled_theme();
+        self.update_toc_word_counts();
         self.navigation_panel.render(
             f,
             main_chunks[0],
             self.is_main_panel(MainPanel::NavigationList),
-            &OCEANIC_NEXT,
+            current_theme(),
             &self.book_manager,
         );
 
@@ -1713,16 +5002,48 @@ impl App {
                 main_chunks[1],
                 book.current_chapter(),
                 book.total_chapters(),
-                &OCEANIC_NEXT,
+                current_theme(),
                 self.is_main_panel(MainPanel::Content),
             );
+            if let (Some(pane), Some(area)) = (&mut self.edition_pane, edition_area) {
+                let max_offset = self.text_reader.get_max_scroll_offset();
+                let progress = if max_offset == 0 {
+                    0.0
+                } else {
+                    self.text_reader.get_scroll_offset() as f64 / max_offset as f64
+                };
+                pane.render(f, area, book.current_chapter(), progress, current_theme());
+            }
+        } else if self.welcome_screen.is_some() {
+            let is_focused = self.is_main_panel(MainPanel::Content);
+            if let Some(welcome_screen) = self.welcome_screen.as_mut() {
+                welcome_screen.render(f, main_chunks[1], is_focused);
+            }
         } else {
             self.render_default_content(f, main_chunks[1], "Select a file to view its content");
         }
 
+        self.refresh_docks();
+        for (index, area) in dock_areas.into_iter().enumerate() {
+            let kind = self.docks[index].kind;
+            let is_focused = self.is_main_panel(MainPanel::Dock(kind));
+            self.docks[index].render(f, area, is_focused, current_theme());
+        }
+
         self.render_help_bar(f, chunks[1], fps_counter);
         self.help_bar_area = chunks[1];
 
+        if self.show_frame_stats {
+            let (layout_hits, layout_misses) = self.text_reader.layout_cache_stats();
+            let figures = CacheFigures {
+                layout_hits,
+                layout_misses,
+                cached_chapters: self.chapter_cache.cached_count(),
+                cached_bytes: self.chapter_cache.approximate_bytes(),
+            };
+            frame_stats_overlay::render(f, main_chunks[1], &self.frame_stats, &figures);
+        }
+
         if matches!(
             self.focused_panel,
             FocusedPanel::Popup(PopupWindow::ReadingHistory)
@@ -1763,7 +5084,7 @@ impl App {
             f.render_widget(dim_block, f.area());
 
             if let Some(ref mut book_search) = self.book_search {
-                book_search.render(f, f.area(), &OCEANIC_NEXT);
+                book_search.render(f, f.area(), current_theme());
             }
         }
 
@@ -1806,7 +5127,7 @@ impl App {
             f.render_widget(dim_block, f.area());
 
             if let Some(ref mut popup) = self.chatgpt_popup {
-                popup.render(f, f.area(), &OCEANIC_NEXT);
+                popup.render(f, f.area(), current_theme());
             }
         }
 
@@ -1822,7 +5143,116 @@ impl App {
             f.render_widget(dim_block, f.area());
 
             if let Some(ref mut popup) = self.language_select_popup {
-                popup.render(f, f.area(), &OCEANIC_NEXT);
+                popup.render(f, f.area(), current_theme());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::Resources)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.resources_popup {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::PageList)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.page_list_popup {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::Entities)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.entity_popup {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::Timeline)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.timeline_popup {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::QuickSwitcher)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.quick_switcher_popup {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Count)) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.count_popup {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ChapterSummary)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Reset)
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.chapter_summary_popup {
+                popup.render(f, f.area());
             }
         }
 
@@ -1830,22 +5260,32 @@ impl App {
         if let Ok(dialog) = self.progress_dialog.lock() {
             dialog.render(f, f.area());
         }
+
+        if self.alerts.is_flashing() {
+            let area = f.area();
+            f.buffer_mut()
+                .set_style(area, Style::default().add_modifier(Modifier::REVERSED));
+        }
+
+        if crate::theme::active_theme() == crate::theme::ThemePreset::Monochrome {
+            crate::color_mode::strip_colors(f.buffer_mut());
+        }
     }
 
     fn render_default_content(&self, f: &mut ratatui::Frame, area: Rect, content: &str) {
         // Use focus-aware colors instead of hardcoded false
         let (text_color, border_color, _bg_color) =
-            OCEANIC_NEXT.get_panel_colors(self.is_main_panel(MainPanel::Content));
+            current_theme().get_panel_colors(self.is_main_panel(MainPanel::Content));
 
         let content_border = Block::default()
             .borders(Borders::ALL)
             .title("Content")
             .border_style(Style::default().fg(border_color))
-            .style(Style::default().bg(OCEANIC_NEXT.base_00));
+            .style(Style::default().bg(current_theme().base_00));
 
         let paragraph = Paragraph::new(content)
             .block(content_border)
-            .style(Style::default().fg(text_color).bg(OCEANIC_NEXT.base_00));
+            .style(Style::default().fg(text_color).bg(current_theme().base_00));
 
         f.render_widget(paragraph, area);
     }
@@ -1925,9 +5365,18 @@ impl App {
 
     fn render_help_bar(&self, f: &mut ratatui::Frame, area: Rect, fps_counter: &FPSCounter) {
         use crate::notification::NotificationLevel;
-        let (_, _, border_color, _, _) = OCEANIC_NEXT.get_interface_colors(false);
-
-        let help_content = if let Some(notification) = self.notifications.get_current() {
+        let (_, _, border_color, _, _) = current_theme().get_interface_colors(false);
+
+        let help_content = if let Some(command_line) = &self.command_line {
+            let completion = command_line
+                .completion_info()
+                .map(|info| format!("{info}  "))
+                .unwrap_or_default();
+            format!(
+                ":{}█  {completion}ESC: Cancel | Enter: Run",
+                command_line.input()
+            )
+        } else if let Some(notification) = self.notifications.get_current() {
             let level_str = match notification.level {
                 NotificationLevel::Info => "INFO",
                 NotificationLevel::Warning => "WARNING",
@@ -1967,7 +5416,10 @@ impl App {
                     "j/k: Navigate | Enter: Select | h/l: Fold/Unfold | H/L: Fold/Unfold All | Tab: Switch | q: Quit"
                 }
                 FocusedPanel::Main(MainPanel::Content) => {
-                    "j/k: Scroll | h/l: Chapter | Ctrl+d/u: Half-screen | Tab: Switch | c: Summary by GPT | u: Switch summary language | Space+o: Open | q: Quit"
+                    "j/k: Scroll | h/l: Chapter | Ctrl+d/u: Half-screen | Tab: Switch | c: Summary by GPT | Space+l: Summary language | u/Ctrl+r: Undo/redo | Space+o: Open | q: Quit"
+                }
+                FocusedPanel::Main(MainPanel::Dock(_)) => {
+                    "j/k: Navigate | Enter: Jump | Tab/Ctrl+W: Switch | ESC: Close panel"
                 }
                 FocusedPanel::Popup(PopupWindow::ReadingHistory) => {
                     "j/k/Scroll: Navigate | Enter/DblClick: Open | ESC: Close"
@@ -1988,6 +5440,22 @@ impl App {
                 FocusedPanel::Popup(PopupWindow::LanguageSelect) => {
                     "j/k: Navigate | Enter: Select | ESC: Close"
                 }
+                FocusedPanel::Popup(PopupWindow::Resources) => {
+                    "j/k: Navigate | Enter: Extract | a: Extract all | ESC: Close"
+                }
+                FocusedPanel::Popup(PopupWindow::PageList) => {
+                    "Type a page number | Enter: Jump | ESC: Close"
+                }
+                FocusedPanel::Popup(PopupWindow::ChapterSummary) => "j/k: Scroll | ESC: Close",
+                FocusedPanel::Popup(PopupWindow::Entities) => {
+                    "j/k: Navigate | Enter/f: First mention | l: Last mention | ESC: Close"
+                }
+                FocusedPanel::Popup(PopupWindow::Timeline | PopupWindow::Count) => {
+                    "j/k: Navigate | Enter: Jump | ESC: Close"
+                }
+                FocusedPanel::Popup(PopupWindow::QuickSwitcher) => {
+                    "Type to filter | Up/Down: Navigate | Enter: Open | ESC: Close"
+                }
             };
             help_text.to_string()
         };
@@ -1995,7 +5463,7 @@ impl App {
         let block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(border_color))
-            .style(Style::default().bg(OCEANIC_NEXT.base_00));
+            .style(Style::default().bg(current_theme().base_00));
 
         let inner_area = block.inner(area);
         f.render_widget(block, area);
@@ -2007,69 +5475,361 @@ impl App {
         };
         let left_para = Paragraph::new(left_content).style(
             Style::default()
-                .fg(OCEANIC_NEXT.base_03)
-                .bg(OCEANIC_NEXT.base_00),
+                .fg(current_theme().base_03)
+                .bg(current_theme().base_00),
         );
         f.render_widget(left_para, inner_area);
 
-        let text_color = OCEANIC_NEXT.base_03;
-        let right_content = Line::from(vec![
-            Span::raw("["),
-            Span::styled(
-                "Space+h: History",
-                Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("] "),
-            Span::raw("["),
-            Span::styled(
-                "Space+d: Stats",
-                Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("] "),
-            Span::raw("["),
-            Span::styled(
-                "?: Help",
-                Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("]"),
-        ]);
+        let text_color = current_theme().base_03;
+        let right_content = Line::from(vec![
+            Span::raw("["),
+            Span::styled(
+                "Space+h: History",
+                Style::default()
+                    .fg(text_color)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Span::raw("] "),
+            Span::raw("["),
+            Span::styled(
+                "Space+d: Stats",
+                Style::default()
+                    .fg(text_color)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Span::raw("] "),
+            Span::raw("["),
+            Span::styled(
+                "?: Help",
+                Style::default()
+                    .fg(text_color)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Span::raw("]"),
+        ]);
+
+        let right_para = Paragraph::new(right_content)
+            .alignment(Alignment::Right)
+            .style(Style::default().bg(current_theme().base_00));
+        f.render_widget(right_para, inner_area);
+    }
+
+    /// Check if a key is a global hotkey that should work regardless of focus
+    /// Returns true if the key was handled as a global hotkey
+    fn handle_global_hotkeys(&mut self, key: crossterm::event::KeyEvent) -> bool {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        match key.code {
+            KeyCode::Char('?') => {
+                // Save current main panel before opening help
+                if let FocusedPanel::Main(panel) = self.focused_panel {
+                    self.previous_main_panel = panel;
+                }
+                self.help_popup = Some(HelpPopup::new());
+                self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
+                true
+            }
+            KeyCode::Char(':') => {
+                self.open_command_line();
+                true
+            }
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_quick_switcher();
+                true
+            }
+            KeyCode::Char(' ') => {
+                self.key_sequence.handle_key(' ');
+                true
+            }
+            KeyCode::Char(c) if self.key_sequence.current_sequence() == " " => {
+                // Handle space + key combinations (global across all panels)
+                self.handle_key_sequence(c)
+            }
+            _ => false,
+        }
+    }
+
+    /// Page mode is on and the reader has the focus, so Space and Backspace turn pages
+    fn is_turning_pages(&self) -> bool {
+        self.text_reader.is_page_mode()
+            && self.is_main_panel(MainPanel::Content)
+            && self.current_book.is_some()
+            && !self.text_reader.is_comment_input_active()
+    }
+
+    /// Turn the page on screen for a Space in page mode, leaving the rest to
+    /// `next_page_or_chapter` once it's clear no Space+key command follows
+    fn start_page_turn(&mut self) {
+        let offset = self.text_reader.get_scroll_offset();
+        self.text_reader.next_page();
+        self.pending_page_turn = Some(offset);
+        self.key_sequence.clear();
+        self.key_sequence.handle_key(' ');
+    }
+
+    /// A Space in page mode that nothing followed for as long as a Space+key command
+    /// may take is a page turn. Returns whether the page changed.
+    pub fn settle_page_turn(&mut self) -> bool {
+        if self.pending_page_turn.is_none() || !self.key_sequence.should_reset_key_sequence() {
+            return false;
+        }
+        if let Some(offset) = self.pending_page_turn.take() {
+            self.key_sequence.clear();
+            self.next_page_or_chapter(offset);
+        }
+        true
+    }
+
+    /// After a Space turned the page from `offset`: save the new position, or go on to the
+    /// next chapter if that was the last page
+    fn next_page_or_chapter(&mut self, offset: usize) {
+        if self.text_reader.get_scroll_offset() != offset {
+            self.save_bookmark();
+            self.update_toc_state();
+        } else if let Err(e) = self.navigate_chapter_relative(ChapterDirection::Next) {
+            self.show_info(e.to_string());
+        }
+    }
+
+    /// Turn back a page, or go to the last page of the previous chapter from the first one
+    fn previous_page_or_chapter(&mut self) {
+        if self.text_reader.previous_page() {
+            self.save_bookmark();
+            self.update_toc_state();
+            return;
+        }
+        match self.navigate_chapter_relative(ChapterDirection::Previous) {
+            Ok(()) => {
+                if let Some(document) = self.text_reader.shared_document() {
+                    let last_block = document.blocks.len().saturating_sub(1);
+                    self.text_reader.restore_to_node_index(last_block);
+                }
+            }
+            Err(e) => self.show_info(e.to_string()),
+        }
+    }
+
+    fn toggle_page_mode(&mut self) {
+        let enabled = !self.text_reader.is_page_mode();
+        self.text_reader.set_page_mode(enabled);
+        self.preferences.page_mode = enabled;
+        if let Err(e) = self.preferences.save() {
+            error!("Failed to save preferences: {e}");
+        }
+
+        if enabled {
+            self.show_info("Page mode: Space and Backspace turn pages");
+        } else {
+            self.show_info("Page mode off");
+        }
+    }
+
+    fn toggle_bionic_reading(&mut self) {
+        let enabled = !self.text_reader.is_bionic_reading();
+        self.text_reader
+            .set_bionic_reading(enabled.then_some(self.preferences.bionic_ratio));
+
+        self.preferences.bionic_reading = enabled;
+        if let Err(e) = self.preferences.save() {
+            error!("Failed to save preferences: {e}");
+        }
+
+        if enabled {
+            self.show_info("Bionic reading enabled");
+        } else {
+            self.show_info("Bionic reading disabled");
+        }
+    }
+
+    fn toggle_paragraph_chunks(&mut self) {
+        let enabled = !self.text_reader.is_chunking_paragraphs();
+        self.text_reader
+            .set_paragraph_chunks(enabled.then_some(self.preferences.paragraph_chunk_sentences));
+
+        self.preferences.paragraph_chunks = enabled;
+        if let Err(e) = self.preferences.save() {
+            error!("Failed to save preferences: {e}");
+        }
+
+        if enabled {
+            self.show_info("Long paragraphs split into chunks");
+        } else {
+            self.show_info("Long paragraphs shown whole");
+        }
+    }
+
+    /// Change the content zoom level by `delta` (0 resets it)
+    fn change_content_zoom(&mut self, delta: i8) {
+        let level = if delta == 0 {
+            0
+        } else {
+            self.text_reader
+                .zoom_level()
+                .saturating_add_signed(delta)
+                .min(crate::markdown_text_reader::MAX_ZOOM_LEVEL)
+        };
+        if level == self.text_reader.zoom_level() && delta != 0 {
+            return;
+        }
+
+        self.text_reader
+            .set_zoom(level, self.preferences.zoom_double_width);
+        self.preferences.content_zoom = level;
+        if let Err(e) = self.preferences.save() {
+            error!("Failed to save preferences: {e}");
+        }
+        self.show_info(format!("Zoom level {level}"));
+    }
+
+    fn cycle_toc_length_indicator(&mut self) {
+        let toc = &mut self.navigation_panel.table_of_contents;
+        let indicator = toc.length_indicator().next();
+        toc.set_length_indicator(indicator);
+        self.toc_word_counts_pending = true;
+
+        self.preferences.toc_length_indicator = indicator;
+        if let Err(e) = self.preferences.save() {
+            error!("Failed to save preferences: {e}");
+        }
+
+        let label = match indicator {
+            TocLengthIndicator::Off => "TOC length indicators hidden",
+            TocLengthIndicator::Words => "TOC shows chapter word counts",
+            TocLengthIndicator::ReadingTime => "TOC shows estimated reading time",
+        };
+        self.show_info(label);
+    }
+
+    /// Switch the current book between the publisher's TOC and one built from its headings
+    fn toggle_heading_toc(&mut self) {
+        let Some(book) = self.current_book.take() else {
+            return;
+        };
+        let enabled = !self.bookmarks.uses_heading_toc(&book.file);
+        self.bookmarks.set_heading_toc(&book.file, enabled);
+        self.switch_to_toc_mode(&book);
+        self.current_book = Some(book);
+        self.update_toc_state();
+
+        self.show_info(if enabled {
+            "TOC built from chapter headings"
+        } else {
+            "Publisher TOC restored"
+        });
+    }
+
+    /// Switch the current book between showing footnotes below the paragraphs that refer
+    /// to them and leaving them where the book puts them
+    fn toggle_inline_notes(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let file = book.file.clone();
+        let enabled = !self.text_reader.is_inlining_notes();
+        self.bookmarks.set_inline_notes(&file, enabled);
+        self.text_reader
+            .set_inline_notes(enabled.then(|| self.book_notes.clone()));
+
+        self.show_info(match (enabled, self.book_notes.is_empty()) {
+            (true, true) => "This book has no footnotes to show inline",
+            (true, false) => "Footnotes shown below their paragraphs",
+            (false, _) => "Footnotes shown where the book puts them",
+        });
+    }
+
+    /// Dialog grouping for a book: its own setting if it has one, otherwise the preference
+    /// applied to the book's language
+    fn resolve_dialog_grouping(&self, path: &str, doc: &EpubDoc<BufReader<std::fs::File>>) -> bool {
+        self.bookmarks
+            .dialog_grouping_override(path)
+            .unwrap_or_else(|| {
+                let language = doc.mdata("language").map(|item| item.value.as_str());
+                self.preferences.dialog_grouping.applies_to(language)
+            })
+    }
+
+    /// Switch dialog grouping for the current book and convert the chapter again. Only the
+    /// spacing inside dialog blocks changes, so the reader stays on the same paragraph.
+    fn toggle_dialog_grouping(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let enabled = !self.text_reader.dialog_grouping();
+        let language = book.epub.mdata("language").map(|item| item.value.as_str());
+        let follows_preference = self.preferences.dialog_grouping.applies_to(language) == enabled;
+        let file = book.file.clone();
+        self.bookmarks
+            .set_dialog_grouping_override(&file, (!follows_preference).then_some(enabled));
+
+        let node_index = self.text_reader.get_current_node_index();
+        self.text_reader.set_dialog_grouping(enabled);
+        self.chapter_cache.clear();
+        self.chapter_cache.set_dialog_grouping(enabled);
+        if !self.virtual_chapter_active {
+            self.update_content();
+            self.text_reader.restore_to_node_index(node_index);
+        }
+
+        self.show_info(if enabled {
+            "Dialog grouping on for this book"
+        } else {
+            "Dialog grouping off for this book"
+        });
+    }
 
-        let right_para = Paragraph::new(right_content)
-            .alignment(Alignment::Right)
-            .style(Style::default().bg(OCEANIC_NEXT.base_00));
-        f.render_widget(right_para, inner_area);
+    /// Switch `<br/>` line layout for the current book and convert the chapter again. Only
+    /// the text inside paragraphs changes, so the reader stays on the same paragraph.
+    fn toggle_line_breaks(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let enabled = !self.text_reader.line_breaks_preserved();
+        let file = book.file.clone();
+        self.bookmarks.set_preserve_line_breaks(&file, enabled);
+
+        let node_index = self.text_reader.get_current_node_index();
+        self.text_reader.set_line_breaks_preserved(enabled);
+        self.chapter_cache.clear();
+        self.chapter_cache.set_line_breaks_preserved(enabled);
+        if !self.virtual_chapter_active {
+            self.update_content();
+            self.text_reader.restore_to_node_index(node_index);
+        }
+
+        self.show_info(if enabled {
+            "Keeping line breaks for this book"
+        } else {
+            "Reflowing line breaks for this book"
+        });
     }
 
-    /// Check if a key is a global hotkey that should work regardless of focus
-    /// Returns true if the key was handled as a global hotkey
-    fn handle_global_hotkeys(&mut self, key: crossterm::event::KeyEvent) -> bool {
-        use crossterm::event::KeyCode;
+    /// Fill in word counts for TOC entries once the search index or the TOC changed;
+    /// results are cached per book. Chapters the index doesn't have get no label.
+    fn update_toc_word_counts(&mut self) {
+        if !self.toc_word_counts_pending
+            || self.navigation_panel.table_of_contents.length_indicator() == TocLengthIndicator::Off
+        {
+            return;
+        }
+        let Some(ref book_search) = self.book_search else {
+            return;
+        };
+        self.toc_word_counts_pending = false;
 
-        match key.code {
-            KeyCode::Char('?') => {
-                // Save current main panel before opening help
-                if let FocusedPanel::Main(panel) = self.focused_panel {
-                    self.previous_main_panel = panel;
-                }
-                self.help_popup = Some(HelpPopup::new());
-                self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
-                true
-            }
-            KeyCode::Char(' ') => {
-                self.key_sequence.handle_key(' ');
-                true
-            }
-            KeyCode::Char(c) if self.key_sequence.current_sequence() == " " => {
-                // Handle space + key combinations (global across all panels)
-                self.handle_key_sequence(c)
-            }
-            _ => false,
+        for href in self
+            .navigation_panel
+            .table_of_contents
+            .chapter_hrefs_missing_word_count()
+        {
+            let Some(word_count) = self
+                .find_spine_index_by_href(&href)
+                .and_then(|index| book_search.chapter_word_count(index))
+            else {
+                continue;
+            };
+            self.navigation_panel
+                .table_of_contents
+                .set_chapter_word_count(&href, word_count);
         }
     }
 
@@ -2143,6 +5903,11 @@ impl App {
                 self.key_sequence.clear();
                 true
             }
+            " m" => {
+                self.show_frame_stats = !self.show_frame_stats;
+                self.key_sequence.clear();
+                true
+            }
             " z" => {
                 // Handle Space->z to copy raw_text_lines for debugging
                 if self.is_main_panel(MainPanel::Content) {
@@ -2167,6 +5932,126 @@ impl App {
                 self.key_sequence.clear();
                 true
             }
+            " b" => {
+                // Handle Space->b to toggle bionic reading emphasis
+                self.toggle_bionic_reading();
+                self.key_sequence.clear();
+                true
+            }
+            " S" => {
+                // Handle Space->S to split long paragraphs into chunks of sentences
+                self.toggle_paragraph_chunks();
+                self.key_sequence.clear();
+                true
+            }
+            " t" => {
+                // Handle Space->t to override the scheduled day/night theme
+                self.toggle_day_night_theme();
+                self.key_sequence.clear();
+                true
+            }
+            " e" => {
+                // Handle Space->e to browse resources embedded in the EPUB
+                self.open_resources_popup();
+                self.key_sequence.clear();
+                true
+            }
+            " y" => {
+                // Handle Space->y to copy the focused link's URL
+                self.copy_focused_link();
+                self.key_sequence.clear();
+                true
+            }
+            " u" => {
+                // Handle Space->u to open the focused link
+                self.open_focused_link();
+                self.key_sequence.clear();
+                true
+            }
+            " l" => {
+                // Handle Space->l to pick the language for chapter summaries
+                self.open_language_select();
+                self.key_sequence.clear();
+                true
+            }
+            " T" => {
+                // Handle Space->T to toggle the heading-based TOC for this book
+                self.toggle_heading_toc();
+                self.key_sequence.clear();
+                true
+            }
+            " n" => {
+                // Handle Space->n to show footnotes inline for this book
+                self.toggle_inline_notes();
+                self.key_sequence.clear();
+                true
+            }
+            " P" => {
+                // Handle Space->P to switch between scrolling and page mode
+                self.toggle_page_mode();
+                self.key_sequence.clear();
+                true
+            }
+            " D" => {
+                // Handle Space->D to toggle dialog grouping for this book
+                self.toggle_dialog_grouping();
+                self.key_sequence.clear();
+                true
+            }
+            " L" => {
+                // Handle Space->L to keep the book's line breaks as they are
+                self.toggle_line_breaks();
+                self.key_sequence.clear();
+                true
+            }
+            " p" => {
+                // Handle Space->p to jump to a print page from the page list
+                self.open_page_list_popup();
+                self.key_sequence.clear();
+                true
+            }
+            " x" => {
+                // Handle Space->x to export the table on screen to CSV
+                self.export_focused_table();
+                self.key_sequence.clear();
+                true
+            }
+            " X" => {
+                // Handle Space->X to copy the table on screen as TSV
+                self.copy_focused_table();
+                self.key_sequence.clear();
+                true
+            }
+            " k" => {
+                // Handle Space->k to save the code block on screen, suggesting a file name
+                self.prompt_code_block_file();
+                self.key_sequence.clear();
+                true
+            }
+            " K" => {
+                // Handle Space->K to append the code block on screen to the scratch file
+                self.append_focused_code_block(None);
+                self.key_sequence.clear();
+                true
+            }
+            " w" => {
+                // Handle Space->w to cycle TOC length indicators
+                self.cycle_toc_length_indicator();
+                self.key_sequence.clear();
+                true
+            }
+            " r" => {
+                // Handle Space->r to jump to a random unread chapter
+                self.jump_to_random_unread_chapter();
+                self.key_sequence.clear();
+                true
+            }
+            " a" => {
+                // Handle Space->a to write a note file on the passage
+                self.open_passage_note();
+                self.key_sequence.clear();
+                true
+            }
             " o" => {
                 // Handle Space->o to open current EPUB with system viewer (global)
                 self.open_with_system_viewer();
@@ -2205,11 +6090,51 @@ impl App {
         }
     }
 
+    /// Whether keys are currently being typed into a text field, where keymap presets
+    /// must not rewrite them
+    fn is_text_entry_active(&self) -> bool {
+        self.text_reader.is_comment_input_active()
+            || self.command_line.is_some()
+            || self.pending_external_link.is_some()
+            || self.is_search_input_mode()
+            || matches!(
+                self.focused_panel,
+                FocusedPanel::Popup(
+                    PopupWindow::PageList | PopupWindow::BookSearch | PopupWindow::QuickSwitcher
+                )
+            )
+    }
+
     /// Handle a single key event with optional screen height for half-screen scrolling
     pub fn handle_key_event_with_screen_height(
         &mut self,
         key: crossterm::event::KeyEvent,
         screen_height: Option<usize>,
+    ) -> Option<AppAction> {
+        // Any key catches a kinetic scroll
+        self.mouse_tracker.stop_momentum();
+
+        // A pending `g` is finished with vim keys (`g Ctrl+G`), so leave the next key alone
+        if self.preferences.keymap == KeymapPreset::Vim
+            || self.is_text_entry_active()
+            || self.key_sequence.current_sequence() == "g"
+        {
+            return self.dispatch_key_event(key, screen_height);
+        }
+
+        let mut action = None;
+        for translated in self.preferences.keymap.translate(key) {
+            if let Some(result) = self.dispatch_key_event(translated, screen_height) {
+                action = Some(result);
+            }
+        }
+        action
+    }
+
+    fn dispatch_key_event(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        screen_height: Option<usize>,
     ) -> Option<AppAction> {
         use crossterm::event::{KeyCode, KeyModifiers};
 
@@ -2222,13 +6147,54 @@ impl App {
             }
         }
 
-        // If image popup is shown, close it on any key press
+        // The `:` prompt takes every key until it runs or is cancelled
+        if let Some(command_line) = &mut self.command_line {
+            match command_line.handle_key(key) {
+                Some(CommandLineAction::Execute(input)) => {
+                    self.command_line = None;
+                    self.execute_command(&input);
+                }
+                Some(CommandLineAction::Cancel) => self.command_line = None,
+                Some(CommandLineAction::Complete) => {
+                    let input = command_line.input().to_string();
+                    let candidates = self.command_completions(&input);
+                    if let Some(command_line) = &mut self.command_line {
+                        command_line.complete(candidates);
+                    }
+                }
+                None => {}
+            }
+            return None;
+        }
+
+        // An external link is waiting for confirmation: 'y' opens it, anything else cancels
+        if let Some(url) = self.pending_external_link.take() {
+            self.notifications.dismiss();
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.open_external_link(&url);
+            } else {
+                self.show_info("Link not opened");
+            }
+            return None;
+        }
+
+        // If image popup is shown, zoom/pan/save, or close it on any other key press
         if matches!(
             self.focused_panel,
             FocusedPanel::Popup(PopupWindow::ImagePopup)
         ) {
-            self.image_popup = None;
-            self.close_popup_to_previous();
+            let action = match self.image_popup.as_mut() {
+                Some(popup) => popup.handle_key(key),
+                None => Some(ImagePopupAction::Close),
+            };
+            match action {
+                Some(ImagePopupAction::Save) => self.save_popup_image(),
+                Some(ImagePopupAction::Close) => {
+                    self.image_popup = None;
+                    self.close_popup_to_previous();
+                }
+                None => {}
+            }
             return None;
         }
 
@@ -2246,13 +6212,15 @@ impl App {
                     BookSearchAction::JumpToChapter {
                         chapter_index,
                         line_number,
+                        query,
                     } => {
                         self.set_main_panel_focus(MainPanel::Content);
                         if let Err(e) = self.navigate_to_chapter(chapter_index) {
                             error!("Failed to navigate to chapter {chapter_index}: {e}");
                             self.show_error(format!("Failed to navigate to chapter: {e}"));
                         } else {
-                            self.text_reader.scroll_to_line(line_number);
+                            self.text_reader
+                                .store_pending_text_line_scroll(line_number, query);
                         }
                     }
                     BookSearchAction::Close => {
@@ -2310,31 +6278,207 @@ impl App {
             return None;
         }
 
-        // If help popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Help) {
-            let action = if let Some(ref mut help) = self.help_popup {
-                help.handle_key(key, &mut self.key_sequence)
+        // If help popup is shown, handle keys for it
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Help) {
+            let action = if let Some(ref mut help) = self.help_popup {
+                help.handle_key(key, &mut self.key_sequence)
+            } else {
+                None
+            };
+
+            if let Some(HelpPopupAction::Close) = action {
+                self.close_popup_to_previous();
+                self.help_popup = None;
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::ChatGPT) {
+            let action = if let Some(ref mut popup) = self.chatgpt_popup {
+                popup.handle_key(key)
+            } else {
+                None
+            };
+
+            if let Some(crate::widget::chatgpt_popup::ChatGPTPopupAction::Close) = action {
+                self.close_popup_to_previous();
+                self.chatgpt_popup = None;
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::ChapterSummary) {
+            let close = self
+                .chapter_summary_popup
+                .as_mut()
+                .is_none_or(|popup| popup.handle_key(key));
+            if close {
+                self.close_popup_to_previous();
+                self.chapter_summary_popup = None;
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::PageList) {
+            let action = if let Some(ref mut popup) = self.page_list_popup {
+                popup.handle_key(key)
+            } else {
+                None
+            };
+
+            match action {
+                Some(PageListAction::Jump(page)) => {
+                    self.close_popup_to_previous();
+                    self.page_list_popup = None;
+                    self.jump_to_page(&page);
+                }
+                Some(PageListAction::Close) => {
+                    self.close_popup_to_previous();
+                    self.page_list_popup = None;
+                }
+                None => {}
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Count) {
+            let action = if let Some(ref mut popup) = self.count_popup {
+                popup.handle_key(key, &mut self.key_sequence)
+            } else {
+                None
+            };
+
+            match action {
+                Some(CountAction::Jump {
+                    term,
+                    chapter_index,
+                    line,
+                }) => {
+                    self.close_popup_to_previous();
+                    self.count_popup = None;
+                    self.set_main_panel_focus(MainPanel::Content);
+                    if let Err(e) = self.navigate_to_chapter(chapter_index) {
+                        error!("Failed to navigate to chapter {chapter_index}: {e}");
+                        self.show_error(format!("Failed to navigate to chapter: {e}"));
+                    } else {
+                        self.text_reader.store_pending_text_line_scroll(line, term);
+                    }
+                }
+                Some(CountAction::Close) => {
+                    self.close_popup_to_previous();
+                    self.count_popup = None;
+                }
+                None => {}
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::QuickSwitcher) {
+            let action = if let Some(ref mut popup) = self.quick_switcher_popup {
+                popup.handle_key(key)
+            } else {
+                None
+            };
+
+            match action {
+                Some(QuickSwitcherAction::Open(target)) => {
+                    self.close_popup_to_previous();
+                    self.quick_switcher_popup = None;
+                    match target {
+                        SwitchTarget::Chapter { href, anchor } => {
+                            self.open_toc_chapter(&href, anchor);
+                        }
+                        SwitchTarget::Book(path) => {
+                            if let Err(e) = self.open_book_for_reading_by_path(&path) {
+                                error!("Failed to open {path}: {e}");
+                                self.show_error(format!("Failed to open book: {e}"));
+                            }
+                        }
+                    }
+                }
+                Some(QuickSwitcherAction::Close) => {
+                    self.close_popup_to_previous();
+                    self.quick_switcher_popup = None;
+                }
+                None => {}
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Timeline) {
+            let action = if let Some(ref mut popup) = self.timeline_popup {
+                popup.handle_key(key, &mut self.key_sequence)
+            } else {
+                None
+            };
+
+            match action {
+                Some(TimelineAction::Jump(entry)) => {
+                    self.close_popup_to_previous();
+                    self.timeline_popup = None;
+                    self.set_main_panel_focus(MainPanel::Content);
+                    if let Err(e) = self.navigate_to_chapter(entry.chapter_index) {
+                        error!("Failed to navigate to chapter {}: {e}", entry.chapter_index);
+                        self.show_error(format!("Failed to navigate to chapter: {e}"));
+                    } else {
+                        self.text_reader.restore_to_node_index(entry.node_index);
+                    }
+                }
+                Some(TimelineAction::Close) => {
+                    self.close_popup_to_previous();
+                    self.timeline_popup = None;
+                }
+                None => {}
+            }
+            return None;
+        }
+
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Entities) {
+            let action = if let Some(ref mut popup) = self.entity_popup {
+                popup.handle_key(key, &mut self.key_sequence)
             } else {
                 None
             };
 
-            if let Some(HelpPopupAction::Close) = action {
-                self.close_popup_to_previous();
-                self.help_popup = None;
+            match action {
+                Some(EntityAction::Jump { name, mention }) => {
+                    self.close_popup_to_previous();
+                    self.entity_popup = None;
+                    self.set_main_panel_focus(MainPanel::Content);
+                    if let Err(e) = self.navigate_to_chapter(mention.chapter_index) {
+                        error!(
+                            "Failed to navigate to chapter {}: {e}",
+                            mention.chapter_index
+                        );
+                        self.show_error(format!("Failed to navigate to chapter: {e}"));
+                    } else {
+                        self.text_reader
+                            .store_pending_text_line_scroll(mention.line_number, name);
+                    }
+                }
+                Some(EntityAction::Close) => {
+                    self.close_popup_to_previous();
+                    self.entity_popup = None;
+                }
+                None => {}
             }
             return None;
         }
 
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::ChatGPT) {
-            let action = if let Some(ref mut popup) = self.chatgpt_popup {
-                popup.handle_key(key)
+        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Resources) {
+            let action = if let Some(ref mut popup) = self.resources_popup {
+                popup.handle_key(key, &mut self.key_sequence)
             } else {
                 None
             };
 
-            if let Some(crate::widget::chatgpt_popup::ChatGPTPopupAction::Close) = action {
-                self.close_popup_to_previous();
-                self.chatgpt_popup = None;
+            match action {
+                Some(ResourcesAction::Extract { ids }) => self.extract_book_resources(&ids),
+                Some(ResourcesAction::Close) => {
+                    self.close_popup_to_previous();
+                    self.resources_popup = None;
+                }
+                None => {}
             }
             return None;
         }
@@ -2387,6 +6531,64 @@ impl App {
             return None;
         }
 
+        if self.window_command_pending {
+            self.window_command_pending = false;
+            self.handle_window_command(key);
+            return None;
+        }
+        if key.code == KeyCode::Char('w')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(self.focused_panel, FocusedPanel::Main(_))
+        {
+            self.key_sequence.clear();
+            self.window_command_pending = true;
+            return None;
+        }
+        if let FocusedPanel::Main(MainPanel::Dock(kind)) = self.focused_panel {
+            if self.handle_dock_key(kind, key) {
+                self.key_sequence.clear();
+                return None;
+            }
+        }
+
+        // In the TOC, `z1`–`z4` show headings down to that depth
+        if let KeyCode::Char(digit @ '1'..='4') = key.code {
+            if self.is_main_panel(MainPanel::NavigationList)
+                && self.navigation_panel.mode == NavigationMode::TableOfContents
+                && self.navigation_panel.reading_order.is_none()
+                && !self.navigation_panel.is_searching()
+                && key.modifiers.is_empty()
+                && self.key_sequence.current_sequence() == "z"
+                && self.key_sequence.pending_count().is_none()
+            {
+                self.key_sequence.clear();
+                let before = self.navigation_panel.table_of_contents.expansion_snapshot();
+                self.navigation_panel
+                    .show_toc_depth(digit.to_digit(10).unwrap_or(1) as usize);
+                if let (Some(before), Some(after)) = (
+                    before,
+                    self.navigation_panel.table_of_contents.expansion_snapshot(),
+                ) {
+                    if TableOfContents::expansion_differs(&before, &after) {
+                        self.undo_history.record(UndoEntry::TocExpansion(before));
+                    }
+                }
+                self.remember_toc_expansion();
+                return None;
+            }
+        }
+
+        // Numeric count prefix for the next motion, e.g. `5j` or `10Ctrl+d`
+        if let KeyCode::Char(digit @ '0'..='9') = key.code {
+            if !key.modifiers.contains(KeyModifiers::CONTROL)
+                && self.key_sequence.current_sequence().is_empty()
+                && self.key_sequence.push_count_digit(digit)
+            {
+                return None;
+            }
+        }
+        let count = self.key_sequence.take_count();
+
         // If navigation panel (file list) has focus, handle keys for it
         if self.is_main_panel(MainPanel::NavigationList) && !self.is_search_input_mode() {
             // Check for global hotkeys first
@@ -2394,9 +6596,47 @@ impl App {
                 return None;
             }
 
+            match key.code {
+                KeyCode::Char('u') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.undo();
+                    return None;
+                }
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.redo();
+                    return None;
+                }
+                _ => {}
+            }
+
+            // Folding is undoable; remember the TOC as it was
+            let folds = matches!(
+                key.code,
+                KeyCode::Char('h' | 'l' | 'H' | 'L' | 'v') | KeyCode::Enter
+            );
+            let toc_before = if folds && !self.navigation_panel.is_searching() {
+                self.navigation_panel.table_of_contents.expansion_snapshot()
+            } else {
+                None
+            };
+
             let action = self
                 .navigation_panel
                 .handle_key(key, &mut self.key_sequence);
+            if is_countable_motion(&key) {
+                for _ in 1..count {
+                    self.navigation_panel
+                        .handle_key(key, &mut self.key_sequence);
+                }
+            }
+
+            if let (Some(before), Some(after)) = (
+                toc_before,
+                self.navigation_panel.table_of_contents.expansion_snapshot(),
+            ) {
+                if TableOfContents::expansion_differs(&before, &after) {
+                    self.undo_history.record(UndoEntry::TocExpansion(before));
+                }
+            }
             let mut bypass = false;
             if let Some(action) = action {
                 use crate::navigation_panel::NavigationPanelAction;
@@ -2412,6 +6652,9 @@ impl App {
                     NavigationPanelAction::SwitchToBookList => {
                         self.switch_to_book_list_mode();
                     }
+                    NavigationPanelAction::NavigateToChapter { href, .. } if href == NOTES_HREF => {
+                        self.open_notes_view();
+                    }
                     NavigationPanelAction::NavigateToChapter { href, anchor } => {
                         if let Some(chapter_index) = self.find_spine_index_by_href(&href) {
                             let _ = self.navigate_to_chapter(chapter_index);
@@ -2433,8 +6676,21 @@ impl App {
                             .table_of_contents
                             .toggle_selected_expansion();
                     }
+                    NavigationPanelAction::EditReadingOrder => self.edit_reading_order(),
+                    NavigationPanelAction::OpenFromClipboard => self.open_book_from_clipboard(),
+                    NavigationPanelAction::SetArchived {
+                        book_index,
+                        archived,
+                    } => self.set_book_archived(book_index, archived),
+                    NavigationPanelAction::MarkReadUpTo { href, read } => {
+                        self.mark_read_up_to(&href, read);
+                    }
+                    NavigationPanelAction::SaveReadingOrder { order } => {
+                        self.save_reading_order(order);
+                    }
                 }
             }
+            self.remember_toc_expansion();
 
             if key.code == KeyCode::Char('q') {
                 self.save_bookmark_with_throttle(true);
@@ -2446,6 +6702,16 @@ impl App {
             }
         }
 
+        // Anything but a Space+key command keeps a page turned by the Space before it
+        if let Some(offset) = self.pending_page_turn {
+            let completes_sequence = matches!(key.code, KeyCode::Char(c) if c != ' ')
+                && self.key_sequence.current_sequence() == " ";
+            if !completes_sequence {
+                self.pending_page_turn = None;
+                self.next_page_or_chapter(offset);
+            }
+        }
+
         match key.code {
             KeyCode::Char('/') => {
                 if self.is_main_panel(MainPanel::Content) {
@@ -2461,7 +6727,16 @@ impl App {
                 } else if self.text_reader.is_searching() {
                     let search_state = self.text_reader.get_search_state();
                     if search_state.mode == SearchMode::NavigationMode {
-                        self.text_reader.next_match();
+                        let mut wrapped = false;
+                        for _ in 0..count {
+                            let before = self.text_reader.get_search_state().current_match_index;
+                            self.text_reader.next_match();
+                            let after = self.text_reader.get_search_state().current_match_index;
+                            wrapped |= search_wrapped(before, after, true);
+                        }
+                        if wrapped {
+                            self.alert_failure();
+                        }
                     } else {
                         self.handle_search_input('n');
                     }
@@ -2476,27 +6751,73 @@ impl App {
                 } else if self.text_reader.is_searching() {
                     let search_state = self.text_reader.get_search_state();
                     if search_state.mode == SearchMode::NavigationMode {
-                        self.text_reader.previous_match();
+                        let mut wrapped = false;
+                        for _ in 0..count {
+                            let before = self.text_reader.get_search_state().current_match_index;
+                            self.text_reader.previous_match();
+                            let after = self.text_reader.get_search_state().current_match_index;
+                            wrapped |= search_wrapped(before, after, false);
+                        }
+                        if wrapped {
+                            self.alert_failure();
+                        }
                     } else {
                         self.handle_search_input('N');
                     }
                 }
             }
+            KeyCode::Char(c @ ('[' | ']')) if self.is_main_panel(MainPanel::Content) => {
+                self.key_sequence.handle_key(c);
+                self.key_sequence.keep_count(count);
+            }
+            KeyCode::Char(c @ ('f' | 'a' | 'i' | 't'))
+                if self.is_main_panel(MainPanel::Content)
+                    && matches!(self.key_sequence.current_sequence().as_str(), "[" | "]") =>
+            {
+                let forward = self.key_sequence.current_sequence() == "]";
+                self.key_sequence.clear();
+                if let Some(kind) = PointOfInterest::from_key(c) {
+                    self.jump_to_point_of_interest(kind, forward, count);
+                }
+            }
             KeyCode::Char('f') => if self.handle_key_sequence('f') {},
             KeyCode::Char('F') => if self.handle_key_sequence('F') {},
             KeyCode::Char('s') => if self.handle_key_sequence('s') {},
+            KeyCode::Char(' ') if self.is_turning_pages() => self.start_page_turn(),
             KeyCode::Char(' ') => if !self.handle_key_sequence(' ') {},
+            KeyCode::Char('g')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && self.key_sequence.current_sequence() == "g" =>
+            {
+                self.key_sequence.clear();
+                self.report_word_count();
+            }
             KeyCode::Char('g') => if !self.handle_key_sequence('g') {},
+            KeyCode::Char(c) if self.key_sequence.current_sequence() == " " => {
+                // Remaining Space+key combinations. One that follows a page turn is done
+                // on the page the Space left, and takes the turn back.
+                let turned_from = self.pending_page_turn.take();
+                if let Some(offset) = turned_from {
+                    self.text_reader.scroll_to_line(offset);
+                }
+                if !self.handle_key_sequence(c) {
+                    if let Some(offset) = turned_from {
+                        self.text_reader.next_page();
+                        self.next_page_or_chapter(offset);
+                    }
+                }
+            }
 
             KeyCode::Char('d') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if self.handle_key_sequence('d') {
                 } else if !self.text_reader.is_comment_input_active() {
                     match self.text_reader.delete_comment_at_cursor() {
-                        Ok(true) => {
+                        Ok(Some(comment)) => {
                             info!("Comment deleted successfully");
-                            self.show_info("Comment deleted");
+                            self.undo_history.record(UndoEntry::CommentDeleted(comment));
+                            self.show_info("Comment deleted (u to undo)");
                         }
-                        Ok(false) => {
+                        Ok(None) => {
                             // Cursor not on a comment, ignore
                         }
                         Err(e) => {
@@ -2507,21 +6828,35 @@ impl App {
                 }
             }
             KeyCode::Char('u') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.open_language_select();
+                self.undo();
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.redo();
+            }
+            KeyCode::Backspace if self.is_turning_pages() => {
+                self.previous_page_or_chapter();
             }
             KeyCode::Char('j') => {
-                self.scroll_down();
+                for _ in 0..count {
+                    self.scroll_down();
+                }
             }
             KeyCode::Char('k') => {
-                self.scroll_up();
+                for _ in 0..count {
+                    self.scroll_up();
+                }
             }
             KeyCode::Char('h') => {
                 if !self.handle_key_sequence('h') {
-                    let _ = self.navigate_chapter_relative(ChapterDirection::Previous);
+                    for _ in 0..count {
+                        let _ = self.navigate_chapter_relative(ChapterDirection::Previous);
+                    }
                 }
             }
             KeyCode::Char('l') => {
-                let _ = self.navigate_chapter_relative(ChapterDirection::Next);
+                for _ in 0..count {
+                    let _ = self.navigate_chapter_relative(ChapterDirection::Next);
+                }
             }
             KeyCode::Char('i') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.jump_forward();
@@ -2532,42 +6867,67 @@ impl App {
                 } else if !self.handle_key_sequence('o') {
                 }
             }
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_quick_switcher();
+            }
             KeyCode::Char('p') => {
                 self.toggle_profiling();
             }
-            KeyCode::Tab => {
+            KeyCode::Enter
+                if self.is_main_panel(MainPanel::Content) && self.current_book.is_none() =>
+            {
+                if let Some(path) = self
+                    .welcome_screen
+                    .as_ref()
+                    .and_then(|screen| screen.suggested_path())
+                {
+                    self.open_welcome_book(path.to_string());
+                }
+            }
+            KeyCode::Enter if self.is_main_panel(MainPanel::Content) => {
+                let toggled_details = self.text_reader.toggle_focused_details();
+                if !toggled_details {
+                    self.open_focused_image();
+                }
+            }
+            KeyCode::Char('+') if self.is_main_panel(MainPanel::Content) => {
+                self.change_content_zoom(1);
+            }
+            KeyCode::Char('-') if self.is_main_panel(MainPanel::Content) => {
+                self.change_content_zoom(-1);
+            }
+            KeyCode::Char('=') if self.is_main_panel(MainPanel::Content) => {
+                self.change_content_zoom(0);
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
                 if !self.has_active_popup() {
-                    match self.focused_panel {
-                        FocusedPanel::Main(MainPanel::NavigationList) => {
-                            // In book list mode, toggle search
-                            if self.navigation_panel.is_in_book_mode() {
-                                if self.navigation_panel.is_searching() {
-                                    self.navigation_panel.exit_search();
-                                } else {
-                                    self.navigation_panel.start_search();
-                                }
-                            } else {
-                                self.navigation_panel
-                                    .table_of_contents
-                                    .clear_manual_navigation();
-                                self.set_main_panel_focus(MainPanel::Content);
-                            }
-                        }
-                        FocusedPanel::Main(MainPanel::Content) => {
-                            self.set_main_panel_focus(MainPanel::NavigationList);
+                    // In book list mode, Tab toggles search
+                    if key.code == KeyCode::Tab
+                        && self.is_main_panel(MainPanel::NavigationList)
+                        && self.navigation_panel.is_in_book_mode()
+                    {
+                        if self.navigation_panel.is_searching() {
+                            self.navigation_panel.exit_search();
+                        } else {
+                            self.navigation_panel.start_search();
                         }
-                        FocusedPanel::Popup(_) => {} // No tab switching in popups
-                    };
+                    } else {
+                        self.cycle_focus(key.code == KeyCode::Tab);
+                    }
                 }
             }
             KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if let Some(visible_height) = screen_height {
-                    self.scroll_half_screen_down(visible_height);
+                    for _ in 0..count {
+                        self.scroll_half_screen_down(visible_height);
+                    }
                 }
             }
             KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if let Some(visible_height) = screen_height {
-                    self.scroll_half_screen_up(visible_height);
+                    for _ in 0..count {
+                        self.scroll_half_screen_up(visible_height);
+                    }
                 }
             }
 
@@ -2576,6 +6936,12 @@ impl App {
                     self.text_reader.handle_upper_g();
                 }
             }
+            KeyCode::Char('Q') if self.text_reader.has_text_selection() => {
+                self.capture_quote();
+            }
+            KeyCode::Char('V') if self.text_reader.has_text_selection() => {
+                self.save_vocabulary_word();
+            }
             KeyCode::Char('a') => {
                 if self.text_reader.has_text_selection() && self.text_reader.start_comment_input() {
                     debug!("Started comment input mode");
@@ -2596,6 +6962,9 @@ impl App {
                 self.help_popup = Some(HelpPopup::new());
                 self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
             }
+            KeyCode::Char(':') => {
+                self.open_command_line();
+            }
             KeyCode::Char('q') => {
                 self.save_bookmark_with_throttle(true);
                 return Some(AppAction::Quit);
@@ -2620,113 +6989,22 @@ impl App {
     }
 
     //todo this does extra parsing of a book. damn claude is dumb
-    fn initialize_search_engine(&mut self, doc: &mut EpubDoc<BufReader<std::fs::File>>) {
-        fn extract_text_from_markdown_doc(doc: &crate::markdown::Document) -> String {
-            let mut lines = Vec::new();
-            for node in &doc.blocks {
-                extract_text_from_block(&node.block, &mut lines);
-            }
-            lines.join("\n")
-        }
-
-        fn extract_text_from_block(block: &crate::markdown::Block, lines: &mut Vec<String>) {
-            use crate::markdown::Block;
-
-            match block {
-                Block::Paragraph { content } | Block::Heading { content, .. } => {
-                    let plain_text = extract_text_from_text(content);
-                    if !plain_text.trim().is_empty() {
-                        lines.push(plain_text);
-                    }
-                }
-                Block::List { items, .. } => {
-                    for item in items {
-                        // ListItem content is Vec<Node>, so process each node
-                        for node in &item.content {
-                            extract_text_from_block(&node.block, lines);
-                        }
-                    }
-                }
-                Block::Quote { content } => {
-                    for node in content {
-                        extract_text_from_block(&node.block, lines);
-                    }
-                }
-                Block::CodeBlock { content, .. } => {
-                    lines.push(content.clone());
-                }
-                Block::Table { rows, header, .. } => {
-                    if let Some(header_row) = header {
-                        let row_text: Vec<String> = header_row
-                            .cells
-                            .iter()
-                            .map(|cell| extract_text_from_text(&cell.content))
-                            .collect();
-                        if !row_text.is_empty() {
-                            lines.push(row_text.join(" "));
-                        }
-                    }
-                    for row in rows {
-                        let row_text: Vec<String> = row
-                            .cells
-                            .iter()
-                            .map(|cell| extract_text_from_text(&cell.content))
-                            .collect();
-                        if !row_text.is_empty() {
-                            lines.push(row_text.join(" "));
-                        }
-                    }
-                }
-                Block::DefinitionList { items } => {
-                    for item in items {
-                        lines.push(extract_text_from_text(&item.term));
-                        // Process each definition (Vec<Vec<Node>>)
-                        for definition in &item.definitions {
-                            for node in definition {
-                                extract_text_from_block(&node.block, lines);
-                            }
-                        }
-                    }
-                }
-                Block::EpubBlock { content, .. } => {
-                    for node in content {
-                        extract_text_from_block(&node.block, lines);
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        fn extract_text_from_text(text: &crate::markdown::Text) -> String {
-            let mut result = String::new();
-
-            for part in text.iter() {
-                match part {
-                    crate::markdown::TextOrInline::Text(text_node) => {
-                        result.push_str(&text_node.content);
-                    }
-                    crate::markdown::TextOrInline::Inline(inline) => match inline {
-                        crate::markdown::Inline::Link { text, .. } => {
-                            result.push_str(&extract_text_from_text(text));
-                        }
-                        crate::markdown::Inline::Image { alt_text, .. } => {
-                            result.push_str(alt_text);
-                        }
-                        crate::markdown::Inline::LineBreak => {
-                            result.push(' ');
-                        }
-                        _ => {}
-                    },
-                }
-            }
-
-            result
-        }
-
+    /// Index every chapter for search and notes. Returns the conversion warnings and each
+    /// chapter's links, for the health summary shown once the book is open.
+    fn initialize_search_engine(
+        &mut self,
+        doc: &mut EpubDoc<BufReader<std::fs::File>>,
+    ) -> (BookHealth, Vec<Vec<Inline>>) {
         let mut search_engine = SearchEngine::new();
         let mut chapters = Vec::new();
-        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
-        let mut converter = HtmlToMarkdownConverter::new();
+        // Converted as for reading, so the timeline's and the notes' block indices point
+        // at the blocks on screen
+        let mut converter = self.text_reader.converter().recording_diagnostics();
+        let mut health = BookHealth::default();
+        let mut chapter_links = Vec::new();
+        let mut book_notes = BookNotes::new();
+        let mut headings = Vec::new();
+        let mut timeline = TimelineCollector::default();
 
         // Process all chapters to extract readable text
         for chapter_index in 0..doc.get_num_chapters() {
@@ -2736,8 +7014,29 @@ impl App {
                         .unwrap_or_else(|| format!("Chapter {}", chapter_index + 1));
 
                     let markdown_doc = converter.convert(&raw_html);
+                    if let Some(diagnostics) = converter.diagnostics() {
+                        health.add_chapter(diagnostics);
+                    }
+                    chapter_links.push(markdown_doc.links().into_iter().cloned().collect());
+
+                    if let Some(chapter_href) = Self::get_chapter_href(doc, chapter_index) {
+                        book_notes.collect_from_chapter(
+                            chapter_index,
+                            &chapter_href,
+                            &title,
+                            &markdown_doc,
+                        );
+                        TocParser::collect_headings(
+                            &markdown_doc,
+                            &chapter_href,
+                            &title,
+                            &mut headings,
+                        );
+                    }
 
-                    let clean_text = extract_text_from_markdown_doc(&markdown_doc);
+                    timeline.collect(&markdown_doc, chapter_index);
+
+                    let clean_text = markdown_doc.plain_text();
                     chapters.push((chapter_index, title, clean_text));
                 }
             }
@@ -2746,6 +7045,11 @@ impl App {
         search_engine.process_chapters(chapters);
 
         self.book_search = Some(BookSearch::new(search_engine));
+        self.toc_word_counts_pending = true;
+        self.book_notes = Arc::new(book_notes);
+        self.heading_toc = TocParser::build_heading_toc(&headings);
+        self.timeline = timeline.into_timeline();
+        (health, chapter_links)
     }
 
     fn open_book_search(&mut self, clear_input: bool) {
@@ -2759,6 +7063,68 @@ impl App {
         }
     }
 
+    /// Pipe the current chapter through the user's `summary_command`, e.g. a local LLM CLI,
+    /// and show what it prints in the summary popup
+    fn run_summary_command(&mut self) {
+        let Some(command) = self.preferences.summary_command.clone() else {
+            self.show_info(
+                "Set \"summary_command\" in preferences.json to a command that reads text on stdin",
+            );
+            return;
+        };
+        let Some(document) = self.text_reader.shared_document() else {
+            self.show_warning("No chapter to summarize");
+            return;
+        };
+        let chapter_text = document.plain_text();
+        let chapter_language = document.language.clone().unwrap_or_default();
+
+        // Name the popup after the program, e.g. "ollama Summary"
+        let program = command
+            .split_whitespace()
+            .next()
+            .and_then(|program| Path::new(program).file_name())
+            .map_or_else(
+                || command.clone(),
+                |name| name.to_string_lossy().to_string(),
+            );
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.chatgpt_popup = Some(crate::widget::chatgpt_popup::ChatGPTPopup::with_source(
+            &program,
+        ));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ChatGPT);
+
+        let book_title = self
+            .current_book
+            .as_ref()
+            .and_then(|book| book.epub.mdata("title").map(|item| item.value.clone()));
+        let env = [
+            ("BOOKOKCAT_BOOK_TITLE", book_title.unwrap_or_default()),
+            (
+                "BOOKOKCAT_CHAPTER_TITLE",
+                self.text_reader
+                    .get_chapter_title()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            (
+                "BOOKOKCAT_SUMMARY_LANGUAGE",
+                self.summary_language.as_str().to_string(),
+            ),
+            ("BOOKOKCAT_CHAPTER_LANGUAGE", chapter_language),
+        ];
+        let sender = self.summary_sender.clone();
+        std::thread::spawn(move || {
+            let result = crate::system_command::run_text_filter(&command, &chapter_text, &env);
+            if let Err(e) = &result {
+                warn!("Summary command failed: {e}");
+            }
+            let _ = sender.send(result);
+        });
+    }
+
     fn open_chatgpt_summarization(&mut self) {
         if let FocusedPanel::Main(panel) = self.focused_panel {
             self.previous_main_panel = panel;
@@ -2835,6 +7201,17 @@ impl App {
     }
 }
 
+/// Motions that repeat when prefixed with a count
+fn is_countable_motion(key: &crossterm::event::KeyEvent) -> bool {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Char('k') => true,
+        KeyCode::Char('d') | KeyCode::Char('u') => key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
 pub struct FPSCounter {
     last_measure: Instant,
     ticks: u16,
@@ -2867,16 +7244,64 @@ impl FPSCounter {
     }
 }
 
+/// Leave the TUI, run `handoff.program` on its file until it exits, and come back
+fn run_external_program<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    executor: &dyn SystemCommandExecutor,
+    handoff: &ExternalHandoff,
+) -> Result<()> {
+    use crossterm::event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    };
+    use crossterm::execute;
+    use crossterm::terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+    };
+
+    // Only a terminal the TUI has taken over needs handing back (not in tests)
+    let suspend = crossterm::terminal::is_raw_mode_enabled()?;
+    if suspend {
+        disable_raw_mode()?;
+        execute!(
+            std::io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableBracketedPaste
+        )?;
+    }
+    let result = executor.run_in_terminal(&handoff.program, &handoff.path);
+    if suspend {
+        execute!(
+            std::io::stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )?;
+        enable_raw_mode()?;
+        terminal.clear()?;
+    }
+    result.map_err(anyhow::Error::msg)
+}
+
 pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
     event_source: &mut dyn EventSource,
 ) -> Result<()> {
-    let tick_rate = Duration::from_millis(50); // Faster tick rate for smoother animation
+    let active_tick_rate = Duration::from_millis(50); // Faster tick rate for smoother animation
+    let idle_tick_rate = Duration::from_secs(1); // Nothing animates while nobody is reading
+    let idle_timeout = app.idle_timeout();
+    let mut idle = false;
+    let mut last_input = std::time::Instant::now();
     let mut last_tick = std::time::Instant::now();
     let mut fps_counter = FPSCounter::new();
     let mut first_render = true; // Ensure we always render at least once on startup
     loop {
+        let tick_rate = if idle {
+            idle_tick_rate
+        } else {
+            active_tick_rate
+        };
         let mut events_processed = 0;
         let mut should_quit = false;
         fps_counter.tick();
@@ -2906,8 +7331,12 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
                 Event::Resize(_cols, _rows) => {
                     app.handle_resize();
                 }
+                Event::Paste(text) => {
+                    app.handle_paste(&text);
+                }
                 _ => {}
             }
+            app.record_session_state();
 
             if should_quit {
                 break;
@@ -2916,6 +7345,29 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
 
         let mut needs_redraw = events_processed > 0;
 
+        if events_processed > 0 {
+            last_input = std::time::Instant::now();
+            if idle {
+                idle = false;
+                app.set_idle(false);
+            }
+        } else if !idle && idle_timeout.is_some_and(|timeout| last_input.elapsed() >= timeout) {
+            idle = true;
+            app.set_idle(true);
+        }
+
+        if let Some(handoff) = app.take_external_handoff() {
+            if let Err(e) =
+                run_external_program(terminal, app.system_command_executor.as_ref(), &handoff)
+            {
+                error!("Failed to run {}: {e}", handoff.program);
+                app.show_error(format!("Failed to run {}: {e}", handoff.program));
+            }
+            // The program may have set a title of its own
+            app.last_window_title = None;
+            needs_redraw = true;
+        }
+
         if first_render {
             needs_redraw = true;
             first_render = false;
@@ -3043,6 +7495,10 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
             }
         }
 
+        if app.receive_download_events() {
+            needs_redraw = true;
+        }
+
         // Check if ChatGPT summary result is available
         if let Ok(result) = app.summary_receiver.try_recv() {
             if let Some(ref mut popup) = app.chatgpt_popup {
@@ -3051,7 +7507,8 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
                         popup.set_summary(summary);
                     }
                     Err(e) => {
-                        popup.set_error(format!("Failed to get ChatGPT summary: {e}"));
+                        let source = popup.source().to_string();
+                        popup.set_error(format!("Failed to get {source} summary: {e}"));
                     }
                 }
                 needs_redraw = true;
@@ -3060,8 +7517,33 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
 
         if last_tick.elapsed() >= tick_rate {
             let highlight_changed = app.text_reader.update_highlight(); // Update highlight state
+            if app.text_reader.update_smooth_scroll() {
+                app.save_bookmark();
+                needs_redraw = true;
+            }
+            if app.receive_prefetched_chapters() {
+                needs_redraw = true;
+            }
+            if app.reload_changed_book() {
+                needs_redraw = true;
+            }
+            if app.settle_page_turn() {
+                needs_redraw = true;
+            }
+            if let Some((lines, column)) = app.mouse_tracker.momentum_step() {
+                if app.has_active_popup() {
+                    app.mouse_tracker.stop_momentum();
+                } else {
+                    app.apply_scroll(lines, column);
+                    needs_redraw = true;
+                }
+            }
             let images_loaded = app.text_reader.check_for_loaded_images();
+            app.track_reading_goal(last_tick.elapsed(), idle);
             let notification_expired = app.notifications.update();
+            if app.show_pending_book_health() {
+                needs_redraw = true;
+            }
             if images_loaded {
                 needs_redraw = true;
                 debug!("Images loaded, forcing redraw");
@@ -3070,7 +7552,7 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
                 needs_redraw = true;
                 debug!("Highlight expired, forcing redraw");
             }
-            if notification_expired {
+            if notification_expired || app.alerts.update() {
                 needs_redraw = true;
             }
             last_tick = std::time::Instant::now();
@@ -3080,6 +7562,14 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
             let draw_start = std::time::Instant::now();
             terminal.draw(|f| app.draw(f, &fps_counter))?;
             let draw_duration = draw_start.elapsed();
+            app.frame_stats.record(draw_duration);
+            if app.alerts.take_bell() {
+                let _ = crossterm::execute!(std::io::stdout(), crossterm::style::Print('\x07'));
+            }
+            if let Some(title) = app.take_window_title() {
+                let _ =
+                    crossterm::execute!(std::io::stdout(), crossterm::terminal::SetTitle(title));
+            }
 
             // Log if drawing/flushing took longer than 10ms
             if draw_duration.as_millis() > 10 {
@@ -3102,4 +7592,64 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::system_command::MockSystemCommandExecutor;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn app_confirming_links(confirm: bool) -> App {
+        let mut app = App::new_with_mock_system_executor(
+            Some("tests/testdata"),
+            Some("/dev/null"),
+            false,
+            MockSystemCommandExecutor::new(),
+        );
+        app.preferences.confirm_external_links = confirm;
+        app
+    }
+
+    fn opened_urls(app: &App) -> Vec<String> {
+        app.system_command_executor
+            .as_any()
+            .downcast_ref::<MockSystemCommandExecutor>()
+            .unwrap()
+            .get_executed_commands()
+    }
+
+    fn press(app: &mut App, c: char) {
+        app.handle_key_event_with_screen_height(
+            KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_external_links_wait_for_confirmation_when_asked_to() {
+        let link = LinkInfo::from_url("https://example.com/notes".to_string());
+        let mut app = app_confirming_links(true);
+
+        app.follow_link(&link);
+        assert!(opened_urls(&app).is_empty());
+        assert_eq!(
+            app.pending_external_link.as_deref(),
+            Some("https://example.com/notes")
+        );
+
+        // Anything but `y` leaves the link unopened
+        press(&mut app, 'n');
+        assert!(opened_urls(&app).is_empty());
+        assert!(app.pending_external_link.is_none());
+
+        app.follow_link(&link);
+        press(&mut app, 'y');
+        assert_eq!(opened_urls(&app), ["URL:https://example.com/notes"]);
+    }
+
+    #[test]
+    fn test_external_links_open_at_once_without_confirmation() {
+        let mut app = app_confirming_links(false);
+        app.follow_link(&LinkInfo::from_url("https://example.com/notes".to_string()));
+        assert_eq!(opened_urls(&app), ["URL:https://example.com/notes"]);
+        assert!(app.pending_external_link.is_none());
+    }
+}
@@ -3,6 +3,20 @@ pub type Range<Idx> = std::ops::Range<Idx>;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Document {
     pub blocks: Vec<Node>,
+    /// What the chapter is written in, as a BCP 47 tag, when that could be told
+    pub language: Option<String>,
+    /// Links to the chapter's own sections, shown under its title
+    pub contents: Option<ChapterContents>,
+}
+
+/// A chapter's table of contents. It is kept out of `blocks` so that block indices, and
+/// the comments anchored to them, stay the same whether or not it is shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChapterContents {
+    /// Index of the block it is shown in front of
+    pub before_block: usize,
+    /// A list of links to the sections, nested by heading level
+    pub list: Node,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -10,6 +24,26 @@ pub struct Node {
     pub block: Block,
     pub source_range: Range<usize>,
     pub id: Option<String>, // HTML id attribute for anchor resolution
+    /// Alignment and indentation from the book's CSS
+    pub layout: BlockLayout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockLayout {
+    /// `None` lets an enclosing element decide
+    pub align: Option<TextAlign>,
+    /// Extra indent levels, from `margin-left` or for text between parts of a split list
+    pub indent: usize,
+    /// Digits reserved for the numbers of an ordered list, so that the parts of a list
+    /// split across elements line up; 0 fits the list's own numbers
+    pub number_width: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -45,6 +79,12 @@ pub enum Block {
         element_name: String,
         content: Vec<Node>,
     },
+    /// `<details>`: a summary line whose content can be folded away
+    Details {
+        summary: Text,
+        content: Vec<Node>,
+        open: bool,
+    },
     ThematicBreak,
 }
 
@@ -204,8 +244,16 @@ impl Node {
             block,
             source_range,
             id,
+            layout: BlockLayout::default(),
         }
     }
+
+    /// The readable text of the block, one line per paragraph, table row or code block
+    pub fn plain_text(&self) -> String {
+        let mut lines = Vec::new();
+        collect_block_text(&self.block, &mut lines);
+        lines.join("\n")
+    }
 }
 
 impl Default for Document {
@@ -216,7 +264,170 @@ impl Default for Document {
 
 impl Document {
     pub fn new() -> Self {
-        Document { blocks: Vec::new() }
+        Document {
+            blocks: Vec::new(),
+            language: None,
+            contents: None,
+        }
+    }
+
+    /// The readable text of the document, one line per paragraph, heading, table row
+    /// or code block
+    pub fn plain_text(&self) -> String {
+        let mut lines = Vec::new();
+        for node in &self.blocks {
+            collect_block_text(&node.block, &mut lines);
+        }
+        lines.join("\n")
+    }
+
+    /// Index of the top-level block that line `line` of `plain_text()` comes from
+    pub fn block_of_plain_text_line(&self, line: usize) -> Option<usize> {
+        let mut next_block_line = 0;
+        for (idx, node) in self.blocks.iter().enumerate() {
+            let mut lines = Vec::new();
+            collect_block_text(&node.block, &mut lines);
+            next_block_line += lines
+                .iter()
+                .map(|text| text.split('\n').count())
+                .sum::<usize>();
+            if line < next_block_line {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    /// Every link in the document, in reading order
+    pub fn links(&self) -> Vec<&Inline> {
+        let mut links = Vec::new();
+        for node in &self.blocks {
+            collect_block_links(&node.block, &mut links);
+        }
+        links
+    }
+}
+
+fn collect_text_links<'a>(text: &'a Text, links: &mut Vec<&'a Inline>) {
+    for item in text.iter() {
+        if let TextOrInline::Inline(link @ Inline::Link { .. }) = item {
+            links.push(link);
+        }
+    }
+}
+
+fn collect_block_links<'a>(block: &'a Block, links: &mut Vec<&'a Inline>) {
+    let nodes = |nodes: &'a [Node], links: &mut Vec<&'a Inline>| {
+        for node in nodes {
+            collect_block_links(&node.block, links);
+        }
+    };
+    match block {
+        Block::Paragraph { content } | Block::Heading { content, .. } => {
+            collect_text_links(content, links)
+        }
+        Block::List { items, .. } => {
+            for item in items {
+                nodes(&item.content, links);
+            }
+        }
+        Block::Quote { content } | Block::EpubBlock { content, .. } => nodes(content, links),
+        Block::Table { rows, header, .. } => {
+            for row in header.iter().chain(rows) {
+                for cell in &row.cells {
+                    collect_text_links(&cell.content, links);
+                }
+            }
+        }
+        Block::DefinitionList { items } => {
+            for item in items {
+                collect_text_links(&item.term, links);
+                for definition in &item.definitions {
+                    nodes(definition, links);
+                }
+            }
+        }
+        Block::Details {
+            summary, content, ..
+        } => {
+            collect_text_links(summary, links);
+            nodes(content, links);
+        }
+        _ => {}
+    }
+}
+
+fn collect_block_text(block: &Block, lines: &mut Vec<String>) {
+    match block {
+        Block::Paragraph { content } | Block::Heading { content, .. } => {
+            let plain_text = content.plain_text();
+            if !plain_text.trim().is_empty() {
+                lines.push(plain_text);
+            }
+        }
+        Block::List { items, .. } => {
+            for item in items {
+                // ListItem content is Vec<Node>, so process each node
+                for node in &item.content {
+                    collect_block_text(&node.block, lines);
+                }
+            }
+        }
+        Block::Quote { content } => {
+            for node in content {
+                collect_block_text(&node.block, lines);
+            }
+        }
+        Block::CodeBlock { content, .. } => {
+            lines.push(content.clone());
+        }
+        Block::Table { rows, header, .. } => {
+            if let Some(header_row) = header {
+                let row_text: Vec<String> = header_row
+                    .cells
+                    .iter()
+                    .map(|cell| cell.content.plain_text())
+                    .collect();
+                if !row_text.is_empty() {
+                    lines.push(row_text.join(" "));
+                }
+            }
+            for row in rows {
+                let row_text: Vec<String> = row
+                    .cells
+                    .iter()
+                    .map(|cell| cell.content.plain_text())
+                    .collect();
+                if !row_text.is_empty() {
+                    lines.push(row_text.join(" "));
+                }
+            }
+        }
+        Block::DefinitionList { items } => {
+            for item in items {
+                lines.push(item.term.plain_text());
+                // Process each definition (Vec<Vec<Node>>)
+                for definition in &item.definitions {
+                    for node in definition {
+                        collect_block_text(&node.block, lines);
+                    }
+                }
+            }
+        }
+        Block::EpubBlock { content, .. } => {
+            for node in content {
+                collect_block_text(&node.block, lines);
+            }
+        }
+        Block::Details {
+            summary, content, ..
+        } => {
+            lines.push(summary.plain_text());
+            for node in content {
+                collect_block_text(&node.block, lines);
+            }
+        }
+        _ => {}
     }
 }
 
@@ -387,6 +598,44 @@ impl Text {
     pub fn insert_front(&mut self, item: TextOrInline) {
         self.0.insert(0, item);
     }
+
+    /// Give `style` to the runs that have no style of their own
+    pub fn apply_style_to_plain(&mut self, style: Style) {
+        for item in self.0.iter_mut() {
+            if let TextOrInline::Text(node) = item
+                && node.style.is_none()
+            {
+                node.style = Some(style.clone());
+            }
+        }
+    }
+
+    /// The words of the text without styling; links contribute their text, images their alt text
+    pub fn plain_text(&self) -> String {
+        let mut result = String::new();
+
+        for part in self.iter() {
+            match part {
+                TextOrInline::Text(text_node) => {
+                    result.push_str(&text_node.content);
+                }
+                TextOrInline::Inline(inline) => match inline {
+                    Inline::Link { text, .. } => {
+                        result.push_str(&text.plain_text());
+                    }
+                    Inline::Image { alt_text, .. } => {
+                        result.push_str(alt_text);
+                    }
+                    Inline::LineBreak => {
+                        result.push(' ');
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        result
+    }
 }
 
 impl IntoIterator for Text {
@@ -398,6 +647,39 @@ impl IntoIterator for Text {
     }
 }
 
+/// Utility function to flatten Text AST into a plain string
+/// Links contribute their text, images their alt text, and line breaks are kept
+pub fn text_to_string(text: &Text) -> String {
+    let mut result = String::new();
+    for item in text.iter() {
+        match item {
+            TextOrInline::Text(text_node) => {
+                result.push_str(&text_node.content);
+            }
+            TextOrInline::Inline(inline) => match inline {
+                Inline::Link {
+                    text: link_text, ..
+                } => {
+                    result.push_str(&text_to_string(link_text));
+                }
+                Inline::Image { alt_text, .. } => {
+                    result.push_str(alt_text);
+                }
+                Inline::Anchor { .. } => {
+                    // Anchors don't contribute to text content
+                }
+                Inline::LineBreak => {
+                    result.push('\n');
+                }
+                Inline::SoftBreak => {
+                    result.push(' ');
+                }
+            },
+        }
+    }
+    result
+}
+
 /// Utility function to classify link href and extract target information
 /// This is used by both the HTML parser and table rendering code
 pub fn classify_link_href(href: &str) -> (LinkType, Option<String>, Option<String>) {
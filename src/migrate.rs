@@ -0,0 +1,247 @@
+use crate::bookmarks::Bookmarks;
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format of `bookmarks.json` written by this version, kept in its `version` field.
+/// 0 is any file from before the field existed.
+pub const BOOKMARKS_VERSION: u64 = 1;
+
+/// What upgrading a file changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upgrade {
+    pub from: u64,
+    pub changes: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Migration {
+    /// There is no file yet
+    Missing,
+    UpToDate,
+    /// The file was rewritten in the current format, the old one kept at `backup`
+    Upgraded {
+        upgrade: Upgrade,
+        backup: PathBuf,
+    },
+}
+
+/// A file written by a newer bookokcat. It must not be backed up as unreadable or written
+/// over: saving would lose whatever the newer version added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewerFormat {
+    pub version: u64,
+}
+
+impl fmt::Display for NewerFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Written by a newer bookokcat (format {}, this one reads up to {BOOKMARKS_VERSION})",
+            self.version
+        )
+    }
+}
+
+impl std::error::Error for NewerFormat {}
+
+/// Whether `error` is, or was caused by, a `NewerFormat`
+pub fn is_newer_format(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<NewerFormat>().is_some()
+}
+
+/// Bring parsed bookmarks up to `BOOKMARKS_VERSION` in place. `None` if they already are;
+/// a `NewerFormat` error for files from a newer bookokcat.
+pub fn upgrade_bookmarks(value: &mut Value) -> Result<Option<Upgrade>> {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > BOOKMARKS_VERSION {
+        return Err(NewerFormat { version }.into());
+    }
+    if version == BOOKMARKS_VERSION {
+        return Ok(None);
+    }
+    let Some(root) = value.as_object_mut() else {
+        bail!("Not a JSON object");
+    };
+
+    let mut changes = Vec::new();
+    if version < 1 {
+        // The first bookmarks kept the chapter's number and a line offset. The chapter
+        // number is still used when there's no chapter file; the offset can't be matched
+        // to a paragraph, so it's dropped.
+        let mut converted = 0;
+        for bookmark in root
+            .get_mut("books")
+            .and_then(Value::as_object_mut)
+            .into_iter()
+            .flat_map(|books| books.values_mut())
+            .filter_map(Value::as_object_mut)
+        {
+            if bookmark.contains_key("chapter_href") {
+                continue;
+            }
+            if let Some(chapter) = bookmark.remove("chapter") {
+                bookmark.entry("chapter_index").or_insert(chapter);
+            }
+            bookmark.remove("scroll_offset");
+            bookmark.insert("chapter_href".to_string(), Value::String(String::new()));
+            converted += 1;
+        }
+        if converted > 0 {
+            changes.push(format!(
+                "{converted} bookmark(s) converted from chapter numbers and line offsets"
+            ));
+        }
+        changes.push("Format version recorded".to_string());
+    }
+
+    root.insert("version".to_string(), BOOKMARKS_VERSION.into());
+    Ok(Some(Upgrade {
+        from: version,
+        changes,
+    }))
+}
+
+/// Rewrite a bookmarks file in the current format, after copying it to a backup beside
+/// it. A file that can't be upgraded is left as it is.
+pub fn migrate_bookmarks_file(path: &Path) -> Result<Migration> {
+    if !path.is_file() {
+        return Ok(Migration::Missing);
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut value: Value = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not valid JSON", path.display()))?;
+    let Some(upgrade) = upgrade_bookmarks(&mut value)
+        .with_context(|| format!("Can't upgrade {}", path.display()))?
+    else {
+        return Ok(Migration::UpToDate);
+    };
+    // Make sure the result loads before anything is replaced
+    serde_json::from_value::<Bookmarks>(value.clone())
+        .with_context(|| format!("{} doesn't load after upgrading", path.display()))?;
+
+    let backup = back_up(path, &format!("v{}", upgrade.from))?;
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string_pretty(&value)?)?;
+    fs::rename(&temp_path, path)?;
+    Ok(Migration::Upgraded { upgrade, backup })
+}
+
+/// Copy `path` to `<path>.<label>-<timestamp>.bak`, returning where it went
+pub fn back_up(path: &Path, label: &str) -> Result<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{label}-{timestamp}.bak"));
+    let backup = PathBuf::from(name);
+    fs::copy(path, &backup).with_context(|| {
+        format!(
+            "Failed to back up {} to {}",
+            path.display(),
+            backup.display()
+        )
+    })?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_old_bookmarks_are_upgraded_with_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let old = json!({
+            "books": {
+                "/books/old.epub": {
+                    "chapter": 3,
+                    "scroll_offset": 120,
+                    "last_read": "2024-05-01T10:00:00Z"
+                },
+                "/books/new.epub": {
+                    "chapter_href": "ch2.xhtml",
+                    "node_index": 7,
+                    "last_read": "2025-01-01T10:00:00Z"
+                }
+            },
+            "read_chapters": { "/books/new.epub": [0, 1] }
+        });
+        fs::write(&file, old.to_string()).unwrap();
+
+        let Migration::Upgraded { upgrade, backup } = migrate_bookmarks_file(&file).unwrap() else {
+            panic!("expected an upgrade");
+        };
+        assert_eq!(upgrade.from, 0);
+        assert_eq!(
+            serde_json::from_str::<Value>(&fs::read_to_string(backup).unwrap()).unwrap(),
+            old
+        );
+
+        let bookmarks = Bookmarks::load_from_file(file.to_str().unwrap()).unwrap();
+        let converted = bookmarks.get_bookmark("/books/old.epub").unwrap();
+        assert_eq!(converted.chapter_index, Some(3));
+        assert_eq!(converted.chapter_href, "");
+        assert_eq!(
+            bookmarks
+                .get_bookmark("/books/new.epub")
+                .unwrap()
+                .node_index,
+            Some(7)
+        );
+        assert!(bookmarks.is_chapter_read("/books/new.epub", 1));
+
+        assert!(matches!(
+            migrate_bookmarks_file(&file).unwrap(),
+            Migration::UpToDate
+        ));
+        assert!(matches!(
+            migrate_bookmarks_file(&dir.path().join("none.json")).unwrap(),
+            Migration::Missing
+        ));
+    }
+
+    #[test]
+    fn test_newer_files_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.json");
+        let newer = json!({ "version": BOOKMARKS_VERSION + 1, "books": {} }).to_string();
+        fs::write(&file, &newer).unwrap();
+
+        assert!(is_newer_format(&migrate_bookmarks_file(&file).unwrap_err()));
+        assert_eq!(fs::read_to_string(&file).unwrap(), newer);
+
+        // Opened read-only: what can be read is there, but nothing is written or backed up
+        let newer = json!({
+            "version": BOOKMARKS_VERSION + 1,
+            "books": {
+                "/books/a.epub": {
+                    "chapter_href": "ch1.xhtml",
+                    "node_index": 4,
+                    "last_read": "2025-01-01T10:00:00Z",
+                    "reading_speed": 250
+                }
+            }
+        })
+        .to_string();
+        fs::write(&file, &newer).unwrap();
+        let mut bookmarks = Bookmarks::load_or_ephemeral(file.to_str());
+        assert_eq!(
+            bookmarks.get_bookmark("/books/a.epub").unwrap().node_index,
+            Some(4)
+        );
+        bookmarks.update_bookmark(
+            "/books/b.epub",
+            "ch1.xhtml".to_string(),
+            Some(1),
+            None,
+            None,
+        );
+        bookmarks.save().unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), newer);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
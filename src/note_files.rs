@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+/// Comments that link a passage to a note file carry a line starting with this
+pub const NOTE_FILE_PREFIX: &str = "Note file: ";
+
+/// Used when the `note_template` preference doesn't name a template file
+pub const DEFAULT_NOTE_TEMPLATE: &str = "# {{chapter_title}}
+
+*{{author}}, {{title}}, chapter {{chapter}}*
+
+{{quote}}
+
+";
+
+/// What a new note file is filled in with
+#[derive(Debug, Clone, Default)]
+pub struct NoteContext {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// 1-based, as shown in the reader
+    pub chapter: usize,
+    pub chapter_title: Option<String>,
+    /// The selected passage, if any
+    pub quote: Option<String>,
+    pub book_path: String,
+    pub date: String,
+}
+
+/// Fill in `{{title}}`, `{{author}}`, `{{chapter}}`, `{{chapter_title}}`, `{{quote}}`,
+/// `{{book}}` and `{{date}}`. The quote comes as a Markdown blockquote; unknown values
+/// are left empty.
+pub fn fill_note_template(template: &str, context: &NoteContext) -> String {
+    let quote = context.quote.as_deref().map_or_else(String::new, |quote| {
+        quote
+            .trim()
+            .lines()
+            .map(|line| format!("> {line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    let chapter_title = context
+        .chapter_title
+        .clone()
+        .unwrap_or_else(|| format!("Chapter {}", context.chapter));
+    [
+        ("{{title}}", context.title.clone().unwrap_or_default()),
+        ("{{author}}", context.author.clone().unwrap_or_default()),
+        ("{{chapter}}", context.chapter.to_string()),
+        ("{{chapter_title}}", chapter_title),
+        ("{{quote}}", quote),
+        ("{{book}}", context.book_path.clone()),
+        ("{{date}}", context.date.clone()),
+    ]
+    .into_iter()
+    .fold(template.to_string(), |text, (placeholder, value)| {
+        text.replace(placeholder, &value)
+    })
+}
+
+/// The comment line that links a passage to its note file
+pub fn note_link(path: &Path) -> String {
+    format!("{NOTE_FILE_PREFIX}{}", path.display())
+}
+
+/// The note file a comment links to, if it does
+pub fn linked_note_file(comment: &str) -> Option<PathBuf> {
+    comment.lines().find_map(|line| {
+        line.strip_prefix(NOTE_FILE_PREFIX)
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_is_filled_in_and_the_link_read_back() {
+        let context = NoteContext {
+            title: Some("Emma".to_string()),
+            author: Some("Jane Austen".to_string()),
+            chapter: 3,
+            chapter_title: None,
+            quote: Some("Emma Woodhouse, handsome,\n\nclever, and rich ".to_string()),
+            book_path: "/books/emma.epub".to_string(),
+            date: "2026-10-16".to_string(),
+        };
+        assert_eq!(
+            fill_note_template(DEFAULT_NOTE_TEMPLATE, &context),
+            "# Chapter 3\n\n*Jane Austen, Emma, chapter 3*\n\n\
+             > Emma Woodhouse, handsome,\n>\n> clever, and rich\n\n"
+        );
+        assert_eq!(
+            fill_note_template("{{book}} {{date}} {{unknown}}", &context),
+            "/books/emma.epub 2026-10-16 {{unknown}}"
+        );
+
+        let path = Path::new("/books/emma_notes/chapter3.md");
+        let comment = format!("Worth rereading\n{}", note_link(path));
+        assert_eq!(linked_note_file(&comment), Some(path.to_path_buf()));
+        assert_eq!(linked_note_file("Worth rereading"), None);
+    }
+}
@@ -1,5 +1,5 @@
 use crossterm::{
-    event::DisableMouseCapture,
+    event::{DisableBracketedPaste, DisableMouseCapture},
     execute,
     terminal::{LeaveAlternateScreen, disable_raw_mode},
 };
@@ -64,12 +64,20 @@ where
 /// - Disabling raw mode
 /// - Exiting alternate screen
 /// - Disabling mouse capture (important for restoring mouse functionality)
+/// - Disabling bracketed paste
 /// - Disabling keyboard enhancement flags
 /// - Showing the cursor
+/// - Putting back the window title saved on start
 fn restore_terminal() {
     let _ = disable_raw_mode();
-    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    );
     let _ = execute!(io::stderr(), crossterm::cursor::Show);
+    let _ = crate::terminal_title::pop_title(&mut io::stdout());
     let _ = writeln!(io::stderr());
 }
 
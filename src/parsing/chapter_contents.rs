@@ -0,0 +1,227 @@
+use crate::markdown::{
+    Block, ChapterContents, Document, Inline, LinkType, ListItem, ListKind, Node, Text,
+    TextOrInline, text_to_string,
+};
+use crate::markdown_text_reader::MarkdownTextReader;
+
+/// Chapters with fewer sections than this are easy enough to find your way around
+const MIN_SECTIONS: usize = 4;
+
+struct Section {
+    level: u8,
+    title: String,
+    anchor: String,
+}
+
+/// Contents for a chapter with at least `MIN_SECTIONS` headings. They go right under the
+/// chapter's title, i.e. its first heading when no later heading is at the same level or
+/// above it, or else at the very top.
+pub fn chapter_contents(document: &Document) -> Option<ChapterContents> {
+    let headings: Vec<(usize, u8)> = document
+        .blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(block_idx, node)| match &node.block {
+            Block::Heading { level, .. } => Some((block_idx, *level as u8)),
+            _ => None,
+        })
+        .collect();
+    let (first_idx, first_level) = *headings.first()?;
+    let has_title = headings[1..].iter().all(|(_, level)| *level > first_level);
+    let before_block = if has_title { first_idx + 1 } else { 0 };
+
+    let sections: Vec<Section> = document.blocks[before_block..]
+        .iter()
+        .filter_map(section)
+        .collect();
+    if sections.len() < MIN_SECTIONS {
+        return None;
+    }
+
+    let mut position = 0;
+    let items = list_items(&sections, &mut position, 0);
+    Some(ChapterContents {
+        before_block,
+        list: Node::new(
+            Block::List {
+                kind: ListKind::Unordered,
+                items,
+            },
+            0..0,
+        ),
+    })
+}
+
+/// A heading and the anchor the reader knows it by: its id, an anchor inside it, or the
+/// one generated from its text
+fn section(node: &Node) -> Option<Section> {
+    let Block::Heading { level, content } = &node.block else {
+        return None;
+    };
+    let text = text_to_string(content);
+    let title = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        return None;
+    }
+    let anchor = node
+        .id
+        .clone()
+        .or_else(|| {
+            content.iter().find_map(|item| match item {
+                TextOrInline::Inline(Inline::Anchor { id }) => Some(id.clone()),
+                _ => None,
+            })
+        })
+        .unwrap_or_else(|| MarkdownTextReader::generate_heading_anchor(&text));
+    Some(Section {
+        level: *level as u8,
+        title,
+        anchor,
+    })
+}
+
+/// Nest sections by level: each one owns the deeper sections that follow it
+fn list_items(sections: &[Section], position: &mut usize, parent_level: u8) -> Vec<ListItem> {
+    let mut items = Vec::new();
+    while let Some(section) = sections.get(*position) {
+        if section.level <= parent_level {
+            break;
+        }
+        *position += 1;
+
+        let link = Inline::Link {
+            text: Text::from(section.title.as_str()),
+            url: format!("#{}", section.anchor),
+            title: None,
+            link_type: LinkType::InternalAnchor,
+            target_chapter: None,
+            target_anchor: Some(section.anchor.clone()),
+        };
+        let mut content = vec![Node::new(
+            Block::Paragraph {
+                content: Text::from(TextOrInline::Inline(link)),
+            },
+            0..0,
+        )];
+        let children = list_items(sections, position, section.level);
+        if !children.is_empty() {
+            content.push(Node::new(
+                Block::List {
+                    kind: ListKind::Unordered,
+                    items: children,
+                },
+                0..0,
+            ));
+        }
+        items.push(ListItem {
+            content,
+            task_status: None,
+        });
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+
+    fn item_titles(items: &[ListItem]) -> Vec<(String, usize)> {
+        items
+            .iter()
+            .map(|item| {
+                let title = match &item.content[0].block {
+                    Block::Paragraph { content } => text_to_string(content),
+                    _ => String::new(),
+                };
+                let children = match item.content.get(1).map(|node| &node.block) {
+                    Some(Block::List { items, .. }) => items.len(),
+                    _ => 0,
+                };
+                (title, children)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_contents_go_under_the_title_and_nest_by_level() {
+        let html = r#"<h1>Chapter One</h1><p>Intro.</p>
+            <h2 id="a">First</h2><p>x</p>
+            <h3><a id="a1"></a>Detail</h3><p>x</p>
+            <h2>Second  part</h2><p>x</p>
+            <h2 id="c">Third</h2><p>x</p>"#;
+        let doc = HtmlToMarkdownConverter::new()
+            .with_chapter_contents(true)
+            .convert(html);
+        let contents = doc.contents.clone().expect("four sections get contents");
+        assert_eq!(contents.before_block, 1);
+
+        let Block::List { items, .. } = &contents.list.block else {
+            panic!("Expected a list");
+        };
+        assert_eq!(
+            item_titles(items),
+            vec![
+                ("First".to_string(), 1),
+                ("Second part".to_string(), 0),
+                ("Third".to_string(), 0)
+            ]
+        );
+        let mut list = Document::new();
+        list.blocks.push(contents.list.clone());
+        let anchors: Vec<String> = list
+            .links()
+            .iter()
+            .filter_map(|link| match link {
+                Inline::Link { target_anchor, .. } => target_anchor.clone(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(anchors, vec!["a", "a1", "second-part", "c"]);
+
+        // Rendered between the title and the first paragraph, outside of any block
+        let mut reader = MarkdownTextReader::new();
+        let rendered = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+        let row = |text: &str| {
+            rendered
+                .lines
+                .iter()
+                .position(|line| line.raw_text.contains(text))
+                .unwrap()
+        };
+        assert!(row("CHAPTER ONE") < row("Third") && row("Third") < row("Intro."));
+        assert_eq!(rendered.lines[row("Detail")].node_index, None);
+        assert_eq!(
+            reader.get_anchor_position("second-part"),
+            rendered
+                .lines
+                .iter()
+                .rposition(|line| line.raw_text.contains("Second part"))
+        );
+    }
+
+    #[test]
+    fn test_short_chapters_and_the_default_get_no_contents() {
+        let html = "<h1>Title</h1><h2>A</h2><h2>B</h2><h2>C</h2><h2>D</h2>";
+        assert!(
+            HtmlToMarkdownConverter::new()
+                .convert(html)
+                .contents
+                .is_none()
+        );
+
+        let short = "<h1>Title</h1><h2>A</h2><h2>B</h2><h2>C</h2>";
+        let doc = HtmlToMarkdownConverter::new()
+            .with_chapter_contents(true)
+            .convert(short);
+        assert!(doc.contents.is_none());
+
+        // Without a title heading the contents lead the chapter
+        let untitled = "<p>Intro</p><h2>A</h2><h2>B</h2><h2>C</h2><h2>D</h2>";
+        let doc = HtmlToMarkdownConverter::new()
+            .with_chapter_contents(true)
+            .convert(untitled);
+        assert_eq!(doc.contents.map(|contents| contents.before_block), Some(0));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::parsing::css::Selector;
+use markup5ever_rcdom::Node;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+/// Elements to strip from chapters before they are converted, e.g. share buttons, ad
+/// blocks or tracking pixels some EPUBs carry along.
+///
+/// Each entry is a simple selector: an optional tag name followed by any number of
+/// `.class`, `#id`, `[attribute]` and `[attribute=value]` parts, like `div.share-buttons`,
+/// `.ad`, `#tracker` or `img[width=1]`. An element matching an `exclude` selector is
+/// removed with everything inside it, unless it also matches an `include` selector.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentFilters {
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub include: Vec<String>,
+}
+
+impl ContentFilters {
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty()
+    }
+
+    /// Remove excluded elements below `node`
+    pub(crate) fn apply(&self, node: &Rc<Node>) {
+        if self.is_empty() {
+            return;
+        }
+        let exclude = parse_selectors(&self.exclude);
+        let include = parse_selectors(&self.include);
+        strip(node, &exclude, &include);
+    }
+}
+
+fn strip(node: &Rc<Node>, exclude: &[Selector], include: &[Selector]) {
+    node.children.borrow_mut().retain(|child| {
+        let matches = |selectors: &[Selector]| selectors.iter().any(|s| s.matches(child));
+        !matches(exclude) || matches(include)
+    });
+    let children = node.children.borrow().clone();
+    for child in &children {
+        strip(child, exclude, include);
+    }
+}
+
+fn parse_selectors(selectors: &[String]) -> Vec<Selector> {
+    selectors
+        .iter()
+        .filter_map(|selector| Selector::parse(selector))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_filters_strip_noise() {
+        let html = r#"<html><body>
+            <p>Keep me.</p>
+            <div class="share buttons"><p>Share on social media</p></div>
+            <p id="promo">Buy the sequel</p>
+            <img src="pixel.gif" width="1" height="1"/>
+            <div class="ad keep"><p>Sponsored but wanted</p></div>
+        </body></html>"#;
+        let filters = ContentFilters {
+            exclude: vec![
+                "div.share".to_string(),
+                "#promo".to_string(),
+                "img[width=1]".to_string(),
+                ".ad".to_string(),
+            ],
+            include: vec![".keep".to_string()],
+        };
+
+        let doc = HtmlToMarkdownConverter::new()
+            .with_filters(filters)
+            .convert(html);
+        let text = doc.plain_text();
+        assert!(text.contains("Keep me."));
+        assert!(text.contains("Sponsored but wanted"));
+        assert!(!text.contains("Share on social media"));
+        assert!(!text.contains("Buy the sequel"));
+        assert_eq!(doc.blocks.len(), 2);
+
+        assert_eq!(Selector::parse(""), None);
+        assert_eq!(Selector::parse("div.[x]"), None);
+    }
+}
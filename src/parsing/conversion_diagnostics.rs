@@ -0,0 +1,309 @@
+use crate::markdown::{
+    Block, Document, HeadingLevel, ListItem, ListKind, Node as MarkdownNode, Text, TextNode,
+};
+use markup5ever_rcdom::{Node, NodeData};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Tags the converter gives a meaning to, or skips on purpose
+const KNOWN_TAGS: &[&str] = &[
+    "html",
+    "head",
+    "body",
+    "title",
+    "meta",
+    "link",
+    "style",
+    "script",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "div",
+    "section",
+    "article",
+    "p",
+    "img",
+    "pre",
+    "math",
+    "ul",
+    "ol",
+    "li",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "th",
+    "td",
+    "dl",
+    "dt",
+    "dd",
+    "blockquote",
+    "details",
+    "summary",
+    "hr",
+    "strong",
+    "b",
+    "em",
+    "i",
+    "code",
+    "a",
+    "br",
+    "del",
+    "s",
+    "strike",
+    "sub",
+    "sup",
+];
+
+/// Plain wrappers whose only job is grouping; passing their content through loses nothing
+const WRAPPER_TAGS: &[&str] = &["span", "header", "footer", "main", "nav"];
+
+/// Attributes the converter reads
+const USED_ATTRIBUTES: &[&str] = &[
+    "id",
+    "href",
+    "title",
+    "src",
+    "alt",
+    "data-type",
+    "rowspan",
+    "start",
+    "open",
+    "epub:type",
+];
+
+/// What a chapter's markup lost on the way through `HtmlToMarkdownConverter`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConversionDiagnostics {
+    /// Tags without special handling, by count; their text is kept without structure
+    pub unsupported_tags: BTreeMap<String, usize>,
+    /// Attributes that were ignored, by count
+    pub dropped_attributes: BTreeMap<String, usize>,
+    /// Block elements that contained text but produced nothing, by count
+    pub empty_output: BTreeMap<String, usize>,
+    /// Runs of text sitting directly in a container instead of a paragraph; they are dropped
+    pub dropped_text: usize,
+    /// Links to chapters or anchors the book doesn't have; filled in by the caller, which
+    /// knows the spine
+    pub broken_links: Vec<String>,
+}
+
+impl ConversionDiagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.unsupported_tags.is_empty()
+            && self.dropped_attributes.is_empty()
+            && self.empty_output.is_empty()
+            && self.dropped_text == 0
+            && self.broken_links.is_empty()
+    }
+
+    /// Count unsupported tags and ignored attributes in a parsed document. MathML is
+    /// converted as a whole, so its inner elements aren't looked at.
+    pub(crate) fn scan_markup(&mut self, node: &Rc<Node>) {
+        if let NodeData::Element {
+            ref name,
+            ref attrs,
+            ..
+        } = node.data
+        {
+            let tag = name.local.as_ref();
+            if !KNOWN_TAGS.contains(&tag) && !WRAPPER_TAGS.contains(&tag) {
+                *self.unsupported_tags.entry(tag.to_string()).or_default() += 1;
+            }
+            for attr in attrs.borrow().iter() {
+                let attribute = attr.name.local.as_ref();
+                if !USED_ATTRIBUTES.contains(&attribute) && !is_document_metadata(attribute) {
+                    *self
+                        .dropped_attributes
+                        .entry(attribute.to_string())
+                        .or_default() += 1;
+                }
+            }
+            if tag == "math" {
+                return;
+            }
+        }
+        for child in node.children.borrow().iter() {
+            self.scan_markup(child);
+        }
+    }
+
+    pub(crate) fn record_empty_output(&mut self, tag: &str) {
+        *self.empty_output.entry(tag.to_string()).or_default() += 1;
+    }
+
+    /// One line per kind of problem, e.g. `Unsupported tags: <caption> ×2, <figure> ×1`
+    pub fn summary_lines(&self) -> Vec<String> {
+        fn counts(map: &BTreeMap<String, usize>, as_tag: bool) -> String {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            entries
+                .into_iter()
+                .map(|(name, count)| {
+                    if as_tag {
+                        format!("<{name}> ×{count}")
+                    } else {
+                        format!("{name} ×{count}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        let mut lines = Vec::new();
+        if !self.unsupported_tags.is_empty() {
+            lines.push(format!(
+                "Unsupported tags (text kept, structure lost): {}",
+                counts(&self.unsupported_tags, true)
+            ));
+        }
+        if !self.dropped_attributes.is_empty() {
+            lines.push(format!(
+                "Ignored attributes: {}",
+                counts(&self.dropped_attributes, false)
+            ));
+        }
+        if !self.empty_output.is_empty() {
+            lines.push(format!(
+                "Elements with text but no output: {}",
+                counts(&self.empty_output, true)
+            ));
+        }
+        if self.dropped_text > 0 {
+            lines.push(format!(
+                "Text outside any paragraph, dropped: {} runs",
+                self.dropped_text
+            ));
+        }
+        if !self.broken_links.is_empty() {
+            lines.push(format!(
+                "Broken links (target not in the book): {}",
+                self.broken_links.join(", ")
+            ));
+        }
+        lines
+    }
+}
+
+/// How a whole book converted, summed over its chapters for the notice shown when it opens
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BookHealth {
+    pub chapters: usize,
+    /// Unsupported tags, elements that rendered empty and dropped runs of text
+    pub warnings: usize,
+    pub broken_links: usize,
+    /// Images in a format the reader can't draw (SVG)
+    pub unsupported_images: usize,
+}
+
+impl BookHealth {
+    /// Count a converted chapter; ignored attributes are too common to be worth a warning
+    pub fn add_chapter(&mut self, diagnostics: &ConversionDiagnostics) {
+        self.chapters += 1;
+        self.warnings += diagnostics.unsupported_tags.values().sum::<usize>()
+            + diagnostics.empty_output.values().sum::<usize>()
+            + diagnostics.dropped_text;
+        self.broken_links += diagnostics.broken_links.len();
+    }
+
+    pub fn has_problems(&self) -> bool {
+        self.warnings > 0 || self.broken_links > 0 || self.unsupported_images > 0
+    }
+
+    /// e.g. `12 chapters parsed, 3 warnings, 1 broken link, 2 images unsupported`
+    pub fn summary(&self) -> String {
+        fn count(n: usize, one: &str, many: &str) -> String {
+            format!("{n} {}", if n == 1 { one } else { many })
+        }
+        let mut parts = vec![count(self.chapters, "chapter parsed", "chapters parsed")];
+        if !self.has_problems() {
+            parts.push("no problems found".to_string());
+        }
+        if self.warnings > 0 {
+            parts.push(count(self.warnings, "warning", "warnings"));
+        }
+        if self.broken_links > 0 {
+            parts.push(count(self.broken_links, "broken link", "broken links"));
+        }
+        if self.unsupported_images > 0 {
+            parts.push(count(
+                self.unsupported_images,
+                "image unsupported",
+                "images unsupported",
+            ));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Namespace declarations and language tags say nothing about how a chapter looks
+fn is_document_metadata(attribute: &str) -> bool {
+    attribute == "lang" || attribute.starts_with("xmlns") || attribute.starts_with("xml:")
+}
+
+/// Whether an element has any non-whitespace text below it
+pub(crate) fn has_text(node: &Rc<Node>) -> bool {
+    match node.data {
+        NodeData::Text { ref contents } => !contents.borrow().trim().is_empty(),
+        _ => node.children.borrow().iter().any(has_text),
+    }
+}
+
+/// The `:doctor` report: per chapter, what its markup lost in conversion. `chapters` holds
+/// a label and the diagnostics of every chapter that had something to report.
+pub fn doctor_report(
+    chapters: &[(String, ConversionDiagnostics)],
+    chapter_count: usize,
+) -> Document {
+    fn block(block: Block) -> MarkdownNode {
+        MarkdownNode::new(block, 0..0)
+    }
+    fn paragraph(content: &str) -> MarkdownNode {
+        let mut text = Text::default();
+        text.push_text(TextNode::new(content.to_string(), None));
+        block(Block::Paragraph { content: text })
+    }
+
+    let mut doc = Document::new();
+    let mut title = Text::default();
+    title.push_text(TextNode::new("Markup diagnostics".to_string(), None));
+    doc.blocks.push(block(Block::Heading {
+        level: HeadingLevel::H1,
+        content: title,
+    }));
+
+    if chapters.is_empty() {
+        doc.blocks.push(paragraph(&format!(
+            "All {chapter_count} chapters converted without losing any markup, and every link \
+             to another chapter resolves."
+        )));
+        return doc;
+    }
+    doc.blocks.push(paragraph(&format!(
+        "{} of {chapter_count} chapters use markup the reader doesn't fully support or have \
+         broken links.",
+        chapters.len()
+    )));
+
+    for (label, diagnostics) in chapters {
+        let mut heading = Text::default();
+        heading.push_text(TextNode::new(label.clone(), None));
+        doc.blocks.push(block(Block::Heading {
+            level: HeadingLevel::H2,
+            content: heading,
+        }));
+        let items = diagnostics
+            .summary_lines()
+            .iter()
+            .map(|line| ListItem::new(vec![paragraph(line)]))
+            .collect();
+        doc.blocks.push(block(Block::List {
+            kind: ListKind::Unordered,
+            items,
+        }));
+    }
+    doc
+}
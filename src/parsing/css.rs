@@ -0,0 +1,372 @@
+use crate::markdown::TextAlign;
+use markup5ever_rcdom::{Node, NodeData};
+use std::rc::Rc;
+
+/// Upper bound for `margin-left` indentation, in indent levels of two columns
+const MAX_INDENT: usize = 8;
+
+/// A simple CSS selector: an optional tag name followed by any number of `.class`,
+/// `#id`, `[attribute]` and `[attribute=value]` parts. Combinators and pseudo-classes
+/// aren't supported.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Selector {
+    tag: Option<String>,
+    classes: Vec<String>,
+    id: Option<String>,
+    attributes: Vec<(String, Option<String>)>,
+}
+
+impl Selector {
+    /// `None` for an empty, malformed or unsupported selector
+    pub(crate) fn parse(selector: &str) -> Option<Self> {
+        let selector = selector.trim();
+        if selector.contains(|c: char| c.is_whitespace() || matches!(c, '>' | '+' | '~' | ':')) {
+            return None;
+        }
+        let mut parsed = Selector::default();
+        let tag_end = selector.find(['.', '#', '[']).unwrap_or(selector.len());
+        if tag_end > 0 && &selector[..tag_end] != "*" {
+            parsed.tag = Some(selector[..tag_end].to_ascii_lowercase());
+        }
+
+        let mut rest = &selector[tag_end..];
+        while let Some(marker) = rest.chars().next() {
+            rest = &rest[1..];
+            if marker == '[' {
+                let end = rest.find(']')?;
+                let attribute = match rest[..end].split_once('=') {
+                    Some((name, value)) => (
+                        name.trim().to_ascii_lowercase(),
+                        Some(value.trim().trim_matches(['"', '\'']).to_string()),
+                    ),
+                    None => (rest[..end].trim().to_ascii_lowercase(), None),
+                };
+                parsed.attributes.push(attribute);
+                rest = &rest[end + 1..];
+                continue;
+            }
+            let end = rest.find(['.', '#', '[']).unwrap_or(rest.len());
+            let name = rest[..end].to_string();
+            if name.is_empty() {
+                return None;
+            }
+            match marker {
+                '.' => parsed.classes.push(name),
+                '#' => parsed.id = Some(name),
+                _ => return None,
+            }
+            rest = &rest[end..];
+        }
+
+        (parsed != Selector::default()).then_some(parsed)
+    }
+
+    /// (ids, classes and attributes, tag) as in CSS specificity
+    fn specificity(&self) -> (usize, usize, usize) {
+        (
+            usize::from(self.id.is_some()),
+            self.classes.len() + self.attributes.len(),
+            usize::from(self.tag.is_some()),
+        )
+    }
+
+    pub(crate) fn matches(&self, node: &Rc<Node>) -> bool {
+        let NodeData::Element {
+            ref name,
+            ref attrs,
+            ..
+        } = node.data
+        else {
+            return false;
+        };
+        if self
+            .tag
+            .as_ref()
+            .is_some_and(|tag| tag.as_str() != name.local.as_ref())
+        {
+            return false;
+        }
+
+        let attrs = attrs.borrow();
+        let attribute = |wanted: &str| {
+            attrs
+                .iter()
+                .find(|attr| attr.name.local.as_ref() == wanted)
+                .map(|attr| attr.value.to_string())
+        };
+        let classes = attribute("class").unwrap_or_default();
+        self.classes
+            .iter()
+            .all(|class| classes.split_whitespace().any(|c| c == class))
+            && self
+                .id
+                .as_ref()
+                .is_none_or(|id| attribute("id").as_ref() == Some(id))
+            && self
+                .attributes
+                .iter()
+                .all(|(name, value)| match (attribute(name), value) {
+                    (Some(actual), Some(expected)) => &actual == expected,
+                    (found, None) => found.is_some(),
+                    (None, Some(_)) => false,
+                })
+    }
+}
+
+/// The properties the reader honors. `None` leaves the inherited or default value alone.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Declarations {
+    pub text_align: Option<TextAlign>,
+    /// `margin-left`, in indent levels
+    pub indent: Option<usize>,
+    pub italic: Option<bool>,
+    pub bold: Option<bool>,
+}
+
+impl Declarations {
+    pub fn parse(block: &str) -> Self {
+        let mut declarations = Declarations::default();
+        for declaration in block.split(';') {
+            let Some((property, value)) = declaration.split_once(':') else {
+                continue;
+            };
+            let value = value
+                .trim()
+                .trim_end_matches("!important")
+                .trim()
+                .to_ascii_lowercase();
+            match property.trim().to_ascii_lowercase().as_str() {
+                "text-align" => {
+                    declarations.text_align = match value.as_str() {
+                        "center" => Some(TextAlign::Center),
+                        "right" | "end" => Some(TextAlign::Right),
+                        "left" | "start" | "justify" => Some(TextAlign::Left),
+                        _ => declarations.text_align,
+                    }
+                }
+                "margin-left" => declarations.indent = indent_levels(&value),
+                "margin" => {
+                    // top right bottom left / top horizontal / all sides
+                    let parts: Vec<_> = value.split_whitespace().collect();
+                    let left = match parts.len() {
+                        4 => parts[3],
+                        2 | 3 => parts[1],
+                        1 => parts[0],
+                        _ => continue,
+                    };
+                    declarations.indent = indent_levels(left);
+                }
+                "font-style" => {
+                    declarations.italic = Some(matches!(value.as_str(), "italic" | "oblique"))
+                }
+                "font-weight" => {
+                    declarations.bold = Some(match value.as_str() {
+                        "bold" | "bolder" => true,
+                        weight => weight.parse::<u16>().is_ok_and(|w| w >= 600),
+                    })
+                }
+                _ => {}
+            }
+        }
+        declarations
+    }
+
+    /// Let `other`'s values win where it sets any
+    fn merge(&mut self, other: &Declarations) {
+        self.text_align = other.text_align.or(self.text_align);
+        self.indent = other.indent.or(self.indent);
+        self.italic = other.italic.or(self.italic);
+        self.bold = other.bold.or(self.bold);
+    }
+}
+
+/// A length as indent levels of two columns, taking 1em as one level
+fn indent_levels(length: &str) -> Option<usize> {
+    let length = length.trim();
+    if length == "0" || length == "auto" {
+        return Some(0);
+    }
+    let number_end = length
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(length.len());
+    let number: f32 = length[..number_end].parse().ok()?;
+    let ems = match &length[number_end..] {
+        "em" | "rem" => number,
+        "px" => number / 16.0,
+        "pt" => number / 12.0,
+        "%" => number / 5.0,
+        _ => return None,
+    };
+    Some((ems.round().max(0.0) as usize).min(MAX_INDENT))
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    selector: Selector,
+    declarations: Declarations,
+}
+
+/// The rules of a book's stylesheets that the reader understands: `text-align`,
+/// `margin-left` and `font-style`/`font-weight` on simple selectors. Everything else,
+/// including `@media` blocks, is skipped.
+#[derive(Debug, Default, Clone)]
+pub struct StyleSheet {
+    /// In cascade order: by specificity, then by position in the source
+    rules: Vec<Rule>,
+}
+
+impl StyleSheet {
+    pub fn parse(css: &str) -> Self {
+        let mut sheet = StyleSheet::default();
+        sheet.extend(css);
+        sheet
+    }
+
+    /// Add the rules of another stylesheet, which win over the existing ones at equal
+    /// specificity
+    pub fn extend(&mut self, css: &str) {
+        let css = strip_comments(css);
+        let mut rest = css.as_str();
+        while let Some(open) = rest.find('{') {
+            // Statements like `@import url(x.css);` end up in front of the next selector
+            let prelude = rest[..open].rsplit(';').next().unwrap_or_default().trim();
+            let body = &rest[open + 1..];
+            let mut depth = 1;
+            let Some(close) = body.find(|c| {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            }) else {
+                break;
+            };
+
+            if !prelude.starts_with('@') {
+                let declarations = Declarations::parse(&body[..close]);
+                if declarations != Declarations::default() {
+                    self.rules.extend(prelude.split(',').filter_map(|selector| {
+                        Some(Rule {
+                            selector: Selector::parse(selector)?,
+                            declarations: declarations.clone(),
+                        })
+                    }));
+                }
+            }
+            rest = &body[close + 1..];
+        }
+        // Stable, so rules of equal specificity keep their source order
+        self.rules.sort_by_key(|rule| rule.selector.specificity());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The properties that apply to an element, its `style` attribute included
+    pub(crate) fn style_for(&self, node: &Rc<Node>) -> Declarations {
+        let mut style = Declarations::default();
+        for rule in self.rules.iter().filter(|rule| rule.selector.matches(node)) {
+            style.merge(&rule.declarations);
+        }
+        if let NodeData::Element { ref attrs, .. } = node.data
+            && let Some(inline) = attrs
+                .borrow()
+                .iter()
+                .find(|attr| attr.name.local.as_ref() == "style")
+        {
+            style.merge(&Declarations::parse(&inline.value));
+        }
+        style
+    }
+}
+
+/// The text of every `<style>` element below `node`
+pub(crate) fn embedded_css(node: &Rc<Node>) -> String {
+    let mut css = String::new();
+    collect_embedded_css(node, &mut css);
+    css
+}
+
+fn collect_embedded_css(node: &Rc<Node>, css: &mut String) {
+    if let NodeData::Element { ref name, .. } = node.data
+        && name.local.as_ref() == "style"
+    {
+        for child in node.children.borrow().iter() {
+            if let NodeData::Text { ref contents } = child.data {
+                css.push_str(&contents.borrow());
+                css.push('\n');
+            }
+        }
+        return;
+    }
+    for child in node.children.borrow().iter() {
+        collect_embedded_css(child, css);
+    }
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stylesheet_cascade() {
+        let sheet = StyleSheet::parse(
+            r#"@import url("fonts.css");
+            /* letters and dedications */
+            p.letter { margin-left: 2em; font-style: italic }
+            .dedication, .epigraph { text-align: center; }
+            @media print { p { text-align: right } }
+            p { text-align: justify; margin: 0 0 0 1.5em }
+            div p { text-align: right }
+            #sig { text-align: right; font-weight: 700 }"#,
+        );
+        assert_eq!(sheet.rules.len(), 5);
+
+        let style = |html: &str| {
+            use html5ever::parse_document;
+            use html5ever::tendril::TendrilSink;
+            use markup5ever_rcdom::RcDom;
+            let dom = parse_document(RcDom::default(), Default::default())
+                .from_utf8()
+                .read_from(&mut html.as_bytes())
+                .unwrap();
+            // document > html > [head, body] > first element of the body
+            let html_node = dom.document.children.borrow()[0].clone();
+            let body = html_node.children.borrow()[1].clone();
+            let element = body.children.borrow()[0].clone();
+            sheet.style_for(&element)
+        };
+
+        let letter = style(r#"<p class="letter">Dear Sir,</p>"#);
+        assert_eq!(letter.indent, Some(2));
+        assert_eq!(letter.italic, Some(true));
+        assert_eq!(letter.text_align, Some(TextAlign::Left));
+
+        let dedication = style(r#"<p class="dedication">For M.</p>"#);
+        assert_eq!(dedication.text_align, Some(TextAlign::Center));
+
+        let signature = style(r#"<p id="sig" class="dedication" style="margin-left: 0">X</p>"#);
+        assert_eq!(signature.text_align, Some(TextAlign::Right));
+        assert_eq!(signature.bold, Some(true));
+        assert_eq!(signature.indent, Some(0));
+
+        assert_eq!(indent_levels("40px"), Some(3));
+        assert_eq!(indent_levels("-1em"), Some(0));
+        assert_eq!(indent_levels("inherit"), None);
+    }
+}
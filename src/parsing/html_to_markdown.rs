@@ -1,12 +1,55 @@
 use crate::markdown::{
-    Block, DefinitionListItem, Document, HeadingLevel, Inline, Node, Style, Text, TextNode,
-    TextOrInline,
+    Block, DefinitionListItem, Document, HeadingLevel, Inline, ListKind, Node, Style, Text,
+    TextAlign, TextNode, TextOrInline,
 };
 use crate::mathml_renderer::{MathMLParser, mathml_to_ascii};
+use crate::parsing::chapter_contents::chapter_contents;
+use crate::parsing::content_filter::ContentFilters;
+use crate::parsing::conversion_diagnostics::{ConversionDiagnostics, has_text};
+use crate::parsing::css::{Declarations, StyleSheet, embedded_css};
+use crate::parsing::language::{chapter_language, is_right_to_left};
+use crate::parsing::substitutions::Substitutions;
+use crate::parsing::text_cleanup::TextCleanup;
 use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{NodeData, RcDom};
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
+use std::sync::Arc;
+
+/// Languages whose fiction sets dialog as paragraphs opening with a dash
+const DASH_DIALOG_LANGUAGES: &[&str] = &[
+    "ru", "uk", "be", "bg", "sr", "mk", "hr", "bs", "sl", "pl", "cs", "sk", "hu", "ro", "es", "ca",
+    "gl", "pt", "it", "fr", "el", "he", "lt", "lv", "et", "fi", "sv",
+];
+
+/// Whether consecutive dash-led paragraphs are joined into one dialog block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DialogGrouping {
+    /// Group in books whose language sets dialog with dashes, or whose language is unknown
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl DialogGrouping {
+    /// Whether dialog is grouped in a book written in `language` (a BCP 47 tag like `ru-RU`)
+    pub fn applies_to(self, language: Option<&str>) -> bool {
+        match self {
+            DialogGrouping::Always => true,
+            DialogGrouping::Never => false,
+            DialogGrouping::Auto => language.is_none_or(|language| {
+                let primary = language.split(['-', '_']).next().unwrap_or_default();
+                DASH_DIALOG_LANGUAGES.contains(&primary.to_ascii_lowercase().as_str())
+            }),
+        }
+    }
+}
+
+/// Blocks that may sit between two parts of a split numbered list
+const MAX_LIST_INTERRUPTION: usize = 3;
 
 /// Strategy for content collection mode
 #[derive(Debug, Clone)]
@@ -113,11 +156,114 @@ struct ProcessingContext {
 /// let markdown_doc = converter.convert(html_content);
 /// # }
 /// ```
-pub struct HtmlToMarkdownConverter {}
+pub struct HtmlToMarkdownConverter {
+    /// Collected per `convert` call when created with `with_diagnostics`
+    diagnostics: Option<ConversionDiagnostics>,
+    /// Elements removed before conversion
+    filters: ContentFilters,
+    /// The book's stylesheet; CSS is only honored when one is set
+    styles: Option<Arc<StyleSheet>>,
+    /// `styles` plus the chapter's own `<style>` elements, for the `convert` call under way
+    chapter_styles: Option<StyleSheet>,
+    /// Set runs of dash-led paragraphs line under line as one dialog, rather than apart
+    group_dialog: bool,
+    /// Repairs for scanned text, applied to every text node
+    cleanup: TextCleanup,
+    /// The book's find/replace rules, run over every text node after `cleanup`
+    substitutions: Substitutions,
+    /// Keep the indentation and spacing of lines set with `<br/>`, for scripts and lyrics
+    preserve_line_breaks: bool,
+    /// List the sections of chapters that have many under their title
+    chapter_contents: bool,
+}
 
 impl HtmlToMarkdownConverter {
     pub fn new() -> Self {
-        HtmlToMarkdownConverter {}
+        HtmlToMarkdownConverter {
+            diagnostics: None,
+            filters: ContentFilters::default(),
+            styles: None,
+            chapter_styles: None,
+            group_dialog: true,
+            cleanup: TextCleanup::default(),
+            substitutions: Substitutions::default(),
+            preserve_line_breaks: false,
+            chapter_contents: false,
+        }
+    }
+
+    /// A converter that also notes what each converted chapter lost: unsupported tags,
+    /// ignored attributes and elements that produced no output
+    pub fn with_diagnostics() -> Self {
+        HtmlToMarkdownConverter {
+            diagnostics: Some(ConversionDiagnostics::default()),
+            filters: ContentFilters::default(),
+            styles: None,
+            chapter_styles: None,
+            group_dialog: true,
+            cleanup: TextCleanup::default(),
+            substitutions: Substitutions::default(),
+            preserve_line_breaks: false,
+            chapter_contents: false,
+        }
+    }
+
+    /// Start noting what converted chapters lose, as `with_diagnostics` does, on a
+    /// converter that's already set up
+    pub fn recording_diagnostics(mut self) -> Self {
+        self.diagnostics = Some(ConversionDiagnostics::default());
+        self
+    }
+
+    /// Strip the elements `filters` exclude before converting
+    pub fn with_filters(mut self, filters: ContentFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Honor the alignment, indentation and font style rules of `styles` and of the
+    /// chapter's `<style>` elements
+    pub fn with_styles(mut self, styles: Option<Arc<StyleSheet>>) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Turn the joining of dash-led dialog paragraphs on or off (on by default)
+    pub fn with_dialog_grouping(mut self, enabled: bool) -> Self {
+        self.group_dialog = enabled;
+        self
+    }
+
+    /// Dehyphenate, spell out ligatures and normalize spaces as `cleanup` asks
+    pub fn with_cleanup(mut self, cleanup: TextCleanup) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+
+    /// Run the book's find/replace rules over its text
+    pub fn with_substitutions(mut self, substitutions: Substitutions) -> Self {
+        self.substitutions = substitutions;
+        self
+    }
+
+    /// Lay out lines broken with `<br/>` as the book set them: only the whitespace
+    /// of the HTML source is collapsed, so indentation and gaps made of no-break, em and
+    /// other typographic spaces survive, and no stray space starts a line
+    pub fn with_line_breaks_preserved(mut self, enabled: bool) -> Self {
+        self.preserve_line_breaks = enabled;
+        self
+    }
+
+    /// Put a list of links to a chapter's sections under its title, when it has enough
+    /// of them to be worth it
+    pub fn with_chapter_contents(mut self, enabled: bool) -> Self {
+        self.chapter_contents = enabled;
+        self
+    }
+
+    /// Diagnostics for the last `convert` call, if enabled
+    pub fn diagnostics(&self) -> Option<&ConversionDiagnostics> {
+        self.diagnostics.as_ref()
     }
 
     fn collect_content(
@@ -204,6 +350,10 @@ impl HtmlToMarkdownConverter {
         current_text: &Text,
         current_style: Option<Style>,
     ) -> Option<String> {
+        if self.preserve_line_breaks {
+            return self.normalize_source_whitespace(content, current_text, current_style);
+        }
+
         // Special case: if content is only whitespace but we have existing text,
         // preserve a single space to maintain separation between elements
         if content.trim().is_empty() {
@@ -239,16 +389,84 @@ impl HtmlToMarkdownConverter {
         Some(adjusted_content)
     }
 
+    /// Like `normalize_text_content`, but only the ASCII whitespace of the HTML source is
+    /// collapsed, and none is kept at the start of a line
+    fn normalize_source_whitespace(
+        &self,
+        content: &str,
+        current_text: &Text,
+        current_style: Option<Style>,
+    ) -> Option<String> {
+        let line_start = matches!(
+            current_text.iter().next_back(),
+            None | Some(TextOrInline::Inline(Inline::LineBreak))
+        );
+        let words: Vec<&str> = content
+            .split(|c: char| c.is_ascii_whitespace())
+            .filter(|word| !word.is_empty())
+            .collect();
+        let source_space = |c: Option<char>| c.is_some_and(|c| c.is_ascii_whitespace());
+        if words.is_empty() {
+            return (!line_start && !content.is_empty()).then(|| " ".to_string());
+        }
+
+        let mut normalized = words.join(" ");
+        if !line_start && source_space(content.chars().next()) {
+            normalized.insert(0, ' ');
+        }
+        if source_space(content.chars().last()) {
+            normalized.push(' ');
+        }
+        Some(if current_style == Some(Style::Code) {
+            self.add_code_spacing(&normalized)
+        } else {
+            normalized
+        })
+    }
+
     pub fn convert(&mut self, html: &str) -> Document {
         let dom = parse_document(RcDom::default(), Default::default())
             .from_utf8()
             .read_from(&mut html.as_bytes())
             .unwrap();
+        self.filters.apply(&dom.document);
+        self.chapter_styles = self.styles.as_ref().map(|styles| {
+            let mut sheet = StyleSheet::clone(styles);
+            sheet.extend(&embedded_css(&dom.document));
+            sheet
+        });
+
+        if let Some(diagnostics) = self.diagnostics.as_mut() {
+            *diagnostics = ConversionDiagnostics::default();
+            diagnostics.scan_markup(&dom.document);
+        }
 
         let mut document = Document::new();
         self.visit_node(&dom.document, &mut document);
 
         self.group_dialog_paragraphs(&mut document);
+        Self::link_list_continuations(&mut document);
+        if self.chapter_contents {
+            document.contents = chapter_contents(&document);
+        }
+
+        document.language = chapter_language(&dom.document, &document.plain_text());
+        // Terminals rarely lay out right-to-left text, but its prose can at least sit on
+        // the right. Code and tables keep their own layout.
+        if document.language.as_deref().is_some_and(is_right_to_left) {
+            for node in &mut document.blocks {
+                if matches!(
+                    node.block,
+                    Block::Heading { .. }
+                        | Block::Paragraph { .. }
+                        | Block::Quote { .. }
+                        | Block::List { .. }
+                        | Block::DefinitionList { .. }
+                ) {
+                    node.layout.align.get_or_insert(TextAlign::Right);
+                }
+            }
+        }
 
         document
     }
@@ -267,9 +485,14 @@ impl HtmlToMarkdownConverter {
             } => {
                 self.visit_element(name, attrs, node, document);
             }
-            NodeData::Text { contents: _ } => {
+            NodeData::Text { ref contents } => {
                 // For now, we'll handle text within element contexts
                 // TODO: Implement text handling
+                if let Some(diagnostics) = self.diagnostics.as_mut() {
+                    if !contents.borrow().trim().is_empty() {
+                        diagnostics.dropped_text += 1;
+                    }
+                }
             }
             _ => {
                 // Handle comments, doctypes, etc. by visiting children
@@ -294,6 +517,43 @@ impl HtmlToMarkdownConverter {
             return;
         }
 
+        let blocks_before = document.blocks.len();
+        self.visit_element_by_tag(tag_name, attrs, node, document);
+        if let Some(style) = self.element_style(node) {
+            Self::apply_block_style(&mut document.blocks[blocks_before..], &style);
+        }
+
+        if let Some(diagnostics) = self.diagnostics.as_mut() {
+            let produces_block = matches!(
+                tag_name,
+                "h1" | "h2"
+                    | "h3"
+                    | "h4"
+                    | "h5"
+                    | "h6"
+                    | "p"
+                    | "pre"
+                    | "math"
+                    | "ul"
+                    | "ol"
+                    | "table"
+                    | "dl"
+                    | "blockquote"
+                    | "details"
+            );
+            if produces_block && document.blocks.len() == blocks_before && has_text(node) {
+                diagnostics.record_empty_output(tag_name);
+            }
+        }
+    }
+
+    fn visit_element_by_tag(
+        &mut self,
+        tag_name: &str,
+        attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>,
+        node: &Rc<markup5ever_rcdom::Node>,
+        document: &mut Document,
+    ) {
         match tag_name {
             "html" | "body" => {
                 for child in node.children.borrow().iter() {
@@ -394,6 +654,9 @@ impl HtmlToMarkdownConverter {
             "blockquote" => {
                 self.handle_blockquote(attrs, node, document);
             }
+            "details" => {
+                self.handle_details(attrs, node, document);
+            }
             "hr" => {
                 document.blocks.push(Node::new(Block::ThematicBreak, 0..0));
             }
@@ -407,6 +670,33 @@ impl HtmlToMarkdownConverter {
         }
     }
 
+    /// The CSS properties of an element, when CSS is honored
+    fn element_style(&self, node: &Rc<markup5ever_rcdom::Node>) -> Option<Declarations> {
+        self.chapter_styles
+            .as_ref()
+            .map(|styles| styles.style_for(node))
+    }
+
+    /// Lay out the paragraphs an element produced as its CSS asks. Inner elements were
+    /// styled first, so their alignment wins while indentation adds up.
+    fn apply_block_style(blocks: &mut [Node], style: &Declarations) {
+        for node in blocks {
+            let Block::Paragraph { content } = &mut node.block else {
+                continue;
+            };
+            if node.layout.align.is_none() {
+                node.layout.align = style.text_align;
+            }
+            node.layout.indent += style.indent.unwrap_or(0);
+            if style.italic == Some(true) {
+                content.apply_style_to_plain(Style::Emphasis);
+            }
+            if style.bold == Some(true) {
+                content.apply_style_to_plain(Style::Strong);
+            }
+        }
+    }
+
     fn handle_heading(
         &mut self,
         tag_name: &str,
@@ -1128,15 +1418,15 @@ impl HtmlToMarkdownConverter {
         &self,
         tag_name: &str,
         attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>,
-    ) -> crate::markdown::ListKind {
+    ) -> ListKind {
         if tag_name == "ol" {
             let start = self
                 .get_attr_value(attrs, "start")
                 .and_then(|s| s.parse::<u32>().ok())
                 .unwrap_or(1);
-            crate::markdown::ListKind::Ordered { start }
+            ListKind::Ordered { start }
         } else {
-            crate::markdown::ListKind::Unordered
+            ListKind::Unordered
         }
     }
 
@@ -1223,6 +1513,45 @@ impl HtmlToMarkdownConverter {
         }
     }
 
+    fn handle_details(
+        &mut self,
+        attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>,
+        node: &Rc<markup5ever_rcdom::Node>,
+        document: &mut Document,
+    ) {
+        let mut summary = Text::default();
+        let mut inner = Document::new();
+
+        for child in node.children.borrow().iter() {
+            match &child.data {
+                NodeData::Element { name, .. }
+                    if name.local.as_ref() == "summary" && summary.is_empty() =>
+                {
+                    summary = self.extract_formatted_content(child);
+                }
+                _ => self.visit_node(child, &mut inner),
+            }
+        }
+
+        // Without a summary browsers show "Details"
+        if summary.is_empty() {
+            summary.push(TextOrInline::Text(TextNode {
+                content: "Details".to_string(),
+                style: None,
+            }));
+        }
+
+        let details_block = Block::Details {
+            summary,
+            content: inner.blocks,
+            open: self.get_attr_value(attrs, "open").is_some(),
+        };
+        let id = self.get_attr_value(attrs, "id");
+        document
+            .blocks
+            .push(Node::new_with_id(details_block, 0..0, id));
+    }
+
     fn handle_epub_block(
         &mut self,
         element_name: &str,
@@ -1376,7 +1705,10 @@ impl HtmlToMarkdownConverter {
     ) {
         match &node.data {
             NodeData::Text { contents } => {
-                let content = contents.borrow().to_string();
+                let content = self
+                    .substitutions
+                    .apply(&self.cleanup.apply(&contents.borrow()))
+                    .into_owned();
                 if let Some(normalized) =
                     self.normalize_text_content(&content, text, context.current_style.clone())
                 {
@@ -1430,7 +1762,10 @@ impl HtmlToMarkdownConverter {
     ) {
         match &node.data {
             NodeData::Text { contents } => {
-                let content = contents.borrow().to_string();
+                let content = self
+                    .substitutions
+                    .apply(&self.cleanup.apply(&contents.borrow()))
+                    .into_owned();
                 if let Some(normalized) = self.normalize_text_content(
                     &content,
                     current_text,
@@ -1491,7 +1826,7 @@ impl HtmlToMarkdownConverter {
         text: &mut Text,
         context: ProcessingContext,
     ) {
-        let new_context = self.get_element_context(tag_name, context.clone());
+        let new_context = self.get_element_context(node, tag_name, context.clone());
 
         match tag_name {
             "a" => {
@@ -1582,7 +1917,7 @@ impl HtmlToMarkdownConverter {
         current_text: &mut Text,
         context: ProcessingContext,
     ) {
-        let new_context = self.get_element_context(tag_name, context.clone());
+        let new_context = self.get_element_context(node, tag_name, context.clone());
 
         match tag_name {
             "a" => {
@@ -1902,6 +2237,7 @@ impl HtmlToMarkdownConverter {
 
     fn get_element_context(
         &self,
+        node: &Rc<markup5ever_rcdom::Node>,
         tag_name: &str,
         mut context: ProcessingContext,
     ) -> ProcessingContext {
@@ -1910,7 +2246,15 @@ impl HtmlToMarkdownConverter {
             "em" | "i" => Some(Style::Emphasis),
             "code" => Some(Style::Code),
             "del" | "s" | "strike" => Some(Style::Strikethrough),
-            _ => context.current_style,
+            _ => match self.element_style(node) {
+                Some(Declarations {
+                    italic: Some(true), ..
+                }) => Some(Style::Emphasis),
+                Some(Declarations {
+                    bold: Some(true), ..
+                }) => Some(Style::Strong),
+                _ => context.current_style,
+            },
         };
         context
     }
@@ -2014,7 +2358,9 @@ impl HtmlToMarkdownConverter {
         }
     }
 
-    /// Groups consecutive dialog paragraphs into single paragraphs with line breaks.
+    /// Groups consecutive dialog paragraphs into single paragraphs with line breaks. With
+    /// grouping off the lines are still one block, set apart by blank lines: block indices,
+    /// which comments and bookmarks are kept by, don't change when it's toggled.
     fn group_dialog_paragraphs(&self, document: &mut Document) {
         let mut i = 0;
 
@@ -2052,6 +2398,69 @@ impl HtmlToMarkdownConverter {
         }
     }
 
+    /// Publishers often split one numbered list into `<ol start=N>` parts with paragraphs
+    /// in between. Parts that continue the previous numbering are linked rather than merged,
+    /// so block indices (and the comments anchored to them) stay put: their numbers share
+    /// one width, and the blocks between them are indented under the item they belong to.
+    fn link_list_continuations(document: &mut Document) {
+        let mut i = 0;
+        while i < document.blocks.len() {
+            let Some(mut next_number) = Self::next_list_number(&document.blocks[i]) else {
+                i += 1;
+                continue;
+            };
+            let mut parts = vec![i];
+            let mut interruption = Vec::new();
+            let mut linked = Vec::new();
+
+            for j in i + 1..document.blocks.len() {
+                match &document.blocks[j].block {
+                    Block::List {
+                        kind: ListKind::Ordered { start },
+                        ..
+                    } if *start == next_number => {
+                        parts.push(j);
+                        linked.append(&mut interruption);
+                        next_number = Self::next_list_number(&document.blocks[j]).unwrap_or(0);
+                    }
+                    Block::Paragraph { .. }
+                    | Block::CodeBlock { .. }
+                    | Block::Quote { .. }
+                    | Block::Table { .. }
+                    | Block::List {
+                        kind: ListKind::Unordered,
+                        ..
+                    } if interruption.len() < MAX_LIST_INTERRUPTION => interruption.push(j),
+                    _ => break,
+                }
+            }
+
+            if parts.len() > 1 {
+                let number_width = (next_number - 1).to_string().len();
+                for &part in &parts {
+                    document.blocks[part].layout.number_width = number_width;
+                }
+                // Under the item text, past the "NN. " marker
+                let marker_levels = (number_width + 2).div_ceil(2);
+                for &block in &linked {
+                    document.blocks[block].layout.indent += marker_levels;
+                }
+            }
+            i = parts[parts.len() - 1] + 1;
+        }
+    }
+
+    /// The number after the last item of an ordered list
+    fn next_list_number(node: &Node) -> Option<u32> {
+        match &node.block {
+            Block::List {
+                kind: ListKind::Ordered { start },
+                items,
+            } => Some(start + items.len() as u32),
+            _ => None,
+        }
+    }
+
     /// Check if a Text content represents dialog (contains dialog lines)
     fn is_dialog_content(content: &Text) -> bool {
         for item in content.clone().into_iter() {
@@ -2092,6 +2501,9 @@ impl HtmlToMarkdownConverter {
 
             if i < dialog_group.len() - 1 {
                 merged.push_inline(Inline::LineBreak);
+                if !self.group_dialog {
+                    merged.push_inline(Inline::LineBreak);
+                }
             }
         }
 
@@ -2475,6 +2887,65 @@ The protocol operates on multiple layers:
         );
     }
 
+    #[test]
+    fn test_dialog_grouping_can_be_turned_off() {
+        let html = "<p>He paused.</p><p>— an aside, not dialog —</p><p>— and another</p>";
+        let grouped = HtmlToMarkdownConverter::new().convert(html);
+        let separate = HtmlToMarkdownConverter::new()
+            .with_dialog_grouping(false)
+            .convert(html);
+        // Either way the dialog is one block, so comments on it stay put when toggling
+        assert_eq!(grouped.blocks.len(), 2);
+        assert_eq!(separate.blocks.len(), 2);
+        let renderer = crate::parsing::markdown_renderer::MarkdownRenderer::new();
+        assert!(
+            renderer
+                .render(&grouped)
+                .contains("— an aside, not dialog —  \n— and another")
+        );
+        assert!(
+            renderer
+                .render(&separate)
+                .contains("— an aside, not dialog —  \n  \n— and another")
+        );
+
+        assert!(DialogGrouping::Auto.applies_to(Some("ru-RU")));
+        assert!(DialogGrouping::Auto.applies_to(None));
+        assert!(!DialogGrouping::Auto.applies_to(Some("en")));
+        assert!(DialogGrouping::Always.applies_to(Some("en")));
+        assert!(!DialogGrouping::Never.applies_to(Some("ru")));
+    }
+
+    #[test]
+    fn test_preserved_line_breaks_keep_indentation() {
+        let html = "<p>INT. KITCHEN<br/>\n    JOHN<br/>\n&nbsp;&nbsp;Where?</p>";
+        let lines = |doc: &Document| -> Vec<String> {
+            let Block::Paragraph { content } = &doc.blocks[0].block else {
+                panic!("Expected paragraph block");
+            };
+            let mut lines = vec![String::new()];
+            for item in content.clone() {
+                match item {
+                    TextOrInline::Text(node) => lines.last_mut().unwrap().push_str(&node.content),
+                    TextOrInline::Inline(Inline::LineBreak) => lines.push(String::new()),
+                    _ => {}
+                }
+            }
+            lines
+        };
+
+        let reflowed = HtmlToMarkdownConverter::new().convert(html);
+        assert_eq!(lines(&reflowed), vec!["INT. KITCHEN", " JOHN", " Where?"]);
+
+        let preserved = HtmlToMarkdownConverter::new()
+            .with_line_breaks_preserved(true)
+            .convert(html);
+        assert_eq!(
+            lines(&preserved),
+            vec!["INT. KITCHEN", "JOHN", "\u{a0}\u{a0}Where?"]
+        );
+    }
+
     #[test]
     fn test_subscript_unicode_conversion() {
         let mut converter = HtmlToMarkdownConverter::new();
@@ -2816,6 +3287,43 @@ The protocol operates on multiple layers:
         assert_eq!(rendered, expected);
     }
 
+    #[test]
+    fn test_details_becomes_collapsible_block() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<details id="spoiler"><summary>Who did it?</summary>
+    <p>The butler.</p>
+    <p>With the candlestick.</p>
+</details>
+<details open><p>No summary here.</p></details>"#;
+
+        let doc = converter.convert(html);
+
+        assert_eq!(doc.blocks.len(), 2, "Expected two details blocks");
+        assert_eq!(doc.blocks[0].id.as_deref(), Some("spoiler"));
+        match &doc.blocks[0].block {
+            Block::Details {
+                summary,
+                content,
+                open,
+            } => {
+                let renderer = crate::parsing::markdown_renderer::MarkdownRenderer::new();
+                assert_eq!(renderer.render_text(summary).trim(), "Who did it?");
+                assert_eq!(content.len(), 2, "Both paragraphs belong to the details");
+                assert!(!open);
+            }
+            other => panic!("Expected details block, found {other:?}"),
+        }
+        match &doc.blocks[1].block {
+            Block::Details { summary, open, .. } => {
+                let renderer = crate::parsing::markdown_renderer::MarkdownRenderer::new();
+                assert_eq!(renderer.render_text(summary).trim(), "Details");
+                assert!(open);
+            }
+            other => panic!("Expected details block, found {other:?}"),
+        }
+    }
+
     #[test]
     fn test_code_block_inside_epub_sidebar() {
         let mut converter = HtmlToMarkdownConverter::new();
@@ -3996,4 +4504,84 @@ The protocol operates on multiple layers:
             panic!("oops 2");
         }
     }
+
+    #[test]
+    fn test_diagnostics_report_lost_markup() {
+        let html = r#"<html xmlns="http://www.w3.org/1999/xhtml"><body>
+            <div>Loose text</div>
+            <p class="intro" style="color:red">Hello <span>there</span></p>
+            <figure><img src="a.png" alt="A"/><figcaption>Caption</figcaption></figure>
+            <table><caption>Totals</caption><tr><td colspan="2">x</td></tr></table>
+            <p><math><mi mathvariant="bold">x</mi></math></p>
+        </body></html>"#;
+
+        let mut converter = HtmlToMarkdownConverter::with_diagnostics();
+        converter.convert(html);
+        let diagnostics = converter.diagnostics().unwrap();
+
+        let tags: Vec<&str> = diagnostics
+            .unsupported_tags
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(tags, ["caption", "figcaption", "figure"]);
+        let attributes: Vec<&str> = diagnostics
+            .dropped_attributes
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(attributes, ["class", "colspan", "style"]);
+        assert_eq!(diagnostics.dropped_text, 2);
+        assert!(diagnostics.empty_output.is_empty());
+        assert!(
+            diagnostics.summary_lines()[1].starts_with("Ignored attributes: class ×1, colspan ×1")
+        );
+
+        converter.convert("<p>Clean</p>");
+        assert!(converter.diagnostics().unwrap().is_empty());
+        assert!(HtmlToMarkdownConverter::new().diagnostics().is_none());
+    }
+
+    #[test]
+    fn test_book_health_sums_chapter_warnings() {
+        use crate::parsing::conversion_diagnostics::BookHealth;
+
+        let mut converter = HtmlToMarkdownConverter::with_diagnostics();
+        let mut health = BookHealth::default();
+        converter.convert(r#"<p class="x">Ignored attributes aren't warnings</p>"#);
+        health.add_chapter(converter.diagnostics().unwrap());
+        assert_eq!(health.summary(), "1 chapter parsed, no problems found");
+
+        converter.convert(r#"<div>Loose text</div><p>Kept</p>"#);
+        health.add_chapter(converter.diagnostics().unwrap());
+        assert_eq!(health.summary(), "2 chapters parsed, 1 warning");
+
+        health.broken_links = 2;
+        health.unsupported_images = 1;
+        assert_eq!(
+            health.summary(),
+            "2 chapters parsed, 1 warning, 2 broken links, 1 image unsupported"
+        );
+    }
+
+    #[test]
+    fn test_document_links_are_found_in_nested_blocks() {
+        let html = r##"<p>See <a href="ch02.html">two</a>.</p>
+            <ul><li><p><a href="ch03.html#a">three</a></p></li></ul>
+            <table><tr><td><a href="#local">here</a></td></tr></table>
+            <blockquote><p><a href="https://example.com">web</a></p></blockquote>"##;
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let urls: Vec<&str> = doc
+            .links()
+            .into_iter()
+            .filter_map(|link| match link {
+                Inline::Link { url, .. } => Some(url.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            urls,
+            ["ch02.html", "ch03.html#a", "#local", "https://example.com"]
+        );
+    }
 }
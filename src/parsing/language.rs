@@ -0,0 +1,181 @@
+use markup5ever_rcdom::{Node, NodeData};
+use std::rc::Rc;
+use whatlang::Lang;
+
+const RIGHT_TO_LEFT: &[&str] = &["ar", "he", "iw", "fa", "ur", "yi", "ps", "dv", "syr"];
+
+/// The language a chapter is written in, as a BCP 47 tag like `en` or `pt-BR`: what its
+/// text looks like when that's clear, or else what its markup declares
+pub fn chapter_language(document: &Rc<Node>, text: &str) -> Option<String> {
+    detect_language(text)
+        .map(str::to_string)
+        .or_else(|| declared_language(document))
+}
+
+/// Whether text in `language` runs right to left
+pub fn is_right_to_left(language: &str) -> bool {
+    let primary = language.split(['-', '_']).next().unwrap_or_default();
+    RIGHT_TO_LEFT.contains(&primary.to_ascii_lowercase().as_str())
+}
+
+/// The `lang` or `xml:lang` of the chapter's `<html>` or `<body>`
+fn declared_language(node: &Rc<Node>) -> Option<String> {
+    for child in node.children.borrow().iter() {
+        if let NodeData::Element { name, attrs, .. } = &child.data {
+            if !matches!(name.local.as_ref(), "html" | "body") {
+                continue;
+            }
+            let lang = attrs
+                .borrow()
+                .iter()
+                .find(|attr| matches!(attr.name.local.as_ref(), "lang" | "xml:lang"))
+                .map(|attr| attr.value.trim().to_string())
+                .filter(|lang| !lang.is_empty());
+            // The body can override the html element
+            if let Some(lang) = declared_language(child).or(lang) {
+                return Some(lang);
+            }
+        }
+    }
+    None
+}
+
+/// Guess the language of a chapter's text. `None` when there's too little text to go on
+/// or no clear winner.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    whatlang::detect(text)
+        .filter(|info| info.is_reliable())
+        .map(|info| language_tag(info.lang()))
+}
+
+/// The BCP 47 tag of a language whatlang tells apart
+fn language_tag(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Afr => "af",
+        Lang::Aka => "ak",
+        Lang::Amh => "am",
+        Lang::Ara => "ar",
+        Lang::Aze => "az",
+        Lang::Bel => "be",
+        Lang::Ben => "bn",
+        Lang::Bul => "bg",
+        Lang::Cat => "ca",
+        Lang::Ces => "cs",
+        Lang::Cmn => "zh",
+        Lang::Dan => "da",
+        Lang::Deu => "de",
+        Lang::Ell => "el",
+        Lang::Eng => "en",
+        Lang::Epo => "eo",
+        Lang::Est => "et",
+        Lang::Fin => "fi",
+        Lang::Fra => "fr",
+        Lang::Guj => "gu",
+        Lang::Heb => "he",
+        Lang::Hin => "hi",
+        Lang::Hrv => "hr",
+        Lang::Hun => "hu",
+        Lang::Hye => "hy",
+        Lang::Ind => "id",
+        Lang::Ita => "it",
+        Lang::Jav => "jv",
+        Lang::Jpn => "ja",
+        Lang::Kan => "kn",
+        Lang::Kat => "ka",
+        Lang::Khm => "km",
+        Lang::Kor => "ko",
+        Lang::Lat => "la",
+        Lang::Lav => "lv",
+        Lang::Lit => "lt",
+        Lang::Mal => "ml",
+        Lang::Mar => "mr",
+        Lang::Mkd => "mk",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Nld => "nl",
+        Lang::Nob => "nb",
+        Lang::Ori => "or",
+        Lang::Pan => "pa",
+        Lang::Pes => "fa",
+        Lang::Pol => "pl",
+        Lang::Por => "pt",
+        Lang::Ron => "ro",
+        Lang::Rus => "ru",
+        Lang::Sin => "si",
+        Lang::Slk => "sk",
+        Lang::Slv => "sl",
+        Lang::Sna => "sn",
+        Lang::Spa => "es",
+        Lang::Srp => "sr",
+        Lang::Swe => "sv",
+        Lang::Tam => "ta",
+        Lang::Tel => "te",
+        Lang::Tgl => "tl",
+        Lang::Tha => "th",
+        Lang::Tuk => "tk",
+        Lang::Tur => "tr",
+        Lang::Ukr => "uk",
+        Lang::Urd => "ur",
+        Lang::Uzb => "uz",
+        Lang::Vie => "vi",
+        Lang::Yid => "yi",
+        Lang::Zul => "zu",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_chapter_language_is_detected_from_text_or_markup() {
+        let convert = |html: &str| HtmlToMarkdownConverter::new().convert(html).language;
+        let english = "<p>It was the best of times, it was the worst of times, it was the age \
+                       of wisdom, it was the age of foolishness, and the spring of hope.</p>";
+        let french = "<p>Longtemps, je me suis couché de bonne heure. Parfois, à peine ma \
+                      bougie éteinte, mes yeux se fermaient si vite que je n'avais pas le temps \
+                      de me dire : « Je m'endors. » Et, une demi-heure après, la pensée qu'il \
+                      était temps de chercher le sommeil m'éveillait.</p>";
+        assert_eq!(convert(english).as_deref(), Some("en"));
+        assert_eq!(convert(french).as_deref(), Some("fr"));
+        assert_eq!(
+            convert(
+                "<p>Все счастливые семьи похожи друг на друга, каждая несчастливая семья \
+                 несчастлива по-своему. Все смешалось в доме Облонских. Жена узнала, что \
+                 муж был в связи с бывшею в их доме француженкою-гувернанткой, и объявила \
+                 мужу, что не может жить с ним в одном доме.</p>"
+            )
+            .as_deref(),
+            Some("ru")
+        );
+        assert_eq!(
+            convert("<p>吾輩は猫である。名前はまだ無い。</p>").as_deref(),
+            Some("ja")
+        );
+        assert_eq!(
+            convert(r#"<html lang="de"><body><p>Kurz.</p></body></html>"#).as_deref(),
+            Some("de")
+        );
+        assert_eq!(convert("<p>Short.</p>"), None);
+    }
+
+    #[test]
+    fn test_right_to_left_chapters_are_aligned_right() {
+        use crate::markdown::TextAlign;
+        let doc = HtmlToMarkdownConverter::new().convert(
+            "<h1>פרק ראשון</h1><p>בראשית ברא אלוהים את השמים ואת הארץ. והארץ הייתה תוהו ובוהו, \
+             וחושך על פני תהום.</p><p>ויאמר אלוהים יהי אור, ויהי אור.</p>\
+             <pre><code>let x = 1;</code></pre>\
+             <table><tr><td>א</td><td>ב</td></tr></table>",
+        );
+        assert_eq!(doc.language.as_deref(), Some("he"));
+        let aligned: Vec<bool> = doc
+            .blocks
+            .iter()
+            .map(|node| node.layout.align == Some(TextAlign::Right))
+            .collect();
+        assert_eq!(aligned, [true, true, true, false, false]);
+        assert!(!is_right_to_left("en-GB"));
+    }
+}
@@ -95,6 +95,26 @@ impl MarkdownRenderer {
             } => {
                 self.render_epub_block(epub_type, element_name, content, output);
             }
+            Block::Details {
+                summary,
+                content,
+                open,
+            } => {
+                // Markdown has no syntax for this, so keep the HTML element
+                output.push_str(if *open {
+                    "<details open>\n"
+                } else {
+                    "<details>\n"
+                });
+                output.push_str(&format!(
+                    "<summary>{}</summary>\n\n",
+                    self.render_text(summary)
+                ));
+                for node in content {
+                    self.render_node(node, output);
+                }
+                output.push_str("</details>\n\n");
+            }
             Block::ThematicBreak => {
                 output.push_str("---\n\n");
             }
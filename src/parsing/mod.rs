@@ -1,4 +1,11 @@
+pub mod chapter_contents;
+pub mod content_filter;
+pub mod conversion_diagnostics;
+pub mod css;
 pub mod html_to_markdown;
+pub mod language;
 pub mod markdown_renderer;
+pub mod substitutions;
+pub mod text_cleanup;
 pub mod text_generator;
 pub mod toc_parser;
@@ -0,0 +1,100 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A find/replace rule from the `substitutions` preference. `find` is a regular
+/// expression; `replace` may refer to its groups as `$1` or `${name}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubstitutionRule {
+    pub find: String,
+    #[serde(default)]
+    pub replace: String,
+}
+
+/// The compiled rules of one book, applied to the text of its chapters as they are
+/// converted, e.g. to fix a recurring OCR typo or drop a footer every chapter repeats
+#[derive(Debug, Clone, Default)]
+pub struct Substitutions {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Substitutions {
+    /// The rules configured for the book at `book_path`, keyed by its file name or full
+    /// path. Returns the rules that compiled and an error for each that didn't.
+    pub fn for_book(
+        config: &BTreeMap<String, Vec<SubstitutionRule>>,
+        book_path: &str,
+    ) -> (Self, Vec<String>) {
+        let file_name = Path::new(book_path)
+            .file_name()
+            .map(|name| name.to_string_lossy());
+        let mut rules = Vec::new();
+        let mut errors = Vec::new();
+        let configured = config
+            .iter()
+            .filter(|(key, _)| *key == book_path || Some(key.as_str()) == file_name.as_deref())
+            .flat_map(|(_, rules)| rules);
+        for rule in configured {
+            match Regex::new(&rule.find) {
+                Ok(find) => rules.push((find, rule.replace.clone())),
+                Err(e) => errors.push(format!("Bad substitution {:?}: {e}", rule.find)),
+            }
+        }
+        (Substitutions { rules }, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Run the rules, in order, over a text node of the chapter's HTML
+    pub(crate) fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (find, replace) in &self.rules {
+            if let Cow::Owned(replaced) = find.replace_all(&text, replace.as_str()) {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_substitutions_apply_to_the_configured_book_only() {
+        let rule = |find: &str, replace: &str| SubstitutionRule {
+            find: find.to_string(),
+            replace: replace.to_string(),
+        };
+        let config = BTreeMap::from([
+            (
+                "scan.epub".to_string(),
+                vec![
+                    rule(r"\btbe\b", "the"),
+                    rule(r"(?i)^\s*Downloaded from .*$", ""),
+                    rule(r"(\d+) ?l\b", "${1}1"),
+                ],
+            ),
+            ("/books/other.epub".to_string(), vec![rule("(", "")]),
+        ]);
+
+        let (substitutions, errors) = Substitutions::for_book(&config, "/home/me/scan.epub");
+        assert!(errors.is_empty());
+        let doc = HtmlToMarkdownConverter::new()
+            .with_substitutions(substitutions)
+            .convert(
+                "<p>In tbe year 184l, tbe whale.</p><p>Downloaded from example.com</p><p>End.</p>",
+            );
+        assert_eq!(doc.plain_text(), "In the year 1841, the whale.\nEnd.");
+
+        let (other, errors) = Substitutions::for_book(&config, "/books/other.epub");
+        assert!(other.is_empty());
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -0,0 +1,107 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// A word broken at the end of a source line, like `hyphen-\nation`
+static LINE_END_HYPHEN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\p{L})-[ \t]*\r?\n\s*(\p{Ll})").unwrap());
+
+/// Typographic ligatures and what they stand for
+const LIGATURES: &[(char, &str)] = &[
+    ('ﬀ', "ff"),
+    ('ﬁ', "fi"),
+    ('ﬂ', "fl"),
+    ('ﬃ', "ffi"),
+    ('ﬄ', "ffl"),
+    ('ﬅ', "st"),
+    ('ﬆ', "st"),
+];
+
+/// Cleanup for books digitized from print, applied to the text of chapters as they are
+/// converted. Everything is off by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextCleanup {
+    /// Drop soft hyphens and join words hyphenated at the end of a source line
+    #[serde(default)]
+    pub dehyphenate: bool,
+    /// Spell out ligature characters (`ﬁ` becomes `fi`), so search and copy find them
+    #[serde(default)]
+    pub ligatures: bool,
+    /// Turn no-break spaces into plain ones and drop invisible word joiners
+    #[serde(default)]
+    pub nbsp: bool,
+}
+
+impl TextCleanup {
+    pub fn is_empty(&self) -> bool {
+        !(self.dehyphenate || self.ligatures || self.nbsp)
+    }
+
+    /// Clean up a text node of the chapter's HTML
+    pub(crate) fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        if self.dehyphenate {
+            if text.contains('\u{ad}') {
+                text = Cow::Owned(text.replace('\u{ad}', ""));
+            }
+            if let Cow::Owned(joined) = LINE_END_HYPHEN.replace_all(&text, "$1$2") {
+                text = Cow::Owned(joined);
+            }
+        }
+        if self.ligatures && text.contains(|c| LIGATURES.iter().any(|(ligature, _)| *ligature == c))
+        {
+            let mut spelled = String::with_capacity(text.len());
+            for c in text.chars() {
+                match LIGATURES.iter().find(|(ligature, _)| *ligature == c) {
+                    Some((_, letters)) => spelled.push_str(letters),
+                    None => spelled.push(c),
+                }
+            }
+            text = Cow::Owned(spelled);
+        }
+        if self.nbsp && text.contains(is_special_space) {
+            text = Cow::Owned(
+                text.chars()
+                    .filter(|c| !matches!(c, '\u{2060}' | '\u{feff}'))
+                    .map(|c| if is_special_space(c) { ' ' } else { c })
+                    .collect(),
+            );
+        }
+        text
+    }
+}
+
+/// No-break spaces and zero-width word joiners
+fn is_special_space(c: char) -> bool {
+    matches!(
+        c,
+        '\u{a0}' | '\u{2007}' | '\u{202f}' | '\u{2060}' | '\u{feff}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_cleanup_repairs_scan_artifacts() {
+        let html = "<p>The de\u{ad}fi\u{ad}nition of a ﬁne ﬂower, re-\n  corded by an\u{a0}old\u{2060} \
+                    hand in 1884-\n1885.</p>";
+        let cleaned = HtmlToMarkdownConverter::new()
+            .with_cleanup(TextCleanup {
+                dehyphenate: true,
+                ligatures: true,
+                nbsp: true,
+            })
+            .convert(html);
+        assert_eq!(
+            cleaned.plain_text(),
+            "The definition of a fine flower, recorded by an old hand in 1884- 1885."
+        );
+
+        let untouched = HtmlToMarkdownConverter::new().convert(html);
+        assert!(untouched.plain_text().contains("ﬁne ﬂower, re- corded"));
+    }
+}
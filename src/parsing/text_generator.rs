@@ -25,7 +25,7 @@ impl TextGenerator {
     }
 
     /// Helper function to extract plain text from HTML, removing tags but keeping content
-    fn extract_text_from_html(html: &str) -> String {
+    pub(crate) fn extract_text_from_html(html: &str) -> String {
         let tag_re = Regex::new(r"<[^>]+>").unwrap();
         let text = tag_re.replace_all(html, " ");
 
@@ -1,6 +1,65 @@
+use crate::markdown::{Block, Document, Inline, Node, TextOrInline, text_to_string};
 use crate::table_of_contents::TocItem;
 use epub::doc::{EpubDoc, NavPoint};
+use log::warn;
 use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+
+const OPS_NAMESPACE: &str = "http://www.idpf.org/2007/ops";
+const NCX_MIME: &str = "application/x-dtbncx+xml";
+
+/// Spine items are only checked this far for front matter
+const MAX_FRONT_MATTER_ITEMS: usize = 8;
+
+/// File names made of these words (`cover.xhtml`, `titlepage.xhtml`, `toc01.html`) are
+/// front matter
+const FRONT_MATTER_NAMES: &[&str] = &[
+    "cover",
+    "title",
+    "titlepage",
+    "halftitle",
+    "copyright",
+    "dedication",
+    "toc",
+    "contents",
+    "frontmatter",
+    "imprint",
+    "colophon",
+    "epigraph",
+];
+
+/// `epub:type` values of front matter pages
+const FRONT_MATTER_TYPES: &[&str] = &[
+    "cover",
+    "frontmatter",
+    "titlepage",
+    "halftitlepage",
+    "copyright-page",
+    "dedication",
+    "toc",
+    "epigraph",
+    "imprint",
+];
+
+/// Deepest heading level that makes it into the heading-based TOC
+const MAX_HEADING_TOC_LEVEL: u8 = 3;
+
+/// A heading found in the converted content, used to build the alternate TOC
+#[derive(Debug, Clone)]
+pub struct HeadingEntry {
+    pub level: u8,
+    pub title: String,
+    pub href: String,
+    pub anchor: Option<String>,
+}
+
+/// A print page from the book's page list
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageTarget {
+    pub label: String,
+    pub href: String,
+    pub anchor: Option<String>,
+}
 
 pub struct TocParser;
 
@@ -21,6 +80,222 @@ impl TocParser {
         Self::convert_navpoints_to_toc_items(&doc.toc)
     }
 
+    /// Read the print page list: the EPUB3 `page-list` nav, or the NCX `pageList` for EPUB2
+    pub fn parse_page_list<R: Read + Seek>(doc: &mut EpubDoc<R>) -> Vec<PageTarget> {
+        if let Some(nav_id) = doc.get_nav_id() {
+            if let Some(pages) = Self::read_page_list(doc, &nav_id, Self::parse_nav_page_list) {
+                return pages;
+            }
+        }
+
+        let ncx_id = doc
+            .resources
+            .iter()
+            .find(|(_, resource)| resource.mime == NCX_MIME)
+            .map(|(id, _)| id.clone());
+        ncx_id
+            .and_then(|id| Self::read_page_list(doc, &id, Self::parse_ncx_page_list))
+            .unwrap_or_default()
+    }
+
+    /// Spine index of the first bodymatter chapter: the `bodymatter` landmark of the EPUB3
+    /// nav, the `text` reference of the EPUB2 guide, or else the first spine item after
+    /// pages that look like a cover, title page, copyright page and so on. `None` when the
+    /// book gives no hint, including when the first page doesn't look like front matter.
+    pub fn find_bodymatter<R: Read + Seek>(doc: &mut EpubDoc<R>) -> Option<usize> {
+        let landmark = doc.get_nav_id().and_then(|nav_id| {
+            let base_dir = doc.resources.get(&nav_id)?.path.parent()?.to_path_buf();
+            let (content, _mime) = doc.get_resource_str(&nav_id)?;
+            Self::parse_nav_bodymatter(&content, &base_dir)
+        });
+        let guide = || {
+            let base_dir = doc.root_base.clone();
+            let content = doc.get_resource_str_by_path(doc.root_file.clone())?;
+            Self::parse_guide_text(&content, &base_dir)
+        };
+        if let Some(href) = landmark.or_else(guide) {
+            let index = doc.spine.iter().position(|item| {
+                doc.resources
+                    .get(&item.idref)
+                    .is_some_and(|resource| resource.path.to_string_lossy() == href)
+            });
+            if index.is_some() {
+                return index;
+            }
+            warn!("Bodymatter {href} is not in the spine");
+        }
+
+        let spine: Vec<String> = doc
+            .spine
+            .iter()
+            .take(MAX_FRONT_MATTER_ITEMS)
+            .map(|item| item.idref.clone())
+            .collect();
+        for (index, idref) in spine.iter().enumerate() {
+            let href = doc
+                .resources
+                .get(idref)
+                .map(|resource| resource.path.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let content = doc
+                .get_resource_str(idref)
+                .map(|(content, _mime)| content)
+                .unwrap_or_default();
+            if !Self::looks_like_front_matter(&href, &content) {
+                return (index > 0).then_some(index);
+            }
+        }
+        None
+    }
+
+    fn parse_nav_bodymatter(content: &str, base_dir: &Path) -> Option<String> {
+        let xml = Self::parse_xml(content)?;
+        let has_type = |node: &roxmltree::Node, wanted: &str| {
+            node.attribute((OPS_NAMESPACE, "type"))
+                .is_some_and(|value| value.split_whitespace().any(|t| t == wanted))
+        };
+        let landmarks = xml
+            .descendants()
+            .find(|node| node.has_tag_name("nav") && has_type(node, "landmarks"))?;
+        let href = landmarks
+            .descendants()
+            .find(|node| node.has_tag_name("a") && has_type(node, "bodymatter"))?
+            .attribute("href")?;
+        let (path, _anchor) = Self::split_href_and_anchor(href);
+        Some(resolve_relative(base_dir, &path))
+    }
+
+    fn parse_guide_text(opf: &str, base_dir: &Path) -> Option<String> {
+        let xml = Self::parse_xml(opf)?;
+        let href = xml
+            .descendants()
+            .filter(|node| node.has_tag_name("reference"))
+            .find(|node| {
+                node.attribute("type")
+                    .is_some_and(|kind| kind.eq_ignore_ascii_case("text"))
+            })?
+            .attribute("href")?;
+        let (path, _anchor) = Self::split_href_and_anchor(href);
+        Some(resolve_relative(base_dir, &path))
+    }
+
+    /// Whether a spine item is front matter, by its file name or the `epub:type` of the
+    /// first typed element in its body
+    fn looks_like_front_matter(href: &str, content: &str) -> bool {
+        let stem = Path::new(href)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let named = stem
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .filter(|word| !word.is_empty())
+            .any(|word| FRONT_MATTER_NAMES.contains(&word));
+        if named {
+            return true;
+        }
+
+        let body = content.find("<body").map_or("", |start| &content[start..]);
+        let Some(start) = body.find("epub:type=") else {
+            return false;
+        };
+        let value = &body[start + "epub:type=".len()..];
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            return false;
+        };
+        value[1..]
+            .split(quote)
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .any(|kind| FRONT_MATTER_TYPES.contains(&kind))
+    }
+
+    fn read_page_list<R: Read + Seek>(
+        doc: &mut EpubDoc<R>,
+        resource_id: &str,
+        parse: fn(&str, &Path) -> Vec<PageTarget>,
+    ) -> Option<Vec<PageTarget>> {
+        let base_dir = doc.resources.get(resource_id)?.path.parent()?.to_path_buf();
+        let (content, _mime) = doc.get_resource_str(resource_id)?;
+        let pages = parse(&content, &base_dir);
+        (!pages.is_empty()).then_some(pages)
+    }
+
+    fn parse_xml(content: &str) -> Option<roxmltree::Document<'_>> {
+        let options = roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        };
+        roxmltree::Document::parse_with_options(content, options)
+            .map_err(|e| warn!("Failed to parse navigation document: {e}"))
+            .ok()
+    }
+
+    fn parse_nav_page_list(content: &str, base_dir: &Path) -> Vec<PageTarget> {
+        let Some(xml) = Self::parse_xml(content) else {
+            return Vec::new();
+        };
+        let Some(nav) = xml.descendants().find(|node| {
+            node.has_tag_name("nav")
+                && (node
+                    .attribute((OPS_NAMESPACE, "type"))
+                    .is_some_and(|value| value.split_whitespace().any(|t| t == "page-list"))
+                    || node.attribute("role") == Some("doc-pagelist"))
+        }) else {
+            return Vec::new();
+        };
+
+        nav.descendants()
+            .filter(|node| node.has_tag_name("a"))
+            .filter_map(|link| {
+                let label: String = link
+                    .descendants()
+                    .filter(|node| node.is_text())
+                    .filter_map(|node| node.text())
+                    .collect::<String>()
+                    .trim()
+                    .to_string();
+                let href = link.attribute("href")?;
+                Self::page_target(label, href, base_dir)
+            })
+            .collect()
+    }
+
+    fn parse_ncx_page_list(content: &str, base_dir: &Path) -> Vec<PageTarget> {
+        let Some(xml) = Self::parse_xml(content) else {
+            return Vec::new();
+        };
+
+        xml.descendants()
+            .filter(|node| node.has_tag_name("pageTarget"))
+            .filter_map(|target| {
+                let label = target
+                    .descendants()
+                    .find(|node| node.has_tag_name("text"))
+                    .and_then(|node| node.text())
+                    .map(|text| text.trim().to_string())
+                    .or_else(|| target.attribute("value").map(str::to_string))?;
+                let src = target
+                    .children()
+                    .find(|node| node.has_tag_name("content"))?
+                    .attribute("src")?;
+                Self::page_target(label, src, base_dir)
+            })
+            .collect()
+    }
+
+    fn page_target(label: String, href: &str, base_dir: &Path) -> Option<PageTarget> {
+        if label.is_empty() {
+            return None;
+        }
+        let (path, anchor) = Self::split_href_and_anchor(href);
+        Some(PageTarget {
+            label,
+            href: resolve_relative(base_dir, &path),
+            anchor,
+        })
+    }
+
     /// Convert NavPoint structure to TocItem structure
     fn convert_navpoints_to_toc_items(navpoints: &[NavPoint]) -> Vec<TocItem> {
         navpoints
@@ -53,6 +328,124 @@ impl TocParser {
             }
         }
     }
+
+    /// Collect the H1–H3 headings of a converted chapter. Only headings that carry an id
+    /// (on the heading or an anchor inside it) can be jumped to; the exception is the first
+    /// heading, which stands for the chapter start. Chapters without any heading get an
+    /// entry titled `fallback_title` so they stay reachable.
+    pub fn collect_headings(
+        doc: &Document,
+        chapter_href: &str,
+        fallback_title: &str,
+        entries: &mut Vec<HeadingEntry>,
+    ) {
+        let first = entries.len();
+        Self::collect_headings_from_nodes(&doc.blocks, chapter_href, first, entries);
+
+        if entries.len() == first {
+            entries.push(HeadingEntry {
+                level: 1,
+                title: fallback_title.to_string(),
+                href: chapter_href.to_string(),
+                anchor: None,
+            });
+        }
+    }
+
+    fn collect_headings_from_nodes(
+        nodes: &[Node],
+        chapter_href: &str,
+        first: usize,
+        entries: &mut Vec<HeadingEntry>,
+    ) {
+        for node in nodes {
+            match &node.block {
+                Block::Heading { level, content } if *level as u8 <= MAX_HEADING_TOC_LEVEL => {
+                    let title = text_to_string(content)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if title.is_empty() {
+                        continue;
+                    }
+
+                    let anchor = node.id.clone().or_else(|| {
+                        content.iter().find_map(|item| match item {
+                            TextOrInline::Inline(Inline::Anchor { id }) => Some(id.clone()),
+                            _ => None,
+                        })
+                    });
+                    if anchor.is_none() && entries.len() > first {
+                        continue;
+                    }
+
+                    entries.push(HeadingEntry {
+                        level: *level as u8,
+                        title,
+                        href: chapter_href.to_string(),
+                        anchor,
+                    });
+                }
+                Block::EpubBlock { content, .. } => {
+                    Self::collect_headings_from_nodes(content, chapter_href, first, entries);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Nest collected headings by level: each heading owns the deeper headings that follow it
+    pub fn build_heading_toc(entries: &[HeadingEntry]) -> Vec<TocItem> {
+        let mut position = 0;
+        Self::build_heading_level(entries, &mut position, 0)
+    }
+
+    fn build_heading_level(
+        entries: &[HeadingEntry],
+        position: &mut usize,
+        parent_level: u8,
+    ) -> Vec<TocItem> {
+        let mut items = Vec::new();
+        while let Some(entry) = entries.get(*position) {
+            if entry.level <= parent_level {
+                break;
+            }
+            *position += 1;
+
+            let children = Self::build_heading_level(entries, position, entry.level);
+            if children.is_empty() {
+                items.push(TocItem::Chapter {
+                    title: entry.title.clone(),
+                    href: entry.href.clone(),
+                    anchor: entry.anchor.clone(),
+                });
+            } else {
+                items.push(TocItem::Section {
+                    title: entry.title.clone(),
+                    href: Some(entry.href.clone()),
+                    anchor: entry.anchor.clone(),
+                    children,
+                    is_expanded: false,
+                });
+            }
+        }
+        items
+    }
+}
+
+/// Resolve an href found in a navigation document against that document's directory
+pub(crate) fn resolve_relative(base_dir: &Path, href: &str) -> String {
+    let mut resolved = PathBuf::new();
+    for component in base_dir.join(href).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved.to_string_lossy().to_string()
 }
 
 #[cfg(test)]
@@ -174,4 +567,125 @@ mod tests {
         assert_eq!(href, "chapter.xhtml");
         assert_eq!(anchor, None);
     }
+
+    #[test]
+    fn test_build_heading_toc_nests_by_level() {
+        let entry = |level: u8, title: &str, anchor: Option<&str>| HeadingEntry {
+            level,
+            title: title.to_string(),
+            href: "ch1.xhtml".to_string(),
+            anchor: anchor.map(str::to_string),
+        };
+        let entries = vec![
+            entry(1, "Part One", None),
+            entry(2, "Arrival", Some("arrival")),
+            entry(3, "The Dock", Some("dock")),
+            entry(2, "Departure", Some("departure")),
+            entry(1, "Part Two", Some("two")),
+        ];
+
+        let toc = TocParser::build_heading_toc(&entries);
+        assert_eq!(toc.len(), 2);
+        match &toc[0] {
+            TocItem::Section {
+                title, children, ..
+            } => {
+                assert_eq!(title, "Part One");
+                assert_eq!(children.len(), 2);
+                assert!(
+                    matches!(&children[0], TocItem::Section { children, .. } if children.len() == 1)
+                );
+                assert!(
+                    matches!(&children[1], TocItem::Chapter { anchor: Some(a), .. } if a == "departure")
+                );
+            }
+            _ => panic!("Expected Section"),
+        }
+        assert!(matches!(&toc[1], TocItem::Chapter { title, .. } if title == "Part Two"));
+    }
+
+    #[test]
+    fn test_parse_nav_page_list() {
+        let nav = r#"<?xml version="1.0" encoding="utf-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<body>
+  <nav epub:type="toc"><ol><li><a href="text/ch1.xhtml">One</a></li></ol></nav>
+  <nav epub:type="page-list" hidden="">
+    <ol>
+      <li><a href="text/ch1.xhtml#page_i">i</a></li>
+      <li><a href="../Text/ch2.xhtml#p12"><span>12</span></a></li>
+    </ol>
+  </nav>
+</body>
+</html>"#;
+
+        let pages = TocParser::parse_nav_page_list(nav, Path::new("OEBPS"));
+        assert_eq!(
+            pages,
+            vec![
+                PageTarget {
+                    label: "i".to_string(),
+                    href: "OEBPS/text/ch1.xhtml".to_string(),
+                    anchor: Some("page_i".to_string()),
+                },
+                PageTarget {
+                    label: "12".to_string(),
+                    href: "Text/ch2.xhtml".to_string(),
+                    anchor: Some("p12".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_bodymatter_from_landmarks_guide_and_names() {
+        let nav = r#"<?xml version="1.0" encoding="utf-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<body>
+  <nav epub:type="landmarks">
+    <ol>
+      <li><a epub:type="cover" href="cover.xhtml">Cover</a></li>
+      <li><a epub:type="bodymatter" href="../Text/ch01.xhtml#start">Start</a></li>
+    </ol>
+  </nav>
+</body>
+</html>"#;
+        assert_eq!(
+            TocParser::parse_nav_bodymatter(nav, Path::new("OEBPS/Nav")),
+            Some("OEBPS/Text/ch01.xhtml".to_string())
+        );
+
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <guide>
+    <reference type="cover" href="cover.html"/>
+    <reference type="text" href="text/part1.html#ch1"/>
+  </guide>
+</package>"#;
+        assert_eq!(
+            TocParser::parse_guide_text(opf, Path::new("OEBPS")),
+            Some("OEBPS/text/part1.html".to_string())
+        );
+
+        assert!(TocParser::looks_like_front_matter(
+            "OEBPS/cover01.xhtml",
+            ""
+        ));
+        assert!(TocParser::looks_like_front_matter(
+            "OEBPS/title_page.xhtml",
+            ""
+        ));
+        assert!(TocParser::looks_like_front_matter(
+            "OEBPS/p002.xhtml",
+            r#"<body><section epub:type="frontmatter copyright-page">"#
+        ));
+        assert!(!TocParser::looks_like_front_matter(
+            "OEBPS/chapter01.xhtml",
+            r#"<body epub:type="bodymatter"><section epub:type="chapter">"#
+        ));
+        assert!(!TocParser::looks_like_front_matter(
+            "OEBPS/content0001.xhtml",
+            ""
+        ));
+    }
 }
@@ -1,4 +1,18 @@
+use crate::failure_alert::FailureAlert;
+use crate::flashcards::FlashcardTemplate;
+use crate::inputs::KeymapPreset;
+use crate::logging::LogFormat;
+use crate::markdown_text_reader::HyperlinkMode;
+use crate::parsing::content_filter::ContentFilters;
+use crate::parsing::html_to_markdown::DialogGrouping;
+use crate::parsing::substitutions::SubstitutionRule;
+use crate::parsing::text_cleanup::TextCleanup;
+use crate::quote_capture::QuoteStyle;
+use crate::reading_goal::ReadingGoal;
+use crate::table_of_contents::TocLengthIndicator;
+use crate::theme::{ThemePreset, ThemeSchedule};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -6,14 +20,263 @@ use std::path::Path;
 pub struct Preferences {
     pub summary_language: String,
 
+    #[serde(default)]
+    pub theme: ThemePreset,
+
+    /// Overrides `theme` with a day/night preset chosen by local time
+    #[serde(default)]
+    pub theme_schedule: Option<ThemeSchedule>,
+
+    /// Width of the navigation panel in percent of the screen; drag its border to change it
+    #[serde(default = "default_nav_panel_percent")]
+    pub nav_panel_percent: u16,
+
+    #[serde(default)]
+    pub bionic_reading: bool,
+
+    #[serde(default = "default_bionic_ratio")]
+    pub bionic_ratio: f32,
+
+    /// Leave a blank line every `paragraph_chunk_sentences` sentences of long paragraphs
+    #[serde(default)]
+    pub paragraph_chunks: bool,
+
+    #[serde(default = "default_paragraph_chunk_sentences")]
+    pub paragraph_chunk_sentences: usize,
+
+    #[serde(default)]
+    pub toc_length_indicator: TocLengthIndicator,
+
+    #[serde(default)]
+    pub sticky_heading: bool,
+
+    /// Lines kept between the cursor (TOC selection, jump targets) and the viewport edges
+    #[serde(default)]
+    pub scrolloff: usize,
+
+    /// Animate half-page and top/bottom jumps in the reader
+    #[serde(default)]
+    pub smooth_scroll: bool,
+
+    /// Show chapters as whole screens that Space and Backspace flip, instead of scrolling
+    #[serde(default)]
+    pub page_mode: bool,
+
+    /// Keep scrolling for a moment after a fast mouse wheel flick
+    #[serde(default)]
+    pub kinetic_scroll: bool,
+
+    /// Show note, highlight and bookmark markers in a gutter left of the text
+    #[serde(default)]
+    pub annotation_gutter: bool,
+
+    /// Mark TOC entries whose chapter is already parsed and cached
+    #[serde(default)]
+    pub chapter_cache_indicator: bool,
+
+    /// Number TOC entries hierarchically, like `2.3.1`
+    #[serde(default)]
+    pub toc_numbering: bool,
+
+    /// Show the percentage read next to TOC entries
+    #[serde(default)]
+    pub toc_progress: bool,
+
+    /// Extra bindings (emacs or arrow keys) on top of the vim-style keys
+    #[serde(default)]
+    pub keymap: KeymapPreset,
+
+    #[serde(default)]
+    pub content_zoom: u8,
+
+    /// Render zoomed text with double-width (fullwidth) glyphs
+    #[serde(default)]
+    pub zoom_double_width: bool,
+
+    /// Ask before handing external links to the browser
+    #[serde(default)]
+    pub confirm_external_links: bool,
+
+    /// Open images with this command instead of the built-in popup ("system" uses the OS default)
+    #[serde(default)]
+    pub image_viewer: Option<String>,
+
+    /// Folder images saved from the image popup go to (the download directory if unset)
+    #[serde(default)]
+    pub image_save_dir: Option<String>,
+
+    /// Shell command for `:summarize`: gets the chapter's text on stdin and prints a summary
+    #[serde(default)]
+    pub summary_command: Option<String>,
+
+    #[serde(default)]
+    pub quote_style: QuoteStyle,
+
+    /// Front and back of the cards `:flashcards` makes from notes
+    #[serde(default)]
+    pub flashcard_template: FlashcardTemplate,
+
+    /// Vocabulary file that saved words are appended to (.csv, or Anki-compatible TSV otherwise)
+    #[serde(default)]
+    pub vocabulary_file: Option<String>,
+
+    /// Shell command that looks up a saved word: gets it on stdin and prints its definition
+    #[serde(default)]
+    pub dictionary_command: Option<String>,
+
+    /// Template for note files made with `Space+a` (the built-in one if unset)
+    #[serde(default)]
+    pub note_template: Option<String>,
+
+    /// Scratch file code blocks are appended to (defaults to `<book>_snippets.txt` next to the book)
+    #[serde(default)]
+    pub code_scratch_file: Option<String>,
+
+    /// Elements (share buttons, ads, tracking pixels) stripped from chapters before display
+    #[serde(default)]
+    pub content_filters: ContentFilters,
+
+    /// Repairs for books digitized from print: dehyphenation, ligatures, no-break spaces
+    #[serde(default)]
+    pub text_cleanup: TextCleanup,
+
+    /// Regex find/replace rules per book, keyed by the book's file name or path
+    #[serde(default)]
+    pub substitutions: BTreeMap<String, Vec<SubstitutionRule>>,
+
+    /// Honor the book's CSS for text alignment, indentation and italics
+    #[serde(default)]
+    pub book_styles: bool,
+
+    /// When external links are clickable hyperlinks (OSC 8) in the terminal
+    #[serde(default)]
+    pub hyperlinks: HyperlinkMode,
+
+    /// Show the book and chapter being read in the terminal's window title
+    #[serde(default = "default_window_title")]
+    pub window_title: bool,
+
+    /// How actions that fail, like following a broken link, are signalled
+    #[serde(default)]
+    pub failure_alert: FailureAlert,
+
+    /// List the sections of long chapters under their title, as links
+    #[serde(default)]
+    pub chapter_contents: bool,
+
+    /// When runs of dash-led paragraphs are joined into one dialog block
+    #[serde(default)]
+    pub dialog_grouping: DialogGrouping,
+
+    /// Log levels applied at startup, e.g. `"info,parsing=trace"` (see `:loglevel`)
+    #[serde(default)]
+    pub log_levels: Option<String>,
+
+    #[serde(default)]
+    pub log_format: LogFormat,
+
+    /// Where the log goes instead of the XDG state directory; read at startup
+    #[serde(default)]
+    pub log_file: Option<String>,
+
+    /// Seconds without input before polling slows down and background parsing pauses
+    /// (0 never idles)
+    #[serde(default = "default_idle_after_secs")]
+    pub idle_after_secs: u64,
+
+    /// Minutes or pages to read each day; meeting it shows a celebration toast
+    #[serde(default)]
+    pub reading_goal: Option<ReadingGoal>,
+
+    /// Hour of the day (0-23) after which `bookokcat remind` sends a desktop notification
+    /// if nothing has been read yet today
+    #[serde(default)]
+    pub reading_reminder_hour: Option<u32>,
+
+    /// Open a book for the first time at its first bodymatter chapter, past the cover,
+    /// title and copyright pages; off always opens at the second page, past what is
+    /// usually the cover
+    #[serde(default = "default_skip_front_matter")]
+    pub skip_front_matter: bool,
+
     #[serde(skip)]
     file_path: Option<String>,
 }
 
+fn default_bionic_ratio() -> f32 {
+    crate::markdown_text_reader::DEFAULT_BIONIC_RATIO
+}
+
+fn default_nav_panel_percent() -> u16 {
+    30
+}
+
+fn default_paragraph_chunk_sentences() -> usize {
+    crate::markdown_text_reader::DEFAULT_CHUNK_SENTENCES
+}
+
+fn default_idle_after_secs() -> u64 {
+    60
+}
+
+fn default_window_title() -> bool {
+    true
+}
+
+fn default_skip_front_matter() -> bool {
+    true
+}
+
 impl Preferences {
     pub fn ephemeral() -> Self {
         Self {
             summary_language: "English".to_string(),
+            theme: ThemePreset::default(),
+            theme_schedule: None,
+            bionic_reading: false,
+            bionic_ratio: default_bionic_ratio(),
+            nav_panel_percent: default_nav_panel_percent(),
+            paragraph_chunks: false,
+            paragraph_chunk_sentences: default_paragraph_chunk_sentences(),
+            toc_length_indicator: TocLengthIndicator::default(),
+            sticky_heading: false,
+            scrolloff: 0,
+            smooth_scroll: false,
+            page_mode: false,
+            kinetic_scroll: false,
+            chapter_cache_indicator: false,
+            toc_numbering: false,
+            toc_progress: false,
+            annotation_gutter: false,
+            keymap: KeymapPreset::default(),
+            content_zoom: 0,
+            zoom_double_width: false,
+            confirm_external_links: false,
+            image_viewer: None,
+            image_save_dir: None,
+            summary_command: None,
+            quote_style: QuoteStyle::default(),
+            flashcard_template: FlashcardTemplate::default(),
+            vocabulary_file: None,
+            dictionary_command: None,
+            code_scratch_file: None,
+            note_template: None,
+            content_filters: ContentFilters::default(),
+            text_cleanup: TextCleanup::default(),
+            substitutions: BTreeMap::new(),
+            book_styles: false,
+            chapter_contents: false,
+            hyperlinks: HyperlinkMode::default(),
+            window_title: default_window_title(),
+            failure_alert: FailureAlert::default(),
+            dialog_grouping: DialogGrouping::default(),
+            log_levels: None,
+            log_format: LogFormat::default(),
+            log_file: None,
+            idle_after_secs: default_idle_after_secs(),
+            reading_goal: None,
+            reading_reminder_hour: None,
+            skip_front_matter: default_skip_front_matter(),
             file_path: None,
         }
     }
@@ -21,6 +284,52 @@ impl Preferences {
     pub fn with_file(file_path: &str) -> Self {
         Self {
             summary_language: "English".to_string(),
+            theme: ThemePreset::default(),
+            theme_schedule: None,
+            bionic_reading: false,
+            bionic_ratio: default_bionic_ratio(),
+            nav_panel_percent: default_nav_panel_percent(),
+            paragraph_chunks: false,
+            paragraph_chunk_sentences: default_paragraph_chunk_sentences(),
+            toc_length_indicator: TocLengthIndicator::default(),
+            sticky_heading: false,
+            scrolloff: 0,
+            smooth_scroll: false,
+            page_mode: false,
+            kinetic_scroll: false,
+            chapter_cache_indicator: false,
+            toc_numbering: false,
+            toc_progress: false,
+            annotation_gutter: false,
+            keymap: KeymapPreset::default(),
+            content_zoom: 0,
+            zoom_double_width: false,
+            confirm_external_links: false,
+            image_viewer: None,
+            image_save_dir: None,
+            summary_command: None,
+            quote_style: QuoteStyle::default(),
+            flashcard_template: FlashcardTemplate::default(),
+            vocabulary_file: None,
+            dictionary_command: None,
+            code_scratch_file: None,
+            note_template: None,
+            content_filters: ContentFilters::default(),
+            text_cleanup: TextCleanup::default(),
+            substitutions: BTreeMap::new(),
+            book_styles: false,
+            chapter_contents: false,
+            hyperlinks: HyperlinkMode::default(),
+            window_title: default_window_title(),
+            failure_alert: FailureAlert::default(),
+            dialog_grouping: DialogGrouping::default(),
+            log_levels: None,
+            log_format: LogFormat::default(),
+            log_file: None,
+            idle_after_secs: default_idle_after_secs(),
+            reading_goal: None,
+            reading_reminder_hour: None,
+            skip_front_matter: default_skip_front_matter(),
             file_path: Some(file_path.to_string()),
         }
     }
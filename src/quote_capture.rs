@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+
+/// How a captured quote and its citation are laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteStyle {
+    /// "text" followed by — Author, Title, Chapter
+    #[default]
+    Plain,
+    /// Markdown blockquote with the citation as the last line
+    Markdown,
+    /// A @quote BibTeX-like entry
+    Bibtex,
+}
+
+/// Where a quote comes from, pulled from the book metadata
+#[derive(Debug, Clone, Default)]
+pub struct Citation {
+    pub author: Option<String>,
+    pub title: Option<String>,
+    pub chapter: Option<String>,
+    pub year: Option<String>,
+}
+
+impl Citation {
+    /// `Author, Title, Chapter`, leaving out what isn't known
+    pub fn source_line(&self) -> String {
+        [&self.author, &self.title, &self.chapter]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Citation key in the usual "authorYear" shape, e.g. `austen1813`
+    fn key(&self) -> String {
+        let name = self
+            .author
+            .as_deref()
+            .and_then(|author| author.split_whitespace().last())
+            .or(self.title.as_deref())
+            .unwrap_or("quote");
+        let mut key: String = name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if let Some(year) = &self.year {
+            key.push_str(year);
+        }
+        key
+    }
+}
+
+/// A book as a reference manager sees it, from its OPF metadata
+#[derive(Debug, Clone, Default)]
+pub struct BookReference {
+    pub authors: Vec<String>,
+    pub title: Option<String>,
+    pub publisher: Option<String>,
+    /// As given in the OPF, e.g. `1851` or `1851-10-18`
+    pub date: Option<String>,
+    pub isbn: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Where a cited passage is in the book
+#[derive(Debug, Clone, Default)]
+pub struct Locator {
+    /// Print page label, when the book has a page list
+    pub page: Option<String>,
+    pub chapter: Option<String>,
+}
+
+/// Entry formats reference managers import
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceFormat {
+    Bibtex,
+    CslJson,
+}
+
+impl BookReference {
+    fn year(&self) -> Option<&str> {
+        self.date.as_deref().and_then(|date| date.get(..4))
+    }
+
+    fn key(&self) -> String {
+        Citation {
+            author: self.authors.first().cloned(),
+            title: self.title.clone(),
+            chapter: None,
+            year: self.year().map(str::to_string),
+        }
+        .key()
+    }
+
+    /// A `@book` entry, or for a passage one with its page and chapter
+    pub fn to_bibtex(&self, locator: Option<&Locator>) -> String {
+        let authors = (!self.authors.is_empty()).then(|| self.authors.join(" and "));
+        let mut named = vec![
+            ("author", authors),
+            ("title", self.title.clone()),
+            ("publisher", self.publisher.clone()),
+            ("year", self.year().map(str::to_string)),
+            ("isbn", self.isbn.clone()),
+            ("language", self.language.clone()),
+        ];
+        if let Some(locator) = locator {
+            named.push(("pages", locator.page.clone()));
+            named.push(("chapter", locator.chapter.clone()));
+        }
+        let fields: Vec<String> = named
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("  {name} = {{{}}}", bibtex_escape(&value?))))
+            .collect();
+        format!("@book{{{},\n{}\n}}", self.key(), fields.join(",\n"))
+    }
+
+    /// A CSL-JSON item; for a passage, a citation item carrying it with a page or
+    /// chapter locator
+    pub fn to_csl_json(&self, locator: Option<&Locator>) -> String {
+        let mut item = serde_json::json!({
+            "id": self.key(),
+            "type": "book",
+        });
+        let names: Vec<serde_json::Value> = self
+            .authors
+            .iter()
+            .map(|name| match name.rsplit_once(' ') {
+                Some((given, family)) => serde_json::json!({"family": family, "given": given}),
+                None => serde_json::json!({"literal": name}),
+            })
+            .collect();
+        if !names.is_empty() {
+            item["author"] = names.into();
+        }
+        let parts: Vec<u32> = self
+            .date
+            .iter()
+            .flat_map(|date| date.split('-'))
+            .map_while(|part| part.parse().ok())
+            .take(3)
+            .collect();
+        if !parts.is_empty() {
+            item["issued"] = serde_json::json!({ "date-parts": [parts] });
+        }
+        for (name, value) in [
+            ("title", &self.title),
+            ("publisher", &self.publisher),
+            ("ISBN", &self.isbn),
+            ("language", &self.language),
+        ] {
+            if let Some(value) = value {
+                item[name] = value.clone().into();
+            }
+        }
+
+        let value = match locator {
+            Some(locator) => {
+                let (label, at) = match (&locator.page, &locator.chapter) {
+                    (Some(page), _) => ("page", Some(page)),
+                    (None, chapter) => ("chapter", chapter.as_ref()),
+                };
+                let mut citation = serde_json::json!({ "id": self.key(), "itemData": item });
+                if let Some(at) = at {
+                    citation["locator"] = at.clone().into();
+                    citation["label"] = label.into();
+                }
+                citation
+            }
+            None => item,
+        };
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+}
+
+/// The ISBN among an EPUB's identifiers, which may be `urn:isbn:...`, `ISBN 978-...` or
+/// bare digits
+pub fn find_isbn<'a>(identifiers: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    identifiers.into_iter().find_map(|identifier| {
+        let lower = identifier.to_lowercase();
+        let digits: String = identifier
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == 'X')
+            .collect();
+        let looks_like_isbn = lower.contains("isbn")
+            || (identifier
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '-' || c == 'X')
+                && matches!(digits.len(), 10 | 13));
+        (looks_like_isbn && matches!(digits.len(), 10 | 13)).then_some(digits)
+    })
+}
+
+pub fn format_quote(text: &str, citation: &Citation, style: QuoteStyle) -> String {
+    let text = text.trim();
+    let source = citation.source_line();
+
+    match style {
+        QuoteStyle::Plain if source.is_empty() => format!("\"{text}\""),
+        QuoteStyle::Plain => format!("\"{text}\"\n— {source}"),
+        QuoteStyle::Markdown => {
+            let mut quote: Vec<String> = text.lines().map(|line| format!("> {line}")).collect();
+            if !source.is_empty() {
+                quote.push(">".to_string());
+                quote.push(format!("> — *{source}*"));
+            }
+            quote.join("\n")
+        }
+        QuoteStyle::Bibtex => {
+            let mut fields = vec![format!(
+                "  quote = {{{}}}",
+                bibtex_escape(&text.replace('\n', " "))
+            )];
+            let named = [
+                ("author", &citation.author),
+                ("title", &citation.title),
+                ("chapter", &citation.chapter),
+                ("year", &citation.year),
+            ];
+            for (name, value) in named {
+                if let Some(value) = value {
+                    fields.push(format!("  {name} = {{{}}}", bibtex_escape(value)));
+                }
+            }
+            format!("@quote{{{},\n{}\n}}", citation.key(), fields.join(",\n"))
+        }
+    }
+}
+
+/// Escape the characters LaTeX treats specially in a BibTeX field value
+fn bibtex_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' | '%' | '#' | '_' | '{' | '}' | '$' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation() -> Citation {
+        Citation {
+            author: Some("Jane Austen".to_string()),
+            title: Some("Pride and Prejudice".to_string()),
+            chapter: Some("Chapter 1".to_string()),
+            year: Some("1813".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_book_reference_formats_with_locator() {
+        let reference = BookReference {
+            authors: vec!["Herman Melville".to_string()],
+            title: Some("Moby-Dick".to_string()),
+            publisher: None,
+            date: Some("1851-10-18".to_string()),
+            isbn: find_isbn(["urn:uuid:1234", "urn:isbn:978-0-14-243724-7"]),
+            language: Some("en".to_string()),
+        };
+        let locator = Locator {
+            page: Some("12".to_string()),
+            chapter: Some("Loomings".to_string()),
+        };
+
+        assert_eq!(
+            reference.to_bibtex(Some(&locator)),
+            "@book{melville1851,\n  author = {Herman Melville},\n  title = {Moby-Dick},\n  \
+             year = {1851},\n  isbn = {9780142437247},\n  language = {en},\n  pages = {12},\n  \
+             chapter = {Loomings}\n}"
+        );
+
+        let book: serde_json::Value = serde_json::from_str(&reference.to_csl_json(None)).unwrap();
+        assert_eq!(book["author"][0]["family"], "Melville");
+        assert_eq!(
+            book["issued"]["date-parts"][0],
+            serde_json::json!([1851, 10, 18])
+        );
+        let passage: serde_json::Value =
+            serde_json::from_str(&reference.to_csl_json(Some(&locator))).unwrap();
+        assert_eq!(passage["locator"], "12");
+        assert_eq!(passage["label"], "page");
+        assert_eq!(passage["itemData"]["ISBN"], "9780142437247");
+
+        assert_eq!(find_isbn(["urn:uuid:0f3a-11"]), None);
+
+        let reference = BookReference {
+            title: Some("Pride & Prejudice_{2} #1 at 100%".to_string()),
+            publisher: Some(r"Smith\Jones".to_string()),
+            ..reference
+        };
+        let bibtex = reference.to_bibtex(None);
+        assert!(
+            bibtex.contains(r"  title = {Pride \& Prejudice\_\{2\} \#1 at 100\%},"),
+            "{bibtex}"
+        );
+        assert!(bibtex.contains(r"  publisher = {Smith\textbackslash{}Jones},"));
+
+        let reference = BookReference {
+            title: Some("~/notes on x^2".to_string()),
+            ..reference
+        };
+        let bibtex = reference.to_bibtex(None);
+        assert!(
+            bibtex.contains(r"  title = {\textasciitilde{}/notes on x\textasciicircum{}2},"),
+            "{bibtex}"
+        );
+    }
+
+    #[test]
+    fn test_format_quote_styles() {
+        let text = "It is a truth universally acknowledged";
+
+        assert_eq!(
+            format_quote(text, &citation(), QuoteStyle::Plain),
+            "\"It is a truth universally acknowledged\"\n— Jane Austen, Pride and Prejudice, Chapter 1"
+        );
+        assert_eq!(
+            format_quote(text, &citation(), QuoteStyle::Markdown),
+            "> It is a truth universally acknowledged\n>\n> — *Jane Austen, Pride and Prejudice, Chapter 1*"
+        );
+
+        let bibtex = format_quote(text, &citation(), QuoteStyle::Bibtex);
+        assert!(bibtex.starts_with("@quote{austen1813,\n"));
+        assert!(bibtex.contains("  author = {Jane Austen}"));
+        assert!(bibtex.ends_with("  year = {1813}\n}"));
+    }
+}
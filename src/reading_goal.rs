@@ -0,0 +1,125 @@
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How much to read each day: `{"minutes": 30}` or `{"pages": 20}` in preferences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadingGoal {
+    Minutes(u32),
+    /// Screens of text scrolled through
+    Pages(u32),
+}
+
+/// Today's progress towards the reading goal; starts over when the date changes
+pub struct GoalTracker {
+    goal: Option<ReadingGoal>,
+    day: NaiveDate,
+    reading_time: Duration,
+    pages: f64,
+    met: bool,
+}
+
+impl GoalTracker {
+    pub fn new(goal: Option<ReadingGoal>) -> Self {
+        GoalTracker {
+            goal,
+            day: Local::now().date_naive(),
+            reading_time: Duration::ZERO,
+            pages: 0.0,
+            met: false,
+        }
+    }
+
+    fn roll_over(&mut self, today: NaiveDate) {
+        if today != self.day {
+            *self = GoalTracker {
+                day: today,
+                ..GoalTracker::new(self.goal)
+            };
+        }
+    }
+
+    /// Count time spent reading and `lines` scrolled forward on a page of `page_height`
+    /// lines. Returns true the moment the goal is met, once a day.
+    pub fn record(
+        &mut self,
+        today: NaiveDate,
+        reading_time: Duration,
+        lines: usize,
+        page_height: usize,
+    ) -> bool {
+        self.roll_over(today);
+        self.reading_time += reading_time;
+        if page_height > 0 {
+            self.pages += lines as f64 / page_height as f64;
+        }
+        if self.met || self.goal.is_none() {
+            return false;
+        }
+        self.met = self.progress().is_some_and(|(done, target)| done >= target);
+        self.met
+    }
+
+    /// Progress and target in the goal's unit
+    fn progress(&self) -> Option<(u32, u32)> {
+        match self.goal? {
+            ReadingGoal::Minutes(target) => {
+                Some(((self.reading_time.as_secs() / 60) as u32, target))
+            }
+            ReadingGoal::Pages(target) => Some((self.pages as u32, target)),
+        }
+    }
+
+    /// e.g. `12/30 minutes read today`
+    pub fn describe(&self) -> String {
+        match (self.goal, self.progress()) {
+            (Some(ReadingGoal::Minutes(_)), Some((done, target))) => {
+                format!("{done}/{target} minutes read today")
+            }
+            (Some(ReadingGoal::Pages(_)), Some((done, target))) => {
+                format!("{done}/{target} pages read today")
+            }
+            _ => "No daily reading goal set".to_string(),
+        }
+    }
+}
+
+/// Whether to remind the reader: it's `hour` or later and nothing was read today
+pub fn reminder_due(now: DateTime<Local>, hour: u32, last_read: Option<DateTime<Local>>) -> bool {
+    now.hour() >= hour && last_read.is_none_or(|last| last.date_naive() != now.date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_goal_is_met_once_a_day() {
+        let monday = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+        let mut tracker = GoalTracker::new(Some(ReadingGoal::Pages(2)));
+        tracker.day = monday;
+
+        assert!(!tracker.record(monday, Duration::from_secs(60), 30, 20));
+        assert_eq!(tracker.describe(), "1/2 pages read today");
+        assert!(tracker.record(monday, Duration::from_secs(60), 10, 20));
+        assert!(!tracker.record(monday, Duration::from_secs(60), 40, 20));
+
+        assert!(!tracker.record(tuesday, Duration::ZERO, 20, 20));
+        assert_eq!(tracker.describe(), "1/2 pages read today");
+
+        let mut tracker = GoalTracker::new(Some(ReadingGoal::Minutes(1)));
+        assert!(tracker.record(Local::now().date_naive(), Duration::from_secs(61), 0, 20));
+    }
+
+    #[test]
+    fn test_reminder_is_due_after_the_hour_without_reading_today() {
+        let at = |day, hour| Local.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+        assert!(reminder_due(at(2, 20), 19, Some(at(1, 21))));
+        assert!(reminder_due(at(2, 20), 19, None));
+        assert!(!reminder_due(at(2, 18), 19, None));
+        assert!(!reminder_due(at(2, 20), 19, Some(at(2, 8))));
+    }
+}
@@ -0,0 +1,485 @@
+use crate::book_manager::BOOK_EXTENSIONS;
+use anyhow::{Context, Result, bail};
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Where a book named on the clipboard (or command line) lives
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookSource {
+    Local(PathBuf),
+    Remote(String),
+}
+
+/// Progress of a book download, sent from the download thread to the UI
+#[derive(Debug)]
+pub enum DownloadEvent {
+    /// The title of the book being fetched, once its package document has been read
+    Title(String),
+    Progress {
+        received: u64,
+        total: Option<u64>,
+    },
+    Finished(Result<PathBuf, String>),
+}
+
+fn has_book_extension(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| BOOK_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+pub fn is_book_url(text: &str) -> bool {
+    let Some(rest) = text
+        .strip_prefix("https://")
+        .or_else(|| text.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let path = rest.split(['?', '#']).next().unwrap_or_default();
+    path.contains('/') && has_book_extension(path)
+}
+
+/// Recognize a book path or an http(s) URL in pasted text. Surrounding whitespace and
+/// quotes, and a `file://` prefix, are ignored.
+pub fn parse_book_source(text: &str) -> Option<BookSource> {
+    let text = text.trim().trim_matches(|c| c == '"' || c == '\'');
+    if text.is_empty() || text.contains('\n') {
+        return None;
+    }
+    if is_book_url(text) {
+        return Some(BookSource::Remote(text.to_string()));
+    }
+    let path = text.strip_prefix("file://").unwrap_or(text);
+    has_book_extension(path).then(|| BookSource::Local(PathBuf::from(path)))
+}
+
+/// Downloads are kept under `<downloads dir>/<md5 of the URL>/<file name>`, so the same
+/// URL is only fetched once and the book keeps its own name in the library
+pub fn cache_path(url: &str) -> Result<PathBuf> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    if file_name.is_empty() {
+        bail!("No file name in {url}");
+    }
+    let digest = md5::compute(url.as_bytes());
+    Ok(crate::app_dirs::downloads_dir()?
+        .join(format!("{digest:x}"))
+        .join(file_name))
+}
+
+/// Fetch `url` into `dest`, calling `progress` with the bytes received so far and the
+/// expected size when the server tells it. Nothing is left at `dest` if the download fails.
+pub async fn download(
+    url: &str,
+    dest: &Path,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<()> {
+    let mut response = reqwest::get(url)
+        .await
+        .with_context(|| format!("Failed to fetch {url}"))?
+        .error_for_status()?;
+    let total = response.content_length();
+
+    if let Some(dir) = dest.parent() {
+        fs::create_dir_all(dir).context("Failed to create cache directory")?;
+    }
+    let partial = dest.with_extension("part");
+    let mut file = fs::File::create(&partial).context("Failed to create download file")?;
+    let mut received = 0;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        received += chunk.len() as u64;
+        progress(received, total);
+    }
+    drop(file);
+    fs::rename(&partial, dest).context("Failed to move download into the cache")?;
+    Ok(())
+}
+
+/// Size of the blocks a `RangeReader` fetches and keeps
+const RANGE_BLOCK: u64 = 64 * 1024;
+/// How much of a book is asked for at a time once it is known to be one
+const DOWNLOAD_CHUNK: u64 = 1024 * 1024;
+
+/// A file on a web server read with HTTP range requests, a block at a time. Blocks are
+/// kept once fetched, so the zip central directory and the entries near it are only
+/// asked for once however often the archive reader seeks back to them.
+pub struct RangeReader {
+    client: reqwest::blocking::Client,
+    url: String,
+    len: u64,
+    pos: u64,
+    blocks: HashMap<u64, Vec<u8>>,
+}
+
+/// The total size in a `Content-Range: bytes 0-0/1234` header
+fn content_range_total(value: &str) -> Option<u64> {
+    value
+        .strip_prefix("bytes ")?
+        .split_once('/')?
+        .1
+        .parse()
+        .ok()
+}
+
+impl RangeReader {
+    /// Ask for the first byte of `url` to learn its size. `None` if the server doesn't
+    /// answer range requests, so the file can only be downloaded whole.
+    pub fn open(url: &str) -> Result<Option<Self>> {
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .get(url)
+            .header(RANGE, "bytes=0-0")
+            .send()
+            .with_context(|| format!("Failed to fetch {url}"))?
+            .error_for_status()?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Ok(None);
+        }
+        let Some(len) = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_range_total)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(RangeReader {
+            client,
+            url: url.to_string(),
+            len,
+            pos: 0,
+            blocks: HashMap::new(),
+        }))
+    }
+
+    /// Size of the whole file
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes `start..end` of the file, in one request
+    pub fn fetch(&self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        let end = end.min(self.len);
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let response = self
+            .client
+            .get(&self.url)
+            .header(RANGE, format!("bytes={start}-{}", end - 1))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(io::Error::other)?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(io::Error::other(format!(
+                "{} stopped answering range requests",
+                self.url
+            )));
+        }
+        let bytes = response.bytes().map_err(io::Error::other)?;
+        if bytes.len() as u64 != end - start {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Expected {} bytes from {}", end - start, self.url),
+            ));
+        }
+        Ok(bytes.to_vec())
+    }
+
+    fn block(&mut self, index: u64) -> io::Result<&[u8]> {
+        if !self.blocks.contains_key(&index) {
+            let start = index * RANGE_BLOCK;
+            let bytes = self.fetch(start, start + RANGE_BLOCK)?;
+            self.blocks.insert(index, bytes);
+        }
+        Ok(&self.blocks[&index])
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let pos = self.pos;
+        let block = self.block(pos / RANGE_BLOCK)?;
+        let offset = (pos % RANGE_BLOCK) as usize;
+        let count = buf.len().min(block.len() - offset);
+        buf[..count].copy_from_slice(&block[offset..offset + count]);
+        self.pos += count as u64;
+        Ok(count)
+    }
+}
+
+impl Seek for RangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Seek before the start"))?;
+        self.pos = pos;
+        Ok(pos)
+    }
+}
+
+/// Whether the end of central directory record is where a zip keeps it: within the
+/// longest possible comment of the end. Checked before reading the archive, which would
+/// otherwise search all of a file that isn't one.
+fn ends_like_a_zip(reader: &mut RangeReader) -> io::Result<bool> {
+    const SIGNATURE: &[u8] = b"PK\x05\x06";
+    const LONGEST_END: u64 = 22 + u16::MAX as u64;
+    reader.seek(SeekFrom::Start(reader.len().saturating_sub(LONGEST_END)))?;
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail)?;
+    Ok(tail
+        .windows(SIGNATURE.len())
+        .any(|bytes| bytes == SIGNATURE))
+}
+
+/// Fetch `url` into `dest` with range requests. An EPUB's central directory and package
+/// document are read first, from the end of the file, so a link to something that isn't
+/// a book fails before the rest is fetched, and `title` learns what is coming. The file is
+/// then fetched in order, carrying on from the `.part` file an interrupted download left.
+/// Returns false, having fetched nothing, if the server doesn't answer range requests.
+pub fn download_ranged(
+    url: &str,
+    dest: &Path,
+    title: impl FnOnce(String),
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<bool> {
+    let Some(mut reader) = RangeReader::open(url)? else {
+        return Ok(false);
+    };
+    if dest
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"))
+    {
+        if !ends_like_a_zip(&mut reader)? {
+            bail!("{url} is not an EPUB");
+        }
+        let doc = epub::doc::EpubDoc::from_reader(&mut reader)
+            .with_context(|| format!("{url} is not an EPUB"))?;
+        if let Some(name) = doc.mdata("title") {
+            title(name.value.clone());
+        }
+    }
+
+    if let Some(dir) = dest.parent() {
+        fs::create_dir_all(dir).context("Failed to create cache directory")?;
+    }
+    let partial = dest.with_extension("part");
+    let resume_from = fs::metadata(&partial)
+        .map(|meta| meta.len())
+        .ok()
+        .filter(|&len| len <= reader.len())
+        .unwrap_or(0);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&partial)
+        .context("Failed to create download file")?;
+    file.set_len(resume_from)?;
+
+    let total = reader.len();
+    let mut received = resume_from;
+    progress(received, Some(total));
+    while received < total {
+        let end = (received + DOWNLOAD_CHUNK).min(total);
+        file.write_all(&reader.fetch(received, end)?)?;
+        received = end;
+        progress(received, Some(total));
+    }
+    drop(file);
+    fs::rename(&partial, dest).context("Failed to move download into the cache")?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Serve `body` over HTTP on a local port, answering range requests if `ranges`.
+    /// Returns the URL of `name` on it and a count of the body bytes sent.
+    fn serve(body: Vec<u8>, name: &str, ranges: bool) -> (String, Arc<AtomicU64>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/{name}", listener.local_addr().unwrap());
+        let sent = Arc::new(AtomicU64::new(0));
+        let counter = sent.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                let mut request = io::BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    if request.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        let (start, end) = value.trim().split_once('-').unwrap();
+                        range = Some((
+                            start.parse::<usize>().unwrap(),
+                            end.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+                let (status, part, content_range) = match range.filter(|_| ranges) {
+                    Some((start, end)) => (
+                        "206 Partial Content",
+                        &body[start..=end.min(body.len() - 1)],
+                        format!("Content-Range: bytes {start}-{end}/{}\r\n", body.len()),
+                    ),
+                    None => ("200 OK", &body[..], String::new()),
+                };
+                let header = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\n{content_range}Connection: close\r\n\r\n",
+                    part.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                if stream.write_all(part).is_ok() {
+                    counter.fetch_add(part.len() as u64, Ordering::SeqCst);
+                }
+            }
+        });
+        (url, sent)
+    }
+
+    #[test]
+    fn test_range_reader_reads_like_the_file() {
+        let body: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let (url, _) = serve(body.clone(), "data.epub", true);
+        let mut reader = RangeReader::open(&url).unwrap().unwrap();
+        assert_eq!(reader.len(), body.len() as u64);
+
+        reader.seek(SeekFrom::End(-10)).unwrap();
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &body[body.len() - 10..]);
+
+        reader.seek(SeekFrom::Start(65_530)).unwrap();
+        let mut across_blocks = [0; 20];
+        reader.read_exact(&mut across_blocks).unwrap();
+        assert_eq!(across_blocks, body[65_530..65_550]);
+    }
+
+    #[test]
+    fn test_ranged_download_checks_the_book_and_resumes() {
+        // A large file ahead of the book's own, so only reading what's needed for the
+        // title shows: the package document is near the end with the central directory
+        let mut source =
+            zip::ZipArchive::new(fs::File::open("tests/testdata/digital_frontier.epub").unwrap())
+                .unwrap();
+        let mut book = io::Cursor::new(Vec::new());
+        let mut zip = zip::ZipWriter::new(&mut book);
+        zip.raw_copy_file(source.by_name("mimetype").unwrap())
+            .unwrap();
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("OEBPS/filler.bin", options).unwrap();
+        zip.write_all(
+            &(0..DOWNLOAD_CHUNK * 2)
+                .map(|i| (i % 251) as u8)
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        for index in 0..source.len() {
+            let entry = source.by_index(index).unwrap();
+            if entry.name() != "mimetype" {
+                zip.raw_copy_file(entry).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+        drop(zip);
+        let book = book.into_inner();
+
+        let (url, sent) = serve(book.clone(), "frontier.epub", true);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("frontier.epub");
+        let already = book.len() / 2;
+        fs::write(dest.with_extension("part"), &book[..already]).unwrap();
+
+        let mut title = None;
+        let mut last_progress = None;
+        assert!(
+            download_ranged(
+                &url,
+                &dest,
+                |name| title = Some(name),
+                |received, total| last_progress = Some((received, total)),
+            )
+            .unwrap()
+        );
+        assert_eq!(fs::read(&dest).unwrap(), book);
+        assert!(!dest.with_extension("part").exists());
+        assert_eq!(
+            title.as_deref(),
+            Some("The Digital Frontier: A Tale of Code and Adventure")
+        );
+        let len = book.len() as u64;
+        assert_eq!(last_progress, Some((len, Some(len))));
+        // The first half came from the interrupted download
+        assert!(sent.load(Ordering::SeqCst) < len);
+    }
+
+    #[test]
+    fn test_ranged_download_turns_down_what_isnt_a_book() {
+        let body = vec![b'x'; 3 * DOWNLOAD_CHUNK as usize];
+        let (url, sent) = serve(body, "page.epub", true);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("page.epub");
+
+        assert!(download_ranged(&url, &dest, |_| {}, |_, _| {}).is_err());
+        assert!(!dest.exists() && !dest.with_extension("part").exists());
+        assert!(sent.load(Ordering::SeqCst) < DOWNLOAD_CHUNK);
+    }
+
+    #[test]
+    fn test_ranged_download_needs_a_server_that_answers_ranges() {
+        let (url, _) = serve(b"book".to_vec(), "book.epub", false);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("book.epub");
+        assert!(!download_ranged(&url, &dest, |_| {}, |_, _| {}).unwrap());
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_parse_book_source() {
+        assert_eq!(
+            parse_book_source("  https://example.com/books/moby.epub?dl=1\n"),
+            Some(BookSource::Remote(
+                "https://example.com/books/moby.epub?dl=1".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_book_source("'/home/me/Books/Paper.PDF'"),
+            Some(BookSource::Local(PathBuf::from("/home/me/Books/Paper.PDF")))
+        );
+        assert_eq!(
+            parse_book_source("file:///tmp/moby.epub"),
+            Some(BookSource::Local(PathBuf::from("/tmp/moby.epub")))
+        );
+        assert_eq!(parse_book_source("https://example.com/"), None);
+        assert_eq!(
+            parse_book_source("tale.txt"),
+            Some(BookSource::Local(PathBuf::from("tale.txt")))
+        );
+        assert_eq!(parse_book_source("notes.md"), None);
+        assert_eq!(parse_book_source("a.epub\nb.epub"), None);
+    }
+}
@@ -215,10 +215,32 @@ pub fn find_matches_in_text(query: &str, items: &[String]) -> Vec<SearchMatch> {
     matches
 }
 
+/// Whether `n` (`forward`) or `N` going from match `before` to match `after` went round
+/// past the last or first match, or found no match to go to
+pub fn search_wrapped(before: Option<usize>, after: Option<usize>, forward: bool) -> bool {
+    match (before, after) {
+        (_, None) => true,
+        (Some(before), Some(after)) if forward => after <= before,
+        (Some(before), Some(after)) => after >= before,
+        (None, Some(_)) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_search_wrapped() {
+        assert!(!search_wrapped(Some(0), Some(1), true));
+        assert!(search_wrapped(Some(2), Some(0), true));
+        assert!(search_wrapped(Some(0), Some(0), true));
+        assert!(!search_wrapped(Some(1), Some(0), false));
+        assert!(search_wrapped(Some(0), Some(2), false));
+        assert!(!search_wrapped(None, Some(2), true));
+        assert!(search_wrapped(None, None, false));
+    }
+
     #[test]
     fn test_find_matches() {
         let items = vec![
@@ -12,12 +12,21 @@ pub struct BookSearchResult {
     pub match_positions: Vec<usize>,
 }
 
+/// How often a term comes up in one chapter
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermCount {
+    pub chapter_index: usize,
+    pub chapter_title: String,
+    pub count: usize,
+    /// Line of the chapter's plain text with the first occurrence
+    pub first_line: usize,
+}
+
 #[derive(Debug)]
 struct ProcessedChapter {
     index: usize,
     title: String,
     lines: Vec<String>,
-    #[allow(dead_code)]
     raw_text: String,
 }
 
@@ -53,6 +62,59 @@ impl SearchEngine {
             .collect();
     }
 
+    pub fn chapter_word_count(&self, chapter_index: usize) -> Option<usize> {
+        self.chapters
+            .iter()
+            .find(|chapter| chapter.index == chapter_index)
+            .map(|chapter| chapter.raw_text.split_whitespace().count())
+    }
+
+    /// Each chapter's index and plain text lines, in spine order
+    pub fn chapter_lines(&self) -> impl Iterator<Item = (usize, &[String])> {
+        self.chapters
+            .iter()
+            .map(|chapter| (chapter.index, chapter.lines.as_slice()))
+    }
+
+    /// Occurrences of `term` per chapter, ignoring case and only as a whole word or phrase.
+    /// Chapters without any are left out.
+    pub fn count_occurrences(&self, term: &str) -> Vec<TermCount> {
+        let term = term.trim().to_lowercase();
+        if term.is_empty() {
+            return Vec::new();
+        }
+
+        self.chapters
+            .iter()
+            .filter_map(|chapter| {
+                let mut count = 0;
+                let mut first_line = None;
+                for (line_idx, line) in chapter.lines.iter().enumerate() {
+                    let line = line.to_lowercase();
+                    let in_line = line
+                        .match_indices(&term)
+                        .filter(|(start, _)| {
+                            let before = line[..*start].chars().next_back();
+                            let after = line[start + term.len()..].chars().next();
+                            !before.is_some_and(char::is_alphanumeric)
+                                && !after.is_some_and(char::is_alphanumeric)
+                        })
+                        .count();
+                    if in_line > 0 {
+                        count += in_line;
+                        first_line.get_or_insert(line_idx);
+                    }
+                }
+                Some(TermCount {
+                    chapter_index: chapter.index,
+                    chapter_title: chapter.title.clone(),
+                    count,
+                    first_line: first_line?,
+                })
+            })
+            .collect()
+    }
+
     pub fn search_fuzzy(&self, query: &str) -> Vec<BookSearchResult> {
         if query.is_empty() {
             return Vec::new();
@@ -291,3 +353,50 @@ impl SearchEngine {
         self.chapters.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_occurrences_per_chapter_of_whole_words() {
+        let mut engine = SearchEngine::new();
+        engine.process_chapters(vec![
+            (
+                0,
+                "Loomings".to_string(),
+                "Call me Ishmael.\nThe whale, the Whale!".to_string(),
+            ),
+            (
+                1,
+                "The Carpet-Bag".to_string(),
+                "No whales here.".to_string(),
+            ),
+            (
+                2,
+                "The Spouter-Inn".to_string(),
+                "A white whale\nwhale-ship".to_string(),
+            ),
+        ]);
+
+        assert_eq!(
+            engine.count_occurrences(" WHALE "),
+            vec![
+                TermCount {
+                    chapter_index: 0,
+                    chapter_title: "Loomings".to_string(),
+                    count: 2,
+                    first_line: 1,
+                },
+                TermCount {
+                    chapter_index: 2,
+                    chapter_title: "The Spouter-Inn".to_string(),
+                    count: 2,
+                    first_line: 0,
+                },
+            ]
+        );
+        assert_eq!(engine.count_occurrences("white whale").len(), 1);
+        assert!(engine.count_occurrences("").is_empty());
+    }
+}
@@ -5,6 +5,9 @@ pub trait SystemCommandExecutor {
     fn open_file(&self, path: &str) -> Result<(), String>;
     fn open_file_at_chapter(&self, path: &str, chapter: usize) -> Result<(), String>;
     fn open_url(&self, url: &str) -> Result<(), String>;
+    /// Run `program` (which may carry arguments, e.g. `code --wait`) on `path` in this
+    /// terminal and wait for it to exit
+    fn run_in_terminal(&self, program: &str, path: &std::path::Path) -> Result<(), String>;
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
@@ -79,6 +82,21 @@ impl SystemCommandExecutor for RealSystemCommandExecutor {
         Ok(())
     }
 
+    fn run_in_terminal(&self, program: &str, path: &std::path::Path) -> Result<(), String> {
+        let mut words = program.split_whitespace();
+        let command = words.next().ok_or("No program given")?;
+        let status = Command::new(command)
+            .args(words)
+            .arg(path)
+            .status()
+            .map_err(|e| e.to_string())?;
+        // Editors exit with an error to mean "discard my changes"; that's not a failure here
+        if !status.success() {
+            log::warn!("{program} exited with {status}");
+        }
+        Ok(())
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -183,6 +201,71 @@ impl RealSystemCommandExecutor {
     }
 }
 
+/// Show a desktop notification with `notify-send`, or Notification Center on macOS
+pub fn send_desktop_notification(title: &str, body: &str) -> Result<(), String> {
+    let status = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {:?} with title {:?}",
+            body.replace('"', "'"),
+            title.replace('"', "'")
+        );
+        Command::new("osascript").args(["-e", &script]).status()
+    } else {
+        Command::new("notify-send").args([title, body]).status()
+    };
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("Notification command failed: {status}")),
+        Err(e) => Err(format!("Failed to send notification: {e}")),
+    }
+}
+
+/// Run a shell command with `input` on its stdin and return what it prints, e.g. a local
+/// LLM CLI asked to summarize. `env` is added to the command's environment.
+pub fn run_text_filter(
+    command: &str,
+    input: &str,
+    env: &[(&str, String)],
+) -> Result<String, String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = shell
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {command}: {e}"))?;
+
+    // Write from another thread so a command that prints before reading everything
+    // can't deadlock on a full pipe
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {command}: {e}"))?;
+    // A command that doesn't read its input closes the pipe early; that's its business
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().last().unwrap_or_default().trim();
+        return Err(format!("{command} failed ({}): {reason}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 #[cfg(any(test, feature = "test-utils"))]
 pub struct MockSystemCommandExecutor {
     pub executed_commands: std::cell::RefCell<Vec<String>>,
@@ -250,7 +333,38 @@ impl SystemCommandExecutor for MockSystemCommandExecutor {
         }
     }
 
+    fn run_in_terminal(&self, program: &str, path: &std::path::Path) -> Result<(), String> {
+        self.executed_commands
+            .borrow_mut()
+            .push(format!("RUN:{program} {}", path.display()));
+        if self.should_fail {
+            Err("Mock failure".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_filter_pipes_text_through_a_shell_command() {
+        let env = [("BOOKOKCAT_CHAPTER_TITLE", "Loomings".to_string())];
+        assert_eq!(
+            run_text_filter(
+                "echo \"$BOOKOKCAT_CHAPTER_TITLE:\"; tr a-z A-Z",
+                "call me",
+                &env
+            ),
+            Ok("Loomings:\nCALL ME".to_string())
+        );
+        let error = run_text_filter("echo model missing >&2; exit 3", "", &[]).unwrap_err();
+        assert!(error.ends_with("model missing"), "{error}");
+    }
+}
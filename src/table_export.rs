@@ -0,0 +1,124 @@
+use crate::markdown::{TableRow, text_to_string};
+
+/// Rebuild the logical cell grid of a table. The parser leaves an empty placeholder
+/// where a cell spans down from the row above; here the spanning cell's text is
+/// repeated in those rows so every exported row is complete.
+pub fn table_grid(header: Option<&TableRow>, rows: &[TableRow]) -> Vec<Vec<String>> {
+    let mut grid = Vec::new();
+    // Per column: text of a cell spanning down, and how many more rows it covers
+    let mut spans: Vec<Option<(String, u32)>> = Vec::new();
+
+    for row in header.into_iter().chain(rows) {
+        let mut values = Vec::with_capacity(row.cells.len());
+        for (column, cell) in row.cells.iter().enumerate() {
+            if spans.len() <= column {
+                spans.resize(column + 1, None);
+            }
+
+            let value = match spans[column].take() {
+                Some((text, remaining)) => {
+                    if remaining > 1 {
+                        spans[column] = Some((text.clone(), remaining - 1));
+                    }
+                    text
+                }
+                None => {
+                    let text = text_to_string(&cell.content).trim().to_string();
+                    if cell.rowspan > 1 {
+                        spans[column] = Some((text.clone(), cell.rowspan - 1));
+                    }
+                    text
+                }
+            };
+            values.push(value);
+        }
+        grid.push(values);
+    }
+
+    grid
+}
+
+/// RFC 4180 CSV: fields with commas, quotes or line breaks are quoted
+pub fn to_csv(grid: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+    for row in grid {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|field| {
+                if field.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Tab-separated rows, as spreadsheets expect on paste; tabs and line breaks inside
+/// cells become spaces
+pub fn to_tsv(grid: &[Vec<String>]) -> String {
+    grid.iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| field.replace(['\t', '\n', '\r'], " "))
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::{TableCell, Text, TextNode, TextOrInline};
+
+    fn text(content: &str) -> Text {
+        let mut text = Text::default();
+        text.push(TextOrInline::Text(TextNode {
+            content: content.to_string(),
+            style: None,
+        }));
+        text
+    }
+
+    #[test]
+    fn test_rowspans_are_repeated_in_export() {
+        let header = TableRow::new(vec![
+            TableCell::new_header(text("Region")),
+            TableCell::new_header(text("Note")),
+        ]);
+        let rows = vec![
+            TableRow::new(vec![
+                TableCell::new_with_rowspan(text("North"), 2),
+                TableCell::new(text("cold, \"windy\"")),
+            ]),
+            TableRow::new(vec![
+                TableCell::new(Text::default()),
+                TableCell::new(text("snow")),
+            ]),
+        ];
+
+        let grid = table_grid(Some(&header), &rows);
+        assert_eq!(
+            grid,
+            vec![
+                vec!["Region", "Note"],
+                vec!["North", "cold, \"windy\""],
+                vec!["North", "snow"],
+            ]
+        );
+        assert_eq!(
+            to_csv(&grid),
+            "Region,Note\r\nNorth,\"cold, \"\"windy\"\"\"\r\nNorth,snow\r\n"
+        );
+        assert_eq!(
+            to_tsv(&grid),
+            "Region\tNote\nNorth\tcold, \"windy\"\nNorth\tsnow"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{self, Write};
+
+/// Window title for what's being read: `bookokcat — <book> — <chapter>`, leaving out
+/// what isn't known. Control characters are dropped so a title can't end the escape
+/// sequence it is sent in.
+pub fn window_title(book: Option<&str>, chapter: Option<&str>) -> String {
+    std::iter::once("bookokcat")
+        .chain(book)
+        .chain(chapter)
+        .map(|part| {
+            // Line breaks become spaces; escapes and other controls are dropped
+            part.split_whitespace()
+                .map(|word| word.chars().filter(|c| !c.is_control()).collect::<String>())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" — ")
+}
+
+/// Save the terminal's window title on its title stack (XTWINOPS 22), to be put back by
+/// `pop_title` on exit. Terminals without a stack ignore both.
+pub fn push_title(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(b"\x1b[22;0t")?;
+    out.flush()
+}
+
+pub fn pop_title(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(b"\x1b[23;0t")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_title_skips_unknown_parts_and_controls() {
+        assert_eq!(
+            window_title(Some("Moby-Dick"), Some("Chapter 1.\nLoomings")),
+            "bookokcat — Moby-Dick — Chapter 1. Loomings"
+        );
+        assert_eq!(
+            window_title(Some("Evil\x07\x1b]0;title"), None),
+            "bookokcat — Evil]0;title"
+        );
+        assert_eq!(window_title(None, Some("  ")), "bookokcat");
+    }
+}
@@ -28,6 +28,14 @@ pub mod test_helpers {
             self
         }
 
+        /// Type each character of `text`, e.g. a `:` command
+        pub fn type_str(mut self, text: &str) -> Self {
+            for c in text.chars() {
+                self = self.press_char(c);
+            }
+            self
+        }
+
         /// Add a Ctrl+character key press
         pub fn press_ctrl_char(mut self, c: char) -> Self {
             self.events.push(SimulatedEventSource::ctrl_char_key(c));
@@ -67,6 +75,17 @@ pub mod test_helpers {
             self
         }
 
+        /// Press Esc
+        pub fn press_esc(mut self) -> Self {
+            self.events.push(Event::Key(KeyEvent {
+                code: KeyCode::Esc,
+                modifiers: KeyModifiers::empty(),
+                kind: crossterm::event::KeyEventKind::Press,
+                state: crossterm::event::KeyEventState::empty(),
+            }));
+            self
+        }
+
         /// Navigate down n times (press 'j' n times)
         pub fn navigate_down(mut self, times: usize) -> Self {
             for _ in 0..times {
@@ -107,6 +126,12 @@ pub mod test_helpers {
             self
         }
 
+        /// Paste `text`, as a terminal with bracketed paste sends it
+        pub fn paste(mut self, text: &str) -> Self {
+            self.events.push(SimulatedEventSource::paste(text));
+            self
+        }
+
         /// Quit the application (press 'q')
         pub fn quit(mut self) -> Self {
             self.events.push(SimulatedEventSource::char_key('q'));
@@ -0,0 +1,482 @@
+use crate::epub_builder::{Chapter, build_epub};
+use anyhow::{Context, Result};
+use epub::doc::EpubDoc;
+use log::info;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+/// Words that start a chapter heading when a number follows: `CHAPTER XII.`, `Part Two`
+const NUMBERED_HEADINGS: &[&str] = &["chapter", "part", "book", "volume", "act", "letter"];
+/// Headings that stand on their own
+const NAMED_HEADINGS: &[&str] = &[
+    "prologue",
+    "epilogue",
+    "preface",
+    "introduction",
+    "foreword",
+    "afterword",
+    "conclusion",
+];
+const NUMBER_WORDS: &[&str] = &[
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+    "twenty",
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "last",
+];
+/// Longer lines are prose, not headings
+const MAX_HEADING_CHARS: usize = 72;
+/// Blank lines in a row that end a section when there are no headings
+const SECTION_BREAK_LINES: usize = 3;
+/// Without headings or section breaks, a longer text is cut into parts of about this
+/// many words
+const WORDS_PER_PART: usize = 5000;
+
+/// Whether `path` names a plain text book
+pub fn is_text_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChapter {
+    pub title: String,
+    /// The chapter's lines, heading included
+    pub text: String,
+}
+
+/// The text between Project Gutenberg's `*** START OF …` and `*** END OF …` lines (all of
+/// it when they're missing), with the title from the header if it has one
+pub fn gutenberg_body(text: &str) -> (Option<String>, &str) {
+    let is_marker = |line: &str, word: &str| {
+        let upper = line.trim().to_uppercase();
+        upper.contains("PROJECT GUTENBERG")
+            && (upper.starts_with("***") && upper.contains(&format!("{word} OF"))
+                || upper.starts_with(&format!("{word} OF")))
+    };
+
+    let mut start = 0;
+    let mut end = text.len();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if start == 0 && is_marker(line, "START") {
+            start = offset + line.len();
+        } else if start > 0 && is_marker(line, "END") {
+            end = offset;
+            break;
+        }
+        offset += line.len();
+    }
+    let title = text[..start].lines().find_map(|line| {
+        line.strip_prefix("Title:")
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty())
+    });
+    (title, &text[start..end])
+}
+
+fn is_numeral(word: &str) -> bool {
+    let word = word.trim_end_matches(['.', ':', ',', '-', '—']);
+    !word.is_empty()
+        && (word.chars().all(|c| c.is_ascii_digit())
+            || word.chars().all(|c| "IVXLCDMivxlcdm".contains(c))
+            || NUMBER_WORDS.contains(&word.to_lowercase().as_str()))
+}
+
+/// Whether a line (with a blank line before it) reads as a chapter heading
+fn is_heading(line: &str) -> bool {
+    let line = line.trim();
+    if line.is_empty() || line.chars().count() > MAX_HEADING_CHARS {
+        return false;
+    }
+    let mut words = line.split_whitespace();
+    let first = words
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(['.', ':'])
+        .to_lowercase();
+    if NAMED_HEADINGS.contains(&first.as_str()) {
+        return words.next().is_none() || line.contains(['.', ':', '—']);
+    }
+    NUMBERED_HEADINGS.contains(&first.as_str()) && words.next().is_some_and(is_numeral)
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Chapters at the headings found in `text`, if there are at least two. A heading with
+/// nothing under it before the next one is part of a table of contents, so it stays
+/// in the text before it.
+fn split_at_headings(text: &str) -> Option<Vec<TextChapter>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut starts: Vec<usize> = (0..lines.len())
+        .filter(|&idx| (idx == 0 || lines[idx - 1].trim().is_empty()) && is_heading(lines[idx]))
+        .collect();
+    let section_words = |starts: &[usize], pos: usize| {
+        let end = starts.get(pos + 1).copied().unwrap_or(lines.len());
+        word_count(&lines[starts[pos] + 1..end].join("\n"))
+    };
+    let mut pos = 0;
+    while pos < starts.len() {
+        if section_words(&starts, pos) == 0 {
+            starts.remove(pos);
+        } else {
+            pos += 1;
+        }
+    }
+    if starts.len() < 2 {
+        return None;
+    }
+
+    let mut chapters = Vec::new();
+    if word_count(&lines[..starts[0]].join("\n")) > 0 {
+        chapters.push(TextChapter {
+            title: "Beginning".to_string(),
+            text: lines[..starts[0]].join("\n"),
+        });
+    }
+    for (pos, &start) in starts.iter().enumerate() {
+        let end = starts.get(pos + 1).copied().unwrap_or(lines.len());
+        chapters.push(TextChapter {
+            title: heading_title(&lines[start..end]),
+            text: lines[start..end].join("\n"),
+        });
+    }
+    Some(chapters)
+}
+
+/// The heading line, with the line after it when that's a title of its own: `CHAPTER I.`
+/// then `Loomings.` on the next line is listed as `CHAPTER I. Loomings.`
+fn heading_title(lines: &[&str]) -> String {
+    let heading = lines[0].split_whitespace().collect::<Vec<_>>().join(" ");
+    let has_own_title = heading.split_whitespace().count() > 2;
+    let mut rest = lines[1..].iter().map(|line| line.trim());
+    let subtitle = rest.by_ref().find(|line| !line.is_empty());
+    let ends_paragraph = rest.next().is_none_or(str::is_empty);
+    match subtitle {
+        Some(subtitle) if !has_own_title && ends_paragraph && is_title_case(subtitle) => {
+            format!("{heading} {subtitle}")
+        }
+        _ => heading,
+    }
+}
+
+/// A short line whose longer words are all capitalized, as titles are and sentences
+/// aren't
+fn is_title_case(line: &str) -> bool {
+    line.chars().count() <= MAX_HEADING_CHARS / 2
+        && !line.ends_with([',', ';'])
+        && line
+            .split_whitespace()
+            .filter(|word| word.chars().filter(|c| c.is_alphabetic()).count() > 3)
+            .all(|word| {
+                word.chars()
+                    .find(|c| c.is_alphabetic())
+                    .is_some_and(char::is_uppercase)
+            })
+}
+
+/// Sections separated by several blank lines in a row, each named by its first line
+fn split_at_breaks(text: &str) -> Option<Vec<TextChapter>> {
+    let mut sections = vec![Vec::new()];
+    let mut blank_run = 0;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            blank_run += 1;
+            continue;
+        }
+        if blank_run >= SECTION_BREAK_LINES && !sections.last().unwrap().is_empty() {
+            sections.push(Vec::new());
+        } else if blank_run > 0 && !sections.last().unwrap().is_empty() {
+            sections.last_mut().unwrap().push("");
+        }
+        blank_run = 0;
+        sections.last_mut().unwrap().push(line);
+    }
+    if sections.len() < 2 {
+        return None;
+    }
+    Some(
+        sections
+            .into_iter()
+            .map(|lines| TextChapter {
+                title: short_title(lines[0]),
+                text: lines.join("\n"),
+            })
+            .collect(),
+    )
+}
+
+/// Parts of about `WORDS_PER_PART` words, cut between paragraphs
+fn split_by_length(text: &str) -> Vec<TextChapter> {
+    let mut parts: Vec<TextChapter> = Vec::new();
+    let mut current = String::new();
+    let mut current_words = 0;
+    for paragraph in paragraphs(text) {
+        if current_words >= WORDS_PER_PART {
+            parts.push(TextChapter {
+                title: format!("Part {}", parts.len() + 1),
+                text: std::mem::take(&mut current),
+            });
+            current_words = 0;
+        }
+        let paragraph = paragraph.join("\n");
+        current_words += word_count(&paragraph);
+        current.push_str(&paragraph);
+        current.push_str("\n\n");
+    }
+    if current_words > 0 || parts.is_empty() {
+        parts.push(TextChapter {
+            title: format!("Part {}", parts.len() + 1),
+            text: current,
+        });
+    }
+    parts
+}
+
+fn short_title(line: &str) -> String {
+    let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(50) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line,
+    }
+}
+
+/// Split a plain text book into chapters: at chapter headings if it has them, else at
+/// runs of blank lines, else into parts of a few thousand words
+pub fn split_chapters(text: &str) -> Vec<TextChapter> {
+    split_at_headings(text)
+        .or_else(|| split_at_breaks(text))
+        .unwrap_or_else(|| split_by_length(text))
+}
+
+fn paragraphs(text: &str) -> Vec<Vec<&str>> {
+    let mut paragraphs = vec![Vec::new()];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !paragraphs.last().unwrap().is_empty() {
+                paragraphs.push(Vec::new());
+            }
+        } else {
+            paragraphs.last_mut().unwrap().push(line.trim_end());
+        }
+    }
+    paragraphs.retain(|lines| !lines.is_empty());
+    paragraphs
+}
+
+/// XHTML for a chapter. Hard-wrapped lines are joined back into paragraphs; short lines
+/// (verse, addresses, lists) keep their breaks.
+fn chapter_body(chapter: &TextChapter, has_heading: bool) -> String {
+    let mut body = String::new();
+    for (idx, lines) in paragraphs(&chapter.text).into_iter().enumerate() {
+        if idx == 0 && has_heading && lines.len() == 1 {
+            body.push_str(&format!(
+                "<h2>{}</h2>\n",
+                html_escape::encode_text(lines[0].trim())
+            ));
+            continue;
+        }
+        let is_verse = lines.len() > 1 && lines.iter().all(|line| line.trim().len() <= 45);
+        let separator = if is_verse { "<br/>\n" } else { " " };
+        let text = lines
+            .iter()
+            .map(|line| html_escape::encode_text(line.trim()).to_string())
+            .collect::<Vec<_>>()
+            .join(separator);
+        body.push_str(&format!("<p>{text}</p>\n"));
+    }
+    body
+}
+
+/// A book with the chapters found in the plain text file at `path`
+pub fn create_text_epub(path: &str) -> Result<EpubDoc<BufReader<File>>> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {path}"))?;
+    let text = String::from_utf8_lossy(&bytes)
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n");
+    let (gutenberg_title, body) = gutenberg_body(&text);
+    let chapters = split_chapters(body);
+    info!("Plain text {path} split into {} chapters", chapters.len());
+
+    let chapters: Vec<Chapter> = chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| Chapter {
+            id: format!("chapter{:04}", index + 1),
+            title: chapter.title.clone(),
+            body: chapter_body(chapter, is_heading(&chapter.title)),
+        })
+        .collect();
+    let title = gutenberg_title.unwrap_or_else(|| {
+        Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Text".to_string())
+    });
+    build_epub(
+        &title,
+        &format!("text-{}", path.replace('/', "_")),
+        &chapters,
+    )
+    .with_context(|| format!("Failed to open the book made of {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(chapters: &[TextChapter]) -> Vec<&str> {
+        chapters
+            .iter()
+            .map(|chapter| chapter.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_chapters_are_found_at_headings() {
+        let text = "\
+The Project Gutenberg eBook of Moby-Dick
+
+Title: Moby-Dick; or The Whale
+
+*** START OF THE PROJECT GUTENBERG EBOOK MOBY-DICK ***
+
+MOBY-DICK
+
+CONTENTS
+
+CHAPTER 1. Loomings.
+
+CHAPTER 2. The Carpet-Bag.
+
+
+CHAPTER 1. Loomings.
+
+Call me Ishmael. Some years ago—never mind how long
+precisely—having little or no money in my purse.
+
+CHAPTER II.
+
+The Carpet-Bag.
+
+I stuffed a shirt or two into my old carpet-bag.
+
+*** END OF THE PROJECT GUTENBERG EBOOK MOBY-DICK ***
+Licence text
+";
+        let (title, body) = gutenberg_body(text);
+        assert_eq!(title.as_deref(), Some("Moby-Dick; or The Whale"));
+        assert!(!body.contains("Title:") && !body.contains("Licence"));
+
+        let chapters = split_chapters(body);
+        assert_eq!(
+            titles(&chapters),
+            [
+                "Beginning",
+                "CHAPTER 1. Loomings.",
+                "CHAPTER II. The Carpet-Bag."
+            ]
+        );
+        // The table of contents stays with the front matter
+        assert!(chapters[0].text.contains("CHAPTER 2. The Carpet-Bag."));
+
+        let body = chapter_body(&chapters[1], true);
+        assert!(body.starts_with("<h2>CHAPTER 1. Loomings.</h2>"));
+        assert!(body.contains("how long precisely—having"));
+    }
+
+    #[test]
+    fn test_heading_heuristics() {
+        assert!(is_heading("CHAPTER XLII."));
+        assert!(is_heading("Chapter 3: The Storm"));
+        assert!(is_heading("PART TWO"));
+        assert!(is_heading("Prologue"));
+        assert!(!is_heading(
+            "Chapter and verse were quoted at length by the vicar."
+        ));
+        assert!(!is_heading("Part of the problem was the weather"));
+        assert!(!is_heading(
+            "Introduction to the evening went badly, as expected"
+        ));
+    }
+
+    #[test]
+    fn test_texts_without_headings_are_still_split() {
+        let text =
+            "The first story.\nIt was short.\n\n\n\nThe second story.\n\nIt had two paragraphs.";
+        let chapters = split_chapters(text);
+        assert_eq!(titles(&chapters), ["The first story.", "The second story."]);
+        assert_eq!(
+            chapters[1].text,
+            "The second story.\n\nIt had two paragraphs."
+        );
+
+        let paragraph = "word ".repeat(1000);
+        let long = [paragraph.as_str(); 12].join("\n\n");
+        assert_eq!(
+            titles(&split_chapters(&long)),
+            ["Part 1", "Part 2", "Part 3"]
+        );
+        assert_eq!(titles(&split_chapters("Just a note.")), ["Part 1"]);
+
+        let verse = TextChapter {
+            title: "Poem".to_string(),
+            text: "Roses are red,\nViolets are blue".to_string(),
+        };
+        assert_eq!(
+            chapter_body(&verse, false),
+            "<p>Roses are red,<br/>\nViolets are blue</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_text_file_opens_as_a_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tale.TXT");
+        fs::write(
+            &path,
+            "CHAPTER I\r\n\r\nOnce <upon> a time.\r\n\r\nCHAPTER II\r\n\r\nThe end.\r\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(is_text_file(path));
+        let mut doc = create_text_epub(path).unwrap();
+        assert_eq!(doc.get_num_chapters(), 2);
+        assert_eq!(doc.mdata("title").unwrap().value, "tale");
+        let (html, _) = doc.get_current_str().unwrap();
+        assert!(html.contains("<h2>CHAPTER I</h2>"));
+        assert!(html.contains("<p>Once &lt;upon&gt; a time.</p>"));
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+/// Word and character counts for a piece of text
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextStats {
+    pub words: usize,
+    pub characters: usize,
+    pub characters_without_spaces: usize,
+}
+
+impl TextStats {
+    /// Words are runs of non-whitespace; characters are Unicode scalar values
+    pub fn of(text: &str) -> TextStats {
+        let characters = text.chars().count();
+        let spaces = text.chars().filter(|c| c.is_whitespace()).count();
+        TextStats {
+            words: text.split_whitespace().count(),
+            characters,
+            characters_without_spaces: characters - spaces,
+        }
+    }
+}
+
+impl fmt::Display for TextStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}, {} {} ({} without spaces)",
+            self.words,
+            if self.words == 1 { "word" } else { "words" },
+            self.characters,
+            if self.characters == 1 {
+                "character"
+            } else {
+                "characters"
+            },
+            self.characters_without_spaces
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_words_and_characters() {
+        let stats = TextStats::of("Ünïcode  text,\nthree words");
+        assert_eq!(
+            stats,
+            TextStats {
+                words: 4,
+                characters: 26,
+                characters_without_spaces: 22,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "4 words, 26 characters (22 without spaces)"
+        );
+        assert_eq!(TextStats::of(" \n").words, 0);
+    }
+}
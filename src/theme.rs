@@ -1,6 +1,9 @@
-use crate::color_mode::smart_color;
+use crate::color_mode::{no_color_requested, smart_color};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Utc};
 use once_cell::sync::Lazy;
 use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
 
 // Color palette structure
 #[allow(dead_code)]
@@ -45,6 +48,239 @@ pub static OCEANIC_NEXT: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
     base_0f: smart_color(0xAB7967),
 });
 
+// High-contrast palette built from the basic ANSI colors so it works on 16-color terminals
+pub static HIGH_CONTRAST: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
+    base_00: Color::Reset,
+    base_01: Color::Black,
+    base_02: Color::Blue,
+    base_03: Color::Gray,
+    base_04: Color::White,
+    base_05: Color::White,
+    base_06: Color::White,
+    base_07: Color::White,
+    base_08: Color::LightRed,
+    base_09: Color::LightYellow,
+    base_0a: Color::Yellow,
+    base_0b: Color::LightGreen,
+    base_0c: Color::LightCyan,
+    base_0d: Color::LightBlue,
+    base_0e: Color::LightMagenta,
+    base_0f: Color::Yellow,
+});
+
+// Monochrome palette: colors are stripped after drawing (see `strip_colors`), so the
+// values here only need to keep selection (non-reset bg) and dimmed text (dark gray fg)
+// distinguishable from regular text.
+pub static MONOCHROME: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
+    base_00: Color::Reset,
+    base_01: Color::Reset,
+    base_02: Color::White,
+    base_03: Color::DarkGray,
+    base_04: Color::Reset,
+    base_05: Color::Reset,
+    base_06: Color::Reset,
+    base_07: Color::Reset,
+    base_08: Color::Reset,
+    base_09: Color::Reset,
+    base_0a: Color::Reset,
+    base_0b: Color::Reset,
+    base_0c: Color::Reset,
+    base_0d: Color::Reset,
+    base_0e: Color::Reset,
+    base_0f: Color::Reset,
+});
+
+// Solarized Light, for daytime reading on a light background
+pub static SOLARIZED_LIGHT: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
+    base_00: smart_color(0xFDF6E3),
+    base_01: smart_color(0xEEE8D5),
+    base_02: smart_color(0x93A1A1),
+    base_03: smart_color(0x839496),
+    base_04: smart_color(0x657B83),
+    base_05: smart_color(0x586E75),
+    base_06: smart_color(0x073642),
+    base_07: smart_color(0x002B36),
+    base_08: smart_color(0xDC322F),
+    base_09: smart_color(0xCB4B16),
+    base_0a: smart_color(0xB58900),
+    base_0b: smart_color(0x859900),
+    base_0c: smart_color(0x2AA198),
+    base_0d: smart_color(0x268BD2),
+    base_0e: smart_color(0x6C71C4),
+    base_0f: smart_color(0xD33682),
+});
+
+/// Built-in palettes selectable through the `theme` preference
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    OceanicNext,
+    HighContrast,
+    Monochrome,
+    SolarizedLight,
+}
+
+impl ThemePreset {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ThemePreset::HighContrast,
+            2 => ThemePreset::Monochrome,
+            3 => ThemePreset::SolarizedLight,
+            _ => ThemePreset::OceanicNext,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            ThemePreset::OceanicNext => 0,
+            ThemePreset::HighContrast => 1,
+            ThemePreset::Monochrome => 2,
+            ThemePreset::SolarizedLight => 3,
+        }
+    }
+
+    pub fn palette(self) -> &'static Base16Palette {
+        match self {
+            ThemePreset::OceanicNext => &OCEANIC_NEXT,
+            ThemePreset::HighContrast => &HIGH_CONTRAST,
+            ThemePreset::Monochrome => &MONOCHROME,
+            ThemePreset::SolarizedLight => &SOLARIZED_LIGHT,
+        }
+    }
+}
+
+static ACTIVE_THEME: AtomicU8 = AtomicU8::new(0);
+
+/// Select the palette used for drawing. `NO_COLOR` always wins over the configured preset.
+pub fn set_active_theme(preset: ThemePreset) {
+    let preset = if no_color_requested() {
+        ThemePreset::Monochrome
+    } else {
+        preset
+    };
+    ACTIVE_THEME.store(preset.as_u8(), Ordering::Relaxed);
+}
+
+pub fn active_theme() -> ThemePreset {
+    ThemePreset::from_u8(ACTIVE_THEME.load(Ordering::Relaxed))
+}
+
+/// Palette of the currently active theme preset
+pub fn current_theme() -> &'static Base16Palette {
+    active_theme().palette()
+}
+
+/// Switch between a day and a night preset by local time, either at fixed times
+/// or at sunrise/sunset when coordinates are configured
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeSchedule {
+    #[serde(default = "default_day_theme")]
+    pub day_theme: ThemePreset,
+    #[serde(default)]
+    pub night_theme: ThemePreset,
+    /// "HH:MM" local time, used when no coordinates are set
+    #[serde(default = "default_day_start")]
+    pub day_start: String,
+    #[serde(default = "default_night_start")]
+    pub night_start: String,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+}
+
+fn default_day_theme() -> ThemePreset {
+    ThemePreset::SolarizedLight
+}
+
+fn default_day_start() -> String {
+    "07:00".to_string()
+}
+
+fn default_night_start() -> String {
+    "19:00".to_string()
+}
+
+impl ThemeSchedule {
+    pub fn preset(&self, is_day: bool) -> ThemePreset {
+        if is_day {
+            self.day_theme
+        } else {
+            self.night_theme
+        }
+    }
+
+    /// Whether the schedule considers `now` to be daytime
+    pub fn is_day_at(&self, now: DateTime<Local>) -> bool {
+        if let (Some(latitude), Some(longitude)) = (self.latitude, self.longitude) {
+            match sun_times_utc(now.date_naive(), latitude, longitude) {
+                SunTimes::Rises { sunrise, sunset } => {
+                    let now = now.with_timezone(&Utc);
+                    return now >= sunrise && now < sunset;
+                }
+                SunTimes::AlwaysUp => return true,
+                SunTimes::AlwaysDown => return false,
+            }
+        }
+
+        let parse = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").ok();
+        let day_start = parse(&self.day_start).unwrap_or(NaiveTime::MIN);
+        let night_start = parse(&self.night_start).unwrap_or(NaiveTime::MIN);
+        let time = now.time();
+        if day_start <= night_start {
+            time >= day_start && time < night_start
+        } else {
+            // Day wraps around midnight
+            time >= day_start || time < night_start
+        }
+    }
+}
+
+enum SunTimes {
+    Rises {
+        sunrise: DateTime<Utc>,
+        sunset: DateTime<Utc>,
+    },
+    AlwaysUp,
+    AlwaysDown,
+}
+
+/// Sunrise and sunset for `date` using the NOAA approximation (accurate to a few minutes)
+fn sun_times_utc(date: NaiveDate, latitude: f64, longitude: f64) -> SunTimes {
+    use std::f64::consts::PI;
+
+    let gamma = 2.0 * PI / 365.0 * (date.ordinal0() as f64);
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+    let decl = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    let lat = latitude.to_radians();
+    let cos_hour_angle =
+        90.833f64.to_radians().cos() / (lat.cos() * decl.cos()) - lat.tan() * decl.tan();
+    if cos_hour_angle < -1.0 {
+        return SunTimes::AlwaysUp;
+    }
+    if cos_hour_angle > 1.0 {
+        return SunTimes::AlwaysDown;
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    let midnight = date.and_time(NaiveTime::MIN).and_utc();
+    let at_minutes = |minutes: f64| midnight + Duration::seconds((minutes * 60.0) as i64);
+    SunTimes::Rises {
+        sunrise: at_minutes(720.0 - 4.0 * (longitude + hour_angle) - eqtime),
+        sunset: at_minutes(720.0 - 4.0 * (longitude - hour_angle) - eqtime),
+    }
+}
+
 // Color utilities for focus states
 impl Base16Palette {
     pub fn get_interface_colors(
@@ -102,3 +338,36 @@ impl Base16Palette {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Timelike};
+
+    #[test]
+    fn test_sun_times_for_greenwich_equinox() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        let SunTimes::Rises { sunrise, sunset } = sun_times_utc(date, 51.48, 0.0) else {
+            panic!("expected sunrise and sunset");
+        };
+        // Roughly 06:00 and 18:15 UTC
+        assert!((5..=6).contains(&sunrise.hour()), "sunrise at {sunrise}");
+        assert!((17..=18).contains(&sunset.hour()), "sunset at {sunset}");
+    }
+
+    #[test]
+    fn test_fixed_time_schedule() {
+        let schedule = ThemeSchedule {
+            day_theme: ThemePreset::SolarizedLight,
+            night_theme: ThemePreset::OceanicNext,
+            day_start: "07:00".to_string(),
+            night_start: "19:30".to_string(),
+            latitude: None,
+            longitude: None,
+        };
+        let at = |hour, minute| Local.with_ymd_and_hms(2024, 6, 1, hour, minute, 0).unwrap();
+        assert!(!schedule.is_day_at(at(6, 59)));
+        assert!(schedule.is_day_at(at(12, 0)));
+        assert!(!schedule.is_day_at(at(19, 30)));
+    }
+}
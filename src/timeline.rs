@@ -0,0 +1,270 @@
+use crate::markdown::{Block, Document, Node};
+use chrono::NaiveDate;
+
+/// Longest label kept for an entry; dated headings can be whole sentences
+const MAX_LABEL_CHARS: usize = 60;
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// A dated heading or epigraph, e.g. a diary entry or a letter
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    pub date: NaiveDate,
+    /// False when neither the entry nor anything before it gave a year
+    pub has_year: bool,
+    pub label: String,
+    pub chapter_index: usize,
+    /// Top-level block of the chapter the entry is in
+    pub node_index: usize,
+}
+
+/// Follows dates through a book in spine order, so "4 May" after "3 May 1897" is 1897
+#[derive(Debug, Default)]
+pub struct TimelineCollector {
+    entries: Vec<TimelineEntry>,
+    year: Option<i32>,
+}
+
+impl TimelineCollector {
+    /// Pick up the dates in a chapter's headings and epigraphs
+    pub fn collect(&mut self, doc: &Document, chapter_index: usize) {
+        for (node_index, node) in doc.blocks.iter().enumerate() {
+            self.collect_node(node, chapter_index, node_index);
+        }
+    }
+
+    fn collect_node(&mut self, node: &Node, chapter_index: usize, node_index: usize) {
+        let dated = match &node.block {
+            Block::Heading { .. } => true,
+            Block::EpubBlock { epub_type, .. } => epub_type
+                .split_whitespace()
+                .any(|kind| matches!(kind, "epigraph" | "dateline")),
+            _ => false,
+        };
+        if dated {
+            let text = node
+                .plain_text()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if let Some(date) = parse_date(&text) {
+                self.add(date, text, chapter_index, node_index);
+                return;
+            }
+        }
+        if let Block::EpubBlock { content, .. } | Block::Quote { content } = &node.block {
+            for child in content {
+                self.collect_node(child, chapter_index, node_index);
+            }
+        }
+    }
+
+    fn add(&mut self, date: ParsedDate, label: String, chapter_index: usize, node_index: usize) {
+        if date.year.is_some() {
+            self.year = date.year;
+        }
+        let year = date.year.or(self.year);
+        let Some(date_value) = NaiveDate::from_ymd_opt(year.unwrap_or(1), date.month, date.day)
+        else {
+            return;
+        };
+        let label = if label.chars().count() > MAX_LABEL_CHARS {
+            let mut short: String = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+            short.push('…');
+            short
+        } else {
+            label
+        };
+        self.entries.push(TimelineEntry {
+            date: date_value,
+            has_year: year.is_some(),
+            label,
+            chapter_index,
+            node_index,
+        });
+    }
+
+    /// The entries in date order; entries on the same day keep their order in the book
+    pub fn into_timeline(mut self) -> Vec<TimelineEntry> {
+        self.entries.sort_by_key(|entry| entry.date);
+        self.entries
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ParsedDate {
+    year: Option<i32>,
+    month: u32,
+    day: u32,
+}
+
+/// Find a date in a heading: `1897-05-03`, `3 May 1897`, `3rd of May`, `May 3rd, 1897`,
+/// `Monday, May 3` or `May 1897` (the 1st)
+fn parse_date(text: &str) -> Option<ParsedDate> {
+    // Each word, and the punctuation that cuts it off from the next, like in `Chapter 3:`
+    let words: Vec<(&str, Option<char>)> = text
+        .split_whitespace()
+        .map(|token| {
+            let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+            let cut_off = token
+                .chars()
+                .last()
+                .filter(|c| matches!(c, ':' | ';' | '.' | ')'));
+            (word, cut_off)
+        })
+        .filter(|(word, _)| !word.is_empty())
+        .collect();
+
+    if let Some((iso, _)) = words.iter().find(|(word, _)| is_iso_date(word)) {
+        let parts: Vec<u32> = iso
+            .split('-')
+            .filter_map(|part| part.parse().ok())
+            .collect();
+        let (year, month, day) = (parts[0] as i32, parts[1], parts[2]);
+        return NaiveDate::from_ymd_opt(year, month, day).map(|_| ParsedDate {
+            year: Some(year),
+            month,
+            day,
+        });
+    }
+
+    let (position, month) = words
+        .iter()
+        .enumerate()
+        .find_map(|(position, (word, _))| month_number(word).map(|month| (position, month)))?;
+    let day_before = position
+        .checked_sub(1)
+        .and_then(|i| match words[i].0.eq_ignore_ascii_case("of") {
+            true => i.checked_sub(1),
+            false => Some(i),
+        })
+        .filter(|&i| words[i].1.is_none())
+        .and_then(|i| day_number(words[i].0));
+    // `Sept. 25` is an abbreviation, but `May: 25 letters` isn't a date
+    let day_after = words
+        .get(position + 1)
+        .filter(|_| matches!(words[position].1, None | Some('.')))
+        .and_then(|(word, _)| day_number(word));
+    let year = words[position + 1..]
+        .iter()
+        .take(3)
+        .find_map(|(word, _)| year_number(word));
+
+    let day = day_before.or(day_after);
+    if day.is_none() && year.is_none() {
+        return None;
+    }
+    let day = day.unwrap_or(1);
+    NaiveDate::from_ymd_opt(year.unwrap_or(2000), month, day)?;
+    Some(ParsedDate { year, month, day })
+}
+
+fn is_iso_date(word: &str) -> bool {
+    let parts: Vec<&str> = word.split('-').collect();
+    parts.len() == 3
+        && parts[0].len() == 4
+        && parts[1..].iter().all(|part| (1..=2).contains(&part.len()))
+        && parts
+            .iter()
+            .all(|part| part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// `March`, `Mar` or `Sept`
+fn month_number(word: &str) -> Option<u32> {
+    let word = word.to_lowercase();
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| *month == word || (word.len() <= 4 && month.starts_with(&word)))
+        .map(|index| index as u32 + 1)
+}
+
+/// `3`, `03` or `3rd`
+fn day_number(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(
+        suffix.to_lowercase().as_str(),
+        "" | "st" | "nd" | "rd" | "th"
+    ) {
+        return None;
+    }
+    digits
+        .parse::<u32>()
+        .ok()
+        .filter(|day| (1..=31).contains(day) && digits.len() <= 2)
+}
+
+fn year_number(word: &str) -> Option<i32> {
+    (word.len() == 4)
+        .then(|| word.parse::<i32>().ok())
+        .flatten()
+        .filter(|year| (1000..=2999).contains(year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_dates_are_parsed_in_common_heading_styles() {
+        let date = |year, month, day| Some(ParsedDate { year, month, day });
+        assert_eq!(parse_date("Letter, 1897-05-03"), date(Some(1897), 5, 3));
+        assert_eq!(parse_date("3 May. Bistritz."), date(None, 5, 3));
+        assert_eq!(parse_date("The 3rd of May, 1897"), date(Some(1897), 5, 3));
+        assert_eq!(parse_date("Monday, Sept. 25th"), date(None, 9, 25));
+        assert_eq!(parse_date("Spring: March 1914"), date(Some(1914), 3, 1));
+        assert_eq!(parse_date("Chapter 3: May I Come In"), None);
+        assert_eq!(parse_date("Mars"), None);
+    }
+
+    #[test]
+    fn test_timeline_orders_entries_by_date_and_carries_the_year() {
+        let mut collector = TimelineCollector::default();
+        let chapter = |html: &str| HtmlToMarkdownConverter::new().convert(html);
+        collector.collect(
+            &chapter("<h1>Mina's Journal</h1><h2>24 July 1897</h2><p>…</p><h2>26 July</h2>"),
+            1,
+        );
+        collector.collect(
+            &chapter(
+                r#"<section epub:type="epigraph"><p>London, 3 May 1897</p></section>
+                <h2>Letter, 25 July</h2>"#,
+            ),
+            2,
+        );
+
+        let timeline = collector.into_timeline();
+        let labels: Vec<(&str, String)> = timeline
+            .iter()
+            .map(|entry| (entry.label.as_str(), entry.date.to_string()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("London, 3 May 1897", "1897-05-03".to_string()),
+                ("24 July 1897", "1897-07-24".to_string()),
+                ("Letter, 25 July", "1897-07-25".to_string()),
+                ("26 July", "1897-07-26".to_string()),
+            ]
+        );
+        assert_eq!((timeline[0].chapter_index, timeline[0].node_index), (2, 0));
+        assert!(timeline.iter().all(|entry| entry.has_year));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::comments::Comment;
+use crate::table_of_contents::TocItem;
+
+/// Oldest entries are dropped past this many
+const MAX_ENTRIES: usize = 100;
+
+/// A reversible change. Undoing or redoing one yields the entry that reverses it again.
+#[derive(Debug, Clone)]
+pub enum UndoEntry {
+    /// The TOC as it was folded before a fold/unfold
+    TocExpansion(Vec<TocItem>),
+    /// A comment that was deleted; undo adds it back
+    CommentDeleted(Comment),
+    /// A comment that undo brought back; redo deletes it again
+    CommentRestored(Comment),
+}
+
+impl UndoEntry {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            UndoEntry::TocExpansion(_) => "TOC folding",
+            UndoEntry::CommentDeleted(_) | UndoEntry::CommentRestored(_) => "comment deletion",
+        }
+    }
+}
+
+/// Undo and redo stacks. A newly recorded change discards whatever could be redone.
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+}
+
+impl UndoHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: UndoEntry) {
+        self.redo.clear();
+        push_capped(&mut self.undo, entry);
+    }
+
+    pub fn take_undo(&mut self) -> Option<UndoEntry> {
+        self.undo.pop()
+    }
+
+    pub fn take_redo(&mut self) -> Option<UndoEntry> {
+        self.redo.pop()
+    }
+
+    /// Store the reverse of an entry that was just undone
+    pub fn push_redo(&mut self, entry: UndoEntry) {
+        push_capped(&mut self.redo, entry);
+    }
+
+    /// Store the reverse of an entry that was just redone
+    pub fn push_undo(&mut self, entry: UndoEntry) {
+        push_capped(&mut self.undo, entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+fn push_capped(stack: &mut Vec<UndoEntry>, entry: UndoEntry) {
+    if stack.len() == MAX_ENTRIES {
+        stack.remove(0);
+    }
+    stack.push(entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folded(title: &str) -> UndoEntry {
+        UndoEntry::TocExpansion(vec![TocItem::Section {
+            title: title.to_string(),
+            href: None,
+            anchor: None,
+            children: Vec::new(),
+            is_expanded: false,
+        }])
+    }
+
+    fn title(entry: &UndoEntry) -> &str {
+        match entry {
+            UndoEntry::TocExpansion(items) => items[0].title(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_new_change_clears_redo() {
+        let mut history = UndoHistory::new();
+        history.record(folded("first"));
+        history.record(folded("second"));
+
+        let undone = history.take_undo().unwrap();
+        assert_eq!(title(&undone), "second");
+        history.push_redo(folded("after second"));
+
+        history.record(folded("third"));
+        assert!(history.take_redo().is_none());
+        assert_eq!(title(&history.take_undo().unwrap()), "third");
+        assert_eq!(title(&history.take_undo().unwrap()), "first");
+        assert!(history.take_undo().is_none());
+
+        for i in 0..=MAX_ENTRIES {
+            history.record(folded(&i.to_string()));
+        }
+        assert_eq!(history.undo.len(), MAX_ENTRIES);
+        assert_eq!(title(&history.undo[0]), "1");
+    }
+}
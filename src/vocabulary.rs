@@ -0,0 +1,140 @@
+use anyhow::Result;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// A word saved while reading, ready for spaced-repetition import
+#[derive(Debug, Clone)]
+pub struct VocabularyEntry {
+    pub word: String,
+    pub definition: String,
+    pub context: String,
+    pub source: String,
+}
+
+impl VocabularyEntry {
+    fn fields(&self) -> [&str; 4] {
+        [&self.word, &self.definition, &self.context, &self.source]
+    }
+}
+
+/// Append an entry to the vocabulary file. Files ending in `.csv` get quoted CSV with a
+/// header row; anything else gets Anki-compatible TSV (one note per line, no header).
+pub fn append_entry(path: &Path, entry: &VocabularyEntry) -> Result<()> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let is_new = !path.exists();
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if is_csv {
+        if is_new {
+            writeln!(file, "word,definition,context,source")?;
+        }
+        writeln!(file, "{}", csv_row(&entry.fields()))?;
+    } else {
+        writeln!(file, "{}", tsv_row(&entry.fields()))?;
+    }
+    Ok(())
+}
+
+/// Definition of `word` from the user's dictionary command, which gets the word on stdin
+/// and in `BOOKOKCAT_WORD`, e.g. `sdcv -n --utf8-output "$BOOKOKCAT_WORD"`
+pub fn look_up_definition(command: &str, word: &str) -> Result<String, String> {
+    crate::system_command::run_text_filter(command, word, &[("BOOKOKCAT_WORD", word.to_string())])
+}
+
+/// The sentence of `paragraph` that contains `word`, used as context for a saved word
+pub fn sentence_containing(paragraph: &str, word: &str) -> String {
+    let Some(position) = paragraph.find(word) else {
+        return paragraph.trim().to_string();
+    };
+    let is_end = |c: char| matches!(c, '.' | '!' | '?');
+    let start = paragraph[..position]
+        .rfind(is_end)
+        .map_or(0, |index| index + 1);
+    let end = paragraph[position + word.len()..]
+        .find(is_end)
+        .map_or(paragraph.len(), |index| position + word.len() + index + 1);
+    paragraph[start..end].trim().to_string()
+}
+
+fn csv_row(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn tsv_row(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| field.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_containing() {
+        let paragraph = "It was late. The lamp was ephemeral, like the night! Then morning came.";
+        assert_eq!(
+            sentence_containing(paragraph, "ephemeral"),
+            "The lamp was ephemeral, like the night!"
+        );
+        assert_eq!(sentence_containing(paragraph, "late"), "It was late.");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_looked_up_definition_fills_the_definition_column() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("words.csv");
+        let definition = look_up_definition(
+            "echo \"$BOOKOKCAT_WORD: lasting a very short time\"",
+            "ephemeral",
+        )
+        .unwrap();
+        let entry = VocabularyEntry {
+            word: "ephemeral".to_string(),
+            definition,
+            context: "The lamp was ephemeral.".to_string(),
+            source: "Book".to_string(),
+        };
+        append_entry(&path, &entry).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            saved.lines().nth(1),
+            Some("ephemeral,ephemeral: lasting a very short time,The lamp was ephemeral.,Book")
+        );
+    }
+
+    #[test]
+    fn test_rows_escape_separators() {
+        assert_eq!(
+            csv_row(&["ephemeral", "", "short, \"fleeting\"", "Book"]),
+            "ephemeral,,\"short, \"\"fleeting\"\"\",Book"
+        );
+        assert_eq!(
+            tsv_row(&["ephemeral", "", "one\ttwo\nthree", "Book"]),
+            "ephemeral\t\tone two three\tBook"
+        );
+    }
+}
@@ -16,6 +16,7 @@ pub enum BookSearchAction {
     JumpToChapter {
         chapter_index: usize,
         line_number: usize,
+        query: String,
     },
     Close,
 }
@@ -79,14 +80,32 @@ impl BookSearch {
         self.focus_mode = FocusMode::Input;
     }
 
+    pub fn chapter_word_count(&self, chapter_index: usize) -> Option<usize> {
+        self.search_engine.chapter_word_count(chapter_index)
+    }
+
+    pub fn search_engine(&self) -> &SearchEngine {
+        &self.search_engine
+    }
+
     pub fn close(&mut self) {
         self.active = false;
     }
 
+    /// The results shown, with the query they were found for
+    pub fn last_results(&self) -> (&str, &[BookSearchResult]) {
+        (&self.last_search_query, &self.results)
+    }
+
     pub fn is_active(&self) -> bool {
         self.active
     }
 
+    /// Whether typing goes into the query rather than moving through the results
+    pub fn is_input_focused(&self) -> bool {
+        matches!(self.focus_mode, FocusMode::Input)
+    }
+
     pub fn update(&mut self) -> Option<BookSearchAction> {
         if let Some(ref query) = self.pending_search {
             if self.last_input_time.elapsed() > Duration::from_millis(200) {
@@ -189,6 +208,7 @@ impl BookSearch {
                     return Some(BookSearchAction::JumpToChapter {
                         chapter_index: result.chapter_index,
                         line_number: result.line_number,
+                        query: self.last_search_query.clone(),
                     });
                 }
             }
@@ -0,0 +1,143 @@
+use crate::chapter_summary::ChapterSummary;
+use crate::table_of_contents::WORDS_PER_MINUTE;
+use crate::theme::current_theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+const POPUP_WIDTH: u16 = 60;
+const POPUP_HEIGHT: u16 = 24;
+
+/// The `:summary` popup: a chapter's length, headings and most frequent terms
+pub struct ChapterSummaryPopup {
+    chapter_title: String,
+    summary: ChapterSummary,
+    scroll: u16,
+    last_popup_area: Option<Rect>,
+}
+
+impl ChapterSummaryPopup {
+    pub fn new(chapter_title: String, summary: ChapterSummary) -> Self {
+        ChapterSummaryPopup {
+            chapter_title,
+            summary,
+            scroll: 0,
+            last_popup_area: None,
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let theme = current_theme();
+        let section = |title: &str| {
+            Line::from(Span::styled(
+                title.to_string(),
+                Style::default()
+                    .fg(theme.base_0d)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+        let minutes = self.summary.words.div_ceil(WORDS_PER_MINUTE).max(1);
+        let language = self
+            .summary
+            .language
+            .as_ref()
+            .map(|language| format!(", language: {language}"))
+            .unwrap_or_default();
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!(
+                    "{} words, about {minutes} min{language}",
+                    self.summary.words
+                ),
+                Style::default().fg(theme.base_04),
+            )),
+            Line::from(""),
+            section("Headings"),
+        ];
+        if self.summary.headings.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  (none)",
+                Style::default().fg(theme.base_03),
+            )));
+        }
+        for heading in &self.summary.headings {
+            lines.push(Line::from(format!("  {heading}")));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(section("Frequent terms"));
+        if self.summary.terms.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  (none)",
+                Style::default().fg(theme.base_03),
+            )));
+        }
+        for (term, count) in &self.summary.terms {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {term}")),
+                Span::styled(format!(" ×{count}"), Style::default().fg(theme.base_03)),
+            ]));
+        }
+        lines
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = POPUP_WIDTH.min(area.width);
+        let height = POPUP_HEIGHT.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let paragraph = Paragraph::new(self.lines())
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(
+                Block::default()
+                    .title(format!(" Summary: {} ", self.chapter_title))
+                    .title_bottom(Line::from(" j/k: Scroll | ESC: Close ").right_aligned())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(current_theme().base_0c))
+                    .style(Style::default().bg(current_theme().base_00)),
+            );
+        f.render_widget(paragraph, popup_area);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    /// Returns true when the popup should close
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return true,
+            KeyCode::Char('j') | KeyCode::Down => {
+                let max_scroll = self.lines().len().saturating_sub(1) as u16;
+                self.scroll = (self.scroll + 1).min(max_scroll);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+            _ => {}
+        }
+        false
+    }
+}
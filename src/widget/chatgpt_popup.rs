@@ -22,17 +22,29 @@ pub enum SummaryState {
 
 pub struct ChatGPTPopup {
     state: SummaryState,
+    /// Where the summary comes from, shown in the title
+    source: String,
     last_popup_area: Option<Rect>,
 }
 
 impl ChatGPTPopup {
     pub fn new() -> Self {
+        Self::with_source("ChatGPT")
+    }
+
+    /// A summary popup for another summarizer, like the `summary_command` hook
+    pub fn with_source(source: &str) -> Self {
         ChatGPTPopup {
             state: SummaryState::Loading,
+            source: source.to_string(),
             last_popup_area: None,
         }
     }
 
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
     pub fn set_summary(&mut self, summary: String) {
         self.state = SummaryState::Success(summary);
     }
@@ -50,21 +62,21 @@ impl ChatGPTPopup {
 
         let (title, content) = match &self.state {
             SummaryState::Loading => (
-                " ChatGPT Summary - Loading... ",
+                format!(" {} Summary - Loading... ", self.source),
                 vec![Line::from(Span::styled(
-                    "Sending to ChatGPT for summarization...",
+                    format!("Sending to {} for summarization...", self.source),
                     Style::default().fg(Color::White),
                 ))],
             ),
             SummaryState::Success(summary) => (
-                " ChatGPT Summary ",
+                format!(" {} Summary ", self.source),
                 summary
                     .lines()
                     .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::White))))
                     .collect(),
             ),
             SummaryState::Error(error) => (
-                " ChatGPT Summary - Error ",
+                format!(" {} Summary - Error ", self.source),
                 vec![Line::from(Span::styled(
                     format!("Error: {}", error),
                     Style::default().fg(Color::White),
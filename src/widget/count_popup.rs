@@ -0,0 +1,214 @@
+use crate::inputs::KeySeq;
+use crate::main_app::VimNavMotions;
+use crate::search_engine::TermCount;
+use crate::theme::current_theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+const POPUP_WIDTH: u16 = 72;
+const TITLE_WIDTH: usize = 36;
+const BAR_WIDTH: usize = 20;
+
+pub enum CountAction {
+    /// Go to the first occurrence of the term in a chapter
+    Jump {
+        term: String,
+        chapter_index: usize,
+        line: usize,
+    },
+    Close,
+}
+
+/// The `:count` popup: how often a term comes up in each chapter
+pub struct CountPopup {
+    term: String,
+    counts: Vec<TermCount>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl CountPopup {
+    pub fn new(term: String, counts: Vec<TermCount>) -> Self {
+        let mut state = ListState::default();
+        if !counts.is_empty() {
+            state.select(Some(0));
+        }
+        CountPopup {
+            term,
+            counts,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = POPUP_WIDTH.min(area.width);
+        let height = (self.counts.len() as u16 + 4)
+            .min(area.height * 4 / 5)
+            .max(6)
+            .min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let theme = current_theme();
+        let total: usize = self.counts.iter().map(|count| count.count).sum();
+        let block = Block::default()
+            .title(format!(
+                " \"{}\": {total} in {} chapters ",
+                self.term,
+                self.counts.len()
+            ))
+            .title_bottom(Line::from(" Enter: Jump | ESC: Close ").right_aligned())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.base_0c))
+            .style(Style::default().bg(theme.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let [header_area, list_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .areas(inner);
+        let header = format!("  {:<TITLE_WIDTH$} {:>6}", "Chapter", "Count");
+        f.render_widget(
+            Paragraph::new(header).style(
+                Style::default()
+                    .fg(theme.base_0d)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            header_area,
+        );
+
+        let most = self
+            .counts
+            .iter()
+            .map(|count| count.count)
+            .max()
+            .unwrap_or(1);
+        let items: Vec<ListItem> = self
+            .counts
+            .iter()
+            .map(|count| {
+                let title: String = format!("{}. {}", count.chapter_index + 1, count.chapter_title)
+                    .chars()
+                    .take(TITLE_WIDTH)
+                    .collect();
+                let bar = "█".repeat((count.count * BAR_WIDTH).div_ceil(most));
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{title:<TITLE_WIDTH$} "),
+                        Style::default().fg(theme.base_05),
+                    ),
+                    Span::styled(
+                        format!("{:>6} ", count.count),
+                        Style::default().fg(theme.base_04),
+                    ),
+                    Span::styled(bar, Style::default().fg(theme.base_0b)),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(theme.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    fn select_offset(&mut self, delta: isize) {
+        if self.counts.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.counts.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, key_seq: &mut KeySeq) -> Option<CountAction> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.handle_j(),
+            KeyCode::Char('k') | KeyCode::Up => self.handle_k(),
+            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => self.handle_gg(),
+            KeyCode::Char('G') => self.handle_upper_g(),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_d()
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_u()
+            }
+            KeyCode::Enter => {
+                return self
+                    .state
+                    .selected()
+                    .and_then(|i| self.counts.get(i))
+                    .map(|count| CountAction::Jump {
+                        term: self.term.clone(),
+                        chapter_index: count.chapter_index,
+                        line: count.first_line,
+                    });
+            }
+            KeyCode::Esc => return Some(CountAction::Close),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl VimNavMotions for CountPopup {
+    fn handle_h(&mut self) {}
+
+    fn handle_j(&mut self) {
+        self.select_offset(1);
+    }
+
+    fn handle_k(&mut self) {
+        self.select_offset(-1);
+    }
+
+    fn handle_l(&mut self) {}
+
+    fn handle_ctrl_d(&mut self) {
+        self.select_offset(10);
+    }
+
+    fn handle_ctrl_u(&mut self) {
+        self.select_offset(-10);
+    }
+
+    fn handle_gg(&mut self) {
+        self.select_offset(-(self.counts.len() as isize));
+    }
+
+    fn handle_upper_g(&mut self) {
+        self.select_offset(self.counts.len() as isize);
+    }
+}
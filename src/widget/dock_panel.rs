@@ -0,0 +1,250 @@
+use crate::theme::Base16Palette;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+/// Lists docked under the reader with `:panel`. They take focus like the navigation
+/// panel and the reader, with Tab and `Ctrl+W`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockKind {
+    /// The last book-wide search's results
+    SearchResults,
+    /// The book's comments
+    Annotations,
+    /// The latest log lines
+    Log,
+}
+
+impl DockKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "search" => Some(DockKind::SearchResults),
+            "annotations" | "comments" => Some(DockKind::Annotations),
+            "log" => Some(DockKind::Log),
+            _ => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            DockKind::SearchResults => "Search results",
+            DockKind::Annotations => "Annotations",
+            DockKind::Log => "Log",
+        }
+    }
+
+    fn empty_hint(self) -> &'static str {
+        match self {
+            DockKind::SearchResults => "No results yet: search the book with Space+F",
+            DockKind::Annotations => "No comments in this book yet: add one with a",
+            DockKind::Log => "Nothing logged yet",
+        }
+    }
+}
+
+/// Where Enter on an entry goes
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockTarget {
+    /// A line of a chapter's text, as book search found it
+    SearchHit {
+        chapter_index: usize,
+        line_number: usize,
+        query: String,
+    },
+    /// A paragraph of a chapter
+    Paragraph {
+        chapter_href: String,
+        node_index: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DockEntry {
+    pub text: String,
+    pub target: Option<DockTarget>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DockAction {
+    /// The selection moved
+    Moved,
+    Jump(DockTarget),
+    Close,
+}
+
+pub struct DockPanel {
+    pub kind: DockKind,
+    entries: Vec<DockEntry>,
+    state: ListState,
+    /// Keep the last entry selected as entries are added, as the log does until the
+    /// selection is moved off it
+    follow_end: bool,
+}
+
+impl DockPanel {
+    pub fn new(kind: DockKind) -> Self {
+        DockPanel {
+            kind,
+            entries: Vec::new(),
+            state: ListState::default(),
+            follow_end: kind == DockKind::Log,
+        }
+    }
+
+    /// Replace the entries, keeping the selection where it was
+    pub fn set_entries(&mut self, entries: Vec<DockEntry>) {
+        self.entries = entries;
+        let last = self.entries.len().checked_sub(1);
+        let selected = if self.follow_end {
+            last
+        } else {
+            self.state
+                .selected()
+                .or(last.map(|_| 0))
+                .map(|selected| selected.min(last.unwrap_or(0)))
+        };
+        self.state.select(selected.filter(|_| last.is_some()));
+    }
+
+    pub fn selected_entry(&self) -> Option<&DockEntry> {
+        self.entries.get(self.state.selected()?)
+    }
+
+    fn select(&mut self, index: usize) {
+        let Some(last) = self.entries.len().checked_sub(1) else {
+            return;
+        };
+        let index = index.min(last);
+        self.state.select(Some(index));
+        self.follow_end = self.kind == DockKind::Log && index == last;
+    }
+
+    /// `None` for keys the panel leaves to the rest of the app
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<DockAction> {
+        let selected = self.state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.select(selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(selected.saturating_sub(1)),
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Enter => {
+                return Some(
+                    self.selected_entry()
+                        .and_then(|entry| entry.target.clone())
+                        .map_or(DockAction::Moved, DockAction::Jump),
+                );
+            }
+            KeyCode::Esc => return Some(DockAction::Close),
+            _ => return None,
+        }
+        Some(DockAction::Moved)
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, is_focused: bool, palette: &Base16Palette) {
+        let (text_color, border_color, _bg_color) = palette.get_panel_colors(is_focused);
+        let (selection_bg, selection_fg) = palette.get_selection_colors(is_focused);
+        let mut title_style = Style::default().fg(border_color);
+        if is_focused {
+            title_style = title_style.add_modifier(Modifier::BOLD);
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .title(Line::styled(
+                format!(" {} ({}) ", self.kind.title(), self.entries.len()),
+                title_style,
+            ))
+            .style(Style::default().bg(palette.base_00));
+
+        let items: Vec<ListItem> = if self.entries.is_empty() {
+            vec![ListItem::new(self.kind.empty_hint()).style(Style::default().fg(palette.base_03))]
+        } else {
+            self.entries
+                .iter()
+                .map(|entry| {
+                    ListItem::new(entry.text.as_str()).style(Style::default().fg(text_color))
+                })
+                .collect()
+        };
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(selection_bg)
+                .fg(selection_fg)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn entries(count: usize) -> Vec<DockEntry> {
+        (0..count)
+            .map(|index| DockEntry {
+                text: format!("line {index}"),
+                target: Some(DockTarget::Paragraph {
+                    chapter_href: "ch1.xhtml".to_string(),
+                    node_index: index,
+                }),
+            })
+            .collect()
+    }
+
+    fn press(panel: &mut DockPanel, code: KeyCode) -> Option<DockAction> {
+        panel.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_selection_survives_new_entries_and_the_log_follows_its_end() {
+        let mut annotations = DockPanel::new(DockKind::Annotations);
+        annotations.set_entries(entries(3));
+        press(&mut annotations, KeyCode::Char('j'));
+        annotations.set_entries(entries(5));
+        assert_eq!(
+            press(&mut annotations, KeyCode::Enter),
+            Some(DockAction::Jump(DockTarget::Paragraph {
+                chapter_href: "ch1.xhtml".to_string(),
+                node_index: 1,
+            }))
+        );
+        annotations.set_entries(entries(1));
+        assert_eq!(
+            annotations.selected_entry().map(|e| e.text.as_str()),
+            Some("line 0")
+        );
+        assert_eq!(press(&mut annotations, KeyCode::Char('x')), None);
+        assert_eq!(
+            press(&mut annotations, KeyCode::Esc),
+            Some(DockAction::Close)
+        );
+
+        let mut log = DockPanel::new(DockKind::Log);
+        log.set_entries(entries(3));
+        log.set_entries(entries(4));
+        assert_eq!(
+            log.selected_entry().map(|e| e.text.as_str()),
+            Some("line 3")
+        );
+        // Moved up, it stays put as lines come in, until sent back to the end
+        press(&mut log, KeyCode::Char('k'));
+        log.set_entries(entries(6));
+        assert_eq!(
+            log.selected_entry().map(|e| e.text.as_str()),
+            Some("line 2")
+        );
+        press(&mut log, KeyCode::Char('G'));
+        log.set_entries(entries(7));
+        assert_eq!(
+            log.selected_entry().map(|e| e.text.as_str()),
+            Some("line 6")
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::edition_compare::{ChapterPair, edition_chapters, match_chapters};
+use crate::markdown::Block as MarkdownBlock;
+use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+use crate::theme::Base16Palette;
+use anyhow::{Context, Result};
+use epub::doc::EpubDoc;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Padding, Paragraph},
+};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Another edition opened with `:compare`, shown beside the reader: the counterpart of the
+/// chapter being read, scrolled along with it
+pub struct EditionPane {
+    /// File names of the book being read and of the other edition
+    book_name: String,
+    other_name: String,
+    other: EpubDoc<BufReader<File>>,
+    pairs: Vec<ChapterPair>,
+    converter: HtmlToMarkdownConverter,
+    /// The chapter of the book being read whose counterpart is loaded, and the
+    /// counterpart's blocks: whether each is a heading, and its text
+    loaded: Option<(usize, Vec<(bool, String)>)>,
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+impl EditionPane {
+    /// Open `other_path` and line its chapters up with `book`'s. `converter` is set up the
+    /// way the reader converts chapters.
+    pub fn open<R: std::io::Read + std::io::Seek>(
+        book: &mut EpubDoc<R>,
+        book_path: &str,
+        other_path: &str,
+        converter: HtmlToMarkdownConverter,
+    ) -> Result<Self> {
+        let mut other =
+            EpubDoc::new(other_path).with_context(|| format!("Failed to open {other_path}"))?;
+        let pairs = match_chapters(&edition_chapters(book), &edition_chapters(&mut other));
+        Ok(EditionPane {
+            book_name: file_name(book_path),
+            other_name: file_name(other_path),
+            other,
+            pairs,
+            converter,
+            loaded: None,
+        })
+    }
+
+    /// How many chapters were matched, and how many are in one edition only
+    pub fn summary(&self) -> String {
+        let only_left = self
+            .pairs
+            .iter()
+            .filter(|pair| pair.right.is_none())
+            .count();
+        let only_right = self.pairs.iter().filter(|pair| pair.left.is_none()).count();
+        let matched = self.pairs.len() - only_left - only_right;
+        format!(
+            "{matched} chapters matched, {only_left} only in {}, {only_right} only in {}",
+            self.book_name, self.other_name
+        )
+    }
+
+    /// The other edition's counterpart of `chapter`, as `(spine index, heading)`
+    pub fn counterpart(&self, chapter: usize) -> Option<&(usize, String)> {
+        self.pairs
+            .iter()
+            .find(|pair| pair.left.as_ref().is_some_and(|(idx, _)| *idx == chapter))
+            .and_then(|pair| pair.right.as_ref())
+    }
+
+    /// The chapters of the other edition that come between `chapter`'s pair and the next
+    /// chapter of the book being read
+    fn only_in_other_after(&self, chapter: usize) -> Vec<&(usize, String)> {
+        let Some(start) = self
+            .pairs
+            .iter()
+            .position(|pair| pair.left.as_ref().is_some_and(|(idx, _)| *idx == chapter))
+        else {
+            return Vec::new();
+        };
+        self.pairs[start + 1..]
+            .iter()
+            .map_while(|pair| pair.left.is_none().then_some(pair.right.as_ref()).flatten())
+            .collect()
+    }
+
+    fn load(&mut self, chapter: usize) {
+        if self
+            .loaded
+            .as_ref()
+            .is_some_and(|(loaded, _)| *loaded == chapter)
+        {
+            return;
+        }
+        let blocks = self
+            .counterpart(chapter)
+            .map(|(index, _)| *index)
+            .and_then(|index| crate::chapter_diff::chapter_html(&mut self.other, index))
+            .map(|html| {
+                self.converter
+                    .convert(&html)
+                    .blocks
+                    .iter()
+                    .map(|node| {
+                        (
+                            matches!(node.block, MarkdownBlock::Heading { .. }),
+                            node.plain_text(),
+                        )
+                    })
+                    .filter(|(_, text)| !text.trim().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.loaded = Some((chapter, blocks));
+    }
+
+    /// Draw the counterpart of `chapter`, scrolled to `progress` (0 to 1) like the reader
+    pub fn render(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        chapter: usize,
+        progress: f64,
+        palette: &Base16Palette,
+    ) {
+        self.load(chapter);
+        let width = area.width.saturating_sub(4).max(1) as usize;
+        let height = area.height.saturating_sub(2) as usize;
+
+        let heading_style = Style::default()
+            .fg(palette.base_0d)
+            .add_modifier(Modifier::BOLD);
+        let alone_style = Style::default()
+            .fg(palette.base_0a)
+            .add_modifier(Modifier::BOLD);
+        let mut lines: Vec<Line> = Vec::new();
+        let title = match self.counterpart(chapter) {
+            Some((index, heading)) => {
+                let (_, blocks) = self.loaded.as_ref().expect("loaded above");
+                for (is_heading, text) in blocks {
+                    for line in textwrap::wrap(text, width) {
+                        let line = Line::from(line.into_owned());
+                        lines.push(if *is_heading {
+                            line.style(heading_style)
+                        } else {
+                            line
+                        });
+                    }
+                    lines.push(Line::default());
+                }
+                format!(" {} [{}] {heading} ", self.other_name, index + 1)
+            }
+            None => {
+                lines.push(
+                    Line::from(format!(
+                        "This chapter is only in {}: {} has nothing like it.",
+                        self.book_name, self.other_name
+                    ))
+                    .style(alone_style),
+                );
+                format!(" {} ", self.other_name)
+            }
+        };
+        let only_in_other = self.only_in_other_after(chapter);
+        if !only_in_other.is_empty() {
+            lines
+                .push(Line::from(format!("Next, only in {}:", self.other_name)).style(alone_style));
+            for (index, heading) in only_in_other {
+                lines.push(Line::from(format!("  [{}] {heading}", index + 1)).style(alone_style));
+            }
+        }
+
+        let max_offset = lines.len().saturating_sub(height);
+        let offset = (progress.clamp(0.0, 1.0) * max_offset as f64).round() as usize;
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .padding(Padding::horizontal(1))
+            .border_style(Style::default().fg(palette.base_03));
+        f.render_widget(
+            Paragraph::new(lines.split_off(offset.min(lines.len())))
+                .block(block)
+                .style(Style::default().fg(palette.base_05).bg(palette.base_00)),
+            area,
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use crate::entity_index::{Entity, Mention};
+use crate::inputs::KeySeq;
+use crate::main_app::VimNavMotions;
+use crate::theme::current_theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+/// Widest the chapter strip gets; longer books share a cell between chapters
+const STRIP_WIDTH: usize = 40;
+const NAME_WIDTH: usize = 24;
+
+pub enum EntityAction {
+    /// Go to a mention of the named entity
+    Jump {
+        name: String,
+        mention: Mention,
+    },
+    Close,
+}
+
+/// The `:entities` panel: names found in the book, how often they're mentioned and in
+/// which chapters
+pub struct EntityPopup {
+    entities: Vec<Entity>,
+    chapter_count: usize,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl EntityPopup {
+    pub fn new(entities: Vec<Entity>, chapter_count: usize) -> Self {
+        let mut state = ListState::default();
+        if !entities.is_empty() {
+            state.select(Some(0));
+        }
+        EntityPopup {
+            entities,
+            chapter_count,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    /// One cell per chapter (or group of chapters), shaded by how often the name comes up
+    fn chapter_strip(&self, entity: &Entity) -> String {
+        let cells = self.chapter_count.clamp(1, STRIP_WIDTH);
+        let mut counts = vec![0; cells];
+        for (chapter, mentions) in &entity.chapters {
+            let cell = chapter * cells / self.chapter_count.max(1);
+            counts[cell.min(cells - 1)] += mentions;
+        }
+        let busiest = counts.iter().copied().max().unwrap_or(0).max(1);
+        counts
+            .iter()
+            .map(|&count| match count {
+                0 => '·',
+                _ if count * 3 <= busiest => '░',
+                _ if count * 3 <= busiest * 2 => '▒',
+                _ => '█',
+            })
+            .collect()
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = ((NAME_WIDTH + STRIP_WIDTH + 14) as u16).min(area.width);
+        let height = (area.height * 4 / 5).max(6).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let theme = current_theme();
+        let items: Vec<ListItem> = self
+            .entities
+            .iter()
+            .map(|entity| {
+                let name: String = entity.name.chars().take(NAME_WIDTH).collect();
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{name:<NAME_WIDTH$}"),
+                        Style::default().fg(theme.base_05),
+                    ),
+                    Span::styled(
+                        format!("{:>5}  ", entity.mentions),
+                        Style::default().fg(theme.base_03),
+                    ),
+                    Span::styled(
+                        self.chapter_strip(entity),
+                        Style::default().fg(theme.base_0e),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(" Who appears where ({}) ", self.entities.len()))
+                    .title_bottom(
+                        Line::from(" Enter/f: First mention | l: Last | ESC: Close ")
+                            .right_aligned(),
+                    )
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.base_0c))
+                    .style(Style::default().bg(theme.base_00)),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(theme.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+
+        f.render_stateful_widget(list, popup_area, &mut self.state);
+    }
+
+    fn select_offset(&mut self, delta: isize) {
+        if self.entities.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.entities.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    fn jump(&self, last: bool) -> Option<EntityAction> {
+        let entity = self.entities.get(self.state.selected()?)?;
+        Some(EntityAction::Jump {
+            name: entity.name.clone(),
+            mention: if last { entity.last } else { entity.first },
+        })
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, key_seq: &mut KeySeq) -> Option<EntityAction> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.handle_j(),
+            KeyCode::Char('k') | KeyCode::Up => self.handle_k(),
+            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => self.handle_gg(),
+            KeyCode::Char('G') => self.handle_upper_g(),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_d()
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_u()
+            }
+            KeyCode::Enter | KeyCode::Char('f') => return self.jump(false),
+            KeyCode::Char('l') => return self.jump(true),
+            KeyCode::Esc => return Some(EntityAction::Close),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl VimNavMotions for EntityPopup {
+    fn handle_h(&mut self) {}
+
+    fn handle_j(&mut self) {
+        self.select_offset(1);
+    }
+
+    fn handle_k(&mut self) {
+        self.select_offset(-1);
+    }
+
+    fn handle_l(&mut self) {}
+
+    fn handle_ctrl_d(&mut self) {
+        self.select_offset(10);
+    }
+
+    fn handle_ctrl_u(&mut self) {
+        self.select_offset(-10);
+    }
+
+    fn handle_gg(&mut self) {
+        self.select_offset(-(self.entities.len() as isize));
+    }
+
+    fn handle_upper_g(&mut self) {
+        self.select_offset(self.entities.len() as isize);
+    }
+}
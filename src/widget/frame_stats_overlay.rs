@@ -0,0 +1,143 @@
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How many recent frames the average and worst frame times cover
+const RECENT_FRAMES: usize = 120;
+const OVERLAY_WIDTH: u16 = 46;
+
+/// Draw times of the most recent frames
+#[derive(Default)]
+pub struct FrameStats {
+    recent: VecDeque<Duration>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.recent.len() == RECENT_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(frame_time);
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.recent.back().copied()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        let total: Duration = self.recent.iter().sum();
+        total.checked_div(self.recent.len() as u32)
+    }
+
+    pub fn worst(&self) -> Option<Duration> {
+        self.recent.iter().max().copied()
+    }
+}
+
+/// Cache figures shown under the frame times
+pub struct CacheFigures {
+    pub layout_hits: u64,
+    pub layout_misses: u64,
+    pub cached_chapters: usize,
+    pub cached_bytes: usize,
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// The overlay's text, one entry per row
+pub fn overlay_lines(stats: &FrameStats, figures: &CacheFigures) -> Vec<String> {
+    let frame = match (stats.last(), stats.average(), stats.worst()) {
+        (Some(last), Some(average), Some(worst)) => format!(
+            "Frame: {} (avg {}, max {})",
+            millis(last),
+            millis(average),
+            millis(worst)
+        ),
+        _ => "Frame: no frames yet".to_string(),
+    };
+
+    let draws = figures.layout_hits + figures.layout_misses;
+    let layout = match (figures.layout_hits * 100).checked_div(draws) {
+        Some(percent) => format!(
+            "Layout cache: {percent}% hits ({}/{draws})",
+            figures.layout_hits
+        ),
+        None => "Layout cache: no draws yet".to_string(),
+    };
+
+    const MB: f64 = 1024.0 * 1024.0;
+    vec![
+        frame,
+        layout,
+        format!(
+            "Cached chapters: {}, ~{:.1} MB",
+            figures.cached_chapters,
+            figures.cached_bytes as f64 / MB
+        ),
+    ]
+}
+
+/// Draw the overlay in the top right corner of `area`
+pub fn render(f: &mut Frame, area: Rect, stats: &FrameStats, figures: &CacheFigures) {
+    let lines = overlay_lines(stats, figures);
+    let width = OVERLAY_WIDTH.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay_area = Rect {
+        x: area.x + area.width - width,
+        y: area.y,
+        width,
+        height,
+    };
+
+    let theme = current_theme();
+    f.render_widget(Clear, overlay_area);
+    let paragraph = Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+        .style(Style::default().fg(theme.base_05).bg(theme.base_01))
+        .block(
+            Block::default()
+                .title(" Frame stats ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.base_0e)),
+        );
+    f.render_widget(paragraph, overlay_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_lines_summarize_recent_frames_and_caches() {
+        let mut stats = FrameStats::new();
+        for ms in [2, 4, 12] {
+            stats.record(Duration::from_millis(ms));
+        }
+        let figures = CacheFigures {
+            layout_hits: 9,
+            layout_misses: 3,
+            cached_chapters: 4,
+            cached_bytes: 3 * 1024 * 1024 / 2,
+        };
+        assert_eq!(
+            overlay_lines(&stats, &figures),
+            [
+                "Frame: 12.0 ms (avg 6.0 ms, max 12.0 ms)",
+                "Layout cache: 75% hits (9/12)",
+                "Cached chapters: 4, ~1.5 MB",
+            ]
+        );
+    }
+}
@@ -1,9 +1,20 @@
 pub mod book_search;
 pub mod book_stat;
+pub mod chapter_summary_popup;
 pub mod chatgpt_popup;
+pub mod count_popup;
+pub mod dock_panel;
+pub mod edition_pane;
+pub mod entity_popup;
+pub mod frame_stats_overlay;
 pub mod help_popup;
 pub mod language_select_popup;
 pub mod navigation_panel;
+pub mod page_list_popup;
 pub mod progress_dialog;
+pub mod quick_switcher_popup;
 pub mod reading_history;
+pub mod resources_popup;
 pub mod text_reader;
+pub mod timeline_popup;
+pub mod welcome_screen;
@@ -8,6 +8,7 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
 };
+use std::collections::BTreeSet;
 use tui_textarea::TextArea;
 
 pub struct BookList {
@@ -19,6 +20,11 @@ pub struct BookList {
     search_state: SearchState,
     search_input: TextArea<'static>,
     filtered: bool, // Whether filter is currently applied
+    scroll_padding: usize,
+    /// Paths of books archived out of the list (they stay on disk and keep their state)
+    archived: BTreeSet<String>,
+    /// Listing the archived books, to restore them, instead of the library
+    show_archived: bool,
 }
 
 impl BookList {
@@ -40,9 +46,64 @@ impl BookList {
             search_state: SearchState::new(),
             search_input: TextArea::default(),
             filtered: false,
+            scroll_padding: 0,
+            archived: BTreeSet::new(),
+            show_archived: false,
         }
     }
 
+    /// Hide these books from the library (or, in the archived view, list only them)
+    pub fn set_archived(&mut self, archived: BTreeSet<String>) {
+        self.archived = archived;
+        let selected = self.selected;
+        if self.filtered {
+            self.apply_filter();
+        } else {
+            self.clear_filter();
+        }
+        let last = self.book_infos.len().saturating_sub(1);
+        self.set_selection_to_index(selected.min(last));
+    }
+
+    pub fn is_showing_archived(&self) -> bool {
+        self.show_archived
+    }
+
+    /// Switch between the library and the archived books
+    pub fn toggle_archived_view(&mut self) {
+        self.show_archived = !self.show_archived;
+        self.clear_filter();
+    }
+
+    /// Select a book by its index in the library, if it is listed
+    pub fn select_book(&mut self, book_index: usize) {
+        if let Some(position) = self.filtered_indices.iter().position(|&i| i == book_index) {
+            self.set_selection_to_index(position);
+        }
+    }
+
+    fn is_listed(&self, book: &BookInfo) -> bool {
+        self.archived.contains(&book.path) == self.show_archived
+    }
+
+    /// Rebuild the visible list from the listed books that match `keep`
+    fn list_books(&mut self, keep: impl Fn(&BookInfo) -> bool) {
+        let (indices, books): (Vec<usize>, Vec<BookInfo>) = self
+            .original_book_infos
+            .iter()
+            .enumerate()
+            .filter(|(_, book)| self.is_listed(book) && keep(book))
+            .map(|(idx, book)| (idx, book.clone()))
+            .unzip();
+        self.filtered_indices = indices;
+        self.book_infos = books;
+    }
+
+    /// Keep the selection this many rows away from the list edges
+    pub fn set_scroll_padding(&mut self, padding: usize) {
+        self.scroll_padding = padding;
+    }
+
     pub fn move_selection_down(&mut self) {
         if self.selected < self.book_infos.len().saturating_sub(1) {
             self.selected += 1;
@@ -140,11 +201,7 @@ impl BookList {
     }
 
     pub fn get_selected_original_index(&self) -> Option<usize> {
-        if self.filtered {
-            self.filtered_indices.get(self.selected).copied()
-        } else {
-            Some(self.selected)
-        }
+        self.filtered_indices.get(self.selected).copied()
     }
 
     pub fn book_count(&self) -> usize {
@@ -221,7 +278,7 @@ impl BookList {
 
         for (idx, book_info) in self.book_infos.iter().enumerate() {
             // Determine base style for this book
-            let base_style = if Some(idx) == current_book_index {
+            let base_style = if self.filtered_indices.get(idx).copied() == current_book_index {
                 Style::default().fg(palette.base_08) // Red for currently open book
             } else {
                 Style::default().fg(text_color)
@@ -288,7 +345,7 @@ impl BookList {
         }
 
         // For the currently open book, we want to keep the red color even when selected
-        let highlight_style = if Some(self.selected) == current_book_index {
+        let highlight_style = if self.get_selected_original_index() == current_book_index {
             // Currently open book is selected - keep red foreground, add selection background
             Style::default().bg(selection_bg).fg(palette.base_08) // Keep red text
         } else {
@@ -300,11 +357,16 @@ impl BookList {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Books")
+                    .title(if self.show_archived {
+                        "Archived books - a restore, A back to library"
+                    } else {
+                        "Books"
+                    })
                     .border_style(Style::default().fg(border_color))
                     .style(Style::default().bg(palette.base_00)),
             )
             .highlight_style(highlight_style)
+            .scroll_padding(self.scroll_padding)
             .style(Style::default().bg(palette.base_00));
 
         f.render_stateful_widget(files, list_area, &mut self.list_state);
@@ -388,30 +450,18 @@ impl BookList {
 
     fn filter_books_by_pattern(&mut self, pattern: &str) {
         if pattern.is_empty() {
-            self.book_infos = self.original_book_infos.clone();
-            self.filtered_indices = (0..self.original_book_infos.len()).collect();
+            self.list_books(|_| true);
             self.filtered = false;
         } else {
-            let mut filtered_books = Vec::new();
-            let mut filtered_indices = Vec::new();
-
-            for (idx, book) in self.original_book_infos.iter().enumerate() {
-                let matches = if pattern.to_lowercase().starts_with("*.") {
+            self.list_books(|book| {
+                if pattern.to_lowercase().starts_with("*.") {
                     // For extension patterns like *.epub, match against the file path
                     Self::wildcard_pattern_matches(pattern, &book.path)
                 } else {
                     // For other patterns, match against display name
                     Self::wildcard_pattern_matches(pattern, &book.display_name)
-                };
-
-                if matches {
-                    filtered_books.push(book.clone());
-                    filtered_indices.push(idx);
                 }
-            }
-
-            self.book_infos = filtered_books;
-            self.filtered_indices = filtered_indices;
+            });
             self.filtered = true;
         }
 
@@ -444,8 +494,7 @@ impl BookList {
     }
 
     pub fn clear_filter(&mut self) {
-        self.book_infos = self.original_book_infos.clone();
-        self.filtered_indices = (0..self.original_book_infos.len()).collect();
+        self.list_books(|_| true);
         self.filtered = false;
         self.selected = 0;
         self.list_state.select(Some(0));
@@ -1,8 +1,10 @@
 pub mod book_list;
+pub mod reading_order;
 pub mod table_of_contents;
 
 pub use book_list::BookList;
-pub use table_of_contents::{SelectedTocItem, TableOfContents, TocItem};
+pub use reading_order::ReadingOrderEditor;
+pub use table_of_contents::{SelectedTocItem, TableOfContents, TocItem, TocLengthIndicator};
 
 use crate::book_manager::BookManager;
 use crate::inputs::KeySeq;
@@ -22,6 +24,22 @@ pub enum NavigationPanelAction {
     },
     ToggleSection,
     SwitchToBookList,
+    EditReadingOrder,
+    SaveReadingOrder {
+        order: Vec<String>,
+    },
+    /// Mark the book up to and including this chapter as read, or as unread
+    MarkReadUpTo {
+        href: String,
+        read: bool,
+    },
+    /// Open the book whose path or URL is on the clipboard
+    OpenFromClipboard,
+    /// Hide a book from the library, or bring it back from the archive
+    SetArchived {
+        book_index: usize,
+        archived: bool,
+    },
     Bypass, // when the component assumes the upper layer should handle the action
 }
 
@@ -52,6 +70,8 @@ pub struct NavigationPanel {
     pub book_list: BookList,
     pub table_of_contents: TableOfContents,
     pub current_book_index: Option<usize>,
+    /// Set while the TOC is in reorder mode
+    pub reading_order: Option<ReadingOrderEditor>,
 }
 
 impl NavigationPanel {
@@ -61,6 +81,7 @@ impl NavigationPanel {
             book_list: BookList::new(book_manager),
             table_of_contents: TableOfContents::new(),
             current_book_index: None,
+            reading_order: None,
         }
     }
 
@@ -113,8 +134,20 @@ impl NavigationPanel {
     pub fn switch_to_toc_mode(&mut self, book_info: CurrentBookInfo) {
         self.mode = NavigationMode::TableOfContents;
 
-        if self.table_of_contents.get_current_book_info().is_none() {
-            self.table_of_contents = TableOfContents::new();
+        let same_book = self
+            .table_of_contents
+            .get_current_book_info()
+            .is_some_and(|info| info.path == book_info.path);
+        if !same_book {
+            // Fresh state for the new book, but keep the display settings
+            let mut table_of_contents = TableOfContents::new();
+            table_of_contents.set_length_indicator(self.table_of_contents.length_indicator());
+            table_of_contents.set_scroll_padding(self.table_of_contents.scroll_padding());
+            table_of_contents.set_cache_indicator(self.table_of_contents.cache_indicator());
+            table_of_contents.set_numbering(self.table_of_contents.numbering());
+            table_of_contents.set_progress_indicator(self.table_of_contents.progress_indicator());
+            table_of_contents.set_follow_locked(self.table_of_contents.is_follow_locked());
+            self.table_of_contents = table_of_contents;
             self.table_of_contents.set_current_book_info(book_info);
         } else {
             self.table_of_contents
@@ -124,6 +157,7 @@ impl NavigationPanel {
 
     pub fn switch_to_book_mode(&mut self) {
         self.mode = NavigationMode::BookSelection;
+        self.reading_order = None;
         // Keep current_book_index so we can highlight the open book
     }
 
@@ -141,18 +175,14 @@ impl NavigationPanel {
     pub fn handle_search_char(&mut self, c: char) {
         match self.mode {
             NavigationMode::BookSelection => self.book_list.handle_search_char(c),
-            NavigationMode::TableOfContents => {
-                // TOC search doesn't support character-by-character input for now
-            }
+            NavigationMode::TableOfContents => self.table_of_contents.handle_search_char(c),
         }
     }
 
     pub fn handle_search_backspace(&mut self) {
         match self.mode {
             NavigationMode::BookSelection => self.book_list.handle_search_backspace(),
-            NavigationMode::TableOfContents => {
-                // TOC search doesn't support backspace for now
-            }
+            NavigationMode::TableOfContents => self.table_of_contents.handle_search_backspace(),
         }
     }
 
@@ -173,6 +203,10 @@ impl NavigationPanel {
     /// Handle mouse click at the given position
     /// Returns true if an item was selected (for double-click handling)
     pub fn handle_mouse_click(&mut self, x: u16, y: u16, area: Rect) -> bool {
+        if self.reading_order.is_some() {
+            // The TOC underneath is hidden while reordering
+            return false;
+        }
         match self.mode {
             NavigationMode::BookSelection => self.book_list.handle_mouse_click(x, y, area),
             NavigationMode::TableOfContents => {
@@ -195,11 +229,18 @@ impl NavigationPanel {
         }
     }
 
+    /// Show TOC headings only down to `depth` levels
+    pub fn show_toc_depth(&mut self, depth: usize) {
+        if self.mode == NavigationMode::TableOfContents {
+            self.table_of_contents.expand_to_depth(depth);
+        }
+    }
+
     /// Get the currently selected index based on the mode
     pub fn get_selected_action(&self) -> SelectedActionOwned {
         match self.mode {
             NavigationMode::BookSelection => {
-                SelectedActionOwned::BookIndex(self.book_list.selected)
+                SelectedActionOwned::BookIndex(self.get_selected_book_index())
             }
             NavigationMode::TableOfContents => {
                 if let Some(item) = self.table_of_contents.get_selected_item() {
@@ -230,6 +271,11 @@ impl NavigationPanel {
                 self.book_list
                     .render(f, area, is_focused, palette, self.current_book_index);
             }
+            NavigationMode::TableOfContents if self.reading_order.is_some() => {
+                if let Some(editor) = &mut self.reading_order {
+                    editor.render(f, area, is_focused, palette);
+                }
+            }
             NavigationMode::TableOfContents => {
                 if let Some(current_idx) = self.current_book_index {
                     if let Some(book) = book_manager.get_book_info(current_idx) {
@@ -253,8 +299,27 @@ impl NavigationPanel {
     ) -> Option<NavigationPanelAction> {
         use crossterm::event::{KeyCode, KeyModifiers};
 
+        if let Some(editor) = &mut self.reading_order {
+            match key.code {
+                KeyCode::Tab | KeyCode::BackTab => return Some(NavigationPanelAction::Bypass),
+                KeyCode::Char('j') | KeyCode::Down => editor.move_selection_down(),
+                KeyCode::Char('k') | KeyCode::Up => editor.move_selection_up(),
+                KeyCode::Char('J') => editor.move_chapter_down(),
+                KeyCode::Char('K') => editor.move_chapter_up(),
+                KeyCode::Char('x') => editor.toggle_skipped(),
+                KeyCode::Enter => {
+                    let order = editor.order();
+                    self.reading_order = None;
+                    return Some(NavigationPanelAction::SaveReadingOrder { order });
+                }
+                KeyCode::Esc => self.reading_order = None,
+                _ => {}
+            }
+            return None;
+        }
+
         match key.code {
-            KeyCode::Tab => Some(NavigationPanelAction::Bypass),
+            KeyCode::Tab | KeyCode::BackTab => Some(NavigationPanelAction::Bypass),
             KeyCode::Char('/') => {
                 self.start_search();
                 None
@@ -291,6 +356,52 @@ impl NavigationPanel {
                 self.handle_upper_g();
                 None
             }
+            KeyCode::Char(c @ ('m' | 'M')) if self.mode == NavigationMode::TableOfContents => self
+                .table_of_contents
+                .selected_chapter_href()
+                .map(|href| NavigationPanelAction::MarkReadUpTo {
+                    href,
+                    read: c == 'm',
+                }),
+            KeyCode::Char('p')
+                if self.mode == NavigationMode::BookSelection
+                    && !key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Some(NavigationPanelAction::OpenFromClipboard)
+            }
+            KeyCode::Char('a') if self.mode == NavigationMode::BookSelection => self
+                .book_list
+                .get_selected_original_index()
+                .map(|book_index| NavigationPanelAction::SetArchived {
+                    book_index,
+                    archived: !self.book_list.is_showing_archived(),
+                }),
+            KeyCode::Char('A') if self.mode == NavigationMode::BookSelection => {
+                self.book_list.toggle_archived_view();
+                None
+            }
+            KeyCode::Char('z')
+                if self.mode == NavigationMode::TableOfContents
+                    && key_seq.handle_key('z') == "zz" =>
+            {
+                self.table_of_contents.center_selection();
+                None
+            }
+            KeyCode::Char('v')
+                if self.mode == NavigationMode::TableOfContents
+                    && key_seq.handle_key('v') == "zv" =>
+            {
+                self.table_of_contents.reveal_active_item();
+                None
+            }
+            KeyCode::Char('o') if self.mode == NavigationMode::TableOfContents => {
+                Some(NavigationPanelAction::EditReadingOrder)
+            }
+            KeyCode::Char('f') if self.mode == NavigationMode::TableOfContents => {
+                let locked = self.table_of_contents.is_follow_locked();
+                self.table_of_contents.set_follow_locked(!locked);
+                None
+            }
             KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.handle_ctrl_d();
                 None
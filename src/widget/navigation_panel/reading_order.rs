@@ -0,0 +1,176 @@
+use crate::theme::Base16Palette;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+/// A chapter of the book's spine as it appears in the reorder list
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadingOrderEntry {
+    pub href: String,
+    pub title: String,
+    pub skipped: bool,
+}
+
+/// The TOC's reorder mode: every chapter in a flat list that can be moved around or
+/// skipped, to build a custom reading order for next/previous chapter navigation
+pub struct ReadingOrderEditor {
+    entries: Vec<ReadingOrderEntry>,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+impl ReadingOrderEditor {
+    /// `chapters` are the spine's `(href, title)` pairs; `saved_order` is the custom order
+    /// kept so far. Chapters left out of it are listed last, marked as skipped.
+    pub fn new(chapters: Vec<(String, String)>, saved_order: &[String]) -> Self {
+        let mut entries: Vec<ReadingOrderEntry> = saved_order
+            .iter()
+            .filter_map(|href| chapters.iter().find(|(chapter, _)| chapter == href))
+            .map(|(href, title)| ReadingOrderEntry {
+                href: href.clone(),
+                title: title.clone(),
+                skipped: false,
+            })
+            .collect();
+        let skipped = !saved_order.is_empty();
+        for (href, title) in chapters {
+            if !entries.iter().any(|entry| entry.href == href) {
+                entries.push(ReadingOrderEntry {
+                    href,
+                    title,
+                    skipped,
+                });
+            }
+        }
+
+        Self {
+            entries,
+            selected: 0,
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn entries(&self) -> &[ReadingOrderEntry] {
+        &self.entries
+    }
+
+    /// The hrefs of the chapters to read, in order
+    pub fn order(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.skipped)
+            .map(|entry| entry.href.clone())
+            .collect()
+    }
+
+    pub fn move_selection_down(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_selection_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    /// Swap the selected chapter with the one below it, keeping it selected
+    pub fn move_chapter_down(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.entries.swap(self.selected, self.selected + 1);
+            self.move_selection_down();
+        }
+    }
+
+    /// Swap the selected chapter with the one above it, keeping it selected
+    pub fn move_chapter_up(&mut self) {
+        if self.selected > 0 {
+            self.entries.swap(self.selected, self.selected - 1);
+            self.move_selection_up();
+        }
+    }
+
+    pub fn toggle_skipped(&mut self) {
+        if let Some(entry) = self.entries.get_mut(self.selected) {
+            entry.skipped = !entry.skipped;
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, is_focused: bool, palette: &Base16Palette) {
+        let (text_color, border_color, _bg_color) = palette.get_panel_colors(is_focused);
+        let (selection_bg, selection_fg) = palette.get_selection_colors(is_focused);
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| {
+                let line = if entry.skipped {
+                    Line::from(Span::styled(
+                        format!("  -  {}", entry.title),
+                        Style::default()
+                            .fg(palette.base_03)
+                            .add_modifier(Modifier::CROSSED_OUT),
+                    ))
+                } else {
+                    Line::from(Span::styled(
+                        format!("{:>3}. {}", idx + 1, entry.title),
+                        Style::default().fg(text_color),
+                    ))
+                };
+                ListItem::new(line)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Reading order - J/K move, x skip, Enter save, Esc cancel")
+                    .border_style(Style::default().fg(border_color))
+                    .style(Style::default().bg(palette.base_00)),
+            )
+            .highlight_style(Style::default().bg(selection_bg).fg(selection_fg))
+            .style(Style::default().bg(palette.base_00));
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapters() -> Vec<(String, String)> {
+        ["intro", "one", "two", "appendix"]
+            .iter()
+            .map(|name| (format!("{name}.xhtml"), name.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reorder_and_skip_chapters() {
+        let mut editor = ReadingOrderEditor::new(chapters(), &[]);
+        assert_eq!(editor.order().len(), 4);
+
+        // Read "two" before "one", and leave out the appendix
+        editor.move_selection_down();
+        editor.move_chapter_down();
+        editor.move_selection_down();
+        editor.toggle_skipped();
+        let order = editor.order();
+        assert_eq!(order, ["intro.xhtml", "two.xhtml", "one.xhtml"]);
+
+        // Chapters missing from a saved order come back as skipped
+        let editor = ReadingOrderEditor::new(chapters(), &order);
+        assert_eq!(editor.order(), order);
+        assert_eq!(editor.entries()[3].title, "appendix");
+        assert!(editor.entries()[3].skipped);
+    }
+}
@@ -5,10 +5,53 @@ use crate::theme::Base16Palette;
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
 };
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tui_textarea::TextArea;
+
+/// Average reading speed used for the reading-time indicator
+pub(crate) const WORDS_PER_MINUTE: usize = 230;
+
+/// What to show next to TOC entries that start a chapter file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TocLengthIndicator {
+    #[default]
+    Off,
+    Words,
+    ReadingTime,
+}
+
+impl TocLengthIndicator {
+    pub fn next(self) -> Self {
+        match self {
+            TocLengthIndicator::Off => TocLengthIndicator::Words,
+            TocLengthIndicator::Words => TocLengthIndicator::ReadingTime,
+            TocLengthIndicator::ReadingTime => TocLengthIndicator::Off,
+        }
+    }
+
+    fn format(self, word_count: usize) -> Option<String> {
+        if word_count == 0 {
+            return None;
+        }
+        match self {
+            TocLengthIndicator::Off => None,
+            TocLengthIndicator::Words if word_count >= 1000 => {
+                Some(format!("{:.1}k words", word_count as f32 / 1000.0))
+            }
+            TocLengthIndicator::Words => Some(format!("{word_count} words")),
+            TocLengthIndicator::ReadingTime => Some(format!(
+                "{} min",
+                word_count.div_ceil(WORDS_PER_MINUTE).max(1)
+            )),
+        }
+    }
+}
 
 /// New ADT-based model for TOC items
 #[derive(Clone, Debug)]
@@ -76,6 +119,24 @@ impl TocItem {
     }
 }
 
+/// A TOC entry as search sees it. Search covers every entry, folded away or not, so
+/// matches are numbered in display order as if all sections were expanded.
+struct SearchEntry {
+    text: String,
+    /// Child indices leading to the entry from the top level; empty for the back button
+    path: Vec<usize>,
+    /// The entry's row in the list, unless a collapsed section hides it
+    row: Option<usize>,
+}
+
+fn section_path(parent: &str, title: &str) -> String {
+    if parent.is_empty() {
+        title.to_string()
+    } else {
+        format!("{parent} › {title}")
+    }
+}
+
 pub struct TableOfContents {
     pub selected_index: usize,
     pub list_state: ListState,
@@ -85,6 +146,17 @@ pub struct TableOfContents {
     manual_navigation: bool,          // True when user is manually navigating TOC
     manual_navigation_cooldown: u8,   // Grace period counter after manual navigation
     search_state: SearchState,
+    search_input: TextArea<'static>,
+    length_indicator: TocLengthIndicator,
+    chapter_word_counts: HashMap<String, usize>, // Cached per chapter file (href without anchor)
+    scroll_padding: usize,                       // Rows kept between the selection and list edges
+    cache_indicator: bool,                       // Mark chapters that are already parsed
+    cached_chapters: HashSet<String>,            // Chapter files (href without anchor) in the cache
+    follow_locked: bool,                         // Don't scroll along with the reading position
+    read_chapters: HashSet<String>,              // Chapter files (href without anchor) already read
+    numbering: bool,                             // Prefix entries with 2.3.1-style numbers
+    progress_indicator: bool,                    // Show how much of each entry has been read
+    chapter_progress: Option<(String, u32)>,     // Chapter file being read and percent scrolled
 }
 
 impl Default for TableOfContents {
@@ -107,6 +179,283 @@ impl TableOfContents {
             manual_navigation: false,
             manual_navigation_cooldown: 0,
             search_state: SearchState::new(),
+            search_input: TextArea::default(),
+            length_indicator: TocLengthIndicator::Off,
+            chapter_word_counts: HashMap::new(),
+            scroll_padding: 0,
+            cache_indicator: false,
+            cached_chapters: HashSet::new(),
+            follow_locked: false,
+            read_chapters: HashSet::new(),
+            numbering: false,
+            progress_indicator: false,
+            chapter_progress: None,
+        }
+    }
+
+    pub fn length_indicator(&self) -> TocLengthIndicator {
+        self.length_indicator
+    }
+
+    /// Keep the selection this many rows away from the list edges
+    pub fn set_scroll_padding(&mut self, padding: usize) {
+        self.scroll_padding = padding;
+    }
+
+    pub fn scroll_padding(&self) -> usize {
+        self.scroll_padding
+    }
+
+    pub fn cache_indicator(&self) -> bool {
+        self.cache_indicator
+    }
+
+    pub fn set_cache_indicator(&mut self, enabled: bool) {
+        self.cache_indicator = enabled;
+    }
+
+    pub fn is_follow_locked(&self) -> bool {
+        self.follow_locked
+    }
+
+    /// Stop or resume scrolling the list along with the section being read
+    pub fn set_follow_locked(&mut self, locked: bool) {
+        self.follow_locked = locked;
+    }
+
+    pub fn numbering(&self) -> bool {
+        self.numbering
+    }
+
+    /// Number entries hierarchically, like `2.3.1`
+    pub fn set_numbering(&mut self, enabled: bool) {
+        self.numbering = enabled;
+    }
+
+    pub fn progress_indicator(&self) -> bool {
+        self.progress_indicator
+    }
+
+    /// Show the percentage read next to entries, once reading has started on them
+    pub fn set_progress_indicator(&mut self, enabled: bool) {
+        self.progress_indicator = enabled;
+    }
+
+    /// How far into the chapter file at `chapter_href` the reader has scrolled
+    pub fn set_chapter_progress(&mut self, chapter_href: &str, percent: u32) {
+        self.chapter_progress = Some((ActiveSection::base_href(chapter_href), percent.min(100)));
+    }
+
+    /// Chapter files whose parsed content is cached; shown as a dot when the indicator is on
+    pub fn set_cached_chapters(&mut self, chapter_hrefs: HashSet<String>) {
+        self.cached_chapters = chapter_hrefs;
+    }
+
+    /// Chapter files that have been read; shown with a check mark
+    pub fn set_read_chapters(&mut self, chapter_hrefs: HashSet<String>) {
+        self.read_chapters = chapter_hrefs;
+    }
+
+    pub fn set_length_indicator(&mut self, indicator: TocLengthIndicator) {
+        self.length_indicator = indicator;
+    }
+
+    pub fn set_chapter_word_count(&mut self, chapter_href: &str, word_count: usize) {
+        self.chapter_word_counts
+            .insert(ActiveSection::base_href(chapter_href), word_count);
+    }
+
+    pub fn clear_chapter_word_counts(&mut self) {
+        self.chapter_word_counts.clear();
+    }
+
+    /// Chapter files (hrefs without anchor) referenced by the TOC, in TOC order
+    pub fn chapter_hrefs(&self) -> Vec<String> {
+        fn collect(items: &[TocItem], out: &mut Vec<String>) {
+            for item in items {
+                if let Some(href) = item.href() {
+                    let base = ActiveSection::base_href(href);
+                    if !out.contains(&base) {
+                        out.push(base);
+                    }
+                }
+                if let TocItem::Section { children, .. } = item {
+                    collect(children, out);
+                }
+            }
+        }
+
+        let mut hrefs = Vec::new();
+        if let Some(ref info) = self.current_book_info {
+            collect(&info.toc_items, &mut hrefs);
+        }
+        hrefs
+    }
+
+    /// Every TOC entry that links somewhere, as `(href, title)` in TOC order
+    pub fn titled_hrefs(&self) -> Vec<(String, String)> {
+        fn collect(items: &[TocItem], out: &mut Vec<(String, String)>) {
+            for item in items {
+                if let Some(href) = item.href() {
+                    out.push((href.to_string(), item.title().to_string()));
+                }
+                if let TocItem::Section { children, .. } = item {
+                    collect(children, out);
+                }
+            }
+        }
+
+        let mut entries = Vec::new();
+        if let Some(ref info) = self.current_book_info {
+            collect(&info.toc_items, &mut entries);
+        }
+        entries
+    }
+
+    /// Chapter files referenced by the TOC whose word count hasn't been computed yet
+    pub fn chapter_hrefs_missing_word_count(&self) -> Vec<String> {
+        self.chapter_hrefs()
+            .into_iter()
+            .filter(|href| !self.chapter_word_counts.contains_key(href))
+            .collect()
+    }
+
+    /// Entries pointing at the start of a cached chapter file get a subtle dot
+    fn is_cached(&self, item: &TocItem) -> bool {
+        self.cache_indicator
+            && item.anchor().is_none()
+            && item.href().is_some_and(|href| {
+                !href.contains('#')
+                    && self
+                        .cached_chapters
+                        .contains(&ActiveSection::base_href(href))
+            })
+    }
+
+    /// Entries pointing at the start of a chapter file that has been read get a check mark
+    fn is_read(&self, item: &TocItem) -> bool {
+        item.anchor().is_none()
+            && item.href().is_some_and(|href| {
+                !href.contains('#') && self.read_chapters.contains(&ActiveSection::base_href(href))
+            })
+    }
+
+    /// The chapter the selected entry stands for: its own href, or for a plain container
+    /// section the first one among its children
+    pub fn selected_chapter_href(&self) -> Option<String> {
+        fn first_href(item: &TocItem) -> Option<String> {
+            if let Some(href) = item.href() {
+                return Some(href.to_string());
+            }
+            match item {
+                TocItem::Section { children, .. } => children.iter().find_map(first_href),
+                TocItem::Chapter { .. } => None,
+            }
+        }
+        match self.get_selected_item()? {
+            SelectedTocItem::TocItem(item) => first_href(item),
+            SelectedTocItem::BackToBooks => None,
+        }
+    }
+
+    /// Length label for an entry; only entries pointing at the start of a file get one
+    fn length_label(&self, item: &TocItem) -> Option<String> {
+        if self.length_indicator == TocLengthIndicator::Off || item.anchor().is_some() {
+            return None;
+        }
+        let href = item.href()?;
+        if href.contains('#') {
+            return None;
+        }
+        let word_count = *self
+            .chapter_word_counts
+            .get(&ActiveSection::base_href(href))?;
+        self.length_indicator.format(word_count)
+    }
+
+    /// Percentage of an entry that has been read: read chapter files count in full and the
+    /// one being read up to the scroll position. Sections cover the files of everything
+    /// under them, weighted by length once word counts are known.
+    fn progress_percent(&self, item: &TocItem) -> Option<u32> {
+        fn files(item: &TocItem, hrefs: &mut Vec<String>) {
+            if let Some(href) = item.href() {
+                let base = ActiveSection::base_href(href);
+                if !hrefs.contains(&base) {
+                    hrefs.push(base);
+                }
+            }
+            if let TocItem::Section { children, .. } = item {
+                for child in children {
+                    files(child, hrefs);
+                }
+            }
+        }
+        // Like the check mark, a lone entry only speaks for a whole chapter file
+        if let TocItem::Chapter { href, anchor, .. } = item {
+            if anchor.is_some() || href.contains('#') {
+                return None;
+            }
+        }
+        let mut hrefs = Vec::new();
+        files(item, &mut hrefs);
+        if hrefs.is_empty() {
+            return None;
+        }
+        let weighted = hrefs
+            .iter()
+            .all(|href| self.chapter_word_counts.contains_key(href));
+        let (mut done, mut total) = (0, 0);
+        for href in &hrefs {
+            let weight = if weighted {
+                self.chapter_word_counts[href]
+            } else {
+                1
+            };
+            let percent = if self.read_chapters.contains(href) {
+                100
+            } else {
+                match &self.chapter_progress {
+                    Some((current, percent)) if current == href => *percent as usize,
+                    _ => 0,
+                }
+            };
+            done += weight * percent;
+            total += weight;
+        }
+        (total > 0).then(|| (done / total) as u32)
+    }
+
+    fn progress_label(&self, item: &TocItem) -> Option<String> {
+        if !self.progress_indicator {
+            return None;
+        }
+        self.progress_percent(item)
+            .filter(|percent| *percent > 0)
+            .map(|percent| format!("{percent}%"))
+    }
+
+    /// The number of the `index`th child of the entry numbered `parent`
+    fn child_number(parent: &str, index: usize) -> String {
+        if parent.is_empty() {
+            (index + 1).to_string()
+        } else {
+            format!("{parent}.{}", index + 1)
+        }
+    }
+
+    /// How an entry reads in the list, as searched and as rendered
+    fn entry_text(item: &TocItem, indent_level: usize, number: Option<&str>) -> String {
+        let indent = "  ".repeat(indent_level + 1);
+        let title = match number {
+            Some(number) => format!("{number} {}", item.title()),
+            None => item.title().to_string(),
+        };
+        match item {
+            TocItem::Chapter { .. } => format!("{indent}{title}"),
+            TocItem::Section { is_expanded, .. } => {
+                let section_icon = if *is_expanded { "⌄" } else { "›" };
+                format!("{indent}{section_icon} {title}")
+            }
         }
     }
 
@@ -276,7 +625,10 @@ impl TableOfContents {
                 let active_index_with_header = active_index + 1;
                 self.active_item_index = Some(active_index_with_header);
 
-                if !self.manual_navigation && self.manual_navigation_cooldown == 0 {
+                if !self.manual_navigation
+                    && self.manual_navigation_cooldown == 0
+                    && !self.follow_locked
+                {
                     self.ensure_item_visible(active_index_with_header, viewport_height);
                 }
             }
@@ -462,6 +814,61 @@ impl TableOfContents {
         }
     }
 
+    /// Scroll the list so the selected item sits in the middle of the viewport, like
+    /// vim's `zz`
+    pub fn center_selection(&mut self) {
+        let inner_height = self.last_viewport_height.saturating_sub(2); // Account for borders
+        *self.list_state.offset_mut() = self.selected_index.saturating_sub(inner_height / 2);
+        self.manual_navigation = true;
+        self.manual_navigation_cooldown = 5;
+    }
+
+    /// Fold everything except the sections leading to the item being read, then select
+    /// and center it, like vim's `zv`
+    pub fn reveal_active_item(&mut self) {
+        let Some(info) = self.current_book_info.as_ref() else {
+            return;
+        };
+        let active = info.active_section.clone();
+        let Some(path) = self.active_item_path(&info.toc_items, &active) else {
+            return;
+        };
+        if let Some(info) = self.current_book_info.as_mut() {
+            Self::set_all_expansion_state(&mut info.toc_items, false);
+            Self::expand_ancestors(&mut info.toc_items, &path);
+        }
+        let index = self
+            .current_book_info
+            .as_ref()
+            .and_then(|info| self.find_active_item_index(&info.toc_items, &active));
+        if let Some(index) = index {
+            let index_with_header = index + 1;
+            self.active_item_index = Some(index_with_header);
+            self.set_selection_to_index(index_with_header);
+            self.center_selection();
+        }
+    }
+
+    /// Where the active item sits in the tree, folded or not, as child indices from the top
+    fn active_item_path(
+        &self,
+        items: &[TocItem],
+        active_section: &ActiveSection,
+    ) -> Option<Vec<usize>> {
+        for (index, item) in items.iter().enumerate() {
+            if self.is_item_active(item, active_section) {
+                return Some(vec![index]);
+            }
+            if let TocItem::Section { children, .. } = item {
+                if let Some(mut path) = self.active_item_path(children, active_section) {
+                    path.insert(0, index);
+                    return Some(path);
+                }
+            }
+        }
+        None
+    }
+
     /// Collapse/fold the currently selected item if it's an expanded section
     pub fn collapse_selected(&mut self) {
         if let Some(ref mut current_book_info) = self.current_book_info {
@@ -520,6 +927,104 @@ impl TableOfContents {
         }
     }
 
+    /// Show headings down to `depth` levels: sections above it are expanded, the rest
+    /// collapsed
+    pub fn expand_to_depth(&mut self, depth: usize) {
+        if let Some(ref mut current_book_info) = self.current_book_info {
+            Self::set_expansion_to_depth(&mut current_book_info.toc_items, depth);
+            // Keep the selection on the list when it got shorter
+            let last_index = Self::count_visible_toc_items(&current_book_info.toc_items);
+            self.selected_index = self.selected_index.min(last_index);
+            self.list_state.select(Some(self.selected_index));
+            // Set cooldown to prevent viewport jumping
+            self.manual_navigation = true;
+            self.manual_navigation_cooldown = 5;
+        }
+    }
+
+    /// The TOC as it is folded now, to hand back to `restore_expansion` later
+    pub fn expansion_snapshot(&self) -> Option<Vec<TocItem>> {
+        self.current_book_info
+            .as_ref()
+            .map(|info| info.toc_items.clone())
+    }
+
+    /// Fold and unfold sections as they were in a snapshot. Sections are matched by
+    /// title and href, so a snapshot of a differently built TOC does nothing harmful.
+    pub fn restore_expansion(&mut self, snapshot: &[TocItem]) {
+        if let Some(ref mut current_book_info) = self.current_book_info {
+            Self::copy_expansion_states(snapshot, &mut current_book_info.toc_items);
+            self.manual_navigation = true;
+            self.manual_navigation_cooldown = 5;
+        }
+    }
+
+    /// The expanded sections, each as the titles leading to it joined by `›`
+    pub fn expanded_sections(&self) -> Option<Vec<String>> {
+        fn collect(items: &[TocItem], parent: &str, output: &mut Vec<String>) {
+            for item in items {
+                if let TocItem::Section {
+                    title,
+                    children,
+                    is_expanded,
+                    ..
+                } = item
+                {
+                    let path = section_path(parent, title);
+                    collect(children, &path, output);
+                    if *is_expanded {
+                        output.push(path);
+                    }
+                }
+            }
+        }
+        let info = self.current_book_info.as_ref()?;
+        let mut expanded = Vec::new();
+        collect(&info.toc_items, "", &mut expanded);
+        Some(expanded)
+    }
+
+    /// Expand the sections listed by `expanded_sections`, and only those
+    pub fn apply_expanded_sections(toc_items: &mut [TocItem], expanded: &[String]) {
+        fn apply(items: &mut [TocItem], parent: &str, expanded: &[String]) {
+            for item in items {
+                if let TocItem::Section {
+                    title,
+                    children,
+                    is_expanded,
+                    ..
+                } = item
+                {
+                    let path = section_path(parent, title);
+                    *is_expanded = expanded.contains(&path);
+                    apply(children, &path, expanded);
+                }
+            }
+        }
+        apply(toc_items, "", expanded);
+    }
+
+    /// Whether two snapshots of the same TOC differ in which sections are expanded
+    pub fn expansion_differs(a: &[TocItem], b: &[TocItem]) -> bool {
+        fn flags(items: &[TocItem], output: &mut Vec<bool>) {
+            for item in items {
+                if let TocItem::Section {
+                    is_expanded,
+                    children,
+                    ..
+                } = item
+                {
+                    output.push(*is_expanded);
+                    flags(children, output);
+                }
+            }
+        }
+        let (mut a_flags, mut b_flags) = (Vec::new(), Vec::new());
+        flags(a, &mut a_flags);
+        flags(b, &mut b_flags);
+        a_flags != b_flags
+    }
+
     /// Helper to set expansion state for all sections
     fn set_all_expansion_state(toc_items: &mut [TocItem], expand: bool) {
         for item in toc_items {
@@ -538,6 +1043,20 @@ impl TableOfContents {
         }
     }
 
+    fn set_expansion_to_depth(toc_items: &mut [TocItem], depth: usize) {
+        for item in toc_items {
+            if let TocItem::Section {
+                is_expanded,
+                children,
+                ..
+            } = item
+            {
+                *is_expanded = depth > 1;
+                Self::set_expansion_to_depth(children, depth.saturating_sub(1));
+            }
+        }
+    }
+
     /// Helper to find and toggle expansion at a specific index
     fn toggle_expansion_at_index(
         toc_items: &mut [TocItem],
@@ -623,6 +1142,7 @@ impl TableOfContents {
     /// Handle mouse click at the given position
     /// Returns true if an item was clicked
     pub fn handle_mouse_click(&mut self, x: u16, y: u16, area: Rect) -> bool {
+        let (_, area) = self.split_search_area(area);
         // Account for the border (1 line at top and bottom)
         if y > area.y && y < area.y + area.height - 1 {
             let relative_y = y - area.y - 1; // Subtract 1 for the top border
@@ -796,6 +1316,7 @@ impl TableOfContents {
         palette: &Base16Palette,
         book_display_name: &str,
     ) {
+        let (search_area, area) = self.split_search_area(area);
         // Store viewport height for scroll calculations
         self.last_viewport_height = area.height as usize;
         let Some(ref current_book_info) = self.current_book_info else {
@@ -821,6 +1342,7 @@ impl TableOfContents {
 
         // Render TOC items
         let mut toc_item_index = 1; // Start at 1 because 0 is the back button
+        let mut search_entry_index = 1;
         self.render_toc_items(
             current_book_info,
             &mut items,
@@ -828,9 +1350,14 @@ impl TableOfContents {
             &current_book_info.toc_items,
             0,
             &mut toc_item_index,
+            &mut search_entry_index,
             is_focused,
+            "",
         );
-        let title = format!("{book_display_name} - Book");
+        let mut title = format!("{book_display_name} - Book");
+        if self.follow_locked {
+            title.push_str(" [locked]");
+        }
         let mut toc_list = List::new(items)
             .block(
                 Block::default()
@@ -839,6 +1366,7 @@ impl TableOfContents {
                     .border_style(Style::default().fg(border_color))
                     .style(Style::default().bg(palette.base_00)),
             )
+            .scroll_padding(self.scroll_padding)
             .style(Style::default().bg(palette.base_00));
 
         if is_focused {
@@ -846,6 +1374,82 @@ impl TableOfContents {
         }
 
         f.render_stateful_widget(toc_list, area, &mut self.list_state);
+
+        if let Some(search_area) = search_area {
+            self.render_search_input(f, search_area, is_focused, palette);
+        }
+    }
+
+    /// While a query is being typed, the list makes room for the search box above it
+    fn split_search_area(&self, area: Rect) -> (Option<Rect>, Rect) {
+        if self.search_state.mode != SearchMode::InputMode || area.height <= 4 {
+            return (None, area);
+        }
+        let search_area = Rect { height: 3, ..area };
+        let list_area = Rect {
+            y: area.y + 3,
+            height: area.height - 3,
+            ..area
+        };
+        (Some(search_area), list_area)
+    }
+
+    fn render_search_input(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        is_focused: bool,
+        palette: &Base16Palette,
+    ) {
+        let (text_color, border_color, _bg_color) = palette.get_panel_colors(is_focused);
+        let matches = if self.search_state.query.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", self.search_state.get_match_info())
+        };
+        let search_block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Search: {matches}(Enter to keep, Esc to cancel)"))
+            .border_style(Style::default().fg(border_color))
+            .style(Style::default().bg(palette.base_00));
+
+        let inner_area = search_block.inner(area);
+        f.render_widget(search_block, area);
+
+        if inner_area.height > 0 && inner_area.width > 0 {
+            let input_style = Style::default().fg(text_color).bg(palette.base_00);
+            let cursor_style = Style::default()
+                .fg(Color::Yellow)
+                .bg(palette.base_00)
+                .add_modifier(Modifier::UNDERLINED);
+
+            self.search_input.set_style(input_style);
+            self.search_input.set_cursor_style(cursor_style);
+            f.render_widget(&self.search_input, inner_area);
+        }
+    }
+
+    pub fn handle_search_char(&mut self, c: char) {
+        if self.search_state.mode == SearchMode::InputMode {
+            self.search_input.insert_char(c);
+            let query = self.get_search_input();
+            self.update_search_query(&query);
+        }
+    }
+
+    pub fn handle_search_backspace(&mut self) {
+        if self.search_state.mode == SearchMode::InputMode {
+            let (_, col) = self.search_input.cursor();
+            if col > 0 {
+                self.search_input.delete_char();
+            }
+            let query = self.get_search_input();
+            self.update_search_query(&query);
+        }
+    }
+
+    fn get_search_input(&self) -> String {
+        self.search_input.lines().join("")
     }
 
     /// Render TOC items using the new ADT structure
@@ -858,12 +1462,20 @@ impl TableOfContents {
         toc_items: &[TocItem],
         indent_level: usize,
         toc_item_index: &mut usize,
+        search_entry_index: &mut usize,
         is_focused: bool,
+        number_prefix: &str,
     ) {
         let (text_color, _border_color, _bg_color) = palette.get_panel_colors(is_focused);
-        for item in toc_items {
+        for (index, item) in toc_items.iter().enumerate() {
+            let number = Self::child_number(number_prefix, index);
+            let full_text = Self::entry_text(
+                item,
+                indent_level,
+                self.numbering.then_some(number.as_str()),
+            );
             match item {
-                TocItem::Chapter { title, .. } => {
+                TocItem::Chapter { .. } => {
                     // Render a simple chapter
                     let should_highlight =
                         self.should_highlight_item(item, &current_book.active_section);
@@ -874,15 +1486,14 @@ impl TableOfContents {
                     };
 
                     let indent = "  ".repeat(indent_level + 1);
-                    let full_text = format!("{indent}{title}");
 
                     // Check if this item matches search
-                    let chapter_content = if self.search_state.active
-                        && self.search_state.is_match(*toc_item_index)
+                    let mut chapter_content = if self.search_state.active
+                        && self.search_state.is_match(*search_entry_index)
                     {
                         self.create_highlighted_line_with_indent(
                             &full_text,
-                            *toc_item_index,
+                            *search_entry_index,
                             base_color,
                             palette,
                             indent.len(),
@@ -893,17 +1504,33 @@ impl TableOfContents {
                             Style::default().fg(base_color),
                         )])
                     };
+                    if self.is_read(item) {
+                        chapter_content
+                            .push_span(Span::styled(" ✓", Style::default().fg(palette.base_0b)));
+                    }
+                    if self.is_cached(item) {
+                        chapter_content
+                            .push_span(Span::styled(" •", Style::default().fg(palette.base_03)));
+                    }
+                    if let Some(label) = self.length_label(item) {
+                        chapter_content.push_span(Span::styled(
+                            format!(" · {label}"),
+                            Style::default().fg(palette.base_03),
+                        ));
+                    }
+                    if let Some(label) = self.progress_label(item) {
+                        chapter_content.push_span(Span::styled(
+                            format!(" · {label}"),
+                            Style::default().fg(palette.base_03),
+                        ));
+                    }
                     items.push(ListItem::new(chapter_content));
                 }
                 TocItem::Section {
-                    title,
-
                     children,
                     is_expanded,
                     ..
                 } => {
-                    let section_icon = if *is_expanded { "⌄" } else { "›" };
-
                     let should_highlight =
                         self.should_highlight_item(item, &current_book.active_section);
                     let base_color = if should_highlight {
@@ -913,15 +1540,14 @@ impl TableOfContents {
                     };
 
                     let indent = "  ".repeat(indent_level + 1);
-                    let full_text = format!("{indent}{section_icon} {title}");
 
                     // Check if this item matches search
-                    let section_content = if self.search_state.active
-                        && self.search_state.is_match(*toc_item_index)
+                    let mut section_content = if self.search_state.active
+                        && self.search_state.is_match(*search_entry_index)
                     {
                         self.create_highlighted_line_with_indent(
                             &full_text,
-                            *toc_item_index,
+                            *search_entry_index,
                             base_color,
                             palette,
                             indent.len(),
@@ -932,9 +1558,30 @@ impl TableOfContents {
                             Style::default().fg(base_color),
                         )])
                     };
+                    if self.is_read(item) {
+                        section_content
+                            .push_span(Span::styled(" ✓", Style::default().fg(palette.base_0b)));
+                    }
+                    if self.is_cached(item) {
+                        section_content
+                            .push_span(Span::styled(" •", Style::default().fg(palette.base_03)));
+                    }
+                    if let Some(label) = self.length_label(item) {
+                        section_content.push_span(Span::styled(
+                            format!(" · {label}"),
+                            Style::default().fg(palette.base_03),
+                        ));
+                    }
+                    if let Some(label) = self.progress_label(item) {
+                        section_content.push_span(Span::styled(
+                            format!(" · {label}"),
+                            Style::default().fg(palette.base_03),
+                        ));
+                    }
                     items.push(ListItem::new(section_content));
 
                     *toc_item_index += 1; // Increment for the section itself
+                    *search_entry_index += 1;
 
                     // Render children if expanded
                     if *is_expanded {
@@ -945,8 +1592,13 @@ impl TableOfContents {
                             children,
                             indent_level + 1,
                             toc_item_index,
+                            search_entry_index,
                             is_focused,
+                            &number,
                         );
+                    } else {
+                        // Search still numbers the entries folded away
+                        *search_entry_index += Self::count_all_toc_items(children);
                     }
 
                     continue; // Skip the increment at the end of the loop since we already did it
@@ -954,6 +1606,7 @@ impl TableOfContents {
             }
 
             *toc_item_index += 1;
+            *search_entry_index += 1;
         }
     }
 
@@ -1093,45 +1746,113 @@ impl TableOfContents {
         Line::from(spans)
     }
 
-    /// Helper method to collect all visible TOC items with their display text
-    fn collect_visible_items(&self) -> Vec<String> {
-        let mut items = Vec::new();
-
-        // Add the back button
-        items.push("← Books List".to_string());
-
-        // Add TOC items
+    /// Every TOC entry with its display text, in the order of the fully expanded list
+    fn search_entries(&self) -> Vec<SearchEntry> {
+        let mut entries = vec![SearchEntry {
+            text: "← Books List".to_string(),
+            path: Vec::new(),
+            row: Some(0),
+        }];
         if let Some(ref book_info) = self.current_book_info {
-            Self::collect_toc_items_text(&book_info.toc_items, &mut items, 0);
+            Self::collect_search_entries(
+                &book_info.toc_items,
+                0,
+                &mut Vec::new(),
+                &mut 1,
+                true,
+                self.numbering.then_some(""),
+                &mut entries,
+            );
         }
-
-        items
+        entries
     }
 
-    /// Recursively collect text from TOC items
-    fn collect_toc_items_text(toc_items: &[TocItem], items: &mut Vec<String>, indent_level: usize) {
-        for item in toc_items {
-            match item {
-                TocItem::Chapter { title, .. } => {
-                    let indent = "  ".repeat(indent_level + 1);
-                    items.push(format!("{indent}{title}"));
-                }
-                TocItem::Section {
-                    title,
+    /// Recursively collect the entries below `toc_items`; `visible` is false under a
+    /// collapsed section
+    fn collect_search_entries(
+        toc_items: &[TocItem],
+        indent_level: usize,
+        path: &mut Vec<usize>,
+        row: &mut usize,
+        visible: bool,
+        number_prefix: Option<&str>,
+        entries: &mut Vec<SearchEntry>,
+    ) {
+        for (index, item) in toc_items.iter().enumerate() {
+            path.push(index);
+            let number = number_prefix.map(|prefix| Self::child_number(prefix, index));
+            let text = Self::entry_text(item, indent_level, number.as_deref());
+            entries.push(SearchEntry {
+                text,
+                path: path.clone(),
+                row: visible.then_some(*row),
+            });
+            if visible {
+                *row += 1;
+            }
+            if let TocItem::Section {
+                children,
+                is_expanded,
+                ..
+            } = item
+            {
+                Self::collect_search_entries(
                     children,
-                    is_expanded,
-                    ..
-                } => {
-                    let section_icon = if *is_expanded { "⌄" } else { "›" };
-                    let indent = "  ".repeat(indent_level + 1);
-                    items.push(format!("{indent}{section_icon} {title}"));
-
-                    // Only collect children if expanded
-                    if *is_expanded {
-                        Self::collect_toc_items_text(children, items, indent_level + 1);
-                    }
-                }
+                    indent_level + 1,
+                    path,
+                    row,
+                    visible && *is_expanded,
+                    number.as_deref(),
+                    entries,
+                );
             }
+            path.pop();
+        }
+    }
+
+    /// Count every item below `toc_items`, expanded or not
+    fn count_all_toc_items(toc_items: &[TocItem]) -> usize {
+        toc_items
+            .iter()
+            .map(|item| match item {
+                TocItem::Section { children, .. } => 1 + Self::count_all_toc_items(children),
+                TocItem::Chapter { .. } => 1,
+            })
+            .sum()
+    }
+
+    /// The search entry shown at `row`
+    fn search_entry_at_row(&self, row: usize) -> usize {
+        self.search_entries()
+            .iter()
+            .position(|entry| entry.row == Some(row))
+            .unwrap_or(0)
+    }
+
+    /// The row of a search entry, or the top if it's folded away
+    fn row_of_search_entry(&self, entry: usize) -> usize {
+        self.search_entries()
+            .get(entry)
+            .and_then(|entry| entry.row)
+            .unwrap_or(0)
+    }
+
+    /// Expand the sections on the way to the item at `path`
+    fn expand_ancestors(toc_items: &mut [TocItem], path: &[usize]) {
+        let Some((&index, rest)) = path.split_first() else {
+            return;
+        };
+        if rest.is_empty() {
+            return;
+        }
+        if let Some(TocItem::Section {
+            children,
+            is_expanded,
+            ..
+        }) = toc_items.get_mut(index)
+        {
+            *is_expanded = true;
+            Self::expand_ancestors(children, rest);
         }
     }
 
@@ -1146,12 +1867,14 @@ impl TableOfContents {
 
 impl SearchablePanel for TableOfContents {
     fn start_search(&mut self) {
-        self.search_state.start_search(self.selected_index);
+        self.search_state
+            .start_search(self.search_entry_at_row(self.selected_index));
+        self.search_input = TextArea::default();
     }
 
     fn cancel_search(&mut self) {
         let original_position = self.search_state.cancel_search();
-        self.set_selection_to_index(original_position);
+        self.set_selection_to_index(self.row_of_search_entry(original_position));
     }
 
     fn confirm_search(&mut self) {
@@ -1159,7 +1882,7 @@ impl SearchablePanel for TableOfContents {
         // If search was cancelled (empty query), restore position
         if !self.search_state.active {
             let original_position = self.search_state.original_position;
-            self.set_selection_to_index(original_position);
+            self.set_selection_to_index(self.row_of_search_entry(original_position));
         }
     }
 
@@ -1171,7 +1894,7 @@ impl SearchablePanel for TableOfContents {
     fn update_search_query(&mut self, query: &str) {
         self.search_state.update_query(query.to_string());
 
-        // Find matches in visible TOC items
+        // Find matches in all TOC items, including those in collapsed sections
         let searchable = self.get_searchable_content();
         let matches = find_matches_in_text(query, &searchable);
         self.search_state.set_matches(matches);
@@ -1198,7 +1921,7 @@ impl SearchablePanel for TableOfContents {
             }
         } else {
             // No current match, find the first match after current selected position
-            let current_position = self.selected_index;
+            let current_position = self.search_entry_at_row(self.selected_index);
 
             // Find the first match that's after the current position
             let mut next_match_idx = None;
@@ -1239,7 +1962,7 @@ impl SearchablePanel for TableOfContents {
             }
         } else {
             // No current match, find the last match before current selected position
-            let current_position = self.selected_index;
+            let current_position = self.search_entry_at_row(self.selected_index);
 
             // Find the last match that's before the current position
             let mut prev_match_idx = None;
@@ -1272,14 +1995,30 @@ impl SearchablePanel for TableOfContents {
         !self.search_state.matches.is_empty()
     }
 
+    /// Select the entry `match_index`, first unfolding the sections it's hidden in
     fn jump_to_match(&mut self, match_index: usize) {
-        if match_index < self.get_total_items() {
-            self.set_selection_to_index(match_index);
+        let Some(entry) = self.search_entries().into_iter().nth(match_index) else {
+            return;
+        };
+        if entry.row.is_none() {
+            if let Some(book_info) = self.current_book_info.as_mut() {
+                Self::expand_ancestors(&mut book_info.toc_items, &entry.path);
+            }
+        }
+        if let Some(row) = self
+            .search_entries()
+            .get(match_index)
+            .and_then(|entry| entry.row)
+        {
+            self.set_selection_to_index(row);
         }
     }
 
     fn get_searchable_content(&self) -> Vec<String> {
-        self.collect_visible_items()
+        self.search_entries()
+            .into_iter()
+            .map(|entry| entry.text)
+            .collect()
     }
 }
 
@@ -1287,3 +2026,206 @@ pub enum SelectedTocItem<'a> {
     BackToBooks,
     TocItem(&'a TocItem),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(title: &str) -> TocItem {
+        TocItem::Chapter {
+            title: title.to_string(),
+            href: format!("{}.xhtml", title.to_lowercase().replace(' ', "_")),
+            anchor: None,
+        }
+    }
+
+    fn section(title: &str, children: Vec<TocItem>) -> TocItem {
+        TocItem::Section {
+            title: title.to_string(),
+            href: None,
+            anchor: None,
+            children,
+            is_expanded: false,
+        }
+    }
+
+    #[test]
+    fn test_search_finds_and_unfolds_entries_in_collapsed_sections() {
+        let mut toc = TableOfContents::new();
+        toc.set_current_book_info(CurrentBookInfo {
+            path: "book.epub".to_string(),
+            toc_items: vec![
+                chapter("Preface"),
+                section(
+                    "Part One",
+                    vec![
+                        chapter("Loomings"),
+                        section("Interlude", vec![chapter("Whale Song")]),
+                    ],
+                ),
+                chapter("The Whale"),
+            ],
+            current_chapter: 0,
+            current_chapter_href: None,
+            active_section: ActiveSection::new(0, "preface.xhtml".to_string(), None),
+        });
+        assert_eq!(toc.get_total_items(), 4);
+
+        toc.start_search();
+        for c in "whale".chars() {
+            toc.handle_search_char(c);
+        }
+        // "Whale Song" comes first in book order and is two sections deep
+        assert_eq!(toc.get_search_state().get_match_info(), "[1/2]");
+        assert_eq!(toc.selected_index, 5);
+        assert_eq!(toc.get_total_items(), 7);
+
+        toc.confirm_search();
+        toc.next_match();
+        assert_eq!(toc.selected_index, 6);
+        toc.previous_match();
+        assert_eq!(toc.selected_index, 5);
+    }
+
+    #[test]
+    fn test_zv_unfolds_only_the_way_to_the_active_item() {
+        let expanded = |title: &str, children| {
+            let mut item = section(title, children);
+            if let TocItem::Section { is_expanded, .. } = &mut item {
+                *is_expanded = true;
+            }
+            item
+        };
+        let mut toc = TableOfContents::new();
+        toc.set_current_book_info(CurrentBookInfo {
+            path: "book.epub".to_string(),
+            toc_items: vec![
+                chapter("Preface"),
+                section(
+                    "Part One",
+                    vec![
+                        chapter("Loomings"),
+                        section("Interlude", vec![chapter("Whale Song")]),
+                    ],
+                ),
+                expanded("Part Two", vec![chapter("The Chase"), chapter("Epilogue")]),
+            ],
+            current_chapter: 3,
+            current_chapter_href: None,
+            active_section: ActiveSection::new(3, "whale_song.xhtml".to_string(), None),
+        });
+        toc.last_viewport_height = 6;
+
+        toc.reveal_active_item();
+        // Part One and Interlude open, Part Two folded away
+        assert_eq!(toc.get_total_items(), 7);
+        assert_eq!(toc.selected_index, 5);
+        assert_eq!(toc.list_state.offset(), 3);
+
+        toc.set_selection_to_index(1);
+        toc.center_selection();
+        assert_eq!(toc.list_state.offset(), 0);
+    }
+
+    #[test]
+    fn test_numbering_and_progress_cover_nested_sections() {
+        let mut toc = TableOfContents::new();
+        toc.set_current_book_info(CurrentBookInfo {
+            path: "book.epub".to_string(),
+            toc_items: vec![
+                chapter("Preface"),
+                section(
+                    "Part One",
+                    vec![
+                        chapter("Loomings"),
+                        section("Interlude", vec![chapter("Whale Song")]),
+                    ],
+                ),
+            ],
+            current_chapter: 1,
+            current_chapter_href: None,
+            active_section: ActiveSection::new(1, "loomings.xhtml".to_string(), None),
+        });
+        toc.set_numbering(true);
+        let texts: Vec<String> = toc.search_entries().into_iter().map(|e| e.text).collect();
+        assert_eq!(texts[1], "  1 Preface");
+        assert_eq!(texts[2], "  › 2 Part One");
+        assert_eq!(texts[5], "      2.2.1 Whale Song");
+
+        toc.set_read_chapters(HashSet::from(["preface.xhtml".to_string()]));
+        toc.set_chapter_progress("loomings.xhtml#start", 50);
+        let info = toc.get_current_book_info().unwrap().clone();
+        let [preface, part_one] = &info.toc_items[..] else {
+            panic!("two top-level entries");
+        };
+        assert_eq!(toc.progress_percent(preface), Some(100));
+        // Loomings half read, Whale Song not started
+        assert_eq!(toc.progress_percent(part_one), Some(25));
+        toc.set_chapter_word_count("loomings.xhtml", 3000);
+        toc.set_chapter_word_count("whale_song.xhtml", 1000);
+        assert_eq!(toc.progress_percent(part_one), Some(37));
+    }
+
+    #[test]
+    fn test_length_labels_show_words_or_minutes_once_counted() {
+        let mut toc = TableOfContents::new();
+        let mut anchored = chapter("Loomings");
+        if let TocItem::Chapter { anchor, .. } = &mut anchored {
+            *anchor = Some("part-2".to_string());
+        }
+        toc.set_current_book_info(CurrentBookInfo {
+            path: "book.epub".to_string(),
+            toc_items: vec![
+                chapter("Preface"),
+                chapter("Loomings"),
+                chapter("The Whale"),
+            ],
+            current_chapter: 0,
+            current_chapter_href: None,
+            active_section: ActiveSection::new(0, "preface.xhtml".to_string(), None),
+        });
+        toc.set_length_indicator(TocLengthIndicator::Words);
+
+        // Nothing counted yet: no labels, and every chapter is waiting for a count
+        assert_eq!(toc.length_label(&chapter("Preface")), None);
+        assert_eq!(
+            toc.chapter_hrefs_missing_word_count(),
+            ["preface.xhtml", "loomings.xhtml", "the_whale.xhtml"]
+        );
+
+        toc.set_chapter_word_count("preface.xhtml", 450);
+        toc.set_chapter_word_count("loomings.xhtml#start", 2345);
+        assert_eq!(toc.chapter_hrefs_missing_word_count(), ["the_whale.xhtml"]);
+        assert_eq!(
+            toc.length_label(&chapter("Preface")).as_deref(),
+            Some("450 words")
+        );
+        assert_eq!(
+            toc.length_label(&chapter("Loomings")).as_deref(),
+            Some("2.3k words")
+        );
+        // Only entries at the start of a file get one
+        assert_eq!(toc.length_label(&anchored), None);
+        assert_eq!(toc.length_label(&chapter("The Whale")), None);
+
+        toc.set_length_indicator(TocLengthIndicator::ReadingTime);
+        assert_eq!(
+            toc.length_label(&chapter("Preface")).as_deref(),
+            Some("2 min")
+        );
+        assert_eq!(
+            toc.length_label(&chapter("Loomings")).as_deref(),
+            Some("11 min")
+        );
+        toc.set_chapter_word_count("the_whale.xhtml", 0);
+        assert_eq!(toc.length_label(&chapter("The Whale")), None);
+        assert!(toc.chapter_hrefs_missing_word_count().is_empty());
+
+        toc.set_length_indicator(TocLengthIndicator::Off);
+        assert_eq!(toc.length_label(&chapter("Preface")), None);
+
+        // Counts are dropped with the book they belong to
+        toc.clear_chapter_word_counts();
+        assert_eq!(toc.chapter_hrefs_missing_word_count().len(), 3);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::parsing::toc_parser::PageTarget;
+use crate::theme::current_theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+const POPUP_WIDTH: u16 = 44;
+const POPUP_HEIGHT: u16 = 6;
+
+pub enum PageListAction {
+    Jump(PageTarget),
+    Close,
+}
+
+/// Compact "go to print page" prompt backed by the book's page list
+pub struct PageListPopup {
+    pages: Vec<PageTarget>,
+    current_page: Option<String>,
+    input: String,
+    last_popup_area: Option<Rect>,
+}
+
+impl PageListPopup {
+    pub fn new(pages: Vec<PageTarget>, current_page: Option<String>) -> Self {
+        PageListPopup {
+            pages,
+            current_page,
+            input: String::new(),
+            last_popup_area: None,
+        }
+    }
+
+    /// The page whose label matches the input; for numbers that aren't in the list
+    /// (unnumbered blank pages), the closest earlier numbered page
+    fn matching_page(&self) -> Option<&PageTarget> {
+        let input = self.input.trim();
+        if input.is_empty() {
+            return None;
+        }
+        if let Some(page) = self
+            .pages
+            .iter()
+            .find(|page| page.label.eq_ignore_ascii_case(input))
+        {
+            return Some(page);
+        }
+
+        let wanted: u32 = input.parse().ok()?;
+        self.pages
+            .iter()
+            .filter_map(|page| page.label.parse::<u32>().ok().map(|number| (number, page)))
+            .filter(|(number, _)| *number <= wanted)
+            .max_by_key(|(number, _)| *number)
+            .map(|(_, page)| page)
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = POPUP_WIDTH.min(area.width);
+        let height = POPUP_HEIGHT.min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let range = match (self.pages.first(), self.pages.last()) {
+            (Some(first), Some(last)) => format!(" Go to page ({}–{}) ", first.label, last.label),
+            _ => " Go to page ".to_string(),
+        };
+
+        let status = if self.input.trim().is_empty() {
+            match &self.current_page {
+                Some(page) => Span::styled(
+                    format!("Currently on page {page}"),
+                    Style::default().fg(current_theme().base_03),
+                ),
+                None => Span::raw(""),
+            }
+        } else {
+            match self.matching_page() {
+                Some(page) if page.label.eq_ignore_ascii_case(self.input.trim()) => Span::styled(
+                    format!("→ page {}", page.label),
+                    Style::default().fg(current_theme().base_0b),
+                ),
+                Some(page) => Span::styled(
+                    format!("→ nearest page {}", page.label),
+                    Style::default().fg(current_theme().base_0a),
+                ),
+                None => Span::styled(
+                    format!("No page \"{}\"", self.input.trim()),
+                    Style::default().fg(current_theme().base_08),
+                ),
+            }
+        };
+
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("Page: ", Style::default().fg(current_theme().base_04)),
+                Span::styled(
+                    format!("{}_", self.input),
+                    Style::default()
+                        .fg(current_theme().base_05)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            Line::from(""),
+            Line::from(status),
+        ];
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(range)
+                .title_bottom(Line::from(" Enter: Jump | ESC: Close ").right_aligned())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(current_theme().base_0c))
+                .style(Style::default().bg(current_theme().base_00)),
+        );
+        f.render_widget(paragraph, popup_area);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<PageListAction> {
+        match key.code {
+            KeyCode::Esc => return Some(PageListAction::Close),
+            KeyCode::Enter => {
+                return self.matching_page().cloned().map(PageListAction::Jump);
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.push(c);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(label: &str) -> PageTarget {
+        PageTarget {
+            label: label.to_string(),
+            href: "ch1.xhtml".to_string(),
+            anchor: Some(format!("p{label}")),
+        }
+    }
+
+    #[test]
+    fn test_matching_page_falls_back_to_nearest_earlier_page() {
+        let mut popup =
+            PageListPopup::new(vec![page("xii"), page("1"), page("2"), page("5")], None);
+
+        popup.input = "XII".to_string();
+        assert_eq!(popup.matching_page().map(|p| p.label.as_str()), Some("xii"));
+
+        popup.input = "4".to_string();
+        assert_eq!(popup.matching_page().map(|p| p.label.as_str()), Some("2"));
+
+        popup.input = "xiv".to_string();
+        assert!(popup.matching_page().is_none());
+    }
+}
@@ -0,0 +1,305 @@
+use crate::fuzzy::fuzzy_match;
+use crate::table_of_contents::TocItem;
+use crate::theme::current_theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+const POPUP_WIDTH: u16 = 80;
+
+/// Where an entry of the quick switcher leads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchTarget {
+    /// A table of contents entry of the open book
+    Chapter {
+        href: String,
+        anchor: Option<String>,
+    },
+    /// A book in the library, by path
+    Book(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchEntry {
+    pub title: String,
+    pub target: SwitchTarget,
+}
+
+/// Entries for the chapters in a table of contents, with sections that can be read
+/// themselves, in book order
+pub fn chapter_entries(items: &[TocItem]) -> Vec<SwitchEntry> {
+    let mut entries = Vec::new();
+    for item in items {
+        match item {
+            TocItem::Chapter {
+                title,
+                href,
+                anchor,
+            } => entries.push(SwitchEntry {
+                title: title.clone(),
+                target: SwitchTarget::Chapter {
+                    href: href.clone(),
+                    anchor: anchor.clone(),
+                },
+            }),
+            TocItem::Section {
+                title,
+                href,
+                anchor,
+                children,
+                ..
+            } => {
+                if let Some(href) = href {
+                    entries.push(SwitchEntry {
+                        title: title.clone(),
+                        target: SwitchTarget::Chapter {
+                            href: href.clone(),
+                            anchor: anchor.clone(),
+                        },
+                    });
+                }
+                entries.extend(chapter_entries(children));
+            }
+        }
+    }
+    entries
+}
+
+pub enum QuickSwitcherAction {
+    Open(SwitchTarget),
+    Close,
+}
+
+/// `Ctrl+P`: one list of the open book's chapters and the library's books, narrowed down
+/// by fuzzy matching what's typed
+pub struct QuickSwitcherPopup {
+    entries: Vec<SwitchEntry>,
+    query: String,
+    /// Entries matching the query, best first, with the positions of the matched chars
+    matches: Vec<(usize, Vec<usize>)>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl QuickSwitcherPopup {
+    pub fn new(entries: Vec<SwitchEntry>) -> Self {
+        let mut popup = QuickSwitcherPopup {
+            entries,
+            query: String::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+            last_popup_area: None,
+        };
+        popup.update_matches();
+        popup
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                fuzzy_match(&self.query, &entry.title)
+                    .map(|(score, positions)| (score, idx, positions))
+            })
+            .collect();
+        // Stable, so equal scores keep chapters in book order ahead of books
+        scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        self.matches = scored
+            .into_iter()
+            .map(|(_, idx, positions)| (idx, positions))
+            .collect();
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    fn selected_target(&self) -> Option<SwitchTarget> {
+        let (idx, _) = self.matches.get(self.state.selected()?)?;
+        Some(self.entries[*idx].target.clone())
+    }
+
+    pub fn select_offset(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.matches.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = POPUP_WIDTH.min(area.width);
+        let height = (area.height * 3 / 5).max(8).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 5,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let theme = current_theme();
+        let block = Block::default()
+            .title(format!(
+                " Go to ({}/{}) ",
+                self.matches.len(),
+                self.entries.len()
+            ))
+            .title_bottom(Line::from(" Enter: Open | ESC: Close ").right_aligned())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.base_0c))
+            .style(Style::default().bg(theme.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let [input_area, list_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .areas(inner);
+
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(theme.base_04)),
+            Span::styled(
+                format!("{}_", self.query),
+                Style::default()
+                    .fg(theme.base_05)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        f.render_widget(input, input_area);
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|(idx, positions)| {
+                let entry = &self.entries[*idx];
+                let kind = match entry.target {
+                    SwitchTarget::Chapter { .. } => "chapter ",
+                    SwitchTarget::Book(_) => "book    ",
+                };
+                let mut spans = vec![Span::styled(kind, Style::default().fg(theme.base_03))];
+                spans.extend(entry.title.chars().enumerate().map(|(pos, c)| {
+                    let style = if positions.contains(&pos) {
+                        Style::default()
+                            .fg(theme.base_0a)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(theme.base_05)
+                    };
+                    Span::styled(c.to_string(), style)
+                }));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(theme.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    /// Typing goes into the query, so the list is moved with the arrows or `Ctrl+N`/`Ctrl+P`
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<QuickSwitcherAction> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(QuickSwitcherAction::Close),
+            KeyCode::Enter => return self.selected_target().map(QuickSwitcherAction::Open),
+            KeyCode::Down => self.select_offset(1),
+            KeyCode::Up => self.select_offset(-1),
+            KeyCode::PageDown => self.select_offset(10),
+            KeyCode::PageUp => self.select_offset(-10),
+            KeyCode::Char('n' | 'j') if ctrl => self.select_offset(1),
+            KeyCode::Char('p' | 'k') if ctrl => self.select_offset(-1),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.update_matches();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(popup: &mut QuickSwitcherPopup, text: &str) {
+        for c in text.chars() {
+            popup.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn test_chapters_and_books_are_matched_together() {
+        let chapter = |title: &str, href: &str| SwitchEntry {
+            title: title.to_string(),
+            target: SwitchTarget::Chapter {
+                href: href.to_string(),
+                anchor: None,
+            },
+        };
+        let mut popup = QuickSwitcherPopup::new(vec![
+            chapter("Loomings", "ch1.xhtml"),
+            chapter("The Whiteness of the Whale", "ch42.xhtml"),
+            SwitchEntry {
+                title: "Walden".to_string(),
+                target: SwitchTarget::Book("/books/walden.epub".to_string()),
+            },
+        ]);
+        assert_eq!(popup.matches.len(), 3);
+
+        type_text(&mut popup, "wal");
+        assert_eq!(popup.matches.len(), 2);
+        assert!(matches!(
+            popup.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(QuickSwitcherAction::Open(SwitchTarget::Book(path))) if path == "/books/walden.epub"
+        ));
+
+        popup.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+        assert!(matches!(
+            popup.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(QuickSwitcherAction::Open(SwitchTarget::Chapter { href, .. })) if href == "ch42.xhtml"
+        ));
+
+        type_text(&mut popup, "zz");
+        assert!(popup.matches.is_empty());
+        assert!(
+            popup
+                .handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use crate::inputs::KeySeq;
+use crate::main_app::VimNavMotions;
+use crate::theme::current_theme;
+use epub::doc::EpubDoc;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+pub enum ResourcesAction {
+    /// Extract the listed resource ids
+    Extract {
+        ids: Vec<String>,
+    },
+    Close,
+}
+
+#[derive(Clone)]
+struct ResourceEntry {
+    id: String,
+    path: String,
+    mime: String,
+}
+
+/// Lists the files shipped inside an EPUB that aren't part of the reading order
+/// (attachments, data files, code samples, images, styles...)
+pub struct ResourcesPopup {
+    items: Vec<ResourceEntry>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl ResourcesPopup {
+    pub fn new<R: Read + Seek>(epub: &EpubDoc<R>) -> Self {
+        let spine_ids: HashSet<&str> = epub.spine.iter().map(|item| item.idref.as_str()).collect();
+
+        let mut items: Vec<ResourceEntry> = epub
+            .resources
+            .iter()
+            .filter(|(id, _)| !spine_ids.contains(id.as_str()))
+            .map(|(id, resource)| ResourceEntry {
+                id: id.clone(),
+                path: resource.path.to_string_lossy().to_string(),
+                mime: resource.mime.clone(),
+            })
+            .collect();
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut state = ListState::default();
+        if !items.is_empty() {
+            state.select(Some(0));
+        }
+
+        ResourcesPopup {
+            items,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(70, 80, area);
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(&item.path, Style::default().fg(current_theme().base_05)),
+                    Span::styled(
+                        format!("  {}", item.mime),
+                        Style::default().fg(current_theme().base_03),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(" Resources ({}) ", self.items.len()))
+                    .title_bottom(
+                        Line::from(" Enter: Extract | a: Extract all | ESC: Close ")
+                            .right_aligned(),
+                    )
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(current_theme().base_0c))
+                    .style(Style::default().bg(current_theme().base_00)),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(current_theme().base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+
+        f.render_stateful_widget(list, popup_area, &mut self.state);
+    }
+
+    fn select_offset(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.items.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    pub fn selected_id(&self) -> Option<&str> {
+        self.state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(|item| item.id.as_str())
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        key_seq: &mut KeySeq,
+    ) -> Option<ResourcesAction> {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.handle_j(),
+            KeyCode::Char('k') | KeyCode::Up => self.handle_k(),
+            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => self.handle_gg(),
+            KeyCode::Char('G') => self.handle_upper_g(),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_d()
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_u()
+            }
+            KeyCode::Char('a') => {
+                return Some(ResourcesAction::Extract {
+                    ids: self.items.iter().map(|item| item.id.clone()).collect(),
+                });
+            }
+            KeyCode::Enter => {
+                return self.selected_id().map(|id| ResourcesAction::Extract {
+                    ids: vec![id.to_string()],
+                });
+            }
+            KeyCode::Esc => return Some(ResourcesAction::Close),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl VimNavMotions for ResourcesPopup {
+    fn handle_h(&mut self) {}
+
+    fn handle_j(&mut self) {
+        self.select_offset(1);
+    }
+
+    fn handle_k(&mut self) {
+        self.select_offset(-1);
+    }
+
+    fn handle_l(&mut self) {}
+
+    fn handle_ctrl_d(&mut self) {
+        self.select_offset(10);
+    }
+
+    fn handle_ctrl_u(&mut self) {
+        self.select_offset(-10);
+    }
+
+    fn handle_gg(&mut self) {
+        self.select_offset(-(self.items.len() as isize));
+    }
+
+    fn handle_upper_g(&mut self) {
+        self.select_offset(self.items.len() as isize);
+    }
+}
+
+/// Write the given resources below `target_dir`, keeping their path inside the EPUB.
+/// Returns the number of files written.
+pub fn extract_resources<R: Read + Seek>(
+    epub: &mut EpubDoc<R>,
+    ids: &[String],
+    target_dir: &Path,
+) -> anyhow::Result<usize> {
+    let mut written = 0;
+    for id in ids {
+        let Some(resource) = epub.resources.get(id) else {
+            continue;
+        };
+        let relative = sanitize_resource_path(&resource.path);
+        let Some((data, _mime)) = epub.get_resource(id) else {
+            continue;
+        };
+
+        let target = target_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, data)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Drop root and parent components so a malicious EPUB can't write outside the target directory
+fn sanitize_resource_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect()
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_resource_path_stays_relative() {
+        assert_eq!(
+            sanitize_resource_path(Path::new("/OEBPS/../../etc/data.csv")),
+            PathBuf::from("OEBPS/etc/data.csv")
+        );
+        assert_eq!(
+            sanitize_resource_path(Path::new("OEBPS/code/sample.py")),
+            PathBuf::from("OEBPS/code/sample.py")
+        );
+    }
+}
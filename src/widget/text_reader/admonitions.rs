@@ -0,0 +1,188 @@
+use super::rendering::RenderContext;
+use super::types::*;
+use crate::markdown::{Block as MarkdownBlock, Node};
+use crate::theme::Base16Palette;
+use ratatui::style::{Color, Modifier, Style as RatatuiStyle};
+use ratatui::text::Span;
+
+/// Epub blocks that call out a remark, drawn as a box with a title bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Admonition {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl Admonition {
+    /// The admonition an `epub:type` stands for. `note` also marks footnotes in some
+    /// books, so it only counts outside `<aside>`.
+    pub fn from_epub_type(epub_type: &str, element_name: &str) -> Option<Self> {
+        epub_type.split_whitespace().find_map(|t| match t {
+            "tip" | "hint" => Some(Admonition::Tip),
+            "important" => Some(Admonition::Important),
+            "warning" => Some(Admonition::Warning),
+            "caution" | "danger" => Some(Admonition::Caution),
+            "note" if element_name != "aside" => Some(Admonition::Note),
+            _ => None,
+        })
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Admonition::Note => "Note",
+            Admonition::Tip => "Tip",
+            Admonition::Important => "Important",
+            Admonition::Warning => "Warning",
+            Admonition::Caution => "Caution",
+        }
+    }
+
+    fn color(self, palette: &Base16Palette) -> Color {
+        match self {
+            Admonition::Note => palette.base_0d,      // Blue
+            Admonition::Tip => palette.base_0b,       // Green
+            Admonition::Important => palette.base_0e, // Purple
+            Admonition::Warning => palette.base_0a,   // Yellow
+            Admonition::Caution => palette.base_08,   // Red
+        }
+    }
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// The block's content inside a frame in the admonition's color. A leading heading
+    /// such as "Warning" becomes the title bar; otherwise the kind of admonition does.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_admonition(
+        &mut self,
+        kind: Admonition,
+        content: &[Node],
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        let color = kind.color(palette);
+        let border_style = RatatuiStyle::default().fg(color);
+        let inner_width = width.saturating_sub(4).max(1);
+        let (title, body) = match content.split_first() {
+            Some((first, rest)) if matches!(first.block, MarkdownBlock::Heading { .. }) => {
+                (first.plain_text(), rest)
+            }
+            _ => (kind.label().to_string(), content),
+        };
+
+        if Self::last_line_has_content(lines) {
+            lines.push(RenderedLine::empty());
+            self.raw_text_lines.push(String::new());
+            *total_height += 1;
+        }
+
+        let title = format!(" {} ", title.trim());
+        let fill = width.saturating_sub(3 + textwrap::core::display_width(&title));
+        let top_rule = format!("╭─{title}{}╮", "─".repeat(fill));
+        lines.push(RenderedLine {
+            spans: vec![
+                Span::styled("╭─", border_style),
+                Span::styled(
+                    title,
+                    RatatuiStyle::default()
+                        .fg(palette.base_00)
+                        .bg(color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("{}╮", "─".repeat(fill)), border_style),
+            ],
+            raw_text: top_rule.clone(),
+            line_type: LineType::HorizontalRule,
+            link_nodes: vec![],
+            node_anchor: None,
+            node_index: None,
+        });
+        self.raw_text_lines.push(top_rule);
+        *total_height += 1;
+
+        let first_body_line = lines.len();
+        for (idx, node) in body.iter().enumerate() {
+            if idx > 0 && Self::last_line_has_content(lines) {
+                lines.push(RenderedLine::empty());
+                self.raw_text_lines.push(String::new());
+                *total_height += 1;
+            }
+            self.render_node(
+                node,
+                lines,
+                total_height,
+                inner_width,
+                palette,
+                is_focused,
+                0,
+                None,
+                RenderContext::InsideContainer,
+            );
+        }
+        while lines.len() > first_body_line && !Self::last_line_has_content(lines) {
+            lines.pop();
+            self.raw_text_lines.pop();
+            *total_height -= 1;
+        }
+
+        for line in lines[first_body_line..]
+            .iter_mut()
+            .filter(|line| !matches!(line.line_type, LineType::ImagePlaceholder { .. }))
+        {
+            let used = textwrap::core::display_width(&line.raw_text);
+            let padding = " ".repeat(inner_width.saturating_sub(used));
+            line.spans.insert(0, Span::styled("│ ", border_style));
+            line.spans.push(Span::raw(padding.clone()));
+            line.spans.push(Span::styled(" │", border_style));
+            line.raw_text = format!("│ {}{padding} │", line.raw_text);
+            for link in &mut line.link_nodes {
+                link.start_col += 2;
+                link.end_col += 2;
+            }
+        }
+
+        let bottom_rule = format!("╰{}╯", "─".repeat(width.saturating_sub(2)));
+        lines.push(RenderedLine {
+            spans: vec![Span::styled(bottom_rule.clone(), border_style)],
+            raw_text: bottom_rule.clone(),
+            line_type: LineType::HorizontalRule,
+            link_nodes: vec![],
+            node_anchor: None,
+            node_index: None,
+        });
+        self.raw_text_lines.push(bottom_rule);
+        lines.push(RenderedLine::empty());
+        self.raw_text_lines.push(String::new());
+        *total_height += 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown_text_reader::MarkdownTextReader;
+
+    #[test]
+    fn test_warning_is_framed_with_its_title() {
+        let html = r#"<div epub:type="warning"><h6>Mind the gap</h6><p>Trains stop here.</p>
+            <p>Keep clear of the doors.</p></div>
+            <aside epub:type="note"><p>Not an admonition.</p></aside>"#;
+        let output = MarkdownTextReader::render_html_to_plain_text(html, 30);
+        let rows: Vec<_> = output.lines().collect();
+        assert_eq!(
+            rows[..6],
+            [
+                "╭─ Mind the gap ─────────────╮",
+                "│ Trains stop here.          │",
+                "│                            │",
+                "│ Keep clear of the doors.   │",
+                "╰────────────────────────────╯",
+                "",
+            ]
+        );
+        assert!(!rows[6..].iter().any(|row| row.starts_with('╭')));
+    }
+}
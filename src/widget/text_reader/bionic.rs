@@ -0,0 +1,117 @@
+use super::types::*;
+use ratatui::{style::Modifier, text::Span};
+
+/// Default share of each word that gets emphasized in bionic reading mode
+pub const DEFAULT_BIONIC_RATIO: f32 = 0.5;
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    pub fn is_bionic_reading(&self) -> bool {
+        self.bionic_ratio.is_some()
+    }
+
+    /// Enable bionic reading with the given ratio, or disable it with `None`
+    pub fn set_bionic_reading(&mut self, ratio: Option<f32>) {
+        self.bionic_ratio = ratio.map(|r| r.clamp(0.1, 0.9));
+        // Rendered lines carry the emphasis, so they must be rebuilt
+        self.cache_generation += 1;
+    }
+
+    /// Post-processing pass: bold the leading part of every word in prose lines
+    pub fn apply_bionic_emphasis(&self, lines: &mut [RenderedLine]) {
+        let Some(ratio) = self.bionic_ratio else {
+            return;
+        };
+
+        for line in lines.iter_mut() {
+            if !matches!(line.line_type, LineType::Text | LineType::ListItem { .. }) {
+                continue;
+            }
+
+            let spans = std::mem::take(&mut line.spans);
+            line.spans = spans
+                .into_iter()
+                .flat_map(|span| emphasize_span(span, ratio))
+                .collect();
+        }
+    }
+}
+
+/// Split a span so that the first `ratio` of each word is bold.
+/// Spans that already have a background (inline code) or bold text are left alone.
+fn emphasize_span(span: Span<'static>, ratio: f32) -> Vec<Span<'static>> {
+    if span.style.bg.is_some() || span.style.add_modifier.contains(Modifier::BOLD) {
+        return vec![span];
+    }
+
+    // Collect (text, is_bold) segments, merging neighbours with the same weight
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    let mut push = |c: char, bold: bool| match segments.last_mut() {
+        Some((text, last_bold)) if *last_bold == bold => text.push(c),
+        _ => segments.push((c.to_string(), bold)),
+    };
+
+    let chars: Vec<char> = span.content.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphanumeric() {
+            push(chars[i], false);
+            i += 1;
+            continue;
+        }
+
+        let word_end = chars[i..]
+            .iter()
+            .position(|c| !c.is_alphanumeric())
+            .map_or(chars.len(), |p| i + p);
+        let word_len = word_end - i;
+        let bold_len = ((word_len as f32 * ratio).ceil() as usize).clamp(1, word_len);
+
+        for (offset, &c) in chars[i..word_end].iter().enumerate() {
+            push(c, offset < bold_len);
+        }
+        i = word_end;
+    }
+
+    segments
+        .into_iter()
+        .map(|(text, bold)| {
+            let style = if bold {
+                span.style.add_modifier(Modifier::BOLD)
+            } else {
+                span.style
+            };
+            Span::styled(text, style)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Style};
+
+    #[test]
+    fn test_emphasize_span_bolds_word_prefixes() {
+        let span = Span::styled("reading is fun", Style::default().fg(Color::White));
+        let spans = emphasize_span(span, 0.5);
+
+        let text: String = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "reading is fun");
+
+        let bold: Vec<&str> = spans
+            .iter()
+            .filter(|s| s.style.add_modifier.contains(Modifier::BOLD))
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(bold, vec!["read", "i", "fu"]);
+        assert!(spans.iter().all(|s| s.style.fg == Some(Color::White)));
+    }
+
+    #[test]
+    fn test_emphasize_span_skips_code() {
+        let span = Span::styled("let x", Style::default().bg(Color::Gray));
+        let spans = emphasize_span(span, 0.5);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].content, "let x");
+    }
+}
@@ -1,9 +1,10 @@
 use super::types::*;
-use crate::comments::{BookComments, Comment};
+use crate::comments::{BookComments, ChapterAnchors, Comment};
 use crate::theme::Base16Palette;
 use log::{debug, warn};
 use ratatui::style::Style as RatatuiStyle;
 use ratatui::text::Span;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tui_textarea::{Input, Key, TextArea};
 
@@ -15,9 +16,28 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.rebuild_chapter_comments();
     }
 
+    /// Show other readers' notes, each with the reader's name, in a color of their own
+    pub fn set_shared_comments(&mut self, comments: Vec<(String, Comment)>) {
+        self.shared_comments = comments;
+        self.rebuild_chapter_comments();
+        self.cache_generation += 1;
+    }
+
     /// Rebuild the comment lookup for the current chapter
     pub fn rebuild_chapter_comments(&mut self) {
         self.current_chapter_comments.clear();
+        self.current_chapter_shared_comments.clear();
+
+        if let Some(chapter_file) = &self.current_chapter_file {
+            for (reader, comment) in &self.shared_comments {
+                if &comment.chapter_href == chapter_file {
+                    self.current_chapter_shared_comments
+                        .entry(comment.paragraph_index)
+                        .or_default()
+                        .push((reader.clone(), comment.clone()));
+                }
+            }
+        }
 
         if let Some(chapter_file) = &self.current_chapter_file {
             if let Some(comments_arc) = &self.book_comments {
@@ -33,6 +53,116 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    /// The element id and text offset of a paragraph of the current chapter, which its
+    /// comments are anchored to
+    fn paragraph_location(&self, node_index: usize) -> (Option<String>, Option<usize>) {
+        self.markdown_document
+            .as_ref()
+            .map(|doc| ChapterAnchors::new(doc).locate(node_index))
+            .unwrap_or_default()
+    }
+
+    /// Put the current chapter's comments back on their paragraphs if `doc` numbers its
+    /// blocks differently from when they were written
+    pub(super) fn reanchor_chapter_comments(&mut self, doc: &crate::markdown::Document) {
+        let (Some(chapter_file), Some(comments_arc)) =
+            (&self.current_chapter_file, &self.book_comments)
+        else {
+            return;
+        };
+        if self.current_chapter_comments.is_empty() {
+            return;
+        }
+        let moved = match comments_arc.lock() {
+            Ok(mut comments) => comments
+                .reanchor_chapter(chapter_file, &ChapterAnchors::new(doc))
+                .unwrap_or_else(|e| {
+                    warn!("Failed to save comments put back on their paragraphs: {e}");
+                    true
+                }),
+            Err(_) => false,
+        };
+        if moved {
+            self.rebuild_chapter_comments();
+        }
+    }
+
+    /// All our notes on the book
+    pub fn all_comments(&self) -> Vec<Comment> {
+        self.book_comments
+            .as_ref()
+            .and_then(|comments| comments.lock().ok())
+            .map(|comments| comments.get_all_comments().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Our notes on the current chapter, in reading order
+    pub fn chapter_comments(&self) -> Vec<Comment> {
+        let mut comments: Vec<Comment> = self
+            .current_chapter_comments
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        comments.sort_by_key(|comment| (comment.paragraph_index, comment.word_range));
+        comments
+    }
+
+    /// The paragraph the selection starts in
+    pub fn selection_node_index(&self) -> Option<usize> {
+        let start = self.selection_start_line()?;
+        self.rendered_content.lines[..=start]
+            .iter()
+            .rev()
+            .find_map(|line| line.node_index)
+    }
+
+    /// The note file linked to a paragraph of the current chapter
+    pub fn note_file_for_node(&self, node_index: usize) -> Option<PathBuf> {
+        self.current_chapter_comments
+            .get(&node_index)?
+            .iter()
+            .find_map(|comment| crate::note_files::linked_note_file(&comment.content))
+    }
+
+    /// Link a paragraph of the current chapter to a note file, adding the link to the
+    /// paragraph's comment or starting one
+    pub fn link_note_file(&mut self, node_index: usize, path: &Path) -> anyhow::Result<()> {
+        use chrono::Utc;
+
+        let (Some(chapter_href), Some(comments_arc)) =
+            (self.current_chapter_file.clone(), &self.book_comments)
+        else {
+            anyhow::bail!("No chapter open");
+        };
+        let link = crate::note_files::note_link(path);
+        {
+            let mut comments = comments_arc.lock().unwrap();
+            let content = comments
+                .get_paragraph_comments(&chapter_href, node_index)
+                .iter()
+                .find(|comment| comment.word_range.is_none())
+                .map_or_else(
+                    || link.clone(),
+                    |comment| format!("{}\n{link}", comment.content),
+                );
+            let (anchor, text_offset) = self.paragraph_location(node_index);
+            comments.add_comment(Comment {
+                chapter_href,
+                paragraph_index: node_index,
+                word_range: None,
+                content,
+                updated_at: Utc::now(),
+                anchor,
+                text_offset,
+            })?;
+        }
+
+        self.rebuild_chapter_comments();
+        self.cache_generation += 1;
+        Ok(())
+    }
+
     /// Start editing an existing comment
     pub fn start_editing_comment(
         &mut self,
@@ -155,6 +285,19 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         false
     }
 
+    /// Insert pasted text into the comment being edited, keeping its line breaks
+    pub fn handle_comment_paste(&mut self, text: &str) -> bool {
+        if !self.comment_input.is_active() {
+            return false;
+        }
+
+        if let Some(textarea) = &mut self.comment_input.textarea {
+            textarea.insert_str(text);
+            return true;
+        }
+        false
+    }
+
     pub fn save_comment(&mut self) {
         if let Some(textarea) = &self.comment_input.textarea {
             let comment_text = textarea.lines().join("\n");
@@ -174,12 +317,15 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                                     _ => None,
                                 };
 
+                                let (anchor, text_offset) = self.paragraph_location(node_idx);
                                 let comment = Comment {
                                     chapter_href: chapter_file.clone(),
                                     paragraph_index: node_idx,
                                     word_range,
                                     content: comment_text.clone(),
                                     updated_at: Utc::now(),
+                                    anchor,
+                                    text_offset,
                                 };
 
                                 if let Err(e) = comments.add_comment(comment) {
@@ -230,25 +376,49 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     }
 
     /// Delete comment at current selection
-    /// Returns true if a comment was deleted
-    pub fn delete_comment_at_cursor(&mut self) -> anyhow::Result<bool> {
-        if let Some((chapter_href, paragraph_index, word_range)) = self.get_comment_at_cursor() {
-            if let Some(comments_arc) = &self.book_comments {
-                let mut comments = comments_arc.lock().unwrap();
-                comments.delete_comment(&chapter_href, paragraph_index, word_range)?;
-
-                drop(comments);
-                self.rebuild_chapter_comments();
+    /// Returns the deleted comment, if there was one under the cursor
+    pub fn delete_comment_at_cursor(&mut self) -> anyhow::Result<Option<Comment>> {
+        let Some((chapter_href, paragraph_index, word_range)) = self.get_comment_at_cursor() else {
+            return Ok(None);
+        };
+        let deleted = self.delete_comment(&chapter_href, paragraph_index, word_range)?;
+        if deleted.is_some() {
+            self.text_selection.clear_selection();
+        }
+        Ok(deleted)
+    }
 
-                self.cache_generation += 1;
+    /// Delete a comment by location, e.g. when redoing a deletion
+    pub fn delete_comment(
+        &mut self,
+        chapter_href: &str,
+        paragraph_index: usize,
+        word_range: Option<(usize, usize)>,
+    ) -> anyhow::Result<Option<Comment>> {
+        let Some(comments_arc) = &self.book_comments else {
+            return Ok(None);
+        };
+        let deleted = comments_arc.lock().unwrap().delete_comment(
+            chapter_href,
+            paragraph_index,
+            word_range,
+        )?;
 
-                self.text_selection.clear_selection();
+        self.rebuild_chapter_comments();
+        self.cache_generation += 1;
+        Ok(Some(deleted))
+    }
 
-                return Ok(true);
-            }
-        }
+    /// Put back a comment that was deleted
+    pub fn restore_comment(&mut self, comment: Comment) -> anyhow::Result<()> {
+        let Some(comments_arc) = &self.book_comments else {
+            anyhow::bail!("No book open");
+        };
+        comments_arc.lock().unwrap().add_comment(comment)?;
 
-        Ok(false)
+        self.rebuild_chapter_comments();
+        self.cache_generation += 1;
+        Ok(())
     }
 
     /// Find the visual line where a specific comment starts rendering
@@ -309,43 +479,88 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
 
         let comment_header = format!("Note // {}", comment.updated_at.format("%m-%d-%y %H:%M"));
-
-        lines.push(RenderedLine {
-            spans: vec![Span::styled(
-                comment_header.clone(),
-                RatatuiStyle::default().fg(palette.base_0e), // Purple text color
-            )],
-            raw_text: comment_header.clone(),
-            line_type: LineType::Comment {
+        self.push_quote_lines(
+            comment_header,
+            &comment.content,
+            RatatuiStyle::default().fg(palette.base_0e), // Purple text color
+            LineType::Comment {
                 chapter_href: comment.chapter_href.clone(),
                 paragraph_index: comment.paragraph_index,
                 word_range: comment.word_range,
             },
+            lines,
+            total_height,
+            width,
+            indent,
+        );
+    }
+
+    /// Other readers' notes on a paragraph, in cyan to tell them from ours
+    pub fn render_shared_comments(
+        &mut self,
+        node_idx: usize,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        indent: usize,
+    ) {
+        let Some(shared) = self.current_chapter_shared_comments.get(&node_idx).cloned() else {
+            return;
+        };
+        for (reader, comment) in shared {
+            let header = format!(
+                "{reader}'s note // {}",
+                comment.updated_at.format("%m-%d-%y %H:%M")
+            );
+            self.push_quote_lines(
+                header,
+                &comment.content,
+                RatatuiStyle::default().fg(palette.base_0c),
+                LineType::SharedComment,
+                lines,
+                total_height,
+                width,
+                indent,
+            );
+        }
+    }
+
+    /// A header line, the content wrapped and quoted with `> `, and an empty line
+    #[allow(clippy::too_many_arguments)]
+    fn push_quote_lines(
+        &mut self,
+        header: String,
+        content: &str,
+        style: RatatuiStyle,
+        line_type: LineType,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        indent: usize,
+    ) {
+        lines.push(RenderedLine {
+            spans: vec![Span::styled(header.clone(), style)],
+            raw_text: header.clone(),
+            line_type: line_type.clone(),
             link_nodes: vec![],
             node_anchor: None,
             node_index: None,
         });
-        self.raw_text_lines.push(comment_header);
+        self.raw_text_lines.push(header);
         *total_height += 1;
 
         let quote_prefix = "> ";
         let effective_width = width.saturating_sub(indent + quote_prefix.len());
 
-        let wrapped_lines = textwrap::wrap(&comment.content, effective_width);
+        let wrapped_lines = textwrap::wrap(content, effective_width);
 
         for line in wrapped_lines {
             let quoted_line = format!("{}{}{}", " ".repeat(indent), quote_prefix, line);
             lines.push(RenderedLine {
-                spans: vec![Span::styled(
-                    quoted_line.clone(),
-                    RatatuiStyle::default().fg(palette.base_0e), // Purple text color
-                )],
+                spans: vec![Span::styled(quoted_line.clone(), style)],
                 raw_text: line.to_string(),
-                line_type: LineType::Comment {
-                    chapter_href: comment.chapter_href.clone(),
-                    paragraph_index: comment.paragraph_index,
-                    word_range: comment.word_range,
-                },
+                line_type: line_type.clone(),
                 link_nodes: vec![],
                 node_anchor: None,
                 node_index: None,
@@ -358,11 +573,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         lines.push(RenderedLine {
             spans: vec![Span::raw("")],
             raw_text: String::new(),
-            line_type: LineType::Comment {
-                chapter_href: comment.chapter_href.clone(),
-                paragraph_index: comment.paragraph_index,
-                word_range: comment.word_range,
-            },
+            line_type,
             link_nodes: vec![],
             node_anchor: None,
             node_index: None,
@@ -371,3 +582,59 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+
+    #[test]
+    fn test_shared_notes_follow_their_paragraph_in_their_own_color() {
+        let doc = HtmlToMarkdownConverter::new().convert("<p>First.</p><p>Second.</p>");
+        let mut reader = MarkdownTextReader::new();
+        reader.set_current_chapter_file(Some("OEBPS/ch1.xhtml".to_string()));
+        let note = |chapter_href: &str| Comment {
+            chapter_href: chapter_href.to_string(),
+            paragraph_index: 0,
+            word_range: None,
+            content: "Worth a second read".to_string(),
+            updated_at: chrono::Utc::now(),
+            anchor: None,
+            text_offset: None,
+        };
+        reader.set_shared_comments(vec![
+            ("alice".to_string(), note("OEBPS/ch1.xhtml")),
+            ("bob".to_string(), note("OEBPS/ch2.xhtml")),
+        ]);
+
+        let rendered = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+        let shared: Vec<_> = rendered
+            .lines
+            .iter()
+            .filter(|line| line.line_type == LineType::SharedComment)
+            .collect();
+        assert!(shared[0].raw_text.starts_with("alice's note // "));
+        assert_eq!(shared[0].spans[0].style.fg, Some(OCEANIC_NEXT.base_0c));
+        assert_eq!(shared[1].raw_text, "Worth a second read");
+        assert!(
+            !rendered
+                .lines
+                .iter()
+                .any(|line| line.raw_text.contains("bob"))
+        );
+
+        let second = rendered
+            .lines
+            .iter()
+            .position(|line| line.raw_text == "Second.")
+            .unwrap();
+        let first_note = rendered
+            .lines
+            .iter()
+            .position(|line| line.line_type == LineType::SharedComment)
+            .unwrap();
+        assert!(first_note < second);
+    }
+}
@@ -0,0 +1,69 @@
+use super::rendering::RenderContext;
+use super::types::*;
+use crate::chapter_diff::BlockChange;
+use crate::markdown::Node;
+use crate::theme::Base16Palette;
+use ratatui::style::{Modifier, Style as RatatuiStyle};
+use ratatui::text::Span;
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Blocks a chapter diff found added or removed, behind a `+` or `-` bar in green or
+    /// red; removed text is also struck through
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_changed_blocks(
+        &mut self,
+        change: BlockChange,
+        content: &[Node],
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        let (marker, color, modifier) = match change {
+            BlockChange::Inserted => ("+ ", palette.base_0b, Modifier::empty()),
+            BlockChange::Deleted => ("- ", palette.base_08, Modifier::CROSSED_OUT),
+            BlockChange::Unchanged => ("  ", palette.base_05, Modifier::empty()),
+        };
+        let marker_width = marker.chars().count();
+
+        let first_line = lines.len();
+        let first_raw_line = self.raw_text_lines.len();
+        for node in content {
+            self.render_node(
+                node,
+                lines,
+                total_height,
+                width.saturating_sub(marker_width).max(1),
+                palette,
+                is_focused,
+                0,
+                None,
+                RenderContext::TopLevel,
+            );
+        }
+        // The blank line after the last block separates the run from what follows
+        let last_line = if lines.last().is_some_and(|line| line.raw_text.is_empty()) {
+            lines.len() - 1
+        } else {
+            lines.len()
+        };
+
+        for line in &mut lines[first_line..last_line] {
+            for span in &mut line.spans {
+                span.style = span.style.fg(color).add_modifier(modifier);
+            }
+            line.spans
+                .insert(0, Span::styled(marker, RatatuiStyle::default().fg(color)));
+            line.raw_text.insert_str(0, marker);
+            for link in &mut line.link_nodes {
+                link.start_col += marker_width;
+                link.end_col += marker_width;
+            }
+        }
+        let raw_lines = first_raw_line..(first_raw_line + last_line - first_line);
+        for raw_line in self.raw_text_lines.get_mut(raw_lines).into_iter().flatten() {
+            raw_line.insert_str(0, marker);
+        }
+    }
+}
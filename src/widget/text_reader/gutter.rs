@@ -0,0 +1,163 @@
+use crate::theme::Base16Palette;
+use ratatui::{Frame, layout::Rect, style::Style as RatatuiStyle};
+
+/// Marker drawn in the one-column gutter left of the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GutterMarker {
+    /// A comment attached to a range of words
+    Highlight,
+    /// A comment on the whole paragraph
+    Note,
+    /// A comment linking the paragraph to a note file
+    NoteFile,
+    /// Where the book was opened from its bookmark
+    Bookmark,
+}
+
+impl GutterMarker {
+    fn symbol(self) -> &'static str {
+        match self {
+            GutterMarker::Highlight => "●",
+            GutterMarker::Note => "◆",
+            GutterMarker::NoteFile => "✎",
+            GutterMarker::Bookmark => "★",
+        }
+    }
+
+    fn style(self, palette: &Base16Palette) -> RatatuiStyle {
+        let color = match self {
+            GutterMarker::Highlight => palette.base_0a,
+            GutterMarker::Note => palette.base_0e,
+            GutterMarker::NoteFile => palette.base_0d,
+            GutterMarker::Bookmark => palette.base_0b,
+        };
+        RatatuiStyle::default().fg(color)
+    }
+}
+
+/// A marker as last drawn, for mouse hit-testing
+pub(super) struct DrawnMarker {
+    pub row: u16,
+    pub line_idx: usize,
+    pub marker: GutterMarker,
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    pub fn set_annotation_gutter(&mut self, enabled: bool) {
+        self.annotation_gutter = enabled;
+    }
+
+    /// Node to mark with ★ in the given chapter
+    pub fn set_bookmark_marker(&mut self, marker: Option<(String, usize)>) {
+        self.bookmark_marker = marker;
+    }
+
+    /// The marker for a rendered line: notes and highlights go on the first line of
+    /// their paragraph, the bookmark on the first line of its node
+    fn gutter_marker_for_line(&self, line_idx: usize) -> Option<GutterMarker> {
+        let node_idx = self.rendered_content.lines.get(line_idx)?.node_index?;
+
+        if let Some(comments) = self.current_chapter_comments.get(&node_idx) {
+            if self.note_file_for_node(node_idx).is_some() {
+                return Some(GutterMarker::NoteFile);
+            }
+            if comments.iter().any(|comment| comment.word_range.is_none()) {
+                return Some(GutterMarker::Note);
+            }
+            if !comments.is_empty() {
+                return Some(GutterMarker::Highlight);
+            }
+        }
+
+        match (&self.bookmark_marker, &self.current_chapter_file) {
+            (Some((href, bookmark_node)), Some(chapter_file))
+                if href == chapter_file && *bookmark_node == node_idx =>
+            {
+                Some(GutterMarker::Bookmark)
+            }
+            _ => None,
+        }
+    }
+
+    /// Draw markers for the visible lines into the column left of `text_area`
+    pub(super) fn render_gutter(
+        &mut self,
+        frame: &mut Frame,
+        content_area: Rect,
+        text_area: Rect,
+        palette: &Base16Palette,
+    ) {
+        self.drawn_gutter_markers.clear();
+        // The comment textarea shifts the lines below it, so markers would be misaligned
+        if !self.annotation_gutter || self.comment_input.is_active() {
+            return;
+        }
+        // The gutter lives in the padding between the border and the text
+        if text_area.x <= content_area.x + 1 {
+            return;
+        }
+        let column = text_area.x - 1;
+
+        let end_offset =
+            (self.scroll_offset + text_area.height as usize).min(self.rendered_content.lines.len());
+        for line_idx in self.scroll_offset..end_offset {
+            let Some(marker) = self.gutter_marker_for_line(line_idx) else {
+                continue;
+            };
+            let row = text_area.y + (line_idx - self.scroll_offset) as u16;
+            frame
+                .buffer_mut()
+                .set_string(column, row, marker.symbol(), marker.style(palette));
+            self.drawn_gutter_markers.push(DrawnMarker {
+                row,
+                line_idx,
+                marker,
+            });
+        }
+    }
+
+    /// Handle a click in the gutter: notes and highlights open their comment for
+    /// editing. Returns the clicked marker; note files are left for the caller to open
+    /// with `gutter_note_file`.
+    pub fn handle_gutter_click(&mut self, column: u16, row: u16) -> Option<GutterMarker> {
+        let text_area = self.last_inner_text_area?;
+        if column + 1 != text_area.x {
+            return None;
+        }
+        let drawn = self
+            .drawn_gutter_markers
+            .iter()
+            .find(|drawn| drawn.row == row)?;
+        let (line_idx, marker) = (drawn.line_idx, drawn.marker);
+
+        if !matches!(marker, GutterMarker::Bookmark | GutterMarker::NoteFile) {
+            let node_idx = self.rendered_content.lines.get(line_idx)?.node_index?;
+            let comment = self
+                .current_chapter_comments
+                .get(&node_idx)?
+                .iter()
+                .min_by_key(|comment| comment.word_range.is_some())?
+                .clone();
+            self.start_editing_comment(
+                comment.chapter_href,
+                comment.paragraph_index,
+                comment.word_range,
+            );
+        }
+        Some(marker)
+    }
+
+    /// The note file behind the ✎ marker drawn on `row`
+    pub fn gutter_note_file(&self, row: u16) -> Option<std::path::PathBuf> {
+        let drawn = self
+            .drawn_gutter_markers
+            .iter()
+            .find(|drawn| drawn.row == row && drawn.marker == GutterMarker::NoteFile)?;
+        let node_idx = self
+            .rendered_content
+            .lines
+            .get(drawn.line_idx)?
+            .node_index?;
+        self.note_file_for_node(node_idx)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::markdown::LinkType;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+
+/// When external links are sent to the terminal as OSC 8 hyperlinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HyperlinkMode {
+    /// When the terminal looks like one that supports them
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl HyperlinkMode {
+    pub fn enabled(self, terminal_supports_hyperlinks: bool) -> bool {
+        match self {
+            HyperlinkMode::Auto => terminal_supports_hyperlinks,
+            HyperlinkMode::Always => true,
+            HyperlinkMode::Never => false,
+        }
+    }
+}
+
+/// `text` as part of a hyperlink to `url`. Ratatui counts the escapes into the width of
+/// the cell, so when flushing a frame it skips the cell after one carrying them
+/// (ratatui#902); `text` is that cell's glyph too, or a wide glyph covering it.
+fn hyperlink_symbol(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+fn cell_width(buffer: &Buffer, x: usize, y: u16) -> usize {
+    buffer
+        .cell((x as u16, y))
+        .map_or(0, |cell| textwrap::core::display_width(cell.symbol()))
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    pub fn set_hyperlinks(&mut self, enabled: bool) {
+        self.hyperlinks = enabled;
+    }
+
+    /// Turn the cells of the external links on screen into OSC 8 hyperlinks, so the
+    /// terminal opens them itself on Ctrl+click. `inserted` is the line the comment box
+    /// was opened before and how many rows it takes.
+    pub(super) fn mark_hyperlinks(
+        &self,
+        buffer: &mut Buffer,
+        text_area: Rect,
+        inserted: Option<(usize, usize)>,
+    ) {
+        // Double-width glyphs no longer line up with the link columns
+        if !self.hyperlinks || self.is_double_width() {
+            return;
+        }
+        let end_offset =
+            (self.scroll_offset + self.visible_height).min(self.rendered_content.lines.len());
+        for line_idx in self.scroll_offset..end_offset {
+            let mut row = line_idx - self.scroll_offset;
+            if let Some((insert_pos, rows)) = inserted
+                && line_idx >= insert_pos
+            {
+                row += rows;
+            }
+            if row >= text_area.height as usize {
+                break;
+            }
+            let y = text_area.y + row as u16;
+
+            for link in &self.rendered_content.lines[line_idx].link_nodes {
+                // Anything else could smuggle escape sequences of its own to the terminal
+                if link.link_type != LinkType::External
+                    || !link.url.chars().all(|c| c.is_ascii_graphic())
+                {
+                    continue;
+                }
+                let start = text_area.x as usize + link.start_col;
+                let end = (text_area.x as usize + link.end_col).min(text_area.right() as usize);
+                let mut x = start;
+                while x < end {
+                    let width = cell_width(buffer, x, y);
+                    // Narrow glyphs go in pairs: the next cell is left for the first to
+                    // cover, as it's skipped when the frame is flushed anyway
+                    let covered = if width > 1 {
+                        None
+                    } else if x + 1 < text_area.right() as usize
+                        && cell_width(buffer, x + 1, y) == 1
+                    {
+                        Some(buffer[(x as u16 + 1, y)].symbol().to_string())
+                    } else {
+                        // Linking a lone glyph would hide whatever comes after it
+                        x += 1;
+                        continue;
+                    };
+                    let cell = &mut buffer[(x as u16, y)];
+                    let text = format!("{}{}", cell.symbol(), covered.unwrap_or_default());
+                    cell.set_symbol(&hyperlink_symbol(&link.url, &text));
+                    x += width.max(2);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+    use ratatui::text::Line;
+    use ratatui::widgets::{Paragraph, Widget};
+
+    #[test]
+    fn test_only_external_links_become_hyperlinks() {
+        let html = r##"<p>See <a href="https://example.com/a">the site</a> or
+            <a href="#intro">the intro</a>.</p>"##;
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = MarkdownTextReader::new();
+        reader.rendered_content = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+        reader.visible_height = 5;
+
+        let area = Rect::new(0, 0, 60, 5);
+        let draw = |reader: &MarkdownTextReader| {
+            let mut buffer = Buffer::empty(area);
+            let lines: Vec<Line> = reader
+                .rendered_content
+                .lines
+                .iter()
+                .map(|line| Line::from(line.spans.clone()))
+                .collect();
+            Paragraph::new(lines).render(area, &mut buffer);
+            reader.mark_hyperlinks(&mut buffer, area, None);
+            buffer
+        };
+
+        let plain = draw(&reader);
+        reader.set_hyperlinks(true);
+        let linked = draw(&reader);
+
+        let first_row: Vec<&str> = (0..60).map(|x| linked[(x, 0)].symbol()).collect();
+        let site_start = first_row.iter().position(|s| s.contains("th")).unwrap();
+        assert_eq!(
+            first_row[site_start],
+            "\x1b]8;;https://example.com/a\x1b\\th\x1b]8;;\x1b\\"
+        );
+        assert_eq!(first_row[site_start + 1], "h");
+        assert_eq!(
+            first_row[site_start + 6],
+            "\x1b]8;;https://example.com/a\x1b\\te\x1b]8;;\x1b\\"
+        );
+        // Only "the site" is linked, in four pairs, not the internal link after it
+        let linked_cells = first_row.iter().filter(|s| s.contains("\x1b]8")).count();
+        assert_eq!(linked_cells, 4);
+        let changed = (0..60)
+            .filter(|&x| plain[(x, 0)].symbol() != linked[(x, 0)].symbol())
+            .count();
+        assert_eq!(changed, 4);
+    }
+}
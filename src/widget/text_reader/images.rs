@@ -32,7 +32,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 }
                 vec
             }
-            EpubBlock { content, .. } => {
+            EpubBlock { content, .. } | Details { content, .. } => {
                 let mut vec = Vec::new();
                 for inner_node in content {
                     vec.append(&mut self.extract_images_from_node(inner_node, book_images));
@@ -192,6 +192,20 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         None
     }
 
+    /// The topmost image at least partly visible in the viewport
+    pub fn focused_image(&self) -> Option<String> {
+        let visible_end = self.scroll_offset + self.visible_height;
+        self.embedded_images
+            .borrow()
+            .iter()
+            .filter(|(_, image)| {
+                let image_end = image.lines_before_image + image.height_cells as usize;
+                image_end > self.scroll_offset && image.lines_before_image < visible_end
+            })
+            .min_by_key(|(_, image)| image.lines_before_image)
+            .map(|(src, _)| src.clone())
+    }
+
     pub fn get_image_picker(&self) -> Option<&Picker> {
         self.image_picker.as_ref()
     }
@@ -212,4 +226,21 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             .iter()
             .find(|&link| link.line == line && column >= link.start_col && column <= link.end_col)
     }
+
+    /// The link keyboard actions apply to: the first link touched by the selection,
+    /// or the first link visible in the viewport when nothing is selected
+    pub fn focused_link(&self) -> Option<&LinkInfo> {
+        if let Some((start, end)) = self.text_selection.get_selection_range() {
+            return self.links.iter().find(|link| {
+                (link.line, link.end_col) >= (start.line, start.column)
+                    && (link.line, link.start_col) <= (end.line, end.column)
+            });
+        }
+
+        let visible_end = self.scroll_offset + self.visible_height;
+        self.links
+            .iter()
+            .filter(|link| link.line >= self.scroll_offset && link.line < visible_end)
+            .min_by_key(|link| (link.line, link.start_col))
+    }
 }
@@ -0,0 +1,173 @@
+use super::types::*;
+use crate::book_notes::{BookNotes, CollectedNote};
+use crate::markdown::{Block as MarkdownBlock, Document, Inline, LinkType, Text, TextOrInline};
+use crate::theme::Base16Palette;
+use ratatui::style::Modifier;
+use ratatui::text::Span;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    pub fn is_inlining_notes(&self) -> bool {
+        self.inline_notes.is_some()
+    }
+
+    /// Show the notes a paragraph refers to right below it, or stop doing so with `None`
+    pub fn set_inline_notes(&mut self, notes: Option<Arc<BookNotes>>) {
+        self.inline_notes = notes;
+        self.cache_generation += 1;
+    }
+
+    /// Notes the links in `content` point to, in order of reference
+    fn referenced_notes(&self, content: &Text) -> Vec<CollectedNote> {
+        let Some(notes) = &self.inline_notes else {
+            return Vec::new();
+        };
+        let mut found: Vec<CollectedNote> = Vec::new();
+        for item in content.iter() {
+            let TextOrInline::Inline(Inline::Link {
+                link_type,
+                target_chapter,
+                target_anchor: Some(anchor),
+                ..
+            }) = item
+            else {
+                continue;
+            };
+            let chapter = match link_type {
+                LinkType::InternalAnchor => self.current_chapter_file.as_deref(),
+                LinkType::InternalChapter => target_chapter.as_deref(),
+                LinkType::External => None,
+            };
+            if let Some(note) = chapter.and_then(|chapter| notes.find(chapter, anchor))
+                && !found.iter().any(|seen| seen.id == note.id)
+            {
+                found.push(note.clone());
+            }
+        }
+        found
+    }
+
+    /// Remember which of this chapter's own notes get shown inline, so they aren't
+    /// repeated where the chapter keeps them
+    pub fn collect_inlined_note_ids(&mut self, doc: &Document) {
+        self.inlined_note_ids = HashSet::new();
+        let Some(chapter_file) = self.current_chapter_file.clone() else {
+            return;
+        };
+        for node in &doc.blocks {
+            if let MarkdownBlock::Paragraph { content } = &node.block {
+                let ids = self
+                    .referenced_notes(content)
+                    .into_iter()
+                    .filter(|note| note.chapter_href == chapter_file)
+                    .filter_map(|note| note.id);
+                self.inlined_note_ids.extend(ids);
+            }
+        }
+    }
+
+    pub fn is_inlined_note(&self, id: Option<&String>) -> bool {
+        id.is_some_and(|id| self.inlined_note_ids.contains(id))
+    }
+
+    /// The notes a paragraph refers to, indented below it behind a bar in quote colors
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_inline_notes(
+        &mut self,
+        content: &Text,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+        indent: usize,
+    ) {
+        let notes = self.referenced_notes(content);
+        if notes.is_empty() {
+            return;
+        }
+
+        let note_color = if is_focused {
+            palette.base_03
+        } else {
+            palette.base_02
+        };
+        for note in notes {
+            for node in &note.content {
+                let MarkdownBlock::Paragraph { content } = &node.block else {
+                    continue;
+                };
+                let rich_spans: Vec<RichSpan> = content
+                    .iter()
+                    .flat_map(|item| self.render_text_or_inline(item, palette, is_focused))
+                    .map(|rich_span| match rich_span {
+                        RichSpan::Text(span) => RichSpan::Text(Span::styled(
+                            span.content.clone(),
+                            span.style.fg(note_color).add_modifier(Modifier::ITALIC),
+                        )),
+                        RichSpan::Link { span, info } => RichSpan::Link {
+                            span: Span::styled(
+                                span.content.clone(),
+                                span.style.fg(note_color).add_modifier(Modifier::ITALIC),
+                            ),
+                            info,
+                        },
+                    })
+                    .collect();
+                self.render_text_spans(
+                    &rich_spans,
+                    Some("│ "),
+                    lines,
+                    total_height,
+                    width,
+                    indent + 1,
+                    false,
+                );
+            }
+        }
+
+        lines.push(RenderedLine::empty());
+        self.raw_text_lines.push(String::new());
+        *total_height += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::book_notes::BookNotes;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_notes_follow_their_paragraph() {
+        let html = r##"<p>A claim.<sup><a data-type="noteref" href="#n1">1</a></sup></p>
+            <p>The next paragraph.</p>
+            <aside epub:type="footnote" id="n1"><p>The source of the claim.</p></aside>"##;
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut notes = BookNotes::new();
+        notes.collect_from_chapter(0, "OEBPS/ch1.xhtml", "One", &doc);
+
+        let mut reader = MarkdownTextReader::new();
+        reader.set_current_chapter_file(Some("OEBPS/ch1.xhtml".to_string()));
+        reader.set_inline_notes(Some(Arc::new(notes)));
+        let rendered = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+        let rows: Vec<_> = rendered
+            .lines
+            .iter()
+            .map(|line| line.raw_text.trim_end())
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        assert_eq!(
+            rows,
+            [
+                "A claim.¹",
+                "  │ The source of the claim.",
+                "The next paragraph."
+            ]
+        );
+    }
+}
@@ -1,18 +1,40 @@
+mod admonitions;
+mod bionic;
 mod comments;
+mod diff_blocks;
+mod gutter;
+mod hyperlinks;
 mod images;
+mod inline_notes;
 mod navigation;
+mod paragraph_chunks;
+mod points_of_interest;
 mod rendering;
 mod search;
 mod selection;
 mod text_selection;
 mod types;
-
+mod watchlist;
+mod wrapping;
+mod zoom;
+
+pub use bionic::DEFAULT_BIONIC_RATIO;
+pub use gutter::GutterMarker;
+pub use hyperlinks::HyperlinkMode;
+pub(crate) use navigation::best_heading_match;
+pub use paragraph_chunks::DEFAULT_CHUNK_SENTENCES;
+pub use points_of_interest::{PointJump, PointOfInterest};
 pub use types::*;
+pub use zoom::MAX_ZOOM_LEVEL;
 
 use crate::comments::{BookComments, Comment};
 use crate::images::background_image_loader::BackgroundImageLoader;
 use crate::markdown::Document;
 use crate::markdown_text_reader::text_selection::TextSelection;
+use crate::parsing::content_filter::ContentFilters;
+use crate::parsing::css::StyleSheet;
+use crate::parsing::substitutions::Substitutions;
+use crate::parsing::text_cleanup::TextCleanup;
 use crate::ratatui_image::{Resize, StatefulImage, ViewportOptions, picker::Picker};
 use crate::search::SearchState;
 use crate::theme::Base16Palette;
@@ -27,7 +49,7 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -52,10 +74,15 @@ pub struct MarkdownTextReader {
     cache_generation: u64,
     last_width: usize,
     last_focus_state: bool,
+    /// Draws that reused the rendered lines, and draws that had to lay the chapter out again
+    layout_cache_hits: u64,
+    layout_cache_misses: u64,
 
     // Text selection
     text_selection: TextSelection,
     raw_text_lines: Vec<String>, // Still needed for clipboard
+    /// Alt text of the images rendered in this chapter, by the first line of their placeholder
+    image_alt_text: HashMap<usize, String>,
     last_content_area: Option<Rect>,
 
     last_inner_text_area: Option<Rect>, // Track the actual text rendering area
@@ -69,6 +96,9 @@ pub struct MarkdownTextReader {
 
     // Deferred node index to restore after rendering
     pending_node_restore: Option<usize>,
+    /// Position restored by the last re-layout and the scroll offset it gave, so repeated
+    /// re-layouts without scrolling don't drift from the original words
+    layout_anchor: Option<((usize, usize), usize)>,
 
     // Raw HTML mode
     show_raw_html: bool,
@@ -83,6 +113,9 @@ pub struct MarkdownTextReader {
     /// Map of anchor IDs to their line positions in rendered content
     anchor_positions: HashMap<String, usize>,
 
+    /// The anchors in `anchor_positions` made up for paragraphs without an id
+    synthetic_anchors: HashSet<String>,
+
     /// Current chapter filename (for resolving relative links)
     current_chapter_file: Option<String>,
 
@@ -92,17 +125,70 @@ pub struct MarkdownTextReader {
     /// Pending anchor scroll after chapter navigation
     pending_anchor_scroll: Option<String>,
 
+    /// Pending jump to a book-wide search result: its plain-text line and the query
+    pending_text_line_scroll: Option<(usize, String)>,
+
     /// Last active anchor for maintaining continuous highlighting
     last_active_anchor: Option<String>,
 
     /// Book comments to display alongside paragraphs
     book_comments: Option<Arc<Mutex<BookComments>>>,
     current_chapter_comments: HashMap<usize, Vec<Comment>>,
+    /// Line of the footnote, image, etc. jumped to last with `]f` and friends
+    last_point_of_interest: Option<usize>,
+    /// Other readers' notes imported for book club reading, with the reader's name
+    shared_comments: Vec<(String, Comment)>,
+    current_chapter_shared_comments: HashMap<usize, Vec<(String, Comment)>>,
 
     /// Comment input state
     comment_input: CommentInputState,
 
     chapter_title: Option<String>,
+
+    // Bionic reading: share of each word to emphasize, None when disabled
+    bionic_ratio: Option<f32>,
+    /// Sentences between the gaps left in long paragraphs, None when they're left whole
+    chunk_sentences: Option<usize>,
+    /// Send external links to the terminal as OSC 8 hyperlinks
+    hyperlinks: bool,
+    /// Terms highlighted wherever they appear in this book
+    watchlist: Vec<String>,
+
+    // Pin the current section heading to the top row while scrolling
+    sticky_heading: bool,
+    content_filters: ContentFilters,
+    text_cleanup: TextCleanup,
+    substitutions: Substitutions,
+    styles: Option<Arc<StyleSheet>>,
+    group_dialog: bool,
+    preserve_line_breaks: bool,
+    chapter_contents: bool,
+    /// Set while footnotes are shown under the paragraphs that refer to them
+    inline_notes: Option<Arc<crate::book_notes::BookNotes>>,
+    /// Ids of this chapter's notes that are shown inline, and not where the chapter has them
+    inlined_note_ids: HashSet<String>,
+    /// Urls of this chapter's links to chapters or anchors that don't exist in the book
+    broken_links: HashSet<String>,
+
+    // Content zoom: narrower, centered text column, optionally with double-width glyphs
+    zoom_level: u8,
+    zoom_double_width: bool,
+
+    // Lines of context kept above jump targets
+    scrolloff: usize,
+    // Animate page jumps towards `smooth_scroll_target`
+    smooth_scroll: bool,
+    smooth_scroll_target: Option<usize>,
+    // Show the chapter as whole screens that are flipped rather than scrolled
+    page_mode: bool,
+
+    // Top-level <details> blocks whose folding differs from their `open` attribute
+    details_toggled: HashSet<usize>,
+
+    // Annotation/bookmark markers left of the text
+    annotation_gutter: bool,
+    bookmark_marker: Option<(String, usize)>,
+    drawn_gutter_markers: Vec<gutter::DrawnMarker>,
 }
 
 impl Default for MarkdownTextReader {
@@ -186,8 +272,11 @@ impl MarkdownTextReader {
             cache_generation: 0,
             last_width: 0,
             last_focus_state: false,
+            layout_cache_hits: 0,
+            layout_cache_misses: 0,
             text_selection: TextSelection::new(),
             raw_text_lines: Vec::new(),
+            image_alt_text: HashMap::new(),
             last_content_area: None,
             last_inner_text_area: None,
             auto_scroll_active: false,
@@ -196,19 +285,50 @@ impl MarkdownTextReader {
             embedded_images: RefCell::new(HashMap::new()),
             background_loader: BackgroundImageLoader::new(),
             pending_node_restore: None,
+            layout_anchor: None,
             raw_html_content: None,
             show_raw_html: false,
             links: Vec::new(),
             embedded_tables: RefCell::new(Vec::new()),
             anchor_positions: HashMap::new(),
+            synthetic_anchors: HashSet::new(),
             current_chapter_file: None,
             search_state: SearchState::new(),
             pending_anchor_scroll: None,
+            pending_text_line_scroll: None,
             last_active_anchor: None,
             book_comments: None,
             current_chapter_comments: HashMap::new(),
+            last_point_of_interest: None,
+            shared_comments: Vec::new(),
+            current_chapter_shared_comments: HashMap::new(),
             comment_input: CommentInputState::default(),
             chapter_title: None,
+            bionic_ratio: None,
+            chunk_sentences: None,
+            hyperlinks: false,
+            watchlist: Vec::new(),
+            sticky_heading: false,
+            content_filters: ContentFilters::default(),
+            text_cleanup: TextCleanup::default(),
+            substitutions: Substitutions::default(),
+            styles: None,
+            group_dialog: true,
+            preserve_line_breaks: false,
+            chapter_contents: false,
+            inline_notes: None,
+            inlined_note_ids: HashSet::new(),
+            broken_links: HashSet::new(),
+            zoom_level: 0,
+            zoom_double_width: false,
+            scrolloff: 0,
+            smooth_scroll: false,
+            smooth_scroll_target: None,
+            page_mode: false,
+            details_toggled: HashSet::new(),
+            annotation_gutter: false,
+            bookmark_marker: None,
+            drawn_gutter_markers: Vec::new(),
         }
     }
 
@@ -220,6 +340,11 @@ impl MarkdownTextReader {
         let visible_end = (self.scroll_offset + self.visible_height).min(self.total_wrapped_lines);
         ((visible_end as f32 / self.total_wrapped_lines as f32) * 100.0) as u32
     }
+
+    /// How far into the chapter the bottom of the screen is, in percent
+    pub fn scroll_percent(&self) -> u32 {
+        self.calculate_progress("", 0, self.visible_height)
+    }
 }
 
 impl MarkdownTextReader {
@@ -243,46 +368,73 @@ impl MarkdownTextReader {
         }
 
         // Account for borders and side padding
-        let width = area.width.saturating_sub(4) as usize;
+        let width = self.zoomed_wrap_width(area.width.saturating_sub(4) as usize);
 
         // Re-render when dimensions, focus, or cached content change
         if self.last_width != width
             || self.last_focus_state != is_focused
             || self.rendered_content.generation != self.cache_generation
         {
+            self.layout_cache_misses += 1;
             if let Some(doc) = self.markdown_document.clone() {
+                // New content starts with no lines; anything else is a re-layout of the
+                // same text, which should keep the same words at the top
+                let layout_position = self.layout_position();
                 self.rendered_content =
                     self.render_document_to_lines(doc.as_ref(), width, palette, is_focused);
                 self.total_wrapped_lines = self.rendered_content.total_height;
                 self.last_width = width;
                 self.last_focus_state = is_focused;
 
+                if let Some(position) = layout_position {
+                    self.restore_layout_position(position);
+                }
+
                 if let Some(node_index) = self.pending_node_restore.take() {
                     self.perform_node_restore(node_index);
                 }
 
                 if let Some(anchor_id) = self.pending_anchor_scroll.take() {
-                    if let Some(target_line) = self.get_anchor_position(&anchor_id) {
+                    if let Some(target_line) = self.resolve_anchor_position(&anchor_id) {
                         self.scroll_to_line(target_line);
                         self.highlight_line_temporarily(target_line, Duration::from_secs(2));
                     } else {
                         warn!("Pending anchor '{anchor_id}' not found after re-render");
                     }
                 }
+
+                if let Some((text_line, query)) = self.pending_text_line_scroll.take() {
+                    if let Some(target_line) = self.text_line_position(text_line, &query) {
+                        self.scroll_to_line(target_line);
+                        self.highlight_line_temporarily(target_line, Duration::from_secs(2));
+                    }
+                }
             }
+        } else {
+            self.layout_cache_hits += 1;
         }
+        self.snap_to_page();
+
         let title_text = if let Some(ref title) = self.chapter_title {
             format!("[{current_chapter}/{total_chapters}] {title}")
         } else {
             format!("Chapter {current_chapter}/{total_chapters}")
         };
 
-        let progress = self.calculate_progress("", width, self.visible_height);
+        let progress = if self.page_mode {
+            let (page, pages) = self.page_position();
+            format!(" Page {page}/{pages} ")
+        } else {
+            format!(
+                " {}% ",
+                self.calculate_progress("", width, self.visible_height)
+            )
+        };
 
         let block = Block::default()
             .borders(Borders::ALL)
             .title(title_text)
-            .title_bottom(Line::from(format!(" {progress}% ")).right_aligned());
+            .title_bottom(Line::from(progress).right_aligned());
 
         // Remove borders so the text sits inside the frame cleanly
         let mut inner_area = block.inner(area);
@@ -290,6 +442,13 @@ impl MarkdownTextReader {
         inner_area.height = inner_area.height.saturating_sub(1);
         inner_area.x = inner_area.x.saturating_add(1);
 
+        // Center the narrower text column when zoomed in
+        let zoom_margin = (inner_area.width as usize)
+            .saturating_sub(self.zoomed_column_width(inner_area.width as usize))
+            / 2;
+        inner_area.x = inner_area.x.saturating_add(zoom_margin as u16);
+        inner_area.width = inner_area.width.saturating_sub(zoom_margin as u16);
+
         // Remember the focused text area for mouse hover/selection logic
         self.last_inner_text_area = Some(inner_area);
 
@@ -393,6 +552,12 @@ impl MarkdownTextReader {
             .wrap(ratatui::widgets::Wrap { trim: false });
 
         frame.render_widget(inner_text_paragraph, inner_area);
+        self.mark_hyperlinks(
+            frame.buffer_mut(),
+            inner_area,
+            textarea_insert_position.map(|pos| (pos, textarea_lines_to_insert)),
+        );
+        self.render_gutter(frame, area, inner_area, palette);
 
         // Second pass: draw inline images over the text block
         let scroll_offset = self.scroll_offset;
@@ -497,6 +662,20 @@ impl MarkdownTextReader {
             }
         }
 
+        // Third pass: pin the current section heading over the first row
+        if let Some(heading_idx) = self.sticky_heading_line() {
+            if let Some(heading_line) = self.rendered_content.lines.get(heading_idx) {
+                let sticky_area = Rect {
+                    height: inner_area.height.min(1),
+                    ..inner_area
+                };
+                let sticky = Paragraph::new(Line::from(heading_line.spans.clone()))
+                    .style(RatatuiStyle::default().bg(palette.base_01));
+                frame.render_widget(ratatui::widgets::Clear, sticky_area);
+                frame.render_widget(sticky, sticky_area);
+            }
+        }
+
         if self.comment_input.is_active() {
             if let Some(ref mut textarea) = self.comment_input.textarea {
                 if let Some(target_line) = self.comment_input.target_line {
@@ -601,6 +780,67 @@ impl MarkdownTextReader {
         frame.render_widget(paragraph, area);
     }
 
+    /// Elements stripped from chapter HTML before it is converted
+    pub fn set_content_filters(&mut self, filters: ContentFilters) {
+        self.content_filters = filters;
+    }
+
+    /// Repairs for scanned text applied to chapters as they are converted
+    pub fn set_text_cleanup(&mut self, cleanup: TextCleanup) {
+        self.text_cleanup = cleanup;
+    }
+
+    /// The current book's find/replace rules, for chapters converted from here on
+    pub fn set_substitutions(&mut self, substitutions: Substitutions) {
+        self.substitutions = substitutions;
+    }
+
+    pub fn substitutions(&self) -> &Substitutions {
+        &self.substitutions
+    }
+
+    /// The current book's stylesheet, when its alignment and indentation are honored
+    pub fn set_styles(&mut self, styles: Option<Arc<StyleSheet>>) {
+        self.styles = styles;
+    }
+
+    /// Whether chapters converted from here on join dash-led dialog paragraphs
+    pub fn set_dialog_grouping(&mut self, enabled: bool) {
+        self.group_dialog = enabled;
+    }
+
+    pub fn dialog_grouping(&self) -> bool {
+        self.group_dialog
+    }
+
+    /// Whether chapters converted from here on keep every `<br/>` and the spacing around it
+    pub fn set_line_breaks_preserved(&mut self, enabled: bool) {
+        self.preserve_line_breaks = enabled;
+    }
+
+    pub fn line_breaks_preserved(&self) -> bool {
+        self.preserve_line_breaks
+    }
+
+    /// Whether chapters converted from here on list their sections under their title
+    pub fn set_chapter_contents(&mut self, enabled: bool) {
+        self.chapter_contents = enabled;
+    }
+
+    /// A converter set up the way chapters are converted for reading. Anything that finds
+    /// blocks by their index in a chapter (notes, the timeline, flashcards) has to convert
+    /// with one of these, or the indices won't match what's on screen.
+    pub fn converter(&self) -> crate::parsing::html_to_markdown::HtmlToMarkdownConverter {
+        crate::parsing::html_to_markdown::HtmlToMarkdownConverter::new()
+            .with_filters(self.content_filters.clone())
+            .with_cleanup(self.text_cleanup)
+            .with_substitutions(self.substitutions.clone())
+            .with_styles(self.styles.clone())
+            .with_dialog_grouping(self.group_dialog)
+            .with_line_breaks_preserved(self.preserve_line_breaks)
+            .with_chapter_contents(self.chapter_contents)
+    }
+
     pub fn set_content_from_string(
         &mut self,
         content_raw_html: &str,
@@ -608,9 +848,7 @@ impl MarkdownTextReader {
     ) {
         self.clear_content();
 
-        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
-        let mut converter = HtmlToMarkdownConverter::new();
-        let doc = Arc::new(converter.convert(content_raw_html));
+        let doc = Arc::new(self.converter().convert(content_raw_html));
 
         self.markdown_document = Some(doc);
         self.chapter_title = chapter_title;
@@ -619,9 +857,40 @@ impl MarkdownTextReader {
         self.cache_generation += 1;
     }
 
+    /// Show a document that doesn't come from a spine item (e.g. the compiled notes chapter)
+    pub fn set_content_from_document(&mut self, doc: Document, chapter_title: Option<String>) {
+        self.clear_content();
+
+        self.markdown_document = Some(Arc::new(doc));
+        self.chapter_title = chapter_title;
+
+        self.cache_generation += 1;
+    }
+
+    /// Show a chapter that was already converted, e.g. by the chapter prefetcher
+    pub fn set_content_from_shared_document(
+        &mut self,
+        doc: Arc<Document>,
+        chapter_title: Option<String>,
+    ) {
+        self.clear_content();
+
+        self.markdown_document = Some(doc);
+        self.chapter_title = chapter_title;
+
+        self.cache_generation += 1;
+    }
+
+    /// The converted document currently shown, shared so it can be cached
+    pub fn shared_document(&self) -> Option<Arc<Document>> {
+        self.markdown_document.clone()
+    }
+
     pub fn clear_content(&mut self) {
         self.scroll_offset = 0;
+        self.layout_anchor = None;
         self.text_selection.clear_selection();
+        self.details_toggled.clear();
 
         // IMPORTANT: Clear the markdown document so new content can be parsed
         self.markdown_document = None;
@@ -629,8 +898,10 @@ impl MarkdownTextReader {
         self.cache_generation += 1;
 
         self.links.clear();
+        self.broken_links.clear();
         self.embedded_tables.borrow_mut().clear();
         self.raw_text_lines.clear();
+        self.image_alt_text.clear();
         self.rendered_content = RenderedContent {
             lines: Vec::new(),
             total_height: 0,
@@ -639,6 +910,12 @@ impl MarkdownTextReader {
         self.embedded_images.borrow_mut().clear();
     }
 
+    /// Style the links with these urls as broken; cleared with the content
+    pub fn set_broken_links(&mut self, urls: HashSet<String>) {
+        self.broken_links = urls;
+        self.cache_generation += 1;
+    }
+
     pub fn set_raw_html(&mut self, html: String) {
         self.raw_html_content = Some(html);
     }
@@ -647,6 +924,11 @@ impl MarkdownTextReader {
         self.show_raw_html = !self.show_raw_html;
     }
 
+    /// How many draws reused the laid-out chapter and how many laid it out again
+    pub fn layout_cache_stats(&self) -> (u64, u64) {
+        (self.layout_cache_hits, self.layout_cache_misses)
+    }
+
     pub fn handle_terminal_resize(&mut self) {
         self.cache_generation += 1;
     }
@@ -1,10 +1,55 @@
 use super::types::*;
 use crate::main_app::VimNavMotions;
 use crate::search::SearchMode;
+use log::info;
+use std::collections::HashSet;
 use std::time::Instant;
 
+/// How alike (Dice coefficient of their words) a heading must be to a missing anchor's
+/// name to stand in for it
+const FUZZY_ANCHOR_MIN_SIMILARITY: f64 = 0.5;
+
+/// Words of an anchor id or heading, lowercased. Tokens with digits (`ch03`, generated
+/// suffixes like `1730150757064067`) say little about the heading and are left out.
+fn anchor_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !word.chars().any(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Of `headings` (a position and the heading's text), the one closest to `anchor_id`, if
+/// any is alike enough to stand in for it. Ties go to the earliest.
+pub(crate) fn best_heading_match<T: Ord>(
+    anchor_id: &str,
+    headings: impl IntoIterator<Item = (T, String)>,
+) -> Option<T> {
+    let wanted = anchor_words(anchor_id);
+    if wanted.is_empty() {
+        return None;
+    }
+    headings
+        .into_iter()
+        .filter_map(|(position, text)| {
+            let words = anchor_words(&text);
+            if words.is_empty() {
+                return None;
+            }
+            let shared = wanted.intersection(&words).count();
+            let similarity = 2.0 * shared as f64 / (wanted.len() + words.len()) as f64;
+            (similarity >= FUZZY_ANCHOR_MIN_SIMILARITY).then_some((position, similarity))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(position, _)| position)
+}
+
 impl crate::markdown_text_reader::MarkdownTextReader {
     pub fn scroll_up(&mut self) {
+        if self.page_mode {
+            self.previous_page();
+            return;
+        }
+        self.smooth_scroll_target = None;
         if self.scroll_offset > 0 {
             self.scroll_offset = self.scroll_offset.saturating_sub(self.scroll_speed);
             self.last_scroll_time = Instant::now();
@@ -15,6 +60,11 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     }
 
     pub fn scroll_down(&mut self) {
+        if self.page_mode {
+            self.next_page();
+            return;
+        }
+        self.smooth_scroll_target = None;
         let max_offset = self.get_max_scroll_offset();
         if self.scroll_offset < max_offset {
             self.scroll_offset = (self.scroll_offset + self.scroll_speed).min(max_offset);
@@ -26,8 +76,12 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     }
 
     pub fn scroll_half_screen_up(&mut self, screen_height: usize) {
+        if self.page_mode {
+            self.previous_page();
+            return;
+        }
         let scroll_amount = screen_height / 2;
-        self.scroll_offset = self.scroll_offset.saturating_sub(scroll_amount);
+        self.scroll_page_to(self.page_scroll_base().saturating_sub(scroll_amount));
         self.highlight_visual_line = Some(0);
         self.highlight_end_time = Instant::now() + std::time::Duration::from_millis(150);
         // Clear current match when manually scrolling so next 'n' finds from new position
@@ -37,9 +91,13 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     }
 
     pub fn scroll_half_screen_down(&mut self, screen_height: usize) {
+        if self.page_mode {
+            self.next_page();
+            return;
+        }
         let scroll_amount = screen_height / 2;
         let max_offset = self.get_max_scroll_offset();
-        self.scroll_offset = (self.scroll_offset + scroll_amount).min(max_offset);
+        self.scroll_page_to((self.page_scroll_base() + scroll_amount).min(max_offset));
         self.highlight_visual_line = Some(screen_height - 1);
         self.highlight_end_time = Instant::now() + std::time::Duration::from_millis(150);
         // Clear current match when manually scrolling so next 'n' finds from new position
@@ -48,18 +106,141 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    pub fn set_scroll_behavior(&mut self, scrolloff: usize, smooth_scroll: bool) {
+        self.scrolloff = scrolloff;
+        self.smooth_scroll = smooth_scroll;
+        self.smooth_scroll_target = None;
+    }
+
+    /// Page jumps continue from where a running animation is heading
+    fn page_scroll_base(&self) -> usize {
+        self.smooth_scroll_target.unwrap_or(self.scroll_offset)
+    }
+
+    /// Scroll for page jumps (half-page, top/bottom): animated when smooth scrolling is on
+    fn scroll_page_to(&mut self, offset: usize) {
+        if self.page_mode {
+            self.smooth_scroll_target = None;
+            self.scroll_offset = self.page_start(offset);
+        } else if self.smooth_scroll && offset != self.scroll_offset {
+            self.smooth_scroll_target = Some(offset);
+        } else {
+            self.smooth_scroll_target = None;
+            self.scroll_offset = offset;
+        }
+    }
+
+    /// Advance a running smooth scroll by one frame; returns true if the view moved
+    pub fn update_smooth_scroll(&mut self) -> bool {
+        let Some(target) = self.smooth_scroll_target else {
+            return false;
+        };
+
+        // Ease out: cover a third of the remaining distance each frame
+        let distance = target.abs_diff(self.scroll_offset);
+        let step = (distance / 3).max(1);
+        if target > self.scroll_offset {
+            self.scroll_offset += step;
+        } else {
+            self.scroll_offset -= step;
+        }
+        if self.scroll_offset == target {
+            self.smooth_scroll_target = None;
+        }
+        true
+    }
+
     pub fn get_scroll_offset(&self) -> usize {
         self.scroll_offset
     }
 
+    /// Lines of text that fit on screen at the last render
+    pub fn visible_height(&self) -> usize {
+        self.visible_height
+    }
+
     pub fn get_max_scroll_offset(&self) -> usize {
+        if self.page_mode {
+            return self.page_start(self.total_wrapped_lines.saturating_sub(1));
+        }
         self.total_wrapped_lines.saturating_sub(self.visible_height)
     }
 
+    pub fn set_page_mode(&mut self, enabled: bool) {
+        self.page_mode = enabled;
+        self.smooth_scroll_target = None;
+        self.snap_to_page();
+    }
+
+    pub fn is_page_mode(&self) -> bool {
+        self.page_mode
+    }
+
+    /// First line of the page that holds `line`
+    fn page_start(&self, line: usize) -> usize {
+        let page_height = self.visible_height.max(1);
+        line / page_height * page_height
+    }
+
+    /// In page mode, move the view back to the start of the page it is on
+    pub fn snap_to_page(&mut self) {
+        if self.page_mode {
+            self.scroll_offset = self.page_start(self.scroll_offset);
+        }
+    }
+
+    /// The current page and the number of pages of the chapter, counting from 1
+    pub fn page_position(&self) -> (usize, usize) {
+        let page_height = self.visible_height.max(1);
+        (
+            self.scroll_offset / page_height + 1,
+            self.total_wrapped_lines.div_ceil(page_height).max(1),
+        )
+    }
+
+    /// Flip to the next page; false when already on the last one
+    pub fn next_page(&mut self) -> bool {
+        let next = self.page_start(self.scroll_offset) + self.visible_height.max(1);
+        if next >= self.total_wrapped_lines {
+            return false;
+        }
+        self.flip_to(next);
+        true
+    }
+
+    /// Flip back a page; false when already on the first one
+    pub fn previous_page(&mut self) -> bool {
+        let current = self.page_start(self.scroll_offset);
+        if current == 0 {
+            return false;
+        }
+        self.flip_to(current.saturating_sub(self.visible_height.max(1)));
+        true
+    }
+
+    fn flip_to(&mut self, offset: usize) {
+        self.smooth_scroll_target = None;
+        self.scroll_offset = offset;
+        self.last_scroll_time = Instant::now();
+        if self.search_state.active && self.search_state.mode == SearchMode::NavigationMode {
+            self.search_state.current_match_index = None;
+        }
+    }
+
     pub fn scroll_to_line(&mut self, target_line: usize) {
+        self.smooth_scroll_target = None;
+        if self.page_mode {
+            self.scroll_offset = self.page_start(target_line);
+            return;
+        }
         // Center target line in viewport if possible
         let desired_offset = if target_line > self.visible_height / 2 {
-            target_line // - self.visible_height  / 2
+            // Keep `scrolloff` lines of context above the target and below it; a margin
+            // too big for both centers the target instead, as in vim
+            let margin = self
+                .scrolloff
+                .min(self.visible_height.saturating_sub(1) / 2);
+            target_line.saturating_sub(margin)
         } else {
             0
         };
@@ -79,6 +260,9 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 .total_height
                 .saturating_sub(self.visible_height);
             self.scroll_offset = self.scroll_offset.min(max_scroll);
+            if self.page_mode {
+                self.scroll_offset = self.page_start(line_idx);
+            }
         }
     }
 
@@ -86,11 +270,103 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.anchor_positions.get(anchor_id).copied()
     }
 
+    /// Where an anchor is; when the chapter has no such id, the heading whose text best
+    /// matches the anchor's name instead (sloppy EPUBs link to ids they never define)
+    pub fn resolve_anchor_position(&self, anchor_id: &str) -> Option<usize> {
+        self.get_anchor_position(anchor_id).or_else(|| {
+            let line = self.heading_matching(anchor_id)?;
+            info!("Anchor '{anchor_id}' missing; using the closest heading at line {line}");
+            Some(line)
+        })
+    }
+
+    /// First line of the heading most similar to `anchor_id`, if any is similar enough
+    fn heading_matching(&self, anchor_id: &str) -> Option<usize> {
+        // Wrapped headings span several lines; collect each one's text from its first line
+        let mut headings: Vec<(usize, String)> = Vec::new();
+        let mut previous_was_heading = false;
+        for (idx, line) in self.rendered_content.lines.iter().enumerate() {
+            let is_heading = matches!(
+                line.line_type,
+                LineType::Heading {
+                    needs_decoration: false,
+                    ..
+                }
+            );
+            match headings.last_mut() {
+                Some((_, text)) if is_heading && previous_was_heading => {
+                    text.push(' ');
+                    text.push_str(&line.raw_text);
+                }
+                _ if is_heading => headings.push((idx, line.raw_text.clone())),
+                _ => {}
+            }
+            previous_was_heading = is_heading;
+        }
+
+        best_heading_match(anchor_id, headings)
+    }
+
+    /// The last anchor at or above `line`, i.e. the closest named position before it.
+    /// On the same line the book's own ids win over synthetic paragraph anchors.
+    pub fn anchor_at_or_before(&self, line: usize) -> Option<&str> {
+        self.anchor_positions
+            .iter()
+            .filter(|(_, position)| **position <= line)
+            .max_by(|(a_id, a_pos), (b_id, b_pos)| {
+                a_pos
+                    .cmp(b_pos)
+                    .then(
+                        self.synthetic_anchors
+                            .contains(b_id.as_str())
+                            .cmp(&self.synthetic_anchors.contains(a_id.as_str())),
+                    )
+                    .then(b_id.cmp(a_id))
+            })
+            .map(|(id, _)| id.as_str())
+    }
+
     pub fn store_pending_anchor_scroll(&mut self, pending_anchor: String) {
         // Store the pending anchor to be processed after anchors are collected
         self.pending_anchor_scroll = Some(pending_anchor);
     }
 
+    /// Scroll, once the chapter is rendered, to line `text_line` of its plain text (as the
+    /// book-wide search numbers lines), on the row where `query` shows up
+    pub fn store_pending_text_line_scroll(&mut self, text_line: usize, query: String) {
+        self.pending_text_line_scroll = Some((text_line, query));
+    }
+
+    /// The rendered line showing line `text_line` of the chapter's plain text. A paragraph
+    /// wraps over several rows, so this is the first of them containing a word of `query`.
+    pub(super) fn text_line_position(&self, text_line: usize, query: &str) -> Option<usize> {
+        let block = self
+            .markdown_document
+            .as_ref()?
+            .block_of_plain_text_line(text_line)?;
+        let lines = &self.rendered_content.lines;
+        let start = lines
+            .iter()
+            .position(|line| line.node_index.is_some_and(|idx| idx >= block))?;
+        let end = lines[start + 1..]
+            .iter()
+            .position(|line| line.node_index.is_some())
+            .map_or(lines.len(), |offset| start + 1 + offset);
+
+        let words: Vec<String> = query
+            .trim()
+            .trim_matches('"')
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        let searchable = self.get_visible_text();
+        let row = (start..end).find(|&idx| {
+            let text = searchable[idx].to_lowercase();
+            words.iter().any(|word| text.contains(word.as_str()))
+        });
+        Some(row.unwrap_or(start))
+    }
+
     //todo: remove
     pub fn highlight_line_temporarily(&mut self, line: usize, duration: std::time::Duration) {
         if line >= self.scroll_offset && line < self.scroll_offset + self.visible_height {
@@ -227,6 +503,125 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         0
     }
 
+    /// The table the reader is looking at
+    pub fn focused_table_index(&self) -> Option<usize> {
+        self.focused_block_index(|block| matches!(block, crate::markdown::Block::Table { .. }))
+    }
+
+    /// The code block the reader is looking at
+    pub fn focused_code_block_index(&self) -> Option<usize> {
+        self.focused_block_index(|block| matches!(block, crate::markdown::Block::CodeBlock { .. }))
+    }
+
+    pub fn is_details_expanded(&self, node_index: usize, open: bool) -> bool {
+        open != self.details_toggled.contains(&node_index)
+    }
+
+    /// Fold or unfold the `<details>` block on screen; false if there is none
+    pub fn toggle_focused_details(&mut self) -> bool {
+        let Some(node_index) = self
+            .focused_block_index(|block| matches!(block, crate::markdown::Block::Details { .. }))
+        else {
+            return false;
+        };
+
+        if !self.details_toggled.remove(&node_index) {
+            self.details_toggled.insert(node_index);
+        }
+        self.cache_generation += 1;
+
+        // Folding from inside the content brings the summary line back into view
+        if let Some(summary_line) = self
+            .rendered_content
+            .lines
+            .iter()
+            .position(|line| line.node_index == Some(node_index))
+        {
+            self.scroll_offset = self.scroll_offset.min(summary_line);
+        }
+        true
+    }
+
+    /// The block matching `is_wanted` that the top line is part of, or else the first
+    /// such block that starts on screen
+    fn focused_block_index(
+        &self,
+        is_wanted: impl Fn(&crate::markdown::Block) -> bool,
+    ) -> Option<usize> {
+        let doc = self.markdown_document.as_ref()?;
+        let is_wanted = |node_idx: usize| {
+            doc.blocks
+                .get(node_idx)
+                .is_some_and(|node| is_wanted(&node.block))
+        };
+
+        let visible_start = self.scroll_offset;
+        let visible_end = visible_start + self.visible_height.max(1);
+        let mut node_at_top = None;
+        for (line_idx, line) in self.rendered_content.lines.iter().enumerate() {
+            if line_idx >= visible_end {
+                break;
+            }
+            let Some(node_idx) = line.node_index else {
+                continue;
+            };
+            if line_idx <= visible_start {
+                node_at_top = Some(node_idx);
+            } else if is_wanted(node_idx) {
+                return node_at_top.filter(|idx| is_wanted(*idx)).or(Some(node_idx));
+            }
+        }
+        node_at_top.filter(|idx| is_wanted(*idx))
+    }
+
+    /// Where the top of the viewport is, independent of how the text is wrapped: the
+    /// top-level block there and how many of its words are scrolled past
+    pub(super) fn layout_position(&self) -> Option<(usize, usize)> {
+        if let Some((position, offset)) = self.layout_anchor {
+            if offset == self.scroll_offset {
+                return Some(position);
+            }
+        }
+        let lines = &self.rendered_content.lines;
+        let top = self.scroll_offset.min(lines.len().checked_sub(1)?);
+        let start = (0..=top)
+            .rev()
+            .find(|&idx| lines[idx].node_index.is_some())?;
+        let words = lines[start..top]
+            .iter()
+            .map(|line| line.raw_text.split_whitespace().count())
+            .sum();
+        Some((lines[start].node_index?, words))
+    }
+
+    /// Scroll back to a position taken by `layout_position` after the text was re-wrapped
+    pub(super) fn restore_layout_position(&mut self, (block, words): (usize, usize)) {
+        let lines = &self.rendered_content.lines;
+        let Some(start) = lines
+            .iter()
+            .position(|line| line.node_index.is_some_and(|idx| idx >= block))
+        else {
+            return;
+        };
+        let mut row = start;
+        let mut words_before = 0;
+        // Stop on the row holding the first word that was at the top
+        while lines
+            .get(row + 1)
+            .is_some_and(|line| line.node_index.is_none())
+        {
+            let row_words = lines[row].raw_text.split_whitespace().count();
+            if words_before + row_words > words {
+                break;
+            }
+            words_before += row_words;
+            row += 1;
+        }
+        self.smooth_scroll_target = None;
+        self.scroll_offset = row.min(self.get_max_scroll_offset());
+        self.layout_anchor = Some(((block, words), self.scroll_offset));
+    }
+
     /// Restore scroll position to show a specific node
     pub fn restore_to_node_index(&mut self, node_index: usize) {
         self.pending_node_restore = Some(node_index);
@@ -243,6 +638,55 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    pub fn set_sticky_heading(&mut self, enabled: bool) {
+        self.sticky_heading = enabled;
+    }
+
+    /// First rendered line of the heading whose section is being scrolled through,
+    /// or None when that heading is still visible (or sticky headings are off)
+    pub fn sticky_heading_line(&self) -> Option<usize> {
+        if !self.sticky_heading || self.scroll_offset == 0 {
+            return None;
+        }
+
+        let lines = &self.rendered_content.lines;
+        if matches!(
+            lines.get(self.scroll_offset).map(|l| &l.line_type),
+            Some(LineType::Heading { .. })
+        ) {
+            return None;
+        }
+
+        let mut heading_idx = (0..self.scroll_offset.min(lines.len()))
+            .rev()
+            .find(|&idx| matches!(lines[idx].line_type, LineType::Heading { .. }))?;
+
+        // Pin the heading's text rather than the line under it
+        while heading_idx > 0
+            && matches!(
+                lines[heading_idx].line_type,
+                LineType::Heading {
+                    needs_decoration: true,
+                    ..
+                }
+            )
+            && matches!(lines[heading_idx - 1].line_type, LineType::Heading { .. })
+        {
+            heading_idx -= 1;
+        }
+
+        // Wrapped headings span several lines; pin the first one
+        let node_index = lines[heading_idx].node_index;
+        while heading_idx > 0
+            && matches!(lines[heading_idx - 1].line_type, LineType::Heading { .. })
+            && lines[heading_idx - 1].node_index == node_index
+        {
+            heading_idx -= 1;
+        }
+
+        Some(heading_idx)
+    }
+
     pub fn set_current_chapter_file(&mut self, chapter_file: Option<String>) {
         self.current_chapter_file = chapter_file;
         self.rebuild_chapter_comments();
@@ -251,6 +695,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     pub fn get_current_chapter_file(&self) -> &Option<String> {
         &self.current_chapter_file
     }
+
+    pub fn get_chapter_title(&self) -> Option<&str> {
+        self.chapter_title.as_deref()
+    }
 }
 
 impl VimNavMotions for crate::markdown_text_reader::MarkdownTextReader {
@@ -285,11 +733,238 @@ impl VimNavMotions for crate::markdown_text_reader::MarkdownTextReader {
     }
 
     fn handle_gg(&mut self) {
-        self.scroll_offset = 0;
+        self.scroll_page_to(0);
     }
 
     fn handle_upper_g(&mut self) {
         let max_offset = self.get_max_scroll_offset();
-        self.scroll_offset = max_offset;
+        self.scroll_page_to(max_offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineType;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+
+    #[test]
+    fn test_missing_anchor_falls_back_to_closest_heading() {
+        let html = "<h2 id=\"intro\">Introduction</h2><p>Text.</p>\
+                    <h2>Evaluation Methodology</h2><p>More text.</p>\
+                    <h2>Closing Thoughts</h2><p>The end.</p>";
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = MarkdownTextReader::new();
+        reader.rendered_content = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+
+        let heading_line = |title: &str| {
+            reader
+                .rendered_content
+                .lines
+                .iter()
+                .position(|line| line.raw_text == title)
+        };
+        assert_eq!(
+            reader.resolve_anchor_position("intro"),
+            heading_line("Introduction")
+        );
+        assert_eq!(
+            reader.resolve_anchor_position("ch03a_evaluation_methodology_1730150757064067"),
+            heading_line("Evaluation Methodology")
+        );
+        assert_eq!(reader.resolve_anchor_position("appendix_b"), None);
+    }
+
+    #[test]
+    fn test_book_ids_win_over_made_up_paragraph_anchors() {
+        // Books are free to use ids that look like the made-up ones
+        let doc = HtmlToMarkdownConverter::new()
+            .convert(r#"<p><a id="p-intro"></a>First.</p><p>Second.</p>"#);
+        let mut reader = MarkdownTextReader::new();
+        reader.set_current_chapter_file(Some("OEBPS/ch1.xhtml".to_string()));
+        reader.rendered_content = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+
+        assert_eq!(reader.anchor_at_or_before(0), Some("p-intro"));
+        let second =
+            MarkdownTextReader::synthetic_paragraph_anchor("OEBPS/ch1.xhtml", 1, "Second.");
+        assert_eq!(reader.anchor_at_or_before(2), Some(second.as_str()));
+    }
+
+    #[test]
+    fn test_rewrapping_keeps_the_top_word_in_view() {
+        let html: String = (0..10)
+            .map(|p| {
+                let words: Vec<String> = (0..60).map(|w| format!("w{p}x{w}")).collect();
+                format!("<p>{}</p>", words.join(" "))
+            })
+            .collect();
+        let doc = HtmlToMarkdownConverter::new().convert(&html);
+        let mut reader = MarkdownTextReader::new();
+        reader.visible_height = 10;
+        let layout = |reader: &mut MarkdownTextReader, width: usize| {
+            reader.rendered_content =
+                reader.render_document_to_lines(&doc, width, &OCEANIC_NEXT, true);
+            reader.total_wrapped_lines = reader.rendered_content.total_height;
+        };
+        let top_words = |reader: &MarkdownTextReader| {
+            reader.rendered_content.lines[reader.scroll_offset]
+                .raw_text
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        layout(&mut reader, 70);
+        reader.scroll_offset = reader
+            .rendered_content
+            .lines
+            .iter()
+            .position(|line| line.raw_text.contains("w5x30 "))
+            .unwrap();
+        let first_word = top_words(&reader)[0].clone();
+
+        for width in [40, 100, 23] {
+            let position = reader.layout_position().unwrap();
+            layout(&mut reader, width);
+            reader.restore_layout_position(position);
+            assert!(
+                top_words(&reader).contains(&first_word),
+                "{first_word} should stay at the top at width {width}"
+            );
+        }
+    }
+
+    /// A reader laid out 60 columns wide, with the line index of each heading
+    fn sticky_reader(html: &str) -> (MarkdownTextReader, Vec<usize>) {
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = MarkdownTextReader::new();
+        reader.set_sticky_heading(true);
+        reader.rendered_content = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+        let headings = reader
+            .rendered_content
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                matches!(
+                    line.line_type,
+                    LineType::Heading {
+                        needs_decoration: false,
+                        ..
+                    }
+                )
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        (reader, headings)
+    }
+
+    #[test]
+    fn test_no_sticky_heading_without_a_heading_above() {
+        let (mut reader, headings) =
+            sticky_reader("<p>One.</p><p>Two.</p><p>Three.</p><h2>Later</h2><p>Four.</p>");
+        reader.scroll_offset = 2;
+        assert!(headings[0] > 2);
+        assert_eq!(reader.sticky_heading_line(), None);
+
+        // Nor at the very top, or with the setting off
+        reader.scroll_offset = 0;
+        assert_eq!(reader.sticky_heading_line(), None);
+        reader.scroll_offset = headings[0] + 1;
+        reader.set_sticky_heading(false);
+        assert_eq!(reader.sticky_heading_line(), None);
+    }
+
+    #[test]
+    fn test_heading_scrolled_past_sticks_until_the_next_reaches_the_top() {
+        let (mut reader, headings) = sticky_reader(
+            "<h2>First</h2><p>One.</p><p>Two.</p><p>Three.</p>\
+             <h2>Second</h2><p>Four.</p><p>Five.</p>",
+        );
+        let (first, second) = (headings[0], headings[1]);
+
+        // The first heading still in view, then scrolled off the top with its underline
+        reader.scroll_offset = first;
+        assert_eq!(reader.sticky_heading_line(), None);
+        reader.scroll_offset = first + 2;
+        assert_eq!(reader.sticky_heading_line(), Some(first));
+        reader.scroll_offset = second - 1;
+        assert_eq!(reader.sticky_heading_line(), Some(first));
+
+        // The next heading on the top row takes over from it
+        reader.scroll_offset = second;
+        assert_eq!(reader.sticky_heading_line(), None);
+        reader.scroll_offset = second + 2;
+        assert_eq!(reader.sticky_heading_line(), Some(second));
+    }
+
+    /// A reader with 100 one-line paragraphs (200 lines) and a 10-line view
+    fn scrolling_reader(scrolloff: usize, smooth_scroll: bool) -> MarkdownTextReader {
+        let html: String = (0..100).map(|p| format!("<p>Line {p}.</p>")).collect();
+        let doc = HtmlToMarkdownConverter::new().convert(&html);
+        let mut reader = MarkdownTextReader::new();
+        reader.set_scroll_behavior(scrolloff, smooth_scroll);
+        reader.visible_height = 10;
+        reader.rendered_content = reader.render_document_to_lines(&doc, 60, &OCEANIC_NEXT, true);
+        reader.total_wrapped_lines = reader.rendered_content.total_height;
+        reader
+    }
+
+    #[test]
+    fn test_scrolloff_keeps_lines_between_the_target_and_both_edges() {
+        let mut reader = scrolling_reader(3, false);
+        reader.scroll_to_line(50);
+        assert_eq!(reader.scroll_offset, 47);
+        let below = reader.scroll_offset + reader.visible_height - 1 - 50;
+        assert!(below >= 3, "{below} lines below the target");
+
+        // Without a margin the target goes to the top
+        let mut reader = scrolling_reader(0, false);
+        reader.scroll_to_line(50);
+        assert_eq!(reader.scroll_offset, 50);
+
+        // More than half the view can't be kept on both sides: the target is centered
+        let mut reader = scrolling_reader(8, false);
+        reader.scroll_to_line(50);
+        assert_eq!(reader.scroll_offset, 46);
+        assert_eq!(50 - reader.scroll_offset, 4);
+        assert_eq!(reader.scroll_offset + reader.visible_height - 1 - 50, 5);
+
+        // Near the end the view stops at the last page
+        reader.scroll_to_line(198);
+        assert_eq!(reader.scroll_offset, reader.get_max_scroll_offset());
+    }
+
+    #[test]
+    fn test_smooth_scroll_eases_to_the_target_and_stops() {
+        let mut reader = scrolling_reader(0, true);
+        reader.scroll_half_screen_down(40);
+        assert_eq!(
+            reader.scroll_offset, 0,
+            "the jump is animated, not immediate"
+        );
+
+        let mut offsets = Vec::new();
+        while reader.update_smooth_scroll() {
+            offsets.push(reader.scroll_offset);
+            assert!(offsets.len() < 100, "the animation never finished");
+        }
+        assert_eq!(reader.scroll_offset, 20);
+        assert_eq!(offsets.first(), Some(&6));
+        assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(!reader.update_smooth_scroll());
+
+        // A second jump continues from where the first was heading
+        reader.scroll_half_screen_down(40);
+        reader.scroll_half_screen_down(40);
+        while reader.update_smooth_scroll() {}
+        assert_eq!(reader.scroll_offset, 60);
+
+        // Without smooth scrolling jumps land at once
+        let mut reader = scrolling_reader(0, false);
+        reader.scroll_half_screen_down(40);
+        assert_eq!(reader.scroll_offset, 20);
+        assert!(!reader.update_smooth_scroll());
     }
 }
@@ -0,0 +1,181 @@
+use super::types::*;
+use ratatui::text::Span;
+
+/// Default number of sentences between the gaps put into long paragraphs
+pub const DEFAULT_CHUNK_SENTENCES: usize = 5;
+
+/// Paragraphs shorter than this many words are left in one piece
+const MIN_CHUNKED_WORDS: usize = 200;
+
+/// Words that end in a period without ending the sentence
+const ABBREVIATIONS: &[&str] = &[
+    "Mr", "Mrs", "Ms", "Dr", "St", "Mt", "Jr", "Sr", "Prof", "Rev", "Capt", "Col", "Gen", "Lt",
+    "No", "vs", "etc", "viz", "cf",
+];
+
+/// Byte offsets in `text` at which a sentence other than the first starts
+fn sentence_starts(text: &str) -> Vec<usize> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut starts = Vec::new();
+    for (i, &(byte_idx, c)) in chars.iter().enumerate() {
+        if !matches!(c, '.' | '!' | '?' | '…') {
+            continue;
+        }
+        if c == '.' {
+            let word: String = text[..byte_idx]
+                .chars()
+                .rev()
+                .take_while(|c| c.is_alphabetic())
+                .collect();
+            let word: String = word.chars().rev().collect();
+            // Initials and abbreviations
+            if word.chars().count() == 1 || ABBREVIATIONS.contains(&word.as_str()) {
+                continue;
+            }
+        }
+
+        let mut next = i + 1;
+        while chars
+            .get(next)
+            .is_some_and(|(_, c)| "\"'”’)]»".contains(*c))
+        {
+            next += 1;
+        }
+        if !chars.get(next).is_some_and(|(_, c)| c.is_whitespace()) {
+            continue;
+        }
+        while chars.get(next).is_some_and(|(_, c)| c.is_whitespace()) {
+            next += 1;
+        }
+        if let Some(&(start, first)) = chars.get(next)
+            && (first.is_uppercase() || "\"'“‘«(".contains(first))
+        {
+            starts.push(start);
+        }
+    }
+    starts
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    pub fn is_chunking_paragraphs(&self) -> bool {
+        self.chunk_sentences.is_some()
+    }
+
+    /// Leave a blank line after every `sentences` sentences of long paragraphs, or stop
+    /// doing so with `None`
+    pub fn set_paragraph_chunks(&mut self, sentences: Option<usize>) {
+        self.chunk_sentences = sentences.map(|sentences| sentences.max(1));
+        self.cache_generation += 1;
+    }
+
+    /// The spans of a paragraph in the pieces it is shown in: several sentences each when
+    /// the paragraph is long, else all of it. Only the layout changes; the document and
+    /// the text searched stay as they are.
+    pub(super) fn paragraph_chunks(&self, spans: Vec<RichSpan>) -> Vec<Vec<RichSpan>> {
+        let Some(sentences) = self.chunk_sentences else {
+            return vec![spans];
+        };
+        let text: String = spans.iter().map(|span| span.content()).collect();
+        if text.split_whitespace().count() < MIN_CHUNKED_WORDS {
+            return vec![spans];
+        }
+        let cuts: Vec<usize> = sentence_starts(&text)
+            .into_iter()
+            .skip(sentences - 1)
+            .step_by(sentences)
+            .collect();
+        if cuts.is_empty() {
+            return vec![spans];
+        }
+
+        let mut chunks = vec![Vec::new()];
+        let mut cuts = cuts.into_iter().peekable();
+        let mut offset = 0;
+        for rich_span in spans {
+            let end = offset + rich_span.content().len();
+            match rich_span {
+                RichSpan::Text(span) => {
+                    let mut piece_start = offset;
+                    while let Some(cut) = cuts.next_if(|cut| *cut < end) {
+                        let piece = &span.content[piece_start - offset..cut - offset];
+                        if !piece.is_empty() {
+                            chunks
+                                .last_mut()
+                                .unwrap()
+                                .push(RichSpan::Text(Span::styled(piece.to_string(), span.style)));
+                        }
+                        chunks.push(Vec::new());
+                        piece_start = cut;
+                    }
+                    let rest = &span.content[piece_start - offset..];
+                    if !rest.is_empty() {
+                        chunks
+                            .last_mut()
+                            .unwrap()
+                            .push(RichSpan::Text(Span::styled(rest.to_string(), span.style)));
+                    }
+                }
+                link @ RichSpan::Link { .. } => {
+                    // Links stay whole; a sentence starting inside one starts no chunk
+                    while cuts.next_if(|cut| *cut < end).is_some() {}
+                    chunks.last_mut().unwrap().push(link);
+                }
+            }
+            offset = end;
+        }
+
+        // The space between two sentences would otherwise end up at the end of a chunk
+        for chunk in &mut chunks {
+            if let Some(RichSpan::Text(span)) = chunk.last_mut() {
+                let trimmed = span.content.trim_end().to_string();
+                span.content = trimmed.into();
+            }
+        }
+        chunks.retain(|chunk| chunk.iter().any(|span| !span.content().trim().is_empty()));
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+
+    #[test]
+    fn test_sentence_starts_skip_abbreviations_and_initials() {
+        let text = "Mr. Holmes met J. Watson. \"Come!” he said. Then… nothing? no.";
+        let starts: Vec<&str> = sentence_starts(text)
+            .into_iter()
+            .map(|start| &text[start..start + 4])
+            .collect();
+        assert_eq!(starts, vec!["\"Com", "Then"]);
+    }
+
+    #[test]
+    fn test_long_paragraphs_get_gaps_without_changing_the_document() {
+        let sentence = "The rain kept falling on the old grey roofs of the town all night. ";
+        let html = format!("<p>{}</p><p>Short one. Two.</p>", sentence.repeat(30));
+        let doc = HtmlToMarkdownConverter::new().convert(&html);
+        let plain_text = doc.plain_text();
+
+        let mut reader = MarkdownTextReader::new();
+        let whole = reader.render_document_to_lines(&doc, 70, &OCEANIC_NEXT, true);
+        reader.set_paragraph_chunks(Some(10));
+        let chunked = reader.render_document_to_lines(&doc, 70, &OCEANIC_NEXT, true);
+
+        // 30 sentences, 14 words each: three chunks, so two more blank lines
+        assert_eq!(chunked.lines.len(), whole.lines.len() + 2);
+        let blank_rows: Vec<usize> = chunked
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.raw_text.is_empty())
+            .map(|(row, _)| row)
+            .collect();
+        assert_eq!(blank_rows, vec![10, 21, 32, 34]);
+        assert!(chunked.lines[11].raw_text.starts_with("The rain"));
+        assert_eq!(doc.plain_text(), plain_text);
+    }
+}
@@ -0,0 +1,201 @@
+use super::types::LineType;
+use crate::markdown::{Block as MarkdownBlock, LinkType};
+use crate::types::LinkInfo;
+use std::time::Duration;
+
+/// Things worth jumping between within a chapter with `]f`/`[f` and friends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointOfInterest {
+    /// A link to a footnote or endnote
+    Footnote,
+    /// A paragraph we left a note or highlight on
+    Annotation,
+    Image,
+    Table,
+}
+
+impl PointOfInterest {
+    pub fn from_key(key: char) -> Option<Self> {
+        match key {
+            'f' => Some(PointOfInterest::Footnote),
+            'a' => Some(PointOfInterest::Annotation),
+            'i' => Some(PointOfInterest::Image),
+            't' => Some(PointOfInterest::Table),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PointOfInterest::Footnote => "footnote",
+            PointOfInterest::Annotation => "annotation",
+            PointOfInterest::Image => "image",
+            PointOfInterest::Table => "table",
+        }
+    }
+}
+
+/// Where a jump to a point of interest landed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointJump {
+    /// 1-based position among the chapter's points of this kind
+    pub index: usize,
+    pub total: usize,
+    /// The search ran off the end of the chapter and started over from the other end
+    pub wrapped: bool,
+}
+
+/// Note markers are short: a number, a symbol like `*` or `†`, or a letter, maybe in
+/// brackets
+fn is_note_reference(link: &LinkInfo) -> bool {
+    if link.link_type == LinkType::External {
+        return false;
+    }
+    let marker = link
+        .text
+        .trim()
+        .trim_start_matches(['[', '('])
+        .trim_end_matches([']', ')']);
+    let mut chars = marker.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() => chars.next().is_none(),
+        Some(_) => {
+            marker.chars().count() <= 4
+                && marker
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || "*†‡§¶".contains(c))
+        }
+        None => false,
+    }
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// First rendered line of every point of `kind` in the chapter, in order
+    fn point_lines(&self, kind: PointOfInterest) -> Vec<usize> {
+        let lines = &self.rendered_content.lines;
+        let starts_node = |line_idx: usize| {
+            line_idx == 0 || lines[line_idx - 1].node_index != lines[line_idx].node_index
+        };
+        (0..lines.len())
+            .filter(|&line_idx| {
+                let line = &lines[line_idx];
+                match kind {
+                    PointOfInterest::Footnote => line.link_nodes.iter().any(is_note_reference),
+                    PointOfInterest::Annotation => {
+                        starts_node(line_idx)
+                            && line.node_index.is_some_and(|node_idx| {
+                                self.current_chapter_comments
+                                    .get(&node_idx)
+                                    .is_some_and(|comments| !comments.is_empty())
+                            })
+                    }
+                    PointOfInterest::Image => {
+                        matches!(line.line_type, LineType::ImagePlaceholder { .. })
+                            && (line_idx == 0 || lines[line_idx - 1].line_type != line.line_type)
+                    }
+                    PointOfInterest::Table => {
+                        starts_node(line_idx)
+                            && line.node_index.is_some_and(|node_idx| {
+                                self.markdown_document.as_ref().is_some_and(|doc| {
+                                    matches!(
+                                        doc.blocks.get(node_idx).map(|node| &node.block),
+                                        Some(MarkdownBlock::Table { .. })
+                                    )
+                                })
+                            })
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Scroll to the next (or previous) point of `kind` after the last one jumped to, or
+    /// after the top of the screen, wrapping around the chapter. `None` if it has none.
+    pub fn jump_to_point_of_interest(
+        &mut self,
+        kind: PointOfInterest,
+        forward: bool,
+    ) -> Option<PointJump> {
+        let points = self.point_lines(kind);
+        if points.is_empty() {
+            return None;
+        }
+        let on_screen = |line: &usize| {
+            *line >= self.scroll_offset && *line < self.scroll_offset + self.visible_height
+        };
+        // Carry on from the point jumped to last while it's still in view
+        let from = self.last_point_of_interest.filter(on_screen);
+        let found = if forward {
+            match from {
+                Some(from) => points.iter().position(|line| *line > from),
+                None => points.iter().position(|line| *line >= self.scroll_offset),
+            }
+        } else {
+            let before = from.unwrap_or(self.scroll_offset);
+            points.iter().rposition(|line| *line < before)
+        };
+        let wrapped = found.is_none();
+        let index = found.unwrap_or(if forward { 0 } else { points.len() - 1 });
+        let line = points[index];
+
+        self.last_point_of_interest = Some(line);
+        self.scroll_to_line(line);
+        self.highlight_line_temporarily(line, Duration::from_secs(2));
+        Some(PointJump {
+            index: index + 1,
+            total: points.len(),
+            wrapped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+
+    #[test]
+    fn test_footnote_and_table_jumps_cycle_through_the_chapter() {
+        let html = r##"<p>A claim.<a href="#n1">1</a> Another.<a href="#n2">[2]</a></p>
+            <p>See <a href="#intro">the introduction</a>.</p>
+            <table><tr><td>a</td><td>b</td></tr></table>
+            <p>Last words.<a href="notes.html#n3">*</a></p>"##;
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = MarkdownTextReader::new();
+        reader.rendered_content = reader.render_document_to_lines(&doc, 80, &OCEANIC_NEXT, true);
+        reader.markdown_document = Some(std::sync::Arc::new(doc));
+        reader.visible_height = 40;
+
+        let footnotes = reader.point_lines(PointOfInterest::Footnote);
+        assert_eq!(footnotes.len(), 2);
+        assert_eq!(reader.point_lines(PointOfInterest::Table).len(), 1);
+        assert!(reader.point_lines(PointOfInterest::Image).is_empty());
+
+        let jump = |reader: &mut MarkdownTextReader, forward| {
+            reader.jump_to_point_of_interest(PointOfInterest::Footnote, forward)
+        };
+        assert_eq!(
+            jump(&mut reader, true),
+            Some(PointJump {
+                index: 1,
+                total: 2,
+                wrapped: false
+            })
+        );
+        assert_eq!(jump(&mut reader, true).map(|jump| jump.index), Some(2));
+        assert_eq!(
+            jump(&mut reader, true).map(|jump| (jump.index, jump.wrapped)),
+            Some((1, true))
+        );
+        assert_eq!(
+            jump(&mut reader, false).map(|jump| (jump.index, jump.wrapped)),
+            Some((2, true))
+        );
+        assert_eq!(
+            reader.jump_to_point_of_interest(PointOfInterest::Image, true),
+            None
+        );
+    }
+}
@@ -1,9 +1,15 @@
+use super::admonitions::Admonition;
 use super::types::*;
+use super::wrapping::{
+    CODE_CONTINUATION, INLINE_CODE_BG, wrap_rich_spans, wrap_rich_spans_widened,
+};
+use crate::chapter_diff::BlockChange;
 use crate::markdown::{
     Block as MarkdownBlock, Document, HeadingLevel, Inline, Node, Style, Text as MarkdownText,
-    TextOrInline,
+    TextAlign, TextOrInline, text_to_string,
 };
-use crate::theme::Base16Palette;
+use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+use crate::theme::{Base16Palette, OCEANIC_NEXT};
 use crate::types::LinkInfo;
 use ratatui::{
     layout::Constraint,
@@ -37,7 +43,7 @@ impl RenderingContext {
 }
 
 impl crate::markdown_text_reader::MarkdownTextReader {
-    fn last_line_has_content(lines: &[RenderedLine]) -> bool {
+    pub(super) fn last_line_has_content(lines: &[RenderedLine]) -> bool {
         lines
             .last()
             .map(|line| !line.raw_text.trim().is_empty())
@@ -55,11 +61,34 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         let mut total_height = 0;
 
         self.raw_text_lines.clear();
+        self.image_alt_text.clear();
         self.anchor_positions.clear();
+        self.synthetic_anchors.clear();
+        self.reanchor_chapter_comments(doc);
+        self.collect_inlined_note_ids(doc);
 
         // Iterate through all blocks in the document
         for (node_idx, node) in doc.blocks.iter().enumerate() {
+            if let Some(contents) = doc
+                .contents
+                .as_ref()
+                .filter(|contents| contents.before_block == node_idx)
+            {
+                // Not a block of the chapter, so it gets no index to hang comments on
+                self.render_node(
+                    &contents.list,
+                    &mut lines,
+                    &mut total_height,
+                    width,
+                    palette,
+                    is_focused,
+                    0,
+                    None,
+                    RenderContext::TopLevel,
+                );
+            }
             self.extract_and_track_anchors_from_node(node, total_height);
+            self.track_synthetic_paragraph_anchor(node, node_idx, total_height);
 
             self.render_node(
                 node,
@@ -74,6 +103,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             );
         }
 
+        self.apply_bionic_emphasis(&mut lines);
+        self.apply_watchlist_highlighting(&mut lines, palette);
+        self.apply_double_width(&mut lines);
+
         self.links.clear();
         for rendered_line in &lines {
             self.links.extend(rendered_line.link_nodes.clone());
@@ -86,6 +119,29 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    /// Convert an HTML snippet and lay it out `width` columns wide, as the reader would,
+    /// returning the text of each row without styling. Used by `bookokcat render-html`.
+    pub fn render_html_to_plain_text(html: &str, width: usize) -> String {
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = Self::new();
+        let rendered = reader.render_document_to_lines(&doc, width, &OCEANIC_NEXT, true);
+
+        let mut output = String::new();
+        for line in &rendered.lines {
+            let text: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            output.push_str(text.trim_end());
+            output.push('\n');
+        }
+        // Trailing blank rows are just the spacing after the last block
+        output.truncate(output.trim_end().len());
+        output.push('\n');
+        output
+    }
+
     pub fn extract_and_track_anchors_from_node(&mut self, node: &Node, current_line: usize) {
         if let Some(html_id) = &node.id {
             self.anchor_positions.insert(html_id.clone(), current_line);
@@ -94,8 +150,8 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         match &node.block {
             MarkdownBlock::Heading { content, .. } => {
                 if node.id.is_none() {
-                    let heading_text = Self::text_to_string(content);
-                    let anchor_id = self.generate_heading_anchor(&heading_text);
+                    let heading_text = text_to_string(content);
+                    let anchor_id = Self::generate_heading_anchor(&heading_text);
                     self.anchor_positions.insert(anchor_id, current_line);
                 }
                 self.extract_inline_anchors_from_text(content, current_line);
@@ -107,8 +163,39 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    /// Give paragraphs without an id a stable anchor, so positions in books without
+    /// markup ids can still be linked to
+    fn track_synthetic_paragraph_anchor(&mut self, node: &Node, node_idx: usize, line: usize) {
+        if node.id.is_some() {
+            return;
+        }
+        if let MarkdownBlock::Paragraph { content } = &node.block {
+            let anchor = Self::synthetic_paragraph_anchor(
+                self.current_chapter_file.as_deref().unwrap_or_default(),
+                node_idx,
+                &text_to_string(content),
+            );
+            if !self.anchor_positions.contains_key(&anchor) {
+                self.anchor_positions.insert(anchor.clone(), line);
+                self.synthetic_anchors.insert(anchor);
+            }
+        }
+    }
+
+    /// `p-` followed by a hash of the chapter, the block's position and the start of its text
+    pub fn synthetic_paragraph_anchor(
+        chapter_href: &str,
+        block_index: usize,
+        text: &str,
+    ) -> String {
+        let prefix: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let prefix: String = prefix.chars().take(32).collect();
+        let digest = md5::compute(format!("{chapter_href}\n{block_index}\n{prefix}"));
+        format!("p-{}", &format!("{digest:x}")[..10])
+    }
+
     /// Generate anchor ID from heading text (simplified version)
-    pub fn generate_heading_anchor(&self, heading_text: &str) -> String {
+    pub fn generate_heading_anchor(heading_text: &str) -> String {
         heading_text
             .to_lowercase()
             .chars()
@@ -142,6 +229,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         context: RenderContext,
     ) {
         use MarkdownBlock::*;
+        let indent = indent + node.layout.indent;
 
         // Store the current node's anchor to add to the first line rendered for this node
         let mut current_node_anchor = node.id.clone();
@@ -154,8 +242,8 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         match &node.block {
             Heading { level, content } => {
                 if current_node_anchor.is_none() {
-                    let heading_text = Self::text_to_string(content);
-                    generated_heading_anchor = Some(self.generate_heading_anchor(&heading_text));
+                    let heading_text = text_to_string(content);
+                    generated_heading_anchor = Some(Self::generate_heading_anchor(&heading_text));
                 }
 
                 self.render_heading(
@@ -178,9 +266,21 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     palette,
                     is_focused,
                     indent,
+                    node.layout.align,
                     node_index,
                     context,
                 );
+                if context == RenderContext::TopLevel && self.is_inlining_notes() {
+                    self.render_inline_notes(
+                        content,
+                        lines,
+                        total_height,
+                        width,
+                        palette,
+                        is_focused,
+                        indent,
+                    );
+                }
             }
 
             CodeBlock { language, content } => {
@@ -207,6 +307,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     palette,
                     is_focused,
                     indent,
+                    node.layout.number_width,
                     node_index,
                 );
             }
@@ -244,6 +345,26 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 self.render_thematic_break(lines, total_height, width, palette, is_focused);
             }
 
+            Details {
+                summary,
+                content,
+                open,
+            } => {
+                // Only top-level blocks can be toggled; nested ones keep their initial state
+                let expanded = node_index.map_or(*open, |idx| self.is_details_expanded(idx, *open));
+                self.render_details(
+                    summary,
+                    content,
+                    expanded,
+                    lines,
+                    total_height,
+                    width,
+                    palette,
+                    is_focused,
+                    indent,
+                );
+            }
+
             DefinitionList { items: def_items } => {
                 self.render_definition_list(
                     def_items,
@@ -255,6 +376,18 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 );
             }
 
+            // Already shown below the paragraph that refers to it
+            EpubBlock { .. } if self.is_inlined_note(node.id.as_ref()) => {}
+
+            EpubBlock {
+                epub_type, content, ..
+            } if epub_type
+                .split_whitespace()
+                .any(|t| matches!(t, "epigraph" | "dedication")) =>
+            {
+                self.render_epigraph(content, lines, total_height, width, palette, is_focused);
+            }
+
             EpubBlock {
                 epub_type,
                 element_name,
@@ -294,38 +427,6 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
-    // Helper method to convert Text AST to plain string
-    pub fn text_to_string(text: &MarkdownText) -> String {
-        let mut result = String::new();
-        for item in text.iter() {
-            match item {
-                TextOrInline::Text(text_node) => {
-                    result.push_str(&text_node.content);
-                }
-                TextOrInline::Inline(inline) => match inline {
-                    Inline::Link {
-                        text: link_text, ..
-                    } => {
-                        result.push_str(&Self::text_to_string(link_text));
-                    }
-                    Inline::Image { alt_text, .. } => {
-                        result.push_str(alt_text);
-                    }
-                    Inline::Anchor { .. } => {
-                        // Anchors don't contribute to text content
-                    }
-                    Inline::LineBreak => {
-                        result.push('\n');
-                    }
-                    Inline::SoftBreak => {
-                        result.push(' ');
-                    }
-                },
-            }
-        }
-        result
-    }
-
     #[allow(clippy::too_many_arguments)]
     pub fn render_heading(
         &mut self,
@@ -337,7 +438,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         palette: &Base16Palette,
         is_focused: bool,
     ) {
-        let heading_text = Self::text_to_string(content);
+        let heading_text = text_to_string(content);
 
         let display_text = if level == HeadingLevel::H1 {
             heading_text.to_uppercase()
@@ -433,6 +534,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         palette: &Base16Palette,
         is_focused: bool,
         indent: usize,
+        align: Option<TextAlign>,
         node_index: Option<usize>,
         context: RenderContext,
     ) {
@@ -441,7 +543,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 TextOrInline::Text(t) => !t.content.trim().is_empty(),
                 TextOrInline::Inline(inline) => match inline {
                     Inline::Image { .. } => true,
-                    Inline::Link { text, .. } => !Self::text_to_string(text).trim().is_empty(),
+                    Inline::Link { text, .. } => !text_to_string(text).trim().is_empty(),
                     Inline::Anchor { .. } | Inline::LineBreak | Inline::SoftBreak => false,
                 },
             });
@@ -451,12 +553,13 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             }
         }
 
+        let first_line = lines.len();
         let mut current_rich_spans = Vec::new();
         let mut has_content = false;
 
         for item in content.iter() {
             match item {
-                TextOrInline::Inline(Inline::Image { url, .. }) => {
+                TextOrInline::Inline(Inline::Image { url, alt_text, .. }) => {
                     // If we have accumulated text before the image, render it first
                     if !current_rich_spans.is_empty() {
                         self.render_text_spans(
@@ -472,7 +575,14 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     }
 
                     // Render the image as a separate block
-                    self.render_image_placeholder(url, lines, total_height, width, palette);
+                    self.render_image_placeholder(
+                        url,
+                        alt_text,
+                        lines,
+                        total_height,
+                        width,
+                        palette,
+                    );
                     has_content = true;
                 }
                 _ => {
@@ -486,15 +596,23 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         // Render any remaining text spans
         if !current_rich_spans.is_empty() {
             let add_empty_line = context == RenderContext::TopLevel;
-            self.render_text_spans(
-                &current_rich_spans,
-                None,
-                lines,
-                total_height,
-                width,
-                indent,
-                add_empty_line,
-            );
+            let chunks = if context == RenderContext::TopLevel {
+                self.paragraph_chunks(current_rich_spans)
+            } else {
+                vec![current_rich_spans]
+            };
+            let last_chunk = chunks.len() - 1;
+            for (chunk_idx, chunk) in chunks.iter().enumerate() {
+                self.render_text_spans(
+                    chunk,
+                    None,
+                    lines,
+                    total_height,
+                    width,
+                    indent,
+                    chunk_idx < last_chunk || add_empty_line,
+                );
+            }
         } else if !has_content {
             // Empty paragraph - just add an empty line
             lines.push(RenderedLine {
@@ -509,6 +627,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             *total_height += 1;
         }
 
+        if let Some(align) = align {
+            Self::align_lines(&mut lines[first_line..], width, align);
+        }
+
         if let Some(node_idx) = node_index {
             let comments_to_render = self.current_chapter_comments.get(&node_idx).cloned();
             if let Some(paragraph_comments) = comments_to_render {
@@ -524,6 +646,32 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     );
                 }
             }
+            self.render_shared_comments(node_idx, lines, total_height, width, palette, indent);
+        }
+    }
+
+    /// Shift wrapped text lines right so they sit centered or flush right in `width`
+    fn align_lines(lines: &mut [RenderedLine], width: usize, align: TextAlign) {
+        for line in lines
+            .iter_mut()
+            .filter(|line| line.line_type == LineType::Text && !line.raw_text.trim().is_empty())
+        {
+            let used = textwrap::core::display_width(line.raw_text.trim_end());
+            let free = width.saturating_sub(used);
+            let padding = match align {
+                TextAlign::Left => 0,
+                TextAlign::Center => free / 2,
+                TextAlign::Right => free,
+            };
+            if padding == 0 {
+                continue;
+            }
+            line.spans.insert(0, Span::raw(" ".repeat(padding)));
+            line.raw_text.insert_str(0, &" ".repeat(padding));
+            for link in &mut line.link_nodes {
+                link.start_col += padding;
+                link.end_col += padding;
+            }
         }
     }
 
@@ -551,7 +699,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                         target_anchor,
                         ..
                     } => {
-                        let link_text_str = Self::text_to_string(link_text);
+                        let link_text_str = text_to_string(link_text);
 
                         // Create link info (line and columns will be set during line creation)
                         let link_info = LinkInfo {
@@ -566,7 +714,15 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                         };
 
                         // Determine styling based on link type
-                        let (link_color, link_modifier) = if is_focused {
+                        let (link_color, link_modifier) = if self.broken_links.contains(url) {
+                            // Red, so a jump that will fail stands out before it's taken
+                            let color = if is_focused {
+                                palette.base_08
+                            } else {
+                                palette.base_03
+                            };
+                            (color, Modifier::UNDERLINED | Modifier::CROSSED_OUT)
+                        } else if is_focused {
                             match link_type {
                                 crate::markdown::LinkType::External => {
                                     (palette.base_0c, Modifier::UNDERLINED) // Cyan + underlined
@@ -652,7 +808,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             Some(Style::Emphasis) => base_style.add_modifier(Modifier::ITALIC),
             Some(Style::Code) => {
                 // Inline code with background
-                RatatuiStyle::default().fg(Color::Black).bg(Color::Gray)
+                RatatuiStyle::default().fg(Color::Black).bg(INLINE_CODE_BG)
             }
             Some(Style::Strikethrough) => base_style.add_modifier(Modifier::CROSSED_OUT),
             None => base_style,
@@ -739,6 +895,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         palette: &Base16Palette,
         is_focused: bool,
         indent: usize,
+        number_width: usize,
         node_index: Option<usize>,
     ) {
         use crate::markdown::ListKind;
@@ -749,7 +906,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 ListKind::Unordered => "• ".to_string(),
                 ListKind::Ordered { start } => {
                     let num = start + idx as u32;
-                    format!("{num}. ")
+                    format!("{num:>number_width$}. ")
                 }
             };
 
@@ -833,26 +990,30 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
         // Render comments for the list if it has a node_index
         if let Some(node_idx) = node_index {
-            let comments_to_render = self.current_chapter_comments.get(&node_idx).cloned();
-            if let Some(paragraph_comments) = comments_to_render {
-                if !paragraph_comments.is_empty() {
-                    lines.push(RenderedLine::empty());
-                    self.raw_text_lines.push(String::new());
-                    *total_height += 1;
+            let paragraph_comments = self
+                .current_chapter_comments
+                .get(&node_idx)
+                .cloned()
+                .unwrap_or_default();
+            let has_shared = self.current_chapter_shared_comments.contains_key(&node_idx);
+            if !paragraph_comments.is_empty() || has_shared {
+                lines.push(RenderedLine::empty());
+                self.raw_text_lines.push(String::new());
+                *total_height += 1;
 
-                    for comment in paragraph_comments {
-                        self.render_comment_as_quote(
-                            &comment,
-                            lines,
-                            total_height,
-                            width,
-                            palette,
-                            is_focused,
-                            indent,
-                        );
-                    }
-                    return; // render_comment_as_quote already adds empty line after
+                for comment in paragraph_comments {
+                    self.render_comment_as_quote(
+                        &comment,
+                        lines,
+                        total_height,
+                        width,
+                        palette,
+                        is_focused,
+                        indent,
+                    );
                 }
+                self.render_shared_comments(node_idx, lines, total_height, width, palette, indent);
+                return; // render_comment_as_quote already adds empty line after
             }
         }
 
@@ -883,7 +1044,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             table_headers = header_row
                 .cells
                 .iter()
-                .map(|cell| Self::text_to_string(&cell.content))
+                .map(|cell| text_to_string(&cell.content))
                 .collect();
         }
 
@@ -892,7 +1053,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             let row_data: Vec<String> = row
                 .cells
                 .iter()
-                .map(|cell| Self::text_to_string(&cell.content))
+                .map(|cell| text_to_string(&cell.content))
                 .collect();
             table_rows.push(row_data);
         }
@@ -1115,6 +1276,65 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             .collect()
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_details(
+        &mut self,
+        summary: &MarkdownText,
+        content: &[Node],
+        expanded: bool,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+        indent: usize,
+    ) {
+        let summary_color = if is_focused {
+            palette.base_0d
+        } else {
+            palette.base_03
+        };
+        let summary_spans: Vec<RichSpan> = summary
+            .iter()
+            .flat_map(|item| self.render_text_or_inline(item, palette, is_focused))
+            .map(|rich_span| match rich_span {
+                RichSpan::Text(span) => RichSpan::Text(Span::styled(
+                    span.content.clone(),
+                    span.style.fg(summary_color).add_modifier(Modifier::BOLD),
+                )),
+                link => link,
+            })
+            .collect();
+
+        self.render_text_spans(
+            &summary_spans,
+            Some(if expanded { "▾ " } else { "▸ " }),
+            lines,
+            total_height,
+            width,
+            indent,
+            true,
+        );
+
+        if !expanded {
+            return;
+        }
+
+        for node in content {
+            self.render_node(
+                node,
+                lines,
+                total_height,
+                width,
+                palette,
+                is_focused,
+                indent + 1,
+                None,
+                RenderContext::TopLevel,
+            );
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn render_quote(
         &mut self,
@@ -1319,11 +1539,96 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 
+    /// Epigraphs and dedications: italic lines centered in a narrower column, set apart
+    /// by extra blank lines rather than the separators of other epub blocks
+    pub fn render_epigraph(
+        &mut self,
+        content: &[Node],
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        let column_width = (width * 3 / 4).max(20).min(width);
+        let blank_lines = |lines: &mut Vec<RenderedLine>, reader: &mut Self, count: usize| {
+            for _ in 0..count {
+                lines.push(RenderedLine::empty());
+                reader.raw_text_lines.push(String::new());
+            }
+        };
+
+        if Self::last_line_has_content(lines) {
+            blank_lines(lines, self, 1);
+            *total_height += 1;
+        }
+        blank_lines(lines, self, 1);
+        *total_height += 1;
+
+        // An epigraph is often a blockquote followed by its attribution
+        let paragraphs = content.iter().flat_map(|node| match &node.block {
+            MarkdownBlock::Quote { content } => content.iter().collect::<Vec<_>>(),
+            _ => vec![node],
+        });
+        for (idx, node) in paragraphs.enumerate() {
+            let MarkdownBlock::Paragraph { content } = &node.block else {
+                self.render_node(
+                    node,
+                    lines,
+                    total_height,
+                    width,
+                    palette,
+                    is_focused,
+                    0,
+                    None,
+                    RenderContext::InsideContainer,
+                );
+                continue;
+            };
+            if idx > 0 {
+                blank_lines(lines, self, 1);
+                *total_height += 1;
+            }
+
+            let rich_spans: Vec<RichSpan> = content
+                .iter()
+                .flat_map(|item| self.render_text_or_inline(item, palette, is_focused))
+                .map(|rich_span| match rich_span {
+                    RichSpan::Text(span) => RichSpan::Text(Span::styled(
+                        span.content.clone(),
+                        span.style.add_modifier(Modifier::ITALIC),
+                    )),
+                    RichSpan::Link { span, info } => RichSpan::Link {
+                        span: Span::styled(
+                            span.content.clone(),
+                            span.style.add_modifier(Modifier::ITALIC),
+                        ),
+                        info,
+                    },
+                })
+                .collect();
+            let first_line = lines.len();
+            self.render_text_spans(
+                &rich_spans,
+                None,
+                lines,
+                total_height,
+                column_width,
+                0,
+                false,
+            );
+            Self::align_lines(&mut lines[first_line..], width, TextAlign::Center);
+        }
+
+        blank_lines(lines, self, 2);
+        *total_height += 2;
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn render_epub_block(
         &mut self,
-        _epub_type: &str,
-        _element_name: &str,
+        epub_type: &str,
+        element_name: &str,
         content: &[crate::markdown::Node],
         lines: &mut Vec<RenderedLine>,
         total_height: &mut usize,
@@ -1331,6 +1636,31 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         palette: &Base16Palette,
         is_focused: bool,
     ) {
+        if let Some(change) = BlockChange::from_epub_type(epub_type) {
+            self.render_changed_blocks(
+                change,
+                content,
+                lines,
+                total_height,
+                width,
+                palette,
+                is_focused,
+            );
+            return;
+        }
+        if let Some(kind) = Admonition::from_epub_type(epub_type, element_name) {
+            self.render_admonition(
+                kind,
+                content,
+                lines,
+                total_height,
+                width,
+                palette,
+                is_focused,
+            );
+            return;
+        }
+
         // Add line separator before the block
         let separator_line = ".".repeat(width);
         lines.push(RenderedLine {
@@ -1474,8 +1804,14 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
         let available_width = available_width.max(1);
 
-        // Wrap the text
-        let wrapped = textwrap::wrap(&plain_text, available_width);
+        // Wrap the text, keeping inline code together where possible
+        let wrapped = if self.is_double_width() {
+            // `width` counts double-width glyphs; wrap by the columns the text takes once
+            // widened, as only ASCII is
+            wrap_rich_spans_widened(&wrappable_rich_spans, &plain_text, available_width * 2)
+        } else {
+            wrap_rich_spans(&wrappable_rich_spans, &plain_text, available_width)
+        };
 
         // Create lines from wrapped text
         for (line_idx, wrapped_line) in wrapped.iter().enumerate() {
@@ -1488,7 +1824,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 wrappable_rich_spans.clone()
             } else {
                 // Multi-line content: map wrapped line back to rich spans
-                self.map_wrapped_line_to_rich_spans(wrapped_line, &wrappable_rich_spans)
+                self.map_wrapped_line_to_rich_spans(&wrapped_line.text, &wrappable_rich_spans)
             };
 
             // Extract spans and links, calculating positions
@@ -1512,6 +1848,12 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     }
                 }
             }
+            if wrapped_line.continued {
+                line_spans.push(Span::styled(
+                    CODE_CONTINUATION,
+                    RatatuiStyle::default().add_modifier(Modifier::DIM),
+                ));
+            }
 
             // Apply indentation by prepending indent span
             if indent > 0 {
@@ -1548,7 +1890,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     final_raw_text.push_str(&prefix_padding);
                 }
             }
-            final_raw_text.push_str(wrapped_line.as_ref());
+            final_raw_text.push_str(&wrapped_line.text);
+            if wrapped_line.continued {
+                final_raw_text.push_str(CODE_CONTINUATION);
+            }
 
             lines.push(RenderedLine {
                 spans: line_spans,
@@ -1561,13 +1906,24 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
             self.raw_text_lines.push(final_raw_text);
             *total_height += 1;
+
+            // Zoomed text is spaced out between lines too
+            if line_idx + 1 < wrapped.len() {
+                for _ in 0..self.zoom_line_spacing() {
+                    lines.push(RenderedLine::empty());
+                    self.raw_text_lines.push(String::new());
+                    *total_height += 1;
+                }
+            }
         }
 
-        // Add empty line after if requested
+        // Add empty line after if requested, and more when zoomed in
         if add_empty_line_after {
-            lines.push(RenderedLine::empty());
-            self.raw_text_lines.push(String::new());
-            *total_height += 1;
+            for _ in 0..=self.zoom_paragraph_spacing() {
+                lines.push(RenderedLine::empty());
+                self.raw_text_lines.push(String::new());
+                *total_height += 1;
+            }
         }
     }
 
@@ -1697,6 +2053,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     fn render_image_placeholder(
         &mut self,
         url: &str,
+        alt_text: &str,
         lines: &mut Vec<RenderedLine>,
         total_height: &mut usize,
         width: usize,
@@ -1769,6 +2126,12 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             loading_status,
         );
 
+        // The alt text isn't drawn, but searching for it should land on the image
+        if !alt_text.trim().is_empty() {
+            self.image_alt_text
+                .insert(lines.len(), alt_text.trim().to_string());
+        }
+
         // Add all the placeholder lines
         for (raw_line, styled_line) in placeholder
             .raw_lines
@@ -1796,3 +2159,119 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::css::StyleSheet;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_synthetic_paragraph_anchor_is_stable() {
+        let anchor = MarkdownTextReader::synthetic_paragraph_anchor(
+            "OEBPS/ch1.xhtml",
+            4,
+            "It was a bright cold day in April, and the clocks were striking thirteen.",
+        );
+        assert!(anchor.starts_with("p-") && anchor.len() == 12);
+
+        // Whitespace and text past the prefix don't matter; chapter and position do
+        assert_eq!(
+            anchor,
+            MarkdownTextReader::synthetic_paragraph_anchor(
+                "OEBPS/ch1.xhtml",
+                4,
+                "It was a  bright cold\nday in April, and the clocks struck one.",
+            )
+        );
+        assert_ne!(
+            anchor,
+            MarkdownTextReader::synthetic_paragraph_anchor(
+                "OEBPS/ch1.xhtml",
+                5,
+                "It was a bright cold day in April, and the clocks were striking thirteen.",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_html_to_plain_text_wraps_at_width() {
+        let html = "<h1>Title</h1><p>The quick brown fox jumps over the lazy dog.</p>\
+                    <ul><li>one</li><li>two</li></ul>";
+        let output = MarkdownTextReader::render_html_to_plain_text(html, 20);
+        assert!(output.lines().all(|line| line.chars().count() <= 20));
+        assert!(output.starts_with("TITLE\n"));
+        assert!(output.contains("\nThe quick brown fox\njumps over the lazy\ndog.\n"));
+        assert!(output.ends_with("• two\n"));
+        assert_eq!(
+            output,
+            MarkdownTextReader::render_html_to_plain_text(html, 20)
+        );
+    }
+
+    #[test]
+    fn test_book_styles_align_and_indent_paragraphs() {
+        let styles =
+            StyleSheet::parse(".dedication { text-align: center } .letter { margin-left: 2em }");
+        let html = r#"<p class="dedication">For Ada</p>
+            <div class="letter"><p>Dear Sir,</p><p style="text-align: right">Yours</p></div>"#;
+        let doc = HtmlToMarkdownConverter::new()
+            .with_styles(Some(Arc::new(styles)))
+            .convert(html);
+        let mut reader = MarkdownTextReader::new();
+        let rendered = reader.render_document_to_lines(&doc, 20, &OCEANIC_NEXT, true);
+        let rows: Vec<_> = rendered
+            .lines
+            .iter()
+            .map(|line| line.raw_text.as_str())
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        assert_eq!(
+            rows,
+            ["      For Ada", "    Dear Sir,", "               Yours"]
+        );
+    }
+
+    #[test]
+    fn test_split_ordered_list_lines_up() {
+        let html = r#"<ol start="8"><li>Eight</li><li>Nine</li></ol>
+            <p>A note on nine.</p>
+            <ol start="10"><li>Ten</li></ol>
+            <ol><li>A new list</li></ol>"#;
+        let output = MarkdownTextReader::render_html_to_plain_text(html, 40);
+        let rows: Vec<_> = output.lines().filter(|row| !row.is_empty()).collect();
+        assert_eq!(
+            rows,
+            [
+                " 8. Eight",
+                " 9. Nine",
+                "    A note on nine.",
+                "10. Ten",
+                "1. A new list"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_epigraph_is_centered_and_set_apart() {
+        let html = r#"<p>Before.</p>
+            <section epub:type="epigraph"><blockquote><p>Call me Ishmael.</p></blockquote>
+            <p>— H. M.</p></section>
+            <p>After.</p>"#;
+        let output = MarkdownTextReader::render_html_to_plain_text(html, 40);
+        let rows: Vec<_> = output.lines().collect();
+        let quote = rows.iter().position(|row| row.contains("Ishmael")).unwrap();
+        let after = rows.iter().position(|row| *row == "After.").unwrap();
+
+        assert_eq!(rows[quote], "            Call me Ishmael.");
+        assert!(rows[quote - 2].is_empty() && rows[quote - 1].is_empty());
+        assert!(
+            rows.iter()
+                .any(|row| row.trim() == "— H. M." && row.starts_with("  "))
+        );
+        assert!(rows[after - 2].is_empty() && rows[after - 1].is_empty());
+    }
+}
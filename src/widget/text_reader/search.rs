@@ -4,12 +4,17 @@ use ratatui::style::{Color, Style as RatatuiStyle};
 use ratatui::text::Span;
 
 impl crate::markdown_text_reader::MarkdownTextReader {
-    /// Get searchable content (visible lines as text)
+    /// Get searchable content (visible lines as text). An image is found by its alt text,
+    /// on the first line of its placeholder.
     pub fn get_visible_text(&self) -> Vec<String> {
         self.rendered_content
             .lines
             .iter()
-            .map(|line| line.raw_text.clone())
+            .enumerate()
+            .map(|(idx, line)| match self.image_alt_text.get(&idx) {
+                Some(alt_text) => alt_text.clone(),
+                None => line.raw_text.clone(),
+            })
             .collect()
     }
 
@@ -23,6 +28,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         if !self.search_state.active || self.search_state.matches.is_empty() {
             return line_spans;
         }
+        // Matches in an image's alt text have nothing drawn to highlight
+        if self.image_alt_text.contains_key(&line_idx) {
+            return line_spans;
+        }
 
         // Check if this line has any search matches
         let line_matches: Vec<_> = self
@@ -146,9 +155,18 @@ impl SearchablePanel for crate::markdown_text_reader::MarkdownTextReader {
         let matches = find_matches_in_text(query, &searchable);
         self.search_state.set_matches(matches);
 
-        // Jump to match if found
-        if let Some(match_index) = self.search_state.get_current_match() {
-            self.jump_to_match(match_index);
+        // Search as you type: stay on the original view while the first match is on it,
+        // otherwise bring it into view; with no match, go back to where the search began
+        let original_position = self.search_state.original_position;
+        match self.search_state.get_current_match() {
+            Some(match_index)
+                if (original_position..original_position + self.visible_height)
+                    .contains(&match_index) =>
+            {
+                self.scroll_offset = original_position;
+            }
+            Some(match_index) => self.jump_to_match(match_index),
+            None => self.scroll_offset = original_position,
         }
     }
 
@@ -241,3 +259,73 @@ impl SearchablePanel for crate::markdown_text_reader::MarkdownTextReader {
         self.get_visible_text()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::search::SearchablePanel;
+    use crate::test_utils::test_helpers::create_test_terminal;
+    use crate::theme::OCEANIC_NEXT;
+
+    #[test]
+    fn test_search_as_you_type_reverts_without_match() {
+        let mut terminal = create_test_terminal(60, 20);
+        let mut reader = MarkdownTextReader::new();
+        let content = (0..100)
+            .map(|i| format!("<p>Line {i}</p>"))
+            .collect::<String>();
+        reader.set_content_from_string(&content, None);
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, &OCEANIC_NEXT, true))
+            .unwrap();
+
+        reader.start_search();
+        reader.update_search_query("Line 1");
+        assert_eq!(reader.get_scroll_offset(), 0, "match already on screen");
+
+        reader.update_search_query("Line 90");
+        let jumped = reader.get_scroll_offset();
+        assert!(jumped > 0);
+
+        reader.update_search_query("Line 90x");
+        assert_eq!(
+            reader.get_scroll_offset(),
+            0,
+            "no match returns to the start"
+        );
+
+        reader.update_search_query("Line 90");
+        assert_eq!(reader.get_scroll_offset(), jumped);
+        reader.cancel_search();
+        assert_eq!(reader.get_scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_image_alt_text_is_searchable_and_book_search_lands_on_it() {
+        let mut terminal = create_test_terminal(60, 20);
+        let mut reader = MarkdownTextReader::new();
+        let mut content = (0..40)
+            .map(|i| format!("<p>Line {i}</p>"))
+            .collect::<String>();
+        content.push_str(r#"<p><img src="fig.png" alt="Perplexity over training steps"/></p>"#);
+        content.extend((0..20).map(|i| format!("<p>After the figure {i}</p>")));
+        reader.set_content_from_string(&content, None);
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, &OCEANIC_NEXT, true))
+            .unwrap();
+
+        let image_line = reader
+            .get_searchable_content()
+            .iter()
+            .position(|line| line == "Perplexity over training steps")
+            .expect("alt text is searchable");
+
+        // The book-wide search numbers plain-text lines: one per paragraph here
+        reader.set_content_from_string(&content, None);
+        reader.store_pending_text_line_scroll(40, "perplexity".to_string());
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, &OCEANIC_NEXT, true))
+            .unwrap();
+        assert_eq!(reader.get_scroll_offset(), image_line);
+    }
+}
@@ -96,6 +96,44 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.text_selection.has_selection()
     }
 
+    /// Rendered line the selection starts on
+    pub fn selection_start_line(&self) -> Option<usize> {
+        self.text_selection
+            .get_selection_range()
+            .map(|(start, _)| start.line)
+    }
+
+    pub fn get_selected_text(&self) -> Option<String> {
+        self.text_selection
+            .extract_selected_text(&self.raw_text_lines)
+    }
+
+    /// Text of the paragraph the selection starts in, with wrapped lines joined
+    pub fn get_selection_paragraph(&self) -> Option<String> {
+        let (start, _) = self.text_selection.get_selection_range()?;
+        let is_blank = |index: &usize| {
+            self.raw_text_lines
+                .get(*index)
+                .is_none_or(|line| line.trim().is_empty())
+        };
+
+        let first = (0..=start.line)
+            .rev()
+            .take_while(|index| !is_blank(index))
+            .last()?;
+        let last = (start.line..self.raw_text_lines.len())
+            .take_while(|index| !is_blank(index))
+            .last()?;
+
+        Some(
+            self.raw_text_lines[first..=last]
+                .iter()
+                .map(|line| line.trim())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+
     pub fn copy_selection_to_clipboard(&self) -> Result<(), String> {
         if let Some(selected_text) = self
             .text_selection
@@ -130,6 +168,30 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             .map_err(|e| format!("Failed to copy to clipboard: {e}"))
     }
 
+    pub fn copy_text_to_clipboard(&self, text: String) -> Result<(), String> {
+        use arboard::Clipboard;
+        let mut clipboard =
+            Clipboard::new().map_err(|e| format!("Failed to access clipboard: {e}"))?;
+        clipboard
+            .set_text(text)
+            .map_err(|e| format!("Failed to copy to clipboard: {e}"))
+    }
+
+    pub fn copy_focused_link_to_clipboard(&self) -> Result<String, String> {
+        let url = self
+            .focused_link()
+            .map(|link| link.url.clone())
+            .ok_or_else(|| "No link on screen".to_string())?;
+
+        use arboard::Clipboard;
+        let mut clipboard =
+            Clipboard::new().map_err(|e| format!("Failed to access clipboard: {e}"))?;
+        clipboard
+            .set_text(url.clone())
+            .map_err(|e| format!("Failed to copy to clipboard: {e}"))?;
+        Ok(url)
+    }
+
     //for debuggin purposes
     pub fn copy_raw_text_lines_to_clipboard(&self) -> Result<(), String> {
         if self.raw_text_lines.is_empty() {
@@ -163,13 +225,20 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         screen_y: u16,
         content_area: Rect,
     ) -> Option<(usize, usize)> {
-        self.text_selection.screen_to_text_coords(
+        let (line, column) = self.text_selection.screen_to_text_coords(
             screen_x,
             screen_y,
             self.scroll_offset,
             content_area.x,
             content_area.y,
-        )
+        )?;
+        // Widened glyphs span two screen columns, but text that wasn't widened keeps its own
+        let column = if self.is_double_width() {
+            self.widened_char_at_column(line, column)
+        } else {
+            column
+        };
+        Some((line, column))
     }
 
     /// Get the full text content of the current chapter
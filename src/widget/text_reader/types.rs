@@ -67,6 +67,8 @@ pub enum LineType {
         paragraph_index: usize,
         word_range: Option<(usize, usize)>,
     },
+    /// A note imported from another reader; shown, but not ours to edit
+    SharedComment,
 }
 
 /// Span that may contain link information
@@ -85,6 +87,13 @@ impl RichSpan {
         }
     }
 
+    pub fn content(&self) -> &str {
+        match self {
+            RichSpan::Text(span) => &span.content,
+            RichSpan::Link { span, .. } => &span.content,
+        }
+    }
+
     /// Get link info if this is a link
     pub fn link_info(&self) -> Option<&LinkInfo> {
         match self {
@@ -0,0 +1,156 @@
+use super::types::*;
+use crate::theme::Base16Palette;
+use ratatui::{
+    style::{Modifier, Style},
+    text::Span,
+};
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Terms (names, technical words) to highlight wherever they appear in the book
+    pub fn set_watchlist(&mut self, terms: Vec<String>) {
+        self.watchlist = terms;
+        // Rendered lines carry the highlighting, so they must be rebuilt
+        self.cache_generation += 1;
+    }
+
+    /// Post-processing pass: highlight watched terms in every line of text
+    pub fn apply_watchlist_highlighting(
+        &self,
+        lines: &mut [RenderedLine],
+        palette: &Base16Palette,
+    ) {
+        if self.watchlist.is_empty() {
+            return;
+        }
+        let terms: Vec<Vec<char>> = self
+            .watchlist
+            .iter()
+            .map(|term| fold_case(term.trim()))
+            .filter(|term| !term.is_empty())
+            .collect();
+        let style = Style::default()
+            .fg(palette.base_0e)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+        for line in lines.iter_mut() {
+            if matches!(
+                line.line_type,
+                LineType::CodeBlock { .. } | LineType::ImagePlaceholder { .. }
+            ) {
+                continue;
+            }
+
+            let spans = std::mem::take(&mut line.spans);
+            line.spans = spans
+                .into_iter()
+                .flat_map(|span| highlight_terms(span, &terms, style))
+                .collect();
+        }
+    }
+}
+
+fn fold_case(text: &str) -> Vec<char> {
+    text.chars().map(fold_char).collect()
+}
+
+/// One-to-one lowercasing, so positions in the folded text match the original
+fn fold_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(folded), None) => folded,
+        _ => c,
+    }
+}
+
+/// Scripts written without spaces between words match anywhere; others only as whole words
+fn is_word_char(c: char) -> bool {
+    let unspaced = matches!(
+        c,
+        '\u{3040}'..='\u{30ff}' // Hiragana, Katakana
+            | '\u{3400}'..='\u{4dbf}' // CJK Extension A
+            | '\u{4e00}'..='\u{9fff}' // CJK Unified Ideographs
+            | '\u{0e00}'..='\u{0e7f}' // Thai
+    );
+    c.is_alphanumeric() && !unspaced
+}
+
+/// Split a span around case-insensitive, whole-word occurrences of `terms`, styling them
+fn highlight_terms(span: Span<'static>, terms: &[Vec<char>], style: Style) -> Vec<Span<'static>> {
+    let chars: Vec<char> = span.content.chars().collect();
+    let folded: Vec<char> = chars.iter().copied().map(fold_char).collect();
+
+    let mut matches: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < folded.len() {
+        let at_word_start = i == 0 || !is_word_char(chars[i - 1]) || !is_word_char(chars[i]);
+        let found = at_word_start
+            .then(|| {
+                terms
+                    .iter()
+                    .filter(|term| folded[i..].starts_with(term))
+                    .filter(|term| {
+                        let end = i + term.len();
+                        end == chars.len()
+                            || !is_word_char(chars[end])
+                            || !is_word_char(chars[end - 1])
+                    })
+                    .map(Vec::len)
+                    .max()
+            })
+            .flatten();
+        match found {
+            Some(len) => {
+                matches.push((i, i + len));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    if matches.is_empty() {
+        return vec![span];
+    }
+
+    let text = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for (start, end) in matches {
+        if start > last {
+            spans.push(Span::styled(text(last, start), span.style));
+        }
+        spans.push(Span::styled(text(start, end), span.style.patch(style)));
+        last = end;
+    }
+    if last < chars.len() {
+        spans.push(Span::styled(text(last, chars.len()), span.style));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watched_terms_match_whole_words_in_any_case_and_script() {
+        let terms: Vec<Vec<char>> = ["ahab", "Ishmael", "Ñoño", "白鯨"]
+            .iter()
+            .map(|term| fold_case(term))
+            .collect();
+        let style = Style::default().add_modifier(Modifier::UNDERLINED);
+        let highlighted = |text: &'static str| -> Vec<String> {
+            highlight_terms(Span::raw(text), &terms, style)
+                .into_iter()
+                .filter(|span| span.style == style)
+                .map(|span| span.content.to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            highlighted("AHAB hunts; Ishmael's friend, not Ahabs."),
+            vec!["AHAB", "Ishmael"]
+        );
+        assert_eq!(highlighted("dijo ñoño"), vec!["ñoño"]);
+        assert_eq!(highlighted("これは白鯨の話"), vec!["白鯨"]);
+        assert!(highlighted("Nothing here").is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+use super::types::RichSpan;
+use super::zoom::widened_width;
+use ratatui::style::Color;
+use std::ops::Range;
+use textwrap::core::{Fragment, Word, break_words, display_width};
+use textwrap::word_splitters::split_words;
+use textwrap::wrap_algorithms::{Penalties, wrap_first_fit, wrap_optimal_fit};
+use textwrap::{WordSeparator, WordSplitter};
+
+/// Background that marks inline code spans
+pub const INLINE_CODE_BG: Color = Color::Gray;
+
+/// Shown at the end of a line where an inline code span too long for it was cut
+pub const CODE_CONTINUATION: &str = "↩";
+
+/// One line of wrapped text and whether it ends inside a cut code span
+#[derive(Debug, PartialEq)]
+pub struct WrappedLine {
+    pub text: String,
+    pub continued: bool,
+}
+
+/// Wrap the text of `rich_spans` to `width` columns. Without inline code this is
+/// `textwrap::wrap`; inline code spans are kept whole and moved to the next line
+/// rather than broken, unless one is wider than a line on its own.
+pub fn wrap_rich_spans(rich_spans: &[RichSpan], text: &str, width: usize) -> Vec<WrappedLine> {
+    wrap_measured(rich_spans, text, width, false)
+}
+
+/// `wrap_rich_spans` for text drawn with double-width glyphs: words are measured by the
+/// columns they take once widened, so text that isn't widened keeps its own width
+pub fn wrap_rich_spans_widened(
+    rich_spans: &[RichSpan],
+    text: &str,
+    width: usize,
+) -> Vec<WrappedLine> {
+    wrap_measured(rich_spans, text, width, true)
+}
+
+fn wrap_measured(
+    rich_spans: &[RichSpan],
+    text: &str,
+    width: usize,
+    widened: bool,
+) -> Vec<WrappedLine> {
+    let code_ranges = inline_code_ranges(rich_spans);
+    if code_ranges.is_empty() && !widened {
+        return textwrap::wrap(text, width)
+            .into_iter()
+            .map(|line| WrappedLine {
+                text: line.into_owned(),
+                continued: false,
+            })
+            .collect();
+    }
+
+    let mut wrapped = Vec::new();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let line_end = line_start + line.len();
+        let ranges: Vec<Range<usize>> = code_ranges
+            .iter()
+            .filter(|range| range.start < line_end && range.end > line_start)
+            .map(|range| {
+                range.start.max(line_start) - line_start..range.end.min(line_end) - line_start
+            })
+            .collect();
+        wrap_line(line, &ranges, width, widened, &mut wrapped);
+        line_start = line_end + 1;
+    }
+    wrapped
+}
+
+/// Byte ranges of the inline code in the concatenated text of `rich_spans`
+fn inline_code_ranges(rich_spans: &[RichSpan]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+    for rich_span in rich_spans {
+        let span = match rich_span {
+            RichSpan::Text(span) => span,
+            RichSpan::Link { span, .. } => span,
+        };
+        let end = offset + span.content.len();
+        if span.style.bg == Some(INLINE_CODE_BG) && end > offset {
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end = end,
+                _ => ranges.push(offset..end),
+            }
+        }
+        offset = end;
+    }
+    ranges
+}
+
+/// A word with the widths it is wrapped by
+#[derive(Debug)]
+struct Measured<'a> {
+    word: Word<'a>,
+    width: usize,
+    whitespace_width: usize,
+    penalty_width: usize,
+}
+
+impl Fragment for Measured<'_> {
+    fn width(&self) -> f64 {
+        self.width as f64
+    }
+
+    fn whitespace_width(&self) -> f64 {
+        self.whitespace_width as f64
+    }
+
+    fn penalty_width(&self) -> f64 {
+        self.penalty_width as f64
+    }
+}
+
+fn wrap_line(
+    line: &str,
+    code_ranges: &[Range<usize>],
+    width: usize,
+    widened: bool,
+    wrapped: &mut Vec<WrappedLine>,
+) {
+    let measure = if widened {
+        widened_width
+    } else {
+        display_width
+    };
+    let splitter = WordSplitter::HyphenSplitter;
+    let mut words: Vec<Word> = Vec::new();
+
+    let mut pos = 0;
+    for range in code_ranges {
+        // Spaces around the code separate it from its neighbours like any others
+        let range = range.start + line[range.clone()].len()
+            - line[range.clone()].trim_start_matches(' ').len()..range.end;
+        words.extend(prose_words(
+            &line[pos..range.start],
+            &splitter,
+            width,
+            widened,
+        ));
+        let code = &line[range.clone()];
+        let spaces_end =
+            range.end + line[range.end..].len() - line[range.end..].trim_start_matches(' ').len();
+        if measure(code) <= width {
+            words.push(Word::from(&line[range.start..spaces_end]));
+        } else {
+            // Cut into pieces that leave room for the continuation marker
+            let mut piece_start = 0;
+            let mut piece_width = 0;
+            for (idx, ch) in code.char_indices() {
+                let ch_width = measure(ch.encode_utf8(&mut [0; 4]));
+                if piece_width + ch_width >= width && idx > piece_start {
+                    let mut piece = Word::from(&code[piece_start..idx]);
+                    piece.penalty = CODE_CONTINUATION;
+                    words.push(piece);
+                    piece_start = idx;
+                    piece_width = 0;
+                }
+                piece_width += ch_width;
+            }
+            words.push(Word::from(&line[range.start + piece_start..spaces_end]));
+        }
+        pos = spaces_end;
+    }
+    words.extend(prose_words(&line[pos..], &splitter, width, widened));
+    let fragments: Vec<Measured> = words
+        .into_iter()
+        .map(|word| Measured {
+            width: measure(&word),
+            whitespace_width: measure(word.whitespace),
+            penalty_width: measure(word.penalty),
+            word,
+        })
+        .collect();
+
+    // What `WrapAlgorithm::new()` does, for fragments other than `Word`
+    let line_widths = [width as f64];
+    let lines = wrap_optimal_fit(&fragments, &line_widths, &Penalties::new())
+        .unwrap_or_else(|_| wrap_first_fit(&fragments, &line_widths));
+
+    let mut idx = 0;
+    for line_words in lines {
+        let Some(last_word) = line_words.last().map(|fragment| &fragment.word) else {
+            wrapped.push(WrappedLine {
+                text: String::new(),
+                continued: false,
+            });
+            continue;
+        };
+        // The words are contiguous slices of `line`
+        let len = line_words
+            .iter()
+            .map(|fragment| fragment.word.len() + fragment.word.whitespace.len())
+            .sum::<usize>()
+            - last_word.whitespace.len();
+        wrapped.push(WrappedLine {
+            text: line[idx..idx + len].to_string(),
+            continued: last_word.penalty == CODE_CONTINUATION,
+        });
+        idx += len + last_word.whitespace.len();
+    }
+    if fragments.is_empty() {
+        wrapped.push(WrappedLine {
+            text: String::new(),
+            continued: false,
+        });
+    }
+}
+
+/// Words of running text, split and broken the way `textwrap::wrap` does it
+fn prose_words<'a>(
+    text: &'a str,
+    splitter: &'a WordSplitter,
+    width: usize,
+    widened: bool,
+) -> Vec<Word<'a>> {
+    let found = WordSeparator::new().find_words(text);
+    let words = split_words(found, splitter);
+    if !widened {
+        return break_words(words, width);
+    }
+
+    // `break_words` goes by the unwidened width, so cut words too wide for a line here
+    let mut broken = Vec::new();
+    for word in words {
+        if widened_width(&word) <= width {
+            broken.push(word);
+            continue;
+        }
+        let mut piece_start = 0;
+        let mut piece_width = 0;
+        for (idx, ch) in word.char_indices() {
+            let ch_width = widened_width(ch.encode_utf8(&mut [0; 4]));
+            if piece_width + ch_width > width && idx > piece_start {
+                broken.push(Word::from(&word.word[piece_start..idx]));
+                piece_start = idx;
+                piece_width = 0;
+            }
+            piece_width += ch_width;
+        }
+        let mut last = Word::from(&word.word[piece_start..]);
+        last.whitespace = word.whitespace;
+        last.penalty = word.penalty;
+        broken.push(last);
+    }
+    broken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Style;
+    use ratatui::text::Span;
+
+    fn code(text: &str) -> RichSpan {
+        RichSpan::Text(Span::styled(
+            text.to_string(),
+            Style::default().bg(INLINE_CODE_BG),
+        ))
+    }
+
+    fn wrap(rich_spans: &[RichSpan], width: usize) -> Vec<(String, bool)> {
+        let text: String = rich_spans
+            .iter()
+            .map(|rich_span| match rich_span {
+                RichSpan::Text(span) | RichSpan::Link { span, .. } => span.content.as_ref(),
+            })
+            .collect();
+        wrap_rich_spans(rich_spans, &text, width)
+            .into_iter()
+            .map(|line| (line.text, line.continued))
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_code_moves_whole_or_is_cut_with_a_marker() {
+        let spans = [
+            RichSpan::Text(Span::raw("Call ")),
+            code("serde-json::to_string"),
+            RichSpan::Text(Span::raw(" then ")),
+            code("std::collections::hash_map::DefaultHasher"),
+            RichSpan::Text(Span::raw(" once.")),
+        ];
+        assert_eq!(
+            wrap(&spans, 24),
+            [
+                ("Call".to_string(), false),
+                ("serde-json::to_string".to_string(), false),
+                ("then".to_string(), false),
+                ("std::collections::hash_".to_string(), true),
+                ("map::DefaultHasher once.".to_string(), false),
+            ]
+        );
+
+        // Prose alone wraps exactly as before
+        let prose = [RichSpan::Text(Span::raw("a well-known fact\nnext"))];
+        assert_eq!(
+            wrap(&prose, 8),
+            [
+                ("a well-".to_string(), false),
+                ("known".to_string(), false),
+                ("fact".to_string(), false),
+                ("next".to_string(), false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use super::types::*;
+use ratatui::text::Span;
+use textwrap::core::display_width;
+
+/// Highest content zoom level; every level narrows the text column by 15% and spaces
+/// the text out further
+pub const MAX_ZOOM_LEVEL: u8 = 3;
+const NARROWING_PER_LEVEL: usize = 15;
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    pub fn zoom_level(&self) -> u8 {
+        self.zoom_level
+    }
+
+    /// Set the zoom level (0 = off) and whether zoomed text uses double-width glyphs
+    pub fn set_zoom(&mut self, level: u8, double_width: bool) {
+        self.zoom_level = level.min(MAX_ZOOM_LEVEL);
+        self.zoom_double_width = double_width;
+        // Wrapping depends on the zoom, so rendered lines must be rebuilt
+        self.cache_generation += 1;
+    }
+
+    pub(super) fn is_double_width(&self) -> bool {
+        self.zoom_level > 0 && self.zoom_double_width
+    }
+
+    /// Screen columns used by the text column at the current zoom level
+    pub(super) fn zoomed_column_width(&self, width: usize) -> usize {
+        width * (100 - NARROWING_PER_LEVEL * self.zoom_level as usize) / 100
+    }
+
+    /// Width to wrap text at, counted in double-width glyphs when those are on. Running
+    /// text is wrapped by the columns it really takes once widened (twice this width).
+    pub(super) fn zoomed_wrap_width(&self, width: usize) -> usize {
+        let column_width = self.zoomed_column_width(width);
+        if self.is_double_width() {
+            column_width / 2
+        } else {
+            column_width
+        }
+    }
+
+    /// Blank lines between the wrapped lines of a paragraph: one from level 2 up
+    pub(super) fn zoom_line_spacing(&self) -> usize {
+        self.zoom_level as usize / 2
+    }
+
+    /// Blank lines added after a paragraph, on top of the usual one: one at levels 1 and
+    /// 2, two at level 3
+    pub(super) fn zoom_paragraph_spacing(&self) -> usize {
+        (self.zoom_level as usize).div_ceil(2)
+    }
+
+    /// Character under screen `column` of rendered line `line`, going by the columns each
+    /// glyph of the widened line really takes
+    pub(super) fn widened_char_at_column(&self, line: usize, column: usize) -> usize {
+        let Some(rendered) = self.rendered_content.lines.get(line) else {
+            return column;
+        };
+        let mut used = 0;
+        let mut index = 0;
+        for ch in rendered.spans.iter().flat_map(|span| span.content.chars()) {
+            used += display_width(ch.encode_utf8(&mut [0; 4]));
+            if used > column {
+                return index;
+            }
+            index += 1;
+        }
+        // Past the end of the line, one character per column as without zoom
+        index + (column - used)
+    }
+
+    /// Post-processing pass: swap ASCII for its fullwidth forms so text renders twice as wide
+    pub(super) fn apply_double_width(&self, lines: &mut [RenderedLine]) {
+        if !self.is_double_width() {
+            return;
+        }
+
+        for line in lines.iter_mut() {
+            if matches!(line.line_type, LineType::ImagePlaceholder { .. }) {
+                continue;
+            }
+            for span in line.spans.iter_mut() {
+                let widened: String = span.content.chars().map(to_fullwidth).collect();
+                *span = Span::styled(widened, span.style);
+            }
+        }
+    }
+}
+
+/// Columns `text` takes once widened with `to_fullwidth`
+pub(super) fn widened_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| display_width(to_fullwidth(c).encode_utf8(&mut [0; 4])))
+        .sum()
+}
+
+fn to_fullwidth(c: char) -> char {
+    match c {
+        ' ' => '\u{3000}',
+        '!'..='~' => char::from_u32(c as u32 + 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::theme::OCEANIC_NEXT;
+
+    /// Rendered rows of two paragraphs that wrap to two lines each at width 12
+    fn rendered_rows(zoom_level: u8) -> Vec<String> {
+        let doc = HtmlToMarkdownConverter::new()
+            .convert("<p>One two three four</p><p>Five six seven eight</p>");
+        let mut reader = MarkdownTextReader::new();
+        reader.set_zoom(zoom_level, false);
+        reader
+            .render_document_to_lines(&doc, 12, &OCEANIC_NEXT, true)
+            .lines
+            .into_iter()
+            .map(|line| line.raw_text)
+            .collect()
+    }
+
+    #[test]
+    fn test_spacing_grows_with_zoom_level() {
+        assert_eq!(
+            rendered_rows(0),
+            ["One two", "three four", "", "Five six", "seven eight", ""]
+        );
+        assert_eq!(
+            rendered_rows(1),
+            [
+                "One two",
+                "three four",
+                "",
+                "",
+                "Five six",
+                "seven eight",
+                "",
+                ""
+            ]
+        );
+        assert_eq!(
+            rendered_rows(3),
+            [
+                "One two",
+                "",
+                "three four",
+                "",
+                "",
+                "",
+                "Five six",
+                "",
+                "seven eight",
+                "",
+                "",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_fullwidth_keeps_char_count() {
+        let widened: String = "Hi, 42!".chars().map(to_fullwidth).collect();
+        assert_eq!(widened, "Ｈｉ，\u{3000}４２！");
+        assert_eq!(widened.chars().count(), 7);
+        assert_eq!(to_fullwidth('é'), 'é');
+        assert_eq!(widened_width("Hé 日本"), 2 + 1 + 2 + 4);
+    }
+
+    #[test]
+    fn test_double_width_selection_follows_mixed_width_text() {
+        let doc = HtmlToMarkdownConverter::new().convert("<p>ab café 日本 cd</p>");
+        let mut reader = MarkdownTextReader::new();
+        reader.set_zoom(1, true);
+        reader.rendered_content = reader.render_document_to_lines(&doc, 40, &OCEANIC_NEXT, true);
+        assert_eq!(reader.rendered_content.lines[0].raw_text, "ab café 日本 cd");
+
+        // Columns of "ａｂ　ｃａｆé　日本　ｃｄ": a b space c a f take two each, é one
+        let char_at = |column| reader.widened_char_at_column(0, column);
+        assert_eq!(char_at(0), 0);
+        assert_eq!(char_at(3), 1);
+        assert_eq!(char_at(12), 6); // é
+        assert_eq!(char_at(13), 7); // space after é
+        assert_eq!(char_at(15), 8); // 日
+        assert_eq!(char_at(18), 9); // 本
+        assert_eq!(char_at(19), 10);
+        assert_eq!(char_at(21), 11); // c
+        assert_eq!(char_at(24), 12); // d
+    }
+
+    #[test]
+    fn test_double_width_wraps_by_real_width() {
+        let doc = HtmlToMarkdownConverter::new().convert("<p>ab 日本語 cd</p>");
+        let mut reader = MarkdownTextReader::new();
+        reader.set_zoom(1, true);
+        // 7 double-width glyphs: 14 columns, which "ab 日本語" fills exactly
+        let lines: Vec<String> = reader
+            .render_document_to_lines(&doc, 7, &OCEANIC_NEXT, true)
+            .lines
+            .into_iter()
+            .map(|line| line.raw_text)
+            .collect();
+        assert_eq!(lines[..2], ["ab 日本語", "cd"]);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::inputs::KeySeq;
+use crate::main_app::VimNavMotions;
+use crate::theme::current_theme;
+use crate::timeline::TimelineEntry;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+const POPUP_WIDTH: u16 = 90;
+
+pub enum TimelineAction {
+    Jump(TimelineEntry),
+    Close,
+}
+
+/// The `:timeline` panel: dated headings and epigraphs in date order rather than the
+/// order they come in the book
+pub struct TimelinePopup {
+    entries: Vec<TimelineEntry>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl TimelinePopup {
+    /// Opens on the first entry of `current_chapter`, if it has one
+    pub fn new(entries: Vec<TimelineEntry>, current_chapter: usize) -> Self {
+        let mut state = ListState::default();
+        if !entries.is_empty() {
+            let current = entries
+                .iter()
+                .position(|entry| entry.chapter_index == current_chapter);
+            state.select(Some(current.unwrap_or(0)));
+        }
+        TimelinePopup {
+            entries,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = POPUP_WIDTH.min(area.width);
+        let height = (area.height * 4 / 5).max(6).min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let theme = current_theme();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let date = if entry.has_year {
+                    entry.date.format("%e %b %Y").to_string()
+                } else {
+                    entry.date.format("%e %b").to_string()
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{date:<12} "), Style::default().fg(theme.base_0a)),
+                    Span::styled(entry.label.clone(), Style::default().fg(theme.base_05)),
+                    Span::styled(
+                        format!("  ch {}", entry.chapter_index + 1),
+                        Style::default().fg(theme.base_03),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(" Timeline ({}) ", self.entries.len()))
+                    .title_bottom(Line::from(" Enter: Jump | ESC: Close ").right_aligned())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.base_0c))
+                    .style(Style::default().bg(theme.base_00)),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(theme.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+
+        f.render_stateful_widget(list, popup_area, &mut self.state);
+    }
+
+    fn select_offset(&mut self, delta: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.entries.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, key_seq: &mut KeySeq) -> Option<TimelineAction> {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.handle_j(),
+            KeyCode::Char('k') | KeyCode::Up => self.handle_k(),
+            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => self.handle_gg(),
+            KeyCode::Char('G') => self.handle_upper_g(),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_d()
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.handle_ctrl_u()
+            }
+            KeyCode::Enter => {
+                return self
+                    .state
+                    .selected()
+                    .and_then(|i| self.entries.get(i))
+                    .cloned()
+                    .map(TimelineAction::Jump);
+            }
+            KeyCode::Esc => return Some(TimelineAction::Close),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl VimNavMotions for TimelinePopup {
+    fn handle_h(&mut self) {}
+
+    fn handle_j(&mut self) {
+        self.select_offset(1);
+    }
+
+    fn handle_k(&mut self) {
+        self.select_offset(-1);
+    }
+
+    fn handle_l(&mut self) {}
+
+    fn handle_ctrl_d(&mut self) {
+        self.select_offset(10);
+    }
+
+    fn handle_ctrl_u(&mut self) {
+        self.select_offset(-10);
+    }
+
+    fn handle_gg(&mut self) {
+        self.select_offset(-(self.entries.len() as isize));
+    }
+
+    fn handle_upper_g(&mut self) {
+        self.select_offset(self.entries.len() as isize);
+    }
+}
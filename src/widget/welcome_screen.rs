@@ -0,0 +1,249 @@
+use crate::bookmarks::Bookmarks;
+use crate::theme::current_theme;
+use rand::seq::SliceRandom;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Padding, Paragraph},
+};
+use std::path::Path;
+
+/// How many recent books are listed
+const RECENT_LIMIT: usize = 8;
+
+const KEY_HINTS: &[(&str, &str)] = &[
+    ("Tab Enter", "continue the suggested book"),
+    ("j/k Enter", "open a book from the library"),
+    ("click", "open a recent book"),
+    ("Ctrl+P", "jump to a book or chapter by name"),
+    ("Space h", "reading history"),
+    (":", "command line"),
+    ("?", "all keys"),
+    ("q", "quit"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentBook {
+    pub path: String,
+    pub title: String,
+    pub chapter: usize,
+    pub total_chapters: usize,
+}
+
+impl RecentBook {
+    /// Share of the chapters read, once the number of chapters is known
+    pub fn percent(&self) -> Option<usize> {
+        (self.total_chapters > 0).then(|| (self.chapter + 1) * 100 / self.total_chapters)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.total_chapters > 0 && self.chapter + 1 >= self.total_chapters
+    }
+}
+
+/// Books with bookmarks whose files are still there, most recently read first
+pub fn recent_books(bookmarks: &Bookmarks, limit: usize) -> Vec<RecentBook> {
+    let mut books: Vec<_> = bookmarks
+        .iter()
+        .filter(|(path, _)| Path::new(path).is_file())
+        .collect();
+    books.sort_by_key(|(_, bookmark)| std::cmp::Reverse(bookmark.last_read));
+    books
+        .into_iter()
+        .take(limit)
+        .map(|(path, bookmark)| RecentBook {
+            path: path.clone(),
+            title: Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.clone()),
+            chapter: bookmark.chapter_index.unwrap_or(0),
+            total_chapters: bookmark.total_chapters.unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Shown in the reader pane while no book is open: the recent books with how far they
+/// were read, one of them picked to carry on with, and the keys to get going
+pub struct WelcomeScreen {
+    recent: Vec<RecentBook>,
+    /// Index into `recent` of the suggested book, picked once so it stays put between draws
+    suggestion: Option<usize>,
+    /// Screen row of each listed book, from the last draw
+    book_rows: Vec<u16>,
+    last_area: Option<Rect>,
+}
+
+impl WelcomeScreen {
+    pub fn new(bookmarks: &Bookmarks) -> Self {
+        Self::with_books(recent_books(bookmarks, RECENT_LIMIT))
+    }
+
+    fn with_books(recent: Vec<RecentBook>) -> Self {
+        // Any unfinished book will do; a finished one only if there's nothing else
+        let unfinished: Vec<usize> = (0..recent.len())
+            .filter(|&idx| !recent[idx].is_finished())
+            .collect();
+        let candidates = if unfinished.is_empty() {
+            (0..recent.len()).collect()
+        } else {
+            unfinished
+        };
+        let suggestion = candidates.choose(&mut rand::thread_rng()).copied();
+        WelcomeScreen {
+            recent,
+            suggestion,
+            book_rows: Vec::new(),
+            last_area: None,
+        }
+    }
+
+    pub fn suggested_path(&self) -> Option<&str> {
+        self.suggestion.map(|idx| self.recent[idx].path.as_str())
+    }
+
+    /// Path of the recent book drawn at the given position, if any
+    pub fn path_at(&self, x: u16, y: u16) -> Option<&str> {
+        let area = self.last_area?;
+        if x <= area.x || x + 1 >= area.x + area.width {
+            return None;
+        }
+        let idx = self.book_rows.iter().position(|&row| row == y)?;
+        Some(self.recent[idx].path.as_str())
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, is_focused: bool) {
+        let theme = current_theme();
+        let (text_color, border_color, _bg_color) = theme.get_panel_colors(is_focused);
+        let dim = Style::default().fg(theme.base_03);
+        let accent = Style::default().fg(theme.base_0d);
+        let heading = Style::default()
+            .fg(theme.base_0a)
+            .add_modifier(Modifier::BOLD);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Welcome")
+            .padding(Padding::horizontal(1))
+            .border_style(Style::default().fg(border_color))
+            .style(Style::default().bg(theme.base_00));
+        let inner = block.inner(area);
+
+        let mut lines = vec![
+            Line::from(Span::styled("bookokcat", heading)).alignment(Alignment::Center),
+            Line::default(),
+        ];
+
+        if let Some(book) = self.suggestion.map(|idx| &self.recent[idx]) {
+            lines.push(Line::from(Span::styled(
+                "Continue where you left off",
+                heading,
+            )));
+            let mut spans = vec![
+                Span::raw("  "),
+                Span::styled(
+                    book.title.clone(),
+                    Style::default()
+                        .fg(theme.base_05)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ];
+            if book.total_chapters > 0 {
+                spans.push(Span::styled(
+                    format!(" — chapter {} of {}", book.chapter + 1, book.total_chapters),
+                    dim,
+                ));
+            }
+            lines.push(Line::from(spans));
+            lines.push(Line::default());
+        }
+
+        self.book_rows.clear();
+        if self.recent.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No books read yet. Select a file to start reading.",
+                Style::default().fg(text_color),
+            )));
+        } else {
+            lines.push(Line::from(Span::styled("Recent books", heading)));
+            let title_width = self
+                .recent
+                .iter()
+                .map(|book| book.title.chars().count())
+                .max()
+                .unwrap_or(0)
+                .min(inner.width.saturating_sub(24) as usize);
+            for book in &self.recent {
+                self.book_rows.push(inner.y + lines.len() as u16);
+                let title: String = book.title.chars().take(title_width).collect();
+                let progress = match book.percent() {
+                    Some(percent) => format!("{:>3}%  {}", percent, progress_bar(percent, 10)),
+                    None => String::new(),
+                };
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        format!("{title:<title_width$}"),
+                        Style::default().fg(text_color),
+                    ),
+                    Span::raw("  "),
+                    Span::styled(progress, accent),
+                ]));
+            }
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from(Span::styled("Keys", heading)));
+        for (key, action) in KEY_HINTS {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {key:<12}"), accent),
+                Span::styled(*action, Style::default().fg(text_color)),
+            ]));
+        }
+
+        self.last_area = Some(area);
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().fg(text_color).bg(theme.base_00));
+        f.render_widget(paragraph, area);
+    }
+}
+
+fn progress_bar(percent: usize, width: usize) -> String {
+    let filled = (percent.min(100) * width).div_ceil(100);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(path: &str, chapter: usize, total_chapters: usize) -> RecentBook {
+        RecentBook {
+            path: path.to_string(),
+            title: path.to_string(),
+            chapter,
+            total_chapters,
+        }
+    }
+
+    #[test]
+    fn test_suggestion_prefers_unfinished_books() {
+        for _ in 0..20 {
+            let screen = WelcomeScreen::with_books(vec![
+                book("done.epub", 9, 10),
+                book("middle.epub", 4, 10),
+            ]);
+            assert_eq!(screen.suggested_path(), Some("middle.epub"));
+        }
+        let screen = WelcomeScreen::with_books(vec![book("done.epub", 9, 10)]);
+        assert_eq!(screen.suggested_path(), Some("done.epub"));
+        assert_eq!(WelcomeScreen::with_books(Vec::new()).suggested_path(), None);
+
+        assert_eq!(book("a", 4, 10).percent(), Some(50));
+        assert_eq!(book("a", 0, 0).percent(), None);
+        assert_eq!(progress_bar(50, 10), "█████░░░░░");
+    }
+}
@@ -0,0 +1,65 @@
+use bookokcat::main_app::{App, run_app_with_event_source};
+use bookokcat::search::SearchablePanel;
+use bookokcat::system_command::MockSystemCommandExecutor;
+use bookokcat::test_utils::test_helpers::{
+    TestScenarioBuilder, capture_terminal_state, create_test_app,
+};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+
+#[test]
+fn test_paste_goes_into_search_box_without_running_commands() {
+    let mut app = create_test_app();
+
+    // The pasted text is full of keys that would otherwise move the selection or quit
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char('/')
+        .paste("jump quickly\nkeep")
+        .press_enter()
+        .quit()
+        .build();
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    run_app_with_event_source(&mut terminal, &mut app, &mut event_source).unwrap();
+
+    assert_eq!(
+        app.navigation_panel.get_search_state().query,
+        "jump quickly keep"
+    );
+}
+
+#[test]
+fn test_paste_goes_into_book_search_query() {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub("tests/testdata/digital_frontier.epub", false)
+        .unwrap();
+
+    // Search the whole book for the pasted word and jump to the first hit, then dock the
+    // results to read them back
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char(' ')
+        .press_char('F')
+        .paste("code")
+        .press_enter()
+        .press_enter()
+        .press_esc()
+        .type_str(":panel search")
+        .press_enter()
+        .quit()
+        .build();
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+
+    run_app_with_event_source(&mut terminal, &mut app, &mut event_source).unwrap();
+
+    let screen = capture_terminal_state(&terminal);
+    assert!(
+        screen.contains(" Search results (") && !screen.contains("Search results (0)"),
+        "{screen}"
+    );
+}
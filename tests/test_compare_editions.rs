@@ -0,0 +1,39 @@
+use bookokcat::main_app::{App, run_app_with_event_source};
+use bookokcat::system_command::MockSystemCommandExecutor;
+use bookokcat::test_utils::test_helpers::{
+    TestScenarioBuilder, capture_terminal_state, create_test_terminal,
+};
+
+/// Run `command` on the `:` prompt and return the screen left after it
+fn run_command(app: &mut App, command: &str) -> String {
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char(':')
+        .type_str(command)
+        .press_enter()
+        .quit()
+        .build();
+    let mut terminal = create_test_terminal(120, 30);
+    let _ = run_app_with_event_source(&mut terminal, app, &mut event_source);
+    capture_terminal_state(&terminal)
+}
+
+#[test]
+fn test_other_edition_is_shown_beside_the_chapter() {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub("tests/testdata/digital_frontier.epub", false)
+        .unwrap();
+
+    let screen = run_command(&mut app, "compare tests/testdata/digital_frontier.epub");
+    // The same book on both sides: the right half shows the chapter being read
+    let title: String = screen.lines().next().unwrap().chars().skip(60).collect();
+    assert!(title.contains("digital_frontier.epub ["), "{title}");
+
+    let screen = run_command(&mut app, "compare off");
+    let title = screen.lines().next().unwrap();
+    assert!(!title.contains("digital_frontier.epub ["), "{title}");
+}
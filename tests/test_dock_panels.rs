@@ -0,0 +1,85 @@
+use bookokcat::main_app::{App, FocusedPanel, MainPanel, run_app_with_event_source};
+use bookokcat::system_command::MockSystemCommandExecutor;
+use bookokcat::test_utils::test_helpers::{
+    TestScenarioBuilder, capture_terminal_state, create_test_terminal,
+};
+use bookokcat::widget::dock_panel::DockKind;
+
+/// Play the key presses queued in `scenario`, then quit, and return the screen left after them
+fn run(app: &mut App, scenario: TestScenarioBuilder) -> String {
+    let mut event_source = scenario.quit().build();
+    let mut terminal = create_test_terminal(120, 40);
+    let _ = run_app_with_event_source(&mut terminal, app, &mut event_source);
+    capture_terminal_state(&terminal)
+}
+
+#[test]
+fn test_docked_search_results_take_focus_with_tab_and_ctrl_w() {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub("tests/testdata/digital_frontier.epub", false)
+        .unwrap();
+
+    // Search the book, jump to the first result, then dock the results under the reader
+    let scenario = TestScenarioBuilder::new()
+        .type_str(" Fcode")
+        .press_enter()
+        .press_enter()
+        .type_str(":panel search")
+        .press_enter();
+    let screen = run(&mut app, scenario);
+    let dock = DockKind::SearchResults;
+    assert_eq!(app.focused_panel, FocusedPanel::Main(MainPanel::Dock(dock)));
+    assert!(
+        screen
+            .lines()
+            .any(|line| line.contains(" Search results (") && !line.contains("Search results (0)")),
+        "{screen}"
+    );
+
+    // Up to the reader and back down, as between vim windows
+    run(
+        &mut app,
+        TestScenarioBuilder::new()
+            .press_ctrl_char('w')
+            .press_char('k'),
+    );
+    assert_eq!(app.focused_panel, FocusedPanel::Main(MainPanel::Content));
+    run(
+        &mut app,
+        TestScenarioBuilder::new()
+            .press_ctrl_char('w')
+            .press_char('j'),
+    );
+    assert_eq!(app.focused_panel, FocusedPanel::Main(MainPanel::Dock(dock)));
+
+    // Tab goes round all three panels
+    run(&mut app, TestScenarioBuilder::new().press_tab());
+    assert_eq!(
+        app.focused_panel,
+        FocusedPanel::Main(MainPanel::NavigationList)
+    );
+
+    // Enter on a result jumps to it in the reader
+    let scenario = TestScenarioBuilder::new()
+        .press_ctrl_char('w')
+        .press_char('l')
+        .press_ctrl_char('w')
+        .press_char('j')
+        .press_enter();
+    run(&mut app, scenario);
+    assert_eq!(app.focused_panel, FocusedPanel::Main(MainPanel::Content));
+
+    // A second `:panel search` closes it
+    let screen = run(
+        &mut app,
+        TestScenarioBuilder::new()
+            .type_str(":panel search")
+            .press_enter(),
+    );
+    assert!(!screen.contains(" Search results ("));
+}
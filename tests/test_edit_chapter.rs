@@ -0,0 +1,66 @@
+use bookokcat::main_app::{App, run_app_with_event_source};
+use bookokcat::system_command::MockSystemCommandExecutor;
+use bookokcat::test_utils::test_helpers::{TestScenarioBuilder, create_test_terminal};
+
+fn run_command(app: &mut App, command: &str) {
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char(':')
+        .type_str(command)
+        .press_enter()
+        .quit()
+        .build();
+    let mut terminal = create_test_terminal(80, 24);
+    let _ = run_app_with_event_source(&mut terminal, app, &mut event_source);
+}
+
+fn executed_commands(app: &App) -> Vec<String> {
+    app.system_command_executor
+        .as_any()
+        .downcast_ref::<MockSystemCommandExecutor>()
+        .unwrap()
+        .get_executed_commands()
+}
+
+#[test]
+fn test_pager_runs_on_a_temporary_copy_of_the_chapter() {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub("tests/testdata/digital_frontier.epub", false)
+        .unwrap();
+
+    run_command(&mut app, "pager");
+
+    let executed = executed_commands(&app);
+    assert_eq!(executed.len(), 1);
+    let path = executed[0]
+        .strip_prefix("RUN:")
+        .and_then(|command| command.rsplit_once(' '))
+        .map(|(_, path)| std::path::PathBuf::from(path))
+        .unwrap();
+    let name = path.file_name().unwrap().to_string_lossy();
+    assert!(
+        name.starts_with("bookokcat-digital_frontier-chapter"),
+        "{name}"
+    );
+    assert!(name.ends_with(".md"), "{name}");
+    // Gone once the program has exited
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_edit_without_a_chapter_runs_nothing() {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+
+    run_command(&mut app, "edit");
+
+    assert!(executed_commands(&app).is_empty());
+}
@@ -0,0 +1,77 @@
+use bookokcat::main_app::{App, ChapterDirection, run_app_with_event_source};
+use bookokcat::system_command::MockSystemCommandExecutor;
+use bookokcat::test_utils::test_helpers::{
+    TestScenarioBuilder, capture_terminal_state, create_test_terminal,
+};
+
+/// Run the scenario and return the screen left after it
+fn run(app: &mut App, scenario: TestScenarioBuilder) -> String {
+    let mut event_source = scenario.quit().build();
+    let mut terminal = create_test_terminal(120, 40);
+    let _ = run_app_with_event_source(&mut terminal, app, &mut event_source);
+    capture_terminal_state(&terminal)
+}
+
+/// The chapter index in the reader's title, e.g. 3 for `[3/5] Chapter 4`
+fn chapter_on_screen(screen: &str) -> usize {
+    let title = screen
+        .split("┌[")
+        .nth(1)
+        .unwrap_or_else(|| panic!("no chapter title on screen:\n{screen}"));
+    title[..title.find('/').unwrap()].parse().unwrap()
+}
+
+/// The five-chapter test book with the notice about its health out of the way
+fn app_with_book() -> App {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub("tests/testdata/digital_frontier.epub", false)
+        .unwrap();
+    app.navigate_to_chapter(0).unwrap();
+    run(&mut app, TestScenarioBuilder::new());
+    app
+}
+
+#[test]
+fn test_random_jumps_only_land_on_unread_chapters() {
+    let mut app = app_with_book();
+    // Moving on from a chapter marks it read: 0 and 1 are read, 2 is open
+    app.navigate_chapter_relative(ChapterDirection::Next)
+        .unwrap();
+    app.navigate_chapter_relative(ChapterDirection::Next)
+        .unwrap();
+    assert_eq!(
+        chapter_on_screen(&run(&mut app, TestScenarioBuilder::new())),
+        2
+    );
+
+    for _ in 0..20 {
+        let screen = run(&mut app, TestScenarioBuilder::new().type_str(" r"));
+        let chapter = chapter_on_screen(&screen);
+        assert!(
+            [2, 3, 4].contains(&chapter),
+            "jumped to read chapter {chapter}"
+        );
+        assert!(screen.contains("Random unread chapter"), "{screen}");
+    }
+}
+
+#[test]
+fn test_random_jump_with_every_chapter_read_stays_put() {
+    let mut app = app_with_book();
+    for _ in 0..4 {
+        app.navigate_chapter_relative(ChapterDirection::Next)
+            .unwrap();
+    }
+
+    let screen = run(&mut app, TestScenarioBuilder::new().type_str(" r"));
+    assert_eq!(chapter_on_screen(&screen), 4);
+    assert!(
+        screen.contains("Every chapter has already been read"),
+        "{screen}"
+    );
+}
@@ -1,10 +1,13 @@
 use bookokcat::book_manager::{BookInfo, BookManager};
-use bookokcat::main_app::VimNavMotions;
+use bookokcat::main_app::{App, FocusedPanel, MainPanel, VimNavMotions, run_app_with_event_source};
 use bookokcat::markdown_text_reader::{ActiveSection, MarkdownTextReader};
 use bookokcat::navigation_panel::{CurrentBookInfo, NavigationMode, NavigationPanel};
+use bookokcat::system_command::MockSystemCommandExecutor;
 use bookokcat::table_of_contents::TocItem;
-use bookokcat::test_utils::test_helpers::create_test_terminal;
+use bookokcat::test_utils::test_helpers::{TestScenarioBuilder, create_test_terminal};
 use bookokcat::theme::Base16Palette;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
 
 mod snapshot_assertions;
 mod svg_generation;
@@ -344,3 +347,231 @@ fn test_text_reader_vim_motion_gg() {
         create_test_failure_handler("test_text_reader_vim_motion_gg"),
     );
 }
+
+#[test]
+fn test_text_reader_page_mode_flips_whole_screens() {
+    let mut terminal = create_test_terminal(50, 20);
+    let mut text_reader = MarkdownTextReader::new();
+    let test_content = (0..=100)
+        .map(|i| format!("<p>This is paragraph {i}.</p>"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    text_reader.set_content_from_string(&test_content, None);
+    text_reader.set_page_mode(true);
+
+    let mut draw = |reader: &mut MarkdownTextReader| {
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 5, get_test_palette(), true))
+            .unwrap();
+    };
+    draw(&mut text_reader);
+    let (page, pages) = text_reader.page_position();
+    assert_eq!(page, 1);
+    assert!(pages > 2);
+
+    // A page is the 17 rows left inside the borders and footer
+    assert!(text_reader.next_page());
+    assert_eq!(text_reader.get_scroll_offset(), 17);
+    text_reader.scroll_down();
+    assert_eq!(text_reader.get_scroll_offset(), 34);
+    assert!(text_reader.previous_page());
+    assert!(text_reader.previous_page());
+    assert!(!text_reader.previous_page());
+    assert_eq!(text_reader.get_scroll_offset(), 0);
+
+    text_reader.handle_upper_g();
+    draw(&mut text_reader);
+    assert_eq!(text_reader.page_position(), (pages, pages));
+    assert!(!text_reader.next_page());
+}
+
+#[test]
+fn test_text_reader_bracket_motion_takes_a_count() {
+    // Five footnotes, far enough apart that no two are on screen together
+    let dir = tempfile::tempdir().unwrap();
+    let body: String = (1..=5)
+        .map(|n| {
+            let filler = "<p>Filler paragraph.</p>".repeat(30);
+            format!("<p>Claim {n}.<a href=\"#n{n}\">{n}</a></p>{filler}")
+        })
+        .collect();
+    let path = dir.path().join("notes.html");
+    std::fs::write(&path, format!("<html><body>{body}</body></html>")).unwrap();
+
+    let mut app = App::new_with_mock_system_executor(
+        dir.path().to_str(),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub(path.to_str().unwrap(), true).unwrap();
+
+    // Lay the chapter out before pressing anything in it
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    let mut event_source = TestScenarioBuilder::new().press_tab().quit().build();
+    let _ = run_app_with_event_source(&mut terminal, &mut app, &mut event_source);
+    assert_eq!(app.focused_panel, FocusedPanel::Main(MainPanel::Content));
+
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char('3')
+        .press_char(']')
+        .press_char('f')
+        .quit()
+        .build();
+    let _ = run_app_with_event_source(&mut terminal, &mut app, &mut event_source);
+    let buffer = terminal.backend().buffer();
+    let screen: Vec<String> = (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        })
+        .collect();
+    assert!(
+        screen
+            .iter()
+            .any(|line| line.contains("Jumped to footnote 3/5")),
+        "{}",
+        screen.join("\n")
+    );
+}
+
+#[test]
+fn test_toc_counts_may_start_with_any_digit() {
+    let mut app = App::new_with_mock_system_executor(
+        Some("tests/testdata"),
+        Some("/dev/null"),
+        false,
+        MockSystemCommandExecutor::new(),
+    );
+    app.load_epub("tests/testdata/digital_frontier.epub", false)
+        .unwrap();
+    app.navigation_panel
+        .switch_to_toc_mode(create_test_book_info_with_toc());
+    app.focused_panel = FocusedPanel::Main(MainPanel::NavigationList);
+    let start = app.navigation_panel.table_of_contents.selected_index;
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char('1')
+        .press_char('2')
+        .press_char('j')
+        .quit()
+        .build();
+    let _ = run_app_with_event_source(&mut terminal, &mut app, &mut event_source);
+    assert_eq!(
+        app.navigation_panel.table_of_contents.selected_index,
+        start + 12
+    );
+    assert_eq!(
+        app.navigation_panel.table_of_contents.get_total_items(),
+        1 + 25
+    );
+
+    // Depth folding moved behind `z`
+    let mut book_info = create_test_book_info_with_toc();
+    book_info.toc_items = vec![TocItem::Section {
+        title: "Part 1".to_string(),
+        href: None,
+        anchor: None,
+        children: book_info.toc_items,
+        is_expanded: true,
+    }];
+    app.navigation_panel.switch_to_toc_mode(book_info);
+    let mut event_source = TestScenarioBuilder::new()
+        .press_char('z')
+        .press_char('1')
+        .quit()
+        .build();
+    let _ = run_app_with_event_source(&mut terminal, &mut app, &mut event_source);
+    assert_eq!(
+        app.navigation_panel.table_of_contents.get_total_items(),
+        1 + 1
+    );
+}
+
+#[test]
+fn test_navigation_panel_shows_toc_down_to_a_depth() {
+    let book_manager = create_test_book_manager();
+    let mut nav_panel = NavigationPanel::new(&book_manager);
+
+    // Two parts, each with a chapter that has two subsections
+    let section = |title: &str, children: Vec<TocItem>| TocItem::Section {
+        title: title.to_string(),
+        href: None,
+        anchor: None,
+        children,
+        is_expanded: false,
+    };
+    let chapter = |title: &str| TocItem::Chapter {
+        title: title.to_string(),
+        href: format!("{title}.xhtml"),
+        anchor: None,
+    };
+    let part = |n: usize| {
+        section(
+            &format!("Part {n}"),
+            vec![section(
+                &format!("Chapter {n}"),
+                vec![chapter(&format!("{n}.1")), chapter(&format!("{n}.2"))],
+            )],
+        )
+    };
+    let mut book_info = create_test_book_info_with_toc();
+    book_info.toc_items = vec![part(1), part(2)];
+    nav_panel.switch_to_toc_mode(book_info);
+
+    // The back button plus the visible headings
+    nav_panel.show_toc_depth(3);
+    assert_eq!(nav_panel.table_of_contents.get_total_items(), 1 + 8);
+    nav_panel.show_toc_depth(2);
+    assert_eq!(nav_panel.table_of_contents.get_total_items(), 1 + 4);
+    nav_panel.handle_upper_g();
+    nav_panel.show_toc_depth(1);
+    assert_eq!(nav_panel.table_of_contents.get_total_items(), 1 + 2);
+    assert_eq!(nav_panel.table_of_contents.selected_index, 2);
+}
+
+#[test]
+fn test_navigation_panel_follow_lock_keeps_the_toc_still() {
+    let book_manager = create_test_book_manager();
+    let mut nav_panel = NavigationPanel::new(&book_manager);
+    nav_panel.switch_to_toc_mode(create_test_book_info_with_toc());
+    let chapter_20 = ActiveSection::new(19, "chapter20.xhtml".to_string(), None);
+
+    nav_panel.table_of_contents.set_follow_locked(true);
+    nav_panel
+        .table_of_contents
+        .update_active_section(&chapter_20, 10);
+    assert_eq!(nav_panel.table_of_contents.list_state.offset(), 0);
+
+    nav_panel.table_of_contents.set_follow_locked(false);
+    nav_panel
+        .table_of_contents
+        .update_active_section(&chapter_20, 10);
+    assert!(nav_panel.table_of_contents.list_state.offset() > 0);
+}
+
+#[test]
+fn test_archived_books_leave_the_library_list() {
+    let mut book_manager = BookManager::new();
+    book_manager.books = (1..=4)
+        .map(|i| BookInfo {
+            display_name: format!("Book {i}"),
+            path: format!("book{i}.epub"),
+        })
+        .collect();
+    let mut nav_panel = NavigationPanel::new(&book_manager);
+
+    nav_panel
+        .book_list
+        .set_archived(["book2.epub".to_string()].into_iter().collect());
+    assert_eq!(nav_panel.book_list.book_count(), 3);
+    nav_panel.move_selection_down();
+    assert_eq!(nav_panel.get_selected_book_index(), 2);
+
+    // The archived view lists only archived books, under their library index
+    nav_panel.book_list.toggle_archived_view();
+    assert_eq!(nav_panel.book_list.book_count(), 1);
+    assert_eq!(nav_panel.get_selected_book_index(), 1);
+}